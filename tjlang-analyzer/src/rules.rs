@@ -8,7 +8,7 @@ use std::time::Instant;
 use tjlang_ast::*;
 use tjlang_diagnostics::{debug_println, DiagnosticCollection, TJLangDiagnostic, ErrorCode, SourceSpan as DiagnosticSourceSpan};
 use tjlang_lexer::Token;
-use tjlang_stdlib::{get_stdlib_function_names, get_stdlib_module_names, is_primitive_method};
+use tjlang_stdlib::{get_stdlib_function_names, get_stdlib_module_names, get_stdlib_signature, is_primitive_method};
 
 /// Base trait for all analysis rules
 pub trait AnalysisRule {
@@ -839,6 +839,12 @@ impl DeadCodeRule {
                     self.analyze_block(&arm.body, diagnostics, file_id, is_unreachable);
                 }
             }
+            Statement::Try(try_stmt) => {
+                self.analyze_block(&try_stmt.body, diagnostics, file_id, is_unreachable);
+                for handler in &try_stmt.handlers {
+                    self.analyze_block(&handler.body, diagnostics, file_id, is_unreachable);
+                }
+            }
             _ => {
                 // For other statements, check if they're unreachable
                 if is_unreachable {
@@ -1968,6 +1974,7 @@ impl CyclomaticComplexityRule {
             Statement::While(_) => 1,
             Statement::For(_) => 1,
             Statement::Match(_) => 1,
+            Statement::Try(try_stmt) => try_stmt.handlers.len() as u32,
             Statement::Block(block) => {
                 let mut complexity = 0;
                 for inner_stmt in &block.statements {
@@ -2067,7 +2074,7 @@ impl FunctionNestingDepthRule {
 
     fn statement_nesting_depth(&self, stmt: &Statement, current_depth: u32) -> u32 {
         match stmt {
-            Statement::If(_) | Statement::While(_) | Statement::For(_) | Statement::Match(_) => {
+            Statement::If(_) | Statement::While(_) | Statement::For(_) | Statement::Match(_) | Statement::Try(_) => {
                 current_depth + 1
             }
             Statement::Block(block) => {
@@ -3679,7 +3686,7 @@ fn check_expr_for_index_bounds(expr: &Expression, diagnostics: &mut DiagnosticCo
 
     match expr {
         // Check for method calls like [1,2,3].at(5) or [1,2,3].get(5)
-        Expression::Call { callee, args, span } => {
+        Expression::Call { callee, args, span, .. } => {
             debug_println!("[DEBUG] [BOUNDS] Checking Call expression");
             if let Expression::Member { target, member, .. } = callee.as_ref() {
                 debug_println!(
@@ -4014,6 +4021,9 @@ fn is_literal_zero(expr: &Expression) -> bool {
     }
 }
 
+/// Variants of the built-in `Option` and `Result` enums, which take one value
+const BUILTIN_VARIANTS: [(&str, &str); 3] = [("Option", "Some"), ("Result", "Ok"), ("Result", "Err")];
+
 // ============================================================================
 // UNDEFINED VARIABLE RULE (A2803)
 // ============================================================================
@@ -4049,6 +4059,30 @@ impl ASTRule for UndefinedVariableRule {
                 global_scope.insert(module);
             }
             
+            // The built-in Option and Result enums and their variants
+            for (enum_name, variant) in BUILTIN_VARIANTS {
+                global_scope.insert(enum_name.to_string());
+                global_scope.insert(variant.to_string());
+            }
+            
+            // Struct and type alias names refer to their types, as in `JSON.decode(text, Point)`
+            for unit in &ast.units {
+                match unit {
+                    ProgramUnit::Declaration(Declaration::Struct(struct_decl)) => {
+                        global_scope.insert(struct_decl.name.clone());
+                    }
+                    ProgramUnit::Declaration(Declaration::Type(type_decl)) => {
+                        global_scope.insert(type_decl.name.clone());
+                    }
+                    // Enum names qualify their variants (`Shape.Empty`), and variants are usable bare
+                    ProgramUnit::Declaration(Declaration::Enum(enum_decl)) => {
+                        global_scope.insert(enum_decl.name.clone());
+                        global_scope.extend(enum_decl.variants.iter().map(|variant| variant.name.clone()));
+                    }
+                    _ => {}
+                }
+            }
+            
             scope_stack.push(global_scope);
             
             check_units_for_undefined_vars(&ast.units, &mut scope_stack, &mut diagnostics, context.file_id);
@@ -4226,7 +4260,7 @@ fn check_block_for_undefined_vars(
 /// Check an expression for undefined variables
 fn check_expr_for_undefined_vars(
     expr: &Expression,
-    scope_stack: &Vec<std::collections::HashSet<String>>,
+    scope_stack: &mut Vec<std::collections::HashSet<String>>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
 ) {
@@ -4293,6 +4327,12 @@ fn check_expr_for_undefined_vars(
             check_expr_for_undefined_vars(then_expr, scope_stack, diagnostics, file_id);
             check_expr_for_undefined_vars(else_expr, scope_stack, diagnostics, file_id);
         }
+        Expression::Lambda { params, body, .. } => {
+            // The body sees its parameters and the variables of the enclosing scopes it captures
+            scope_stack.push(params.iter().map(|param| param.name.clone()).collect());
+            check_expr_for_undefined_vars(body, scope_stack, diagnostics, file_id);
+            scope_stack.pop();
+        }
         // Literals don't reference variables
         Expression::Literal(_) => {}
         _ => {}
@@ -4321,8 +4361,8 @@ impl ASTRule for ParameterTypeValidationRule {
         if let Some(ast) = &context.ast {
             debug_println!("[DEBUG] [PARAM_TYPE] AST is present with {} units", ast.units.len());
             
-            // Track user-defined functions and their parameter types
-            let mut user_functions: std::collections::HashMap<String, Vec<tjlang_ast::Type>> = std::collections::HashMap::new();
+            // Track user-defined functions and their parameters
+            let mut user_functions: std::collections::HashMap<String, Vec<tjlang_ast::Parameter>> = std::collections::HashMap::new();
             
             // First pass: collect all user-defined functions with their parameter types
             collect_user_function_types(&ast.units, &mut user_functions);
@@ -4338,21 +4378,17 @@ impl ASTRule for ParameterTypeValidationRule {
     }
 }
 
-/// Collect all user-defined functions and their parameter types
+/// Collect all user-defined functions and their parameters
 fn collect_user_function_types(
     units: &[ProgramUnit],
-    user_functions: &mut std::collections::HashMap<String, Vec<tjlang_ast::Type>>,
+    user_functions: &mut std::collections::HashMap<String, Vec<tjlang_ast::Parameter>>,
 ) {
     for unit in units {
         match unit {
             ProgramUnit::Declaration(Declaration::Function(func)) => {
-                let param_types: Vec<tjlang_ast::Type> = func.params.iter()
-                    .map(|p| p.param_type.clone())
-                    .collect();
-                let param_count = param_types.len();
-                user_functions.insert(func.name.clone(), param_types);
+                user_functions.insert(func.name.clone(), func.params.clone());
                 debug_println!("[DEBUG] [PARAM_TYPE] Collected function: {} with {} parameters", 
-                    func.name, param_count);
+                    func.name, func.params.len());
             }
             _ => {}
         }
@@ -4362,13 +4398,14 @@ fn collect_user_function_types(
 /// Check function calls for parameter type mismatches
 fn check_function_call_types(
     units: &[ProgramUnit],
-    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Type>>,
+    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Parameter>>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
 ) {
     for unit in units {
         match unit {
             ProgramUnit::Declaration(Declaration::Function(func)) => {
+                check_default_value_types(func, diagnostics, file_id);
                 check_function_body_types(&func.body, user_functions, diagnostics, file_id);
             }
             _ => {}
@@ -4376,10 +4413,38 @@ fn check_function_call_types(
     }
 }
 
+/// Check that parameter default values match the declared parameter types
+fn check_default_value_types(
+    func: &tjlang_ast::FunctionDecl,
+    diagnostics: &mut DiagnosticCollection,
+    file_id: codespan::FileId,
+) {
+    for param in &func.params {
+        if let Some(default) = &param.default_value {
+            let default_type = infer_expression_type(default);
+            if !is_type_compatible(&default_type, &param.param_type) {
+                let message = format!(
+                    "Default value of parameter '{}' in function '{}' expects type {:?}, got {:?}",
+                    param.name,
+                    func.name,
+                    format_type(&param.param_type),
+                    format_type(&default_type)
+                );
+                diagnostics.add(tjlang_diagnostics::TJLangDiagnostic::new(
+                    ErrorCode::AnalyzerWrongArgumentType,
+                    codespan_reporting::diagnostic::Severity::Error,
+                    message,
+                    tjlang_diagnostics::SourceSpan::new(file_id, param.span.span),
+                ));
+            }
+        }
+    }
+}
+
 /// Check function body for parameter type mismatches
 fn check_function_body_types(
     body: &tjlang_ast::Block,
-    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Type>>,
+    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Parameter>>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
 ) {
@@ -4391,7 +4456,7 @@ fn check_function_body_types(
 /// Check statement for parameter type mismatches
 fn check_statement_types(
     statement: &tjlang_ast::Statement,
-    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Type>>,
+    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Parameter>>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
 ) {
@@ -4399,6 +4464,9 @@ fn check_statement_types(
         tjlang_ast::Statement::Expression(expr) => {
             check_expression_types(expr, user_functions, diagnostics, file_id);
         }
+        tjlang_ast::Statement::Variable(var_decl) => {
+            check_expression_types(&var_decl.value, user_functions, diagnostics, file_id);
+        }
         tjlang_ast::Statement::Return(return_stmt) => {
             if let Some(expr) = &return_stmt.value {
                 check_expression_types(expr, user_functions, diagnostics, file_id);
@@ -4439,7 +4507,7 @@ fn check_statement_types(
 /// Check block for parameter type mismatches
 fn check_block_types(
     block: &tjlang_ast::Block,
-    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Type>>,
+    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Parameter>>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
 ) {
@@ -4451,30 +4519,76 @@ fn check_block_types(
 /// Check expression for parameter type mismatches
 fn check_expression_types(
     expr: &tjlang_ast::Expression,
-    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Type>>,
+    user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Parameter>>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
 ) {
     match expr {
-        tjlang_ast::Expression::Call { callee, args, span } => {
+        tjlang_ast::Expression::Call { callee, args, named_args, span } => {
             // Check if this is a direct function call (Variable)
             match callee.as_ref() {
                 tjlang_ast::Expression::Variable { name: func_name, .. } => {
                     debug_println!("[DEBUG] [PARAM_TYPE] Checking function call: {}", func_name);
                     
                     // Check if it's a user-defined function
-                    if let Some(expected_param_types) = user_functions.get(func_name) {
+                    if let Some(params) = user_functions.get(func_name) {
                         debug_println!("[DEBUG] [PARAM_TYPE] Found user function: {} (expected {} params, got {})", 
-                            func_name, expected_param_types.len(), args.len());
+                            func_name, params.len(), args.len() + named_args.len());
                         
                         // Validate argument count first
-                        if args.len() != expected_param_types.len() {
+                        if args.len() > params.len() {
                             debug_println!("[DEBUG] [PARAM_TYPE] Parameter count mismatch for function: {}", func_name);
                             return; // Let ParameterCountRule handle this
                         }
                         
-                        // Check each argument type
-                        for (i, (arg, expected_type)) in args.iter().zip(expected_param_types.iter()).enumerate() {
+                        // Match positional arguments, then keyword arguments, to parameters
+                        let mut bound: Vec<Option<&tjlang_ast::Expression>> = args.iter().map(Some).collect();
+                        bound.resize(params.len(), None);
+                        for named_arg in named_args {
+                            let message = match params.iter().position(|p| p.name == named_arg.name) {
+                                Some(i) if bound[i].is_none() => {
+                                    bound[i] = Some(&named_arg.value);
+                                    continue;
+                                }
+                                Some(_) => format!(
+                                    "Function '{}' got multiple values for parameter '{}'",
+                                    func_name, named_arg.name
+                                ),
+                                None => format!(
+                                    "Function '{}' has no parameter named '{}'",
+                                    func_name, named_arg.name
+                                ),
+                            };
+                            diagnostics.add(tjlang_diagnostics::TJLangDiagnostic::new(
+                                ErrorCode::AnalyzerWrongArgumentCount,
+                                codespan_reporting::diagnostic::Severity::Error,
+                                message,
+                                tjlang_diagnostics::SourceSpan::new(file_id, named_arg.span.span),
+                            ));
+                        }
+                        
+                        // Without keywords, a missing argument is a count mismatch
+                        if !named_args.is_empty() {
+                            for (param, arg) in params.iter().zip(bound.iter()) {
+                                if arg.is_none() && param.default_value.is_none() {
+                                    diagnostics.add(tjlang_diagnostics::TJLangDiagnostic::new(
+                                        ErrorCode::AnalyzerWrongArgumentCount,
+                                        codespan_reporting::diagnostic::Severity::Error,
+                                        format!(
+                                            "Function '{}' is missing an argument for parameter '{}'",
+                                            func_name, param.name
+                                        ),
+                                        tjlang_diagnostics::SourceSpan::new(file_id, span.span),
+                                    ));
+                                }
+                            }
+                        }
+                        
+                        // Check the type of each argument that was passed
+                        let supplied = params.iter().zip(bound.iter()).enumerate()
+                            .filter_map(|(i, (param, arg))| arg.map(|arg| (i, param, arg)));
+                        for (i, param, arg) in supplied {
+                            let expected_type = &param.param_type;
                             let arg_type = infer_expression_type(arg);
                             if !is_type_compatible(&arg_type, expected_type) {
                                 debug_println!("[DEBUG] [PARAM_TYPE] Type mismatch for function '{}' parameter {}: expected {:?}, got {:?}", 
@@ -4483,7 +4597,7 @@ fn check_expression_types(
                                 let message = format!(
                                     "Function '{}' parameter '{}' (position {}) expects type {:?}, got {:?}",
                                     func_name, 
-                                    get_parameter_name(func_name, i, user_functions),
+                                    param.name,
                                     i + 1,
                                     format_type(expected_type),
                                     format_type(&arg_type)
@@ -4508,7 +4622,7 @@ fn check_expression_types(
                 }
                 _ => {
                     // For method calls, check the arguments but don't validate the method itself
-                    for arg in args {
+                    for arg in args.iter().chain(named_args.iter().map(|named| &named.value)) {
                         check_expression_types(arg, user_functions, diagnostics, file_id);
                     }
                 }
@@ -4568,19 +4682,21 @@ fn is_type_compatible(arg_type: &tjlang_ast::Type, expected_type: &tjlang_ast::T
     if matches!(arg_type, tjlang_ast::Type::Primitive(tjlang_ast::PrimitiveType::Any)) {
         return true;
     }
+
+    // An `any` parameter accepts every value, and an int widens to a float
+    match (arg_type, expected_type) {
+        (_, tjlang_ast::Type::Primitive(tjlang_ast::PrimitiveType::Any))
+        | (tjlang_ast::Type::Primitive(tjlang_ast::PrimitiveType::Int), tjlang_ast::Type::Primitive(tjlang_ast::PrimitiveType::Float)) => {
+            return true;
+        }
+        _ => {}
+    }
     
     // For now, only check exact matches
     // TODO: Add more sophisticated type compatibility rules
     arg_type == expected_type
 }
 
-/// Get parameter name for error messages
-fn get_parameter_name(func_name: &str, param_index: usize, user_functions: &std::collections::HashMap<String, Vec<tjlang_ast::Type>>) -> String {
-    // For now, just return a generic parameter name
-    // TODO: Store parameter names in the function signature
-    format!("param_{}", param_index + 1)
-}
-
 /// Format type for error messages
 fn format_type(ty: &tjlang_ast::Type) -> String {
     match ty {
@@ -4619,14 +4735,19 @@ impl ASTRule for UndefinedFunctionRule {
         if let Some(ast) = &context.ast {
             debug_println!("[DEBUG] [UNDEF_FUNC] AST is present with {} units", ast.units.len());
             
-            // Track user-defined functions and their parameter counts
-            let mut user_functions: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+            // Track user-defined functions and how many arguments they accept
+            let mut user_functions: std::collections::HashMap<String, std::ops::RangeInclusive<usize>> = std::collections::HashMap::new();
             
             // Get stdlib function names (module::function format)
-            let stdlib_functions = get_stdlib_function_names();
+            let mut stdlib_functions = get_stdlib_function_names();
             
             // First pass: collect all user-defined functions
             collect_user_functions(&ast.units, &mut user_functions);
+            collect_variant_constructors(&ast.units, &mut user_functions, &mut stdlib_functions);
+            collect_impl_method_names(&ast.units, &mut stdlib_functions);
+            
+            // Names brought in by imports are resolved by the module loader at runtime
+            collect_imported_names(&ast.units, &mut stdlib_functions);
             
             // Second pass: check all function calls
             check_function_calls(&ast.units, &user_functions, &stdlib_functions, &mut diagnostics, context.file_id);
@@ -4640,16 +4761,78 @@ impl ASTRule for UndefinedFunctionRule {
 }
 
 
-/// Collect all user-defined functions and their parameter counts
+/// Collect all user-defined functions and how many arguments they accept.
+/// Parameters with default values may be left out.
 fn collect_user_functions(
     units: &[ProgramUnit],
-    user_functions: &mut std::collections::HashMap<String, usize>,
+    user_functions: &mut std::collections::HashMap<String, std::ops::RangeInclusive<usize>>,
 ) {
     for unit in units {
         if let ProgramUnit::Declaration(Declaration::Function(func_decl)) = unit {
             debug_println!("[DEBUG] [UNDEF_FUNC] Found user function: {} with {} params", 
                 func_decl.name, func_decl.params.len());
-            user_functions.insert(func_decl.name.clone(), func_decl.params.len());
+            let required = func_decl.params.iter().filter(|p| p.default_value.is_none()).count();
+            user_functions.insert(func_decl.name.clone(), required..=func_decl.params.len());
+        }
+    }
+}
+
+/// Collect enum variant constructors, which are called like functions with one
+/// argument per field. Bare names (`Circle(2.0)`) are checked like user functions
+/// and qualified names (`Shape.Circle(2.0)`) are recorded as `Shape::Circle`.
+/// The built-in `Some`, `Ok` and `Err` are always available.
+fn collect_variant_constructors(
+    units: &[ProgramUnit],
+    user_functions: &mut std::collections::HashMap<String, std::ops::RangeInclusive<usize>>,
+    qualified_names: &mut std::collections::HashSet<String>,
+) {
+    for (enum_name, variant) in BUILTIN_VARIANTS {
+        user_functions.insert(variant.to_string(), 1..=1);
+        qualified_names.insert(format!("{}::{}", enum_name, variant));
+    }
+    for unit in units {
+        if let ProgramUnit::Declaration(Declaration::Enum(enum_decl)) = unit {
+            for variant in enum_decl.variants.iter().filter(|variant| !variant.fields.is_empty()) {
+                let arity = variant.fields.len();
+                user_functions.insert(variant.name.clone(), arity..=arity);
+                qualified_names.insert(format!("{}::{}", enum_decl.name, variant.name));
+            }
+        }
+    }
+}
+
+/// Collect the names of methods declared in impl blocks as `*::method`. The
+/// receiver's type is only known at runtime, so a method call on a value is
+/// accepted when any impl block declares a method of that name.
+fn collect_impl_method_names(
+    units: &[ProgramUnit],
+    names: &mut std::collections::HashSet<String>,
+) {
+    for unit in units {
+        if let ProgramUnit::Declaration(Declaration::Implementation(impl_block)) = unit {
+            names.extend(impl_block.methods.iter().map(|method| format!("*::{}", method.name)));
+        }
+    }
+}
+
+/// Collect names bound by imports. Selective imports are recorded by name and
+/// module aliases as `alias::*`, since their exports are only known at runtime.
+fn collect_imported_names(
+    units: &[ProgramUnit],
+    imported: &mut std::collections::HashSet<String>,
+) {
+    for unit in units {
+        if let ProgramUnit::Import(import) = unit {
+            match import {
+                ImportDecl::Simple { module, alias, .. } => {
+                    let name = alias.clone()
+                        .unwrap_or_else(|| module.parts.last().cloned().unwrap_or_default());
+                    imported.insert(format!("{}::*", name));
+                }
+                ImportDecl::Selective { items, .. } => {
+                    imported.extend(items.iter().cloned());
+                }
+            }
         }
     }
 }
@@ -4657,15 +4840,23 @@ fn collect_user_functions(
 /// Check all function calls in the program
 fn check_function_calls(
     units: &[ProgramUnit],
-    user_functions: &std::collections::HashMap<String, usize>,
+    user_functions: &std::collections::HashMap<String, std::ops::RangeInclusive<usize>>,
     stdlib_functions: &std::collections::HashSet<String>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
 ) {
+    // Calling a variable, such as a parameter holding a closure, is resolved at runtime
+    let mut callable_names = stdlib_functions.clone();
+    collect_global_variable_names(units, &mut callable_names);
+    let stdlib_functions = &callable_names;
+
     for unit in units {
         match unit {
             ProgramUnit::Declaration(Declaration::Function(func_decl)) => {
-                check_block_for_function_calls(&func_decl.body, user_functions, stdlib_functions, diagnostics, file_id);
+                let mut local_names = stdlib_functions.clone();
+                local_names.extend(func_decl.params.iter().map(|param| param.name.clone()));
+                collect_block_variable_names(&func_decl.body, &mut local_names);
+                check_block_for_function_calls(&func_decl.body, user_functions, &local_names, diagnostics, file_id);
             }
             ProgramUnit::Declaration(Declaration::Variable(var_decl)) => {
                 // Check variable initializer for function calls
@@ -4683,10 +4874,67 @@ fn check_function_calls(
     }
 }
 
+/// Collect the names of variables declared at the top level of a program
+fn collect_global_variable_names(
+    units: &[ProgramUnit],
+    names: &mut std::collections::HashSet<String>,
+) {
+    for unit in units {
+        match unit {
+            ProgramUnit::Declaration(Declaration::Variable(var_decl)) => {
+                names.insert(var_decl.name.clone());
+            }
+            ProgramUnit::Statement(stmt) => collect_statement_variable_names(stmt, names),
+            _ => {}
+        }
+    }
+}
+
+/// Collect the names of variables declared anywhere in a block, including loop variables
+fn collect_block_variable_names(
+    block: &Block,
+    names: &mut std::collections::HashSet<String>,
+) {
+    for stmt in &block.statements {
+        collect_statement_variable_names(stmt, names);
+    }
+}
+
+fn collect_statement_variable_names(
+    stmt: &Statement,
+    names: &mut std::collections::HashSet<String>,
+) {
+    match stmt {
+        Statement::Variable(var_decl) => {
+            names.insert(var_decl.name.clone());
+        }
+        Statement::If(if_stmt) => {
+            collect_block_variable_names(&if_stmt.then_block, names);
+            if let Some(else_block) = &if_stmt.else_block {
+                collect_block_variable_names(else_block, names);
+            }
+        }
+        Statement::While(while_stmt) => collect_block_variable_names(&while_stmt.body, names),
+        Statement::DoWhile(do_while) => collect_block_variable_names(&do_while.body, names),
+        Statement::For(ForStatement::ForEach { var_name, body, .. }) => {
+            names.insert(var_name.clone());
+            collect_block_variable_names(body, names);
+        }
+        Statement::For(ForStatement::CStyle { initializer, body, .. }) => {
+            if let Some(init) = initializer {
+                collect_statement_variable_names(init, names);
+            }
+            collect_block_variable_names(body, names);
+        }
+        Statement::Block(block) => collect_block_variable_names(block, names),
+        _ => {}
+    }
+}
+
 /// Check a statement for function calls
 fn check_statement_for_function_calls(
     stmt: &Statement,
-    user_functions: &std::collections::HashMap<String, usize>,
+    user_functions: &std::collections::HashMap<String, std::ops::RangeInclusive<usize>>,
     stdlib_functions: &std::collections::HashSet<String>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
@@ -4748,7 +4996,7 @@ fn check_statement_for_function_calls(
 /// Check a block for function calls
 fn check_block_for_function_calls(
     block: &Block,
-    user_functions: &std::collections::HashMap<String, usize>,
+    user_functions: &std::collections::HashMap<String, std::ops::RangeInclusive<usize>>,
     stdlib_functions: &std::collections::HashSet<String>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
@@ -4761,7 +5009,7 @@ fn check_block_for_function_calls(
 /// Check an expression for function calls
 fn check_expr_for_function_calls(
     expr: &Expression,
-    user_functions: &std::collections::HashMap<String, usize>,
+    user_functions: &std::collections::HashMap<String, std::ops::RangeInclusive<usize>>,
     stdlib_functions: &std::collections::HashSet<String>,
     diagnostics: &mut DiagnosticCollection,
     file_id: codespan::FileId,
@@ -4769,26 +5017,33 @@ fn check_expr_for_function_calls(
     use tjlang_diagnostics::ErrorCode;
     
     match expr {
-        Expression::Call { callee, args, span } => {
+        Expression::Call { callee, args, named_args, span } => {
             // Check if this is a direct function call (Variable) or a method call (Member)
             match callee.as_ref() {
                 Expression::Variable { name: func_name, .. } => {
                     debug_println!("[DEBUG] [UNDEF_FUNC] Checking function call: {}", func_name);
                     
                     // Check if it's a user-defined function
-                    if let Some(&expected_param_count) = user_functions.get(func_name) {
-                        debug_println!("[DEBUG] [UNDEF_FUNC] Found user function: {} (expected {} params, got {})", 
-                            func_name, expected_param_count, args.len());
+                    if let Some(accepted) = user_functions.get(func_name) {
+                        // Keyword arguments count towards the total
+                        let arg_count = args.len() + named_args.len();
+                        debug_println!("[DEBUG] [UNDEF_FUNC] Found user function: {} (expected {:?} params, got {})", 
+                            func_name, accepted, arg_count);
                         
                         // Validate argument count for user-defined functions
-                        if args.len() != expected_param_count {
-                            debug_println!("[DEBUG] [UNDEF_FUNC] Wrong argument count detected for '{}': expected {}, got {}", 
-                                func_name, expected_param_count, args.len());
+                        if !accepted.contains(&arg_count) {
+                            debug_println!("[DEBUG] [UNDEF_FUNC] Wrong argument count detected for '{}': expected {:?}, got {}", 
+                                func_name, accepted, arg_count);
                             debug_println!("[DEBUG] [UNDEF_FUNC] Call span: {:?}", span.span);
                             
+                            let expected = if accepted.start() == accepted.end() {
+                                accepted.end().to_string()
+                            } else {
+                                format!("{} to {}", accepted.start(), accepted.end())
+                            };
                             let message = format!(
                                 "Function '{}' expects {} argument(s), but {} were provided",
-                                func_name, expected_param_count, args.len()
+                                func_name, expected, arg_count
                             );
                             let diag_span = tjlang_diagnostics::SourceSpan::new(file_id, span.span);
                             
@@ -4802,7 +5057,7 @@ fn check_expr_for_function_calls(
                             diagnostics.add(diagnostic);
                             debug_println!("[DEBUG] [UNDEF_FUNC] Diagnostic added");
                         }
-                    } else {
+                    } else if !stdlib_functions.contains(func_name) {
                         // Not a user function - this would be an undefined function
                         debug_println!("[DEBUG] [UNDEF_FUNC] Function '{}' not found in user functions", func_name);
                         
@@ -4827,13 +5082,16 @@ fn check_expr_for_function_calls(
                         debug_println!("[DEBUG] [UNDEF_FUNC] Checking method call: {}", full_name);
                         
                         // Check if it's a stdlib function - stdlib functions handle their own argument validation
-                        if !stdlib_functions.contains(&full_name) {
+                        if !stdlib_functions.contains(&full_name)
+                            && !stdlib_functions.contains(&format!("{}::*", module_name)) {
                             debug_println!("[DEBUG] [UNDEF_FUNC] Method '{}' not found in stdlib", full_name);
                             
                             // Check if it's a primitive method (e.g., vec.at(), str.to_string())
-                            // These are dynamically checked at runtime, so we skip them
+                            // or a method from an impl block. These are dynamically checked at
+                            // runtime, so we skip them
                             // We only report truly undefined module functions
-                            if !is_primitive_method(member) {
+                            if !is_primitive_method(member)
+                                && !stdlib_functions.contains(&format!("*::{}", member)) {
                                 let message = format!("Method '{}' does not exist on '{}'", member, module_name);
                                 let diag_span = tjlang_diagnostics::SourceSpan::new(file_id, span.span);
                                 
@@ -4844,6 +5102,31 @@ fn check_expr_for_function_calls(
                                     diag_span,
                                 );
                                 
+                                diagnostics.add(diagnostic);
+                            }
+                        } else if let Some(signature) = get_stdlib_signature(module_name, member) {
+                            // Stdlib functions declare how many arguments they accept
+                            let arg_count = args.len() + named_args.len();
+                            let too_many = signature.max_args().is_some_and(|max| arg_count > max);
+                            if arg_count < signature.min_args() || too_many {
+                                let expected = match signature.max_args() {
+                                    None => format!("at least {}", signature.min_args()),
+                                    Some(max) if max == signature.min_args() => max.to_string(),
+                                    Some(max) => format!("{} to {}", signature.min_args(), max),
+                                };
+                                let message = format!(
+                                    "Function '{}.{}' expects {} argument(s), but {} were provided",
+                                    module_name, member, expected, arg_count
+                                );
+                                let diag_span = tjlang_diagnostics::SourceSpan::new(file_id, span.span);
+                                
+                                let diagnostic = tjlang_diagnostics::TJLangDiagnostic::new(
+                                    ErrorCode::AnalyzerWrongArgumentCount,
+                                    codespan_reporting::diagnostic::Severity::Error,
+                                    message,
+                                    diag_span,
+                                ).with_note(signature.to_string());
+                                
                                 diagnostics.add(diagnostic);
                             }
                         }
@@ -4859,7 +5142,7 @@ fn check_expr_for_function_calls(
             }
             
             // Check all arguments
-            for arg in args {
+            for arg in args.iter().chain(named_args.iter().map(|named| &named.value)) {
                 check_expr_for_function_calls(arg, user_functions, stdlib_functions, diagnostics, file_id);
             }
         }
@@ -329,6 +329,24 @@ y: int = x + 5
         assert!(!has_error_code(&result, ErrorCode::AnalyzerWrongArgumentCount));
     }
 
+    #[test]
+    fn test_undefined_variable_rule_type_names() {
+        let source = r#"
+# Test: Struct and type alias names refer to their types
+type Point { x: int, y: int }
+type Points = [Point]
+def load(text: str) -> any {
+    return [JSON.decode(text, Point), JSON.decode(text, Points), JSON.decode(text, Missing)]
+}
+"#;
+        let result = analyze_source(source);
+
+        // Only the undeclared name should be reported
+        let undefined = get_diagnostics_by_code(&result, ErrorCode::AnalyzerUndefinedVariable);
+        assert_eq!(undefined.len(), 1);
+        assert!(undefined[0].message.contains("'Missing'"));
+    }
+
     #[test]
     fn test_undefined_variable_rule_function_parameter() {
         let source = r#"
@@ -343,6 +361,26 @@ def add(a: int, b: int) -> int {
         assert!(!has_error_code(&result, ErrorCode::AnalyzerWrongArgumentCount));
     }
 
+    #[test]
+    fn test_undefined_variable_rule_lambda_parameters_and_captures() {
+        let source = r#"
+# Test: Lambda bodies see their parameters and the variables they capture
+def make_adder(n: int) -> any {
+    return (x: int) -> x + n
+}
+def make_scaler(n: int) -> any {
+    factor: int = n * 2
+    return (x: int) -> x * factor + missing
+}
+"#;
+        let result = analyze_source(source);
+
+        // Only the variable declared nowhere should be reported
+        let undefined = get_diagnostics_by_code(&result, ErrorCode::AnalyzerUndefinedVariable);
+        assert!(!undefined.is_empty());
+        assert!(undefined.iter().all(|d| d.message.contains("'missing'")), "unexpected: {:?}", undefined);
+    }
+
     #[test]
     fn test_undefined_variable_rule_stdlib_modules() {
         let source = r#"
@@ -383,6 +421,108 @@ def main() -> int {
         assert!(diagnostic.message.contains("Function 'nonexistent_function' is called but never declared"));
     }
 
+    #[test]
+    fn test_undefined_function_rule_call_through_variable() {
+        let source = r#"
+# Test: Calling a parameter or local that holds a closure
+def apply(f: fn(int) -> int, v: int) -> int {
+    return f(v)
+}
+def main() -> int {
+    add5: any = (x: int) -> x + 5
+    return apply(add5, add5(1))
+}
+"#;
+        let result = analyze_source(source);
+
+        // Should NOT detect undefined functions
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerUndefinedFunction));
+    }
+
+    #[test]
+    fn test_undefined_function_rule_impl_methods_and_variants() {
+        let source = r#"
+# Test: Impl methods, enum names and variant constructors
+type Point { x: int, y: int }
+interface Printable { show() -> str }
+impl Printable: Point {
+    show() -> str { return "point" }
+}
+enum Shape { Circle(float), Empty }
+def main() -> int {
+    p: Point = Point { x: 1, y: 2 }
+    IO.println(p.show())
+    c: Shape = Circle(2.0)
+    d: Shape = Shape.Circle(1.0)
+    e: Shape = Shape.Empty
+    f: Shape = Empty
+    p.hide()
+    return Circle(1.0, 2.0)
+}
+"#;
+        let result = analyze_source(source);
+
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerUndefinedVariable));
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerUndefinedFunction));
+        // Only the method no impl block declares is reported
+        let missing = get_diagnostics_by_code(&result, ErrorCode::AnalyzerMethodNotFoundStatic);
+        assert!(!missing.is_empty());
+        assert!(missing.iter().all(|d| d.message.contains("'hide'")), "unexpected: {:?}", missing);
+        // Variant constructors take one argument per field
+        let counts = get_diagnostics_by_code(&result, ErrorCode::AnalyzerWrongArgumentCount);
+        assert!(counts.iter().any(|d| d.message.contains("'Circle'")), "unexpected: {:?}", counts);
+    }
+
+    #[test]
+    fn test_undefined_function_rule_option_and_result() {
+        let source = r#"
+# Test: Built-in Option and Result constructors and their methods
+def parse(s: str) -> Result<int, str> {
+    if s == "" {
+        return Err("empty")
+    }
+    return Ok(s.length())
+}
+def main() -> int {
+    r: Result<int, str> = parse("abc")
+    o: Option<int> = Some(r.unwrap())
+    if o.is_none() or r.is_err() {
+        return r.unwrap_or(0)
+    }
+    return Some(1, 2).unwrap()
+}
+"#;
+        let result = analyze_source(source);
+
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerUndefinedFunction));
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerMethodNotFoundStatic));
+        // Some takes exactly one value
+        let counts = get_diagnostics_by_code(&result, ErrorCode::AnalyzerWrongArgumentCount);
+        assert!(counts.iter().any(|d| d.message.contains("'Some'")), "unexpected: {:?}", counts);
+    }
+
+    #[test]
+    fn test_undefined_function_rule_task_and_channel_methods() {
+        let source = r#"
+# Test: Methods on spawned tasks and channels
+def work(n: int) -> int {
+    return n * n
+}
+def main() -> int {
+    ch: any = CHANNEL.new()
+    t: any = spawn work(7)
+    ch.send(t.join())
+    if t.is_done() {
+        IO.println(ch.try_recv())
+    }
+    return ch.recv()
+}
+"#;
+        let result = analyze_source(source);
+
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerMethodNotFoundStatic));
+    }
+
     #[test]
     fn test_undefined_function_rule_wrong_argument_count() {
         let source = r#"
@@ -458,6 +598,32 @@ result: float = MATH.sqrt(16.0)
         assert!(!has_error_code(&result, ErrorCode::AnalyzerMethodNotFoundStatic));
     }
 
+    #[test]
+    fn test_stdlib_calls_checked_against_signatures() {
+        let source = r#"
+# Test: Stdlib calls are checked against their binding signatures
+root: float = MATH.sqrt(16.0)
+padded: str = STRING.pad_left("7", 3)
+divisor: int = MATH.gcd(12)
+wrong: float = MATH.sqrt("sixteen")
+"#;
+        // Argument types come from the type-check rules, which the default config enables
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerUndefinedVariable));
+        let count_errors = get_diagnostics_by_code(&result, ErrorCode::AnalyzerWrongArgumentCount);
+        assert_eq!(count_errors.len(), 1);
+        assert!(count_errors[0]
+            .message
+            .contains("Function 'MATH.gcd' expects 2 argument(s), but 1 were provided"));
+        let type_errors = get_diagnostics_by_code(&result, ErrorCode::AnalyzerTypeMismatch);
+        assert!(!type_errors.is_empty());
+        assert!(type_errors
+            .iter()
+            .all(|d| d.message.contains("Argument 1 ('a') of MATH.sqrt: expected Float, found Str")));
+    }
+
     #[test]
     fn test_undefined_function_rule_primitive_method() {
         let source = r#"
@@ -1142,6 +1308,279 @@ unused_var: int = 42  # Warning
         assert!(summary.execution_time.as_micros() > 0);
     }
 
+    // ============================================================================
+    // OPERATOR OVERLOADING TESTS
+    // ============================================================================
+
+    #[test]
+    fn test_operator_without_impl_reported() {
+        let source = r#"
+type Vec2 { x: int, y: int }
+interface Arith {
+    +(other: Vec2) -> Vec2
+}
+impl Arith: Vec2 {
+    +(other: Vec2) -> Vec2 {
+        return Vec2 { x: self.x + other.x, y: self.y + other.y }
+    }
+}
+a: Vec2 = Vec2 { x: 1, y: 2 }
+c: Vec2 = a * a
+"#;
+        // Operator checks come from the type-check rules, which the default config enables
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        let diagnostics = get_diagnostics_by_code(&result, ErrorCode::AnalyzerTraitNotImplemented);
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics[0].message.contains("does not implement operator '*'"));
+    }
+
+    #[test]
+    fn test_operator_without_impl_inside_function_reported() {
+        let source = r#"
+type W { x: int }
+def main() -> int {
+    a: W = W { x: 1 }
+    if a.x > 0 {
+        c: W = a + a
+    }
+    a.x = 2
+    return 0
+}
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        let diagnostics = get_diagnostics_by_code(&result, ErrorCode::AnalyzerTraitNotImplemented);
+        // Only `a + a` is reported; assigning to a field is not an operator call
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics.iter().all(|d| d.message.contains("Type 'W' does not implement operator '+'")));
+    }
+
+    #[test]
+    fn test_implemented_operators_accepted() {
+        let source = r#"
+type Vec2 { x: int, y: int }
+interface Arith {
+    +(other: Vec2) -> Vec2
+}
+impl Arith: Vec2 {
+    +(other: Vec2) -> Vec2 {
+        return Vec2 { x: self.x + other.x, y: self.y + other.y }
+    }
+}
+a: Vec2 = Vec2 { x: 1, y: 2 }
+b: Vec2 = a + a
+same: bool = a == b
+n: int = b.x
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerTraitNotImplemented));
+    }
+
+    #[test]
+    fn test_right_operand_operator_only_for_commutative() {
+        let source = r#"
+type Money { cents: int }
+interface Scale {
+    *(factor: int) -> Money
+    -(amount: int) -> Money
+}
+impl Scale: Money {
+    *(factor: int) -> Money {
+        return Money { cents: self.cents * factor }
+    }
+    -(amount: int) -> Money {
+        return Money { cents: self.cents - amount }
+    }
+}
+m: Money = Money { cents: 5 }
+doubled: Money = 2 * m
+less: Money = 2 - m
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        let diagnostics = get_diagnostics_by_code(&result, ErrorCode::AnalyzerTraitNotImplemented);
+        // `2 * m` uses Money's `*`; only `2 - m` is reported
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics.iter().all(|d| d.message.contains("Operator '-' is not commutative")));
+    }
+
+    // ============================================================================
+    // GENERIC BOUND TESTS
+    // ============================================================================
+
+    #[test]
+    fn test_generic_bound_violation_reported() {
+        let source = r#"
+type P { v: int }
+def smaller<T: implements [Order]>(a: T, b: T) -> T {
+    if b < a {
+        return b
+    }
+    return a
+}
+p: P = smaller(P { v: 1 }, P { v: 2 })
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        let diagnostics = get_diagnostics_by_code(&result, ErrorCode::AnalyzerInvalidTraitBound);
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics[0].message.contains("Type 'P' does not implement 'Order'"));
+    }
+
+    #[test]
+    fn test_generic_bound_violation_inside_function_reported() {
+        let source = r#"
+type P { v: int }
+def smaller<T: implements [Order]>(a: T, b: T) -> T {
+    if b < a {
+        return b
+    }
+    return a
+}
+def main() -> int {
+    for (i: int; [1, 2]) {
+        first: P = P { v: i }
+        p: P = smaller(first, P { v: 2 })
+    }
+    return 0
+}
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        let diagnostics = get_diagnostics_by_code(&result, ErrorCode::AnalyzerInvalidTraitBound);
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics[0].message.contains("Type 'P' does not implement 'Order'"));
+    }
+
+    #[test]
+    fn test_generic_bounds_satisfied() {
+        let source = r#"
+type Q { v: int }
+impl Order: Q {
+    <(other: Q) -> bool {
+        return self.v < other.v
+    }
+}
+def smaller<T: implements [Order]>(a: T, b: T) -> T {
+    if b < a {
+        return b
+    }
+    return a
+}
+n: int = smaller(3, 7)
+q: Q = smaller(Q { v: 1 }, Q { v: 2 })
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerInvalidTraitBound));
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerTypeMismatch));
+    }
+
+    #[test]
+    fn test_generic_parameter_inferred_from_first_argument() {
+        let source = r#"
+def smaller<T: implements [Order]>(a: T, b: T) -> T {
+    return a
+}
+s: str = smaller("a", 3)
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        assert!(has_error_code(&result, ErrorCode::AnalyzerTypeMismatch));
+    }
+
+    // ============================================================================
+    // DEFAULT AND KEYWORD ARGUMENT TESTS
+    // ============================================================================
+
+    #[test]
+    fn test_default_and_keyword_arguments_accepted() {
+        let source = r#"
+def connect(host: str, port: int = 8080) -> str {
+    return host
+}
+def main() -> int {
+    a: str = connect("a")
+    b: str = connect("b", port: 1)
+    c: str = connect(port: 2, host: "c")
+    return 0
+}
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerWrongArgumentCount));
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerWrongArgumentType));
+    }
+
+    #[test]
+    fn test_keyword_argument_errors_reported() {
+        let source = r#"
+def connect(host: str, port: int = 8080) -> str {
+    return host
+}
+def main() -> int {
+    a: str = connect("a", hots: "b")
+    b: str = connect(port: 1)
+    c: str = connect()
+    return 0
+}
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        let messages: Vec<String> = get_diagnostics_by_code(&result, ErrorCode::AnalyzerWrongArgumentCount)
+            .iter()
+            .map(|d| d.message.clone())
+            .collect();
+        assert!(messages.iter().any(|m| m.contains("has no parameter named 'hots'")));
+        assert!(messages.iter().any(|m| m.contains("missing an argument for parameter 'host'")));
+        assert!(messages.iter().any(|m| m.contains("expects 1 to 2 argument(s), but 0 were provided")));
+    }
+
+    #[test]
+    fn test_keyword_and_default_types_checked() {
+        let source = r#"
+def connect(host: str, port: int = "80") -> str {
+    return host
+}
+def main() -> int {
+    a: str = connect("a", port: "x")
+    return 0
+}
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        let messages: Vec<String> = get_diagnostics_by_code(&result, ErrorCode::AnalyzerWrongArgumentType)
+            .iter()
+            .map(|d| d.message.clone())
+            .collect();
+        assert!(messages.iter().any(|m| m.contains("parameter 'port' (position 2)")));
+        assert!(messages.iter().any(|m| m.contains("Default value of parameter 'port'")));
+    }
+
+    #[test]
+    fn test_any_parameters_and_int_to_float_arguments_accepted() {
+        let source = r#"
+def half(n: float, scale: float = 1) -> float {
+    return n / 2.0
+}
+def show(x: any) -> int {
+    return 1
+}
+def main() -> int {
+    h: float = half(3)
+    g: float = half(3, scale: 2)
+    b: int = show("x")
+    c: int = show(x: 2)
+    return 0
+}
+"#;
+        let pipeline = AnalysisPipeline::new();
+        let result = pipeline.analyze(source, create_test_file_id(source));
+        assert!(!has_error_code(&result, ErrorCode::AnalyzerWrongArgumentType));
+    }
+
     // ============================================================================
     // EDGE CASES AND ERROR HANDLING TESTS
     // ============================================================================
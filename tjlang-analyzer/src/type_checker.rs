@@ -3,11 +3,13 @@
 //! A comprehensive type checker that performs full type checking
 //! with proper diagnostics and type inference.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tjlang_ast::*;
 use tjlang_diagnostics::{debug_println, DiagnosticCollection, TJLangDiagnostic, ErrorCode, SourceSpan as DiagnosticSourceSpan};
 use codespan_reporting::diagnostic::Severity;
-use tjlang_types::{Type, TypeEnvironment};
+use tjlang_types::algebraic_types::AlgebraicType;
+use tjlang_types::interfaces::MethodSignature;
+use tjlang_types::{ConstraintSolver, Type, TypeEnvironment};
 use codespan::{FileId, Files};
 use tjlang_stdlib;
 
@@ -17,6 +19,20 @@ pub struct TypeChecker {
     environment: TypeEnvironment,
     type_map: HashMap<String, Type>,
     current_file_id: FileId,
+    /// Names of structs and enums declared in the program
+    user_types: HashSet<String>,
+    /// Declared field types of each struct
+    struct_fields: HashMap<String, HashMap<String, Type>>,
+    /// Operator symbols each user type implements, with the operator's result type
+    operator_impls: HashMap<String, HashMap<String, Type>>,
+    /// Interface implementations, built-in and declared by `impl` blocks
+    constraint_solver: ConstraintSolver,
+    /// Generic parameters of each generic function
+    generic_functions: HashMap<String, Vec<GenericParam>>,
+    /// Declared parameters of each function, for keyword arguments and defaults
+    function_params: HashMap<String, Vec<Parameter>>,
+    /// Generic parameter names in scope while converting a function's types
+    type_params: HashSet<String>,
 }
 
 impl TypeChecker {
@@ -31,6 +47,13 @@ impl TypeChecker {
             environment: TypeEnvironment::new(),
             type_map: HashMap::new(),
             current_file_id: file_id,
+            user_types: HashSet::new(),
+            struct_fields: HashMap::new(),
+            operator_impls: HashMap::new(),
+            constraint_solver: ConstraintSolver::new(),
+            generic_functions: HashMap::new(),
+            function_params: HashMap::new(),
+            type_params: HashSet::new(),
         }
     }
 
@@ -39,15 +62,212 @@ impl TypeChecker {
         // Clear previous diagnostics
         self.diagnostics = DiagnosticCollection::new();
         self.type_map.clear();
+        self.collect_user_types(program);
         
         // Type check each program unit
         for unit in &program.units {
             self.check_program_unit(unit)?;
         }
+
+        // Bodies are checked once every function signature is known
+        for unit in &program.units {
+            if let ProgramUnit::Declaration(Declaration::Function(func_decl))
+            | ProgramUnit::Export(ExportDecl::Declaration(Declaration::Function(func_decl))) = unit
+            {
+                self.check_function_body(func_decl)?;
+            }
+        }
         
         Ok(self.type_map.clone())
     }
     
+    /// Record user-defined types, their fields and the operators their `impl` blocks provide
+    fn collect_user_types(&mut self, program: &Program) {
+        self.user_types.clear();
+        self.struct_fields.clear();
+        self.operator_impls.clear();
+        self.constraint_solver = ConstraintSolver::new();
+        self.generic_functions.clear();
+        self.function_params.clear();
+
+        let declarations: Vec<&Declaration> = program
+            .units
+            .iter()
+            .filter_map(|unit| match unit {
+                ProgramUnit::Declaration(decl) | ProgramUnit::Export(ExportDecl::Declaration(decl)) => Some(decl),
+                _ => None,
+            })
+            .collect();
+
+        for decl in &declarations {
+            match decl {
+                Declaration::Struct(struct_decl) => {
+                    self.user_types.insert(struct_decl.name.clone());
+                }
+                Declaration::Enum(enum_decl) => {
+                    self.user_types.insert(enum_decl.name.clone());
+                }
+                _ => {}
+            }
+        }
+        for decl in &declarations {
+            match decl {
+                Declaration::Struct(struct_decl) => {
+                    let fields = struct_decl
+                        .fields
+                        .iter()
+                        .map(|field| (field.name.clone(), self.ast_type_to_type(&field.field_type)))
+                        .collect();
+                    self.struct_fields.insert(struct_decl.name.clone(), fields);
+                }
+                Declaration::Function(func_decl) => {
+                    self.function_params
+                        .insert(func_decl.name.clone(), func_decl.params.clone());
+                    if !func_decl.generic_params.is_empty() {
+                        self.generic_functions
+                            .insert(func_decl.name.clone(), func_decl.generic_params.clone());
+                    }
+                }
+                Declaration::Implementation(impl_block) => {
+                    let signatures = impl_block
+                        .methods
+                        .iter()
+                        .map(|method| MethodSignature {
+                            name: method.name.clone(),
+                            params: method
+                                .params
+                                .iter()
+                                .map(|p| AlgebraicType::from_ast_type(&p.param_type))
+                                .collect(),
+                            return_type: AlgebraicType::from_ast_type(&method.return_type),
+                            span: self.convert_span(method.span.clone()),
+                        })
+                        .collect();
+                    self.constraint_solver.register_implementation(
+                        impl_block.type_name.clone(),
+                        impl_block.trait_name.clone(),
+                        signatures,
+                    );
+                    for method in &impl_block.methods {
+                        if Self::is_operator_symbol(&method.name) {
+                            let arg_count = method.params.iter().filter(|p| p.name != "self").count();
+                            let result_type = self.ast_type_to_type(&method.return_type);
+                            self.operator_impls
+                                .entry(impl_block.type_name.clone())
+                                .or_default()
+                                .insert(UnaryOperator::impl_method_key(&method.name, arg_count), result_type);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether a method name is an overloadable operator symbol
+    fn is_operator_symbol(name: &str) -> bool {
+        name.chars().next().map(|c| !c.is_alphanumeric() && c != '_').unwrap_or(false)
+            || name == "and"
+            || name == "or"
+    }
+
+    /// Name of a user-defined struct or enum type
+    fn user_type_name<'a>(&self, ty: &'a Type) -> Option<&'a str> {
+        match ty {
+            Type::Generic(name, _) if self.user_types.contains(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Result type of an operator applied to a user type, reporting a diagnostic
+    /// when the type does not implement it
+    fn check_operator_impl(&mut self, type_name: &str, symbol: &str, span: &tjlang_ast::SourceSpan) -> Option<Type> {
+        let found = self
+            .operator_impls
+            .get(type_name)
+            .and_then(|operators| operators.get(symbol))
+            .cloned();
+        if found.is_none() {
+            self.add_diagnostic(
+                ErrorCode::AnalyzerTraitNotImplemented,
+                Severity::Error,
+                format!(
+                    "Type '{}' does not implement operator '{}'; add an impl of an interface that declares it",
+                    type_name, symbol
+                ),
+                self.convert_span(span.clone())
+            );
+        }
+        found
+    }
+
+    /// Type check a binary operator applied to a user type, or `None` when neither
+    /// operand has a user type
+    fn check_binary_operator_overload(&mut self, left_type: &Type, operator: &BinaryOperator, right_type: &Type, span: &tjlang_ast::SourceSpan) -> Option<Type> {
+        // Plain assignment is not an operator; `+=` and the like apply their base operator
+        if *operator == BinaryOperator::Assign {
+            return None;
+        }
+        if let Some(base_operator) = operator.compound_operator() {
+            return self.check_binary_operator_overload(left_type, &base_operator, right_type, span);
+        }
+
+        let left_name = self.user_type_name(left_type).map(str::to_string);
+        let right_name = self.user_type_name(right_type).map(str::to_string);
+        let (receiver, symbol) = match operator {
+            // Equality and logical operators also have a built-in meaning on every type
+            BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::And | BinaryOperator::Or => {
+                return (left_name.is_some() || right_name.is_some()).then_some(Type::Bool);
+            }
+            // `>` and `<=` are derived from the right operand's `<`, and `>=` from the left's
+            BinaryOperator::GreaterThan | BinaryOperator::LessThanEqual => (right_name.or(left_name)?, "<".to_string()),
+            BinaryOperator::LessThan | BinaryOperator::GreaterThanEqual => (left_name.or(right_name)?, "<".to_string()),
+            _ => {
+                let symbol = operator.to_string();
+                let implements = |name: &Option<String>| {
+                    name.as_ref()
+                        .and_then(|name| self.operator_impls.get(name))
+                        .is_some_and(|operators| operators.contains_key(&symbol))
+                };
+                // Only commutative operators fall back to the right operand's method
+                if !implements(&left_name) && implements(&right_name) {
+                    let right_name = right_name?;
+                    if !operator.is_commutative() {
+                        self.add_diagnostic(
+                            ErrorCode::AnalyzerTraitNotImplemented,
+                            Severity::Error,
+                            format!(
+                                "Operator '{}' is not commutative, so it only dispatches on its left operand; '{}' implements it but is on the right",
+                                symbol, right_name
+                            ),
+                            self.convert_span(span.clone())
+                        );
+                        return Some(right_type.clone());
+                    }
+                    (right_name, symbol)
+                } else {
+                    (left_name.or(right_name)?, symbol)
+                }
+            }
+        };
+        let result = self.check_operator_impl(&receiver, &symbol, span);
+        let is_comparison = matches!(
+            operator,
+            BinaryOperator::LessThan | BinaryOperator::LessThanEqual | BinaryOperator::GreaterThan | BinaryOperator::GreaterThanEqual
+        );
+        Some(if is_comparison { Type::Bool } else { result.unwrap_or_else(|| left_type.clone()) })
+    }
+
+    /// Type check an index expression, which user types support through a `[]` operator
+    fn check_index_expression(&mut self, target: &Expression, index: &Expression, span: &tjlang_ast::SourceSpan) -> Result<Type, DiagnosticCollection> {
+        let target_type = self.check_expression_with_span(target, Some(span))?;
+        self.check_expression_with_span(index, Some(span))?;
+        match self.user_type_name(&target_type).map(str::to_string) {
+            Some(type_name) => Ok(self.check_operator_impl(&type_name, "[]", span).unwrap_or(Type::Any)),
+            None => Ok(Type::Int), // Default fallback
+        }
+    }
+
     /// Type check a program unit
     fn check_program_unit(&mut self, unit: &ProgramUnit) -> Result<(), DiagnosticCollection> {
         match unit {
@@ -139,6 +359,9 @@ impl TypeChecker {
             return Ok(());
         }
         
+        // Generic parameters are type variables within the signature
+        self.type_params = func_decl.generic_params.iter().map(|g| g.name.clone()).collect();
+
         // Type check parameters
         let mut param_types = Vec::new();
         for param in &func_decl.params {
@@ -155,6 +378,8 @@ impl TypeChecker {
             self.ast_type_to_type(&func_decl.return_type)
         };
         
+        self.type_params.clear();
+
         // Create function type using tuple syntax
         let function_type = Type::Function(param_types, Box::new(return_type));
         
@@ -163,6 +388,146 @@ impl TypeChecker {
         Ok(())
     }
     
+    /// Type check a function body with its parameters in scope. Locals and pattern
+    /// bindings are only tracked roughly, so of the body's diagnostics only the
+    /// generic bound and operator checks, which fire on known user types, are kept;
+    /// mismatches and undefined names inside bodies are left to the dedicated rules.
+    fn check_function_body(&mut self, func_decl: &FunctionDecl) -> Result<(), DiagnosticCollection> {
+        const BODY_DIAGNOSTIC_CODES: [ErrorCode; 2] =
+            [ErrorCode::AnalyzerInvalidTraitBound, ErrorCode::AnalyzerTraitNotImplemented];
+
+        self.type_params = func_decl.generic_params.iter().map(|g| g.name.clone()).collect();
+        let bindings = func_decl
+            .params
+            .iter()
+            .map(|param| (param.name.clone(), self.ast_type_to_type(&param.param_type)))
+            .collect();
+        let outer = std::mem::take(&mut self.diagnostics);
+        let result = self.with_bindings(bindings, |checker| checker.check_body_block(&func_decl.body));
+        let body_diagnostics = std::mem::replace(&mut self.diagnostics, outer);
+        self.type_params.clear();
+        result?;
+
+        for diagnostic in body_diagnostics.iter() {
+            if BODY_DIAGNOSTIC_CODES.contains(&diagnostic.code) {
+                self.diagnostics.add(diagnostic.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Type check the statements of a block in a function body; locals it declares
+    /// go out of scope at its end
+    fn check_body_block(&mut self, block: &Block) -> Result<(), DiagnosticCollection> {
+        let outer_scope = self.type_map.clone();
+        let result = block.statements.iter().try_for_each(|stmt| self.check_body_statement(stmt));
+        self.type_map = outer_scope;
+        result
+    }
+
+    /// Type check a statement in a function body
+    fn check_body_statement(&mut self, stmt: &Statement) -> Result<(), DiagnosticCollection> {
+        match stmt {
+            Statement::Variable(var_decl) => {
+                let value_type = self.check_expression(&var_decl.value)?;
+                let var_type = if self.is_any_type(&var_decl.var_type) {
+                    value_type
+                } else {
+                    self.ast_type_to_type(&var_decl.var_type)
+                };
+                self.type_map.insert(var_decl.name.clone(), var_type);
+            }
+            Statement::Expression(expr) => {
+                self.check_expression(expr)?;
+            }
+            Statement::If(if_stmt) => {
+                self.check_expression(&if_stmt.condition)?;
+                self.check_body_block(&if_stmt.then_block)?;
+                for elif in &if_stmt.elif_branches {
+                    self.check_expression(&elif.condition)?;
+                    self.check_body_block(&elif.block)?;
+                }
+                if let Some(else_block) = &if_stmt.else_block {
+                    self.check_body_block(else_block)?;
+                }
+            }
+            Statement::While(while_stmt) => {
+                self.check_expression(&while_stmt.condition)?;
+                self.check_body_block(&while_stmt.body)?;
+            }
+            Statement::DoWhile(do_while) => {
+                self.check_body_block(&do_while.body)?;
+                self.check_expression(&do_while.condition)?;
+            }
+            Statement::For(ForStatement::ForEach { var_name, var_type, iterable, body, .. }) => {
+                self.check_expression(iterable)?;
+                let bindings = vec![(var_name.clone(), self.ast_type_to_type(var_type))];
+                self.with_bindings(bindings, |checker| checker.check_body_block(body))?;
+            }
+            Statement::For(ForStatement::CStyle { initializer, condition, increment, body, .. }) => {
+                let outer_scope = self.type_map.clone();
+                let result = (|| {
+                    if let Some(initializer) = initializer {
+                        self.check_body_statement(initializer)?;
+                    }
+                    for expr in condition.iter().chain(increment) {
+                        self.check_expression(expr)?;
+                    }
+                    self.check_body_block(body)
+                })();
+                self.type_map = outer_scope;
+                result?;
+            }
+            Statement::Match(match_stmt) => {
+                self.check_expression(&match_stmt.expression)?;
+                for arm in &match_stmt.arms {
+                    let bindings = self.pattern_bindings(&arm.pattern);
+                    self.with_bindings(bindings, |checker| {
+                        if let Some(guard) = &arm.guard {
+                            checker.check_expression(guard)?;
+                        }
+                        checker.check_body_block(&arm.body)
+                    })?;
+                }
+            }
+            Statement::Return(return_stmt) => {
+                if let Some(expr) = &return_stmt.value {
+                    self.check_expression(expr)?;
+                }
+            }
+            Statement::Raise(raise_stmt) => {
+                self.check_expression(&raise_stmt.value)?;
+            }
+            Statement::Try(try_stmt) => {
+                self.check_body_block(&try_stmt.body)?;
+                for handler in &try_stmt.handlers {
+                    let bindings = handler.pattern.as_ref().map(|p| self.pattern_bindings(p)).unwrap_or_default();
+                    self.with_bindings(bindings, |checker| checker.check_body_block(&handler.body))?;
+                }
+            }
+            Statement::Block(block) => self.check_body_block(block)?,
+            Statement::Break(_) | Statement::Continue(_) | Statement::Pass(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Variables a pattern binds; fields of constructor and struct patterns are untyped
+    fn pattern_bindings(&self, pattern: &Pattern) -> Vec<(String, Type)> {
+        match pattern {
+            Pattern::Variable { name, pattern_type, .. } => vec![(name.clone(), self.ast_type_to_type(pattern_type))],
+            Pattern::TraitCheck { name, .. } => vec![(name.clone(), Type::Any)],
+            Pattern::Constructor { fields, .. } => fields.iter().flat_map(|field| self.untyped_bindings(field)).collect(),
+            Pattern::Struct { fields, .. } => fields.iter().flat_map(|(_, field)| self.untyped_bindings(field)).collect(),
+            Pattern::Tuple { patterns, .. } => patterns.iter().flat_map(|p| self.pattern_bindings(p)).collect(),
+            Pattern::Literal(_) | Pattern::Wildcard(_) => Vec::new(),
+        }
+    }
+
+    /// Variables a nested pattern binds, all of unknown type
+    fn untyped_bindings(&self, pattern: &Pattern) -> Vec<(String, Type)> {
+        self.pattern_bindings(pattern).into_iter().map(|(name, _)| (name, Type::Any)).collect()
+    }
+
     /// Type check an expression
     fn check_expression(&mut self, expr: &Expression) -> Result<Type, DiagnosticCollection> {
         self.check_expression_with_span(expr, None)
@@ -181,8 +546,8 @@ impl TypeChecker {
             Expression::Unary { operator, operand, span } => {
                 self.check_unary_expression_with_span(operator, operand, span)
             },
-            Expression::Call { callee, args, span } => {
-                self.check_function_call_with_span(callee, args, span)
+            Expression::Call { callee, args, named_args, span } => {
+                self.check_function_call_with_span(callee, args, named_args, span)
             },
             Expression::If { condition, then_expr, else_expr, span } => {
                 self.check_if_expression_with_span(condition, then_expr, else_expr, span)
@@ -208,6 +573,12 @@ impl TypeChecker {
             Expression::Member { target, member, span } => {
                 self.check_member_access_with_span(target, member, span)
             },
+            Expression::StructLiteral { name, .. } if self.user_types.contains(name) => {
+                Ok(Type::Generic(name.clone(), vec![]))
+            },
+            Expression::Index { target, index, span } => {
+                self.check_index_expression(target, index, span)
+            },
             _ => {
                 // Handle other expression types
                 Ok(Type::Int) // Default fallback
@@ -419,6 +790,11 @@ impl TypeChecker {
     fn check_binary_expression_with_span(&mut self, left: &Expression, operator: &BinaryOperator, right: &Expression, span: &tjlang_ast::SourceSpan) -> Result<Type, DiagnosticCollection> {
         let left_type = self.check_expression_with_span(left, Some(span))?;
         let right_type = self.check_expression_with_span(right, Some(span))?;
+
+        // Operators on structs and enums dispatch to their `impl` methods
+        if let Some(result_type) = self.check_binary_operator_overload(&left_type, operator, &right_type, span) {
+            return Ok(result_type);
+        }
         
         match operator {
             BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply | BinaryOperator::Divide => {
@@ -491,6 +867,22 @@ impl TypeChecker {
     /// Type check a unary expression with span
     fn check_unary_expression_with_span(&mut self, operator: &UnaryOperator, operand: &Expression, span: &tjlang_ast::SourceSpan) -> Result<Type, DiagnosticCollection> {
         let operand_type = self.check_expression_with_span(operand, Some(span))?;
+
+        if let Some(type_name) = self.user_type_name(&operand_type).map(str::to_string) {
+            let implemented = self
+                .operator_impls
+                .get(&type_name)
+                .and_then(|operators| operators.get(&operator.method_key()))
+                .cloned();
+            return Ok(match (implemented, operator) {
+                (Some(result_type), _) => result_type,
+                // `not` falls back to truthiness when the type has no `!` operator
+                (None, UnaryOperator::Not) => Type::Bool,
+                (None, _) => self
+                    .check_operator_impl(&type_name, &operator.method_key(), span)
+                    .unwrap_or(operand_type),
+            });
+        }
         
         match operator {
             UnaryOperator::Negate => {
@@ -524,25 +916,75 @@ impl TypeChecker {
     }
     
     /// Type check a function call with span
-    fn check_function_call_with_span(&mut self, callee: &Expression, args: &[Expression], span: &tjlang_ast::SourceSpan) -> Result<Type, DiagnosticCollection> {
+    fn check_function_call_with_span(&mut self, callee: &Expression, args: &[Expression], named_args: &[FieldInit], span: &tjlang_ast::SourceSpan) -> Result<Type, DiagnosticCollection> {
+        if let Expression::Member { target, member, .. } = callee {
+            if let Some(signature) = self.stdlib_function(target, member) {
+                return self.check_stdlib_call(signature, args, named_args, span);
+            }
+        }
+
         let callee_type = self.check_expression_with_span(callee, Some(span))?;
         
+        let (generic_params, declared) = match callee {
+            Expression::Variable { name, .. } => (
+                self.generic_functions.get(name).cloned(),
+                self.function_params.get(name).cloned(),
+            ),
+            _ => (None, None),
+        };
+
         match callee_type {
             Type::Function(params, return_type) => {
-                if args.len() != params.len() {
+                // Pair each argument with the index of its parameter. Parameters with a
+                // default may be left out; unknown keywords are reported by
+                // ParameterTypeValidationRule.
+                let mut supplied: Vec<(usize, &Expression)> = args.iter().enumerate().collect();
+                let mut required = params.len();
+                if let Some(declared) = &declared {
+                    for named_arg in named_args {
+                        if let Some(index) = declared.iter().position(|p| p.name == named_arg.name) {
+                            if index >= args.len() {
+                                supplied.push((index, &named_arg.value));
+                            }
+                        }
+                    }
+                    required = declared.iter().filter(|p| p.default_value.is_none()).count();
+                }
+                let missing_required = declared.as_ref().is_some_and(|declared| {
+                    declared.iter().enumerate().any(|(i, p)| {
+                        p.default_value.is_none() && !supplied.iter().any(|(index, _)| *index == i)
+                    })
+                });
+                if args.len() > params.len() || (declared.is_none() && args.len() != params.len()) || missing_required {
+                    let expected = if required == params.len() {
+                        params.len().to_string()
+                    } else {
+                        format!("{} to {}", required, params.len())
+                    };
                     self.add_diagnostic(
                         ErrorCode::AnalyzerTypeMismatch,
                         Severity::Error,
-                        format!("Expected {} arguments, found {}", params.len(), args.len()),
+                        format!("Expected {} arguments, found {}", expected, args.len() + named_args.len()),
                         self.convert_span(span.clone())
                     );
                     return Ok(Type::Int);
                 }
                 
+                let mut arg_types = Vec::new();
+                for (index, arg) in &supplied {
+                    arg_types.push((*index, self.check_expression_with_span(arg, Some(span))?));
+                }
+
+                // Infer generic parameters from the arguments, the first binding winning
+                let mut bindings = HashMap::new();
+                for (index, arg_type) in &arg_types {
+                    Self::infer_type_params(&params[*index], arg_type, &mut bindings);
+                }
+
                 // Check argument types
-                for (i, (arg, param_type)) in args.iter().zip(params.iter()).enumerate() {
-                    let arg_type = self.check_expression_with_span(arg, Some(span))?;
-                    if !self.is_type_compatible(&arg_type, param_type) {
+                for &(i, ref arg_type) in &arg_types {
+                    let param_type = Self::substitute_type_params(&params[i], &bindings);
+                    if !self.is_type_compatible(arg_type, &param_type) {
                         self.add_diagnostic(
                             ErrorCode::AnalyzerTypeMismatch,
                             Severity::Error,
@@ -551,8 +993,12 @@ impl TypeChecker {
                         );
                     }
                 }
-                
-                Ok(*return_type)
+
+                if let (Some(generic_params), Expression::Variable { name, .. }) = (&generic_params, callee) {
+                    self.check_generic_bounds(name, generic_params, &bindings, span);
+                }
+
+                Ok(Self::substitute_type_params(&return_type, &bindings))
             },
             _ => {
                 self.add_diagnostic(
@@ -566,6 +1012,157 @@ impl TypeChecker {
         }
     }
     
+    /// Whether an expression names a stdlib module that no variable shadows
+    fn is_stdlib_module(&self, target: &Expression) -> bool {
+        matches!(target, Expression::Variable { name, .. }
+            if !self.type_map.contains_key(name) && tjlang_stdlib::get_stdlib_module_names().contains(name))
+    }
+
+    /// Signature of a stdlib module function, unless a variable shadows the module
+    fn stdlib_function(&self, target: &Expression, member: &str) -> Option<&'static tjlang_stdlib::StdlibSignature> {
+        match target {
+            Expression::Variable { name, .. } if self.is_stdlib_module(target) => {
+                tjlang_stdlib::get_stdlib_signature(name, member)
+            }
+            _ => None,
+        }
+    }
+
+    /// Type check a call to a stdlib function against its signature. The argument
+    /// count is checked by UndefinedFunctionRule.
+    fn check_stdlib_call(
+        &mut self,
+        signature: &tjlang_stdlib::StdlibSignature,
+        args: &[Expression],
+        named_args: &[FieldInit],
+        span: &tjlang_ast::SourceSpan,
+    ) -> Result<Type, DiagnosticCollection> {
+        let params = signature.params.iter().map(Some).chain(std::iter::repeat(signature.rest.as_ref()));
+        for (i, (arg, param)) in args.iter().zip(params).enumerate() {
+            let arg_type = self.check_expression_with_span(arg, Some(span))?;
+            let Some(param) = param else {
+                break;
+            };
+            let param_type = Self::stdlib_type_to_type(&param.ty);
+            if !self.is_type_compatible(&arg_type, &param_type) {
+                self.add_diagnostic(
+                    ErrorCode::AnalyzerTypeMismatch,
+                    Severity::Error,
+                    format!(
+                        "Argument {} ('{}') of {}.{}: expected {:?}, found {:?}",
+                        i + 1, param.name, signature.module, signature.name, param_type, arg_type
+                    ),
+                    self.convert_span(span.clone())
+                );
+            }
+        }
+        for named_arg in named_args {
+            self.check_expression_with_span(&named_arg.value, Some(span))?;
+        }
+        Ok(Self::stdlib_type_to_type(&signature.return_type))
+    }
+
+    /// Convert a stdlib signature type. Stdlib `any` parameters accept every value;
+    /// optional results are the bare value or None, and struct results such as
+    /// `FileMetadata` are not declared in the program, so both are left unchecked.
+    fn stdlib_type_to_type(ast_type: &tjlang_ast::Type) -> Type {
+        match ast_type {
+            tjlang_ast::Type::Primitive(primitive) => match primitive {
+                tjlang_ast::PrimitiveType::Int => Type::Int,
+                tjlang_ast::PrimitiveType::Float => Type::Float,
+                tjlang_ast::PrimitiveType::Bool => Type::Bool,
+                tjlang_ast::PrimitiveType::Str => Type::Str,
+                tjlang_ast::PrimitiveType::Any => Type::Any,
+            },
+            tjlang_ast::Type::Vec { element_type, .. } => Type::Vec(Box::new(Self::stdlib_type_to_type(element_type))),
+            tjlang_ast::Type::Set { element_type, .. } => Type::Set(Box::new(Self::stdlib_type_to_type(element_type))),
+            tjlang_ast::Type::Map { key_type, value_type, .. } => Type::Map(
+                Box::new(Self::stdlib_type_to_type(key_type)),
+                Box::new(Self::stdlib_type_to_type(value_type)),
+            ),
+            tjlang_ast::Type::Tuple { types, .. } => Type::Tuple(types.iter().map(Self::stdlib_type_to_type).collect()),
+            _ => Type::Any,
+        }
+    }
+
+    /// Bind the type variables in `param` to the matching parts of `arg`
+    fn infer_type_params(param: &Type, arg: &Type, bindings: &mut HashMap<String, Type>) {
+        match (param, arg) {
+            (Type::Variable(name), _) => {
+                bindings.entry(name.clone()).or_insert_with(|| arg.clone());
+            }
+            (Type::Vec(param), Type::Vec(arg))
+            | (Type::Set(param), Type::Set(arg))
+            | (Type::Option(param), Type::Option(arg))
+            | (Type::Task(param), Type::Task(arg)) => Self::infer_type_params(param, arg, bindings),
+            (Type::Map(param_key, param_value), Type::Map(arg_key, arg_value))
+            | (Type::Result(param_key, param_value), Type::Result(arg_key, arg_value)) => {
+                Self::infer_type_params(param_key, arg_key, bindings);
+                Self::infer_type_params(param_value, arg_value, bindings);
+            }
+            (Type::Tuple(params), Type::Tuple(args)) if params.len() == args.len() => {
+                for (param, arg) in params.iter().zip(args) {
+                    Self::infer_type_params(param, arg, bindings);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace bound type variables in `ty`
+    fn substitute_type_params(ty: &Type, bindings: &HashMap<String, Type>) -> Type {
+        let substitute = |ty: &Type| Box::new(Self::substitute_type_params(ty, bindings));
+        match ty {
+            Type::Variable(name) => bindings.get(name).cloned().unwrap_or_else(|| ty.clone()),
+            Type::Vec(inner) => Type::Vec(substitute(inner)),
+            Type::Set(inner) => Type::Set(substitute(inner)),
+            Type::Option(inner) => Type::Option(substitute(inner)),
+            Type::Task(inner) => Type::Task(substitute(inner)),
+            Type::Map(key, value) => Type::Map(substitute(key), substitute(value)),
+            Type::Result(ok, err) => Type::Result(substitute(ok), substitute(err)),
+            Type::Tuple(types) => {
+                Type::Tuple(types.iter().map(|t| Self::substitute_type_params(t, bindings)).collect())
+            }
+            Type::Function(params, return_type) => Type::Function(
+                params.iter().map(|t| Self::substitute_type_params(t, bindings)).collect(),
+                substitute(return_type),
+            ),
+            _ => ty.clone(),
+        }
+    }
+
+    /// Report each generic parameter whose inferred type does not implement all of its bounds
+    fn check_generic_bounds(
+        &mut self,
+        function_name: &str,
+        generic_params: &[GenericParam],
+        bindings: &HashMap<String, Type>,
+        span: &tjlang_ast::SourceSpan,
+    ) {
+        for param in generic_params {
+            let Some(bound_type) = bindings.get(&param.name) else {
+                continue;
+            };
+            if matches!(bound_type, Type::Any | Type::Variable(_)) {
+                continue;
+            }
+            let type_name = bound_type.to_string();
+            for bound in &param.bounds {
+                if !self.constraint_solver.type_implements_interface(&type_name, bound) {
+                    self.add_diagnostic(
+                        ErrorCode::AnalyzerInvalidTraitBound,
+                        Severity::Error,
+                        format!(
+                            "Type '{}' does not implement '{}', required by generic parameter '{}' of '{}'",
+                            type_name, bound, param.name, function_name
+                        ),
+                        self.convert_span(span.clone())
+                    );
+                }
+            }
+        }
+    }
+
     /// Type check an if expression with span
     fn check_if_expression_with_span(&mut self, condition: &Expression, then_expr: &Expression, else_expr: &Expression, span: &tjlang_ast::SourceSpan) -> Result<Type, DiagnosticCollection> {
         let condition_type = self.check_expression_with_span(condition, Some(span))?;
@@ -639,7 +1236,9 @@ impl TypeChecker {
             param_types.push(param_type);
         }
 
-        let return_type = self.check_expression_with_span(body, Some(span))?;
+        // The body sees the parameters on top of the variables it captures
+        let bindings = params.iter().map(|param| param.name.clone()).zip(param_types.iter().cloned()).collect();
+        let return_type = self.with_bindings(bindings, |checker| checker.check_expression_with_span(body, Some(span)))?;
         
         Ok(Type::Function(param_types, Box::new(return_type)))
     }
@@ -654,6 +1253,30 @@ impl TypeChecker {
         if from == to {
             return true;
         }
+
+        // A type variable left unbound by inference accepts anything
+        if matches!(to, Type::Variable(_)) {
+            return true;
+        }
+
+        // Values of unknown type, such as untyped stdlib results, are checked at runtime
+        if matches!(from, Type::Any) || matches!(to, Type::Any) {
+            return true;
+        }
+
+        // Containers are compatible when their elements are
+        match (from, to) {
+            (Type::Vec(from), Type::Vec(to)) | (Type::Set(from), Type::Set(to)) | (Type::Option(from), Type::Option(to)) => {
+                return self.is_type_compatible(from, to);
+            }
+            (Type::Map(from_key, from_value), Type::Map(to_key, to_value)) => {
+                return self.is_type_compatible(from_key, to_key) && self.is_type_compatible(from_value, to_value);
+            }
+            (Type::Tuple(from), Type::Tuple(to)) if from.len() == to.len() => {
+                return from.iter().zip(to).all(|(from, to)| self.is_type_compatible(from, to));
+            }
+            _ => {}
+        }
         
         // Allow int to float conversion
         if matches!(from, Type::Int) && matches!(to, Type::Float) {
@@ -703,6 +1326,12 @@ impl TypeChecker {
                 let param_types: Vec<Type> = params.iter().map(|p| self.ast_type_to_type(p)).collect();
                 Type::Function(param_types, Box::new(self.ast_type_to_type(return_type)))
             },
+            tjlang_ast::Type::Identifier(name) if self.user_types.contains(name) => {
+                Type::Generic(name.clone(), vec![])
+            },
+            tjlang_ast::Type::Identifier(name) if self.type_params.contains(name) => {
+                Type::Variable(name.clone())
+            },
             _ => Type::Int, // Default fallback
         }
     }
@@ -714,8 +1343,39 @@ impl TypeChecker {
     
     /// Infer function return type from function body
     fn infer_function_return_type(&mut self, func_decl: &FunctionDecl) -> Result<Type, DiagnosticCollection> {
+        // Returned expressions may refer to the parameters and to locals declared in the body
+        let mut bindings: Vec<(String, Type)> = func_decl
+            .params
+            .iter()
+            .map(|param| (param.name.clone(), self.ast_type_to_type(&param.param_type)))
+            .collect();
+        for stmt in &func_decl.body.statements {
+            if let Statement::Variable(var_decl) = stmt {
+                bindings.push((var_decl.name.clone(), self.ast_type_to_type(&var_decl.var_type)));
+            }
+        }
+
         // Analyze the function body to determine return type
-        self.analyze_function_body(&func_decl.body)
+        self.with_bindings(bindings, |checker| checker.analyze_function_body(&func_decl.body))
+    }
+
+    /// Run `check` with extra variables in scope, restoring any they shadow afterwards
+    fn with_bindings<T>(&mut self, bindings: Vec<(String, Type)>, check: impl FnOnce(&mut Self) -> T) -> T {
+        let shadowed: Vec<(String, Option<Type>)> = bindings
+            .into_iter()
+            .map(|(name, ty)| {
+                let previous = self.type_map.insert(name.clone(), ty);
+                (name, previous)
+            })
+            .collect();
+        let result = check(self);
+        for (name, previous) in shadowed.into_iter().rev() {
+            match previous {
+                Some(ty) => self.type_map.insert(name, ty),
+                None => self.type_map.remove(&name),
+            };
+        }
+        result
     }
     
     /// Analyze function body to determine return type
@@ -822,6 +1482,23 @@ impl TypeChecker {
     
     /// Type check member access with span
     fn check_member_access_with_span(&mut self, target: &Expression, member: &str, span: &tjlang_ast::SourceSpan) -> Result<Type, DiagnosticCollection> {
+        // Stdlib module functions are typed by their binding signatures; calls to
+        // unknown ones are reported by UndefinedFunctionRule
+        if self.is_stdlib_module(target) {
+            return Ok(match self.stdlib_function(target, member) {
+                Some(signature) => Type::Function(
+                    signature
+                        .params
+                        .iter()
+                        .chain(&signature.rest)
+                        .map(|param| Self::stdlib_type_to_type(&param.ty))
+                        .collect(),
+                    Box::new(Self::stdlib_type_to_type(&signature.return_type)),
+                ),
+                None => Type::Any,
+            });
+        }
+
         // First, type check the target expression
         let target_type = self.check_expression_with_span(target, Some(span))?;
         
@@ -836,6 +1513,15 @@ impl TypeChecker {
             Type::Map(_, _) => self.check_map_member(member, span),
             Type::Tuple(_) => self.check_tuple_member(member, span),
             Type::Any => Ok(Type::Any), // Any type can have any member
+            Type::Generic(ref name, _) if self.user_types.contains(name) => {
+                // Struct fields have their declared type; methods are resolved at runtime
+                Ok(self
+                    .struct_fields
+                    .get(name)
+                    .and_then(|fields| fields.get(member))
+                    .cloned()
+                    .unwrap_or(Type::Any))
+            },
             _ => {
                 self.add_diagnostic(
                     ErrorCode::AnalyzerTypeMismatch,
@@ -882,6 +1568,13 @@ impl TypeChecker {
             "is_positive" => Ok(Type::Bool),
             "is_negative" => Ok(Type::Bool),
             "is_zero" => Ok(Type::Bool),
+            // Arithmetic with explicit overflow behaviour
+            "checked_add" | "checked_sub" | "checked_mul" | "checked_div" | "checked_pow" => {
+                Ok(Type::Option(Box::new(Type::Int)))
+            }
+            "wrapping_add" | "wrapping_sub" | "wrapping_mul" | "wrapping_div" | "wrapping_pow"
+            | "saturating_add" | "saturating_sub" | "saturating_mul" | "saturating_div"
+            | "saturating_pow" => Ok(Type::Int),
             // Conversion methods
             "to_int" => Ok(Type::Int),
             "to_float" => Ok(Type::Float),
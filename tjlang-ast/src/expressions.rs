@@ -3,7 +3,7 @@
 use super::*;
 
 /// Expression system
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Expression {
     Literal(Literal),
     Variable {
@@ -24,6 +24,8 @@ pub enum Expression {
     Call {
         callee: Box<Expression>,
         args: Vec<Expression>,
+        /// Keyword arguments, written after the positional ones
+        named_args: Vec<FieldInit>,
         span: SourceSpan,
     },
     Index {
@@ -36,6 +38,10 @@ pub enum Expression {
         member: String,
         span: SourceSpan,
     },
+    Propagate {
+        expression: Box<Expression>,
+        span: SourceSpan,
+    },
     Lambda {
         params: Vec<Parameter>,
         body: Box<Expression>,
@@ -86,7 +92,7 @@ pub enum Expression {
 }
 
 /// Binary operators
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BinaryOperator {
     Add,
     Subtract,
@@ -108,10 +114,88 @@ pub enum BinaryOperator {
     And,
     Or,
     Assign,
+    AddAssign,
+    SubtractAssign,
+    MultiplyAssign,
+    DivideAssign,
+    ModuloAssign,
+    PowerAssign,
+    ShiftLeftAssign,
+    ShiftRightAssign,
+    BitAndAssign,
+    BitOrAssign,
+    BitXorAssign,
+}
+
+impl Expression {
+    /// Source location of the expression; literals carry none
+    pub fn span(&self) -> Option<&SourceSpan> {
+        match self {
+            Expression::Literal(_) => None,
+            Expression::Variable { span, .. }
+            | Expression::Binary { span, .. }
+            | Expression::Unary { span, .. }
+            | Expression::Call { span, .. }
+            | Expression::Index { span, .. }
+            | Expression::Member { span, .. }
+            | Expression::Propagate { span, .. }
+            | Expression::Lambda { span, .. }
+            | Expression::Range { span, .. }
+            | Expression::Spawn { span, .. }
+            | Expression::If { span, .. }
+            | Expression::Match { span, .. }
+            | Expression::StructLiteral { span, .. }
+            | Expression::TupleLiteral { span, .. }
+            | Expression::VecLiteral { span, .. }
+            | Expression::SetLiteral { span, .. }
+            | Expression::MapLiteral { span, .. } => Some(span),
+        }
+    }
+}
+
+impl BinaryOperator {
+    /// Whether this operator assigns to its left operand
+    pub fn is_assignment(&self) -> bool {
+        *self == BinaryOperator::Assign || self.compound_operator().is_some()
+    }
+
+    /// The operator a compound assignment such as `+=` applies before assigning
+    pub fn compound_operator(&self) -> Option<BinaryOperator> {
+        match self {
+            BinaryOperator::AddAssign => Some(BinaryOperator::Add),
+            BinaryOperator::SubtractAssign => Some(BinaryOperator::Subtract),
+            BinaryOperator::MultiplyAssign => Some(BinaryOperator::Multiply),
+            BinaryOperator::DivideAssign => Some(BinaryOperator::Divide),
+            BinaryOperator::ModuloAssign => Some(BinaryOperator::Modulo),
+            BinaryOperator::PowerAssign => Some(BinaryOperator::Power),
+            BinaryOperator::ShiftLeftAssign => Some(BinaryOperator::ShiftLeft),
+            BinaryOperator::ShiftRightAssign => Some(BinaryOperator::ShiftRight),
+            BinaryOperator::BitAndAssign => Some(BinaryOperator::BitAnd),
+            BinaryOperator::BitOrAssign => Some(BinaryOperator::BitOr),
+            BinaryOperator::BitXorAssign => Some(BinaryOperator::BitXor),
+            _ => None,
+        }
+    }
+
+    /// Whether swapping the operands leaves the result unchanged
+    pub fn is_commutative(&self) -> bool {
+        matches!(
+            self,
+            BinaryOperator::Add
+                | BinaryOperator::Multiply
+                | BinaryOperator::Equal
+                | BinaryOperator::NotEqual
+                | BinaryOperator::BitAnd
+                | BinaryOperator::BitXor
+                | BinaryOperator::BitOr
+                | BinaryOperator::And
+                | BinaryOperator::Or
+        )
+    }
 }
 
 /// Unary operators
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum UnaryOperator {
     Negate,
     Not,
@@ -119,7 +203,7 @@ pub enum UnaryOperator {
 }
 
 /// Literal values
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Literal {
     Int(i64),
     Float(f64),
@@ -130,14 +214,14 @@ pub enum Literal {
     None,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum FStringPart {
     Text(String),
     Expression(Box<Expression>),
 }
 
 /// Field initialization
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FieldInit {
     pub name: String,
     pub value: Expression,
@@ -145,7 +229,7 @@ pub struct FieldInit {
 }
 
 /// Map entry
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MapEntry {
     pub key: Expression,
     pub value: Expression,
@@ -187,6 +271,17 @@ impl fmt::Display for BinaryOperator {
             BinaryOperator::And => write!(f, "and"),
             BinaryOperator::Or => write!(f, "or"),
             BinaryOperator::Assign => write!(f, "="),
+            BinaryOperator::AddAssign => write!(f, "+="),
+            BinaryOperator::SubtractAssign => write!(f, "-="),
+            BinaryOperator::MultiplyAssign => write!(f, "*="),
+            BinaryOperator::DivideAssign => write!(f, "/="),
+            BinaryOperator::ModuloAssign => write!(f, "%="),
+            BinaryOperator::PowerAssign => write!(f, "**="),
+            BinaryOperator::ShiftLeftAssign => write!(f, "<<="),
+            BinaryOperator::ShiftRightAssign => write!(f, ">>="),
+            BinaryOperator::BitAndAssign => write!(f, "&="),
+            BinaryOperator::BitOrAssign => write!(f, "|="),
+            BinaryOperator::BitXorAssign => write!(f, "^="),
         }
     }
 }
@@ -200,3 +295,21 @@ impl fmt::Display for UnaryOperator {
         }
     }
 }
+
+impl UnaryOperator {
+    /// Name under which an `impl` method overloading this operator is stored,
+    /// kept apart from the binary operator with the same symbol
+    pub fn method_key(&self) -> String {
+        format!("unary {}", self)
+    }
+
+    /// Storage name for an `impl` method: an operator method named `-`, `!` or
+    /// `~` that takes no arguments overloads the unary operator
+    pub fn impl_method_key(name: &str, arg_count: usize) -> String {
+        if arg_count == 0 && matches!(name, "-" | "!" | "~") {
+            format!("unary {}", name)
+        } else {
+            name.to_string()
+        }
+    }
+}
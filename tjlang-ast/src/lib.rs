@@ -2,6 +2,7 @@
 //!
 //! Abstract Syntax Tree nodes for TJLang language constructs.
 
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 // Re-export SourceSpan from codespan to avoid duplication
@@ -9,21 +10,21 @@ pub use codespan::FileId;
 use codespan::Span;
 
 /// A source span representing a location in source code
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SourceSpan {
     pub file_id: FileId,
     pub span: Span,
 }
 
 /// A complete TJLang program
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Program {
     pub units: Vec<ProgramUnit>,
     pub span: SourceSpan,
 }
 
 /// Top-level program units
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ProgramUnit {
     Module(ModuleDecl),
     Import(ImportDecl),
@@ -34,14 +35,14 @@ pub enum ProgramUnit {
 }
 
 /// Module declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModuleDecl {
     pub name: String,
     pub span: SourceSpan,
 }
 
 /// Import declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ImportDecl {
     Simple {
         module: QualifiedName,
@@ -56,14 +57,14 @@ pub enum ImportDecl {
 }
 
 /// Qualified name (e.g., "std.collections.Map")
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QualifiedName {
     pub parts: Vec<String>,
     pub span: SourceSpan,
 }
 
 /// Export declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ExportDecl {
     Declaration(Declaration),
     Identifier(String),
@@ -71,7 +72,7 @@ pub enum ExportDecl {
 }
 
 /// All possible declarations
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Declaration {
     Function(FunctionDecl),
     Interface(InterfaceDecl),
@@ -84,7 +85,7 @@ pub enum Declaration {
 }
 
 /// Function declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FunctionDecl {
     pub name: String,
     pub generic_params: Vec<GenericParam>,
@@ -95,7 +96,7 @@ pub struct FunctionDecl {
 }
 
 /// Generic parameter with trait bounds
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GenericParam {
     pub name: String,
     pub bounds: Vec<String>,
@@ -103,7 +104,7 @@ pub struct GenericParam {
 }
 
 /// Function parameter
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Parameter {
     pub name: String,
     pub param_type: Type,
@@ -112,7 +113,7 @@ pub struct Parameter {
 }
 
 /// Type system
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Type {
     Primitive(PrimitiveType),
     Identifier(String),
@@ -159,7 +160,7 @@ pub enum Type {
 }
 
 /// Primitive types
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PrimitiveType {
     Int,
     Float,
@@ -174,7 +175,7 @@ mod statements;
 
 // Custom type declarations
 /// Type alias declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TypeDecl {
     pub name: String,
     pub type_alias: Type,
@@ -182,7 +183,7 @@ pub struct TypeDecl {
 }
 
 /// Struct declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StructDecl {
     pub name: String,
     pub fields: Vec<FieldDecl>,
@@ -190,7 +191,7 @@ pub struct StructDecl {
 }
 
 /// Field declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FieldDecl {
     pub name: String,
     pub field_type: Type,
@@ -198,7 +199,7 @@ pub struct FieldDecl {
 }
 
 /// Enum declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnumDecl {
     pub name: String,
     pub type_params: Vec<String>,
@@ -207,7 +208,7 @@ pub struct EnumDecl {
 }
 
 /// Enum variant
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnumVariant {
     pub name: String,
     pub fields: Vec<Type>,
@@ -215,7 +216,7 @@ pub struct EnumVariant {
 }
 
 /// Interface declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InterfaceDecl {
     pub name: String,
     pub extends: Vec<String>,
@@ -224,7 +225,7 @@ pub struct InterfaceDecl {
 }
 
 /// Method signature
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MethodSig {
     pub name: String,
     pub params: Vec<Parameter>,
@@ -233,7 +234,7 @@ pub struct MethodSig {
 }
 
 /// Implementation block
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImplBlock {
     pub trait_name: String,
     pub type_name: String,
@@ -242,7 +243,7 @@ pub struct ImplBlock {
 }
 
 /// Method declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MethodDecl {
     pub name: String,
     pub params: Vec<Parameter>,
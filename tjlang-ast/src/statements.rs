@@ -3,7 +3,7 @@
 use super::*;
 
 /// Interface declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InterfaceDecl {
     pub name: String,
     pub extends: Vec<String>,
@@ -12,7 +12,7 @@ pub struct InterfaceDecl {
 }
 
 /// Method signature
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MethodSignature {
     pub name: String,
     pub params: Vec<Parameter>,
@@ -21,7 +21,7 @@ pub struct MethodSignature {
 }
 
 /// Type declaration (type alias)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TypeDecl {
     pub name: String,
     pub type_def: Type,
@@ -29,7 +29,7 @@ pub struct TypeDecl {
 }
 
 /// Enum declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnumDecl {
     pub name: String,
     pub type_params: Vec<String>,
@@ -38,7 +38,7 @@ pub struct EnumDecl {
 }
 
 /// Enum variant
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnumVariant {
     pub name: String,
     pub fields: Vec<Type>,
@@ -46,7 +46,7 @@ pub struct EnumVariant {
 }
 
 /// Struct declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StructDecl {
     pub name: String,
     pub fields: Vec<FieldDecl>,
@@ -54,7 +54,7 @@ pub struct StructDecl {
 }
 
 /// Field declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FieldDecl {
     pub name: String,
     pub field_type: Type,
@@ -62,7 +62,7 @@ pub struct FieldDecl {
 }
 
 /// Variable declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VariableDecl {
     pub name: String,
     pub var_type: Type,
@@ -71,7 +71,7 @@ pub struct VariableDecl {
 }
 
 /// Implementation block
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImplBlock {
     pub trait_name: Option<String>,
     pub type_name: String,
@@ -80,7 +80,7 @@ pub struct ImplBlock {
 }
 
 /// Method declaration
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MethodDecl {
     pub name: String,
     pub params: Vec<Parameter>,
@@ -90,14 +90,14 @@ pub struct MethodDecl {
 }
 
 /// Block of statements
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Block {
     pub statements: Vec<Statement>,
     pub span: SourceSpan,
 }
 
 /// All possible statements
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Statement {
     Variable(VariableDecl),
     Expression(Expression),
@@ -111,11 +111,35 @@ pub enum Statement {
     Continue(ContinueStatement),
     Pass(PassStatement),
     Raise(RaiseStatement),
+    Try(TryStatement),
     Block(Block),
 }
 
+impl Statement {
+    /// Source location of the statement
+    pub fn span(&self) -> Option<&SourceSpan> {
+        match self {
+            Statement::Variable(decl) => Some(&decl.span),
+            Statement::Expression(expr) => expr.span(),
+            Statement::If(stmt) => Some(&stmt.span),
+            Statement::While(stmt) => Some(&stmt.span),
+            Statement::DoWhile(stmt) => Some(&stmt.span),
+            Statement::For(ForStatement::ForEach { span, .. })
+            | Statement::For(ForStatement::CStyle { span, .. }) => Some(span),
+            Statement::Match(stmt) => Some(&stmt.span),
+            Statement::Return(stmt) => Some(&stmt.span),
+            Statement::Break(stmt) => Some(&stmt.span),
+            Statement::Continue(stmt) => Some(&stmt.span),
+            Statement::Pass(stmt) => Some(&stmt.span),
+            Statement::Raise(stmt) => Some(&stmt.span),
+            Statement::Try(stmt) => Some(&stmt.span),
+            Statement::Block(block) => Some(&block.span),
+        }
+    }
+}
+
 /// If statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IfStatement {
     pub condition: Expression,
     pub then_block: Block,
@@ -125,7 +149,7 @@ pub struct IfStatement {
 }
 
 /// Elif branch
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ElifBranch {
     pub condition: Expression,
     pub block: Block,
@@ -133,7 +157,7 @@ pub struct ElifBranch {
 }
 
 /// While statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WhileStatement {
     pub condition: Expression,
     pub body: Block,
@@ -141,7 +165,7 @@ pub struct WhileStatement {
 }
 
 /// Do-while statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DoWhileStatement {
     pub body: Block,
     pub condition: Expression,
@@ -149,7 +173,7 @@ pub struct DoWhileStatement {
 }
 
 /// For statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ForStatement {
     ForEach {
         var_name: String,
@@ -168,7 +192,7 @@ pub enum ForStatement {
 }
 
 /// Match statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MatchStatement {
     pub expression: Expression,
     pub arms: Vec<MatchArm>,
@@ -176,7 +200,7 @@ pub struct MatchStatement {
 }
 
 /// Match arm
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MatchArm {
     pub pattern: Pattern,
     pub guard: Option<Expression>,
@@ -185,7 +209,7 @@ pub struct MatchArm {
 }
 
 /// Pattern matching
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Pattern {
     Literal(Literal),
     Variable {
@@ -216,33 +240,49 @@ pub enum Pattern {
 }
 
 /// Return statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReturnStatement {
     pub value: Option<Expression>,
     pub span: SourceSpan,
 }
 
 /// Break statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BreakStatement {
     pub span: SourceSpan,
 }
 
 /// Continue statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ContinueStatement {
     pub span: SourceSpan,
 }
 
 /// Pass statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PassStatement {
     pub span: SourceSpan,
 }
 
 /// Raise statement
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RaiseStatement {
     pub value: Expression,
     pub span: SourceSpan,
 }
+
+/// Try statement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TryStatement {
+    pub body: Block,
+    pub handlers: Vec<ExceptClause>,
+    pub span: SourceSpan,
+}
+
+/// Except clause of a try statement; without a pattern it catches any raised value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExceptClause {
+    pub pattern: Option<Pattern>,
+    pub body: Block,
+    pub span: SourceSpan,
+}
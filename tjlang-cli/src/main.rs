@@ -2,21 +2,37 @@
 //!
 //! Command-line interface for the TJLang advanced interpreter.
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use codespan_reporting::diagnostic::Severity;
 use codespan_reporting::term::{
     self,
     termcolor::{ColorChoice, StandardStream},
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tjlang_ast::Program;
 use tjlang_diagnostics::debug_println;
 use tjlang_diagnostics::utils::debug;
 use tjlang_diagnostics::{DiagnosticCollection, ErrorCode, SourceSpan, TJLangDiagnostic};
 use tjlang_lexer::lex;
 use tjlang_parser::parse;
-use tjlang_runtime::Interpreter;
+use tjlang_runtime::compiled::CompiledProgram;
+use tjlang_runtime::interpreter::{RuntimeError, StackFrame, DEFAULT_MAX_CALL_DEPTH};
+use tjlang_runtime::stdlib_integration::{stdlib_signatures, StdlibSignature};
+use tjlang_runtime::{Backend, IntOverflow, Interpreter, Value};
 use tjlang_analyzer::{AnalysisPipeline, RuleConfig};
 
+/// Stack reserved for the interpreter thread before any calls are made
+const INTERPRETER_BASE_STACK: usize = 16 * 1024 * 1024;
+/// Stack reserved for each nested call, about twice what a recursive call was
+/// measured to use (roughly 115 KiB unoptimized and 12 KiB optimized)
+const INTERPRETER_STACK_PER_CALL: usize = if cfg!(debug_assertions) { 256 * 1024 } else { 32 * 1024 };
+/// Largest stack the interpreter thread may reserve
+const MAX_INTERPRETER_STACK: usize = 1024 * 1024 * 1024;
+/// Call frames shown in a runtime error's stack trace
+const MAX_TRACE_FRAMES: usize = 10;
+/// Extension of programs compiled with `tjlang build`
+const COMPILED_EXTENSION: &str = "tjc";
+
 /// TJLang - Advanced Programming Language Interpreter
 #[derive(Parser)]
 #[command(name = "tjlang")]
@@ -25,13 +41,17 @@ use tjlang_analyzer::{AnalysisPipeline, RuleConfig};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Seed the random number generator of programs that run, so that every run
+    /// makes the same random choices
+    #[arg(long, global = true)]
+    seed: Option<u64>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Run a TJLang program
+    /// Run a TJLang program, or a program compiled with `build`
     Run {
-        /// Path to the TJLang file
+        /// Path to the TJLang file or compiled `.tjc` file
         file: PathBuf,
         /// Enable debug mode
         #[arg(short, long)]
@@ -45,6 +65,37 @@ enum Commands {
         /// Use strict analysis rules
         #[arg(long)]
         strict: bool,
+        /// Maximum depth of nested function calls before a stack overflow error
+        #[arg(long, default_value_t = DEFAULT_MAX_CALL_DEPTH)]
+        max_depth: usize,
+        /// How to execute the program; compiled programs always run on the VM
+        #[arg(long, value_enum, default_value_t = BackendArg::Interp)]
+        backend: BackendArg,
+        /// What happens when integer arithmetic overflows a 64-bit int
+        #[arg(long, value_enum, default_value_t = IntOverflowArg::Error)]
+        int_overflow: IntOverflowArg,
+        /// Skip checking declared variable, parameter and return types while the program runs
+        #[arg(long)]
+        no_runtime_typecheck: bool,
+    },
+    /// Parse, check and compile a TJLang program ahead of time
+    Build {
+        /// Path to the TJLang file
+        file: PathBuf,
+        /// Where to write the compiled program; defaults to the source path with a `.tjc` extension
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Use specific configuration file
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Use strict analysis rules
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Show documentation for the standard library
+    Doc {
+        /// A module such as `MATH`, or a function such as `MATH.sqrt`; lists the modules when omitted
+        topic: Option<String>,
     },
     /// Configure analysis rules and settings
     Config {
@@ -53,6 +104,61 @@ enum Commands {
     },
 }
 
+/// Execution backends selectable with `--backend`
+#[derive(Clone, Copy, ValueEnum)]
+enum BackendArg {
+    /// Tree-walking interpreter
+    Interp,
+    /// Bytecode virtual machine
+    Vm,
+}
+
+impl From<BackendArg> for Backend {
+    fn from(backend: BackendArg) -> Self {
+        match backend {
+            BackendArg::Interp => Backend::Interpreter,
+            BackendArg::Vm => Backend::Vm,
+        }
+    }
+}
+
+/// Integer overflow policies selectable with `--int-overflow`
+#[derive(Clone, Copy, ValueEnum)]
+enum IntOverflowArg {
+    /// Stop with a runtime error
+    Error,
+    /// Continue with arbitrary-precision integers
+    Promote,
+}
+
+impl From<IntOverflowArg> for IntOverflow {
+    fn from(int_overflow: IntOverflowArg) -> Self {
+        match int_overflow {
+            IntOverflowArg::Error => IntOverflow::Error,
+            IntOverflowArg::Promote => IntOverflow::Promote,
+        }
+    }
+}
+
+/// Interpreter settings given on the `run` command line
+struct RunOptions {
+    max_depth: usize,
+    int_overflow: IntOverflow,
+    runtime_typecheck: bool,
+    seed: Option<u64>,
+}
+
+impl RunOptions {
+    fn configure(&self, interpreter: &mut Interpreter) {
+        interpreter.set_max_call_depth(self.max_depth);
+        interpreter.set_int_overflow(self.int_overflow);
+        interpreter.set_runtime_typecheck(self.runtime_typecheck);
+        if let Some(seed) = self.seed {
+            interpreter.set_random_seed(seed);
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum ConfigCommands {
     /// List all available rules and their status
@@ -115,8 +221,33 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             verbose,
             config,
             strict,
+            max_depth,
+            backend,
+            int_overflow,
+            no_runtime_typecheck,
+        } => {
+            let options = RunOptions {
+                max_depth,
+                int_overflow: int_overflow.into(),
+                runtime_typecheck: !no_runtime_typecheck,
+                seed: cli.seed,
+            };
+            if file.extension().is_some_and(|ext| ext == COMPILED_EXTENSION) {
+                run_compiled(&file, &options)?;
+            } else {
+                run_program(&file, debug, verbose, config, strict, backend.into(), &options)?;
+            }
+        }
+        Commands::Build {
+            file,
+            output,
+            config,
+            strict,
         } => {
-            run_program(&file, debug, verbose, config, strict)?;
+            build_program(&file, output, config, strict)?;
+        }
+        Commands::Doc { topic } => {
+            show_docs(topic.as_deref());
         }
         Commands::Config { command } => {
             handle_config_command(command)?;
@@ -128,11 +259,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 /// Run a TJLang program
 fn run_program(
-    file: &PathBuf,
+    file: &Path,
     debug: bool,
     verbose: bool,
     config_file: Option<PathBuf>,
     strict: bool,
+    backend: Backend,
+    options: &RunOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     debug_println!(" Running TJLang program: {}", file.display());
 
@@ -142,8 +275,117 @@ fn run_program(
         debug_println!(" Verbose mode: {}", verbose);
     }
 
-    // Read the source file with proper error handling
-    let source = match std::fs::read_to_string(file) {
+    let source = read_file(file, |file| std::fs::read_to_string(file));
+
+    if verbose {
+        debug_println!(" Source code ({} bytes):", source.len());
+        debug_println!("{}", source);
+        debug_println!("---");
+    }
+
+    // Create a file ID for the source
+    use codespan::Files;
+    let mut files: Files<String> = Files::new();
+    let file_id = files.add(file.to_string_lossy().to_string(), source.clone());
+
+    let config = load_configuration(config_file, strict)?;
+    let ast = check_program(file, &source, &files, file_id, debug, verbose, config)?;
+
+    if debug {
+        debug_println!(" AST:");
+        debug_println!("{:#?}", ast);
+    }
+
+    execute(file, files, options, true, |interpreter| {
+        if verbose {
+            debug_println!(" Running interpreter...");
+        }
+        interpreter.set_backend(backend);
+        debug_println!("[DEBUG] AST units: {}", ast.units.len());
+        for (i, unit) in ast.units.iter().enumerate() {
+            debug_println!("[DEBUG] Unit {}: {:?}", i, std::mem::discriminant(unit));
+        }
+        interpreter.interpret_program(&ast)
+    })
+}
+
+/// Run a program compiled with `tjlang build`
+fn run_compiled(file: &Path, options: &RunOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = read_file(file, |file| std::fs::read(file));
+    let compiled = match CompiledProgram::from_bytes(&bytes) {
+        Ok(compiled) => compiled,
+        Err(e) => {
+            eprintln!("Error: {}: {}", file.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    // Errors are shown against the source only if it is still the one the program was built from
+    let source = match std::fs::read_to_string(&compiled.source_path) {
+        Ok(source) if compiled.matches_source(&source) => Some(source),
+        Ok(_) => {
+            eprintln!(
+                "Warning: {} has changed since {} was built; rebuild it to see source in errors",
+                compiled.source_path,
+                file.display()
+            );
+            None
+        }
+        Err(_) => None,
+    };
+
+    // The source is the first file registered, as it was when building, so spans refer to it
+    let mut files: codespan::Files<String> = codespan::Files::new();
+    let has_source = source.is_some();
+    let file_id = files.add(compiled.source_path.clone(), source.unwrap_or_default());
+    if file_id != compiled.file_id {
+        eprintln!("Error: {}: spans refer to an unknown source file", file.display());
+        std::process::exit(1);
+    }
+
+    execute(file, files, options, has_source, |interpreter| interpreter.interpret_compiled(&compiled))
+}
+
+/// Compile a program and save it, with its syntax tree, as a `.tjc` file
+fn build_program(
+    file: &Path,
+    output: Option<PathBuf>,
+    config_file: Option<PathBuf>,
+    strict: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = read_file(file, |file| std::fs::read_to_string(file));
+
+    use codespan::Files;
+    let mut files: Files<String> = Files::new();
+    let file_id = files.add(file.to_string_lossy().to_string(), source.clone());
+
+    let config = load_configuration(config_file, strict)?;
+    let ast = check_program(file, &source, &files, file_id, false, false, config)?;
+
+    let compiled = CompiledProgram::compile(ast, file.to_string_lossy().to_string(), &source, file_id);
+    let output = output.unwrap_or_else(|| file.with_extension(COMPILED_EXTENSION));
+    std::fs::write(&output, compiled.to_bytes()?)?;
+    Ok(())
+}
+
+/// Report a runtime error in a compiled program whose source is not available
+fn report_without_source(file: &Path, error: &RuntimeError) {
+    eprintln!("\nRuntime Error in {}: {}", file.display(), error.message);
+    eprintln!("  at bytes {}..{} of the source", error.span.start().to_usize(), error.span.end().to_usize());
+    for frame in error.stack.iter().take(MAX_TRACE_FRAMES) {
+        eprintln!(
+            "  in '{}' called at bytes {}..{}",
+            frame.function,
+            frame.span.start().to_usize(),
+            frame.span.end().to_usize()
+        );
+    }
+}
+
+
+/// Read a file, reporting why it could not be read and exiting if it cannot
+fn read_file<T>(file: &Path, read: impl FnOnce(&Path) -> std::io::Result<T>) -> T {
+    match read(file) {
         Ok(content) => content,
         Err(e) => {
             // Use diagnostic system for file errors
@@ -193,24 +435,24 @@ fn run_program(
             display_diagnostics(&files, &diagnostics).ok();
             std::process::exit(1);
         }
-    };
-
-    if verbose {
-        debug_println!(" Source code ({} bytes):", source.len());
-        debug_println!("{}", source);
-        debug_println!("---");
     }
+}
 
-    // Create a file ID for the source
-    use codespan::Files;
-    let mut files: Files<String> = Files::new();
-    let file_id = files.add(file.to_string_lossy().to_string(), source.clone());
-
+/// Parse a program and run static analysis on it, exiting with diagnostics if either fails
+fn check_program(
+    file: &Path,
+    source: &str,
+    files: &codespan::Files<String>,
+    file_id: codespan::FileId,
+    debug: bool,
+    verbose: bool,
+    config: RuleConfig,
+) -> Result<Program, Box<dyn std::error::Error>> {
     // Lex the source
     if verbose {
         debug_println!(" Lexing source...");
     }
-    let (tokens, diagnostics) = lex(&source, file_id);
+    let (tokens, diagnostics) = lex(source, file_id);
 
     if debug {
         debug_println!(" Tokens:");
@@ -223,7 +465,7 @@ fn run_program(
     if verbose {
         debug_println!(" Parsing tokens...");
     }
-    let (ast, parse_diagnostics) = match parse(&source, file_id) {
+    let (ast, parse_diagnostics) = match parse(source, file_id) {
         Ok((ast, diagnostics)) => {
             debug_println!("[DEBUG] Parse successful! AST units: {}", ast.units.len());
             for (i, unit) in ast.units.iter().enumerate() {
@@ -238,7 +480,7 @@ fn run_program(
             if !diagnostics.is_empty() {
                 eprintln!("Parse Error: Failed to parse {}", file.display());
                 eprintln!();
-                display_diagnostics(&files, &diagnostics)?;
+                display_diagnostics(files, &diagnostics)?;
             } else {
                 eprintln!(
                     "Parse Error: Failed to parse {} (no diagnostic information available)",
@@ -259,11 +501,9 @@ fn run_program(
         debug_println!(" Running static analysis...");
     }
 
-    // Load configuration
-    let config = load_configuration(config_file, strict)?;
     let pipeline = AnalysisPipeline::with_config(config);
 
-    let analysis_result = pipeline.analyze(&source, file_id);
+    let analysis_result = pipeline.analyze(source, file_id);
 
     if debug {
         debug_println!(" Analysis completed:");
@@ -276,7 +516,7 @@ fn run_program(
     if !analysis_result.diagnostics.is_empty() {
         eprintln!("\nStatic Analysis Errors in {}:", file.display());
         eprintln!();
-        display_diagnostics(&files, &analysis_result.diagnostics)?;
+        display_diagnostics(files, &analysis_result.diagnostics)?;
 
         // Count errors (not warnings)
         let error_count = analysis_result
@@ -294,61 +534,181 @@ fn run_program(
         }
     }
 
-    if debug {
-        debug_println!(" AST:");
-        debug_println!("{:#?}", ast);
-    }
+    Ok(ast)
+}
 
-    // Interpret the program
-    if verbose {
-        debug_println!(" Running interpreter...");
-    }
-    debug_println!("[DEBUG] Creating interpreter...");
-    let mut interpreter = Interpreter::new();
-    debug_println!("[DEBUG] Interpreter created successfully");
-    debug_println!("[DEBUG] Starting program interpretation...");
-    debug_println!("[DEBUG] AST units: {}", ast.units.len());
-    for (i, unit) in ast.units.iter().enumerate() {
-        debug_println!("[DEBUG] Unit {}: {:?}", i, std::mem::discriminant(unit));
+/// Run a program on a new interpreter and report any runtime error against the
+/// sources in `files`
+fn execute(
+    file: &Path,
+    files: codespan::Files<String>,
+    options: &RunOptions,
+    has_source: bool,
+    run: impl FnOnce(&mut Interpreter) -> Result<Value, RuntimeError> + Send,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Deep recursion needs far more stack than the main thread has, so the
+    // program runs on a thread sized for the call depth limit. Calls that nest
+    // many statements deep can need more; the interpreter grows into it.
+    let stack_size = options
+        .max_depth
+        .saturating_mul(INTERPRETER_STACK_PER_CALL)
+        .saturating_add(INTERPRETER_BASE_STACK);
+    if stack_size > MAX_INTERPRETER_STACK {
+        eprintln!(
+            "Error: --max-depth {} needs more stack than the interpreter can reserve; the largest supported depth is {}",
+            options.max_depth,
+            (MAX_INTERPRETER_STACK - INTERPRETER_BASE_STACK) / INTERPRETER_STACK_PER_CALL
+        );
+        std::process::exit(1);
     }
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .name("tjlang-interpreter".to_string())
+            .stack_size(stack_size)
+            .spawn_scoped(scope, || -> Result<(), String> {
+                debug_println!("[DEBUG] Creating interpreter...");
+                let mut interpreter = Interpreter::new();
+                options.configure(&mut interpreter);
+                // Imports resolve next to the entry file; module sources join its file database
+                let module_root = match file.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                    _ => PathBuf::from("."),
+                };
+                interpreter.module_system_mut().add_search_path(module_root);
+                interpreter.module_system_mut().set_files(files);
+                debug_println!("[DEBUG] Interpreter created successfully");
 
-    debug_println!("[DEBUG] About to call interpret_program...");
+                let result = match run(&mut interpreter) {
+                    Ok(result) => {
+                        debug_println!("[DEBUG] Program completed successfully!");
+                        debug_println!(" Result type: {:?}", std::mem::discriminant(&result));
+                        debug_println!(" Result: {}", result.to_string());
+                        result
+                    }
+                    Err(e) => {
+                        debug_println!(" Program execution failed: {}", e);
 
-    let result = match interpreter.interpret_program(&ast) {
-        Ok(result) => {
-            debug_println!("[DEBUG] Program completed successfully!");
-            debug_println!(" Result type: {:?}", std::mem::discriminant(&result));
-            debug_println!(" Result: {}", result.to_string());
-            result
-        }
-        Err(e) => {
-            debug_println!(" Program execution failed: {}", e);
-
-            // Convert runtime error to diagnostic with proper location tracking
-            let span = SourceSpan::new(e.file_id, e.span);
-            let diagnostic = TJLangDiagnostic::new(
-                ErrorCode::RuntimeValueError,
-                Severity::Error,
-                format!("Runtime Error: {}", e.message),
-                span,
-            )
-            .with_note("The program failed during execution.".to_string())
-            .with_note("Run with --debug flag for more detailed information.".to_string());
+                        if !has_source {
+                            report_without_source(file, &e);
+                            std::process::exit(1);
+                        }
 
-            let mut diagnostics = DiagnosticCollection::new();
-            diagnostics.add(diagnostic);
+                        // Convert runtime error to diagnostic with proper location tracking
+                        let span = SourceSpan::new(e.file_id, e.span);
+                        let code = if e.raised.is_some() {
+                            ErrorCode::RuntimeUncaughtException
+                        } else {
+                            ErrorCode::RuntimeValueError
+                        };
+                        let mut diagnostic = TJLangDiagnostic::new(
+                            code,
+                            Severity::Error,
+                            format!("Runtime Error: {}", e.message),
+                            span,
+                        );
 
-            eprintln!("\nRuntime Error in {}:", file.display());
-            display_diagnostics(&files, &diagnostics)?;
-            std::process::exit(1);
-        }
-    };
+                        // Show the calls the error unwound through, innermost first. Recursive
+                        // calls from the same site are shown once with a repeat count.
+                        let files = interpreter.module_system().files();
+                        let mut groups: Vec<(&StackFrame, usize)> = Vec::new();
+                        for frame in &e.stack {
+                            match groups.last_mut() {
+                                Some((last, count))
+                                    if last.function == frame.function && last.span == frame.span =>
+                                {
+                                    *count += 1
+                                }
+                                _ => groups.push((frame, 1)),
+                            }
+                        }
+                        for (frame, count) in groups.iter().take(MAX_TRACE_FRAMES) {
+                            let repeated = if *count > 1 {
+                                format!(" ({} times)", count)
+                            } else {
+                                String::new()
+                            };
+                            diagnostic = diagnostic
+                                .with_secondary_label(
+                                    SourceSpan::new(frame.file_id, frame.span),
+                                    format!("in call to {}({})", frame.function, frame.arguments),
+                                )
+                                .with_note(format!(
+                                    "in '{}' called from {}{}",
+                                    frame.function,
+                                    describe_location(files, frame.file_id, frame.span),
+                                    repeated
+                                ));
+                        }
+                        if groups.len() > MAX_TRACE_FRAMES {
+                            let hidden: usize = groups[MAX_TRACE_FRAMES..].iter().map(|(_, count)| count).sum();
+                            diagnostic = diagnostic.with_note(format!("... and {} more calls", hidden));
+                        }
+
+                        let diagnostic = diagnostic
+                            .with_note("The program failed during execution.".to_string())
+                            .with_note("Run with --debug flag for more detailed information.".to_string());
+
+                        let mut diagnostics = DiagnosticCollection::new();
+                        diagnostics.add(diagnostic);
 
-    debug_println!("[DEBUG] After interpret_program call");
+                        eprintln!("\nRuntime Error in {}:", file.display());
+                        display_diagnostics(interpreter.module_system().files(), &diagnostics)
+                            .map_err(|e| e.to_string())?;
+                        std::process::exit(1);
+                    }
+                };
+
+                debug_println!("[DEBUG] After interpret_program call");
+                Ok(())
+            })
+            .map_err(|e| e.to_string())?
+            .join()
+            .map_err(|_| "The interpreter thread panicked".to_string())?
+    })?;
 
     Ok(())
 }
 
+/// Print standard library documentation from the stdlib binding signatures
+fn show_docs(topic: Option<&str>) {
+    let signatures = stdlib_signatures();
+    let Some(topic) = topic else {
+        let mut modules: Vec<&str> = signatures.iter().map(|signature| signature.module).collect();
+        modules.sort_unstable();
+        modules.dedup();
+        println!("Standard library modules:");
+        println!();
+        for module in modules {
+            let count = signatures.iter().filter(|signature| signature.module == module).count();
+            println!("  {:<12} {} function(s)", module, count);
+        }
+        println!();
+        println!("Use 'tjlang doc MODULE' to list a module's functions, or 'tjlang doc MODULE.function' for one function");
+        return;
+    };
+
+    let (module, function) = match topic.split_once('.') {
+        Some((module, function)) => (module.to_uppercase(), Some(function)),
+        None => (topic.to_uppercase(), None),
+    };
+    let matching: Vec<&StdlibSignature> = signatures
+        .iter()
+        .filter(|signature| signature.module == module && function.is_none_or(|name| signature.name == name))
+        .collect();
+    if matching.is_empty() {
+        eprintln!("Error: No standard library module or function named '{}'", topic);
+        eprintln!("Use 'tjlang doc' to see available modules");
+        std::process::exit(1);
+    }
+    for signature in matching {
+        println!("{}", signature);
+        for line in signature.doc().lines() {
+            println!("    {}", line);
+        }
+        println!();
+    }
+}
+
 /// Handle configuration commands
 fn handle_config_command(command: ConfigCommands) -> Result<(), Box<dyn std::error::Error>> {
     match command {
@@ -931,6 +1291,19 @@ fn validate_configuration_file(file: &PathBuf) -> Result<(), Box<dyn std::error:
     Ok(())
 }
 
+/// Format a span as `file:line:column` for diagnostic notes
+fn describe_location(files: &codespan::Files<String>, file_id: codespan::FileId, span: codespan::Span) -> String {
+    match files.location(file_id, span.start()) {
+        Ok(location) => format!(
+            "{}:{}:{}",
+            files.name(file_id).to_string_lossy(),
+            location.line.number(),
+            location.column.number()
+        ),
+        Err(_) => "unknown location".to_string(),
+    }
+}
+
 /// Display diagnostics using codespan-reporting
 fn display_diagnostics(
     files: &codespan::Files<String>,
@@ -0,0 +1,115 @@
+//! Differential tests: every example program must behave the same on the
+//! tree-walking interpreter, on the bytecode VM and after being compiled to a
+//! `.tjc` file.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Directories of example programs, relative to the repository root
+const PROGRAM_DIRS: &[&str] = &["test_files", "docs/demos"];
+
+fn repository_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+fn programs() -> Vec<PathBuf> {
+    let root = repository_root();
+    let mut programs = Vec::new();
+    for dir in PROGRAM_DIRS {
+        for entry in std::fs::read_dir(root.join(dir)).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "tj") {
+                programs.push(path);
+            }
+        }
+    }
+    programs.sort();
+    programs
+}
+
+fn tjlang(args: &[&OsStr]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tjlang"))
+        .current_dir(repository_root())
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+fn run(program: &Path, backend: &str) -> Output {
+    tjlang(&["run".as_ref(), program.as_ref(), "--backend".as_ref(), backend.as_ref()])
+}
+
+#[test]
+fn test_backends_agree_on_every_program() {
+    let programs = programs();
+    assert!(!programs.is_empty(), "no example programs found");
+
+    let mut mismatches = Vec::new();
+    for program in &programs {
+        let interpreted = run(program, "interp");
+        let compiled = run(program, "vm");
+        if interpreted.status.code() != compiled.status.code()
+            || interpreted.stdout != compiled.stdout
+            || interpreted.stderr != compiled.stderr
+        {
+            mismatches.push(format!(
+                "{}\n--- interp ({:?}) ---\n{}{}\n--- vm ({:?}) ---\n{}{}",
+                program.display(),
+                interpreted.status.code(),
+                String::from_utf8_lossy(&interpreted.stdout),
+                String::from_utf8_lossy(&interpreted.stderr),
+                compiled.status.code(),
+                String::from_utf8_lossy(&compiled.stdout),
+                String::from_utf8_lossy(&compiled.stderr),
+            ));
+        }
+    }
+    assert!(mismatches.is_empty(), "backends disagree:\n{}", mismatches.join("\n"));
+}
+
+#[test]
+fn test_vm_backend_runs_backend_test() {
+    let output = run(&repository_root().join("test_files/backend_test.tj"), "vm");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("fib(15) = 610"));
+    assert!(stdout.contains("error: cannot divide by zero"));
+    assert!(stdout.contains("squares = [100, 1, 4, 9, 16, 25]"));
+}
+
+#[test]
+fn test_compiled_programs_match_interpreter() {
+    let output_dir = std::env::temp_dir().join(format!("tjlang-build-{}", std::process::id()));
+    std::fs::create_dir_all(&output_dir).unwrap();
+
+    let mut mismatches = Vec::new();
+    for program in programs() {
+        let interpreted = run(&program, "interp");
+        let compiled_path = output_dir.join(program.file_stem().unwrap()).with_extension("tjc");
+        let built = tjlang(&["build".as_ref(), program.as_ref(), "-o".as_ref(), compiled_path.as_ref()]);
+        if !built.status.success() {
+            // Programs that fail analysis cannot be built, and cannot be run either
+            if interpreted.status.success() {
+                mismatches.push(format!("{} runs but does not build", program.display()));
+            }
+            continue;
+        }
+
+        let compiled = tjlang(&["run".as_ref(), compiled_path.as_ref()]);
+        if interpreted.status.code() != compiled.status.code() || interpreted.stdout != compiled.stdout {
+            mismatches.push(format!(
+                "{}\n--- interp ({:?}) ---\n{}\n--- compiled ({:?}) ---\n{}{}",
+                program.display(),
+                interpreted.status.code(),
+                String::from_utf8_lossy(&interpreted.stdout),
+                compiled.status.code(),
+                String::from_utf8_lossy(&compiled.stdout),
+                String::from_utf8_lossy(&compiled.stderr),
+            ));
+        }
+    }
+    std::fs::remove_dir_all(&output_dir).ok();
+    assert!(mismatches.is_empty(), "compiled programs disagree:\n{}", mismatches.join("\n"));
+}
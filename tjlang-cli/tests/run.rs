@@ -0,0 +1,179 @@
+//! End-to-end tests of `tjlang run`: programs go through static analysis
+//! before they run, unlike the runtime's own tests.
+
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// Write `source` to a temporary file and run it with the extra arguments
+fn run_source(name: &str, source: &str, args: &[&str]) -> Output {
+    let path: PathBuf = std::env::temp_dir().join(format!("tjlang-run-{}-{}.tj", name, std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_tjlang"))
+        .arg("run")
+        .arg(&path)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).ok();
+    output
+}
+
+fn stdout_of(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "program failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_closures_pass_analysis_and_run() {
+    let source = r#"
+def make_adder(n: int) -> any {
+    return (x: int) -> x + n
+}
+
+def apply(f: fn(int) -> int, v: int) -> int {
+    return f(v)
+}
+
+def main() -> int {
+    add5: any = make_adder(5)
+    factor: int = 3
+    IO.print(add5(10))
+    IO.print(" ")
+    IO.print(apply((x: int) -> x * factor, 7))
+    return 0
+}
+"#;
+    let output = run_source("closures", source, &[]);
+    assert_eq!(stdout_of(&output), "15 21");
+}
+
+#[test]
+fn test_impl_methods_and_enum_variants_pass_analysis_and_run() {
+    let source = r#"
+type Point { x: int, y: int }
+interface Printable { show() -> str }
+impl Printable: Point {
+    show() -> str {
+        return "(" + self.x.to_string() + "," + self.y.to_string() + ")"
+    }
+}
+enum Shape { Circle(float), Empty }
+
+def main() -> int {
+    p: Point = Point { x: 1, y: 2 }
+    IO.print(p.show())
+    c: Shape = Circle(2.0)
+    d: Shape = Shape.Circle(3.0)
+    e: Shape = Shape.Empty
+    match d {
+        Circle(r): { IO.print(" " + r.to_string()) }
+        _: { IO.print(" other") }
+    }
+    IO.print(" " + c.to_string() + " " + e.to_string())
+    return 0
+}
+"#;
+    let output = run_source("impl-enum", source, &[]);
+    assert_eq!(stdout_of(&output), "(1,2) 3 Shape::Circle (2) Shape::Empty");
+}
+
+#[test]
+fn test_option_and_result_pass_analysis_and_run() {
+    let source = r#"
+def parse(s: str) -> Result<int, str> {
+    if s == "" {
+        return Err("empty")
+    }
+    return Ok(s.length())
+}
+
+def double_parsed(s: str) -> Result<int, str> {
+    n: int = parse(s)?
+    return Ok(n * 2)
+}
+
+def find(xs: [int], target: int) -> Option<int> {
+    for (x: int; xs) {
+        if x == target {
+            return Some(x)
+        }
+    }
+    return None
+}
+
+def main() -> int {
+    r: Result<int, str> = double_parsed("abc")
+    e: Result<int, str> = double_parsed("")
+    IO.print(r.unwrap())
+    IO.print(" " + r.is_ok().to_string() + " " + e.unwrap_or(-1).to_string())
+    o: Option<int> = find([1, 2, 3], 5)
+    IO.print(" " + o.is_none().to_string())
+    IO.print(" " + find([1, 2, 3], 2).unwrap().to_string())
+    return 0
+}
+"#;
+    let output = run_source("option-result", source, &[]);
+    assert_eq!(stdout_of(&output), "6 true -1 true 2");
+}
+
+#[test]
+fn test_tasks_and_channels_pass_analysis_and_run() {
+    let source = r#"
+def produce(ch: any, n: int) -> int {
+    ch.send(n * n)
+    return n
+}
+
+def main() -> int {
+    ch: any = CHANNEL.new()
+    t: any = spawn produce(ch, 7)
+    IO.print(t.join())
+    IO.print(" " + ch.recv().to_string())
+    IO.print(" " + ch.try_recv().to_string())
+    return 0
+}
+"#;
+    let output = run_source("tasks", source, &[]);
+    assert_eq!(stdout_of(&output), "7 49 None");
+}
+
+#[test]
+fn test_max_depth_beyond_stack_cap_is_rejected() {
+    let source = "def main() -> int {\n    return 0\n}\n";
+    let output = run_source("max-depth", source, &["--max-depth", &usize::MAX.to_string()]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("needs more stack than the interpreter can reserve"), "{}", stderr);
+}
+
+#[test]
+fn test_seed_is_accepted_before_and_after_the_subcommand() {
+    let source = r#"
+def main() -> int {
+    xs: [int] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+    COLLECTIONS.shuffle(xs)
+    IO.print(xs)
+    return 0
+}
+"#;
+    let after = stdout_of(&run_source("seed-after", source, &["--seed", "5"]));
+    assert_eq!(stdout_of(&run_source("seed-again", source, &["--seed", "5"])), after);
+    assert_ne!(stdout_of(&run_source("seed-other", source, &["--seed", "6"])), after);
+
+    let path: PathBuf = std::env::temp_dir().join(format!("tjlang-run-seed-before-{}.tj", std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    let before = Command::new(env!("CARGO_BIN_EXE_tjlang"))
+        .args(["--seed", "5", "run"])
+        .arg(&path)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(stdout_of(&before), after);
+}
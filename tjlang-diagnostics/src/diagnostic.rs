@@ -33,6 +33,8 @@ pub struct TJLangDiagnostic {
     pub message: String,
     pub primary_span: SourceSpan,
     pub secondary_spans: Vec<SourceSpan>,
+    /// Secondary spans that carry a message of their own
+    pub secondary_labels: Vec<(SourceSpan, String)>,
     pub suggestions: Vec<Suggestion>,
     pub notes: Vec<String>,
 }
@@ -50,6 +52,7 @@ impl TJLangDiagnostic {
             message,
             primary_span,
             secondary_spans: Vec::new(),
+            secondary_labels: Vec::new(),
             suggestions: Vec::new(),
             notes: Vec::new(),
         }
@@ -60,6 +63,11 @@ impl TJLangDiagnostic {
         self
     }
 
+    pub fn with_secondary_label(mut self, span: SourceSpan, message: String) -> Self {
+        self.secondary_labels.push((span, message));
+        self
+    }
+
     pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
         self.suggestions.push(suggestion);
         self
@@ -84,6 +92,11 @@ impl TJLangDiagnostic {
         for span in &self.secondary_spans {
             diagnostic = diagnostic.with_labels(vec![Label::secondary(span.file_id, span.span)]);
         }
+        for (span, message) in &self.secondary_labels {
+            diagnostic = diagnostic.with_labels(vec![
+                Label::secondary(span.file_id, span.span).with_message(message)
+            ]);
+        }
 
         // Add suggestions as notes
         for suggestion in &self.suggestions {
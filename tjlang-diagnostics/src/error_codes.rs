@@ -140,6 +140,7 @@ pub enum ErrorCode {
     RuntimeWrongArgumentCount,
     RuntimeMethodNotFound,
     RuntimeNullPointer,
+    RuntimeUncaughtException,
 
     // File system errors (F5000-F5999)
     FileNotFound,
@@ -290,6 +291,7 @@ impl ErrorCode {
             ErrorCode::RuntimeWrongArgumentCount => "R4009",
             ErrorCode::RuntimeMethodNotFound => "R4010",
             ErrorCode::RuntimeNullPointer => "R4011",
+            ErrorCode::RuntimeUncaughtException => "R4012",
 
             // File system errors
             ErrorCode::FileNotFound => "F5000",
@@ -418,7 +420,8 @@ impl ErrorCode {
             | ErrorCode::RuntimeUndefinedFunction
             | ErrorCode::RuntimeWrongArgumentCount
             | ErrorCode::RuntimeMethodNotFound
-            | ErrorCode::RuntimeNullPointer => "Runtime",
+            | ErrorCode::RuntimeNullPointer
+            | ErrorCode::RuntimeUncaughtException => "Runtime",
 
             ErrorCode::FileNotFound
             | ErrorCode::FilePermissionDenied
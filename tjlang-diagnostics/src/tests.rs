@@ -85,6 +85,7 @@ mod error_code_tests {
         assert_eq!(ErrorCode::RuntimeMemoryError.as_str(), "R4002");
         assert_eq!(ErrorCode::RuntimeTypeError.as_str(), "R4003");
         assert_eq!(ErrorCode::RuntimeValueError.as_str(), "R4004");
+        assert_eq!(ErrorCode::RuntimeUncaughtException.as_str(), "R4012");
     }
 
     #[test]
@@ -308,6 +309,24 @@ mod diagnostic_tests {
         assert_eq!(diagnostic.secondary_spans[0], secondary_span);
     }
 
+    #[test]
+    fn test_diagnostic_with_secondary_label() {
+        let primary_span = create_test_span();
+        let call_site = create_test_span_at(30, 40);
+
+        let diagnostic = TJLangDiagnostic::new(
+            ErrorCode::RuntimeValueError,
+            Severity::Error,
+            "division by zero".to_string(),
+            primary_span,
+        )
+        .with_secondary_label(call_site, "called from 'main'".to_string());
+
+        let codespan = diagnostic.to_codespan_diagnostic();
+        assert_eq!(codespan.labels.len(), 2);
+        assert_eq!(codespan.labels[1].message, "called from 'main'");
+    }
+
     #[test]
     fn test_diagnostic_with_suggestion() {
         let span = create_test_span();
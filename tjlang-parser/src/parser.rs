@@ -1,7 +1,6 @@
 //! Pest-based parser for TJLang
 //! This replaces the recursive descent parser with a more robust pest-based solution
 
-use codespan::Files;
 use pest::iterators::Pair;
 use pest::Parser;
 use pest_derive::Parser;
@@ -29,6 +28,51 @@ struct ErrorContext {
     note: Option<String>,
 }
 
+/// Binary operator for an operator token
+fn binary_operator(op: &str) -> Option<BinaryOperator> {
+    Some(match op {
+        "+" => BinaryOperator::Add,
+        "-" => BinaryOperator::Subtract,
+        "*" => BinaryOperator::Multiply,
+        "/" => BinaryOperator::Divide,
+        "%" => BinaryOperator::Modulo,
+        "**" => BinaryOperator::Power,
+        "==" => BinaryOperator::Equal,
+        "!=" => BinaryOperator::NotEqual,
+        "<" => BinaryOperator::LessThan,
+        "<=" => BinaryOperator::LessThanEqual,
+        ">" => BinaryOperator::GreaterThan,
+        ">=" => BinaryOperator::GreaterThanEqual,
+        "<<" => BinaryOperator::ShiftLeft,
+        ">>" => BinaryOperator::ShiftRight,
+        "&" => BinaryOperator::BitAnd,
+        "^" => BinaryOperator::BitXor,
+        "|" => BinaryOperator::BitOr,
+        "and" => BinaryOperator::And,
+        "or" => BinaryOperator::Or,
+        _ => return None,
+    })
+}
+
+/// Assignment operator for an `assign_op` token
+fn assignment_operator(op: &str) -> Option<BinaryOperator> {
+    Some(match op {
+        "=" => BinaryOperator::Assign,
+        "+=" => BinaryOperator::AddAssign,
+        "-=" => BinaryOperator::SubtractAssign,
+        "*=" => BinaryOperator::MultiplyAssign,
+        "/=" => BinaryOperator::DivideAssign,
+        "%=" => BinaryOperator::ModuloAssign,
+        "**=" => BinaryOperator::PowerAssign,
+        "<<=" => BinaryOperator::ShiftLeftAssign,
+        ">>=" => BinaryOperator::ShiftRightAssign,
+        "&=" => BinaryOperator::BitAndAssign,
+        "|=" => BinaryOperator::BitOrAssign,
+        "^=" => BinaryOperator::BitXorAssign,
+        _ => return None,
+    })
+}
+
 /// Main parser struct using pest
 pub struct PestParser {
     pub diagnostics: DiagnosticCollection,
@@ -460,6 +504,14 @@ impl PestParser {
                                     parse_errors.push(e);
                                 }
                         },
+                        Rule::import_decl => match self.parse_import_decl(inner) {
+                                Ok(import_decl) => {
+                                    units.push(ProgramUnit::Import(import_decl));
+                                }
+                                Err(e) => {
+                                    parse_errors.push(e);
+                                }
+                        },
                         Rule::export_decl => match self.parse_export_decl(inner) {
                                 Ok(export_decl) => {
                                     units.push(ProgramUnit::Export(export_decl));
@@ -620,6 +672,10 @@ impl PestParser {
                         let raise_stmt = self.parse_raise_stmt(inner)?;
                         Ok(Some(Statement::Raise(raise_stmt)))
                     }
+                    Rule::try_stmt => {
+                        let try_stmt = self.parse_try_stmt(inner)?;
+                        Ok(Some(Statement::Try(try_stmt)))
+                    }
                     _ => Ok(None),
                 }
             }
@@ -656,6 +712,19 @@ impl PestParser {
         })
     }
 
+    fn parse_match_expr(
+        &mut self,
+        pair: Pair<Rule>,
+    ) -> Result<Expression, Box<dyn std::error::Error>> {
+        // A match expression has the same shape as a match statement
+        let match_stmt = self.parse_match_stmt(pair)?;
+        Ok(Expression::Match {
+            expression: Box::new(match_stmt.expression),
+            arms: match_stmt.arms,
+            span: match_stmt.span,
+        })
+    }
+
     fn parse_match_arm(
         &mut self,
         pair: Pair<Rule>,
@@ -1062,6 +1131,21 @@ impl PestParser {
                     }
                     // Otherwise drill down
                 }
+                Rule::bit_or_expr
+                | Rule::bit_xor_expr
+                | Rule::bit_and_expr
+                | Rule::equality
+                | Rule::relational
+                | Rule::shift_expr
+                | Rule::additive
+                | Rule::power
+                    if all_children.len() > 1 =>
+                {
+                    // A comparison, bitwise, shift, additive or power rule with multiple
+                    // children has an operator
+                    debug_println!("[DEBUG] [CMP] Found {:?} with {} children - parsing as binary operation", current.as_rule(), all_children.len());
+                    return self.parse_binary_operation(current);
+                }
                 _ => {}
             }
 
@@ -1314,22 +1398,8 @@ impl PestParser {
             let op = children[1].as_str();
             let right = self.parse_expression(children[2].clone())?;
             
-            let operator = match op {
-                "+" => BinaryOperator::Add,
-                "-" => BinaryOperator::Subtract,
-                "*" => BinaryOperator::Multiply,
-                "/" => BinaryOperator::Divide,
-                "%" => BinaryOperator::Modulo,
-                "==" => BinaryOperator::Equal,
-                "!=" => BinaryOperator::NotEqual,
-                "<" => BinaryOperator::LessThan,
-                "<=" => BinaryOperator::LessThanEqual,
-                ">" => BinaryOperator::GreaterThan,
-                ">=" => BinaryOperator::GreaterThanEqual,
-                "and" => BinaryOperator::And,
-                "or" => BinaryOperator::Or,
-                _ => return Err(format!("Unknown binary operator: {}", op).into()),
-            };
+            let operator = binary_operator(op)
+                .ok_or_else(|| format!("Unknown binary operator: {}", op))?;
             
             return Ok(Expression::Binary {
                 left: Box::new(left),
@@ -1369,22 +1439,8 @@ impl PestParser {
                 let right = self.parse_expression(children[i].clone())?;
                 i += 1;
 
-                let operator = match op {
-                    "+" => BinaryOperator::Add,
-                    "-" => BinaryOperator::Subtract,
-                    "*" => BinaryOperator::Multiply,
-                    "/" => BinaryOperator::Divide,
-                    "%" => BinaryOperator::Modulo,
-                    "==" => BinaryOperator::Equal,
-                    "!=" => BinaryOperator::NotEqual,
-                    "<" => BinaryOperator::LessThan,
-                    "<=" => BinaryOperator::LessThanEqual,
-                    ">" => BinaryOperator::GreaterThan,
-                    ">=" => BinaryOperator::GreaterThanEqual,
-                    "and" => BinaryOperator::And,
-                    "or" => BinaryOperator::Or,
-                    _ => return Err(format!("Unknown binary operator: {}", op).into()),
-                };
+                let operator = binary_operator(op)
+                    .ok_or_else(|| format!("Unknown binary operator: {}", op))?;
 
                 // Build left-associative tree
                 left = Expression::Binary {
@@ -1464,7 +1520,7 @@ impl PestParser {
                                 assign_op.as_str(),
                                 assign_op.as_rule()
                             );
-                            if assign_op.as_str() == "=" {
+                            if let Some(operator) = assignment_operator(assign_op.as_str()) {
                                 // This is a real assignment - parse left and right sides
                                 let right =
                                     assignment_iter.next().ok_or("Missing right operand")?;
@@ -1487,7 +1543,7 @@ impl PestParser {
 
                                 Ok(Expression::Binary {
                                     left: Box::new(left_expr),
-                                    operator: BinaryOperator::Assign,
+                                    operator,
                                     right: Box::new(right_expr),
                                     span: self.create_span(inner.as_span()),
                                 })
@@ -1683,13 +1739,17 @@ impl PestParser {
                 )?;
                 
                 if let Some(assign_pair) = inner.next() {
-                    if assign_pair.as_str() == "=" {
+                    if let Some(operator) = assignment_operator(assign_pair.as_str()) {
                         let right = self.parse_expression_with_depth(
                             inner.next().ok_or("Missing right operand")?,
                             depth,
                         )?;
-                        // For now, just return the right side since we don't have assignment expressions in AST yet
-                        Ok(right)
+                        Ok(Expression::Binary {
+                            left: Box::new(left),
+                            operator,
+                            right: Box::new(right),
+                            span: self.create_span(span),
+                        })
                     } else {
                         Ok(left)
                     }
@@ -2220,6 +2280,7 @@ impl PestParser {
                             .ok_or("Empty parenthesized expression")?;
                         self.parse_expression(inner_expr)
                     }
+                    Rule::match_expr => self.parse_match_expr(inner),
                     Rule::spawn_expr => {
                         let span = inner.as_span();
                         let mut it = inner
@@ -2652,7 +2713,11 @@ impl PestParser {
         &mut self,
         pair: Pair<Rule>,
     ) -> Result<RaiseStatement, Box<dyn std::error::Error>> {
-        let span = pair.as_span();
+        // Trailing whitespace is part of the match; drop it so diagnostics point at the statement itself
+        let text = pair.as_str();
+        let start = pair.as_span().start();
+        let span = pest::Span::new(pair.get_input(), start, start + text.trim_end().len())
+            .unwrap_or_else(|| pair.as_span());
         let mut inner = pair.into_inner();
         
         // The "raise" keyword is consumed by the grammar, so the first inner pair is the expression
@@ -2664,6 +2729,58 @@ impl PestParser {
         })
     }
 
+    /// Parse try statement with its except clauses
+    fn parse_try_stmt(
+        &mut self,
+        pair: Pair<Rule>,
+    ) -> Result<TryStatement, Box<dyn std::error::Error>> {
+        let span = pair.as_span();
+        let mut inner = pair
+            .into_inner()
+            .filter(|p| p.as_rule() != Rule::WHITESPACE);
+
+        // The "try" keyword is consumed by the grammar, so the first inner pair is the body
+        let body = self.parse_block(inner.next().ok_or("Missing block in try statement")?)?;
+
+        let mut handlers = Vec::new();
+        for clause in inner {
+            if clause.as_rule() == Rule::except_clause {
+                handlers.push(self.parse_except_clause(clause)?);
+            }
+        }
+
+        Ok(TryStatement {
+            body,
+            handlers,
+            span: self.create_span(span),
+        })
+    }
+
+    fn parse_except_clause(
+        &mut self,
+        pair: Pair<Rule>,
+    ) -> Result<ExceptClause, Box<dyn std::error::Error>> {
+        let span = pair.as_span();
+        let mut pattern = None;
+        let mut body = None;
+        for child in pair
+            .into_inner()
+            .filter(|p| p.as_rule() != Rule::WHITESPACE)
+        {
+            match child.as_rule() {
+                Rule::pattern => pattern = Some(self.parse_pattern(child)?),
+                Rule::block => body = Some(self.parse_block(child)?),
+                _ => {}
+            }
+        }
+
+        Ok(ExceptClause {
+            pattern,
+            body: body.ok_or("Missing block in except clause")?,
+            span: self.create_span(span),
+        })
+    }
+
     /// Parse function declaration
     fn parse_function_decl(
         &mut self,
@@ -2880,11 +2997,15 @@ impl PestParser {
             .to_string();
         
         let param_type = self.parse_type(inner.next().ok_or("Missing parameter type")?)?;
+        let default_value = match inner.next() {
+            Some(default_pair) => Some(self.parse_expression(default_pair)?),
+            None => None,
+        };
         
         Ok(Parameter {
             name,
             param_type,
-            default_value: None, // Default parameters not yet supported in parser
+            default_value,
             span: self.create_span(span),
         })
     }
@@ -3066,13 +3187,8 @@ impl PestParser {
             .ok_or("Missing field name in field initialization")?;
         let name = name_pair.as_str().to_string();
         
-        // Skip the colon
-        if let Some(colon_pair) = inner.next() {
-            if colon_pair.as_str() != ":" {
-                return Err("Expected ':' in field initialization".into());
-            }
-        }
-        
+        // Note: colon ":" is a literal in the grammar and not included in the parse tree
+
         // Parse the expression value
         let value_pair = inner
             .next()
@@ -3116,12 +3232,18 @@ impl PestParser {
     ) -> Result<Expression, Box<dyn std::error::Error>> {
         match pair.as_rule() {
             Rule::spawn_expr => {
-                // Handle spawn expressions - placeholder to avoid recursion
-                Ok(Expression::Spawn { 
-                    expression: Box::new(Expression::Literal(Literal::None)), 
-                    span: self.create_span(pair.as_span()),
+                let span = pair.as_span();
+                let mut it = pair
+                    .into_inner()
+                    .filter(|p| p.as_rule() != Rule::WHITESPACE);
+                let expr_pair = it.next().ok_or("Missing expression to spawn")?;
+                let expression = self.parse_expression(expr_pair)?;
+                Ok(Expression::Spawn {
+                    expression: Box::new(expression),
+                    span: self.create_span(span),
                 })
             }
+            Rule::match_expr => self.parse_match_expr(pair),
             Rule::range_expr => self.parse_range_expr(pair),
             Rule::literal => {
                 let literal = self.parse_literal(pair)?;
@@ -3138,8 +3260,7 @@ impl PestParser {
             Rule::lambda_expr => self.parse_lambda_expr(pair),
             Rule::expression => {
                 // Handle parenthesized expressions: "(" ~ expression ~ ")"
-                // For now, return a simple placeholder to avoid recursion
-                Ok(Expression::Literal(Literal::None))
+                self.parse_expression(pair)
             }
             _ => Err(format!("Unexpected primary content: {:?}", pair.as_rule()).into()),
         }
@@ -3189,6 +3310,9 @@ impl PestParser {
                 Rule::member_suffix => {
                     expr = self.parse_member_suffix(expr, suffix_pair)?;
                 }
+                Rule::propagate_suffix => {
+                    expr = self.parse_propagate_suffix(expr, suffix_pair);
+                }
                 _ => {
                     return Err(
                         format!("Unexpected postfix suffix: {:?}", suffix_pair.as_rule()).into(),
@@ -3209,20 +3333,27 @@ impl PestParser {
     ) -> Result<Expression, Box<dyn std::error::Error>> {
         let span = pair.as_span();
         let mut args = Vec::new();
-        let mut inner = pair
-            .into_inner()
-            .filter(|p| p.as_rule() != Rule::WHITESPACE);
-
-        // Parse arguments if present
-        if let Some(arg_list_pair) = inner.next() {
-            if arg_list_pair.as_rule() == Rule::argument_list {
-                args = self.parse_argument_list(arg_list_pair)?;
+        let mut named_args = Vec::new();
+
+        // Positional arguments come first, then keyword arguments
+        for list_pair in pair.into_inner().filter(|p| p.as_rule() != Rule::WHITESPACE) {
+            match list_pair.as_rule() {
+                Rule::argument_list => args = self.parse_argument_list(list_pair)?,
+                Rule::field_init_list => {
+                    for field_pair in list_pair.into_inner() {
+                        if field_pair.as_rule() == Rule::field_init {
+                            named_args.push(self.parse_field_init(field_pair)?);
+                        }
+                    }
+                }
+                _ => {}
             }
         }
 
         Ok(Expression::Call {
             callee: Box::new(callee),
             args,
+            named_args,
             span: self.create_span(span),
         })
     }
@@ -3273,6 +3404,15 @@ impl PestParser {
         })
     }
 
+    /// Parse `?` propagation suffix
+    fn parse_propagate_suffix(&mut self, expression: Expression, pair: Pair<Rule>) -> Expression {
+        let span = pair.as_span();
+        Expression::Propagate {
+            expression: Box::new(expression),
+            span: self.create_span(span),
+        }
+    }
+
     /// Parse lambda expression
     fn parse_lambda_expr(
         &mut self,
@@ -3341,6 +3481,9 @@ impl PestParser {
                 Rule::member_suffix => {
                     expr = self.parse_member_suffix(expr, suffix_pair)?;
                 }
+                Rule::propagate_suffix => {
+                    expr = self.parse_propagate_suffix(expr, suffix_pair);
+                }
                 _ => {
                     return Err(
                         format!("Unexpected suffix rule: {:?}", suffix_pair.as_rule()).into(),
@@ -3364,6 +3507,7 @@ impl PestParser {
         let first = inner.next().ok_or("Empty primary_no_range")?;
 
         match first.as_rule() {
+            Rule::match_expr => self.parse_match_expr(first),
             Rule::spawn_expr => {
                 // Handle spawn expressions inline
                 let span = first.as_span();
@@ -3502,23 +3646,12 @@ impl PestParser {
     /// Parse option type
     fn parse_option_type(&mut self, pair: Pair<Rule>) -> Result<Type, Box<dyn std::error::Error>> {
         let span = pair.as_span();
+        // The `?` marker is a literal, so it has no pair of its own
+        let is_optional = pair.as_str().starts_with('?');
         let mut inner = pair
             .into_inner()
             .filter(|p| p.as_rule() != Rule::WHITESPACE);
 
-        // Check if this is an optional type
-        let is_optional = if let Some(first) = inner.next() {
-            if first.as_str() == "?" {
-                true
-            } else {
-                // This is the function_type, not the optional marker
-                let function_type = self.parse_function_type(first)?;
-                return Ok(function_type);
-            }
-        } else {
-            false
-        };
-
         // Parse the function type
         let function_type =
             self.parse_function_type(inner.next().ok_or("Missing function type")?)?;
@@ -3671,7 +3804,8 @@ impl PestParser {
     /// Parse primary type
     fn parse_primary_type(&mut self, pair: Pair<Rule>) -> Result<Type, Box<dyn std::error::Error>> {
         let span = pair.as_span();
-        let inner = pair.into_inner().next().ok_or("Empty primary type")?;
+        let mut children = pair.into_inner();
+        let inner = children.next().ok_or("Empty primary type")?;
 
         match inner.as_rule() {
             Rule::primitive_type => {
@@ -3716,20 +3850,17 @@ impl PestParser {
             }
             Rule::qualified_name => {
                 let qualified_name = inner.as_str().to_string();
-                // Check if there are type parameters
-                if let Some(type_params_pair) = inner.into_inner().next() {
-                    if type_params_pair.as_rule() == Rule::type_params {
+                // Type parameters follow the name rather than being part of it
+                match children.next() {
+                    Some(type_params_pair) if type_params_pair.as_rule() == Rule::type_params => {
                         let type_params = self.parse_type_params(type_params_pair)?;
                         Ok(Type::Generic {
                             name: qualified_name,
                             type_args: type_params,
                             span: self.create_span(span),
                         })
-                    } else {
-                        Ok(Type::Identifier(qualified_name))
                     }
-                } else {
-                    Ok(Type::Identifier(qualified_name))
+                    _ => Ok(Type::Identifier(qualified_name)),
                 }
             }
             Rule::result_type => {
@@ -3911,10 +4042,7 @@ impl PestParser {
             .into_inner()
             .filter(|p| p.as_rule() != Rule::WHITESPACE);
 
-        // Skip "enum" keyword
-        inner.next().ok_or("Missing enum keyword")?;
-
-        // Parse enum name
+        // The "enum" keyword is consumed by the grammar, so the first inner pair is the name
         let name = inner
             .next()
             .ok_or("Missing enum name")?
@@ -4171,11 +4299,13 @@ impl PestParser {
             .next()
             .ok_or("Missing trait name")?
             .as_str()
+            .trim()
             .to_string();
         let type_name = inner
             .next()
             .ok_or("Missing type name")?
             .as_str()
+            .trim()
             .to_string();
 
         let mut methods = Vec::new();
@@ -4275,12 +4405,46 @@ impl PestParser {
         Ok(identifiers)
     }
 
+    /// Parse import declaration
+    fn parse_import_decl(
+        &mut self,
+        pair: Pair<Rule>,
+    ) -> Result<ImportDecl, Box<dyn std::error::Error>> {
+        let span = self.create_span(pair.as_span());
+        let mut inner = pair
+            .into_inner()
+            .filter(|p| p.as_rule() != Rule::WHITESPACE);
+        let first = inner.next().ok_or("Empty import declaration")?;
+
+        match first.as_rule() {
+            Rule::identifier_list => {
+                let items = self.parse_identifier_list(first)?;
+                let module_pair = inner.next().ok_or("Missing module name in import")?;
+                let module = self.parse_qualified_name(module_pair);
+                Ok(ImportDecl::Selective { module, items, span })
+            }
+            Rule::qualified_name => {
+                let module = self.parse_qualified_name(first);
+                let alias = inner.next().map(|alias| alias.as_str().to_string());
+                Ok(ImportDecl::Simple { module, alias, span })
+            }
+            _ => Err(format!("Expected module name in import, got {:?}", first.as_rule()).into()),
+        }
+    }
+
+    /// Parse a dotted module name such as `geometry.shapes`
+    fn parse_qualified_name(&mut self, pair: Pair<Rule>) -> QualifiedName {
+        QualifiedName {
+            parts: pair.as_str().split('.').map(|part| part.trim().to_string()).collect(),
+            span: self.create_span(pair.as_span()),
+        }
+    }
+
     /// Parse export declaration
     fn parse_export_decl(
         &mut self,
         pair: Pair<Rule>,
     ) -> Result<ExportDecl, Box<dyn std::error::Error>> {
-        let span = pair.as_span();
         let inner = pair.into_inner().next().ok_or("Empty export declaration")?;
         
         match inner.as_rule() {
@@ -4292,6 +4456,14 @@ impl PestParser {
                 let type_decl = self.parse_type_decl(inner)?;
                 Ok(ExportDecl::Declaration(Declaration::Type(type_decl)))
             }
+            Rule::struct_decl => {
+                let struct_decl = self.parse_struct_decl(inner)?;
+                Ok(ExportDecl::Declaration(Declaration::Struct(struct_decl)))
+            }
+            Rule::enum_decl => {
+                let enum_decl = self.parse_enum_decl(inner)?;
+                Ok(ExportDecl::Declaration(Declaration::Enum(enum_decl)))
+            }
             Rule::interface_decl => {
                 let interface_decl = self.parse_interface_decl(inner)?;
                 Ok(ExportDecl::Declaration(Declaration::Interface(interface_decl)))
@@ -4304,16 +4476,14 @@ impl PestParser {
                 let identifiers = self.parse_identifier_list(inner)?;
                 Ok(ExportDecl::IdentifierList(identifiers))
             }
-            _ => return Err(format!("Expected function_decl, type_decl, interface_decl, identifier, or identifier_list in export, got {:?}", inner.as_rule()).into()),
+            _ => Err(format!("Expected function_decl, type_decl, struct_decl, enum_decl, interface_decl, identifier, or identifier_list in export, got {:?}", inner.as_rule()).into()),
         }
     }
 
     /// Create a SourceSpan from a pest span
     fn create_span(&self, span: pest::Span) -> tjlang_ast::SourceSpan {
-        let mut files = Files::new();
-        let file_id = files.add("input.tj", "");
         tjlang_ast::SourceSpan {
-            file_id,
+            file_id: self.current_file_id,
             span: codespan::Span::new(
                 codespan::ByteIndex(span.start() as u32),
                 codespan::ByteIndex(span.end() as u32),
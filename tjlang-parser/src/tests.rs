@@ -1306,6 +1306,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_optional_and_union_types() {
+        let source = "x: ?int = None\ny: int | ?str = 1";
+        let mut parser = PestParser::new();
+        let file_id = create_test_file_id();
+        let program = parser.parse(source, file_id).expect("Should parse");
+
+        let var_types: Vec<&Type> = program
+            .units
+            .iter()
+            .map(|unit| match unit {
+                ProgramUnit::Declaration(Declaration::Variable(var)) => &var.var_type,
+                other => panic!("Expected variable declaration, got: {:?}", other),
+            })
+            .collect();
+
+        assert!(
+            matches!(var_types[0], Type::Option { inner, .. } if **inner == Type::Primitive(PrimitiveType::Int)),
+            "Expected ?int, got: {:?}",
+            var_types[0]
+        );
+        match var_types[1] {
+            Type::Union { types, .. } => {
+                assert_eq!(types[0], Type::Primitive(PrimitiveType::Int));
+                assert!(matches!(&types[1], Type::Option { inner, .. } if **inner == Type::Primitive(PrimitiveType::Str)));
+            }
+            other => panic!("Expected int | ?str, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_generic_type_arguments() {
+        let source = "m: Map<str, [int]> = {}\no: Option<int> = None";
+        let mut parser = PestParser::new();
+        let file_id = create_test_file_id();
+        let program = parser.parse(source, file_id).expect("Should parse");
+
+        let type_args: Vec<(&String, &Vec<Type>)> = program
+            .units
+            .iter()
+            .map(|unit| match unit {
+                ProgramUnit::Declaration(Declaration::Variable(var)) => match &var.var_type {
+                    Type::Generic { name, type_args, .. } => (name, type_args),
+                    other => panic!("Expected a generic type, got: {:?}", other),
+                },
+                other => panic!("Expected variable declaration, got: {:?}", other),
+            })
+            .collect();
+
+        assert_eq!(type_args[0].0, "Map");
+        assert_eq!(type_args[0].1[0], Type::Primitive(PrimitiveType::Str));
+        assert!(matches!(&type_args[0].1[1], Type::Vec { .. }), "got: {:?}", type_args[0].1);
+        assert_eq!(type_args[1].0, "Option");
+        assert_eq!(type_args[1].1, &vec![Type::Primitive(PrimitiveType::Int)]);
+    }
+
     #[test]
     fn test_parse_simple_type() {
         let source = "int";
@@ -2862,6 +2918,263 @@ mod tests {
         parse_ok_program_helper("def main() -> int { match Some((1,2)) { Some((a: int, b: int)): { return a + b } None: { return 0 } } }");
     }
 
+    #[test]
+    fn test_grammar_match_trait_check() {
+        parse_match_stmt_ok("match v { p: implements [Printable]: { pass } _: { pass } }");
+    }
+
+    #[test]
+    fn test_parse_match_expression() {
+        use crate::parser::PestParser;
+
+        let source = "label: str = match n { 0: { \"zero\" } _: { \"many\" } }";
+        let mut parser = PestParser::new();
+        let file_id = create_test_file_id();
+        let program = parser.parse(source, file_id).expect("Should parse");
+
+        match &program.units[0] {
+            ProgramUnit::Declaration(Declaration::Variable(var)) => match &var.value {
+                Expression::Match { arms, .. } => assert_eq!(arms.len(), 2),
+                other => panic!("Expected match expression, got: {:?}", other),
+            },
+            other => panic!("Expected variable declaration, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_equality_operators() {
+        use crate::parser::PestParser;
+
+        let cases = [
+            ("x: bool = a == b", BinaryOperator::Equal),
+            ("x: bool = a != b", BinaryOperator::NotEqual),
+            ("x: bool = a % 2 == 0", BinaryOperator::Equal),
+            ("x: bool = a <= b", BinaryOperator::LessThanEqual),
+        ];
+        for (source, expected) in cases {
+            let mut parser = PestParser::new();
+            let file_id = create_test_file_id();
+            let program = parser.parse(source, file_id).expect("Should parse");
+            match &program.units[0] {
+                ProgramUnit::Declaration(Declaration::Variable(var)) => match &var.value {
+                    Expression::Binary { operator, .. } => {
+                        assert_eq!(*operator, expected, "Wrong operator for {}", source)
+                    }
+                    other => panic!("Expected binary expression for {}, got: {:?}", source, other),
+                },
+                other => panic!("Expected variable declaration, got: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_try_except() {
+        use crate::parser::PestParser;
+
+        let source = "def main() -> int { try { raise \"boom\" } except e: str { return 1 } except { return 2 } return 0 }";
+        let mut parser = PestParser::new();
+        let file_id = create_test_file_id();
+        let program = parser.parse(source, file_id).expect("Should parse");
+
+        match &program.units[0] {
+            ProgramUnit::Declaration(Declaration::Function(func)) => match &func.body.statements[0] {
+                Statement::Try(try_stmt) => {
+                    assert!(matches!(try_stmt.body.statements[0], Statement::Raise(_)));
+                    assert_eq!(try_stmt.handlers.len(), 2);
+                    assert!(matches!(
+                        try_stmt.handlers[0].pattern,
+                        Some(Pattern::Variable { .. })
+                    ));
+                    assert!(try_stmt.handlers[1].pattern.is_none());
+                }
+                other => panic!("Expected try statement, got: {:?}", other),
+            },
+            other => panic!("Expected function declaration, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_propagate_suffix() {
+        use crate::parser::PestParser;
+
+        let source = "def f() -> int { return load(1)?.size }";
+        let mut parser = PestParser::new();
+        let file_id = create_test_file_id();
+        let program = parser.parse(source, file_id).expect("Should parse");
+
+        match &program.units[0] {
+            ProgramUnit::Declaration(Declaration::Function(func)) => match &func.body.statements[0] {
+                Statement::Return(ret) => match &ret.value {
+                    Some(Expression::Member { target, member, .. }) => {
+                        assert_eq!(member, "size");
+                        match target.as_ref() {
+                            Expression::Propagate { expression, .. } => {
+                                assert!(matches!(expression.as_ref(), Expression::Call { .. }));
+                            }
+                            other => panic!("Expected propagate expression, got: {:?}", other),
+                        }
+                    }
+                    other => panic!("Expected member access, got: {:?}", other),
+                },
+                other => panic!("Expected return statement, got: {:?}", other),
+            },
+            other => panic!("Expected function declaration, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_spawn_keeps_expression() {
+        use crate::parser::PestParser;
+
+        let source = "def main() -> int { return spawn work(7) }";
+        let mut parser = PestParser::new();
+        let file_id = create_test_file_id();
+        let program = parser.parse(source, file_id).expect("Should parse");
+
+        match &program.units[0] {
+            ProgramUnit::Declaration(Declaration::Function(func)) => match &func.body.statements[0] {
+                Statement::Return(ret) => match &ret.value {
+                    Some(Expression::Spawn { expression, .. }) => {
+                        assert!(matches!(expression.as_ref(), Expression::Call { .. }));
+                    }
+                    other => panic!("Expected spawn expression, got: {:?}", other),
+                },
+                other => panic!("Expected return statement, got: {:?}", other),
+            },
+            other => panic!("Expected function declaration, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_import_declarations() {
+        use crate::parser::PestParser;
+
+        let source = "import geometry.shapes as s\nimport { area, Point } from geometry.shapes\nexport def f() -> int { return 1 }";
+        let mut parser = PestParser::new();
+        let file_id = create_test_file_id();
+        let program = parser.parse(source, file_id).expect("Should parse");
+
+        match &program.units[0] {
+            ProgramUnit::Import(ImportDecl::Simple { module, alias, .. }) => {
+                assert_eq!(module.parts, vec!["geometry".to_string(), "shapes".to_string()]);
+                assert_eq!(alias.as_deref(), Some("s"));
+            }
+            other => panic!("Expected simple import, got: {:?}", other),
+        }
+        match &program.units[1] {
+            ProgramUnit::Import(ImportDecl::Selective { module, items, .. }) => {
+                assert_eq!(module.parts, vec!["geometry".to_string(), "shapes".to_string()]);
+                assert_eq!(items, &vec!["area".to_string(), "Point".to_string()]);
+            }
+            other => panic!("Expected selective import, got: {:?}", other),
+        }
+        assert!(matches!(
+            &program.units[2],
+            ProgramUnit::Export(ExportDecl::Declaration(Declaration::Function(_)))
+        ));
+    }
+
+    #[test]
+    fn test_parse_bitwise_shift_and_power_operators() {
+        use crate::parser::PestParser;
+
+        let cases = [
+            ("x: int = a | b", BinaryOperator::BitOr),
+            ("x: int = a ^ b", BinaryOperator::BitXor),
+            ("x: int = a & b", BinaryOperator::BitAnd),
+            ("x: int = a << 2", BinaryOperator::ShiftLeft),
+            ("x: int = a + 1 >> 2", BinaryOperator::ShiftRight),
+            ("x: int = a ** 2", BinaryOperator::Power),
+        ];
+        for (source, expected) in cases {
+            let mut parser = PestParser::new();
+            let file_id = create_test_file_id();
+            let program = parser.parse(source, file_id).expect("Should parse");
+            match &program.units[0] {
+                ProgramUnit::Declaration(Declaration::Variable(var)) => match &var.value {
+                    Expression::Binary { operator, .. } => {
+                        assert_eq!(*operator, expected, "Wrong operator for {}", source)
+                    }
+                    other => panic!("Expected binary expression for {}, got: {:?}", source, other),
+                },
+                other => panic!("Expected variable declaration, got: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_compound_assignment_targets() {
+        use crate::parser::PestParser;
+
+        let cases = [
+            ("v[i] = x", BinaryOperator::Assign),
+            ("p.x += 3", BinaryOperator::AddAssign),
+            ("a.b[2].c -= 1", BinaryOperator::SubtractAssign),
+            ("n *= 2", BinaryOperator::MultiplyAssign),
+            ("n /= 2", BinaryOperator::DivideAssign),
+            ("n %= 2", BinaryOperator::ModuloAssign),
+            ("n **= 2", BinaryOperator::PowerAssign),
+            ("n <<= 2", BinaryOperator::ShiftLeftAssign),
+            ("n >>= 2", BinaryOperator::ShiftRightAssign),
+            ("n &= 2", BinaryOperator::BitAndAssign),
+            ("n |= 2", BinaryOperator::BitOrAssign),
+            ("n ^= 2", BinaryOperator::BitXorAssign),
+        ];
+        for (source, expected) in cases {
+            let mut parser = PestParser::new();
+            let file_id = create_test_file_id();
+            let program = parser.parse(source, file_id).expect("Should parse");
+            match &program.units[0] {
+                ProgramUnit::Expression(Expression::Binary { left, operator, .. }) => {
+                    assert_eq!(*operator, expected, "Wrong operator for {}", source);
+                    assert!(
+                        matches!(
+                            left.as_ref(),
+                            Expression::Variable { .. }
+                                | Expression::Index { .. }
+                                | Expression::Member { .. }
+                        ),
+                        "Unexpected assignment target for {}: {:?}",
+                        source,
+                        left
+                    );
+                }
+                other => panic!("Expected assignment for {}, got: {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_default_params_and_keyword_args() {
+        let source = r#"def connect(host: str, port: int = 8080) -> str {
+    return host
+}
+connect("a", port: 1)"#;
+        let mut parser = PestParser::new();
+        let file_id = create_test_file_id();
+        let program = parser.parse(source, file_id).expect("Should parse");
+
+        match &program.units[0] {
+            ProgramUnit::Declaration(Declaration::Function(func)) => {
+                assert!(func.params[0].default_value.is_none());
+                assert!(matches!(
+                    func.params[1].default_value,
+                    Some(Expression::Literal(Literal::Int(8080)))
+                ));
+            }
+            other => panic!("Expected function declaration, got: {:?}", other),
+        }
+        match &program.units[1] {
+            ProgramUnit::Expression(Expression::Call { args, named_args, .. }) => {
+                assert_eq!(args.len(), 1);
+                assert_eq!(named_args.len(), 1);
+                assert_eq!(named_args[0].name, "port");
+                assert!(matches!(named_args[0].value, Expression::Literal(Literal::Int(1))));
+            }
+            other => panic!("Expected call, got: {:?}", other),
+        }
+    }
+
     // Multiple methods in impl blocks
     #[test]
     fn test_grammar_parse_impl_multiple_methods() {
@@ -3355,4 +3668,35 @@ mod tests {
 
         println!("✓ All range expression with method calls tests passed");
     }
+
+    #[test]
+    fn test_parse_struct_literal_and_enum_names() {
+        use crate::parser::PestParser;
+
+        let source = "enum Shape<T> { Circle(T), Empty }\np: Point = Point { x: 1, y: (2 + 3) }";
+        let mut parser = PestParser::new();
+        let file_id = create_test_file_id();
+        let program = parser.parse(source, file_id).expect("Should parse");
+
+        match &program.units[0] {
+            ProgramUnit::Declaration(Declaration::Enum(enum_decl)) => {
+                assert_eq!(enum_decl.name, "Shape");
+                assert_eq!(enum_decl.type_params, vec!["T".to_string()]);
+                assert_eq!(enum_decl.variants.len(), 2);
+            }
+            other => panic!("Expected enum declaration, got: {:?}", other),
+        }
+
+        match &program.units[1] {
+            ProgramUnit::Declaration(Declaration::Variable(var)) => match &var.value {
+                Expression::StructLiteral { name, fields, .. } => {
+                    assert_eq!(name, "Point");
+                    assert_eq!(fields.len(), 2);
+                    assert!(matches!(fields[1].value, Expression::Binary { .. }));
+                }
+                other => panic!("Expected struct literal, got: {:?}", other),
+            },
+            other => panic!("Expected variable declaration, got: {:?}", other),
+        }
+    }
 }
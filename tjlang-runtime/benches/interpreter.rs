@@ -0,0 +1,73 @@
+//! Interpreter benchmarks
+//!
+//! Run with `cargo bench -p tjlang-runtime`. Each program is parsed once and
+//! interpreted several times; the fastest and median runs are reported.
+
+use std::time::{Duration, Instant};
+use codespan::Files;
+use tjlang_parser::parse;
+use tjlang_runtime::Interpreter;
+
+const RUNS: usize = 5;
+
+const FIB: &str = r#"
+def fib(n: int) -> int {
+    if n < 2 {
+        return n
+    }
+    return fib(n - 1) + fib(n - 2)
+}
+
+def main() -> int {
+    return fib(25)
+}
+"#;
+
+const VECTOR_LOOP: &str = r#"
+def square(n: int) -> int {
+    return n * n
+}
+
+def main() -> int {
+    values: [int] = []
+    for (i: int; 0 $ 10000) {
+        values.push(square(i))
+    }
+    total: int = 0
+    for (v: int; values) {
+        if v % 2 == 0 {
+            total = total + v
+        }
+    }
+    return total
+}
+"#;
+
+fn bench(name: &str, source: &str) {
+    let mut files = Files::new();
+    let file_id = files.add(name, source);
+    let (program, _) = parse(source, file_id).expect("benchmark program should parse");
+
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let mut interpreter = Interpreter::new();
+            let start = Instant::now();
+            interpreter
+                .interpret_program(&program)
+                .expect("benchmark program should run");
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    println!(
+        "{:<12} fastest {:>9.2?}   median {:>9.2?}",
+        name,
+        times[0],
+        times[RUNS / 2]
+    );
+}
+
+fn main() {
+    bench("fib(25)", FIB);
+    bench("vector_loop", VECTOR_LOOP);
+}
@@ -0,0 +1,431 @@
+//! Arbitrary-precision integers
+//!
+//! Integers too large for an `i64`, produced by arithmetic that overflows when
+//! the interpreter runs with `IntOverflow::Promote`. A number is stored as a
+//! sign and a magnitude of base-2^32 digits, least significant first, with no
+//! leading zero digits.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Shl, Shr, Sub};
+
+/// A signed integer of any size
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        // Zero has no sign
+        let negative = negative && !magnitude.is_empty();
+        Self { negative, magnitude }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The value as an `i64`, if it is in range
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0u64, |acc, &digit| (acc << 32) | digit as u64);
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    /// The nearest float
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &digit| acc * 4294967296.0 + digit as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn abs(&self) -> BigInt {
+        Self::from_parts(false, self.magnitude.clone())
+    }
+
+    /// Raise to a power by repeated squaring
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from(1i64);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Quotient and remainder of truncating division, as `i64` `/` and `%`
+    /// compute them. `None` when dividing by zero.
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = divide_magnitudes(&self.magnitude, &divisor.magnitude);
+        Some((
+            Self::from_parts(self.negative != divisor.negative, quotient),
+            Self::from_parts(self.negative, remainder),
+        ))
+    }
+
+    /// The lowest `digits` digits of the two's complement form
+    fn twos_complement(&self, digits: usize) -> Vec<u32> {
+        let mut words: Vec<u32> = (0..digits).map(|i| self.magnitude.get(i).copied().unwrap_or(0)).collect();
+        if self.negative {
+            negate_words(&mut words);
+        }
+        words
+    }
+
+    /// The number whose two's complement form is `words`, sign bit last
+    fn from_twos_complement(mut words: Vec<u32>) -> BigInt {
+        let negative = words.last().is_some_and(|&word| word & 0x8000_0000 != 0);
+        if negative {
+            negate_words(&mut words);
+        }
+        Self::from_parts(negative, words)
+    }
+
+    /// Combine two numbers digit by digit in two's complement, as `i64` bitwise
+    /// operators do
+    fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+        // One spare digit so the sign bit is never a value bit
+        let digits = self.magnitude.len().max(other.magnitude.len()) + 1;
+        let words = self
+            .twos_complement(digits)
+            .into_iter()
+            .zip(other.twos_complement(digits))
+            .map(|(a, b)| op(a, b))
+            .collect();
+        Self::from_twos_complement(words)
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        Self::from_parts(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(value: u64) -> Self {
+        Self::from_parts(false, vec![value as u32, (value >> 32) as u32])
+    }
+}
+
+impl PartialEq<i64> for BigInt {
+    fn eq(&self, other: &i64) -> bool {
+        self.to_i64() == Some(*other)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitudes(&self.magnitude, &other.magnitude));
+        }
+        // Opposite signs: subtract the smaller magnitude from the larger
+        match compare_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, subtract_magnitudes(&other.magnitude, &self.magnitude))
+            }
+            _ => BigInt::from_parts(self.negative, subtract_magnitudes(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.magnitude.iter().enumerate() {
+                let total = product[i + j] as u64 + a as u64 * b as u64 + carry;
+                product[i + j] = total as u32;
+                carry = total >> 32;
+            }
+            product[i + other.magnitude.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, product)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl BitAnd for &BigInt {
+    type Output = BigInt;
+
+    fn bitand(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BigInt {
+    type Output = BigInt;
+
+    fn bitor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BigInt {
+    type Output = BigInt;
+
+    fn bitxor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+}
+
+/// Multiply by a power of two
+impl Shl<u32> for &BigInt {
+    type Output = BigInt;
+
+    fn shl(self, bits: u32) -> BigInt {
+        let (digits, offset) = ((bits / 32) as usize, bits % 32);
+        let mut magnitude = vec![0u32; digits];
+        let mut carry = 0u32;
+        for &digit in &self.magnitude {
+            let wide = (digit as u64) << offset;
+            magnitude.push(wide as u32 | carry);
+            carry = (wide >> 32) as u32;
+        }
+        magnitude.push(carry);
+        BigInt::from_parts(self.negative, magnitude)
+    }
+}
+
+/// Divide by a power of two, rounding toward negative infinity as `i64` `>>` does
+impl Shr<u32> for &BigInt {
+    type Output = BigInt;
+
+    fn shr(self, bits: u32) -> BigInt {
+        let (digits, offset) = ((bits / 32) as usize, bits % 32);
+        let kept = self.magnitude.get(digits..).unwrap_or_default();
+        let magnitude = (0..kept.len())
+            .map(|i| {
+                let wide = (kept.get(i + 1).copied().unwrap_or(0) as u64) << 32 | kept[i] as u64;
+                (wide >> offset) as u32
+            })
+            .collect();
+        let truncated = BigInt::from_parts(self.negative, magnitude);
+        let dropped_bits = self.magnitude[..digits.min(self.magnitude.len())].iter().any(|&digit| digit != 0)
+            || kept.first().is_some_and(|&digit| digit & ((1u32 << offset) - 1) != 0);
+        if self.negative && dropped_bits {
+            &truncated - &BigInt::from(1i64)
+        } else {
+            truncated
+        }
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Peel off nine decimal digits at a time, least significant first
+        let mut chunks = Vec::new();
+        let mut rest = self.magnitude.clone();
+        while !rest.is_empty() {
+            let (quotient, remainder) = divide_by_digit(&rest, 1_000_000_000);
+            chunks.push(remainder);
+            rest = quotient;
+            while rest.last() == Some(&0) {
+                rest.pop();
+            }
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    /// Parse a decimal integer with an optional leading `-`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("'{}' is not an integer", text));
+        }
+        // Take nine decimal digits at a time, most significant first
+        let chunk_base = BigInt::from(1_000_000_000i64);
+        let first_len = match digits.len() % 9 {
+            0 => 9,
+            len => len,
+        };
+        let mut magnitude = BigInt::default();
+        let mut start = 0;
+        let mut end = first_len;
+        while start < digits.len() {
+            let chunk: i64 = digits[start..end].parse().map_err(|_| format!("'{}' is not an integer", text))?;
+            magnitude = &(&magnitude * &chunk_base) + &BigInt::from(chunk);
+            start = end;
+            end += 9;
+        }
+        Ok(if negative { -&magnitude } else { magnitude })
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (longer, shorter) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(longer.len() + 1);
+    let mut carry = 0u64;
+    for (i, &digit) in longer.iter().enumerate() {
+        let total = digit as u64 + shorter.get(i).copied().unwrap_or(0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// `a - b`, where `a` is at least `b`
+fn subtract_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let mut total = digit as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    difference
+}
+
+/// Two's complement negation in place: invert every digit and add one
+fn negate_words(words: &mut [u32]) {
+    let mut carry = true;
+    for word in words {
+        let (sum, overflowed) = (!*word).overflowing_add(carry as u32);
+        *word = sum;
+        carry = overflowed;
+    }
+}
+
+fn divide_by_digit(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    (quotient, remainder as u32)
+}
+
+/// Long division one bit at a time; `b` must not be zero
+fn divide_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let (quotient, remainder) = divide_by_digit(a, *divisor);
+        return (quotient, vec![remainder]);
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + next bit of a
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for digit in remainder.iter_mut() {
+            let shifted = (*digit as u64) << 1 | carry as u64;
+            *digit = shifted as u32;
+            carry = (shifted >> 32) as u32;
+        }
+        if carry != 0 {
+            remainder.push(carry);
+        }
+        if compare_magnitudes(&remainder, b) != Ordering::Less {
+            remainder = subtract_magnitudes(&remainder, b);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, remainder)
+}
@@ -1,440 +1,904 @@
 //! TJLang Bytecode
-//! 
-//! Advanced bytecode instruction set for the TJLang VM.
+//!
+//! Instruction set for the TJLang VM and the compiler that produces it. Each
+//! function body, lambda body and top-level statement compiles to its own
+//! `Bytecode`. Locals use the slots chosen by the resolver, and operations on
+//! values are carried out by the same interpreter helpers the tree-walking
+//! backend uses, so both backends behave the same. Bytecode serializes, so a
+//! compiled program can be saved and run later (see `compiled`).
 
+use crate::resolver::{Resolution, Slot};
 use crate::values::Value;
-use tjlang_ast::{Expression, Type, SourceSpan};
+use codespan::{FileId, Span};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tjlang_ast::*;
 
 /// Bytecode instruction set
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Instruction {
     // Stack operations
-    Push(Value),
+    /// Push a value from the constant pool
+    Push(usize),
     Pop,
     Dup,
-    Swap,
-    
-    // Arithmetic operations
-    Add,
-    Subtract,
-    Multiply,
-    Divide,
-    Modulo,
-    Power,
-    
-    // Comparison operations
-    Equal,
-    NotEqual,
-    LessThan,
-    GreaterThan,
-    LessThanEqual,
-    GreaterThanEqual,
-    
-    // Logical operations
-    And,
-    Or,
-    Not,
-    
-    // Bitwise operations
-    BitAnd,
-    BitOr,
-    BitXor,
-    BitNot,
-    ShiftLeft,
-    ShiftRight,
-    
-    // Variable operations
-    Load(String),
-    Store(String),
-    LoadLocal(usize),
-    StoreLocal(usize),
-    LoadGlobal(String),
+
+    // Operators, applied through the interpreter so user types dispatch to their impl methods
+    Binary(BinaryOperator),
+    Unary(UnaryOperator),
+    Index,
+    GetField(String),
+
+    // Variable operations. A local that has not been assigned yet falls back to the global
+    // of the same name, and an undefined variable is reported at `span`.
+    LoadLocal { slot: Slot, name: String, span: SourceSpan },
+    LoadGlobal { name: String, span: SourceSpan },
+    StoreLocal(Slot),
     StoreGlobal(String),
-    
+    /// Check the value on top of the stack against the declared type of the variable it
+    /// is about to be stored in, wrapping it when the type is a union
+    CheckType { name: String, declared: Type },
+    /// Pop the value, then the index and container for `Index` and the container for
+    /// `Field`, store the value and push it back. A compound operator combines the
+    /// current value with the new one first.
+    Assign { target: AssignTarget, operator: Option<BinaryOperator>, span: SourceSpan },
+
     // Function operations
-    Call(String, usize), // function name, arg count
-    CallClosure(usize), // arg count
+    /// Pop the keyword argument values, the positional arguments and the callee
+    Call { args: usize, keywords: Vec<String> },
+    /// Pop the keyword argument values, the positional arguments and the receiver
+    CallMethod { name: String, args: usize, keywords: Vec<String> },
+    /// Create a closure from a lambda, capturing the current frames
+    MakeClosure(usize),
     Return,
-    TailCall(String, usize),
-    
+    /// Leave the bytecode with a `break` or `continue` that no loop inside it handles
+    Break,
+    Continue,
+    /// Unwrap `Some`/`Ok`, or return `None`/`Err` early from the enclosing function
+    Propagate(SourceSpan),
+
     // Control flow
-    Jump(usize), // absolute address
-    JumpIf(usize), // jump if top of stack is truthy
-    JumpIfNot(usize), // jump if top of stack is falsy
-    
+    Jump(usize),      // absolute address
+    JumpIfNot(usize), // pop the condition and jump if it is falsy
+
     // Pattern matching
-    Match(usize), // number of arms
-    MatchArm(usize), // arm index
-    MatchGuard,
-    MatchBind(String),
-    MatchDestruct(String, usize), // struct name, field count
-    
+    /// Match the value on top of the stack against a pattern, binding its names in the
+    /// slots of the arm or clause at `clause`, or jump to `otherwise` when it does not match
+    MatchPattern { pattern: usize, clause: SourceSpan, otherwise: usize },
+    /// Pop a value and run a match expression's arms against it
+    MatchExpression(usize),
+
+    // Exceptions
+    Raise,
+    /// Catch values raised before the matching `PopExcept`, continuing at the handler
+    /// address with the raised value pushed
+    SetupExcept(usize),
+    PopExcept,
+    /// A clause caught the raised value
+    EndExcept,
+    /// No clause caught the raised value, so it keeps unwinding
+    Reraise,
+    /// Fail with a runtime error at the current location
+    Fail(String),
+
     // Concurrency
-    Spawn,
-    Send,
-    Receive,
-    Yield,
-    Join,
-    
+    Spawn(usize),
+
     // Data structure operations
-    NewStruct(String, usize), // struct name, field count
-    GetField(String),
-    SetField(String),
-    NewEnum(String, String, usize), // enum name, variant, field count
+    /// Check that a struct literal only names fields the struct declares
+    CheckStruct { name: String, fields: Vec<(String, SourceSpan)> },
+    NewStruct { name: String, fields: Vec<String> },
     NewTuple(usize),
     NewVec(usize),
     NewSet(usize),
-    NewMap(usize),
-    VecPush,
-    VecPop,
-    VecGet(usize),
-    VecSet(usize),
-    SetAdd,
-    SetRemove,
-    SetContains,
-    MapGet,
-    MapSet,
-    MapRemove,
-    MapContains,
-    
-    // Type operations
-    TypeCheck(Type),
-    TypeCast(Type),
-    IsType(Type),
-    
-    // Memory operations
-    Alloc(usize), // size in bytes
-    Dealloc,
-    GcMark,
-    GcSweep,
-    
-    // Debug operations
-    DebugPrint,
-    DebugStack,
-    DebugHeap,
-    Breakpoint,
-    
+    NewMap(usize), // pairs of key and value
+    NewRange { inclusive: bool },
+    /// Join the string forms of the top values, for f-strings
+    Concat(usize),
+
+    // Iteration
+    /// Replace the value on top of the stack with an iterator over it
+    GetIter,
+    /// Push the next value of the iterator on top of the stack, or jump when it is done
+    ForIter(usize),
+
     // Special operations
-    Halt,
     Nop,
 }
 
-/// A complete bytecode program
-#[derive(Debug, Clone)]
-pub struct Bytecode {
-    pub instructions: Vec<Instruction>,
-    pub constants: Vec<Value>,
-    pub functions: Vec<Function>,
-    pub globals: Vec<String>,
+/// What an `Assign` instruction writes to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AssignTarget {
+    Variable { name: String, slot: Option<Slot> },
+    Index,
+    Field(String),
 }
 
-/// Function definition in bytecode
-#[derive(Debug, Clone)]
-pub struct Function {
-    pub name: String,
+/// A lambda whose closures a `MakeClosure` instruction creates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lambda {
     pub params: Vec<String>,
     pub param_types: Vec<Type>,
-    pub return_type: Type,
-    pub start_address: usize,
-    pub local_count: usize,
-    pub is_closure: bool,
+    /// Shared by every closure created from the lambda, so its bytecode is compiled once
+    pub body: Arc<Expression>,
+}
+
+/// A literal in a bytecode's constant pool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Constant {
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl Constant {
+    /// The runtime value of the constant
+    pub fn to_value(&self) -> Value {
+        match self {
+            Constant::None => Value::None,
+            Constant::Bool(b) => Value::Bool(*b),
+            Constant::Int(i) => Value::Int(*i),
+            Constant::Float(f) => Value::Float(*f),
+            Constant::String(s) => Value::String(s.clone()),
+        }
+    }
+
+    /// Whether two constants are interchangeable; unlike `==`, floats compare by their bits
+    /// so `0.0` and `-0.0` stay apart
+    fn same(&self, other: &Constant) -> bool {
+        match (self, other) {
+            (Constant::Float(a), Constant::Float(b)) => a.to_bits() == b.to_bits(),
+            _ => self == other,
+        }
+    }
+}
+
+/// Compiled code for a function body, lambda body or top-level statement
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bytecode {
+    pub instructions: Vec<Instruction>,
+    /// Literals the `Push` instructions refer to
+    pub constants: Vec<Constant>,
+    /// Where each instruction runs in the source. Errors with no more precise
+    /// location point there, as they do in the tree-walking interpreter.
+    pub locations: Vec<Option<SourceSpan>>,
+    pub patterns: Vec<Pattern>,
+    pub lambdas: Vec<Lambda>,
+    /// Expressions that spawned tasks evaluate
+    pub spawns: Vec<Arc<Expression>>,
+    /// Arms of match expressions, which run as their own bytecode with the matched
+    /// value on the stack
+    pub matches: Vec<Arc<Bytecode>>,
 }
 
 impl Bytecode {
-    /// Create a new bytecode program
+    /// Create an empty bytecode program
     pub fn new() -> Self {
-        Self {
-            instructions: Vec::new(),
-            constants: Vec::new(),
-            functions: Vec::new(),
-            globals: Vec::new(),
-        }
+        Self::default()
     }
-    
-    /// Add an instruction to the program
-    pub fn add_instruction(&mut self, instruction: Instruction) -> usize {
+
+    /// Add an instruction, returning its address
+    pub fn add_instruction(&mut self, instruction: Instruction, location: Option<SourceSpan>) -> usize {
         let address = self.instructions.len();
         self.instructions.push(instruction);
+        self.locations.push(location);
         address
     }
-    
-    /// Add a constant value
-    pub fn add_constant(&mut self, value: Value) -> usize {
-        let index = self.constants.len();
-        self.constants.push(value);
-        index
-    }
-    
-    /// Add a function definition
-    pub fn add_function(&mut self, function: Function) {
-        self.functions.push(function);
+
+    /// Add a constant to the pool, reusing an equal one, and return its index
+    pub fn add_constant(&mut self, constant: Constant) -> usize {
+        match self.constants.iter().position(|existing| existing.same(&constant)) {
+            Some(index) => index,
+            None => {
+                self.constants.push(constant);
+                self.constants.len() - 1
+            }
+        }
     }
-    
+
     /// Get an instruction at a specific address
     pub fn get_instruction(&self, address: usize) -> Option<&Instruction> {
         self.instructions.get(address)
     }
-    
-    /// Get a constant by index
-    pub fn get_constant(&self, index: usize) -> Option<&Value> {
-        self.constants.get(index)
-    }
-    
-    /// Find a function by name
-    pub fn find_function(&self, name: &str) -> Option<&Function> {
-        self.functions.iter().find(|f| f.name == name)
-    }
-    
+
     /// Get the total instruction count
     pub fn instruction_count(&self) -> usize {
         self.instructions.len()
     }
 }
 
-impl Default for Bytecode {
-    fn default() -> Self {
-        Self::new()
+/// What kind of code a compiled unit was compiled from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum UnitKind {
+    /// A function or method body
+    Body,
+    /// A lambda body, parameter default, spawned expression or top-level expression
+    Expression,
+    /// A top-level statement
+    Statement,
+}
+
+/// Identifies a compiled unit by the kind and source location of the code it came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UnitKey {
+    pub kind: UnitKind,
+    pub file_id: FileId,
+    pub span: Span,
+}
+
+impl UnitKey {
+    fn new(kind: UnitKind, span: &SourceSpan) -> Self {
+        Self { kind, file_id: span.file_id, span: span.span }
     }
 }
 
+/// Bytecode compiled so far, keyed by the source location of the code it came from
+#[derive(Debug, Clone, Default)]
+pub struct CodeCache {
+    units: HashMap<UnitKey, Arc<Bytecode>>,
+}
+
+impl CodeCache {
+    /// Bytecode for a function or method body, compiling it on first use
+    pub fn block(&mut self, block: &Block, resolution: &Resolution) -> Arc<Bytecode> {
+        self.units
+            .entry(UnitKey::new(UnitKind::Body, &block.span))
+            .or_insert_with(|| Arc::new(BytecodeCompiler::new(resolution).compile_body(block)))
+            .clone()
+    }
+
+    /// Bytecode for an expression such as a lambda body or parameter default. Literals
+    /// have no location to key them by and are compiled each time.
+    pub fn expression(&mut self, expr: &Expression, resolution: &Resolution) -> Arc<Bytecode> {
+        let compile = || Arc::new(BytecodeCompiler::new(resolution).compile_root_expression(expr));
+        match expr.span() {
+            Some(span) => self
+                .units
+                .entry(UnitKey::new(UnitKind::Expression, span))
+                .or_insert_with(compile)
+                .clone(),
+            None => compile(),
+        }
+    }
+
+    /// Bytecode for a top-level statement
+    pub fn statement(&mut self, stmt: &Statement, resolution: &Resolution) -> Arc<Bytecode> {
+        let compile = || Arc::new(BytecodeCompiler::new(resolution).compile_root_statement(stmt));
+        match stmt.span() {
+            Some(span) => self
+                .units
+                .entry(UnitKey::new(UnitKind::Statement, span))
+                .or_insert_with(compile)
+                .clone(),
+            None => compile(),
+        }
+    }
+
+    /// Compile every piece of code in a program ahead of time, including the lambdas
+    /// and spawned expressions inside it
+    pub fn compile_program(&mut self, program: &Program, resolution: &Resolution) {
+        for unit in &program.units {
+            let code = match unit {
+                ProgramUnit::Declaration(decl) | ProgramUnit::Export(ExportDecl::Declaration(decl)) => {
+                    self.compile_declaration(decl, resolution);
+                    continue;
+                }
+                ProgramUnit::Expression(expr) => self.expression(expr, resolution),
+                ProgramUnit::Statement(stmt) => self.statement(stmt, resolution),
+                _ => continue,
+            };
+            self.compile_nested(&code, resolution);
+        }
+    }
+
+    fn compile_declaration(&mut self, decl: &Declaration, resolution: &Resolution) {
+        match decl {
+            Declaration::Function(func) => self.compile_function(&func.params, &func.body, resolution),
+            Declaration::Implementation(impl_block) => {
+                for method in &impl_block.methods {
+                    self.compile_function(&method.params, &method.body, resolution);
+                }
+            }
+            Declaration::Variable(var) => {
+                let code = self.expression(&var.value, resolution);
+                self.compile_nested(&code, resolution);
+            }
+            _ => {}
+        }
+    }
+
+    fn compile_function(&mut self, params: &[Parameter], body: &Block, resolution: &Resolution) {
+        for default in params.iter().filter_map(|param| param.default_value.as_ref()) {
+            let code = self.expression(default, resolution);
+            self.compile_nested(&code, resolution);
+        }
+        let code = self.block(body, resolution);
+        self.compile_nested(&code, resolution);
+    }
+
+    /// Compile the lambda bodies and spawned expressions a piece of bytecode refers to
+    fn compile_nested(&mut self, code: &Bytecode, resolution: &Resolution) {
+        let nested: Vec<&Expression> = code
+            .lambdas
+            .iter()
+            .map(|lambda| &*lambda.body)
+            .chain(code.spawns.iter().map(|spawn| &**spawn))
+            .collect();
+        for expr in nested {
+            let inner = self.expression(expr, resolution);
+            self.compile_nested(&inner, resolution);
+        }
+        for arms in &code.matches {
+            self.compile_nested(arms, resolution);
+        }
+    }
+
+    /// Every compiled unit, in a stable order
+    pub fn units(&self) -> Vec<(UnitKey, Arc<Bytecode>)> {
+        let mut units: Vec<_> = self.units.iter().map(|(key, code)| (*key, code.clone())).collect();
+        units.sort_by_key(|(key, _)| *key);
+        units
+    }
+
+    /// Add bytecode compiled earlier, such as units loaded from a compiled program
+    pub fn insert(&mut self, key: UnitKey, code: Arc<Bytecode>) {
+        self.units.insert(key, code);
+    }
+}
+
+/// Jump targets of the loop a `break` or `continue` belongs to
+struct LoopLabels {
+    continue_target: usize,
+    /// Jumps to patch with the loop's exit address
+    breaks: Vec<usize>,
+    /// Try blocks already open when the loop started
+    handlers: usize,
+}
+
 /// Bytecode compiler that converts AST to bytecode
-pub struct BytecodeCompiler {
+pub struct BytecodeCompiler<'a> {
     bytecode: Bytecode,
-    current_function: Option<String>,
-    local_vars: Vec<String>,
-    label_counter: usize,
+    resolution: &'a Resolution,
+    loops: Vec<LoopLabels>,
+    /// Try blocks open at the current point
+    handlers: usize,
+    /// Location given to the next instruction emitted
+    location: Option<SourceSpan>,
 }
 
-impl BytecodeCompiler {
-    /// Create a new bytecode compiler
-    pub fn new() -> Self {
+impl<'a> BytecodeCompiler<'a> {
+    /// Create a new bytecode compiler using the slots of a resolved program
+    pub fn new(resolution: &'a Resolution) -> Self {
         Self {
             bytecode: Bytecode::new(),
-            current_function: None,
-            local_vars: Vec::new(),
-            label_counter: 0,
+            resolution,
+            loops: Vec::new(),
+            handlers: 0,
+            location: None,
         }
     }
-    
-    /// Compile an expression to bytecode
-    pub fn compile_expression(&mut self, expr: &Expression) -> Result<(), String> {
-        match expr {
-            Expression::Literal(literal) => {
-                let value = self.compile_literal(literal);
-                self.bytecode.add_instruction(Instruction::Push(value));
-            },
-            Expression::Variable(name) => {
-                if let Some(index) = self.local_vars.iter().position(|v| v == name) {
-                    self.bytecode.add_instruction(Instruction::LoadLocal(index));
-                } else {
-                    self.bytecode.add_instruction(Instruction::Load(name.clone()));
-                }
-            },
-            Expression::Binary { left, operator, right, .. } => {
-                self.compile_expression(left)?;
-                self.compile_expression(right)?;
-                self.compile_binary_operator(operator);
-            },
-            Expression::Unary { operator, operand, .. } => {
-                self.compile_expression(operand)?;
-                self.compile_unary_operator(operator);
-            },
-            Expression::Call { callee, args, .. } => {
-                for arg in args {
-                    self.compile_expression(arg)?;
+
+    /// Compile a function body. The bytecode leaves the value of its last statement.
+    pub fn compile_body(mut self, block: &Block) -> Bytecode {
+        self.compile_block(block);
+        self.bytecode
+    }
+
+    /// Compile a single top-level statement
+    pub fn compile_root_statement(mut self, stmt: &Statement) -> Bytecode {
+        self.compile_statement(stmt);
+        self.bytecode
+    }
+
+    /// Compile an expression evaluated on its own
+    pub fn compile_root_expression(mut self, expr: &Expression) -> Bytecode {
+        self.compile_expression(expr);
+        self.bytecode
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        let location = self.location.take();
+        self.bytecode.add_instruction(instruction, location)
+    }
+
+    fn push_constant(&mut self, constant: Constant) {
+        let index = self.bytecode.add_constant(constant);
+        self.emit(Instruction::Push(index));
+    }
+
+    /// Report errors from the next instruction at `span`
+    fn locate(&mut self, span: &SourceSpan) {
+        self.location = Some(span.clone());
+    }
+
+    /// Emit a pending location before a jump target, so jumping back does not set it again
+    fn flush_location(&mut self) {
+        if self.location.is_some() {
+            self.emit(Instruction::Nop);
+        }
+    }
+
+    fn here(&self) -> usize {
+        self.bytecode.instruction_count()
+    }
+
+    /// Point the jump at `address` to the next instruction
+    fn patch(&mut self, address: usize) {
+        let target = self.here();
+        match &mut self.bytecode.instructions[address] {
+            Instruction::Jump(to)
+            | Instruction::JumpIfNot(to)
+            | Instruction::ForIter(to)
+            | Instruction::SetupExcept(to) => *to = target,
+            Instruction::MatchPattern { otherwise, .. } => *otherwise = target,
+            _ => {}
+        }
+    }
+
+    /// Compile a block, leaving the value of its last statement
+    fn compile_block(&mut self, block: &Block) {
+        if block.statements.is_empty() {
+            self.push_constant(Constant::None);
+            return;
+        }
+        for (i, stmt) in block.statements.iter().enumerate() {
+            if i > 0 {
+                self.emit(Instruction::Pop);
+            }
+            self.compile_statement(stmt);
+        }
+    }
+
+    /// Compile a statement, leaving its value
+    fn compile_statement(&mut self, stmt: &Statement) {
+        if let Some(span) = stmt.span() {
+            self.locate(span);
+        }
+        match stmt {
+            Statement::Expression(expr) => self.compile_expression(expr),
+            Statement::Variable(var) => {
+                self.compile_expression(&var.value);
+                self.emit(Instruction::CheckType { name: var.name.clone(), declared: var.var_type.clone() });
+                self.emit(Instruction::Dup);
+                self.compile_store(&var.name, &var.span);
+            }
+            Statement::Return(ret_stmt) => {
+                match &ret_stmt.value {
+                    Some(expr) => self.compile_expression(expr),
+                    None => {
+                        self.push_constant(Constant::None);
+                    }
                 }
-                match callee.as_ref() {
-                    Expression::Variable(name) => {
-                        self.bytecode.add_instruction(Instruction::Call(name.clone(), args.len()));
-                    },
-                    _ => {
-                        self.bytecode.add_instruction(Instruction::CallClosure(args.len()));
+                self.emit(Instruction::Return);
+            }
+            Statement::Break(_) => match self.loops.last() {
+                Some(labels) => {
+                    let open = self.handlers - labels.handlers;
+                    for _ in 0..open {
+                        self.emit(Instruction::PopExcept);
+                    }
+                    let jump = self.emit(Instruction::Jump(0));
+                    if let Some(labels) = self.loops.last_mut() {
+                        labels.breaks.push(jump);
                     }
                 }
+                None => {
+                    self.emit(Instruction::Break);
+                }
             },
-            Expression::If { condition, then_expr, else_expr, .. } => {
-                self.compile_expression(condition)?;
-                let jump_if_false = self.bytecode.add_instruction(Instruction::JumpIfNot(0)); // placeholder
-                self.compile_expression(then_expr)?;
-                let jump_end = self.bytecode.add_instruction(Instruction::Jump(0)); // placeholder
-                
-                // Update the jump address
-                let else_start = self.bytecode.instruction_count();
-                self.bytecode.instructions[jump_if_false] = Instruction::JumpIfNot(else_start);
-                
-                self.compile_expression(else_expr)?;
-                let end_address = self.bytecode.instruction_count();
-                self.bytecode.instructions[jump_end] = Instruction::Jump(end_address);
-            },
-            Expression::Match { expression, arms, .. } => {
-                self.compile_expression(expression)?;
-                self.bytecode.add_instruction(Instruction::Match(arms.len()));
-                
-                for (i, arm) in arms.iter().enumerate() {
-                    self.compile_pattern(&arm.pattern)?;
-                    if let Some(guard) = &arm.guard {
-                        self.compile_expression(guard)?;
-                        self.bytecode.add_instruction(Instruction::MatchGuard);
+            Statement::Continue(_) => match self.loops.last() {
+                Some(labels) => {
+                    let (target, open) = (labels.continue_target, self.handlers - labels.handlers);
+                    for _ in 0..open {
+                        self.emit(Instruction::PopExcept);
                     }
-                    self.bytecode.add_instruction(Instruction::MatchArm(i));
+                    self.emit(Instruction::Jump(target));
                 }
-            },
-            Expression::Spawn { expression, .. } => {
-                self.compile_expression(expression)?;
-                self.bytecode.add_instruction(Instruction::Spawn);
-            },
-            Expression::StructLiteral { name, fields, .. } => {
-                for field in fields {
-                    self.compile_expression(&field.value)?;
+                None => {
+                    self.emit(Instruction::Continue);
                 }
-                self.bytecode.add_instruction(Instruction::NewStruct(name.clone(), fields.len()));
             },
-            Expression::TupleLiteral { elements, .. } => {
-                for element in elements {
-                    self.compile_expression(element)?;
+            Statement::If(if_stmt) => {
+                let mut ends = Vec::new();
+                self.compile_expression(&if_stmt.condition);
+                let mut next = self.emit(Instruction::JumpIfNot(0));
+                self.compile_block(&if_stmt.then_block);
+                ends.push(self.emit(Instruction::Jump(0)));
+                for elif_branch in &if_stmt.elif_branches {
+                    self.patch(next);
+                    self.compile_expression(&elif_branch.condition);
+                    next = self.emit(Instruction::JumpIfNot(0));
+                    self.compile_block(&elif_branch.block);
+                    ends.push(self.emit(Instruction::Jump(0)));
                 }
-                self.bytecode.add_instruction(Instruction::NewTuple(elements.len()));
-            },
-            Expression::VecLiteral { elements, .. } => {
-                for element in elements {
-                    self.compile_expression(element)?;
+                self.patch(next);
+                match &if_stmt.else_block {
+                    Some(else_block) => self.compile_block(else_block),
+                    None => {
+                        self.push_constant(Constant::None);
+                    }
                 }
-                self.bytecode.add_instruction(Instruction::NewVec(elements.len()));
-            },
-            Expression::SetLiteral { elements, .. } => {
-                for element in elements {
-                    self.compile_expression(element)?;
+                for end in ends {
+                    self.patch(end);
                 }
-                self.bytecode.add_instruction(Instruction::NewSet(elements.len()));
-            },
-            Expression::MapLiteral { entries, .. } => {
-                for entry in entries {
-                    self.compile_expression(&entry.key)?;
-                    self.compile_expression(&entry.value)?;
+            }
+            Statement::While(while_stmt) => {
+                self.flush_location();
+                let start = self.here();
+                self.compile_expression(&while_stmt.condition);
+                let exit = self.emit(Instruction::JumpIfNot(0));
+                self.compile_loop_body(&while_stmt.body, start);
+                self.emit(Instruction::Jump(start));
+                self.patch(exit);
+                self.finish_loop();
+            }
+            Statement::DoWhile(do_while_stmt) => {
+                // `continue` starts the body again without checking the condition
+                self.flush_location();
+                let start = self.here();
+                self.compile_loop_body(&do_while_stmt.body, start);
+                self.compile_expression(&do_while_stmt.condition);
+                let exit = self.emit(Instruction::JumpIfNot(0));
+                self.emit(Instruction::Jump(start));
+                self.patch(exit);
+                self.finish_loop();
+            }
+            Statement::For(ForStatement::ForEach { var_name, iterable, body, span, .. }) => {
+                self.compile_expression(iterable);
+                self.emit(Instruction::GetIter);
+                let start = self.emit(Instruction::ForIter(0));
+                self.compile_store(var_name, span);
+                self.compile_loop_body(body, start);
+                self.emit(Instruction::Jump(start));
+                self.patch(start);
+                self.close_loop();
+                // The iterator stays on the stack while the loop runs
+                self.emit(Instruction::Pop);
+                self.push_constant(Constant::None);
+            }
+            Statement::For(ForStatement::CStyle { initializer, condition, increment, body, .. }) => {
+                if let Some(init_stmt) = initializer {
+                    self.compile_statement(init_stmt);
+                    self.emit(Instruction::Pop);
                 }
-                self.bytecode.add_instruction(Instruction::NewMap(entries.len()));
-            },
-            Expression::Lambda { params, body, .. } => {
-                // TODO: Implement closure compilation
-                self.compile_expression(body)?;
-            },
-            Expression::Range { start, end, inclusive, .. } => {
-                self.compile_expression(start)?;
-                self.compile_expression(end)?;
-                if *inclusive {
-                    self.bytecode.add_instruction(Instruction::Push(Value::Bool(true)));
-                } else {
-                    self.bytecode.add_instruction(Instruction::Push(Value::Bool(false)));
+                // `continue` goes straight back to the condition, as in the interpreter
+                self.flush_location();
+                let start = self.here();
+                let exit = condition.as_ref().map(|cond_expr| {
+                    self.compile_expression(cond_expr);
+                    self.emit(Instruction::JumpIfNot(0))
+                });
+                self.compile_loop_body(body, start);
+                if let Some(inc_expr) = increment {
+                    self.compile_expression(inc_expr);
+                    self.emit(Instruction::Pop);
                 }
-                // TODO: Implement range creation
-            },
-            Expression::Index { target, index, .. } => {
-                self.compile_expression(target)?;
-                self.compile_expression(index)?;
-                // TODO: Implement indexing
-            },
-            Expression::Member { target, member, .. } => {
-                self.compile_expression(target)?;
-                self.bytecode.add_instruction(Instruction::GetField(member.clone()));
-            },
+                self.emit(Instruction::Jump(start));
+                if let Some(exit) = exit {
+                    self.patch(exit);
+                }
+                self.finish_loop();
+            }
+            Statement::Block(block) => self.compile_block(block),
+            Statement::Match(match_stmt) => {
+                self.compile_expression(&match_stmt.expression);
+                self.locate(&match_stmt.span);
+                self.compile_arms(&match_stmt.arms);
+            }
+            Statement::Raise(raise_stmt) => {
+                self.compile_expression(&raise_stmt.value);
+                self.locate(&raise_stmt.span);
+                self.emit(Instruction::Raise);
+            }
+            Statement::Try(try_stmt) => self.compile_try(try_stmt),
+            Statement::Pass(_) => {
+                self.push_constant(Constant::None);
+            }
         }
-        Ok(())
-    }
-    
-    fn compile_literal(&self, literal: &tjlang_ast::Literal) -> Value {
-        match literal {
-            tjlang_ast::Literal::Int(i) => Value::Int(*i),
-            tjlang_ast::Literal::Float(f) => Value::Float(*f),
-            tjlang_ast::Literal::String(s) => Value::String(s.clone()),
-            tjlang_ast::Literal::FString(s) => Value::String(s.clone()),
-            tjlang_ast::Literal::FStringInterpolation(parts) => {
-                // TODO: Implement f-string interpolation
-                Value::String("".to_string())
-            },
-            tjlang_ast::Literal::Bool(b) => Value::Bool(*b),
-            tjlang_ast::Literal::None => Value::None,
+    }
+
+    /// Compile the body of a loop starting at `continue_target`; the loop's value is `None`
+    fn compile_loop_body(&mut self, body: &Block, continue_target: usize) {
+        self.loops.push(LoopLabels {
+            continue_target,
+            breaks: Vec::new(),
+            handlers: self.handlers,
+        });
+        self.compile_block(body);
+        self.emit(Instruction::Pop);
+    }
+
+    /// Point the current loop's `break`s here
+    fn close_loop(&mut self) {
+        if let Some(labels) = self.loops.pop() {
+            for jump in labels.breaks {
+                self.patch(jump);
+            }
         }
     }
-    
-    fn compile_binary_operator(&mut self, operator: &tjlang_ast::BinaryOperator) -> usize {
-        match operator {
-            tjlang_ast::BinaryOperator::Add => self.bytecode.add_instruction(Instruction::Add),
-            tjlang_ast::BinaryOperator::Subtract => self.bytecode.add_instruction(Instruction::Subtract),
-            tjlang_ast::BinaryOperator::Multiply => self.bytecode.add_instruction(Instruction::Multiply),
-            tjlang_ast::BinaryOperator::Divide => self.bytecode.add_instruction(Instruction::Divide),
-            tjlang_ast::BinaryOperator::Modulo => self.bytecode.add_instruction(Instruction::Modulo),
-            tjlang_ast::BinaryOperator::Power => self.bytecode.add_instruction(Instruction::Power),
-            tjlang_ast::BinaryOperator::Equal => self.bytecode.add_instruction(Instruction::Equal),
-            tjlang_ast::BinaryOperator::NotEqual => self.bytecode.add_instruction(Instruction::NotEqual),
-            tjlang_ast::BinaryOperator::LessThan => self.bytecode.add_instruction(Instruction::LessThan),
-            tjlang_ast::BinaryOperator::GreaterThan => self.bytecode.add_instruction(Instruction::GreaterThan),
-            tjlang_ast::BinaryOperator::LessThanEqual => self.bytecode.add_instruction(Instruction::LessThanEqual),
-            tjlang_ast::BinaryOperator::GreaterThanEqual => self.bytecode.add_instruction(Instruction::GreaterThanEqual),
-            tjlang_ast::BinaryOperator::And => self.bytecode.add_instruction(Instruction::And),
-            tjlang_ast::BinaryOperator::Or => self.bytecode.add_instruction(Instruction::Or),
-            tjlang_ast::BinaryOperator::Assign => {
-                // TODO: Implement assignment
-                0
-            },
-            _ => {
-                // TODO: Implement other operators
-                0
+
+    /// Close the current loop and leave its value
+    fn finish_loop(&mut self) {
+        self.close_loop();
+        self.push_constant(Constant::None);
+    }
+
+    /// Compile match arms against the value on top of the stack, leaving the value of
+    /// the arm that runs
+    fn compile_arms(&mut self, arms: &[MatchArm]) {
+        let mut ends = Vec::new();
+        for arm in arms {
+            let test = self.compile_pattern(&arm.pattern, &arm.span);
+            let guard = arm.guard.as_ref().map(|guard| {
+                self.compile_expression(guard);
+                self.emit(Instruction::JumpIfNot(0))
+            });
+            self.emit(Instruction::Pop);
+            self.compile_block(&arm.body);
+            ends.push(self.emit(Instruction::Jump(0)));
+            self.patch(test);
+            if let Some(guard) = guard {
+                self.patch(guard);
             }
         }
+        self.emit(Instruction::Fail("No matching pattern found".to_string()));
+        for end in ends {
+            self.patch(end);
+        }
     }
-    
-    fn compile_unary_operator(&mut self, operator: &tjlang_ast::UnaryOperator) -> usize {
-        match operator {
-            tjlang_ast::UnaryOperator::Negate => {
-                self.bytecode.add_instruction(Instruction::Push(Value::Int(0)));
-                self.bytecode.add_instruction(Instruction::Subtract);
-                0
-            },
-            tjlang_ast::UnaryOperator::Not => self.bytecode.add_instruction(Instruction::Not),
-            tjlang_ast::UnaryOperator::BitNot => self.bytecode.add_instruction(Instruction::BitNot),
+
+    fn compile_pattern(&mut self, pattern: &Pattern, clause: &SourceSpan) -> usize {
+        let index = self.bytecode.patterns.len();
+        self.bytecode.patterns.push(pattern.clone());
+        self.emit(Instruction::MatchPattern {
+            pattern: index,
+            clause: clause.clone(),
+            otherwise: 0,
+        })
+    }
+
+    /// Compile a try statement. The handler starts with the raised value on the stack
+    /// and tries each except clause in turn.
+    fn compile_try(&mut self, try_stmt: &TryStatement) {
+        let setup = self.emit(Instruction::SetupExcept(0));
+        self.handlers += 1;
+        self.compile_block(&try_stmt.body);
+        self.handlers -= 1;
+        self.emit(Instruction::PopExcept);
+        let mut ends = vec![self.emit(Instruction::Jump(0))];
+
+        self.patch(setup);
+        for handler in &try_stmt.handlers {
+            let test = handler
+                .pattern
+                .as_ref()
+                .map(|pattern| self.compile_pattern(pattern, &handler.span));
+            self.emit(Instruction::Pop);
+            self.emit(Instruction::EndExcept);
+            self.compile_block(&handler.body);
+            ends.push(self.emit(Instruction::Jump(0)));
+            match test {
+                Some(test) => self.patch(test),
+                // A clause without a pattern catches everything, so later clauses never run
+                None => break,
+            }
+        }
+        self.emit(Instruction::Pop);
+        self.emit(Instruction::Reraise);
+        for end in ends {
+            self.patch(end);
         }
     }
-    
-    fn compile_pattern(&mut self, pattern: &tjlang_ast::Pattern) -> Result<(), String> {
-        match pattern {
-            tjlang_ast::Pattern::Literal(literal) => {
-                let value = self.compile_literal(literal);
-                self.bytecode.add_instruction(Instruction::Push(value));
-                self.bytecode.add_instruction(Instruction::Equal);
-            },
-            tjlang_ast::Pattern::Variable { name, .. } => {
-                self.bytecode.add_instruction(Instruction::MatchBind(name.clone()));
-            },
-            tjlang_ast::Pattern::Constructor { name, fields, .. } => {
+
+    /// Pop the value on top of the stack into the variable declared at `span`
+    fn compile_store(&mut self, name: &str, span: &SourceSpan) {
+        match self.resolution.local(span) {
+            Some(slot) => self.emit(Instruction::StoreLocal(slot)),
+            None => self.emit(Instruction::StoreGlobal(name.to_string())),
+        };
+    }
+
+    /// Compile an expression, leaving its value
+    pub fn compile_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Literal(literal) => self.compile_literal(literal),
+            Expression::Variable { name, span } => {
+                let name = name.clone();
+                let instruction = match self.resolution.local(span) {
+                    Some(slot) => Instruction::LoadLocal { slot, name, span: span.clone() },
+                    None => Instruction::LoadGlobal { name, span: span.clone() },
+                };
+                self.emit(instruction);
+            }
+            Expression::Binary { left, operator, right, span } => {
+                self.locate(span);
+                if operator.is_assignment() {
+                    let target = match left.as_ref() {
+                        Expression::Variable { name, span } => AssignTarget::Variable {
+                            name: name.clone(),
+                            slot: self.resolution.local(span),
+                        },
+                        Expression::Index { target, index, .. } => {
+                            self.compile_expression(target);
+                            self.compile_expression(index);
+                            AssignTarget::Index
+                        }
+                        Expression::Member { target, member, .. } => {
+                            self.compile_expression(target);
+                            AssignTarget::Field(member.clone())
+                        }
+                        _ => {
+                            self.emit(Instruction::Fail(
+                                "Left side of assignment must be a variable, index or field".to_string(),
+                            ));
+                            return;
+                        }
+                    };
+                    self.compile_expression(right);
+                    self.emit(Instruction::Assign {
+                        target,
+                        operator: operator.compound_operator(),
+                        span: span.clone(),
+                    });
+                    return;
+                }
+                self.compile_expression(left);
+                self.compile_expression(right);
+                self.locate(span);
+                self.emit(Instruction::Binary(operator.clone()));
+            }
+            Expression::Unary { operator, operand, span } => {
+                self.compile_expression(operand);
+                self.locate(span);
+                self.emit(Instruction::Unary(operator.clone()));
+            }
+            Expression::Call { callee, args, named_args, span } => {
+                self.locate(span);
+                let method = match callee.as_ref() {
+                    Expression::Member { target, member, .. } => {
+                        self.compile_expression(target);
+                        Some(member)
+                    }
+                    _ => {
+                        self.compile_expression(callee);
+                        None
+                    }
+                };
+                for arg in args {
+                    self.compile_expression(arg);
+                }
+                for named_arg in named_args {
+                    self.compile_expression(&named_arg.value);
+                }
+                let keywords = named_args.iter().map(|arg| arg.name.clone()).collect();
+                self.locate(span);
+                match method {
+                    Some(member) => self.emit(Instruction::CallMethod {
+                        name: member.clone(),
+                        args: args.len(),
+                        keywords,
+                    }),
+                    None => self.emit(Instruction::Call { args: args.len(), keywords }),
+                };
+            }
+            Expression::Member { target, member, .. } => {
+                self.compile_expression(target);
+                self.emit(Instruction::GetField(member.clone()));
+            }
+            Expression::Spawn { expression, span } => {
+                let index = self.bytecode.spawns.len();
+                self.bytecode.spawns.push(Arc::new((**expression).clone()));
+                self.locate(span);
+                self.emit(Instruction::Spawn(index));
+            }
+            Expression::Propagate { expression, span } => {
+                self.compile_expression(expression);
+                self.emit(Instruction::Propagate(span.clone()));
+            }
+            Expression::Index { target, index, span } => {
+                self.compile_expression(target);
+                self.compile_expression(index);
+                self.locate(span);
+                self.emit(Instruction::Index);
+            }
+            Expression::Lambda { params, body, .. } => {
+                let index = self.bytecode.lambdas.len();
+                self.bytecode.lambdas.push(Lambda {
+                    params: params.iter().map(|p| p.name.clone()).collect(),
+                    param_types: params.iter().map(|p| p.param_type.clone()).collect(),
+                    body: Arc::new((**body).clone()),
+                });
+                self.emit(Instruction::MakeClosure(index));
+            }
+            Expression::If { condition, then_expr, else_expr, .. } => {
+                self.compile_expression(condition);
+                let else_jump = self.emit(Instruction::JumpIfNot(0));
+                self.compile_expression(then_expr);
+                let end_jump = self.emit(Instruction::Jump(0));
+                self.patch(else_jump);
+                self.compile_expression(else_expr);
+                self.patch(end_jump);
+            }
+            Expression::Match { expression, arms, .. } => {
+                // The arms run as their own bytecode: a `return` in an arm only ends the
+                // match, and `break` or `continue` cannot reach a loop outside it
+                self.compile_expression(expression);
+                let mut arms_compiler = BytecodeCompiler::new(self.resolution);
+                arms_compiler.compile_arms(arms);
+                let index = self.bytecode.matches.len();
+                self.bytecode.matches.push(Arc::new(arms_compiler.bytecode));
+                self.emit(Instruction::MatchExpression(index));
+            }
+            Expression::StructLiteral { name, fields, span } => {
+                self.locate(span);
+                self.emit(Instruction::CheckStruct {
+                    name: name.clone(),
+                    fields: fields.iter().map(|f| (f.name.clone(), f.span.clone())).collect(),
+                });
                 for field in fields {
-                    self.compile_pattern(field)?;
+                    self.compile_expression(&field.value);
                 }
-                self.bytecode.add_instruction(Instruction::NewEnum(name.clone(), "".to_string(), fields.len()));
-            },
-            tjlang_ast::Pattern::Struct { name, fields, .. } => {
-                for (field_name, field_pattern) in fields {
-                    self.compile_pattern(field_pattern)?;
+                self.emit(Instruction::NewStruct {
+                    name: name.clone(),
+                    fields: fields.iter().map(|f| f.name.clone()).collect(),
+                });
+            }
+            Expression::VecLiteral { elements, .. } => {
+                for element in elements {
+                    self.compile_expression(element);
                 }
-                self.bytecode.add_instruction(Instruction::NewStruct(name.clone(), fields.len()));
-            },
-            tjlang_ast::Pattern::Tuple { patterns, .. } => {
-                for pattern in patterns {
-                    self.compile_pattern(pattern)?;
+                self.emit(Instruction::NewVec(elements.len()));
+            }
+            Expression::SetLiteral { elements, .. } => {
+                for element in elements {
+                    self.compile_expression(element);
                 }
-                self.bytecode.add_instruction(Instruction::NewTuple(patterns.len()));
-            },
-            tjlang_ast::Pattern::Wildcard(_) => {
-                // Wildcard matches anything, no instruction needed
-            },
-            _ => {
-                // TODO: Implement other pattern types
+                self.emit(Instruction::NewSet(elements.len()));
+            }
+            Expression::MapLiteral { entries, .. } => {
+                for entry in entries {
+                    self.compile_expression(&entry.key);
+                    self.compile_expression(&entry.value);
+                }
+                self.emit(Instruction::NewMap(entries.len()));
+            }
+            Expression::TupleLiteral { elements, .. } => {
+                for element in elements {
+                    self.compile_expression(element);
+                }
+                self.emit(Instruction::NewTuple(elements.len()));
+            }
+            Expression::Range { start, end, inclusive, .. } => {
+                self.compile_expression(start);
+                self.compile_expression(end);
+                self.emit(Instruction::NewRange { inclusive: *inclusive });
             }
         }
-        Ok(())
     }
-    
-    /// Get the compiled bytecode
-    pub fn into_bytecode(self) -> Bytecode {
-        self.bytecode
+
+    fn compile_literal(&mut self, literal: &Literal) {
+        let constant = match literal {
+            Literal::Int(i) => Constant::Int(*i),
+            Literal::Float(f) => Constant::Float(*f),
+            Literal::String(s) | Literal::FString(s) => Constant::String(s.clone()),
+            Literal::FStringInterpolation(parts) => {
+                for part in parts {
+                    match part {
+                        FStringPart::Text(text) => {
+                            self.push_constant(Constant::String(text.clone()));
+                        }
+                        FStringPart::Expression(expr) => self.compile_expression(expr),
+                    }
+                }
+                self.emit(Instruction::Concat(parts.len()));
+                return;
+            }
+            Literal::Bool(b) => Constant::Bool(*b),
+            Literal::None => Constant::None,
+        };
+        self.push_constant(constant);
     }
 }
@@ -0,0 +1,100 @@
+//! Compiled programs
+//!
+//! `tjlang build` saves a parsed and compiled program to a `.tjc` file so
+//! `tjlang run` can start it later without parsing or analysing the source
+//! again. A file holds the magic bytes `TJC\0`, the format version as a
+//! little-endian `u16`, and then a `CompiledProgram` in the encoding of the
+//! `serialization` module.
+//!
+//! This is not a standalone bytecode format: the file embeds the program's
+//! syntax tree next to its function table. Loading it re-runs the resolver
+//! and the declaration passes over that tree to register types, impls and
+//! globals before every function body, lambda and top-level statement runs
+//! from the saved bytecode. Units also keep syntax-tree nodes of their own for
+//! the patterns, lambdas and spawned expressions they refer to.
+//!
+//! Each unit in the function table carries its own constant pool and a span
+//! table giving the source location of every instruction, so runtime errors
+//! still point into the original source when it is present and unchanged.
+
+use crate::bytecode::{Bytecode, CodeCache, UnitKey};
+use crate::resolver;
+use crate::serialization;
+use codespan::FileId;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tjlang_ast::Program;
+
+/// Bytes every compiled program file starts with
+pub const MAGIC: &[u8; 4] = b"TJC\0";
+
+/// Version of the compiled program format. Bump it whenever the layout of
+/// `CompiledProgram`, the bytecode or the syntax tree changes.
+pub const FORMAT_VERSION: u16 = 3;
+
+/// A program compiled ahead of time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledProgram {
+    /// Path of the source file, as given when it was built
+    pub source_path: String,
+    /// SHA-256 hash of the source
+    pub source_hash: [u8; 32],
+    /// File the program's source spans refer to
+    pub file_id: FileId,
+    /// Syntax tree the declarations are registered from when the program loads
+    pub program: Program,
+    /// Bytecode of every function body, lambda and top-level statement
+    pub functions: Vec<(UnitKey, Arc<Bytecode>)>,
+}
+
+impl CompiledProgram {
+    /// Compile all the code in a parsed program
+    pub fn compile(program: Program, source_path: String, source: &str, file_id: FileId) -> Self {
+        let resolution = resolver::resolve_program(&program);
+        let mut code = CodeCache::default();
+        code.compile_program(&program, &resolution);
+        Self {
+            source_path,
+            source_hash: hash_source(source),
+            file_id,
+            program,
+            functions: code.units(),
+        }
+    }
+
+    /// Whether the program was compiled from this source
+    pub fn matches_source(&self, source: &str) -> bool {
+        hash_source(source) == self.source_hash
+    }
+
+    /// Encode the program as the contents of a `.tjc` file
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        let body = serialization::to_bytes(self).map_err(|e| format!("Failed to encode program: {}", e))?;
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    /// Decode the contents of a `.tjc` file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let header_len = MAGIC.len() + 2;
+        if bytes.len() < header_len || &bytes[..MAGIC.len()] != MAGIC {
+            return Err("Not a compiled TJLang program".to_string());
+        }
+        let version = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+        if version != FORMAT_VERSION {
+            return Err(format!(
+                "Compiled program has format version {}, but this tjlang reads version {}; rebuild it",
+                version, FORMAT_VERSION
+            ));
+        }
+        serialization::from_bytes(&bytes[header_len..]).map_err(|e| format!("Corrupt compiled program: {}", e))
+    }
+}
+
+/// SHA-256 hash of a program's source
+pub fn hash_source(source: &str) -> [u8; 32] {
+    Sha256::digest(source.as_bytes()).into()
+}
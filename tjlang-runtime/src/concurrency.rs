@@ -7,11 +7,20 @@ use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
 use std::time::{Duration, Instant};
 use std::future::Future;
-use std::task::Poll;
 use std::pin::Pin;
 use std::task::{Context, Waker, Poll as TaskPoll};
+use crate::interpreter::RuntimeError;
 use crate::values::Value;
 
+/// Outcome of a task: its value, or the error that ended it
+pub type TaskResult = Result<Value, RuntimeError>;
+
+/// Methods TJLang code can call on a task handle
+pub const TASK_METHODS: &[&str] = &["join", "await", "is_done"];
+
+/// Methods TJLang code can call on a channel
+pub const CHANNEL_METHODS: &[&str] = &["send", "recv", "try_recv"];
+
 /// Concurrency runtime for managing tasks and channels
 pub struct ConcurrencyRuntime {
     /// Active tasks
@@ -21,7 +30,10 @@ pub struct ConcurrencyRuntime {
     next_task_id: u64,
     
     /// Global channel registry
-    channels: HashMap<String, Channel>,
+    channels: HashMap<u64, Channel>,
+    
+    /// Channel ID counter
+    next_channel_id: u64,
     
     /// Task scheduler
     scheduler: TaskScheduler,
@@ -32,19 +44,19 @@ pub struct ConcurrencyRuntime {
 
 /// A concurrent task
 #[derive(Debug)]
-struct Task {
+pub struct Task {
     pub id: u64,
     pub name: String,
     pub status: TaskStatus,
-    pub handle: Option<thread::JoinHandle<Value>>,
+    pub handle: Option<thread::JoinHandle<TaskResult>>,
     pub waker: Option<Waker>,
-    pub result: Option<Value>,
+    pub result: Option<TaskResult>,
     pub created_at: Instant,
 }
 
 /// Task status
 #[derive(Debug, Clone, PartialEq)]
-enum TaskStatus {
+pub enum TaskStatus {
     Pending,
     Running,
     Blocked,
@@ -52,19 +64,20 @@ enum TaskStatus {
     Failed,
 }
 
-/// A channel for communication between tasks
+/// A channel for communication between tasks. The receiver is shared so a
+/// blocking receive does not hold the runtime lock.
 #[derive(Debug)]
-struct Channel {
+pub struct Channel {
     pub name: String,
     pub sender: mpsc::Sender<Value>,
-    pub receiver: mpsc::Receiver<Value>,
+    pub receiver: Arc<Mutex<mpsc::Receiver<Value>>>,
     pub buffer_size: usize,
     pub message_count: usize,
 }
 
 /// Task scheduler for managing task execution
 #[derive(Debug)]
-struct TaskScheduler {
+pub struct TaskScheduler {
     /// Ready queue
     ready_queue: Vec<u64>,
     
@@ -72,16 +85,16 @@ struct TaskScheduler {
     blocked_tasks: HashMap<u64, BlockReason>,
     
     /// Maximum concurrent tasks
-    max_concurrent: usize,
+    pub max_concurrent: usize,
     
     /// Current running tasks
-    running_tasks: usize,
+    pub running_tasks: usize,
 }
 
 /// Reason why a task is blocked
 #[derive(Debug, Clone)]
-enum BlockReason {
-    WaitingForChannel(String),
+pub enum BlockReason {
+    WaitingForChannel(u64),
     WaitingForTask(u64),
     Sleeping(Duration),
     WaitingForIO,
@@ -89,7 +102,7 @@ enum BlockReason {
 
 /// Concurrency statistics
 #[derive(Debug, Default)]
-struct ConcurrencyStats {
+pub struct ConcurrencyStats {
     pub total_tasks: u64,
     pub completed_tasks: u64,
     pub failed_tasks: u64,
@@ -104,6 +117,7 @@ impl ConcurrencyRuntime {
             tasks: HashMap::new(),
             next_task_id: 0,
             channels: HashMap::new(),
+            next_channel_id: 0,
             scheduler: TaskScheduler::new(),
             stats: ConcurrencyStats::default(),
         }
@@ -134,12 +148,15 @@ impl ConcurrencyRuntime {
     /// Spawn a task with a specific function
     pub fn spawn<F>(&mut self, name: String, func: F) -> Result<TaskHandle, String>
     where
-        F: FnOnce() -> Value + Send + 'static,
+        F: FnOnce() -> TaskResult + Send + 'static,
     {
         let id = self.next_task_id;
         self.next_task_id += 1;
         
-        let handle = thread::spawn(func);
+        let handle = thread::Builder::new()
+            .name(name.clone())
+            .spawn(func)
+            .map_err(|e| format!("Failed to spawn task: {}", e))?;
         
         let task = Task {
             id,
@@ -160,80 +177,94 @@ impl ConcurrencyRuntime {
     /// Create a new channel
     pub fn create_channel(&mut self, name: String, buffer_size: usize) -> Result<ChannelHandle, String> {
         let (sender, receiver) = mpsc::channel();
+        let id = self.next_channel_id;
+        self.next_channel_id += 1;
         
         let channel = Channel {
-            name: name.clone(),
+            name,
             sender,
-            receiver,
+            receiver: Arc::new(Mutex::new(receiver)),
             buffer_size,
             message_count: 0,
         };
         
-        self.channels.insert(name.clone(), channel);
+        self.channels.insert(id, channel);
         self.stats.active_channels += 1;
         
-        Ok(ChannelHandle { name })
+        Ok(ChannelHandle { id })
     }
     
     /// Send a message through a channel
-    pub fn send(&mut self, channel_name: &str, value: Value) -> Result<(), String> {
-        if let Some(channel) = self.channels.get_mut(channel_name) {
+    pub fn send(&mut self, channel_id: u64, value: Value) -> Result<(), String> {
+        if let Some(channel) = self.channels.get_mut(&channel_id) {
             channel.sender.send(value).map_err(|_| "Channel send failed".to_string())?;
             channel.message_count += 1;
             self.stats.total_messages += 1;
             Ok(())
         } else {
-            Err(format!("Channel '{}' not found", channel_name))
+            Err(format!("Channel {} not found", channel_id))
         }
     }
     
+    /// Get a channel's receiving end, so a blocking receive can happen outside the runtime lock
+    pub fn receiver(&self, channel_id: u64) -> Result<Arc<Mutex<mpsc::Receiver<Value>>>, String> {
+        self.channels
+            .get(&channel_id)
+            .map(|channel| Arc::clone(&channel.receiver))
+            .ok_or_else(|| format!("Channel {} not found", channel_id))
+    }
+    
     /// Receive a message from a channel
-    pub fn receive(&mut self, channel_name: &str) -> Result<Value, String> {
-        if let Some(channel) = self.channels.get_mut(channel_name) {
-            channel.receiver.recv().map_err(|_| "Channel receive failed".to_string())
-        } else {
-            Err(format!("Channel '{}' not found", channel_name))
-        }
+    pub fn receive(&mut self, channel_id: u64) -> Result<Value, String> {
+        let receiver = self.receiver(channel_id)?;
+        let receiver = receiver.lock().map_err(|_| "Channel receiver poisoned".to_string())?;
+        receiver.recv().map_err(|_| "Channel receive failed".to_string())
     }
     
     /// Try to receive a message without blocking
-    pub fn try_receive(&mut self, channel_name: &str) -> Result<Option<Value>, String> {
-        if let Some(channel) = self.channels.get_mut(channel_name) {
-            match channel.receiver.try_recv() {
-                Ok(value) => Ok(Some(value)),
-                Err(mpsc::TryRecvError::Empty) => Ok(None),
-                Err(mpsc::TryRecvError::Disconnected) => Err("Channel disconnected".to_string()),
-            }
-        } else {
-            Err(format!("Channel '{}' not found", channel_name))
+    pub fn try_receive(&mut self, channel_id: u64) -> Result<Option<Value>, String> {
+        let receiver = self.receiver(channel_id)?;
+        let receiver = receiver.lock().map_err(|_| "Channel receiver poisoned".to_string())?;
+        match receiver.try_recv() {
+            Ok(value) => Ok(Some(value)),
+            Err(mpsc::TryRecvError::Empty) => Ok(None),
+            Err(mpsc::TryRecvError::Disconnected) => Err("Channel disconnected".to_string()),
         }
     }
     
-    /// Wait for a task to complete
-    pub fn join_task(&mut self, task_id: u64) -> Result<Value, String> {
+    /// Take a running task's thread handle so it can be joined outside the runtime lock.
+    /// Returns `None` once the task has already been joined.
+    pub fn take_task_handle(&mut self, task_id: u64) -> Result<Option<thread::JoinHandle<TaskResult>>, String> {
+        self.tasks
+            .get_mut(&task_id)
+            .map(|task| task.handle.take())
+            .ok_or_else(|| format!("Task {} not found", task_id))
+    }
+    
+    /// Record the outcome of a joined task; a panicked task has no outcome
+    pub fn finish_task(&mut self, task_id: u64, result: Option<TaskResult>) {
         if let Some(task) = self.tasks.get_mut(&task_id) {
-            if let Some(handle) = task.handle.take() {
-                match handle.join() {
-                    Ok(result) => {
-                        task.status = TaskStatus::Completed;
-                        task.result = Some(result.clone());
-                        self.stats.completed_tasks += 1;
-                        Ok(result)
-                    },
-                    Err(_) => {
-                        task.status = TaskStatus::Failed;
-                        self.stats.failed_tasks += 1;
-                        Err("Task execution failed".to_string())
-                    }
-                }
+            if matches!(result, Some(Ok(_))) {
+                task.status = TaskStatus::Completed;
+                self.stats.completed_tasks += 1;
             } else {
-                Err("Task handle not available".to_string())
+                task.status = TaskStatus::Failed;
+                self.stats.failed_tasks += 1;
             }
-        } else {
-            Err(format!("Task {} not found", task_id))
+            task.result = result;
         }
     }
     
+    /// Wait for a task to complete. Joining an already joined task returns its recorded outcome.
+    pub fn join_task(&mut self, task_id: u64) -> Result<TaskResult, String> {
+        if let Some(handle) = self.take_task_handle(task_id)? {
+            self.finish_task(task_id, handle.join().ok());
+        }
+        self.get_task_result(task_id)
+            .cloned()
+            .ok_or_else(|| format!("Task {} panicked", task_id))
+    }
+    
     /// Check if a task is completed
     pub fn is_task_completed(&self, task_id: u64) -> bool {
         self.tasks.get(&task_id)
@@ -241,18 +272,28 @@ impl ConcurrencyRuntime {
             .unwrap_or(false)
     }
     
+    /// Check if a task has stopped running, successfully or not
+    pub fn is_task_finished(&self, task_id: u64) -> bool {
+        self.tasks.get(&task_id)
+            .map(|task| match &task.handle {
+                Some(handle) => handle.is_finished(),
+                None => true,
+            })
+            .unwrap_or(false)
+    }
+    
     /// Get task result if completed
-    pub fn get_task_result(&self, task_id: u64) -> Option<&Value> {
+    pub fn get_task_result(&self, task_id: u64) -> Option<&TaskResult> {
         self.tasks.get(&task_id)
             .and_then(|task| task.result.as_ref())
     }
     
     /// Block a task on a channel
-    pub fn block_on_channel(&mut self, task_id: u64, channel_name: String) {
+    pub fn block_on_channel(&mut self, task_id: u64, channel_id: u64) {
         if let Some(task) = self.tasks.get_mut(&task_id) {
             task.status = TaskStatus::Blocked;
         }
-        self.scheduler.blocked_tasks.insert(task_id, BlockReason::WaitingForChannel(channel_name));
+        self.scheduler.blocked_tasks.insert(task_id, BlockReason::WaitingForChannel(channel_id));
     }
     
     /// Block a task waiting for another task
@@ -288,11 +329,16 @@ impl ConcurrencyRuntime {
             if let Some(task) = self.tasks.get_mut(&task_id) {
                 if let Some(handle) = task.handle.take() {
                     match handle.join() {
-                        Ok(result) => {
+                        Ok(Ok(value)) => {
                             task.status = TaskStatus::Completed;
-                            task.result = Some(result);
+                            task.result = Some(Ok(value));
                             self.stats.completed_tasks += 1;
                         },
+                        Ok(Err(error)) => {
+                            task.status = TaskStatus::Failed;
+                            task.result = Some(Err(error));
+                            self.stats.failed_tasks += 1;
+                        },
                         Err(_) => {
                             task.status = TaskStatus::Failed;
                             self.stats.failed_tasks += 1;
@@ -306,11 +352,10 @@ impl ConcurrencyRuntime {
         let mut unblocked_tasks = Vec::new();
         for (task_id, reason) in &self.scheduler.blocked_tasks {
             match reason {
-                BlockReason::WaitingForChannel(channel_name) => {
-                    if let Some(channel) = self.channels.get(channel_name) {
-                        if channel.receiver.try_recv().is_ok() {
-                            unblocked_tasks.push(*task_id);
-                        }
+                BlockReason::WaitingForChannel(channel_id) => {
+                    // The channel still exists; a blocked receive wakes itself when data arrives
+                    if self.channels.contains_key(channel_id) {
+                        unblocked_tasks.push(*task_id);
                     }
                 },
                 BlockReason::WaitingForTask(target_id) => {
@@ -318,7 +363,7 @@ impl ConcurrencyRuntime {
                         unblocked_tasks.push(*task_id);
                     }
                 },
-                BlockReason::Sleeping(duration) => {
+                BlockReason::Sleeping(_duration) => {
                     // TODO: Implement sleep logic
                 },
                 BlockReason::WaitingForIO => {
@@ -371,9 +416,9 @@ pub struct TaskHandle {
 }
 
 /// Handle to a channel
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct ChannelHandle {
-    pub name: String,
+    pub id: u64,
 }
 
 /// Async task future
@@ -383,7 +428,7 @@ pub struct AsyncTask {
 }
 
 impl Future for AsyncTask {
-    type Output = Value;
+    type Output = TaskResult;
     
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> TaskPoll<Self::Output> {
         let mut runtime = self.runtime.lock().unwrap();
@@ -392,7 +437,7 @@ impl Future for AsyncTask {
             if let Some(result) = runtime.get_task_result(self.task_id) {
                 TaskPoll::Ready(result.clone())
             } else {
-                TaskPoll::Ready(Value::None)
+                TaskPoll::Ready(Ok(Value::None))
             }
         } else {
             // Store waker for later
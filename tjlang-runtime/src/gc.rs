@@ -1,340 +1,523 @@
 //! TJLang Garbage Collector
-//! 
-//! Advanced garbage collector with generational collection and concurrent marking.
+//!
+//! Containers (structs, vectors, sets, maps and module globals) and the frames
+//! closures capture are reference counted, so they are freed as soon as the last
+//! handle to them goes away. Reference counting cannot free containers that refer
+//! to each other in a cycle; this collector reclaims those.
+//!
+//! Every container is registered with the collector of the thread that allocates
+//! it. A collection counts how many references to each registered object come
+//! from other registered objects. An object with more handles than that is also
+//! held from outside the heap (by a variable in an environment or call frame, an
+//! operand on the VM stack, or a value native code is working with) and is a
+//! root. Everything reachable from the roots survives. The contents of every
+//! other object are cleared, which breaks their cycles and lets reference
+//! counting free them.
+//!
+//! Objects start in the young generation. Collections triggered by allocation
+//! only examine young objects, treating references from old objects as roots,
+//! and promote the survivors. A full collection examines both generations; it
+//! runs once enough objects have been promoted, or when a script calls
+//! `GC.collect()`.
+//!
+//! Tasks share containers with the interpreter that spawned them, so nothing is
+//! collected while a task spawned from the thread, or from its tasks, is running.
 
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock, Weak};
 use std::time::{Duration, Instant};
-use crate::values::Value;
+
+use crate::values::{Frame, Value};
+
+/// Young objects allocated before the young generation is collected
+const YOUNG_THRESHOLD: usize = 1000;
+
+thread_local! {
+    static COLLECTOR: RefCell<GarbageCollector> = RefCell::new(GarbageCollector::new());
+
+    /// Tasks running in the family of tasks this thread belongs to
+    static RUNNING_TASKS: RefCell<Arc<AtomicUsize>> = RefCell::new(Arc::new(AtomicUsize::new(0)));
+}
 
 /// Garbage collector with generational collection
 pub struct GarbageCollector {
     /// Young generation (recently allocated objects)
-    young_gen: Vec<GcObject>,
-    
-    /// Old generation (long-lived objects)
-    old_gen: Vec<GcObject>,
-    
-    /// Object ID counter
-    next_id: usize,
-    
-    /// Root references (globals, stack, etc.)
-    roots: HashSet<usize>,
-    
+    young_gen: Vec<Handle>,
+
+    /// Old generation (objects that survived a collection)
+    old_gen: Vec<Handle>,
+
     /// Collection statistics
     stats: GcStats,
-    
-    /// Collection threshold
-    threshold: usize,
-    
-    /// Collection frequency
-    collection_interval: Duration,
-    last_collection: Instant,
-}
 
-/// A garbage collected object
-#[derive(Debug, Clone)]
-struct GcObject {
-    pub id: usize,
-    pub value: Value,
-    pub generation: Generation,
-    pub marked: bool,
-    pub references: Vec<usize>,
-    pub size: usize,
-}
+    /// Objects promoted since the last full collection
+    promoted: usize,
 
-/// Generation of an object
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum Generation {
-    Young,
-    Old,
+    /// Old objects that survived the last full collection
+    old_survivors: usize,
 }
 
 /// Garbage collection statistics
-#[derive(Debug, Default)]
-struct GcStats {
+#[derive(Debug, Clone, Default)]
+pub struct GcStats {
     pub total_collections: usize,
+    pub full_collections: usize,
     pub objects_collected: usize,
+    /// Estimated size of the contents of the collected objects
     pub bytes_freed: usize,
     pub collection_time: Duration,
+    /// Live objects in the young generation
+    pub young_objects: usize,
+    /// Live objects in the old generation
+    pub old_objects: usize,
 }
 
-impl GarbageCollector {
-    /// Create a new garbage collector
-    pub fn new() -> Self {
-        Self {
-            young_gen: Vec::new(),
-            old_gen: Vec::new(),
-            next_id: 0,
-            roots: HashSet::new(),
-            stats: GcStats::default(),
-            threshold: 1000, // Start collecting after 1000 objects
-            collection_interval: Duration::from_millis(100),
-            last_collection: Instant::now(),
-        }
+/// Handle to a registered object, which does not keep it alive
+pub enum Handle {
+    Fields(Weak<RwLock<HashMap<String, Value>>>),
+    Vec(Weak<RwLock<Vec<Value>>>),
+    Set(Weak<RwLock<HashSet<Value>>>),
+    Map(Weak<RwLock<HashMap<Value, Value>>>),
+    Frame(Weak<Frame>),
+}
+
+/// Contents of a container the collector can trace
+pub trait Traceable: Sized {
+    fn handle(contents: &Arc<RwLock<Self>>) -> Handle;
+}
+
+impl Traceable for HashMap<String, Value> {
+    fn handle(contents: &Arc<RwLock<Self>>) -> Handle {
+        Handle::Fields(Arc::downgrade(contents))
     }
-    
-    /// Allocate a new object
-    pub fn allocate(&mut self, value: Value) -> usize {
-        let id = self.next_id;
-        self.next_id += 1;
-        
-        let size = self.calculate_size(&value);
-        let object = GcObject {
-            id,
-            value,
-            generation: Generation::Young,
-            marked: false,
-            references: Vec::new(),
-            size,
-        };
-        
-        self.young_gen.push(object);
-        
-        // Check if we need to collect
-        if self.should_collect() {
-            self.collect();
-        }
-        
-        id
+}
+
+impl Traceable for Vec<Value> {
+    fn handle(contents: &Arc<RwLock<Self>>) -> Handle {
+        Handle::Vec(Arc::downgrade(contents))
     }
-    
-    /// Get an object by ID
-    pub fn get(&self, id: usize) -> Option<&Value> {
-        // Search young generation first
-        if let Some(object) = self.young_gen.iter().find(|o| o.id == id) {
-            return Some(&object.value);
+}
+
+impl Traceable for HashSet<Value> {
+    fn handle(contents: &Arc<RwLock<Self>>) -> Handle {
+        Handle::Set(Arc::downgrade(contents))
+    }
+}
+
+impl Traceable for HashMap<Value, Value> {
+    fn handle(contents: &Arc<RwLock<Self>>) -> Handle {
+        Handle::Map(Arc::downgrade(contents))
+    }
+}
+
+impl Handle {
+    fn upgrade(&self) -> Option<GcObject> {
+        match self {
+            Handle::Fields(weak) => weak.upgrade().map(GcObject::Fields),
+            Handle::Vec(weak) => weak.upgrade().map(GcObject::Vec),
+            Handle::Set(weak) => weak.upgrade().map(GcObject::Set),
+            Handle::Map(weak) => weak.upgrade().map(GcObject::Map),
+            Handle::Frame(weak) => weak.upgrade().map(GcObject::Frame),
         }
-        
-        // Search old generation
-        if let Some(object) = self.old_gen.iter().find(|o| o.id == id) {
-            return Some(&object.value);
+    }
+
+    fn is_live(&self) -> bool {
+        match self {
+            Handle::Fields(weak) => weak.strong_count() > 0,
+            Handle::Vec(weak) => weak.strong_count() > 0,
+            Handle::Set(weak) => weak.strong_count() > 0,
+            Handle::Map(weak) => weak.strong_count() > 0,
+            Handle::Frame(weak) => weak.strong_count() > 0,
         }
-        
-        None
     }
-    
-    /// Get a mutable reference to an object
-    pub fn get_mut(&mut self, id: usize) -> Option<&mut Value> {
-        // Search young generation first
-        if let Some(object) = self.young_gen.iter_mut().find(|o| o.id == id) {
-            return Some(&mut object.value);
+}
+
+/// A registered object, kept alive while a collection examines it
+enum GcObject {
+    Fields(Arc<RwLock<HashMap<String, Value>>>),
+    Vec(Arc<RwLock<Vec<Value>>>),
+    Set(Arc<RwLock<HashSet<Value>>>),
+    Map(Arc<RwLock<HashMap<Value, Value>>>),
+    Frame(Arc<Frame>),
+}
+
+impl GcObject {
+    fn handle(&self) -> Handle {
+        match self {
+            GcObject::Fields(object) => Handle::Fields(Arc::downgrade(object)),
+            GcObject::Vec(object) => Handle::Vec(Arc::downgrade(object)),
+            GcObject::Set(object) => Handle::Set(Arc::downgrade(object)),
+            GcObject::Map(object) => Handle::Map(Arc::downgrade(object)),
+            GcObject::Frame(object) => Handle::Frame(Arc::downgrade(object)),
         }
-        
-        // Search old generation
-        if let Some(object) = self.old_gen.iter_mut().find(|o| o.id == id) {
-            return Some(&mut object.value);
+    }
+
+    /// Address identifying the object, as in `Shared::address`
+    fn address(&self) -> usize {
+        match self {
+            GcObject::Fields(object) => Arc::as_ptr(object) as *const () as usize,
+            GcObject::Vec(object) => Arc::as_ptr(object) as *const () as usize,
+            GcObject::Set(object) => Arc::as_ptr(object) as *const () as usize,
+            GcObject::Map(object) => Arc::as_ptr(object) as *const () as usize,
+            GcObject::Frame(object) => frame_address(object),
         }
-        
-        None
     }
-    
-    /// Add a root reference
-    pub fn add_root(&mut self, id: usize) {
-        self.roots.insert(id);
+
+    /// Number of handles to the object, including the collector's own
+    fn strong_count(&self) -> usize {
+        match self {
+            GcObject::Fields(object) => Arc::strong_count(object),
+            GcObject::Vec(object) => Arc::strong_count(object),
+            GcObject::Set(object) => Arc::strong_count(object),
+            GcObject::Map(object) => Arc::strong_count(object),
+            GcObject::Frame(object) => Arc::strong_count(object),
+        }
     }
-    
-    /// Remove a root reference
-    pub fn remove_root(&mut self, id: usize) {
-        self.roots.remove(&id);
+
+    /// Visit the address of every object this one refers to. Returns false,
+    /// without visiting anything, if the contents are locked.
+    fn trace(&self, visit: &mut dyn FnMut(usize)) -> bool {
+        match self {
+            GcObject::Fields(object) => match object.try_read() {
+                Ok(fields) => fields.values().for_each(|v| trace_value(v, visit)),
+                Err(_) => return false,
+            },
+            GcObject::Vec(object) => match object.try_read() {
+                Ok(items) => items.iter().for_each(|v| trace_value(v, visit)),
+                Err(_) => return false,
+            },
+            GcObject::Set(object) => match object.try_read() {
+                Ok(items) => items.iter().for_each(|v| trace_value(v, visit)),
+                Err(_) => return false,
+            },
+            GcObject::Map(object) => match object.try_read() {
+                Ok(entries) => entries.iter().for_each(|(k, v)| {
+                    trace_value(k, visit);
+                    trace_value(v, visit);
+                }),
+                Err(_) => return false,
+            },
+            GcObject::Frame(frame) => frame.iter().flatten().for_each(|v| trace_value(v, visit)),
+        }
+        true
     }
-    
-    /// Perform garbage collection
-    pub fn collect(&mut self) {
-        let start_time = Instant::now();
-        
-        // Mark phase
-        self.mark();
-        
-        // Sweep phase
-        self.sweep();
-        
-        // Promote surviving young objects to old generation
-        self.promote();
-        
-        // Update statistics
-        let collection_time = start_time.elapsed();
-        self.stats.total_collections += 1;
-        self.stats.collection_time = collection_time;
-        self.last_collection = start_time;
-        
-        // Adjust threshold based on collection efficiency
-        self.adjust_threshold();
+
+    /// Drop the contents of an unreachable object, returning their estimated size.
+    /// Frames can't be modified; they are freed once the closures holding them are.
+    fn clear(&self) -> usize {
+        match self {
+            GcObject::Fields(object) => match object.try_write() {
+                Ok(mut fields) => {
+                    let fields = std::mem::take(&mut *fields);
+                    fields.values().map(calculate_size).sum()
+                }
+                Err(_) => 0,
+            },
+            GcObject::Vec(object) => match object.try_write() {
+                Ok(mut items) => std::mem::take(&mut *items).iter().map(calculate_size).sum(),
+                Err(_) => 0,
+            },
+            GcObject::Set(object) => match object.try_write() {
+                Ok(mut items) => std::mem::take(&mut *items).iter().map(calculate_size).sum(),
+                Err(_) => 0,
+            },
+            GcObject::Map(object) => match object.try_write() {
+                Ok(mut entries) => std::mem::take(&mut *entries)
+                    .iter()
+                    .map(|(k, v)| calculate_size(k) + calculate_size(v))
+                    .sum(),
+                Err(_) => 0,
+            },
+            GcObject::Frame(frame) => frame.iter().flatten().map(calculate_size).sum(),
+        }
     }
-    
-    /// Mark all reachable objects
-    fn mark(&mut self) {
-        // Clear all marks
-        for object in &mut self.young_gen {
-            object.marked = false;
+}
+
+fn frame_address(frame: &Arc<Frame>) -> usize {
+    Arc::as_ptr(frame) as *const () as usize
+}
+
+/// Visit the objects a value holds, looking inside values that own their
+/// contents (tuples, enum variants and unions)
+fn trace_value(value: &Value, visit: &mut dyn FnMut(usize)) {
+    match value {
+        Value::Struct { fields, .. } => visit(fields.address()),
+        Value::Vec(items) => visit(items.address()),
+        Value::Set(items) => visit(items.address()),
+        Value::Map(entries) => visit(entries.address()),
+        Value::Tuple(elements) | Value::Enum { fields: elements, .. } => {
+            elements.iter().for_each(|v| trace_value(v, visit))
         }
-        for object in &mut self.old_gen {
-            object.marked = false;
+        Value::Union { value, .. } => trace_value(value, visit),
+        Value::Function { globals: Some(globals), .. } => visit(globals.address()),
+        Value::Closure { captured, globals, .. } => {
+            captured.iter().for_each(|frame| visit(frame_address(frame)));
+            visit(globals.address());
         }
-        
-        // Mark from roots
-        let root_ids: Vec<usize> = self.roots.iter().cloned().collect();
-        for root_id in root_ids {
-            self.mark_object(root_id);
+        _ => {}
+    }
+}
+
+impl GarbageCollector {
+    /// Create a new garbage collector
+    pub fn new() -> Self {
+        Self {
+            young_gen: Vec::new(),
+            old_gen: Vec::new(),
+            stats: GcStats::default(),
+            promoted: 0,
+            old_survivors: 0,
         }
     }
-    
-    /// Mark an object and all its references
-    fn mark_object(&mut self, id: usize) {
-        // Find and mark the object
-        let mut references = Vec::new();
-        
-        if let Some(object) = self.young_gen.iter_mut().find(|o| o.id == id) {
-            if object.marked {
-                return; // Already marked
-            }
-            object.marked = true;
-            references = object.references.clone();
-        } else if let Some(object) = self.old_gen.iter_mut().find(|o| o.id == id) {
-            if object.marked {
-                return; // Already marked
+
+    /// Check if the young generation should be collected
+    fn should_collect(&self) -> bool {
+        self.young_gen.len() >= YOUNG_THRESHOLD
+    }
+
+    /// Check if the old generation has grown enough to collect it too
+    fn should_collect_full(&self) -> bool {
+        self.promoted > YOUNG_THRESHOLD.max(self.old_survivors / 4)
+    }
+
+    /// Find the unreachable objects of the young generation, or of both
+    /// generations for a full collection, and promote the rest
+    fn find_garbage(&mut self, full: bool) -> Vec<GcObject> {
+        let mut handles = std::mem::take(&mut self.young_gen);
+        let young_count = handles.len();
+        if full {
+            handles.append(&mut self.old_gen);
+        }
+
+        // Objects that can't be upgraded were already freed by reference counting
+        let objects: Vec<(GcObject, bool)> = handles
+            .iter()
+            .enumerate()
+            .filter_map(|(i, handle)| handle.upgrade().map(|object| (object, i < young_count)))
+            .collect();
+        drop(handles);
+        let index: HashMap<usize, usize> = objects
+            .iter()
+            .enumerate()
+            .map(|(i, (object, _))| (object.address(), i))
+            .collect();
+
+        let mut internal = vec![0usize; objects.len()];
+        let mut references = vec![Vec::new(); objects.len()];
+        let mut reachable = vec![false; objects.len()];
+        let mut pending = Vec::new();
+        for (i, (object, _)) in objects.iter().enumerate() {
+            let edges = &mut references[i];
+            let traced = object.trace(&mut |address| {
+                if let Some(&j) = index.get(&address) {
+                    internal[j] += 1;
+                    edges.push(j);
+                }
+            });
+            // A locked object is in use, and its references are unknown
+            if !traced {
+                reachable[i] = true;
+                pending.push(i);
             }
-            object.marked = true;
-            references = object.references.clone();
         }
-        
-        // Mark all references
-        for ref_id in references {
-            self.mark_object(ref_id);
+
+        // Roots have handles besides those held by the objects examined
+        for (i, (object, _)) in objects.iter().enumerate() {
+            if !reachable[i] && object.strong_count() - 1 > internal[i] {
+                reachable[i] = true;
+                pending.push(i);
+            }
         }
-    }
-    
-    /// Sweep unmarked objects
-    fn sweep(&mut self) {
-        let mut young_survivors = Vec::new();
-        let mut bytes_freed = 0;
-        let mut objects_collected = 0;
-        
-        // Sweep young generation
-        for object in self.young_gen.drain(..) {
-            if object.marked {
-                young_survivors.push(object);
-            } else {
-                bytes_freed += object.size;
-                objects_collected += 1;
+        while let Some(i) = pending.pop() {
+            for &j in &references[i] {
+                if !reachable[j] {
+                    reachable[j] = true;
+                    pending.push(j);
+                }
             }
         }
-        
-        // Sweep old generation
-        let mut old_survivors = Vec::new();
-        for object in self.old_gen.drain(..) {
-            if object.marked {
-                old_survivors.push(object);
+
+        let mut garbage = Vec::new();
+        for ((object, young), reachable) in objects.into_iter().zip(reachable) {
+            if reachable {
+                if young {
+                    self.promoted += 1;
+                }
+                self.old_gen.push(object.handle());
             } else {
-                bytes_freed += object.size;
-                objects_collected += 1;
+                garbage.push(object);
             }
         }
-        
-        self.young_gen = young_survivors;
-        self.old_gen = old_survivors;
-        
-        // Update statistics
+        if full {
+            self.promoted = 0;
+            self.old_survivors = self.old_gen.len();
+        }
+        garbage
+    }
+
+    /// Forget objects already freed by reference counting, without collecting.
+    /// Used while tasks are running, so the generations don't grow unbounded.
+    fn discard_freed(&mut self, full: bool) {
+        self.young_gen.retain(Handle::is_live);
+        self.promoted += self.young_gen.len();
+        self.old_gen.append(&mut self.young_gen);
+        if full {
+            self.old_gen.retain(Handle::is_live);
+            self.promoted = 0;
+            self.old_survivors = self.old_gen.len();
+        }
+    }
+
+    /// Record the results of a collection
+    fn record(&mut self, full: bool, objects_collected: usize, bytes_freed: usize, time: Duration) {
+        self.stats.total_collections += 1;
+        if full {
+            self.stats.full_collections += 1;
+        }
         self.stats.objects_collected += objects_collected;
         self.stats.bytes_freed += bytes_freed;
+        self.stats.collection_time += time;
     }
-    
-    /// Promote surviving young objects to old generation
-    fn promote(&mut self) {
-        let mut to_promote = Vec::new();
-        
-        for object in self.young_gen.drain(..) {
-            if object.marked {
-                let mut promoted = object;
-                promoted.generation = Generation::Old;
-                to_promote.push(promoted);
-            }
+
+    /// Get garbage collection statistics
+    pub fn stats(&self) -> GcStats {
+        GcStats {
+            young_objects: self.young_gen.iter().filter(|h| h.is_live()).count(),
+            old_objects: self.old_gen.iter().filter(|h| h.is_live()).count(),
+            ..self.stats.clone()
         }
-        
-        self.old_gen.extend(to_promote);
     }
-    
-    /// Check if we should collect
-    fn should_collect(&self) -> bool {
-        let total_objects = self.young_gen.len() + self.old_gen.len();
-        total_objects > self.threshold || 
-        self.last_collection.elapsed() > self.collection_interval
+}
+
+impl Default for GarbageCollector {
+    fn default() -> Self {
+        Self::new()
     }
-    
-    /// Adjust collection threshold based on efficiency
-    fn adjust_threshold(&mut self) {
-        if self.stats.total_collections > 0 {
-            let efficiency = self.stats.objects_collected as f64 / self.stats.total_collections as f64;
-            if efficiency < 0.1 {
-                // Low efficiency, increase threshold
-                self.threshold = (self.threshold as f64 * 1.5) as usize;
-            } else if efficiency > 0.5 {
-                // High efficiency, decrease threshold
-                self.threshold = (self.threshold as f64 * 0.8) as usize;
+}
+
+/// Register a newly allocated container with this thread's collector,
+/// collecting garbage if enough objects have been allocated since the last time
+pub(crate) fn register(handle: Handle) {
+    let collect = COLLECTOR
+        .try_with(|collector| match collector.try_borrow_mut() {
+            Ok(mut collector) => {
+                collector.young_gen.push(handle);
+                collector.should_collect().then(|| collector.should_collect_full())
             }
-        }
+            Err(_) => None,
+        })
+        .ok()
+        .flatten();
+    if let Some(full) = collect {
+        collect_garbage(full);
     }
-    
-    /// Calculate the size of a value
-    fn calculate_size(&self, value: &Value) -> usize {
-        match value {
-            Value::Int(_) => 8,
-            Value::Float(_) => 8,
-            Value::Bool(_) => 1,
-            Value::String(s) => s.len() + 8, // String length + overhead
-            Value::None => 0,
-            Value::Struct { fields, .. } => {
-                fields.iter().map(|(_, v)| self.calculate_size(v)).sum::<usize>() + 8
-            },
-            Value::Enum { fields, .. } => {
-                fields.iter().map(|v| self.calculate_size(v)).sum::<usize>() + 8
-            },
-            Value::Tuple(elements) => {
-                elements.iter().map(|v| self.calculate_size(v)).sum::<usize>() + 8
-            },
-            Value::Vec(elements) => {
-                elements.iter().map(|v| self.calculate_size(v)).sum::<usize>() + 8
-            },
-            Value::Set(elements) => {
-                elements.iter().map(|v| self.calculate_size(v)).sum::<usize>() + 8
-            },
-            Value::Map(entries) => {
-                entries.iter().map(|(k, v)| self.calculate_size(k) + self.calculate_size(v)).sum::<usize>() + 8
-            },
-            Value::Function { .. } => 16, // Function overhead
-            Value::Closure { .. } => 16, // Closure overhead
-            Value::Channel { .. } => 16, // Channel overhead
-            Value::Task { .. } => 16, // Task overhead
-            Value::Reference(_) => 8, // Reference overhead
-            Value::Type(_) => 8, // Type overhead
-        }
-    }
-    
-    /// Get the total number of objects
-    pub fn object_count(&self) -> usize {
-        self.young_gen.len() + self.old_gen.len()
+}
+
+/// Register the frame a closure captures
+pub(crate) fn register_frame(frame: &Arc<Frame>) {
+    register(Handle::Frame(Arc::downgrade(frame)));
+}
+
+/// Collect unreachable objects in both generations, returning how many were
+/// collected
+pub fn collect() -> usize {
+    collect_garbage(true)
+}
+
+fn collect_garbage(full: bool) -> usize {
+    if tasks_running() {
+        let _ = COLLECTOR.try_with(|collector| {
+            if let Ok(mut collector) = collector.try_borrow_mut() {
+                collector.discard_freed(full);
+            }
+        });
+        return 0;
     }
-    
-    /// Get the total memory usage
-    pub fn memory_usage(&self) -> usize {
-        self.young_gen.iter().map(|o| o.size).sum::<usize>() +
-        self.old_gen.iter().map(|o| o.size).sum::<usize>()
+    let start = Instant::now();
+    let garbage = COLLECTOR
+        .try_with(|collector| {
+            collector
+                .try_borrow_mut()
+                .map(|mut collector| collector.find_garbage(full))
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+
+    // Clear the garbage after releasing the collector
+    let objects_collected = garbage.len();
+    let bytes_freed = garbage.iter().map(GcObject::clear).sum();
+    drop(garbage);
+    let _ = COLLECTOR.try_with(|collector| {
+        if let Ok(mut collector) = collector.try_borrow_mut() {
+            collector.record(full, objects_collected, bytes_freed, start.elapsed());
+        }
+    });
+    objects_collected
+}
+
+/// Statistics of this thread's collector
+pub fn stats() -> GcStats {
+    COLLECTOR
+        .try_with(|collector| collector.borrow().stats())
+        .unwrap_or_default()
+}
+
+fn tasks_running() -> bool {
+    RUNNING_TASKS
+        .try_with(|running| running.borrow().load(Ordering::SeqCst) > 0)
+        .unwrap_or(true)
+}
+
+/// Counts a spawned task as running until dropped
+pub(crate) struct RunningTask(Arc<AtomicUsize>);
+
+impl RunningTask {
+    /// Count a task spawned from this thread
+    pub(crate) fn start() -> Self {
+        let running = RUNNING_TASKS.with(|running| Arc::clone(&running.borrow()));
+        running.fetch_add(1, Ordering::SeqCst);
+        RunningTask(running)
     }
-    
-    /// Get garbage collection statistics
-    pub fn stats(&self) -> &GcStats {
-        &self.stats
+
+    /// Join the spawning thread's family of tasks; call on the task's own thread
+    pub(crate) fn enter(&self) {
+        RUNNING_TASKS.with(|running| *running.borrow_mut() = Arc::clone(&self.0));
     }
-    
-    /// Force a full collection
-    pub fn force_collect(&mut self) {
-        self.collect();
+}
+
+impl Drop for RunningTask {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
     }
 }
 
-impl Default for GarbageCollector {
-    fn default() -> Self {
-        Self::new()
+/// Estimate the size of a value
+fn calculate_size(value: &Value) -> usize {
+    match value {
+        Value::Int(_) => 8,
+        Value::BigInt(i) => i.to_string().len() / 2 + 24,
+        Value::Float(_) => 8,
+        Value::Bool(_) => 1,
+        Value::String(s) => s.len() + 8, // String length + overhead
+        Value::None => 0,
+        // Shared containers are counted when they are collected themselves
+        Value::Struct { .. } | Value::Vec(_) | Value::Set(_) | Value::Map(_) => 8,
+        Value::Enum { fields, .. } => fields.iter().map(calculate_size).sum::<usize>() + 8,
+        Value::Tuple(elements) => elements.iter().map(calculate_size).sum::<usize>() + 8,
+        Value::Union { value, .. } => calculate_size(value) + 8,
+        Value::Range { .. } => 24,
+        Value::Iterator(_) => 16,
+        Value::Regex(regex) => regex.pattern().len() + 16,
+        Value::Generator(_) => 16,
+        Value::Native(_) => 16,
+        Value::Function { .. } => 16, // Function overhead
+        Value::Closure { .. } => 16,  // Closure overhead
+        Value::EnumConstructor { .. } => 16,
+        Value::Channel { .. } => 16,  // Channel overhead
+        Value::Task { .. } => 16,     // Task overhead
+        Value::Reference(_) => 8,     // Reference overhead
+        Value::Type(_) => 8,          // Type overhead
     }
 }
@@ -1,9 +1,22 @@
 //! TJLang Interpreter
 //!
 //! A real interpreter that works with the TJLang AST.
+use crate::bigint::BigInt;
+use crate::bytecode::CodeCache;
+use crate::compiled::CompiledProgram;
+use crate::concurrency::{ConcurrencyRuntime, CHANNEL_METHODS, TASK_METHODS};
+use crate::gc;
+use crate::iterators::{self, IteratorContext, IteratorRef, IteratorState};
+use crate::module_system::{ExportItem, ModuleSystem};
+use crate::pattern_matching::{MatchResult, PatternContext, PatternMatcher};
+use crate::resolver::{self, Resolution, Slot};
+use crate::stdlib::json::TypeDeclarations;
+use crate::stdlib::random::Generator;
 use crate::stdlib_integration::StdlibRegistry;
-use crate::values::Value;
+use crate::values::{Frame, Globals, Value};
+use crate::vm::VirtualMachine;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
 use tjlang_ast::*;
 use tjlang_diagnostics::debug_println;
 use codespan::{FileId, Span};
@@ -25,6 +38,56 @@ pub enum ExecutionResult {
     Return(Value),
     Break,
     Continue,
+    /// A value raised with `raise`, unwinding until an `except` clause catches it
+    Raise(Value),
+}
+
+/// A location an assignment writes to, with its container already evaluated
+pub(crate) enum Place {
+    Variable { name: String, slot: Option<Slot> },
+    Index { target: Value, index: Value },
+    Field { target: Value, name: String },
+}
+
+/// A function call that an error unwound through
+#[derive(Debug, Clone)]
+pub struct StackFrame {
+    pub function: String,
+    /// Where the function was called from
+    pub file_id: FileId,
+    pub span: Span,
+    /// The arguments it was called with, such as `n = 3, name = "ada"`
+    pub arguments: String,
+}
+
+/// How deeply calls may nest before the interpreter reports a stack overflow
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// Stack left when evaluation moves to a new segment. Nested statements and
+/// expressions recurse without a call, so the depth limit alone cannot keep the
+/// thread's stack from overflowing; the segments are freed as evaluation returns.
+const STACK_RED_ZONE: usize = 1024 * 1024;
+/// Size of each stack segment evaluation grows into
+const STACK_SEGMENT_SIZE: usize = 8 * 1024 * 1024;
+
+/// How function bodies and top-level code are executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// Walk the syntax tree directly
+    #[default]
+    Interpreter,
+    /// Compile to bytecode and run it on the virtual machine
+    Vm,
+}
+
+/// What happens when integer arithmetic overflows the range of an `i64`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntOverflow {
+    /// Raise a runtime error
+    #[default]
+    Error,
+    /// Continue with an arbitrary-precision integer
+    Promote,
 }
 
 /// Runtime error with location information
@@ -33,11 +96,59 @@ pub struct RuntimeError {
     pub message: String,
     pub file_id: FileId,
     pub span: Span,
+    /// The raised value when this error is an uncaught `raise`
+    pub raised: Option<Box<Value>>,
+    /// Calls the error unwound through, innermost first
+    pub stack: Vec<StackFrame>,
+    /// The `Err`/`None` value a `?` is returning early from the enclosing function
+    pub propagated: Option<Box<Value>>,
 }
 
 impl RuntimeError {
     pub fn new(message: String, file_id: FileId, span: Span) -> Self {
-        Self { message, file_id, span }
+        Self {
+            message,
+            file_id,
+            span,
+            raised: None,
+            stack: Vec::new(),
+            propagated: None,
+        }
+    }
+
+    /// Create an error for a raised value that has left the function that raised it
+    pub fn raised(value: Value, file_id: FileId, span: Span) -> Self {
+        let mut error = Self::new(format!("Uncaught exception: {}", value.to_string()), file_id, span);
+        error.raised = Some(Box::new(value));
+        error
+    }
+
+    /// Create an early return for `?`; the message is only seen when no function catches it
+    pub fn propagated(value: Value, file_id: FileId, span: Span) -> Self {
+        let mut error = Self::new("Cannot use '?' outside of a function".to_string(), file_id, span);
+        error.propagated = Some(Box::new(value));
+        error
+    }
+
+    /// The value an `except` clause sees for this error: the raised value, or the
+    /// message of a runtime or native error as a string. An early return from `?`
+    /// is not an error and cannot be caught.
+    pub fn caught_value(&self) -> Option<Value> {
+        if self.propagated.is_some() {
+            return None;
+        }
+        Some(match &self.raised {
+            Some(value) => (**value).clone(),
+            None => Value::String(self.message.clone()),
+        })
+    }
+
+    /// Turn an early return from `?` back into the returning function's result
+    pub fn catch_propagated(self) -> Result<Value, RuntimeError> {
+        match self.propagated {
+            Some(value) => Ok(*value),
+            None => Err(self),
+        }
     }
 }
 
@@ -49,61 +160,201 @@ impl std::fmt::Display for RuntimeError {
 
 impl std::error::Error for RuntimeError {}
 
-/// Runtime environment for variable storage
+/// Runtime environment for variable storage. Locals live in the slots the
+/// resolver assigned them; every other name is a global of the running module.
 #[derive(Debug, Clone)]
 pub struct Environment {
-    variables: HashMap<String, Value>,
-    parent: Option<Box<Environment>>,
+    globals: Globals,
+    /// Slots of the running call, or of top-level code outside any call
+    frame: Frame,
+    /// Frames a running closure captured, innermost first
+    enclosing: Vec<Arc<Frame>>,
 }
 
 impl Environment {
     pub fn new() -> Self {
-        Self {
-            variables: HashMap::new(),
-            parent: None,
-        }
+        Self::for_call(Globals::new(), Vec::new())
     }
 
-    pub fn with_parent(parent: Environment) -> Self {
+    /// The environment a call runs in: an empty frame over the given globals
+    fn for_call(globals: Globals, enclosing: Vec<Arc<Frame>>) -> Self {
         Self {
-            variables: HashMap::new(),
-            parent: Some(Box::new(parent)),
+            globals,
+            frame: Vec::new(),
+            enclosing,
         }
     }
 
+    /// Define a global, or replace its value
     pub fn define(&mut self, name: String, value: Value) {
-        self.variables.insert(name, value);
+        self.globals.set(name, value);
     }
 
-    pub fn get(&self, name: &str) -> Option<&Value> {
-        if let Some(value) = self.variables.get(name) {
-            Some(value)
-        } else if let Some(parent) = &self.parent {
-            parent.get(name)
-        } else {
-            None
+    /// Look up a global by name
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.globals.get(name)
+    }
+
+    pub fn globals(&self) -> Globals {
+        self.globals.clone()
+    }
+
+    /// Value in a local slot, if it has been assigned
+    fn local(&self, slot: Slot) -> Option<&Value> {
+        let frame = match slot.depth {
+            0 => &self.frame,
+            depth => self.enclosing.get(depth - 1)?,
+        };
+        frame.get(slot.index)?.as_ref()
+    }
+
+    pub(crate) fn set_local(&mut self, slot: Slot, value: Value) {
+        let frame = match slot.depth {
+            0 => &mut self.frame,
+            // Closures capture by value, so this writes to the closure's own copy
+            depth => match self.enclosing.get_mut(depth - 1) {
+                Some(frame) => Arc::make_mut(frame),
+                None => return,
+            },
+        };
+        if frame.len() <= slot.index {
+            frame.resize(slot.index + 1, None);
         }
+        frame[slot.index] = Some(value);
     }
 
-    pub fn set(&mut self, name: &str, value: Value) -> bool {
-        if self.variables.contains_key(name) {
-            self.variables.insert(name.to_string(), value);
-            true
-        } else if let Some(parent) = &mut self.parent {
-            parent.set(name, value)
-        } else {
-            false
+    /// Snapshot the frames visible here, for a closure created in this environment
+    fn capture(&self) -> Vec<Arc<Frame>> {
+        let mut captured = Vec::with_capacity(self.enclosing.len() + 1);
+        let frame = Arc::new(self.frame.clone());
+        gc::register_frame(&frame);
+        captured.push(frame);
+        captured.extend(self.enclosing.iter().cloned());
+        captured
+    }
+}
+
+/// A method from an `impl` block. It runs over the globals of the module that
+/// declared it, wherever it is called from.
+#[derive(Debug, Clone)]
+struct ImplMethod {
+    decl: MethodDecl,
+    globals: Globals,
+}
+
+/// What a call replaces while it runs, put back by `restore_environment`
+struct SavedScope {
+    environment: Environment,
+    /// Address of the globals of the module whose types were visible
+    types_module: usize,
+    type_params: Vec<String>,
+}
+
+/// The types visible to one module: those it declares and those it imports
+#[derive(Debug, Clone, Default)]
+struct TypeScope {
+    structs: HashMap<String, StructDecl>,
+    enums: HashMap<String, EnumDecl>,
+    interfaces: HashMap<String, InterfaceDecl>,
+    type_aliases: HashMap<String, Type>,
+    /// Methods registered through `impl` blocks, keyed by type name then method name
+    impls: HashMap<String, HashMap<String, ImplMethod>>,
+    /// Interfaces implemented by each type name
+    implemented_interfaces: HashMap<String, Vec<String>>,
+}
+
+impl TypeScope {
+    /// The named types, with their methods and the interfaces they implement
+    fn exported(&self, names: &[String]) -> TypeScope {
+        let mut exported = TypeScope::default();
+        for name in names {
+            if let Some(decl) = self.structs.get(name) {
+                exported.structs.insert(name.clone(), decl.clone());
+            }
+            if let Some(decl) = self.enums.get(name) {
+                exported.enums.insert(name.clone(), decl.clone());
+            }
+            if let Some(decl) = self.interfaces.get(name) {
+                exported.interfaces.insert(name.clone(), decl.clone());
+            }
+            if let Some(alias) = self.type_aliases.get(name) {
+                exported.type_aliases.insert(name.clone(), alias.clone());
+            }
+            if let Some(methods) = self.impls.get(name) {
+                exported.impls.insert(name.clone(), methods.clone());
+            }
+            if let Some(interfaces) = self.implemented_interfaces.get(name) {
+                exported.implemented_interfaces.insert(name.clone(), interfaces.clone());
+            }
+        }
+        exported
+    }
+
+    /// Whether the scope declares a type of this name
+    fn declares(&self, name: &str) -> bool {
+        self.structs.contains_key(name)
+            || self.enums.contains_key(name)
+            || self.interfaces.contains_key(name)
+            || self.type_aliases.contains_key(name)
+    }
+
+    /// Add the types another module exports
+    fn import(&mut self, imported: &TypeScope) {
+        self.structs.extend(imported.structs.clone());
+        self.enums.extend(imported.enums.clone());
+        self.interfaces.extend(imported.interfaces.clone());
+        self.type_aliases.extend(imported.type_aliases.clone());
+        for (type_name, methods) in &imported.impls {
+            self.impls.entry(type_name.clone()).or_default().extend(methods.clone());
+        }
+        for (type_name, interfaces) in &imported.implemented_interfaces {
+            let implemented = self.implemented_interfaces.entry(type_name.clone()).or_default();
+            for interface in interfaces {
+                if !implemented.contains(interface) {
+                    implemented.push(interface.clone());
+                }
+            }
         }
     }
 }
 
 /// TJLang Interpreter
 pub struct Interpreter {
-    environment: Environment,
+    pub(crate) environment: Environment,
+    /// Slots of the local variables in every program run so far
+    resolution: Arc<Resolution>,
     functions: HashMap<String, FunctionDecl>,
+    /// Types of the module whose code is running
+    types: TypeScope,
+    /// Address of the globals of the module `types` belongs to
+    types_module: usize,
+    /// Types of the other modules, keyed by the address of their globals
+    module_types: HashMap<usize, TypeScope>,
+    /// Types each loaded module exports, by module name
+    exported_types: HashMap<String, TypeScope>,
     stdlib: StdlibRegistry,
+    /// Tasks and channels, shared with every task spawned by this program
+    concurrency: Arc<Mutex<ConcurrencyRuntime>>,
+    /// Modules loaded through `import`
+    modules: ModuleSystem,
     current_file_id: Option<FileId>,
     current_span: Option<Span>,
+    /// Number of function calls currently running
+    call_depth: usize,
+    max_call_depth: usize,
+    backend: Backend,
+    int_overflow: IntOverflow,
+    /// Check declared variable, parameter and return types as the program runs
+    runtime_typecheck: bool,
+    /// Type parameters the running code may name in declared types: the running
+    /// function's generic parameters, or `Self` in a method
+    type_params: Vec<String>,
+    /// Bytecode compiled so far for the VM backend
+    code: CodeCache,
+    /// The error a function called from native code raised, until the native call returns
+    callback_error: Option<RuntimeError>,
+    /// Default random number generator, shared with every task spawned by this program
+    random: Generator,
 }
 
 impl Interpreter {
@@ -111,22 +362,345 @@ impl Interpreter {
         debug_println!(" Registering stdlib functions...");
         debug_println!("[DEBUG] DEBUG: Interpreter::new() called");
         let environment = Environment::new();
+        let types_module = environment.globals.address();
         let functions = HashMap::new();
         let stdlib = StdlibRegistry::new();
 
         let mut interpreter = Self {
             environment,
+            resolution: Arc::default(),
             functions,
+            types: TypeScope::default(),
+            types_module,
+            module_types: HashMap::new(),
+            exported_types: HashMap::new(),
             stdlib,
+            concurrency: Arc::new(Mutex::new(ConcurrencyRuntime::new())),
+            modules: ModuleSystem::new(),
             current_file_id: None,
             current_span: None,
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            backend: Backend::default(),
+            int_overflow: IntOverflow::default(),
+            runtime_typecheck: true,
+            type_params: Vec::new(),
+            code: CodeCache::default(),
+            callback_error: None,
+            random: Generator::from_entropy(),
         };
         interpreter.register_stdlib_functions();
+        interpreter.register_builtin_enums();
+        interpreter.register_builtin_interfaces();
         debug_println!("[DEBUG] Interpreter created successfully (stdlib enabled)");
 
         interpreter
     }
 
+    /// Create the interpreter a spawned task runs on: a copy of the current call's
+    /// locals and of the declarations, sharing this program's globals, tasks and channels
+    fn fork(&self) -> Interpreter {
+        Interpreter {
+            environment: self.environment.clone(),
+            resolution: Arc::clone(&self.resolution),
+            functions: self.functions.clone(),
+            types: self.types.clone(),
+            types_module: self.types_module,
+            module_types: self.module_types.clone(),
+            exported_types: HashMap::new(),
+            stdlib: self.stdlib.clone(),
+            concurrency: Arc::clone(&self.concurrency),
+            // Imports have already run; tasks only see their results
+            modules: ModuleSystem::new(),
+            current_file_id: self.current_file_id,
+            current_span: self.current_span,
+            call_depth: 0,
+            max_call_depth: self.max_call_depth,
+            backend: self.backend,
+            int_overflow: self.int_overflow,
+            runtime_typecheck: self.runtime_typecheck,
+            type_params: self.type_params.clone(),
+            code: self.code.clone(),
+            callback_error: None,
+            random: self.random.clone(),
+        }
+    }
+
+    /// Switch to the environment a call runs in, along with the types of the module
+    /// its globals belong to and the type parameters it may name. Returns what
+    /// `restore_environment` switches back to.
+    fn swap_environment(&mut self, environment: Environment, type_params: Vec<String>) -> SavedScope {
+        let types_module = self.switch_types(environment.globals.address());
+        SavedScope {
+            environment: std::mem::replace(&mut self.environment, environment),
+            types_module,
+            type_params: std::mem::replace(&mut self.type_params, type_params),
+        }
+    }
+
+    fn restore_environment(&mut self, saved: SavedScope) {
+        self.environment = saved.environment;
+        self.switch_types(saved.types_module);
+        self.type_params = saved.type_params;
+    }
+
+    /// Make the types of a module, identified by the address of its globals, the
+    /// visible ones. Returns the module whose types were visible before.
+    fn switch_types(&mut self, module: usize) -> usize {
+        let previous = self.types_module;
+        if module != previous {
+            let types = self.module_types.remove(&module).unwrap_or_default();
+            let outgoing = std::mem::replace(&mut self.types, types);
+            self.module_types.insert(previous, outgoing);
+            self.types_module = module;
+        }
+        previous
+    }
+
+    /// Limit how deeply calls may nest. Deeper recursion fails with a stack overflow
+    /// error; the thread running the interpreter needs enough stack for this many calls.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Choose how the program runs: by walking the syntax tree, or as bytecode on the VM
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+    }
+
+    /// Choose whether integer overflow is an error or promotes to an arbitrary-precision integer
+    pub fn set_int_overflow(&mut self, int_overflow: IntOverflow) {
+        self.int_overflow = int_overflow;
+    }
+
+    /// Choose whether declared types are checked at runtime. Without the checks,
+    /// values are bound as they are and union declarations don't wrap them.
+    pub fn set_runtime_typecheck(&mut self, enabled: bool) {
+        self.runtime_typecheck = enabled;
+    }
+
+    /// Seed the default random number generator, so that every source of randomness
+    /// in the standard library produces the same values on each run
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.random.reseed(seed);
+    }
+
+    /// The default random number generator
+    pub(crate) fn random(&self) -> &Generator {
+        &self.random
+    }
+
+    /// Modules loaded so far, and the files they came from
+    pub fn module_system(&self) -> &ModuleSystem {
+        &self.modules
+    }
+
+    /// Configure module search paths and source files before running a program
+    pub fn module_system_mut(&mut self) -> &mut ModuleSystem {
+        &mut self.modules
+    }
+
+    /// Lock the shared concurrency runtime
+    fn concurrency(&self) -> Result<MutexGuard<'_, ConcurrencyRuntime>, RuntimeError> {
+        self.concurrency
+            .lock()
+            .map_err(|_| self.runtime_error("Concurrency runtime is poisoned".to_string()))
+    }
+
+    /// Open a new channel for tasks to communicate over
+    pub(crate) fn create_channel(&mut self) -> Result<Value, String> {
+        let mut runtime = self.concurrency.lock().map_err(|_| "Concurrency runtime is poisoned".to_string())?;
+        let handle = runtime.create_channel("channel".to_string(), 0)?;
+        Ok(Value::Channel { id: handle.id })
+    }
+
+    /// Wait for a spawned task. A task that failed fails the joiner with its error,
+    /// so a raised value can be caught around the join.
+    fn join_task(&mut self, id: u64) -> Result<Value, RuntimeError> {
+        let handle = self
+            .concurrency()?
+            .take_task_handle(id)
+            .map_err(|e| self.runtime_error(e))?;
+        // Join outside the lock so the task can still use channels and spawn
+        if let Some(handle) = handle {
+            let outcome = handle.join().ok();
+            self.concurrency()?.finish_task(id, outcome);
+        }
+        let outcome = self.concurrency()?.get_task_result(id).cloned();
+        match outcome {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(error)) if error.raised.is_some() => Err(error),
+            Some(Err(error)) => Err(RuntimeError {
+                message: format!("Task {} failed: {}", id, error.message),
+                ..error
+            }),
+            None => Err(self.runtime_error(format!("Task {} panicked", id))),
+        }
+    }
+
+    /// Methods on task and channel handles
+    fn call_concurrency_method(
+        &mut self,
+        target: &Value,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, RuntimeError> {
+        let expected_args = if method == "send" { 1 } else { 0 };
+        if args.len() != expected_args {
+            return Err(self.runtime_error(format!(
+                "{} method requires exactly {} argument(s)",
+                method, expected_args
+            )));
+        }
+        match (target, method) {
+            (Value::Task { id }, "join" | "await") => self.join_task(*id),
+            (Value::Task { id }, "is_done") => Ok(Value::Bool(self.concurrency()?.is_task_finished(*id))),
+            (Value::Channel { id }, "send") => {
+                self.concurrency()?
+                    .send(*id, args[0].clone())
+                    .map_err(|e| self.runtime_error(e))?;
+                Ok(Value::None)
+            }
+            (Value::Channel { id }, "recv") => {
+                // Block outside the runtime lock so senders can make progress
+                let receiver = self.concurrency()?.receiver(*id).map_err(|e| self.runtime_error(e))?;
+                let received = receiver.lock().ok().and_then(|receiver| receiver.recv().ok());
+                received.ok_or_else(|| self.runtime_error(format!("Channel {} is closed", id)))
+            }
+            (Value::Channel { id }, "try_recv") => {
+                let received = self
+                    .concurrency()?
+                    .try_receive(*id)
+                    .map_err(|e| self.runtime_error(e))?;
+                Ok(received.map(Value::some).unwrap_or(Value::None))
+            }
+            _ => Err(self.runtime_error(format!(
+                "No method '{}' found on {}",
+                method,
+                self.get_value_type_name(target)
+            ))),
+        }
+    }
+
+    /// Iterator over a value: ranges and collections, iterators themselves, and
+    /// values whose type implements `Iterator`
+    fn iterator_for(&self, value: Value) -> Option<IteratorRef> {
+        if self.value_implements(&value, "Iterator") {
+            return Some(iterators::new_iterator(IteratorState::Object(value)));
+        }
+        iterators::from_collection(value)
+    }
+
+    /// Iterator for a `for` loop over a value
+    pub(crate) fn iterator_over(&self, value: Value) -> Result<IteratorRef, RuntimeError> {
+        self.iterator_for(value.clone()).ok_or_else(|| {
+            self.runtime_error(format!(
+                "Cannot iterate over value of type: {}",
+                self.get_value_type_name(&value)
+            ))
+        })
+    }
+
+    /// Whether a method call is handled by the iterator protocol. Collections
+    /// only gain `iter()`; ranges, iterators and user iterators get the adapters.
+    fn is_iterator_method(&self, target: &Value, method: &str) -> bool {
+        match target {
+            Value::Range { .. } => {
+                iterators::ITERATOR_METHODS.contains(&method) && method != "next"
+                    || iterators::RANGE_METHODS.contains(&method)
+            }
+            Value::Iterator(_) => iterators::ITERATOR_METHODS.contains(&method),
+            Value::Vec(_) | Value::Set(_) | Value::Map(_) | Value::String(_) => method == "iter",
+            _ => {
+                iterators::ITERATOR_METHODS.contains(&method)
+                    && self.value_implements(target, "Iterator")
+            }
+        }
+    }
+
+    /// Methods of the iterator protocol. Adapters return new lazy iterators that
+    /// share their source, so nothing is evaluated until values are pulled.
+    fn call_iterator_method(
+        &mut self,
+        target: &Value,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, RuntimeError> {
+        let expected_args = match method {
+            "map" | "filter" | "take" | "zip" | "contains" => 1,
+            _ => 0,
+        };
+        if args.len() != expected_args {
+            return Err(self.runtime_error(format!(
+                "{} method requires exactly {} argument(s)",
+                method, expected_args
+            )));
+        }
+
+        if let Value::Range { start, end, inclusive } = target {
+            match method {
+                "length" => return Ok(Value::Int(iterators::range_length(*start, *end, *inclusive))),
+                "contains" => {
+                    return match &args[0] {
+                        Value::Int(value) => Ok(Value::Bool(iterators::range_contains(
+                            *start, *end, *inclusive, *value,
+                        ))),
+                        _ => Ok(Value::Bool(false)),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let source = self.iterator_for(target.clone()).ok_or_else(|| {
+            self.runtime_error(format!(
+                "Cannot iterate over value of type: {}",
+                self.get_value_type_name(target)
+            ))
+        })?;
+        let state = match method {
+            "iter" => return Ok(Value::Iterator(source)),
+            "next" => {
+                return Ok(iterators::next_value(&source, self)?
+                    .map(Value::some)
+                    .unwrap_or(Value::None))
+            }
+            "collect" => return Ok(Value::vec(iterators::collect(&source, self)?)),
+            "map" => IteratorState::Map { source, function: args[0].clone() },
+            "filter" => IteratorState::Filter { source, predicate: args[0].clone() },
+            "take" => match &args[0] {
+                Value::Int(count) if *count >= 0 => IteratorState::Take {
+                    source,
+                    remaining: *count as usize,
+                },
+                other => {
+                    return Err(self.runtime_error(format!(
+                        "take expects a non-negative int, got {}",
+                        other.to_string()
+                    )))
+                }
+            },
+            "zip" => {
+                let right = self.iterator_for(args[0].clone()).ok_or_else(|| {
+                    self.runtime_error(format!(
+                        "Cannot zip with value of type: {}",
+                        self.get_value_type_name(&args[0])
+                    ))
+                })?;
+                IteratorState::Zip { left: source, right }
+            }
+            "enumerate" => IteratorState::Enumerate { source, index: 0 },
+            _ => {
+                return Err(self.runtime_error(format!(
+                    "No method '{}' found on {}",
+                    method,
+                    self.get_value_type_name(target)
+                )))
+            }
+        };
+        Ok(Value::Iterator(iterators::new_iterator(state)))
+    }
+
     /// Set the current execution context for error tracking
     pub fn set_execution_context(&mut self, file_id: FileId, span: Span) {
         self.current_file_id = Some(file_id);
@@ -134,7 +708,7 @@ impl Interpreter {
     }
 
     /// Create a runtime error with current execution context
-    fn runtime_error(&self, message: String) -> RuntimeError {
+    pub(crate) fn runtime_error(&self, message: String) -> RuntimeError {
         let file_id = self.current_file_id.unwrap_or_else(|| {
             // Fallback to a dummy file ID if no context is set
             let mut files = codespan::Files::new();
@@ -144,8 +718,156 @@ impl Interpreter {
         RuntimeError::new(message, file_id, span)
     }
 
+    /// Create an error carrying a raised value, located at the current execution context
+    pub(crate) fn raised_error(&self, value: Value) -> RuntimeError {
+        let located = self.runtime_error(String::new());
+        RuntimeError::raised(value, located.file_id, located.span)
+    }
+
+    /// Count a call entering, failing once calls nest deeper than the limit
+    fn enter_call(&mut self) -> Result<(), RuntimeError> {
+        if self.call_depth >= self.max_call_depth {
+            return Err(self.runtime_error(format!(
+                "Stack overflow: calls nested deeper than {} levels",
+                self.max_call_depth
+            )));
+        }
+        self.call_depth += 1;
+        Ok(())
+    }
+
+    /// Record the call an error is unwinding through
+    fn unwind_frame(
+        &self,
+        mut error: RuntimeError,
+        function: &str,
+        params: &[String],
+        args: &[Value],
+        call_site: (Option<FileId>, Option<Span>),
+    ) -> RuntimeError {
+        if let (Some(file_id), Some(span)) = call_site {
+            error.stack.push(StackFrame {
+                function: function.to_string(),
+                file_id,
+                span,
+                arguments: Self::summarize_arguments(params, args),
+            });
+        }
+        error
+    }
+
+    /// Describe a call's arguments for a stack trace, shortening long values
+    fn summarize_arguments(params: &[String], args: &[Value]) -> String {
+        const MAX_VALUE_LEN: usize = 32;
+        params
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| {
+                let mut value = match arg {
+                    Value::String(s) => format!("{:?}", s),
+                    other => other.to_string(),
+                };
+                if value.chars().count() > MAX_VALUE_LEN {
+                    value = value.chars().take(MAX_VALUE_LEN).collect::<String>() + "...";
+                }
+                format!("{} = {}", param, value)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Match positional and keyword arguments to parameter slots. A slot left empty
+    /// takes the parameter's default value.
+    fn bind_arguments(
+        &self,
+        func_name: &str,
+        params: &[String],
+        param_defaults: &[Option<Expression>],
+        args: &[Value],
+        keyword_args: &[(String, Value)],
+    ) -> Result<Vec<Option<Value>>, RuntimeError> {
+        let has_default = |i: usize| matches!(param_defaults.get(i), Some(Some(_)));
+        let required = (0..params.len()).filter(|&i| !has_default(i)).count();
+        let arity_error = || {
+            let expected = if required == params.len() {
+                params.len().to_string()
+            } else {
+                format!("{} to {}", required, params.len())
+            };
+            self.runtime_error(format!(
+                "Function '{}' expects {} arguments, got {}",
+                func_name,
+                expected,
+                args.len() + keyword_args.len()
+            ))
+        };
+
+        if args.len() > params.len() {
+            return Err(arity_error());
+        }
+        let mut slots: Vec<Option<Value>> = args.iter().cloned().map(Some).collect();
+        slots.resize(params.len(), None);
+
+        for (keyword, value) in keyword_args {
+            let index = params.iter().position(|p| p == keyword).ok_or_else(|| {
+                self.runtime_error(format!(
+                    "Function '{}' has no parameter named '{}'",
+                    func_name, keyword
+                ))
+            })?;
+            if slots[index].is_some() {
+                return Err(self.runtime_error(format!(
+                    "Function '{}' got multiple values for parameter '{}'",
+                    func_name, keyword
+                )));
+            }
+            slots[index] = Some(value.clone());
+        }
+
+        if let Some(missing) = (0..params.len()).find(|&i| slots[i].is_none() && !has_default(i)) {
+            if keyword_args.is_empty() {
+                return Err(arity_error());
+            }
+            return Err(self.runtime_error(format!(
+                "Function '{}' is missing an argument for parameter '{}'",
+                func_name, params[missing]
+            )));
+        }
+        Ok(slots)
+    }
+
+    /// Store each parameter in the frame slots from `first_slot` on, evaluating the
+    /// defaults of those left empty. A default can refer to the parameters before it.
+    fn bind_parameters(
+        &mut self,
+        params: &[String],
+        param_defaults: &[Option<Expression>],
+        slots: Vec<Option<Value>>,
+        first_slot: usize,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let mut values = Vec::with_capacity(params.len());
+        for (i, (param_name, slot)) in params.iter().zip(slots).enumerate() {
+            let value = match (slot, param_defaults.get(i)) {
+                (Some(value), _) => value,
+                (None, Some(Some(default))) => self.evaluate(default)?,
+                (None, _) => Value::None,
+            };
+            debug_println!("                 Binding param {} = {:?}", param_name, value);
+            self.environment.set_local(Slot { depth: 0, index: first_slot + i }, value.clone());
+            values.push(value);
+        }
+        Ok(values)
+    }
+
     /// Validate function call parameters
-    fn validate_function_call(&self, func_name: &str, params: &[String], param_types: &[Type], args: &[Value]) -> Result<(), RuntimeError> {
+    fn validate_function_call(
+        &self,
+        func_name: &str,
+        generic_params: &[GenericParam],
+        params: &[String],
+        param_types: &[Type],
+        args: &[Value],
+    ) -> Result<(), RuntimeError> {
         // Check parameter count
         if args.len() != params.len() {
             return Err(self.runtime_error(format!(
@@ -154,40 +876,344 @@ impl Interpreter {
             )));
         }
 
-        // Check parameter types
+        if !self.runtime_typecheck {
+            return Ok(());
+        }
+
+        // Check parameter types, binding each type parameter to the type of the
+        // first argument it appears in
+        let mut bindings = HashMap::new();
         for (i, (arg, param_type)) in args.iter().zip(param_types.iter()).enumerate() {
-            if !self.is_type_compatible(arg, param_type) {
+            if !self.is_generic_compatible(arg, param_type, generic_params, &mut bindings) {
                 return Err(self.runtime_error(format!(
-                    "Function '{}' parameter '{}' (position {}) expects type {:?}, got {:?}",
-                    func_name, params[i], i + 1, param_type, self.get_value_type(arg)
+                    "Function '{}' parameter '{}' (position {}) expects type {}, got {}",
+                    func_name, params[i], i + 1, format_type(param_type), self.describe_type(arg)
                 )));
             }
         }
 
+        // Static analysis reports bound violations, but may have been skipped
+        for generic in generic_params {
+            let Some(bound_value) = bindings.get(&generic.name) else {
+                continue;
+            };
+            for bound in &generic.bounds {
+                if !self.value_implements(bound_value, bound) {
+                    return Err(self.runtime_error(format!(
+                        "Function '{}' requires type parameter '{}' to implement '{}', but '{}' does not",
+                        func_name,
+                        generic.name,
+                        bound,
+                        self.generic_type_name(bound_value)
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the arguments of a closure call against the lambda's parameter types
+    fn check_closure_arguments(&self, params: &[String], param_types: &[Type], args: &[Value]) -> Result<(), RuntimeError> {
+        if !self.runtime_typecheck {
+            return Ok(());
+        }
+        for (i, (arg, param_type)) in args.iter().zip(param_types).enumerate() {
+            if !self.is_type_compatible(arg, param_type) {
+                return Err(self.runtime_error(format!(
+                    "Closure parameter '{}' (position {}) expects type {}, got {}",
+                    params[i], i + 1, format_type(param_type), self.describe_type(arg)
+                )));
+            }
+        }
         Ok(())
     }
 
+    /// Check a value against a parameter type that may mention type parameters.
+    /// A type parameter accepts any value the first time it is seen and values of
+    /// the same type after that.
+    fn is_generic_compatible(
+        &self,
+        value: &Value,
+        expected_type: &Type,
+        generic_params: &[GenericParam],
+        bindings: &mut HashMap<String, Value>,
+    ) -> bool {
+        if generic_params.is_empty() {
+            return self.is_type_compatible(value, expected_type);
+        }
+        match (value, expected_type) {
+            (_, Type::Identifier(name)) if generic_params.iter().any(|g| &g.name == name) => {
+                match bindings.get(name) {
+                    Some(bound) => self.generic_type_name(bound) == self.generic_type_name(value),
+                    None => {
+                        bindings.insert(name.clone(), value.clone());
+                        true
+                    }
+                }
+            }
+            (Value::Vec(items), Type::Vec { element_type, .. }) => items
+                .read()
+                .iter()
+                .all(|item| self.is_generic_compatible(item, element_type, generic_params, bindings)),
+            (Value::Set(items), Type::Set { element_type, .. }) => items
+                .read()
+                .iter()
+                .all(|item| self.is_generic_compatible(item, element_type, generic_params, bindings)),
+            (Value::Tuple(items), Type::Tuple { types, .. }) => {
+                items.len() == types.len()
+                    && items
+                        .iter()
+                        .zip(types)
+                        .all(|(item, ty)| self.is_generic_compatible(item, ty, generic_params, bindings))
+            }
+            (Value::None, Type::Option { .. }) => true,
+            (Value::Enum { name, fields, .. }, Type::Option { inner, .. }) if name == "Option" => fields
+                .iter()
+                .all(|field| self.is_generic_compatible(field, inner, generic_params, bindings)),
+            _ => self.is_type_compatible(value, expected_type),
+        }
+    }
+
+    /// Type name used to compare type parameter bindings and in bound errors
+    fn generic_type_name(&self, value: &Value) -> String {
+        self.impl_type_name(value)
+            .unwrap_or_else(|| self.get_value_type(value).to_string())
+    }
+
+    /// Check a value bound to a declared variable, wrapping it if the type is a union
+    pub(crate) fn check_variable_type(&self, value: Value, declared: &Type, name: &str) -> Result<Value, RuntimeError> {
+        self.check_declared_type(value, declared, || format!("variable '{}' is declared as", name))
+    }
+
+    /// Check the value a function or method returns against its declared return type
+    fn check_return_type(&self, value: Value, declared: &Type, function: &str) -> Result<Value, RuntimeError> {
+        self.check_declared_type(value, declared, || format!("function '{}' is declared to return", function))
+    }
+
+    /// Check a value against a declared type. A value bound to a union type is wrapped
+    /// in a `Value::Union` recording the types it may hold; any union it was already
+    /// wrapped in is replaced.
+    fn check_declared_type(
+        &self,
+        value: Value,
+        declared: &Type,
+        subject: impl FnOnce() -> String,
+    ) -> Result<Value, RuntimeError> {
+        if !self.runtime_typecheck {
+            return Ok(value);
+        }
+        let value = match value {
+            Value::Union { value, .. } => *value,
+            value => value,
+        };
+        if !self.is_type_compatible(&value, declared) {
+            return Err(self.runtime_error(format!(
+                "Type mismatch: {} {}, got {}",
+                subject(),
+                format_type(declared),
+                self.describe_type(&value)
+            )));
+        }
+        Ok(match self.resolve_type_alias(declared) {
+            Type::Union { types, .. } => value.wrap_in_union(types.iter().map(format_type).collect()),
+            _ => value,
+        })
+    }
+
+    /// Wrap the arguments bound to union-typed parameters, which start at `first_slot`
+    /// of the current frame
+    fn wrap_union_parameters(&mut self, param_types: &[Type], first_slot: usize, values: &[Value]) {
+        if !self.runtime_typecheck {
+            return;
+        }
+        for (i, (param_type, value)) in param_types.iter().zip(values).enumerate() {
+            if let Type::Union { types, .. } = self.resolve_type_alias(param_type) {
+                let wrapped = value.unwrap_union().clone().wrap_in_union(types.iter().map(format_type).collect());
+                self.environment.set_local(Slot { depth: 0, index: first_slot + i }, wrapped);
+            }
+        }
+    }
+
+    /// Follow type aliases to the type they name
+    fn resolve_type_alias<'t>(&'t self, ty: &'t Type) -> &'t Type {
+        match ty {
+            Type::Identifier(name) => match self.types.type_aliases.get(name) {
+                Some(aliased) => self.resolve_type_alias(aliased),
+                None => ty,
+            },
+            _ => ty,
+        }
+    }
+
+    /// Type of a value as it appears in type mismatch errors
+    fn describe_type(&self, value: &Value) -> String {
+        match value {
+            Value::None => "None".to_string(),
+            _ => format_type(&value.get_type()),
+        }
+    }
+
     /// Check if a value is compatible with a type
     fn is_type_compatible(&self, value: &Value, expected_type: &Type) -> bool {
         match (value, expected_type) {
-            (Value::Int(_), Type::Primitive(PrimitiveType::Int)) => true,
+            // A union value is checked by the value it holds
+            (Value::Union { value, .. }, _) => self.is_type_compatible(value, expected_type),
+            (_, Type::Union { types, .. }) => types.iter().any(|ty| self.is_type_compatible(value, ty)),
+            (Value::Int(_) | Value::BigInt(_), Type::Primitive(PrimitiveType::Int)) => true,
             (Value::Float(_), Type::Primitive(PrimitiveType::Float)) => true,
             (Value::Bool(_), Type::Primitive(PrimitiveType::Bool)) => true,
             (Value::String(_), Type::Primitive(PrimitiveType::Str)) => true,
             (Value::None, Type::Primitive(PrimitiveType::Any)) => true,
             // Allow int to float coercion
             (Value::Int(_), Type::Primitive(PrimitiveType::Float)) => true,
-            (Value::Float(_), Type::Primitive(PrimitiveType::Int)) => true,
             // Allow any type for Any
             (_, Type::Primitive(PrimitiveType::Any)) => true,
+            // Resolve type aliases before comparing
+            (_, Type::Identifier(type_name)) if self.types.type_aliases.contains_key(type_name) => {
+                self.is_type_compatible(value, &self.types.type_aliases[type_name])
+            }
+            // User-defined structs and enums match by name
+            (Value::Struct { name, .. }, Type::Identifier(type_name))
+            | (Value::Enum { name, .. }, Type::Identifier(type_name))
+                if name == type_name =>
+            {
+                true
+            }
+            // Collections match when every element does
+            (Value::Vec(items), Type::Vec { element_type, .. }) => {
+                items.read().iter().all(|item| self.is_type_compatible(item, element_type))
+            }
+            (Value::Set(items), Type::Set { element_type, .. }) => {
+                items.read().iter().all(|item| self.is_type_compatible(item, element_type))
+            }
+            (Value::Map(entries), Type::Map { key_type, value_type, .. }) => {
+                entries.read().iter().all(|(key, value)| {
+                    self.is_type_compatible(key, key_type)
+                        && self.is_type_compatible(value, value_type)
+                })
+            }
+            (Value::Tuple(items), Type::Tuple { types, .. }) => {
+                items.len() == types.len()
+                    && items.iter().zip(types).all(|(item, ty)| self.is_type_compatible(item, ty))
+            }
+            (Value::Channel { .. }, Type::Identifier(type_name)) => type_name == "Channel",
+            (Value::Task { .. }, Type::Identifier(type_name)) => type_name == "Task",
+            (Value::Regex(_), Type::Identifier(type_name)) => type_name == "Regex",
+            (Value::Generator(_), Type::Identifier(type_name)) => type_name == "Generator",
+            (Value::Native(object), Type::Identifier(type_name)) => type_name == object.type_name(),
+            (Value::Range { .. }, Type::Identifier(type_name)) if type_name == "Range" => true,
+            (Value::Range { .. } | Value::Iterator(_), Type::Identifier(type_name))
+                if type_name == "Iterator" =>
+            {
+                true
+            }
+            // Option accepts None, Some(value) or a bare value of the inner type
+            (Value::None, Type::Option { .. }) => true,
+            (Value::Enum { name, fields, .. }, Type::Option { inner, .. }) if name == "Option" => {
+                fields.iter().all(|field| self.is_type_compatible(field, inner))
+            }
+            (_, Type::Option { inner, .. }) => self.is_type_compatible(value, inner),
+            (Value::Enum { name, variant, fields }, Type::Result { ok_type, error_type, .. })
+                if name == "Result" =>
+            {
+                let expected = if variant == "Ok" { ok_type } else { error_type };
+                fields.iter().all(|field| self.is_type_compatible(field, expected))
+            }
+            // Functions and closures are checked by arity only; native functions
+            // validate their own arguments
+            (Value::Function { name, .. }, Type::Function { .. })
+                if self.stdlib.get_function(name).is_some() =>
+            {
+                true
+            }
+            (Value::Function { params, .. }, Type::Function { params: expected, .. })
+            | (Value::Closure { params, .. }, Type::Function { params: expected, .. }) => {
+                params.len() == expected.len()
+            }
+            (Value::EnumConstructor { field_types, .. }, Type::Function { params: expected, .. }) => {
+                field_types.len() == expected.len()
+            }
+            // Interface types accept any value whose type implements the interface
+            (_, Type::Identifier(type_name)) if self.types.interfaces.contains_key(type_name) => self
+                .impl_type_name(value)
+                .map(|value_type| self.type_implements(&value_type, type_name))
+                .unwrap_or(false),
+            (Value::None, Type::Identifier(type_name)) if matches!(type_name.as_str(), "None" | "void") => true,
+            // Collection types named without their element types
+            (Value::Vec(_), Type::Identifier(type_name)) if type_name == "Vec" => true,
+            (Value::Set(_), Type::Identifier(type_name)) if type_name == "Set" => true,
+            (Value::Map(_), Type::Identifier(type_name)) if type_name == "Map" => true,
+            // `Vec<T>` and `Set<T>` are the collection types under another spelling
+            (Value::Vec(items), Type::Generic { name, type_args, .. }) if name == "Vec" && type_args.len() == 1 => {
+                items.read().iter().all(|item| self.is_type_compatible(item, &type_args[0]))
+            }
+            (Value::Set(items), Type::Generic { name, type_args, .. }) if name == "Set" && type_args.len() == 1 => {
+                items.read().iter().all(|item| self.is_type_compatible(item, &type_args[0]))
+            }
+            // So are `Map<K, V>`, `Option<T>` and `Result<T, E>`
+            (_, Type::Generic { name, type_args, span }) if name == "Map" && type_args.len() == 2 => {
+                let map = Type::Map {
+                    key_type: Box::new(type_args[0].clone()),
+                    value_type: Box::new(type_args[1].clone()),
+                    span: span.clone(),
+                };
+                self.is_type_compatible(value, &map)
+            }
+            (_, Type::Generic { name, type_args, span }) if name == "Option" && type_args.len() == 1 => {
+                let option = Type::Option {
+                    inner: Box::new(type_args[0].clone()),
+                    span: span.clone(),
+                };
+                self.is_type_compatible(value, &option)
+            }
+            (_, Type::Generic { name, type_args, span }) if name == "Result" && type_args.len() == 2 => {
+                let result = Type::Result {
+                    ok_type: Box::new(type_args[0].clone()),
+                    error_type: Box::new(type_args[1].clone()),
+                    span: span.clone(),
+                };
+                self.is_type_compatible(value, &result)
+            }
+            // Other generic types match by name; their type arguments aren't known at runtime
+            (_, Type::Generic { name, .. }) => self.is_type_compatible(value, &Type::Identifier(name.clone())),
+            // A type parameter in scope, such as `T` or `Self`, accepts any value.
+            // Any other name is not a type visible here, so nothing matches it.
+            (_, Type::Identifier(type_name)) => self.type_params.contains(type_name),
             _ => false,
         }
     }
 
+    /// Name under which `impl` blocks for this value's type are registered
+    fn impl_type_name(&self, value: &Value) -> Option<String> {
+        match value.unwrap_union() {
+            Value::Struct { name, .. } | Value::Enum { name, .. } => Some(name.clone()),
+            Value::Int(_) | Value::BigInt(_) => Some("int".to_string()),
+            Value::Float(_) => Some("float".to_string()),
+            Value::Bool(_) => Some("bool".to_string()),
+            Value::String(_) => Some("str".to_string()),
+            _ => None,
+        }
+    }
+
+    /// Check whether a type has an `impl` block for the given interface
+    fn type_implements(&self, type_name: &str, interface_name: &str) -> bool {
+        self.types.implemented_interfaces
+            .get(type_name)
+            .map(|interfaces| interfaces.iter().any(|i| i == interface_name))
+            .unwrap_or(false)
+    }
+
+    /// Look up a method registered for the value's type through an `impl` block
+    fn find_method(&self, value: &Value, method: &str) -> Option<ImplMethod> {
+        let type_name = self.impl_type_name(value)?;
+        self.types.impls.get(&type_name)?.get(method).cloned()
+    }
+
     /// Get the type name of a value for error messages
     fn get_value_type(&self, value: &Value) -> &'static str {
         match value {
-            Value::Int(_) => "int",
+            Value::Int(_) | Value::BigInt(_) => "int",
             Value::Float(_) => "float",
             Value::Bool(_) => "bool",
             Value::String(_) => "str",
@@ -196,6 +1222,7 @@ impl Interpreter {
             Value::Struct { .. } => "Struct",
             Value::Function { .. } => "Function",
             Value::Closure { .. } => "Closure",
+            Value::EnumConstructor { .. } => "Function",
             _ => "Unknown",
         }
     }
@@ -203,29 +1230,28 @@ impl Interpreter {
     /// Register all stdlib functions in the environment
     fn register_stdlib_functions(&mut self) {
         debug_println!("[DEBUG] DEBUG: register_stdlib_functions called");
-        let function_names = self.stdlib.get_function_names();
-        debug_println!(
-            " Found {} stdlib functions to register",
-            function_names.len()
-        );
+        let signatures = crate::stdlib_integration::stdlib_signatures();
+        debug_println!(" Found {} stdlib functions to register", signatures.len());
 
-        // First, collect all module names
+        // Group the functions by module, typed from their binding signatures
         let mut modules: HashMap<String, HashMap<String, Value>> = HashMap::new();
 
-        for function_name in &function_names {
-            if let Some((module, func)) = function_name.split_once("::") {
-                let func_value = Value::Function {
-                    name: function_name.clone(),
-                    params: vec![], // Native functions handle their own parameter validation
-                    param_types: vec![], // Native functions handle their own parameter validation
-                    body: Block { statements: vec![], span: dummy_span() }, // Native functions don't have TJLang bodies
-                    closure: HashMap::new(),
-                };
-                modules
-                    .entry(module.to_string())
-                    .or_insert_with(HashMap::new)
-                    .insert(func.to_string(), func_value);
-            }
+        for signature in signatures {
+            let params: Vec<_> = signature.params.iter().chain(&signature.rest).collect();
+            let func_value = Value::Function {
+                name: signature.qualified_name(),
+                generic_params: vec![],
+                params: params.iter().map(|param| param.name.to_string()).collect(),
+                param_types: params.iter().map(|param| param.ty.clone()).collect(),
+                param_defaults: vec![None; params.len()],
+                return_type: signature.return_type.clone(),
+                body: Arc::new(Block { statements: vec![], span: dummy_span() }), // Native functions don't have TJLang bodies
+                globals: None,
+            };
+            modules
+                .entry(signature.module.to_string())
+                .or_default()
+                .insert(signature.name.to_string(), func_value);
         }
 
         // Register modules as structs
@@ -236,10 +1262,7 @@ impl Interpreter {
                 module_name,
                 functions.keys().collect::<Vec<_>>()
             );
-            let module_value = Value::Struct {
-                name: module_name.clone(),
-                fields: functions,
-            };
+            let module_value = Value::new_struct(module_name.clone(), functions);
             self.environment.define(module_name.clone(), module_value);
             debug_println!(
                 "[DEBUG] DEBUG: Module {} registered successfully",
@@ -253,15 +1276,114 @@ impl Interpreter {
     /// Interpret a complete program
     pub fn interpret_program(&mut self, program: &Program) -> Result<Value, RuntimeError> {
         debug_println!("[DEBUG] Starting program interpretation...");
+        self.resolve(program);
+        let mut result = self.execute_units(program)?;
+
+        // Third pass: call main function if it exists
+        debug_println!(" Third pass: checking for main function...");
+        if let Some(main_func) = self.functions.get("main") {
+            debug_println!(" Found main function, calling it...");
+            let main_span = main_func.span.clone();
+            let main_value = Value::Function {
+                name: "main".to_string(),
+                generic_params: vec![],
+                params: main_func.params.iter().map(|p| p.name.clone()).collect(),
+                param_types: main_func.params.iter().map(|p| p.param_type.clone()).collect(),
+                param_defaults: main_func.params.iter().map(|p| p.default_value.clone()).collect(),
+                return_type: main_func.return_type.clone(),
+                body: Arc::new(main_func.body.clone()), // Store actual main function body
+                globals: Some(self.environment.globals()),
+            };
+            // Calls from the runtime into main point at the start of its declaration
+            let main_start = main_span.span.start();
+            self.set_execution_context(main_span.file_id, Span::new(main_start, main_start));
+            result = self.interpret_call(&main_value, &[])?;
+            debug_println!(" Main function result: {:?}", result);
+        } else {
+            debug_println!(" No main function found");
+        }
+
+        debug_println!(" Program interpretation completed successfully");
+        Ok(result)
+    }
+
+    /// Run a program compiled ahead of time. Its bytecode is used as is, so it
+    /// always runs on the VM backend; declarations are still registered from
+    /// the syntax tree saved alongside it.
+    pub fn interpret_compiled(&mut self, compiled: &CompiledProgram) -> Result<Value, RuntimeError> {
+        self.backend = Backend::Vm;
+        for (key, code) in &compiled.functions {
+            self.code.insert(*key, code.clone());
+        }
+        self.interpret_program(&compiled.program)
+    }
+
+    /// Work out where the local variables of a program are stored before running it
+    fn resolve(&mut self, program: &Program) {
+        Arc::make_mut(&mut self.resolution).extend(resolver::resolve_program(program));
+    }
+
+    /// The declaration a program unit introduces, including exported ones
+    fn unit_declaration(unit: &ProgramUnit) -> Option<&Declaration> {
+        match unit {
+            ProgramUnit::Declaration(decl) | ProgramUnit::Export(ExportDecl::Declaration(decl)) => {
+                Some(decl)
+            }
+            _ => None,
+        }
+    }
+
+    /// Source location of a program unit, for errors raised while running it
+    fn unit_span(unit: &ProgramUnit) -> Option<&SourceSpan> {
+        if let Some(decl) = Self::unit_declaration(unit) {
+            return Some(match decl {
+                Declaration::Function(func) => &func.span,
+                Declaration::Interface(interface) => &interface.span,
+                Declaration::Type(alias) => &alias.span,
+                Declaration::Enum(enum_decl) => &enum_decl.span,
+                Declaration::Struct(struct_decl) => &struct_decl.span,
+                Declaration::Variable(var) => &var.span,
+                Declaration::Implementation(impl_block) => &impl_block.span,
+                Declaration::Module(module) => &module.span,
+            });
+        }
+        match unit {
+            ProgramUnit::Import(ImportDecl::Simple { span, .. })
+            | ProgramUnit::Import(ImportDecl::Selective { span, .. }) => Some(span),
+            ProgramUnit::Expression(expr) => expr.span(),
+            ProgramUnit::Statement(stmt) => stmt.span(),
+            _ => None,
+        }
+    }
+
+    /// Register a program's declarations and run its top-level code, without calling `main`
+    fn execute_units(&mut self, program: &Program) -> Result<Value, RuntimeError> {
         debug_println!(" Program has {} units", program.units.len());
 
-        // First pass: collect all function declarations
-        debug_println!(" First pass: collecting function declarations...");
+        // First pass: collect all function and type declarations
+        debug_println!(" First pass: collecting function and type declarations...");
         for (i, unit) in program.units.iter().enumerate() {
             debug_println!("  Unit {}: {:?}", i, std::mem::discriminant(unit));
-            if let ProgramUnit::Declaration(Declaration::Function(func)) = unit {
-                debug_println!("     Registering function: {}", func.name);
-                self.functions.insert(func.name.clone(), func.clone());
+            match Self::unit_declaration(unit) {
+                Some(Declaration::Function(func)) => {
+                    debug_println!("     Registering function: {}", func.name);
+                    self.functions.insert(func.name.clone(), func.clone());
+                }
+                Some(Declaration::Implementation(_)) => {}
+                Some(decl) if Self::is_type_declaration(decl) => {
+                    if let Some(span) = Self::unit_span(unit) {
+                        self.set_execution_context(span.file_id, span.span);
+                    }
+                    self.interpret_declaration(decl)?;
+                }
+                _ => {}
+            }
+        }
+        // Impl blocks are registered once every interface they might refer to is known
+        for unit in &program.units {
+            if let Some(decl @ Declaration::Implementation(impl_block)) = Self::unit_declaration(unit) {
+                self.set_execution_context(impl_block.span.file_id, impl_block.span.span);
+                self.interpret_declaration(decl)?;
             }
         }
         debug_println!("[DEBUG] Registered {} functions", self.functions.len());
@@ -273,10 +1395,20 @@ impl Interpreter {
             debug_println!("  Executing unit {}: {:?}", i, std::mem::discriminant(unit));
             
             // Set execution context for error tracking
-            self.set_execution_context(program.span.file_id, program.span.span);
+            if let Some(span) = Self::unit_span(unit) {
+                self.set_execution_context(span.file_id, span.span);
+            }
             
             match unit {
-                ProgramUnit::Declaration(decl) => {
+                ProgramUnit::Import(import) => {
+                    self.interpret_import(import)?;
+                }
+                ProgramUnit::Declaration(decl) | ProgramUnit::Export(ExportDecl::Declaration(decl))
+                    if Self::is_type_declaration(decl) =>
+                {
+                    debug_println!("     Type declaration already registered");
+                }
+                ProgramUnit::Declaration(decl) | ProgramUnit::Export(ExportDecl::Declaration(decl)) => {
                     debug_println!(
                         "     Interpreting declaration: {:?}",
                         std::mem::discriminant(decl)
@@ -289,7 +1421,7 @@ impl Interpreter {
                         "    [DEBUG] Interpreting expression: {:?}",
                         std::mem::discriminant(expr)
                     );
-                    result = self.interpret_expression(expr)?;
+                    result = self.evaluate(expr)?;
                     debug_println!("    [DEBUG] Expression result: {:?}", result);
                 }
                 ProgramUnit::Statement(stmt) => {
@@ -297,7 +1429,7 @@ impl Interpreter {
                         "    [DEBUG] Interpreting statement: {:?}",
                         std::mem::discriminant(stmt)
                     );
-                    result = self.interpret_statement(stmt)?;
+                    result = self.execute_statement(stmt)?;
                     debug_println!("    [DEBUG] Statement result: {:?}", result);
                 }
                 _ => {
@@ -306,25 +1438,114 @@ impl Interpreter {
             }
         }
 
-        // Third pass: call main function if it exists
-        debug_println!(" Third pass: checking for main function...");
-        if let Some(main_func) = self.functions.get("main") {
-            debug_println!(" Found main function, calling it...");
-            let main_value = Value::Function {
-                name: "main".to_string(),
-                params: main_func.params.iter().map(|p| p.name.clone()).collect(),
-                param_types: main_func.params.iter().map(|p| p.param_type.clone()).collect(),
-                body: main_func.body.clone(), // Store actual main function body
-                closure: HashMap::new(),
-            };
-            result = self.interpret_call(&main_value, &[])?;
-            debug_println!(" Main function result: {:?}", result);
-        } else {
-            debug_println!(" No main function found");
+        Ok(result)
+    }
+
+    /// Bind the names an `import` brings into scope, loading the module first if needed
+    fn interpret_import(&mut self, import: &ImportDecl) -> Result<(), RuntimeError> {
+        let (module, span) = match import {
+            ImportDecl::Simple { module, span, .. } | ImportDecl::Selective { module, span, .. } => {
+                (module, span)
+            }
+        };
+        let module_name = module.parts.join(".");
+        self.set_execution_context(span.file_id, span.span);
+        let exports = self.load_module(&module_name)?;
+
+        match import {
+            ImportDecl::Simple { alias, .. } => {
+                // `import geometry.shapes` binds `shapes` unless an alias is given
+                let binding = alias
+                    .clone()
+                    .unwrap_or_else(|| module.parts.last().cloned().unwrap_or_default());
+                self.environment.define(
+                    binding,
+                    Value::new_struct(module_name, exports),
+                );
+            }
+            ImportDecl::Selective { items, .. } => {
+                for item in items {
+                    match exports.get(item) {
+                        Some(value) => self.environment.define(item.clone(), value.clone()),
+                        None => {
+                            return Err(self.runtime_error(format!(
+                                "Module '{}' does not export '{}'",
+                                module_name, item
+                            )))
+                        }
+                    }
+                }
+            }
         }
+        Ok(())
+    }
 
-        debug_println!(" Program interpretation completed successfully");
-        Ok(result)
+    /// Execute a module the first time it is imported and return its exported values
+    fn load_module(&mut self, module_name: &str) -> Result<HashMap<String, Value>, RuntimeError> {
+        if !self.modules.is_executed(module_name) {
+            // Loads every module in the import graph and rejects cycles before anything runs
+            self.modules
+                .resolve_dependencies(module_name)
+                .map_err(|e| self.runtime_error(e))?;
+            let module = self
+                .modules
+                .load_module(module_name)
+                .map_err(|e| self.runtime_error(e))?;
+            debug_println!(" Executing module {} from {}", module_name, module.path.display());
+
+            // Modules run in their own global scope and never call their own `main`
+            let import_site = (self.current_file_id, self.current_span);
+            let importer = self.swap_environment(Environment::new(), Vec::new());
+            let importer_functions = std::mem::take(&mut self.functions);
+            self.register_stdlib_functions();
+            self.register_builtin_enums();
+            self.register_builtin_interfaces();
+            self.resolve(&module.program);
+            let executed = self.execute_units(&module.program);
+            let exported_types = self.types.exported(&module.export_names);
+            let module_env = std::mem::replace(&mut self.environment, Environment::new());
+            self.restore_environment(importer);
+            self.functions = importer_functions;
+            self.current_file_id = import_site.0;
+            self.current_span = import_site.1;
+            executed?;
+
+            // Exported functions carry the module's globals wherever they are called
+            self.modules
+                .mark_executed(module_name)
+                .map_err(|e| self.runtime_error(e))?;
+            for name in &module.export_names {
+                let value = match module_env.get(name) {
+                    Some(value) => value,
+                    None if exported_types.declares(name) => Value::Type(Type::Identifier(name.clone())),
+                    None => {
+                        return Err(self.runtime_error(format!(
+                            "Module '{}' exports '{}', which it does not define",
+                            module_name, name
+                        )))
+                    }
+                };
+                self.modules.export(module_name, name.clone(), ExportItem::Value(value));
+            }
+            self.exported_types.insert(module_name.to_string(), exported_types);
+        }
+
+        // Only the types a module exports are visible to its importers
+        if let Some(types) = self.exported_types.get(module_name) {
+            self.types.import(types);
+        }
+
+        let exports = self
+            .modules
+            .import_all(module_name)
+            .map_err(|e| self.runtime_error(e))?;
+        Ok(exports
+            .into_iter()
+            .filter_map(|(name, item)| match item {
+                ExportItem::Value(value) => Some((name, value)),
+                _ => None,
+            })
+            .collect())
     }
 
     /// Interpret a declaration
@@ -341,10 +1562,13 @@ impl Interpreter {
                 // The function body will be interpreted when the function is called
                 let func_value = Value::Function {
                     name: func.name.clone(),
+                    generic_params: func.generic_params.clone(),
                     params: func.params.iter().map(|p| p.name.clone()).collect(),
                     param_types: func.params.iter().map(|p| p.param_type.clone()).collect(),
-                    body: func.body.clone(), // Store actual function body
-                    closure: HashMap::new(),
+                    param_defaults: func.params.iter().map(|p| p.default_value.clone()).collect(),
+                    return_type: func.return_type.clone(),
+                    body: Arc::new(func.body.clone()), // Store actual function body
+                    globals: Some(self.environment.globals()),
                 };
                 self.environment.define(func.name.clone(), func_value);
                 debug_println!(
@@ -355,7 +1579,8 @@ impl Interpreter {
             }
             Declaration::Variable(var) => {
                 debug_println!("         Processing variable: {}", var.name);
-                let value = self.interpret_expression(&var.value)?;
+                let value = self.evaluate(&var.value)?;
+                let value = self.check_variable_type(value, &var.var_type, &var.name)?;
                 self.environment.define(var.name.clone(), value.clone());
                 debug_println!(
                     "        [DEBUG] Variable {} stored with value: {:?}",
@@ -364,15 +1589,217 @@ impl Interpreter {
                 );
                 Ok(value)
             }
-            _ => {
-                debug_println!("         Skipping unknown declaration type");
+            Declaration::Struct(struct_decl) => {
+                debug_println!("         Registering struct: {}", struct_decl.name);
+                self.types.structs
+                    .insert(struct_decl.name.clone(), struct_decl.clone());
                 Ok(Value::None)
             }
+            Declaration::Enum(enum_decl) => {
+                debug_println!("         Registering enum: {}", enum_decl.name);
+                self.register_enum(enum_decl);
+                Ok(Value::None)
+            }
+            Declaration::Interface(interface_decl) => {
+                debug_println!("         Registering interface: {}", interface_decl.name);
+                self.types.interfaces
+                    .insert(interface_decl.name.clone(), interface_decl.clone());
+                Ok(Value::None)
+            }
+            Declaration::Type(type_decl) => {
+                debug_println!("         Registering type alias: {}", type_decl.name);
+                self.types.type_aliases
+                    .insert(type_decl.name.clone(), type_decl.type_alias.clone());
+                Ok(Value::None)
+            }
+            Declaration::Implementation(impl_block) => {
+                debug_println!(
+                    "         Registering impl {} for {}",
+                    impl_block.trait_name,
+                    impl_block.type_name
+                );
+                if let Some(interface) = self.types.interfaces.get(&impl_block.trait_name) {
+                    for sig in &interface.methods {
+                        if !impl_block.methods.iter().any(|m| m.name == sig.name) {
+                            return Err(self.runtime_error(format!(
+                                "impl {}: {} is missing method '{}' required by interface '{}'",
+                                impl_block.trait_name,
+                                impl_block.type_name,
+                                sig.name,
+                                impl_block.trait_name
+                            )));
+                        }
+                    }
+                }
+                let globals = self.environment.globals();
+                let methods = self.types.impls.entry(impl_block.type_name.clone()).or_default();
+                for method in &impl_block.methods {
+                    let arg_count = method.params.iter().filter(|p| p.name != "self").count();
+                    let method = ImplMethod { decl: method.clone(), globals: globals.clone() };
+                    methods.insert(UnaryOperator::impl_method_key(&method.decl.name, arg_count), method);
+                }
+                self.types.implemented_interfaces
+                    .entry(impl_block.type_name.clone())
+                    .or_default()
+                    .push(impl_block.trait_name.clone());
+                Ok(Value::None)
+            }
+            Declaration::Module(_) => {
+                debug_println!("         Skipping module declaration");
+                Ok(Value::None)
+            }
+        }
+    }
+
+    /// Whether a declaration only introduces types and can be registered before execution
+    fn is_type_declaration(decl: &Declaration) -> bool {
+        matches!(
+            decl,
+            Declaration::Struct(_)
+                | Declaration::Enum(_)
+                | Declaration::Interface(_)
+                | Declaration::Type(_)
+                | Declaration::Implementation(_)
+        )
+    }
+
+    /// Define an enum's variant constructors. Variants are reachable both as
+    /// `Shape.Circle` and as bare `Circle`
+    fn register_enum(&mut self, enum_decl: &EnumDecl) {
+        let mut constructors = HashMap::new();
+        for variant in &enum_decl.variants {
+            let constructor = self.enum_variant_constructor(&enum_decl.name, variant);
+            self.environment
+                .define(variant.name.clone(), constructor.clone());
+            constructors.insert(variant.name.clone(), constructor);
+        }
+        self.environment.define(
+            enum_decl.name.clone(),
+            Value::new_struct(enum_decl.name.clone(), constructors),
+        );
+        self.types.enums.insert(enum_decl.name.clone(), enum_decl.clone());
+    }
+
+    /// Register the built-in `Option` (`Some`; the empty case is `None`) and
+    /// `Result` (`Ok`, `Err`) enums
+    fn register_builtin_enums(&mut self) {
+        let variant = |name: &str| EnumVariant {
+            name: name.to_string(),
+            fields: vec![Type::Primitive(PrimitiveType::Any)],
+            span: dummy_span(),
+        };
+        let builtins = [
+            ("Option", vec!["T"], vec![variant("Some")]),
+            ("Result", vec!["T", "E"], vec![variant("Ok"), variant("Err")]),
+        ];
+        for (name, type_params, variants) in builtins {
+            self.register_enum(&EnumDecl {
+                name: name.to_string(),
+                type_params: type_params.into_iter().map(String::from).collect(),
+                variants,
+                span: dummy_span(),
+            });
+        }
+    }
+
+    /// Register the built-in `Iterator` interface. Types implementing it can be
+    /// used in `for` loops and with the iterator adapters.
+    fn register_builtin_interfaces(&mut self) {
+        let next = MethodSig {
+            name: "next".to_string(),
+            params: vec![],
+            return_type: Type::Option {
+                inner: Box::new(Type::Identifier("T".to_string())),
+                span: dummy_span(),
+            },
+            span: dummy_span(),
+        };
+        self.types.interfaces.insert(
+            "Iterator".to_string(),
+            InterfaceDecl {
+                name: "Iterator".to_string(),
+                extends: vec![],
+                methods: vec![next],
+                span: dummy_span(),
+            },
+        );
+
+        // Operator interfaces; an `impl` provides the method named by the symbol
+        let operand = |name: &str, param_type: Type| Parameter {
+            name: name.to_string(),
+            param_type,
+            default_value: None,
+            span: dummy_span(),
+        };
+        let self_type = || Type::Identifier("Self".to_string());
+        let operators = [
+            ("Addable", "+", operand("other", self_type()), self_type()),
+            ("Eq", "==", operand("other", self_type()), Type::Primitive(PrimitiveType::Bool)),
+            ("Order", "<", operand("other", self_type()), Type::Primitive(PrimitiveType::Bool)),
+            (
+                "Indexable",
+                "[]",
+                operand("index", Type::Primitive(PrimitiveType::Any)),
+                Type::Identifier("T".to_string()),
+            ),
+        ];
+        for (interface, symbol, param, return_type) in operators {
+            let method = MethodSig {
+                name: symbol.to_string(),
+                params: vec![param],
+                return_type,
+                span: dummy_span(),
+            };
+            self.types.interfaces.insert(
+                interface.to_string(),
+                InterfaceDecl {
+                    name: interface.to_string(),
+                    extends: vec![],
+                    methods: vec![method],
+                    span: dummy_span(),
+                },
+            );
+        }
+
+        // Primitive operators satisfy the operator interfaces in generic bounds
+        let primitives: [(&str, &[&str]); 4] = [
+            ("int", &["Addable", "Eq", "Order"]),
+            ("float", &["Addable", "Eq", "Order"]),
+            ("str", &["Addable", "Eq"]),
+            ("bool", &["Eq"]),
+        ];
+        for (type_name, interfaces) in primitives {
+            self.types.implemented_interfaces
+                .entry(type_name.to_string())
+                .or_default()
+                .extend(interfaces.iter().map(|i| i.to_string()));
+        }
+    }
+
+    /// Build the runtime value for an enum variant: unit variants are values,
+    /// variants with fields are constructor functions
+    fn enum_variant_constructor(&self, enum_name: &str, variant: &EnumVariant) -> Value {
+        if variant.fields.is_empty() {
+            return Value::Enum {
+                name: enum_name.to_string(),
+                variant: variant.name.clone(),
+                fields: vec![],
+            };
+        }
+        Value::EnumConstructor {
+            enum_name: enum_name.to_string(),
+            variant: variant.name.clone(),
+            field_types: variant.fields.clone(),
         }
     }
 
     /// Interpret an expression
     pub fn interpret_expression(&mut self, expr: &Expression) -> Result<Value, RuntimeError> {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || self.interpret_expression_unguarded(expr))
+    }
+
+    /// Interpret an expression on the current stack segment
+    fn interpret_expression_unguarded(&mut self, expr: &Expression) -> Result<Value, RuntimeError> {
         debug_println!(
             "        [DEBUG] Interpreting expression: {:?}",
             std::mem::discriminant(expr)
@@ -383,12 +1810,15 @@ impl Interpreter {
                 debug_println!("           Literal: {:?}", literal);
                 self.interpret_literal(literal)
             }
-            Expression::Variable { name, .. } => {
+            Expression::Variable { name, span } => {
                 debug_println!("          [DEBUG] Variable: {}", name);
-                self.environment
-                    .get(name)
-                    .cloned()
-                    .ok_or_else(|| self.runtime_error(format!("Undefined variable: {}", name)))
+                match self.lookup_variable(name, self.resolution.local(span)) {
+                    Some(value) => Ok(value),
+                    None => {
+                        self.set_execution_context(span.file_id, span.span);
+                        Err(self.runtime_error(format!("Undefined variable: {}", name)))
+                    }
+                }
             }
             Expression::Binary {
                 left,
@@ -403,43 +1833,48 @@ impl Interpreter {
                 // Set execution context for the binary operation
                 self.set_execution_context(span.file_id, span.span);
 
-                // Special handling for assignment
-                if *operator == BinaryOperator::Assign {
-                    if let Expression::Variable { name: var_name, .. } = left.as_ref() {
-                        let value = self.interpret_expression(right)?;
-                        self.environment.define(var_name.clone(), value.clone());
-                        return Ok(value);
-                    } else {
-                        return Err(self.runtime_error("Left side of assignment must be a variable".to_string()));
+                // Assignment and compound assignment such as `+=`
+                if operator.is_assignment() {
+                    let place = self.resolve_place(left)?;
+                    let mut value = self.interpret_expression(right)?;
+                    if let Some(base_operator) = operator.compound_operator() {
+                        let current = self.read_place(&place)?;
+                        self.set_execution_context(span.file_id, span.span);
+                        value = self.apply_binary_operator(&current, &base_operator, &value)?;
                     }
+                    self.set_execution_context(span.file_id, span.span);
+                    self.write_place(place, value.clone())?;
+                    return Ok(value);
                 }
 
                 let left_val = self.interpret_expression(left)?;
                 debug_println!("           Left value: {:?}", left_val);
                 let right_val = self.interpret_expression(right)?;
                 debug_println!("           Right value: {:?}", right_val);
-                let result = self.interpret_binary_operation(&left_val, operator, &right_val);
+                self.set_execution_context(span.file_id, span.span);
+                let result = self.apply_binary_operator(&left_val, operator, &right_val);
                 debug_println!("           Binary result: {:?}", result);
                 result
             }
             Expression::Unary {
-                operator, operand, ..
+                operator, operand, span
             } => {
                 debug_println!("[DEBUG] [UNARY] Unary operation: {:?}", operator);
                 let operand_val = self.interpret_expression(operand)?;
                 debug_println!("[DEBUG] [UNARY] Operand value: {:?}", operand_val);
-                let result = self.interpret_unary_operation(operator, &operand_val);
+                self.set_execution_context(span.file_id, span.span);
+                let result = self.apply_unary_operator(operator, &operand_val);
                 debug_println!("[DEBUG] [UNARY] Result: {:?}", result);
                 result
             }
-            Expression::Call { callee, args, span } => {
+            Expression::Call { callee, args, named_args, span } => {
                 debug_println!("           Function call with {} args", args.len());
                 debug_println!("          [DEBUG] Callee expression: {:?}", callee);
                 
                 // Set execution context for the function call
                 self.set_execution_context(span.file_id, span.span);
 
-                // Special handling for method calls on primitives
+                // Method calls dispatch on the evaluated receiver
                 if let Expression::Member { target, member, .. } = callee.as_ref() {
                     debug_println!("          [DEBUG] Method call: {}.{}()", "target", member);
                     let target_val = self.interpret_expression(target)?;
@@ -447,201 +1882,13 @@ impl Interpreter {
                         "          [DEBUG] Target value: {:?}",
                         std::mem::discriminant(&target_val)
                     );
-
-                    // Only apply primitive method handling to actual primitive types, not structs
-                    let is_primitive = matches!(
-                        target_val,
-                        Value::Int(_)
-                            | Value::Float(_)
-                            | Value::Bool(_)
-                            | Value::String(_)
-                            | Value::None
-                            | Value::Tuple(_)
-                            | Value::Vec(_)
-                            | Value::Set(_)
-                            | Value::Map(_)
-                    );
-
-                    if is_primitive && args.is_empty() {
-                        match member.as_str() {
-                            "to_string" => return Ok(Value::String(target_val.to_string())),
-                            "clone" => return Ok(target_val.clone()),
-                            "type_name" => {
-                                return Ok(Value::String(crate::primitive_methods::get_type_name(
-                                    &target_val,
-                                )))
-                            }
-                            "is_null" => return Ok(Value::Bool(matches!(target_val, Value::None))),
-                            "is_not_null" => {
-                                return Ok(Value::Bool(!matches!(target_val, Value::None)))
-                            }
-                            "hash" => {
-                                return Ok(Value::Int(crate::primitive_methods::get_hash_code(
-                                    &target_val,
-                                )))
-                            }
-                            "is_int" => {
-                                return Ok(Value::Bool(matches!(target_val, Value::Int(_))))
-                            }
-                            "is_float" => {
-                                return Ok(Value::Bool(matches!(target_val, Value::Float(_))))
-                            }
-                            "is_bool" => {
-                                return Ok(Value::Bool(matches!(target_val, Value::Bool(_))))
-                            }
-                            "is_str" => {
-                                return Ok(Value::Bool(matches!(target_val, Value::String(_))))
-                            }
-                            "is_none" => return Ok(Value::Bool(matches!(target_val, Value::None))),
-                            "debug_string" => {
-                                return Ok(Value::String(format!("{:?}", target_val)))
-                            }
-                            "pretty_string" => {
-                                return Ok(Value::String(
-                                    crate::primitive_methods::get_pretty_string(&target_val),
-                                ))
-                            }
-                            "reverse" => {
-                                // Special handling for reverse as in-place modification
-                                if matches!(target_val, Value::Vec(_)) {
-                                    let result = crate::primitive_methods::get_primitive_method(
-                                        &target_val,
-                                        member,
-                                    ).map_err(|e| self.runtime_error(e))?;
-                                    // If the target is a simple variable reference, update it in the environment
-                                    if let Expression::Variable { name: var_name, .. } = &**target {
-                                        debug_println!(
-                                            "           Updating variable {} in-place",
-                                            var_name
-                                        );
-                                        self.environment.define(var_name.clone(), result.clone());
-                                    }
-                                    return Ok(result);
-                                } else {
-                                    return crate::primitive_methods::get_primitive_method(
-                                        &target_val,
-                                        member,
-                                    ).map_err(|e| self.runtime_error(e));
-                                }
-                            }
-                            "sort" => {
-                                // Special handling for sort as in-place modification
-                                if matches!(target_val, Value::Vec(_)) {
-                                    let result = crate::primitive_methods::get_primitive_method(
-                                        &target_val,
-                                        member,
-                                    ).map_err(|e| self.runtime_error(e))?;
-                                    // If the target is a simple variable reference, update it in the environment
-                                    if let Expression::Variable { name: var_name, .. } = &**target {
-                                        debug_println!(
-                                            "           Updating variable {} in-place",
-                                            var_name
-                                        );
-                                        self.environment.define(var_name.clone(), result.clone());
-                                    }
-                                    return Ok(result);
-                                } else {
-                                    return crate::primitive_methods::get_primitive_method(
-                                        &target_val,
-                                        member,
-                                    ).map_err(|e| self.runtime_error(e));
-                                }
-                            }
-                            _ => {
-                                // Try type-specific methods
-                                return crate::primitive_methods::get_primitive_method(
-                                    &target_val,
-                                    member,
-                                ).map_err(|e| self.runtime_error(e));
-                            }
-                        }
-                    } else if is_primitive {
-                        // For primitive methods that do take arguments
-                        let mut arg_values = Vec::new();
-                        for arg in args {
-                            arg_values.push(self.interpret_expression(arg)?);
-                        }
-
-                        // Special handling for collection methods that should modify in-place
-                        if matches!(target_val, Value::Vec(_))
-                            && matches!(
-                                member.as_str(),
-                                "push"
-                                    | "pop"
-                                    | "insert"
-                                    | "remove"
-                                    | "set"
-                                    | "reverse"
-                                    | "sort"
-                                    | "sort_by"
-                            )
-                        {
-                            // For vector methods that modify in-place, we need to update the original variable
-                            let result = crate::primitive_methods::execute_primitive_method(
-                                &target_val,
-                                member,
-                                &arg_values,
-                            ).map_err(|e| self.runtime_error(e))?;
-
-                            // If the target is a simple variable reference, update it in the environment
-                            if let Expression::Variable { name: var_name, .. } = &**target {
-                                // Fixed: &**target to dereference Box<Expression>
-                                debug_println!(
-                                    "           Updating variable {} in-place",
-                                    var_name
-                                );
-                                self.environment.define(var_name.clone(), result.clone());
-                            }
-
-                            return Ok(result);
-                        } else if matches!(target_val, Value::Set(_))
-                            && matches!(member.as_str(), "insert" | "remove")
-                        {
-                            // For set methods that modify in-place
-                            let result = crate::primitive_methods::execute_primitive_method(
-                                &target_val,
-                                member,
-                                &arg_values,
-                            ).map_err(|e| self.runtime_error(e))?;
-
-                            // If the target is a simple variable reference, update it in the environment
-                            if let Expression::Variable { name: var_name, .. } = &**target {
-                                debug_println!(
-                                    "           Updating variable {} in-place",
-                                    var_name
-                                );
-                                self.environment.define(var_name.clone(), result.clone());
-                            }
-
-                            return Ok(result);
-                        } else if matches!(target_val, Value::Map(_))
-                            && matches!(member.as_str(), "insert" | "set" | "remove" | "clear")
-                        {
-                            // For map methods that modify in-place
-                            let result = crate::primitive_methods::execute_primitive_method(
-                                &target_val,
-                                member,
-                                &arg_values,
-                            ).map_err(|e| self.runtime_error(e))?;
-
-                            // If the target is a simple variable reference, update it in the environment
-                            if let Expression::Variable { name: var_name, .. } = &**target {
-                                debug_println!(
-                                    "           Updating variable {} in-place",
-                                    var_name
-                                );
-                                self.environment.define(var_name.clone(), result.clone());
-                            }
-
-                            return Ok(result);
-                        }
-
-                        return crate::primitive_methods::execute_primitive_method(
-                            &target_val,
-                            member,
-                            &arg_values,
-                        ).map_err(|e| self.runtime_error(e));
+                    let mut arg_values = Vec::new();
+                    for arg in args {
+                        arg_values.push(self.interpret_expression(arg)?);
                     }
+                    let keyword_values = self.interpret_keyword_args(named_args)?;
+                    self.set_execution_context(span.file_id, span.span);
+                    return self.call_member(&target_val, member, &arg_values, &keyword_values);
                 }
 
                 let callee_val = self.interpret_expression(callee)?;
@@ -657,12 +1904,14 @@ impl Interpreter {
                     debug_println!("             Arg {} resolved to: {:?}", i, arg_val);
                     arg_values.push(arg_val);
                 }
+                let keyword_values = self.interpret_keyword_args(named_args)?;
+                self.set_execution_context(span.file_id, span.span);
                 debug_println!(
                     "           Calling function: {:?} with args: {:?}",
                     callee_val,
                     arg_values
                 );
-                let result = self.interpret_call(&callee_val, &arg_values);
+                let result = self.interpret_call_with_keywords(&callee_val, &arg_values, &keyword_values);
                 debug_println!("          [DEBUG] Function call result: {:?}", result);
                 result
             }
@@ -676,19 +1925,25 @@ impl Interpreter {
                 debug_println!("          [DEBUG] Target value content: {:?}", target_val);
                 self.interpret_member_access(&target_val, member)
             }
-            Expression::Index { target, index, .. } => {
+            Expression::Spawn { expression, span } => {
+                self.set_execution_context(span.file_id, span.span);
+                self.spawn_task(Arc::new((**expression).clone()))
+            }
+            Expression::Propagate { expression, span } => {
+                let value = self.interpret_expression(expression)?;
+                self.propagate(value, span)
+            }
+            Expression::Index { target, index, span } => {
                 let target_val = self.interpret_expression(target)?;
                 let index_val = self.interpret_expression(index)?;
-                self.interpret_index_access(&target_val, &index_val)
-            }
-            Expression::Lambda { params, body, .. } => {
-                // Create a closure
-                Ok(Value::Closure {
-                    params: params.iter().map(|p| p.name.clone()).collect(),
-                    body: *body.clone(),
-                    closure: HashMap::new(),
-                })
+                self.set_execution_context(span.file_id, span.span);
+                self.index_value(&target_val, &index_val)
             }
+            Expression::Lambda { params, body, .. } => Ok(self.make_closure(
+                params.iter().map(|p| p.name.clone()).collect(),
+                params.iter().map(|p| p.param_type.clone()).collect(),
+                Arc::new((**body).clone()),
+            )),
             Expression::If {
                 condition,
                 then_expr,
@@ -706,32 +1961,32 @@ impl Interpreter {
                 expression, arms, ..
             } => {
                 let value_val = self.interpret_expression(expression)?;
-                self.interpret_match(&value_val, arms)
+                let result = self.interpret_match(&value_val, arms)?;
+                self.completion_value(result)
             }
-            Expression::StructLiteral { name, fields, .. } => {
+            Expression::StructLiteral { name, fields, span } => {
+                self.set_execution_context(span.file_id, span.span);
+                self.check_struct_fields(name, fields.iter().map(|field| (field.name.as_str(), &field.span)))?;
                 let mut field_map = HashMap::new();
                 for field in fields {
                     let value = self.interpret_expression(&field.value)?;
                     field_map.insert(field.name.clone(), value);
                 }
-                Ok(Value::Struct {
-                    name: name.clone(),
-                    fields: field_map,
-                })
+                Ok(Value::new_struct(name.clone(), field_map))
             }
             Expression::VecLiteral { elements, .. } => {
                 let mut vec = Vec::new();
                 for element in elements {
                     vec.push(self.interpret_expression(element)?);
                 }
-                Ok(Value::Vec(vec))
+                Ok(Value::vec(vec))
             }
             Expression::SetLiteral { elements, .. } => {
                 let mut set = std::collections::HashSet::new();
                 for element in elements {
                     set.insert(self.interpret_expression(element)?);
                 }
-                Ok(Value::Set(set))
+                Ok(Value::set(set))
             }
             Expression::MapLiteral { entries, .. } => {
                 let mut map = std::collections::HashMap::new();
@@ -740,7 +1995,7 @@ impl Interpreter {
                     let value = self.interpret_expression(&entry.value)?;
                     map.insert(key, value);
                 }
-                Ok(Value::Map(map))
+                Ok(Value::map(map))
             }
             Expression::TupleLiteral { elements, .. } => {
                 let mut tuple = Vec::new();
@@ -758,19 +2013,92 @@ impl Interpreter {
                 debug_println!("           Range expression");
                 let start_val = self.interpret_expression(start)?;
                 let end_val = self.interpret_expression(end)?;
+                self.make_range(start_val, end_val, *inclusive)
+            }
+        }
+    }
 
-                if let (Value::Int(start_int), Value::Int(end_int)) = (start_val, end_val) {
-                    let vec: Vec<Value> = if *inclusive {
-                        (start_int..=end_int).map(|i| Value::Int(i)).collect()
-                    } else {
-                        (start_int..end_int).map(|i| Value::Int(i)).collect()
-                    };
-                    Ok(Value::Vec(vec))
-                } else {
-                    Err(self.runtime_error("Range bounds must be integers".to_string()))
-                }
+    /// Start a task that evaluates `body` on its own snapshot of the program state
+    pub(crate) fn spawn_task(&mut self, body: Arc<Expression>) -> Result<Value, RuntimeError> {
+        let mut task_interpreter = self.fork();
+        let running = gc::RunningTask::start();
+        let handle = self
+            .concurrency()?
+            .spawn("spawn".to_string(), move || {
+                running.enter();
+                task_interpreter.evaluate(&body)
+            })
+            .map_err(|e| self.runtime_error(e))?;
+        Ok(Value::Task { id: handle.id })
+    }
+
+    /// Apply `?` to a value: unwrap `Some`/`Ok`, or return `None`/`Err` early from
+    /// the enclosing function
+    pub(crate) fn propagate(&mut self, value: Value, span: &SourceSpan) -> Result<Value, RuntimeError> {
+        match &value {
+            Value::Enum { name, variant, fields }
+                if (name == "Option" && variant == "Some")
+                    || (name == "Result" && variant == "Ok") =>
+            {
+                Ok(fields.first().cloned().unwrap_or(Value::None))
             }
-            _ => Err(self.runtime_error("Unsupported expression type".to_string())),
+            // Err and None return early from the enclosing function
+            Value::None => Err(RuntimeError::propagated(value, span.file_id, span.span)),
+            Value::Enum { name, .. } if name == "Result" => {
+                Err(RuntimeError::propagated(value, span.file_id, span.span))
+            }
+            _ => {
+                self.set_execution_context(span.file_id, span.span);
+                Err(self.runtime_error(format!(
+                    "Cannot use '?' on {} value; expected Option or Result",
+                    self.get_value_type_name(&value)
+                )))
+            }
+        }
+    }
+
+    /// Create a closure that captures the enclosing frames by value
+    pub(crate) fn make_closure(&self, params: Vec<String>, param_types: Vec<Type>, body: Arc<Expression>) -> Value {
+        Value::Closure {
+            params,
+            param_types,
+            body,
+            captured: self.environment.capture(),
+            globals: self.environment.globals(),
+            type_params: self.type_params.clone(),
+        }
+    }
+
+    /// Reject a struct literal whose struct is not visible here, or that names a
+    /// field its struct does not declare
+    pub(crate) fn check_struct_fields<'f>(
+        &mut self,
+        name: &str,
+        fields: impl IntoIterator<Item = (&'f str, &'f SourceSpan)>,
+    ) -> Result<(), RuntimeError> {
+        let struct_decl = match self.types.structs.get(name) {
+            Some(struct_decl) => struct_decl,
+            None => return Err(self.runtime_error(format!("Unknown struct type '{}'", name))),
+        };
+        let unknown_field = fields
+            .into_iter()
+            .find(|(field, _)| !struct_decl.fields.iter().any(|f| f.name == *field));
+        if let Some((field, span)) = unknown_field {
+            self.set_execution_context(span.file_id, span.span);
+            return Err(self.runtime_error(format!(
+                "Struct '{}' has no field named '{}'",
+                name, field
+            )));
+        }
+        Ok(())
+    }
+
+    /// Create a range. Ranges are lazy; values are produced as they are iterated
+    pub(crate) fn make_range(&self, start: Value, end: Value, inclusive: bool) -> Result<Value, RuntimeError> {
+        if let (Value::Int(start), Value::Int(end)) = (start, end) {
+            Ok(Value::Range { start, end, inclusive })
+        } else {
+            Err(self.runtime_error("Range bounds must be integers".to_string()))
         }
     }
 
@@ -818,6 +2146,13 @@ impl Interpreter {
             BinaryOperator::Multiply => self.multiply_values(left, right),
             BinaryOperator::Divide => self.divide_values(left, right),
             BinaryOperator::Modulo => self.modulo_values(left, right),
+            BinaryOperator::Power => self.power_values(left, right),
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => {
+                self.shift_values(left, op, right)
+            }
+            BinaryOperator::BitAnd | BinaryOperator::BitOr | BinaryOperator::BitXor => {
+                self.bitwise_values(left, op, right)
+            }
             BinaryOperator::Equal => Ok(Value::Bool(left == right)),
             BinaryOperator::NotEqual => Ok(Value::Bool(left != right)),
             BinaryOperator::LessThan => self.compare_values(left, right, |a, b| a < b),
@@ -832,6 +2167,94 @@ impl Interpreter {
         result
     }
 
+    /// `impl` method for an operator symbol on a user struct or enum
+    fn find_operator_method(&self, value: &Value, symbol: &str) -> Option<ImplMethod> {
+        match value.unwrap_union() {
+            Value::Struct { .. } | Value::Enum { .. } => self.find_method(value, symbol),
+            _ => None,
+        }
+    }
+
+    /// Apply a binary operator. When an operand is a user struct or enum, the
+    /// `impl` method named by the operator symbol is called with the left operand
+    /// as `self`; commutative operators fall back to the right operand's method,
+    /// `!=` is derived from `==`, and `>`, `<=` and `>=` from `<`.
+    pub(crate) fn apply_binary_operator(
+        &mut self,
+        left: &Value,
+        op: &BinaryOperator,
+        right: &Value,
+    ) -> Result<Value, RuntimeError> {
+        let symbol = op.to_string();
+        if let Some(method) = self.find_operator_method(left, &symbol) {
+            return self.call_method(left, &method, std::slice::from_ref(right));
+        }
+        if op.is_commutative() {
+            if let Some(method) = self.find_operator_method(right, &symbol) {
+                return self.call_method(right, &method, std::slice::from_ref(left));
+            }
+        }
+
+        // (receiver, argument, method, whether to negate the result)
+        let derived = match op {
+            BinaryOperator::NotEqual => self.find_operator_method(left, "==").map(|m| (left, right, m, true)),
+            BinaryOperator::GreaterThan => self.find_operator_method(right, "<").map(|m| (right, left, m, false)),
+            BinaryOperator::LessThanEqual => self.find_operator_method(right, "<").map(|m| (right, left, m, true)),
+            BinaryOperator::GreaterThanEqual => self.find_operator_method(left, "<").map(|m| (left, right, m, true)),
+            _ => None,
+        };
+        if let Some((receiver, argument, method, negate)) = derived {
+            let result = self.call_method(receiver, &method, std::slice::from_ref(argument))?;
+            return Ok(if negate { Value::Bool(!self.is_truthy(&result)) } else { result });
+        }
+
+        // Equality and logical operators keep their built-in meaning on user types
+        let overloadable = !matches!(
+            op,
+            BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::And | BinaryOperator::Or
+        );
+        if overloadable {
+            if let Value::Struct { name, .. } | Value::Enum { name, .. } = right.unwrap_union() {
+                if self.find_operator_method(right, &symbol).is_some() {
+                    return Err(self.runtime_error(format!(
+                        "Operator '{}' is not commutative, so it only dispatches on its left operand; \
+                         '{}' implements it but is on the right",
+                        symbol, name
+                    )));
+                }
+            }
+            for operand in [left, right] {
+                if let Value::Struct { name, .. } | Value::Enum { name, .. } = operand.unwrap_union() {
+                    if !matches!(name.as_str(), "Option" | "Result") {
+                        return Err(self.runtime_error(format!(
+                            "Type '{}' does not implement operator '{}'",
+                            name, symbol
+                        )));
+                    }
+                }
+            }
+        }
+        self.interpret_binary_operation(left, op, right)
+    }
+
+    /// Apply a unary operator, calling a user type's `-`, `!` or `~` method when
+    /// it declares one that takes no arguments
+    pub(crate) fn apply_unary_operator(&mut self, op: &UnaryOperator, operand: &Value) -> Result<Value, RuntimeError> {
+        match self.find_operator_method(operand, &op.method_key()) {
+            Some(method) => self.call_method(operand, &method, &[]),
+            None => self.interpret_unary_operation(op, operand),
+        }
+    }
+
+    /// Index into a value, calling a user type's `[]` method
+    pub(crate) fn index_value(&mut self, target: &Value, index: &Value) -> Result<Value, RuntimeError> {
+        let (target, index) = (target.unwrap_union(), index.unwrap_union());
+        match self.find_operator_method(target, "[]") {
+            Some(method) => self.call_method(target, &method, std::slice::from_ref(index)),
+            None => self.interpret_index_access(target, index),
+        }
+    }
+
     /// Interpret a unary operation
     fn interpret_unary_operation(
         &self,
@@ -841,18 +2264,60 @@ impl Interpreter {
         match op {
             UnaryOperator::Not => Ok(Value::Bool(!self.is_truthy(operand))),
             UnaryOperator::Negate => self.negate_value(operand),
-            UnaryOperator::BitNot => Err(self.runtime_error("Bitwise operations not implemented".to_string())),
+            UnaryOperator::BitNot => match operand.unwrap_union() {
+                Value::Int(a) => Ok(Value::Int(!a)),
+                _ => Err(self.runtime_error("Cannot apply ~ to this type".to_string())),
+            },
+        }
+    }
+
+    /// Evaluate the keyword arguments of a call, in the order they were written
+    fn interpret_keyword_args(
+        &mut self,
+        named_args: &[FieldInit],
+    ) -> Result<Vec<(String, Value)>, RuntimeError> {
+        let mut keyword_values = Vec::with_capacity(named_args.len());
+        for named_arg in named_args {
+            keyword_values.push((named_arg.name.clone(), self.interpret_expression(&named_arg.value)?));
         }
+        Ok(keyword_values)
     }
 
     /// Interpret a function call
     pub fn interpret_call(&mut self, callee: &Value, args: &[Value]) -> Result<Value, RuntimeError> {
+        self.interpret_call_with_keywords(callee, args, &[])
+    }
+
+    /// Interpret a function call that may also pass keyword arguments
+    pub fn interpret_call_with_keywords(
+        &mut self,
+        callee: &Value,
+        args: &[Value],
+        keyword_args: &[(String, Value)],
+    ) -> Result<Value, RuntimeError> {
         debug_println!(
             "            [DEBUG] interpret_call: {:?}",
             std::mem::discriminant(callee)
         );
+        let call_site = (self.current_file_id, self.current_span);
+
+        // Only functions declared in TJLang have parameter names to match keywords against
+        if !keyword_args.is_empty() {
+            let takes_keywords = matches!(
+                callee,
+                Value::Function { name, .. }
+                    if !name.starts_with("primitive_method::")
+                        && self.stdlib.get_function(name).is_none()
+            );
+            if !takes_keywords {
+                return Err(self.runtime_error(
+                    "Keyword arguments can only be passed to functions declared with 'def'".to_string(),
+                ));
+            }
+        }
+
         match callee {
-            Value::Function { name, params, param_types, body, .. } => {
+            Value::Function { name, generic_params, params, param_types, param_defaults, return_type, body, globals } => {
                 debug_println!("             Calling function: {}", name);
 
                 // Check if it's a primitive method call
@@ -884,7 +2349,15 @@ impl Interpreter {
                         name,
                         args
                     );
-                    let result = native_func(self, args).map_err(|e| self.runtime_error(e))?;
+                    // Native functions see the values inside unions
+                    let unwrapped: Vec<Value>;
+                    let args = if args.iter().any(|arg| matches!(arg, Value::Union { .. })) {
+                        unwrapped = args.iter().map(|arg| arg.unwrap_union().clone()).collect();
+                        &unwrapped[..]
+                    } else {
+                        args
+                    };
+                    let result = native_func(self, args).map_err(|e| self.native_error(e))?;
                     debug_println!("              [DEBUG] Stdlib function result: {:?}", result);
                     return Ok(result);
                 }
@@ -897,32 +2370,53 @@ impl Interpreter {
                 debug_println!("               Function params: {:?}", params);
                 debug_println!("               Function body: {:?}", body);
 
-                // Validate parameters (count and types)
-                self.validate_function_call(name, params, param_types, args)?;
+                // Match positional and keyword arguments to parameters
+                let slots = self.bind_arguments(name, params, param_defaults, args, keyword_args)?;
 
                 debug_println!(
                     "               Creating new environment with {} params",
                     params.len()
                 );
-                // Create new environment with parameters that has access to global scope
-                let mut new_env = Environment::with_parent(self.environment.clone());
-                for (param_name, arg) in params.iter().zip(args.iter()) {
-                    debug_println!("                 Binding param {} = {:?}", param_name, arg);
-                    new_env.define(param_name.clone(), arg.clone());
-                }
+                // The call gets a fresh frame over the globals of the module that
+                // declared the function, so functions imported from a module see its globals
+                let globals = globals.clone().unwrap_or_else(|| self.environment.globals());
+                let new_env = Environment::for_call(globals, Vec::new());
+
+                // Save current environment and switch to new one, so that defaults
+                // are evaluated where the earlier parameters are visible
+                let type_params = generic_params.iter().map(|g| g.name.clone()).collect();
+                let old_env = self.swap_environment(new_env, type_params);
+
+                // Validate parameters (count and types) once the defaults are filled in
+                let bound = self
+                    .bind_parameters(params, param_defaults, slots, 0)
+                    .and_then(|values| {
+                        self.validate_function_call(name, generic_params, params, param_types, &values)?;
+                        self.wrap_union_parameters(param_types, 0, &values);
+                        self.enter_call()?;
+                        Ok(values)
+                    });
+                let values = match bound {
+                    Ok(values) => values,
+                    Err(error) => {
+                        self.restore_environment(old_env);
+                        return Err(error);
+                    }
+                };
 
                 debug_println!("               Executing function body...");
                 debug_println!("              [DEBUG] Function body: {:?}", body);
-                // Save current environment and switch to new one
-                let old_env = std::mem::replace(&mut self.environment, new_env);
-                let result = match self.interpret_block_with_control_flow(body) {
-                    Ok(ExecutionResult::Value(val)) => Ok(val),
-                    Ok(ExecutionResult::Return(val)) => Ok(val),
-                    Ok(ExecutionResult::Break) => Err(self.runtime_error("Break statement outside of loop".to_string())),
-                    Ok(ExecutionResult::Continue) => Err(self.runtime_error("Continue statement outside of loop".to_string())),
-                    Err(e) => Err(e),
-                };
-                self.environment = old_env;
+                // A body that runs off its end gives the value of its last statement,
+                // which is checked like a returned value
+                let result = match self.execute_body(body) {
+                    Ok(result) => self
+                        .completion_value(result)
+                        .and_then(|value| self.check_return_type(value, return_type, name)),
+                    Err(e) => e.catch_propagated(),
+                }
+                .map_err(|e| self.unwind_frame(e, name, params, &values, call_site));
+                self.call_depth -= 1;
+                self.restore_environment(old_env);
                 debug_println!(
                     "              [DEBUG] Function {} completed with result: {:?}",
                     name,
@@ -930,20 +2424,57 @@ impl Interpreter {
                 );
                 result
             }
-            Value::Closure { params, body, .. } => {
+            Value::Closure { params, param_types, body, captured, globals, type_params } => {
                 debug_println!("             Calling closure with {} params", params.len());
-                // Create new environment with parameters
-                let mut new_env = Environment::new();
-                for (param_name, arg) in params.iter().zip(args.iter()) {
-                    new_env.define(param_name.clone(), arg.clone());
+                if args.len() != params.len() {
+                    return Err(self.runtime_error(format!(
+                        "Closure expects {} arguments, got {}",
+                        params.len(),
+                        args.len()
+                    )));
                 }
 
-                // Save current environment and switch to new one
-                let old_env = std::mem::replace(&mut self.environment, new_env);
-                let result = self.interpret_expression(body);
-                self.environment = old_env;
+                // Parameters fill the first slots; captured variables are reached
+                // through the frames the closure was created in
+                let mut new_env = Environment::for_call(globals.clone(), captured.clone());
+                new_env.frame = args.iter().cloned().map(Some).collect();
+
+                // Save current environment and switch to new one. Parameter types are
+                // checked there, where the type parameters the lambda saw are in scope.
+                let old_env = self.swap_environment(new_env, type_params.clone());
+                let checked = self.check_closure_arguments(params, param_types, args).and_then(|()| {
+                    self.wrap_union_parameters(param_types, 0, args);
+                    self.enter_call()
+                });
+                if let Err(error) = checked {
+                    self.restore_environment(old_env);
+                    return Err(error);
+                }
+                let result = self
+                    .evaluate(body)
+                    .or_else(RuntimeError::catch_propagated)
+                    .map_err(|e| self.unwind_frame(e, "<lambda>", params, args, call_site));
+                self.call_depth -= 1;
+                self.restore_environment(old_env);
                 result
             }
+            // Enum variant constructors build the variant from their arguments
+            Value::EnumConstructor { enum_name, variant, field_types } => {
+                if args.len() != field_types.len() {
+                    return Err(self.runtime_error(format!(
+                        "Enum variant '{}::{}' expects {} field(s), got {}",
+                        enum_name,
+                        variant,
+                        field_types.len(),
+                        args.len()
+                    )));
+                }
+                Ok(Value::Enum {
+                    name: enum_name.clone(),
+                    variant: variant.clone(),
+                    fields: args.to_vec(),
+                })
+            }
             _ => {
                 debug_println!("             Cannot call non-function value: {:?}", callee);
                 Err(self.runtime_error("Cannot call non-function value".to_string()))
@@ -951,21 +2482,277 @@ impl Interpreter {
         }
     }
 
+    /// Call `target.member(args)`: an impl method, a built-in method of the value,
+    /// or a function stored in a field or exported by a module
+    pub(crate) fn call_member(
+        &mut self,
+        target_val: &Value,
+        member: &str,
+        args: &[Value],
+        keyword_args: &[(String, Value)],
+    ) -> Result<Value, RuntimeError> {
+        let target_val = target_val.unwrap_union();
+        // Methods from impl blocks, unless a struct field of that name shadows them
+        let is_field = matches!(
+            target_val,
+            Value::Struct { fields, .. } if fields.read().contains_key(member)
+        );
+        if !is_field {
+            if let Some(method) = self.find_method(target_val, member) {
+                debug_println!("          [DEBUG] Dispatching impl method: {}", member);
+                return self
+                    .call_method_with_receiver(target_val, &method, args, keyword_args)
+                    .map(|(result, _)| result);
+            }
+        }
+
+        // Built-in methods only take positional arguments
+        if !keyword_args.is_empty() {
+            return Err(self.runtime_error(format!(
+                "Method '{}' does not take keyword arguments",
+                member
+            )));
+        }
+
+        // Tasks and channels are handles into the concurrency runtime
+        let is_concurrency_method = match target_val {
+            Value::Task { .. } => TASK_METHODS.contains(&member),
+            Value::Channel { .. } => CHANNEL_METHODS.contains(&member),
+            _ => false,
+        };
+        if is_concurrency_method {
+            return self.call_concurrency_method(target_val, member, args);
+        }
+
+        // Option and Result methods may call back into TJLang functions
+        if crate::primitive_methods::is_option_result_method(target_val, member) {
+            return self.call_option_result_method(target_val, member, args);
+        }
+
+        // Vector, set and map methods may call back into TJLang functions
+        if crate::primitive_methods::is_collection_callback_method(target_val, member) {
+            return self.call_collection_callback_method(target_val, member, args);
+        }
+
+        // Lazy iteration over ranges, collections and user iterators
+        if self.is_iterator_method(target_val, member) {
+            return self.call_iterator_method(target_val, member, args);
+        }
+
+        // Collections are shared, so mutating methods change them in place and
+        // every binding of the same collection sees the change
+        if crate::primitive_methods::is_mutating_method(target_val, member) {
+            return crate::primitive_methods::execute_mutating_method(target_val, member, args)
+                .map_err(|e| self.runtime_error(e));
+        }
+
+        // Only apply primitive method handling to actual primitive types, and to
+        // structs/enums for the universal methods (to_string, clone, ...)
+        let is_primitive = matches!(
+            target_val,
+            Value::Int(_)
+                | Value::BigInt(_)
+                | Value::Float(_)
+                | Value::Bool(_)
+                | Value::String(_)
+                | Value::None
+                | Value::Tuple(_)
+                | Value::Vec(_)
+                | Value::Set(_)
+                | Value::Map(_)
+                | Value::Range { .. }
+                | Value::Iterator(_)
+                | Value::Regex(_)
+                | Value::Generator(_)
+                | Value::Native(_)
+                | Value::Enum { .. }
+        ) || (matches!(target_val, Value::Struct { .. }) && !is_field);
+
+        if is_primitive && args.is_empty() {
+            return match member {
+                "to_string" => Ok(Value::String(target_val.to_string())),
+                "clone" | "copy" => Ok(target_val.deep_copy()),
+                "type_name" => Ok(Value::String(crate::primitive_methods::get_type_name(target_val))),
+                "is_null" => Ok(Value::Bool(matches!(target_val, Value::None))),
+                "is_not_null" => Ok(Value::Bool(!matches!(target_val, Value::None))),
+                "hash" => Ok(Value::Int(crate::primitive_methods::get_hash_code(target_val))),
+                "is_int" => Ok(Value::Bool(matches!(target_val, Value::Int(_)))),
+                "is_float" => Ok(Value::Bool(matches!(target_val, Value::Float(_)))),
+                "is_bool" => Ok(Value::Bool(matches!(target_val, Value::Bool(_)))),
+                "is_str" => Ok(Value::Bool(matches!(target_val, Value::String(_)))),
+                "is_none" => Ok(Value::Bool(matches!(target_val, Value::None))),
+                "debug_string" => Ok(Value::String(format!("{:?}", target_val))),
+                "pretty_string" => Ok(Value::String(crate::primitive_methods::get_pretty_string(target_val))),
+                // Try type-specific methods
+                _ => crate::primitive_methods::get_primitive_method(target_val, member)
+                    .map_err(|e| self.runtime_error(e)),
+            };
+        } else if is_primitive {
+            // For primitive methods that do take arguments
+            return crate::primitive_methods::execute_primitive_method(target_val, member, args)
+                .map_err(|e| self.runtime_error(e));
+        }
+
+        // A function stored in a struct field or exported by a module
+        let callee_val = self.interpret_member_access(target_val, member)?;
+        self.interpret_call_with_keywords(&callee_val, args, keyword_args)
+    }
+
+    /// Call a method from an impl block with `self` bound to the receiver
+    fn call_method(
+        &mut self,
+        receiver: &Value,
+        method: &ImplMethod,
+        args: &[Value],
+    ) -> Result<Value, RuntimeError> {
+        self.call_method_with_receiver(receiver, method, args, &[])
+            .map(|(result, _)| result)
+    }
+
+    /// Call a method, also returning `self` as the method body left it
+    fn call_method_with_receiver(
+        &mut self,
+        receiver: &Value,
+        method: &ImplMethod,
+        args: &[Value],
+        keyword_args: &[(String, Value)],
+    ) -> Result<(Value, Value), RuntimeError> {
+        let ImplMethod { decl: method, globals } = method;
+        debug_println!("             Calling method: {}", method.name);
+        let call_site = (self.current_file_id, self.current_span);
+        let type_name = self.impl_type_name(receiver).unwrap_or_default();
+        let qualified_name = format!("{}.{}", type_name, method.name);
+
+        // An explicit leading `self` parameter receives the receiver itself
+        let explicit_self = method.params.first().map(|p| p.name == "self").unwrap_or(false);
+        let declared = if explicit_self { &method.params[1..] } else { &method.params[..] };
+        let params: Vec<String> = declared.iter().map(|p| p.name.clone()).collect();
+        let param_types: Vec<Type> = declared.iter().map(|p| p.param_type.clone()).collect();
+        let param_defaults: Vec<Option<Expression>> =
+            declared.iter().map(|p| p.default_value.clone()).collect();
+        let slots = self.bind_arguments(&qualified_name, &params, &param_defaults, args, keyword_args)?;
+
+        // `self` takes the first slot, followed by the parameters
+        let mut new_env = Environment::for_call(globals.clone(), Vec::new());
+        new_env.frame.push(Some(receiver.clone()));
+
+        let old_env = self.swap_environment(new_env, vec!["Self".to_string()]);
+        let bound = self
+            .bind_parameters(&params, &param_defaults, slots, 1)
+            .and_then(|values| {
+                self.validate_function_call(&qualified_name, &[], &params, &param_types, &values)?;
+                self.wrap_union_parameters(&param_types, 1, &values);
+                self.enter_call()?;
+                Ok(values)
+            });
+        let values = match bound {
+            Ok(values) => values,
+            Err(error) => {
+                self.restore_environment(old_env);
+                return Err(error);
+            }
+        };
+        let result = match self.execute_body(&method.body) {
+            Ok(result) => self
+                .completion_value(result)
+                .and_then(|value| self.check_return_type(value, &method.return_type, &qualified_name)),
+            Err(e) => e.catch_propagated(),
+        }
+        .map_err(|e| self.unwind_frame(e, &qualified_name, &params, &values, call_site));
+        self.call_depth -= 1;
+        let updated_receiver = self
+            .environment
+            .local(Slot { depth: 0, index: 0 })
+            .cloned()
+            .unwrap_or_else(|| receiver.clone());
+        self.restore_environment(old_env);
+        debug_println!(
+            "              [DEBUG] Method {} completed with result: {:?}",
+            qualified_name,
+            result
+        );
+        result.map(|value| (value, updated_receiver))
+    }
+
+    /// Run an Option/Result method, letting it call TJLang functions
+    fn call_option_result_method(
+        &mut self,
+        target: &Value,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, RuntimeError> {
+        crate::primitive_methods::execute_option_result_method(
+            target,
+            method,
+            args,
+            &mut |function, call_args| self.call_function_value(function, call_args),
+        )
+        .map_err(|message| self.native_error(message))
+    }
+
+    /// Run a vector, set or map method that takes a TJLang function
+    fn call_collection_callback_method(
+        &mut self,
+        target: &Value,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, RuntimeError> {
+        crate::primitive_methods::execute_collection_callback_method(
+            target,
+            method,
+            args,
+            &mut |function, call_args| self.call_function_value(function, call_args),
+        )
+        .map_err(|message| self.native_error(message))
+    }
+
+    /// Call a TJLang function or closure from native code: a stdlib function or a
+    /// built-in method that takes a callback. A failure comes back as its message for
+    /// the native code to return; the call into native code then raises the original
+    /// error, so it keeps its raised value, span and stack.
+    pub fn call_function_value(&mut self, function: &Value, args: &[Value]) -> Result<Value, String> {
+        let function = function.unwrap_union();
+        if !matches!(
+            function,
+            Value::Function { .. } | Value::Closure { .. } | Value::EnumConstructor { .. }
+        ) {
+            return Err(format!("Expected a function, got {}", self.describe_type(function)));
+        }
+        // Errors the native code reports afterwards belong to its own call site
+        let call_site = (self.current_file_id, self.current_span);
+        let result = self.interpret_call(function, args);
+        (self.current_file_id, self.current_span) = call_site;
+        result.map_err(|error| {
+            let message = error.message.clone();
+            self.callback_error = Some(error);
+            message
+        })
+    }
+
+    /// The error a native call that failed with `message` raises: the error of the
+    /// function it called back into when it passed that failure on
+    fn native_error(&mut self, message: String) -> RuntimeError {
+        match self.callback_error.take() {
+            Some(error) if error.message == message => error,
+            _ => self.runtime_error(message),
+        }
+    }
+
     /// Interpret member access
-    fn interpret_member_access(&self, target: &Value, member: &str) -> Result<Value, RuntimeError> {
+    pub(crate) fn interpret_member_access(&self, target: &Value, member: &str) -> Result<Value, RuntimeError> {
         debug_println!(
             "[DEBUG] DEBUG: interpret_member_access called: target={:?}, member={}",
             target,
             member
         );
-        match target {
+        match target.unwrap_union() {
             Value::Struct { fields, name } => {
                 debug_println!(
                     "[DEBUG] DEBUG: Found struct '{}' with fields: {:?}",
                     name,
-                    fields.keys().collect::<Vec<_>>()
+                    fields.read().keys().collect::<Vec<_>>()
                 );
-                if let Some(field_value) = fields.get(member) {
+                if let Some(field_value) = fields.read().get(member) {
                     debug_println!(
                         "[DEBUG] DEBUG: Found field '{}' in struct '{}'",
                         member,
@@ -1000,7 +2787,7 @@ impl Interpreter {
         // For methods that don't require arguments, execute them directly
         match method {
             "to_string" => Ok(Value::String(target.to_string())),
-            "clone" => Ok(target.clone()),
+            "clone" | "copy" => Ok(target.deep_copy()),
             "type_name" => Ok(Value::String(crate::primitive_methods::get_type_name(
                 target,
             ))),
@@ -1025,19 +2812,25 @@ impl Interpreter {
     /// Get the type name of a value for error messages
     fn get_value_type_name(&self, value: &Value) -> &'static str {
         match value {
-            Value::Int(_) => "int",
+            Value::Int(_) | Value::BigInt(_) => "int",
             Value::Float(_) => "float",
             Value::String(_) => "string",
             Value::Bool(_) => "bool",
             Value::Struct { .. } => "struct",
             Value::Function { .. } => "function",
             Value::Closure { .. } => "closure",
+            Value::EnumConstructor { .. } => "function",
             Value::None => "none",
             Value::Enum { .. } => "enum",
             Value::Tuple(_) => "tuple",
             Value::Vec(_) => "vec",
             Value::Set(_) => "set",
             Value::Map(_) => "map",
+            Value::Range { .. } => "range",
+            Value::Iterator(_) => "iterator",
+            Value::Regex(_) => "regex",
+            Value::Generator(_) => "generator",
+            Value::Native(object) => object.type_name(),
             Value::Channel { .. } => "channel",
             Value::Task { .. } => "task",
             Value::Reference(_) => "reference",
@@ -1046,10 +2839,129 @@ impl Interpreter {
         }
     }
 
+    /// Read a variable from its slot, falling back to the globals when it is not a
+    /// local or has not been assigned yet
+    pub(crate) fn lookup_variable(&self, name: &str, slot: Option<Slot>) -> Option<Value> {
+        slot.and_then(|slot| self.environment.local(slot).cloned())
+            .or_else(|| self.environment.get(name))
+            // A struct or type alias name refers to the type, as in `JSON.decode(text, Point)`
+            .or_else(|| {
+                (self.types.structs.contains_key(name) || self.types.type_aliases.contains_key(name))
+                    .then(|| Value::Type(Type::Identifier(name.to_string())))
+            })
+    }
+
+    /// Assign a variable in its slot, or as a global when it is not a local
+    fn store_variable(&mut self, name: String, slot: Option<Slot>, value: Value) {
+        match slot {
+            Some(slot) => self.environment.set_local(slot, value),
+            None => self.environment.define(name, value),
+        }
+    }
+
+    /// Store the values a match arm or except clause pattern bound
+    pub(crate) fn bind_pattern(&mut self, clause: &SourceSpan, bindings: HashMap<String, Value>) {
+        for (name, value) in bindings {
+            let slot = self
+                .resolution
+                .pattern_slot(clause, &name)
+                .map(|index| Slot { depth: 0, index });
+            self.store_variable(name, slot, value);
+        }
+    }
+
+    /// Evaluate the container and index of an assignment target, so compound
+    /// assignments evaluate them only once
+    fn resolve_place(&mut self, target: &Expression) -> Result<Place, RuntimeError> {
+        match target {
+            Expression::Variable { name, span } => Ok(Place::Variable {
+                name: name.clone(),
+                slot: self.resolution.local(span),
+            }),
+            Expression::Index { target, index, .. } => {
+                let target = self.interpret_expression(target)?;
+                let index = self.interpret_expression(index)?;
+                Ok(Place::Index { target, index })
+            }
+            Expression::Member { target, member, .. } => {
+                let target = self.interpret_expression(target)?;
+                Ok(Place::Field { target, name: member.clone() })
+            }
+            _ => Err(self.runtime_error(
+                "Left side of assignment must be a variable, index or field".to_string(),
+            )),
+        }
+    }
+
+    /// Current value stored at an assignment target
+    pub(crate) fn read_place(&mut self, place: &Place) -> Result<Value, RuntimeError> {
+        match place {
+            Place::Variable { name, slot } => self
+                .lookup_variable(name, *slot)
+                .ok_or_else(|| self.runtime_error(format!("Undefined variable: {}", name))),
+            Place::Index { target, index } => self.index_value(target, index),
+            Place::Field { target, name } => self.interpret_member_access(target, name),
+        }
+    }
+
+    /// Store a value at an assignment target. Containers are shared, so writing
+    /// into one is visible through every binding that refers to it.
+    pub(crate) fn write_place(&mut self, place: Place, value: Value) -> Result<(), RuntimeError> {
+        match place {
+            Place::Variable { name, slot } => {
+                self.store_variable(name, slot, value);
+                Ok(())
+            }
+            Place::Index { target, index } => match (&target, &index) {
+                (Value::Vec(vec), Value::Int(idx)) => {
+                    let mut vec = vec.write();
+                    if *idx >= 0 && (*idx as usize) < vec.len() {
+                        vec[*idx as usize] = value;
+                        Ok(())
+                    } else {
+                        Err(self.runtime_error("Index out of bounds".to_string()))
+                    }
+                }
+                (Value::Vec(_), _) => {
+                    Err(self.runtime_error("Vector index must be an integer".to_string()))
+                }
+                (Value::Map(map), _) => {
+                    map.write().insert(index, value);
+                    Ok(())
+                }
+                _ => Err(self.runtime_error(format!(
+                    "Cannot assign to an index of {} value",
+                    self.get_value_type_name(&target)
+                ))),
+            },
+            Place::Field { target, name } => match &target {
+                Value::Struct { name: struct_name, fields } => {
+                    let mut fields = fields.write();
+                    match fields.get_mut(&name) {
+                        Some(field) => {
+                            *field = value;
+                            Ok(())
+                        }
+                        None => Err(self.runtime_error(format!(
+                            "Struct '{}' has no field named '{}'",
+                            struct_name, name
+                        ))),
+                    }
+                }
+                _ => Err(self.runtime_error(format!(
+                    "Cannot assign to field '{}' of {} value",
+                    name,
+                    self.get_value_type_name(&target)
+                ))),
+            },
+        }
+    }
+
     /// Interpret index access
     fn interpret_index_access(&self, target: &Value, index: &Value) -> Result<Value, RuntimeError> {
         match (target, index) {
             (Value::Vec(vec), Value::Int(idx)) => {
+                let vec = vec.read();
                 if *idx >= 0 && (*idx as usize) < vec.len() {
                     Ok(vec[*idx as usize].clone())
                 } else {
@@ -1063,51 +2975,39 @@ impl Interpreter {
                     Err(self.runtime_error("Index out of bounds".to_string()))
                 }
             }
+            (Value::Map(map), key) => map.read().get(key).cloned().ok_or_else(|| {
+                self.runtime_error(format!("Key {} not found in map", key.to_string()))
+            }),
             _ => Err(self.runtime_error("Cannot index non-vector/tuple value or invalid index type".to_string())),
         }
     }
 
-    /// Interpret a match expression
-    fn interpret_match(&mut self, value: &Value, arms: &[MatchArm]) -> Result<Value, RuntimeError> {
+    /// Interpret a match expression or statement, running the first arm that matches
+    fn interpret_match(&mut self, value: &Value, arms: &[MatchArm]) -> Result<ExecutionResult, RuntimeError> {
         for arm in arms {
-            if self.pattern_matches(value, &arm.pattern) {
-                return self.interpret_block(&arm.body);
+            let bindings = match PatternMatcher::new().match_pattern(value, &arm.pattern, self) {
+                MatchResult::Matched { bindings, .. } => bindings,
+                MatchResult::NotMatched | MatchResult::GuardFailed => continue,
+            };
+
+            // Bindings live in slots local to the arm, shared by the guard and the body
+            self.bind_pattern(&arm.span, bindings);
+            if let Some(result) = self.interpret_match_arm(arm)? {
+                return Ok(result);
             }
         }
         Err(self.runtime_error("No matching pattern found".to_string()))
     }
 
-    /// Check if a pattern matches a value
-    fn pattern_matches(&self, value: &Value, pattern: &Pattern) -> bool {
-        match (value, pattern) {
-            (Value::Int(v), Pattern::Literal(Literal::Int(p))) => v == p,
-            (Value::Float(v), Pattern::Literal(Literal::Float(p))) => (v - p).abs() < f64::EPSILON,
-            (Value::Bool(v), Pattern::Literal(Literal::Bool(p))) => v == p,
-            (Value::String(v), Pattern::Literal(Literal::String(p))) => v == p,
-            (
-                Value::Struct {
-                    name: v_name,
-                    fields: v_fields,
-                },
-                Pattern::Struct {
-                    name: p_name,
-                    fields: p_fields,
-                    ..
-                },
-            ) => {
-                v_name == p_name
-                    && p_fields.len() == v_fields.len()
-                    && p_fields.iter().all(|(p_field, p_value)| {
-                        v_fields
-                            .get(p_field)
-                            .map(|v_value| self.pattern_matches(v_value, p_value))
-                            .unwrap_or(false)
-                    })
-            }
-            (_, Pattern::Variable { .. }) => true, // Variables always match
-            (_, Pattern::Wildcard(_)) => true,
-            _ => false,
+    /// Run a matched arm, returning `None` when its guard rejects the value
+    fn interpret_match_arm(&mut self, arm: &MatchArm) -> Result<Option<ExecutionResult>, RuntimeError> {
+        if let Some(guard) = &arm.guard {
+            let guard_val = self.interpret_expression(guard)?;
+            if !self.is_truthy(&guard_val) {
+                return Ok(None);
+            }
         }
+        self.interpret_block_with_control_flow(&arm.body).map(Some)
     }
 
     /// Interpret a block with control flow handling
@@ -1132,6 +3032,7 @@ impl Interpreter {
                 ExecutionResult::Return(val) => return Ok(ExecutionResult::Return(val)),
                 ExecutionResult::Break => return Ok(ExecutionResult::Break),
                 ExecutionResult::Continue => return Ok(ExecutionResult::Continue),
+                ExecutionResult::Raise(val) => return Ok(ExecutionResult::Raise(val)),
                 ExecutionResult::Value(val) => ExecutionResult::Value(val),
             };
             debug_println!(
@@ -1144,22 +3045,21 @@ impl Interpreter {
         Ok(result)
     }
 
-    /// Interpret a block (legacy method for backward compatibility)
-    fn interpret_block(&mut self, block: &Block) -> Result<Value, RuntimeError> {
-        match self.interpret_block_with_control_flow(block)? {
-            ExecutionResult::Value(val) => Ok(val),
-            ExecutionResult::Return(val) => Ok(val),
-            ExecutionResult::Break => Err(self.runtime_error("Break statement outside of loop".to_string())),
-            ExecutionResult::Continue => Err(self.runtime_error("Continue statement outside of loop".to_string())),
-        }
-    }
-
     /// Interpret a statement with control flow handling
     fn interpret_statement_with_control_flow(&mut self, stmt: &Statement) -> Result<ExecutionResult, RuntimeError> {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || self.interpret_statement_unguarded(stmt))
+    }
+
+    /// Interpret a statement on the current stack segment
+    fn interpret_statement_unguarded(&mut self, stmt: &Statement) -> Result<ExecutionResult, RuntimeError> {
         debug_println!(
             "                    [DEBUG] interpret_statement_with_control_flow: {:?}",
             std::mem::discriminant(stmt)
         );
+        // Errors with no more precise location point at the statement
+        if let Some(span) = stmt.span() {
+            self.set_execution_context(span.file_id, span.span);
+        }
         match stmt {
             Statement::Expression(expr) => {
                 debug_println!("                       Expression statement");
@@ -1169,7 +3069,9 @@ impl Interpreter {
             Statement::Variable(var) => {
                 debug_println!("                       Variable statement: {}", var.name);
                 let value = self.interpret_expression(&var.value)?;
-                self.environment.define(var.name.clone(), value.clone());
+                let value = self.check_variable_type(value, &var.var_type, &var.name)?;
+                let slot = self.resolution.local(&var.span);
+                self.store_variable(var.name.clone(), slot, value.clone());
                 Ok(ExecutionResult::Value(value))
             }
             Statement::Return(ret_stmt) => {
@@ -1249,6 +3151,7 @@ impl Interpreter {
                         ExecutionResult::Break => break,
                         ExecutionResult::Continue => continue,
                         ExecutionResult::Return(val) => return Ok(ExecutionResult::Return(val)),
+                        ExecutionResult::Raise(val) => return Ok(ExecutionResult::Raise(val)),
                         ExecutionResult::Value(_) => {}
                     }
                 }
@@ -1260,6 +3163,7 @@ impl Interpreter {
                         ExecutionResult::Break => break,
                         ExecutionResult::Continue => continue,
                         ExecutionResult::Return(val) => return Ok(ExecutionResult::Return(val)),
+                        ExecutionResult::Raise(val) => return Ok(ExecutionResult::Raise(val)),
                         ExecutionResult::Value(_) => {}
                     }
                     let condition_val = self.interpret_expression(&do_while_stmt.condition)?;
@@ -1275,62 +3179,24 @@ impl Interpreter {
                         var_name,
                         iterable,
                         body,
+                        span,
                         ..
                     } => {
                         debug_println!("[DEBUG] FOR_LOOP: iterable expression = {:?}", iterable);
-                        // First, check if it's a Range expression and convert to vector
-                        let iter_val = if let Expression::Range {
-                            start,
-                            end,
-                            inclusive,
-                            ..
-                        } = iterable
-                        {
-                            debug_println!("[DEBUG] FOR_LOOP: Detected Range expression, start={:?}, end={:?}, inclusive={}", start, end, inclusive);
-                            let start_val = self.interpret_expression(start)?;
-                            let end_val = self.interpret_expression(end)?;
-
-                            if let (Value::Int(start_int), Value::Int(end_int)) =
-                                (start_val, end_val)
-                            {
-                                let vec: Vec<Value> = if *inclusive {
-                                    (start_int..=end_int).map(|i| Value::Int(i)).collect()
-                                } else {
-                                    (start_int..end_int).map(|i| Value::Int(i)).collect()
-                                };
-                                Value::Vec(vec)
-                            } else {
-                                return Err(self.runtime_error("Range bounds must be integers".to_string()));
-                            }
-                        } else {
-                            debug_println!(
-                                "[DEBUG] FOR_LOOP: NOT a Range expression, evaluating iterable"
-                            );
-                            // Evaluate the iterable expression
-                            self.interpret_expression(iterable)?
-                        };
-
-                        debug_println!("[DEBUG] FOR_LOOP: iter_val = {:?}", iter_val);
-                        // Now iterate over the value
-                        if let Value::Vec(vec) = iter_val {
-                            debug_println!(
-                                "[DEBUG] FOR_LOOP: Iterating over vector with {} items",
-                                vec.len()
-                            );
-                            for item in vec {
-                                self.environment.define(var_name.clone(), item);
-                                match self.interpret_block_with_control_flow(body)? {
-                                    ExecutionResult::Break => break,
-                                    ExecutionResult::Continue => continue,
-                                    ExecutionResult::Return(val) => return Ok(ExecutionResult::Return(val)),
-                                    ExecutionResult::Value(_) => {}
-                                }
+                        let iter_val = self.interpret_expression(iterable)?;
+                        let iterator = self.iterator_over(iter_val)?;
+
+                        // Values are pulled one at a time so ranges and iterators stay lazy
+                        let slot = self.resolution.local(span);
+                        while let Some(item) = iterators::next_value(&iterator, self)? {
+                            self.store_variable(var_name.clone(), slot, item);
+                            match self.interpret_block_with_control_flow(body)? {
+                                ExecutionResult::Break => break,
+                                ExecutionResult::Continue => continue,
+                                ExecutionResult::Return(val) => return Ok(ExecutionResult::Return(val)),
+                                ExecutionResult::Raise(val) => return Ok(ExecutionResult::Raise(val)),
+                                ExecutionResult::Value(_) => {}
                             }
-                        } else {
-                            return Err(self.runtime_error(format!(
-                                "Cannot iterate over value of type: {:?}",
-                                iter_val
-                            )));
                         }
                     }
                     ForStatement::CStyle {
@@ -1362,6 +3228,7 @@ impl Interpreter {
                                 ExecutionResult::Break => break,
                                 ExecutionResult::Continue => continue,
                                 ExecutionResult::Return(val) => return Ok(ExecutionResult::Return(val)),
+                                ExecutionResult::Raise(val) => return Ok(ExecutionResult::Raise(val)),
                                 ExecutionResult::Value(_) => {}
                             }
 
@@ -1375,33 +3242,159 @@ impl Interpreter {
                 Ok(ExecutionResult::Value(Value::None))
             }
             Statement::Block(block) => self.interpret_block_with_control_flow(block),
+            Statement::Match(match_stmt) => {
+                let value = self.interpret_expression(&match_stmt.expression)?;
+                self.set_execution_context(match_stmt.span.file_id, match_stmt.span.span);
+                self.interpret_match(&value, &match_stmt.arms)
+            }
+            Statement::Raise(raise_stmt) => {
+                let value = self.interpret_expression(&raise_stmt.value)?;
+                self.set_execution_context(raise_stmt.span.file_id, raise_stmt.span.span);
+                Ok(ExecutionResult::Raise(value))
+            }
+            Statement::Try(try_stmt) => self.interpret_try(try_stmt),
             _ => Ok(ExecutionResult::Value(Value::None)),
         }
     }
 
+    /// Interpret a try statement, running the first except clause whose pattern matches the raised value.
+    /// Runtime errors are caught as their message.
+    fn interpret_try(&mut self, try_stmt: &TryStatement) -> Result<ExecutionResult, RuntimeError> {
+        // A raise in the body itself arrives as a control-flow result; one from a
+        // called function, or a runtime error, arrives as an error
+        let (raised, uncaught) = match self.interpret_block_with_control_flow(&try_stmt.body) {
+            Ok(ExecutionResult::Raise(value)) => (value.clone(), Ok(ExecutionResult::Raise(value))),
+            Err(error) => match error.caught_value() {
+                Some(value) => (value, Err(error)),
+                None => return Err(error),
+            },
+            other => return other,
+        };
+
+        for handler in &try_stmt.handlers {
+            let bindings = match &handler.pattern {
+                None => HashMap::new(),
+                Some(pattern) => match PatternMatcher::new().match_pattern(&raised, pattern, self) {
+                    MatchResult::Matched { bindings, .. } => bindings,
+                    MatchResult::NotMatched | MatchResult::GuardFailed => continue,
+                },
+            };
+
+            self.bind_pattern(&handler.span, bindings);
+            return self.interpret_block_with_control_flow(&handler.body);
+        }
+
+        // No clause matched, so keep unwinding
+        uncaught
+    }
+
     /// Interpret a statement (legacy method for backward compatibility)
     fn interpret_statement(&mut self, stmt: &Statement) -> Result<Value, RuntimeError> {
-        match self.interpret_statement_with_control_flow(stmt)? {
+        let result = self.interpret_statement_with_control_flow(stmt)?;
+        self.completion_value(result)
+    }
+
+    /// The value a body or statement finished with. `break`, `continue` and `raise`
+    /// that nothing inside handled become errors.
+    pub(crate) fn completion_value(&self, result: ExecutionResult) -> Result<Value, RuntimeError> {
+        match result {
             ExecutionResult::Value(val) => Ok(val),
             ExecutionResult::Return(val) => Ok(val),
             ExecutionResult::Break => Err(self.runtime_error("Break statement outside of loop".to_string())),
             ExecutionResult::Continue => Err(self.runtime_error("Continue statement outside of loop".to_string())),
+            ExecutionResult::Raise(val) => Err(self.raised_error(val)),
+        }
+    }
+
+    /// Run a function or method body on the selected backend
+    fn execute_body(&mut self, body: &Block) -> Result<ExecutionResult, RuntimeError> {
+        match self.backend {
+            Backend::Interpreter => self.interpret_block_with_control_flow(body),
+            Backend::Vm => {
+                let code = self.code.block(body, &self.resolution);
+                VirtualMachine::new(self, &code).run()
+            }
+        }
+    }
+
+    /// Evaluate an expression on the selected backend
+    pub(crate) fn evaluate(&mut self, expr: &Expression) -> Result<Value, RuntimeError> {
+        match self.backend {
+            Backend::Interpreter => self.interpret_expression(expr),
+            Backend::Vm => {
+                let code = self.code.expression(expr, &self.resolution);
+                let result = VirtualMachine::new(self, &code).run()?;
+                self.completion_value(result)
+            }
+        }
+    }
+
+    /// Run a top-level statement on the selected backend
+    fn execute_statement(&mut self, stmt: &Statement) -> Result<Value, RuntimeError> {
+        match self.backend {
+            Backend::Interpreter => self.interpret_statement(stmt),
+            Backend::Vm => {
+                let code = self.code.statement(stmt, &self.resolution);
+                let result = VirtualMachine::new(self, &code).run()?;
+                self.completion_value(result)
+            }
+        }
+    }
+
+    /// An integer result, subject to the overflow policy when it doesn't fit in
+    /// an `i64`. `None` if the policy rejects it.
+    pub(crate) fn integer_value(&self, value: BigInt) -> Option<Value> {
+        match Value::integer(value) {
+            Value::BigInt(_) if self.int_overflow == IntOverflow::Error => None,
+            value => Some(value),
         }
     }
 
+    /// Integer arithmetic. `checked` computes the result of two `i64`s; when it
+    /// overflows, or an operand is already big, `exact` computes it instead and the
+    /// overflow policy decides what happens. `None` if an operand isn't an `int`.
+    fn integer_arithmetic(
+        &self,
+        left: &Value,
+        symbol: &str,
+        right: &Value,
+        checked: fn(i64, i64) -> Option<i64>,
+        exact: fn(&BigInt, &BigInt) -> BigInt,
+    ) -> Option<Result<Value, RuntimeError>> {
+        let (a, b) = match (left, right) {
+            (Value::Int(a), Value::Int(b)) => match checked(*a, *b) {
+                Some(result) => return Some(Ok(Value::Int(result))),
+                None => (BigInt::from(*a), BigInt::from(*b)),
+            },
+            _ => (left.to_big_int()?, right.to_big_int()?),
+        };
+        Some(self.integer_value(exact(&a, &b)).ok_or_else(|| {
+            self.runtime_error(format!(
+                "Integer overflow in {} {} {}",
+                left.to_string(),
+                symbol,
+                right.to_string()
+            ))
+        }))
+    }
+
     /// Helper methods for operations
     fn add_values(&self, left: &Value, right: &Value) -> Result<Value, RuntimeError> {
         // Handle union types by unwrapping them
         let left_unwrapped = left.unwrap_union();
         let right_unwrapped = right.unwrap_union();
-        
+        if let Some(result) =
+            self.integer_arithmetic(left_unwrapped, "+", right_unwrapped, i64::checked_add, |a, b| a + b)
+        {
+            return result;
+        }
+
         match (left_unwrapped, right_unwrapped) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a + b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a + b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Float(*a as f64 + b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a + *b as f64)),
             (Value::String(a), Value::String(b)) => Ok(Value::String(format!("{}{}", a, b))),
-            _ => Err(self.runtime_error("Cannot add these types".to_string())),
+            (a, b) => match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => Ok(Value::Float(a + b)),
+                _ => Err(self.runtime_error("Cannot add these types".to_string())),
+            },
         }
     }
 
@@ -1409,12 +3402,14 @@ impl Interpreter {
         // Handle union types by unwrapping them
         let left_unwrapped = left.unwrap_union();
         let right_unwrapped = right.unwrap_union();
-        
-        match (left_unwrapped, right_unwrapped) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a - b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a - b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Float(*a as f64 - b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a - *b as f64)),
+        if let Some(result) =
+            self.integer_arithmetic(left_unwrapped, "-", right_unwrapped, i64::checked_sub, |a, b| a - b)
+        {
+            return result;
+        }
+
+        match (left_unwrapped.as_f64(), right_unwrapped.as_f64()) {
+            (Some(a), Some(b)) => Ok(Value::Float(a - b)),
             _ => Err(self.runtime_error("Cannot subtract these types".to_string())),
         }
     }
@@ -1423,12 +3418,14 @@ impl Interpreter {
         // Handle union types by unwrapping them
         let left_unwrapped = left.unwrap_union();
         let right_unwrapped = right.unwrap_union();
-        
-        match (left_unwrapped, right_unwrapped) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a * b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a * b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Float(*a as f64 * b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a * *b as f64)),
+        if let Some(result) =
+            self.integer_arithmetic(left_unwrapped, "*", right_unwrapped, i64::checked_mul, |a, b| a * b)
+        {
+            return result;
+        }
+
+        match (left_unwrapped.as_f64(), right_unwrapped.as_f64()) {
+            (Some(a), Some(b)) => Ok(Value::Float(a * b)),
             _ => Err(self.runtime_error("Cannot multiply these types".to_string())),
         }
     }
@@ -1437,36 +3434,20 @@ impl Interpreter {
         // Handle union types by unwrapping them
         let left_unwrapped = left.unwrap_union();
         let right_unwrapped = right.unwrap_union();
-        
-        match (left_unwrapped, right_unwrapped) {
-            (Value::Int(a), Value::Int(b)) => {
-                if *b == 0 {
-                    Err(self.runtime_error("Division by zero".to_string()))
-                } else {
-                    Ok(Value::Int(a / b))
-                }
-            }
-            (Value::Float(a), Value::Float(b)) => {
-                if *b == 0.0 {
-                    Err(self.runtime_error("Division by zero".to_string()))
-                } else {
-                    Ok(Value::Float(a / b))
-                }
-            }
-            (Value::Int(a), Value::Float(b)) => {
-                if *b == 0.0 {
-                    Err(self.runtime_error("Division by zero".to_string()))
-                } else {
-                    Ok(Value::Float(*a as f64 / b))
-                }
-            }
-            (Value::Float(a), Value::Int(b)) => {
-                if *b == 0 {
-                    Err(self.runtime_error("Division by zero".to_string()))
-                } else {
-                    Ok(Value::Float(a / *b as f64))
-                }
-            }
+        let divides_by_zero = matches!(right_unwrapped, Value::Int(0))
+            || matches!(right_unwrapped, Value::Float(b) if *b == 0.0);
+        if divides_by_zero && left_unwrapped.as_f64().is_some() {
+            return Err(self.runtime_error("Division by zero".to_string()));
+        }
+        // Only i64::MIN / -1 overflows
+        if let Some(result) = self.integer_arithmetic(left_unwrapped, "/", right_unwrapped, i64::checked_div, |a, b| {
+            a.div_rem(b).map(|(quotient, _)| quotient).unwrap_or_default()
+        }) {
+            return result;
+        }
+
+        match (left_unwrapped.as_f64(), right_unwrapped.as_f64()) {
+            (Some(a), Some(b)) => Ok(Value::Float(a / b)),
             _ => Err(self.runtime_error("Cannot divide these types".to_string())),
         }
     }
@@ -1475,16 +3456,92 @@ impl Interpreter {
         // Handle union types by unwrapping them
         let left_unwrapped = left.unwrap_union();
         let right_unwrapped = right.unwrap_union();
-        
+        if matches!(right_unwrapped, Value::Int(0)) && left_unwrapped.to_big_int().is_some() {
+            return Err(self.runtime_error("Modulo by zero".to_string()));
+        }
+
+        self.integer_arithmetic(left_unwrapped, "%", right_unwrapped, i64::checked_rem, |a, b| {
+            a.div_rem(b).map(|(_, remainder)| remainder).unwrap_or_default()
+        })
+        .unwrap_or_else(|| Err(self.runtime_error("Cannot modulo these types".to_string())))
+    }
+
+    fn power_values(&self, left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+        // Handle union types by unwrapping them
+        let left_unwrapped = left.unwrap_union();
+        let right_unwrapped = right.unwrap_union();
+
         match (left_unwrapped, right_unwrapped) {
-            (Value::Int(a), Value::Int(b)) => {
-                if *b == 0 {
-                    Err(self.runtime_error("Modulo by zero".to_string()))
-                } else {
-                    Ok(Value::Int(a % b))
+            (Value::Int(_) | Value::BigInt(_), Value::Int(b)) if *b >= 0 => {
+                let overflow = || {
+                    self.runtime_error(format!(
+                        "Integer overflow in {} ** {}",
+                        left_unwrapped.to_string(),
+                        b
+                    ))
+                };
+                if let Value::Int(a) = left_unwrapped {
+                    if let Some(result) = u32::try_from(*b).ok().and_then(|b| a.checked_pow(b)) {
+                        return Ok(Value::Int(result));
+                    }
                 }
+                let base = left_unwrapped.to_big_int().unwrap_or_default();
+                let exponent = u32::try_from(*b).map_err(|_| overflow())?;
+                self.integer_value(base.pow(exponent)).ok_or_else(overflow)
             }
-            _ => Err(self.runtime_error("Cannot modulo these types".to_string())),
+            // Negative integer exponents produce a float, as with float operands
+            (a, b) => match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => Ok(Value::Float(a.powf(b))),
+                _ => Err(self.runtime_error("Cannot raise these types to a power".to_string())),
+            },
+        }
+    }
+
+    /// `<<` is checked like the arithmetic operators, so bits shifted out of an
+    /// `int` follow the overflow policy; `>>` rounds toward negative infinity
+    fn shift_values(&self, left: &Value, op: &BinaryOperator, right: &Value) -> Result<Value, RuntimeError> {
+        let (left, right) = (left.unwrap_union(), right.unwrap_union());
+        if left.to_big_int().is_none() || right.to_big_int().is_none() {
+            return Err(self.runtime_error("Cannot shift these types".to_string()));
+        }
+        let in_range = matches!(right, Value::Int(b) if u32::try_from(*b).is_ok());
+        if !in_range {
+            return Err(self.runtime_error(format!("Shift amount {} is out of range", right.to_string())));
+        }
+        // The amount was checked to fit a `u32` above
+        fn amount(b: &BigInt) -> u32 {
+            b.to_i64().unwrap_or_default() as u32
+        }
+        let result = if *op == BinaryOperator::ShiftLeft {
+            self.integer_arithmetic(
+                left,
+                "<<",
+                right,
+                |a, b| a.checked_shl(b as u32).filter(|shifted| shifted >> b == a),
+                |a, b| a << amount(b),
+            )
+        } else {
+            self.integer_arithmetic(left, ">>", right, |a, b| Some(a >> b.min(63)), |a, b| a >> amount(b))
+        };
+        result.unwrap_or_else(|| Err(self.runtime_error("Cannot shift these types".to_string())))
+    }
+
+    fn bitwise_values(&self, left: &Value, op: &BinaryOperator, right: &Value) -> Result<Value, RuntimeError> {
+        let (left, right) = (left.unwrap_union(), right.unwrap_union());
+        let integer_result = match op {
+            BinaryOperator::BitAnd => self.integer_arithmetic(left, "&", right, |a, b| Some(a & b), |a, b| a & b),
+            BinaryOperator::BitOr => self.integer_arithmetic(left, "|", right, |a, b| Some(a | b), |a, b| a | b),
+            BinaryOperator::BitXor => self.integer_arithmetic(left, "^", right, |a, b| Some(a ^ b), |a, b| a ^ b),
+            _ => None,
+        };
+        if let Some(result) = integer_result {
+            return result;
+        }
+        match (left, right, op) {
+            (Value::Bool(a), Value::Bool(b), BinaryOperator::BitAnd) => Ok(Value::Bool(a & b)),
+            (Value::Bool(a), Value::Bool(b), BinaryOperator::BitOr) => Ok(Value::Bool(a | b)),
+            (Value::Bool(a), Value::Bool(b), BinaryOperator::BitXor) => Ok(Value::Bool(a ^ b)),
+            _ => Err(self.runtime_error(format!("Cannot apply {} to these types", op))),
         }
     }
 
@@ -1496,36 +3553,131 @@ impl Interpreter {
         let left_unwrapped = left.unwrap_union();
         let right_unwrapped = right.unwrap_union();
         
-        match (left_unwrapped, right_unwrapped) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Bool(cmp(*a as f64, *b as f64))),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Bool(cmp(*a, *b))),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Bool(cmp(*a as f64, *b))),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Bool(cmp(*a, *b as f64))),
+        // Integers are compared exactly, by comparing their ordering with zero
+        if let (Some(a), Some(b)) = (left_unwrapped.to_big_int(), right_unwrapped.to_big_int()) {
+            return Ok(Value::Bool(cmp(a.cmp(&b) as i8 as f64, 0.0)));
+        }
+        match (left_unwrapped.as_f64(), right_unwrapped.as_f64()) {
+            (Some(a), Some(b)) => Ok(Value::Bool(cmp(a, b))),
             _ => Err(self.runtime_error("Cannot compare these types".to_string())),
         }
     }
 
     fn negate_value(&self, operand: &Value) -> Result<Value, RuntimeError> {
         match operand {
-            Value::Int(a) => Ok(Value::Int(-a)),
+            Value::Int(a) => match a.checked_neg() {
+                Some(negated) => Ok(Value::Int(negated)),
+                None => self.integer_value(-&BigInt::from(*a)).ok_or_else(|| {
+                    self.runtime_error(format!("Integer overflow in -({})", a))
+                }),
+            },
+            // Negating can bring i64::MAX + 1 back into range
+            Value::BigInt(a) => Ok(Value::integer(-a)),
             Value::Float(a) => Ok(Value::Float(-a)),
             _ => Err(self.runtime_error("Cannot negate this type".to_string())),
         }
     }
 
-    fn is_truthy(&self, value: &Value) -> bool {
-        match value {
+    pub(crate) fn is_truthy(&self, value: &Value) -> bool {
+        match value.unwrap_union() {
             Value::Bool(b) => *b,
             Value::Int(i) => *i != 0,
             Value::Float(f) => *f != 0.0,
             Value::String(s) => !s.is_empty(),
-            Value::Vec(v) => !v.is_empty(),
+            Value::Vec(v) => !v.read().is_empty(),
             Value::None => false,
             _ => true,
         }
     }
 }
 
+impl IteratorContext for Interpreter {
+    fn call_function(&mut self, function: &Value, args: &[Value]) -> Result<Value, RuntimeError> {
+        self.interpret_call(function, args)
+    }
+
+    fn call_next(&mut self, object: &Value) -> Result<(Value, Value), RuntimeError> {
+        match self.find_method(object, "next") {
+            Some(method) => self.call_method_with_receiver(object, &method, &[], &[]),
+            None => Err(self.runtime_error(format!(
+                "No method 'next' found on {}",
+                self.get_value_type_name(object)
+            ))),
+        }
+    }
+
+    fn iteration_error(&self, message: String) -> RuntimeError {
+        self.runtime_error(message)
+    }
+}
+
+impl PatternContext for Interpreter {
+    fn value_has_type(&self, value: &Value, pattern_type: &Type) -> bool {
+        match (value.unwrap_union(), pattern_type) {
+            // Patterns test the actual type, so numeric coercions do not apply
+            (Value::Int(_), Type::Primitive(PrimitiveType::Float))
+            | (Value::Float(_), Type::Primitive(PrimitiveType::Int)) => false,
+            _ => self.is_type_compatible(value, pattern_type),
+        }
+    }
+
+    fn value_implements(&self, value: &Value, interface_name: &str) -> bool {
+        self.impl_type_name(value)
+            .map(|type_name| self.type_implements(&type_name, interface_name))
+            .unwrap_or(false)
+    }
+
+    fn is_enum_variant(&self, name: &str) -> bool {
+        self.types.enums
+            .values()
+            .any(|enum_decl| enum_decl.variants.iter().any(|v| v.name == name))
+    }
+}
+
+impl TypeDeclarations for Interpreter {
+    fn struct_fields(&self, name: &str) -> Option<Vec<(String, Type)>> {
+        self.types.structs.get(name).map(|struct_decl| {
+            struct_decl
+                .fields
+                .iter()
+                .map(|field| (field.name.clone(), field.field_type.clone()))
+                .collect()
+        })
+    }
+
+    fn type_alias(&self, name: &str) -> Option<Type> {
+        self.types.type_aliases.get(name).cloned()
+    }
+}
+
+/// Write a type the way it is written in TJLang source
+pub fn format_type(ty: &Type) -> String {
+    let list = |types: &[Type]| types.iter().map(format_type).collect::<Vec<_>>().join(", ");
+    match ty {
+        Type::Primitive(PrimitiveType::Int) => "int".to_string(),
+        Type::Primitive(PrimitiveType::Float) => "float".to_string(),
+        Type::Primitive(PrimitiveType::Bool) => "bool".to_string(),
+        Type::Primitive(PrimitiveType::Str) => "str".to_string(),
+        Type::Primitive(PrimitiveType::Any) => "any".to_string(),
+        Type::Identifier(name) => name.clone(),
+        Type::Generic { name, type_args, .. } => format!("{}<{}>", name, list(type_args)),
+        Type::Union { types, .. } => types.iter().map(format_type).collect::<Vec<_>>().join(" | "),
+        Type::Option { inner, .. } => format!("Option<{}>", format_type(inner)),
+        Type::Result { ok_type, error_type, .. } => {
+            format!("Result<{}, {}>", format_type(ok_type), format_type(error_type))
+        }
+        Type::Function { params, return_type, .. } => {
+            format!("fn({}) -> {}", list(params), format_type(return_type))
+        }
+        Type::Vec { element_type, .. } => format!("[{}]", format_type(element_type)),
+        Type::Set { element_type, .. } => format!("{{{}}}", format_type(element_type)),
+        Type::Map { key_type, value_type, .. } => {
+            format!("Map<{}, {}>", format_type(key_type), format_type(value_type))
+        }
+        Type::Tuple { types, .. } => format!("({})", list(types)),
+    }
+}
+
 /// Closure for function values
 #[derive(Debug, Clone)]
 pub struct Closure {
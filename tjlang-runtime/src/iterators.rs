@@ -0,0 +1,245 @@
+//! TJLang Iterators
+//!
+//! Lazy iteration over ranges, collections and user types implementing the
+//! `Iterator` interface. Adapters such as `map` and `filter` pull one value at a
+//! time from their source, calling back into the interpreter when needed.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::interpreter::RuntimeError;
+use crate::values::Value;
+
+/// Shared iterator state. Copies of an iterator value advance together.
+pub type IteratorRef = Arc<Mutex<IteratorState>>;
+
+/// Methods on iterators; ranges support them too
+pub const ITERATOR_METHODS: &[&str] = &[
+    "iter",
+    "next",
+    "map",
+    "filter",
+    "take",
+    "zip",
+    "enumerate",
+    "collect",
+];
+
+/// Additional methods on range values
+pub const RANGE_METHODS: &[&str] = &["length", "contains"];
+
+/// Where the next value of an iterator comes from
+#[derive(Debug)]
+pub enum IteratorState {
+    /// Integers counting up from `next` to `end`
+    Range { next: i64, end: i64, inclusive: bool },
+    /// The items of a collection
+    Items(std::vec::IntoIter<Value>),
+    /// A value whose type implements `Iterator`; its `next()` is called for each item
+    Object(Value),
+    /// Values of `source` passed through `function`
+    Map { source: IteratorRef, function: Value },
+    /// Values of `source` for which `predicate` is truthy
+    Filter { source: IteratorRef, predicate: Value },
+    /// At most `remaining` more values of `source`
+    Take { source: IteratorRef, remaining: usize },
+    /// Pairs of values from `left` and `right`, ending with the shorter one
+    Zip { left: IteratorRef, right: IteratorRef },
+    /// `(index, value)` pairs of `source`
+    Enumerate { source: IteratorRef, index: i64 },
+    /// Values produced by native code, such as the documents of a JSON lines file
+    Native(NativeSource),
+}
+
+/// A Rust iterator producing values, or a message when producing one fails
+pub struct NativeSource(Box<dyn Iterator<Item = Result<Value, String>> + Send>);
+
+impl NativeSource {
+    pub fn new(source: impl Iterator<Item = Result<Value, String>> + Send + 'static) -> Self {
+        NativeSource(Box::new(source))
+    }
+}
+
+impl std::fmt::Debug for NativeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("NativeSource")
+    }
+}
+
+/// Interpreter services needed to advance an iterator
+pub trait IteratorContext {
+    /// Call a TJLang function or closure
+    fn call_function(&mut self, function: &Value, args: &[Value]) -> Result<Value, RuntimeError>;
+
+    /// Call `next()` on a user iterator, returning the result and the receiver as
+    /// the method left it
+    fn call_next(&mut self, object: &Value) -> Result<(Value, Value), RuntimeError>;
+
+    /// Create an error located at the current execution context
+    fn iteration_error(&self, message: String) -> RuntimeError;
+}
+
+/// Wrap iterator state in a new shared handle
+pub fn new_iterator(state: IteratorState) -> IteratorRef {
+    Arc::new(Mutex::new(state))
+}
+
+/// Iterator over a range or collection, or `None` if the value is not one.
+/// Iterator values return their own shared state.
+pub fn from_collection(value: Value) -> Option<IteratorRef> {
+    let state = match value {
+        Value::Union { value, .. } => return from_collection(*value),
+        Value::Iterator(iterator) => return Some(iterator),
+        Value::Range { start, end, inclusive } => IteratorState::Range { next: start, end, inclusive },
+        // Collections are iterated over a snapshot of their current contents
+        Value::Vec(items) => IteratorState::Items(items.read().clone().into_iter()),
+        Value::Tuple(items) => IteratorState::Items(items.into_iter()),
+        Value::Set(items) => IteratorState::Items(items.read().iter().cloned().collect::<Vec<_>>().into_iter()),
+        Value::Map(entries) => IteratorState::Items(
+            entries
+                .read()
+                .iter()
+                .map(|(key, value)| Value::Tuple(vec![key.clone(), value.clone()]))
+                .collect::<Vec<_>>()
+                .into_iter(),
+        ),
+        Value::String(s) => IteratorState::Items(
+            s.chars()
+                .map(|c| Value::String(c.to_string()))
+                .collect::<Vec<_>>()
+                .into_iter(),
+        ),
+        _ => return None,
+    };
+    Some(new_iterator(state))
+}
+
+/// What to do after the state lock is released
+enum Step {
+    Object(Value),
+    Map(IteratorRef, Value),
+    Filter(IteratorRef, Value),
+    Pull(IteratorRef),
+    Zip(IteratorRef, IteratorRef),
+    Enumerate(IteratorRef, i64),
+}
+
+fn lock<'a>(
+    iterator: &'a IteratorRef,
+    context: &dyn IteratorContext,
+) -> Result<MutexGuard<'a, IteratorState>, RuntimeError> {
+    iterator
+        .lock()
+        .map_err(|_| context.iteration_error("Iterator state is poisoned".to_string()))
+}
+
+/// Advance an iterator, returning `None` once it is exhausted
+pub fn next_value(
+    iterator: &IteratorRef,
+    context: &mut dyn IteratorContext,
+) -> Result<Option<Value>, RuntimeError> {
+    // The lock is not held while pulling from a source or running TJLang code,
+    // which may itself use this iterator
+    let step = {
+        let mut state = lock(iterator, context)?;
+        match &mut *state {
+            IteratorState::Range { next, end, inclusive } => {
+                let in_range = if *inclusive { *next <= *end } else { *next < *end };
+                if !in_range {
+                    return Ok(None);
+                }
+                let current = *next;
+                match next.checked_add(1) {
+                    Some(following) => *next = following,
+                    // Yielded i64::MAX; nothing follows it
+                    None => *inclusive = false,
+                }
+                return Ok(Some(Value::Int(current)));
+            }
+            IteratorState::Items(items) => return Ok(items.next()),
+            IteratorState::Native(source) => {
+                return source.0.next().transpose().map_err(|e| context.iteration_error(e));
+            }
+            IteratorState::Object(object) => Step::Object(object.clone()),
+            IteratorState::Map { source, function } => Step::Map(source.clone(), function.clone()),
+            IteratorState::Filter { source, predicate } => {
+                Step::Filter(source.clone(), predicate.clone())
+            }
+            IteratorState::Take { source, remaining } => {
+                if *remaining == 0 {
+                    return Ok(None);
+                }
+                *remaining -= 1;
+                Step::Pull(source.clone())
+            }
+            IteratorState::Zip { left, right } => Step::Zip(left.clone(), right.clone()),
+            IteratorState::Enumerate { source, index } => {
+                let current = *index;
+                *index += 1;
+                Step::Enumerate(source.clone(), current)
+            }
+        }
+    };
+
+    match step {
+        Step::Object(object) => {
+            let (result, updated) = context.call_next(&object)?;
+            if let IteratorState::Object(current) = &mut *lock(iterator, context)? {
+                *current = updated;
+            }
+            match result {
+                Value::None => Ok(None),
+                Value::Enum { name, variant, mut fields } if name == "Option" && variant == "Some" => {
+                    Ok(fields.pop())
+                }
+                other => Err(context.iteration_error(format!(
+                    "Iterator next() must return an Option, got {}",
+                    other.to_string()
+                ))),
+            }
+        }
+        Step::Map(source, function) => match next_value(&source, context)? {
+            Some(value) => context.call_function(&function, &[value]).map(Some),
+            None => Ok(None),
+        },
+        Step::Filter(source, predicate) => {
+            while let Some(value) = next_value(&source, context)? {
+                if context.call_function(&predicate, std::slice::from_ref(&value))?.is_truthy() {
+                    return Ok(Some(value));
+                }
+            }
+            Ok(None)
+        }
+        Step::Pull(source) => next_value(&source, context),
+        Step::Zip(left, right) => {
+            let Some(first) = next_value(&left, context)? else {
+                return Ok(None);
+            };
+            Ok(next_value(&right, context)?.map(|second| Value::Tuple(vec![first, second])))
+        }
+        Step::Enumerate(source, index) => Ok(next_value(&source, context)?
+            .map(|value| Value::Tuple(vec![Value::Int(index), value]))),
+    }
+}
+
+/// Drain an iterator into a vector
+pub fn collect(
+    iterator: &IteratorRef,
+    context: &mut dyn IteratorContext,
+) -> Result<Vec<Value>, RuntimeError> {
+    let mut items = Vec::new();
+    while let Some(value) = next_value(iterator, context)? {
+        items.push(value);
+    }
+    Ok(items)
+}
+
+/// Number of integers in a range
+pub fn range_length(start: i64, end: i64, inclusive: bool) -> i64 {
+    let length = end as i128 - start as i128 + if inclusive { 1 } else { 0 };
+    length.clamp(0, i64::MAX as i128) as i64
+}
+
+/// Whether a range includes an integer
+pub fn range_contains(start: i64, end: i64, inclusive: bool, value: i64) -> bool {
+    value >= start && if inclusive { value <= end } else { value < end }
+}
@@ -2,15 +2,26 @@
 //!
 //! A real interpreter that works with the TJLang AST.
 
+pub mod bigint;
+pub mod bytecode;
+pub mod compiled;
+pub mod concurrency;
+pub mod gc;
 pub mod interpreter;
+pub mod iterators;
+pub mod module_system;
+pub mod pattern_matching;
 pub mod primitive_methods;
+pub mod resolver;
+pub mod serialization;
 pub mod stdlib;
 pub mod stdlib_integration;
 pub mod values;
+pub mod vm;
 
 #[cfg(test)]
 mod tests;
 
 // Re-export main types
-pub use interpreter::Interpreter;
+pub use interpreter::{Backend, IntOverflow, Interpreter};
 pub use values::Value;
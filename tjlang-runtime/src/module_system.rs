@@ -4,23 +4,27 @@
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use crate::values::Value;
-use tjlang_ast::{Program, Declaration, FunctionDecl, Type, SourceSpan};
+use codespan::{FileId, Files};
+use tjlang_ast::{Program, ProgramUnit, Declaration, ExportDecl, ImportDecl, FunctionDecl, Type};
 
 /// Module system for managing imports and exports
 pub struct ModuleSystem {
-    /// Loaded modules
+    /// Modules that have finished executing, with their exported values
     modules: HashMap<String, Module>,
     
     /// Module search paths
     search_paths: Vec<PathBuf>,
     
-    /// Module cache
+    /// Parsed modules, keyed by dotted module name
     cache: HashMap<String, Arc<Module>>,
     
     /// Import graph for dependency resolution
     import_graph: HashMap<String, Vec<String>>,
+    
+    /// Source of every loaded file, for diagnostics
+    files: Files<String>,
 }
 
 /// A loaded module
@@ -32,9 +36,15 @@ pub struct Module {
     /// Module path
     pub path: PathBuf,
     
+    /// Parsed module source
+    pub program: Program,
+    
     /// Module declarations
     pub declarations: Vec<Declaration>,
     
+    /// Names listed by the module's `export` declarations
+    pub export_names: Vec<String>,
+    
     /// Exported items
     pub exports: HashMap<String, ExportItem>,
     
@@ -88,9 +98,25 @@ impl ModuleSystem {
             search_paths: Vec::new(),
             cache: HashMap::new(),
             import_graph: HashMap::new(),
+            files: Files::new(),
         }
     }
     
+    /// Source files loaded so far, including any added with `add_file`
+    pub fn files(&self) -> &Files<String> {
+        &self.files
+    }
+    
+    /// Replace the file database, e.g. with one that already holds the entry file
+    pub fn set_files(&mut self, files: Files<String>) {
+        self.files = files;
+    }
+    
+    /// Add a source file so spans inside it can be reported
+    pub fn add_file(&mut self, path: &Path, source: String) -> FileId {
+        self.files.add(path.to_string_lossy().to_string(), source)
+    }
+    
     /// Add a search path for modules
     pub fn add_search_path(&mut self, path: PathBuf) {
         self.search_paths.push(path);
@@ -107,7 +133,7 @@ impl ModuleSystem {
         let module_path = self.find_module(name)?;
         
         // Load module content
-        let module = self.load_module_from_path(&module_path)?;
+        let module = self.load_module_from_path(name, &module_path)?;
         
         // Cache the module
         let module_arc = Arc::new(module);
@@ -116,16 +142,17 @@ impl ModuleSystem {
         Ok(module_arc)
     }
     
-    /// Find a module file
+    /// Find a module file; `geometry.shapes` is `geometry/shapes.tj` or `geometry/shapes/mod.tj`
     fn find_module(&self, name: &str) -> Result<PathBuf, String> {
+        let relative: PathBuf = name.split('.').collect();
         for search_path in &self.search_paths {
-            let module_path = search_path.join(format!("{}.tj", name));
+            let module_path = search_path.join(relative.with_extension("tj"));
             if module_path.exists() {
                 return Ok(module_path);
             }
             
             // Check for module directory
-            let module_dir = search_path.join(name);
+            let module_dir = search_path.join(&relative);
             let module_file = module_dir.join("mod.tj");
             if module_file.exists() {
                 return Ok(module_file);
@@ -135,22 +162,54 @@ impl ModuleSystem {
         Err(format!("Module '{}' not found", name))
     }
     
-    /// Load module from file path
-    fn load_module_from_path(&self, path: &Path) -> Result<Module, String> {
-        // TODO: Implement actual file loading and parsing
-        // For now, return a dummy module
+    /// Load and parse a module file
+    fn load_module_from_path(&mut self, name: &str, path: &Path) -> Result<Module, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read module '{}' ({}): {}", name, path.display(), e))?;
+        let file_id = self.add_file(path, source);
+        let program = match tjlang_parser::parse(self.files.source(file_id), file_id) {
+            Ok((program, _)) => program,
+            Err(diagnostics) => {
+                let reason = diagnostics
+                    .iter()
+                    .next()
+                    .map(|d| d.message.clone())
+                    .unwrap_or_else(|| "unknown parse error".to_string());
+                return Err(format!("Failed to parse module '{}' ({}): {}", name, path.display(), reason));
+            }
+        };
         
-        let name = path.file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
+        let mut declarations = Vec::new();
+        let mut export_names = Vec::new();
+        let mut dependencies = Vec::new();
+        for unit in &program.units {
+            match unit {
+                ProgramUnit::Declaration(decl) => declarations.push(decl.clone()),
+                ProgramUnit::Import(ImportDecl::Simple { module, .. })
+                | ProgramUnit::Import(ImportDecl::Selective { module, .. }) => {
+                    dependencies.push(module.parts.join("."));
+                }
+                ProgramUnit::Export(ExportDecl::Declaration(decl)) => {
+                    export_names.extend(Self::declared_name(decl));
+                    declarations.push(decl.clone());
+                }
+                ProgramUnit::Export(ExportDecl::Identifier(export)) => export_names.push(export.clone()),
+                ProgramUnit::Export(ExportDecl::IdentifierList(exports)) => {
+                    export_names.extend(exports.iter().cloned());
+                }
+                _ => {}
+            }
+        }
+        self.import_graph.insert(name.to_string(), dependencies.clone());
         
         Ok(Module {
-            name,
+            name: name.to_string(),
             path: path.to_path_buf(),
-            declarations: Vec::new(),
+            program,
+            declarations,
+            export_names,
             exports: HashMap::new(),
-            dependencies: Vec::new(),
+            dependencies,
             metadata: ModuleMetadata {
                 version: "1.0.0".to_string(),
                 author: "Unknown".to_string(),
@@ -161,26 +220,53 @@ impl ModuleSystem {
         })
     }
     
-    /// Import items from a module
-    pub fn import(&mut self, module_name: &str, items: &[String]) -> Result<HashMap<String, ExportItem>, String> {
+    /// The name a declaration introduces
+    fn declared_name(decl: &Declaration) -> Option<String> {
+        match decl {
+            Declaration::Function(func) => Some(func.name.clone()),
+            Declaration::Struct(struct_decl) => Some(struct_decl.name.clone()),
+            Declaration::Enum(enum_decl) => Some(enum_decl.name.clone()),
+            Declaration::Interface(interface_decl) => Some(interface_decl.name.clone()),
+            Declaration::Type(type_decl) => Some(type_decl.name.clone()),
+            Declaration::Variable(var) => Some(var.name.clone()),
+            _ => None,
+        }
+    }
+    
+    /// Record that a module has executed; its exports are added with `export`
+    pub fn mark_executed(&mut self, module_name: &str) -> Result<(), String> {
         let module = self.load_module(module_name)?;
+        self.modules.insert(module_name.to_string(), (*module).clone());
+        Ok(())
+    }
+    
+    /// Check whether a module has already executed
+    pub fn is_executed(&self, module_name: &str) -> bool {
+        self.modules.contains_key(module_name)
+    }
+    
+    /// Import items from an executed module
+    pub fn import(&mut self, module_name: &str, items: &[String]) -> Result<HashMap<String, ExportItem>, String> {
+        let exports = self.import_all(module_name)?;
         let mut imported_items = HashMap::new();
         
         for item_name in items {
-            if let Some(export_item) = module.exports.get(item_name) {
+            if let Some(export_item) = exports.get(item_name) {
                 imported_items.insert(item_name.clone(), export_item.clone());
             } else {
-                return Err(format!("Item '{}' not found in module '{}'", item_name, module_name));
+                return Err(format!("Module '{}' does not export '{}'", module_name, item_name));
             }
         }
         
         Ok(imported_items)
     }
     
-    /// Import all items from a module
+    /// Import all items from an executed module
     pub fn import_all(&mut self, module_name: &str) -> Result<HashMap<String, ExportItem>, String> {
-        let module = self.load_module(module_name)?;
-        Ok(module.exports.clone())
+        self.modules
+            .get(module_name)
+            .map(|module| module.exports.clone())
+            .ok_or_else(|| format!("Module '{}' has not been executed", module_name))
     }
     
     /// Export an item from the current module
@@ -199,39 +285,41 @@ impl ModuleSystem {
         }
     }
     
-    /// Resolve module dependencies
+    /// Resolve module dependencies, loading each module, in the order they must execute
     pub fn resolve_dependencies(&mut self, module_name: &str) -> Result<Vec<String>, String> {
         let mut resolved = Vec::new();
-        let mut visited = std::collections::HashSet::new();
+        let mut import_path = Vec::new();
         
-        self.resolve_dependencies_recursive(module_name, &mut resolved, &mut visited)?;
+        self.resolve_dependencies_recursive(module_name, &mut resolved, &mut import_path)?;
         
         Ok(resolved)
     }
     
-    /// Recursive dependency resolution
+    /// Recursive dependency resolution; `import_path` is the chain of imports being followed
     fn resolve_dependencies_recursive(
         &mut self,
         module_name: &str,
         resolved: &mut Vec<String>,
-        visited: &mut std::collections::HashSet<String>,
+        import_path: &mut Vec<String>,
     ) -> Result<(), String> {
-        if visited.contains(module_name) {
-            return Err(format!("Circular dependency detected: {}", module_name));
+        if let Some(start) = import_path.iter().position(|name| name == module_name) {
+            let mut cycle = import_path[start..].to_vec();
+            cycle.push(module_name.to_string());
+            return Err(format!("Import cycle detected: {}", cycle.join(" -> ")));
         }
         
-        visited.insert(module_name.to_string());
+        import_path.push(module_name.to_string());
         
         let module = self.load_module(module_name)?;
         
         for dependency in &module.dependencies {
             if !resolved.contains(dependency) {
-                self.resolve_dependencies_recursive(dependency, resolved, visited)?;
+                self.resolve_dependencies_recursive(dependency, resolved, import_path)?;
             }
         }
         
         resolved.push(module_name.to_string());
-        visited.remove(module_name);
+        import_path.pop();
         
         Ok(())
     }
@@ -1,10 +1,11 @@
 //! TJLang Pattern Matching Engine
-//! 
-//! Advanced pattern matching with destructuring, guards, and exhaustiveness checking.
+//!
+//! Pattern matching with destructuring and variable bindings. Guards are
+//! evaluated by the interpreter once a pattern has matched.
 
 use std::collections::HashMap;
 use crate::values::Value;
-use tjlang_ast::{Pattern, Expression, Type, SourceSpan};
+use tjlang_ast::{Literal, Pattern, Type};
 
 /// Pattern matching result
 #[derive(Debug, Clone)]
@@ -20,22 +21,22 @@ pub enum MatchResult {
     GuardFailed,
 }
 
+/// Type information the matcher needs from the interpreter
+pub trait PatternContext {
+    /// Check whether a value satisfies the type of an `x: T` pattern
+    fn value_has_type(&self, value: &Value, pattern_type: &Type) -> bool;
+
+    /// Check whether a value's type implements the named interface
+    fn value_implements(&self, value: &Value, interface_name: &str) -> bool;
+
+    /// Check whether a bare name refers to an enum variant rather than a new binding
+    fn is_enum_variant(&self, name: &str) -> bool;
+}
+
 /// Pattern matching engine
 pub struct PatternMatcher {
     /// Current bindings from pattern matching
     bindings: HashMap<String, Value>,
-    
-    /// Pattern compilation cache
-    pattern_cache: HashMap<String, CompiledPattern>,
-}
-
-/// Compiled pattern for efficient matching
-#[derive(Debug, Clone)]
-struct CompiledPattern {
-    pub pattern: Pattern,
-    pub bindings: Vec<String>,
-    pub guards: Vec<Expression>,
-    pub exhaustiveness_checked: bool,
 }
 
 impl PatternMatcher {
@@ -43,15 +44,19 @@ impl PatternMatcher {
     pub fn new() -> Self {
         Self {
             bindings: HashMap::new(),
-            pattern_cache: HashMap::new(),
         }
     }
-    
+
     /// Match a value against a pattern
-    pub fn match_pattern(&mut self, value: &Value, pattern: &Pattern) -> MatchResult {
+    pub fn match_pattern(
+        &mut self,
+        value: &Value,
+        pattern: &Pattern,
+        context: &dyn PatternContext,
+    ) -> MatchResult {
         self.bindings.clear();
-        
-        match self.match_pattern_internal(value, pattern) {
+
+        match self.match_pattern_internal(value, pattern, context) {
             MatchResult::Matched { bindings, guard_passed } => {
                 self.bindings = bindings;
                 MatchResult::Matched { bindings: self.bindings.clone(), guard_passed }
@@ -59,172 +64,142 @@ impl PatternMatcher {
             result => result,
         }
     }
-    
+
     /// Internal pattern matching logic
-    fn match_pattern_internal(&mut self, value: &Value, pattern: &Pattern) -> MatchResult {
+    fn match_pattern_internal(
+        &mut self,
+        value: &Value,
+        pattern: &Pattern,
+        context: &dyn PatternContext,
+    ) -> MatchResult {
+        // Values of union-typed variables match by the value they hold
+        let value = value.unwrap_union();
         match (value, pattern) {
             // Literal patterns
-            (Value::Int(a), Pattern::Literal(tjlang_ast::Literal::Int(b))) => {
-                if a == b {
-                    MatchResult::Matched { bindings: HashMap::new(), guard_passed: true }
-                } else {
-                    MatchResult::NotMatched
-                }
-            },
-            (Value::Float(a), Pattern::Literal(tjlang_ast::Literal::Float(b))) => {
-                if (a - b).abs() < f64::EPSILON {
-                    MatchResult::Matched { bindings: HashMap::new(), guard_passed: true }
-                } else {
-                    MatchResult::NotMatched
-                }
-            },
-            (Value::Bool(a), Pattern::Literal(tjlang_ast::Literal::Bool(b))) => {
-                if a == b {
-                    MatchResult::Matched { bindings: HashMap::new(), guard_passed: true }
-                } else {
-                    MatchResult::NotMatched
-                }
+            (Value::Int(a), Pattern::Literal(Literal::Int(b))) => Self::matched_if(a == b),
+            (Value::Float(a), Pattern::Literal(Literal::Float(b))) => {
+                Self::matched_if((a - b).abs() < f64::EPSILON)
             },
-            (Value::String(a), Pattern::Literal(tjlang_ast::Literal::String(b))) => {
-                if a == b {
-                    MatchResult::Matched { bindings: HashMap::new(), guard_passed: true }
+            (Value::Bool(a), Pattern::Literal(Literal::Bool(b))) => Self::matched_if(a == b),
+            (Value::String(a), Pattern::Literal(Literal::String(b))) => Self::matched_if(a == b),
+            (Value::None, Pattern::Literal(Literal::None)) => Self::matched_if(true),
+
+            // Typed variable patterns bind only when the value has the declared type
+            (value, Pattern::Variable { name, pattern_type, .. }) => {
+                if context.value_has_type(value, pattern_type) {
+                    Self::bind(name, value)
                 } else {
                     MatchResult::NotMatched
                 }
             },
-            (Value::None, Pattern::Literal(tjlang_ast::Literal::None)) => {
-                MatchResult::Matched { bindings: HashMap::new(), guard_passed: true }
-            },
-            
-            // Variable patterns
-            (value, Pattern::Variable { name, .. }) => {
-                let mut bindings = HashMap::new();
-                bindings.insert(name.clone(), value.clone());
-                MatchResult::Matched { bindings, guard_passed: true }
-            },
-            
+
             // Wildcard patterns
-            (_, Pattern::Wildcard(_)) => {
-                MatchResult::Matched { bindings: HashMap::new(), guard_passed: true }
-            },
-            
+            (_, Pattern::Wildcard(_)) => Self::matched_if(true),
+
             // Constructor patterns
-            (Value::Enum { name: enum_name, variant: variant_name, fields: value_fields }, 
-             Pattern::Constructor { name: pattern_name, fields: pattern_fields, .. }) => {
-                if enum_name == pattern_name && variant_name == pattern_name {
-                    if value_fields.len() == pattern_fields.len() {
-                        let mut bindings = HashMap::new();
-                        for (i, pattern_field) in pattern_fields.iter().enumerate() {
-                            if let Some(value_field) = value_fields.get(i) {
-                                match self.match_pattern_internal(value_field, pattern_field) {
-                                    MatchResult::Matched { bindings: field_bindings, guard_passed } => {
-                                        if !guard_passed {
-                                            return MatchResult::GuardFailed;
-                                        }
-                                        bindings.extend(field_bindings);
-                                    },
-                                    MatchResult::NotMatched => return MatchResult::NotMatched,
-                                    MatchResult::GuardFailed => return MatchResult::GuardFailed,
-                                }
-                            } else {
-                                return MatchResult::NotMatched;
-                            }
-                        }
-                        MatchResult::Matched { bindings, guard_passed: true }
-                    } else {
-                        MatchResult::NotMatched
-                    }
+            (Value::Enum { variant: variant_name, fields: value_fields, .. },
+             Pattern::Constructor { name: pattern_name, fields: pattern_fields, .. })
+                if variant_name == pattern_name =>
+            {
+                if value_fields.len() == pattern_fields.len() {
+                    self.match_all(value_fields.iter().zip(pattern_fields.iter()), context)
                 } else {
                     MatchResult::NotMatched
                 }
             },
-            
+
+            // A bare name that is not an enum variant binds the whole value
+            (value, Pattern::Constructor { name, fields, .. })
+                if fields.is_empty() && !context.is_enum_variant(name) =>
+            {
+                Self::bind(name, value)
+            },
+
             // Struct patterns
-            (Value::Struct { name: struct_name, fields: value_fields }, 
+            (Value::Struct { name: struct_name, fields: value_fields },
              Pattern::Struct { name: pattern_name, fields: pattern_fields, .. }) => {
-                if struct_name == pattern_name {
-                    let mut bindings = HashMap::new();
-                    for (field_name, pattern_field) in pattern_fields {
-                        if let Some(value_field) = value_fields.get(field_name) {
-                            match self.match_pattern_internal(value_field, pattern_field) {
-                                MatchResult::Matched { bindings: field_bindings, guard_passed } => {
-                                    if !guard_passed {
-                                        return MatchResult::GuardFailed;
-                                    }
-                                    bindings.extend(field_bindings);
-                                },
-                                MatchResult::NotMatched => return MatchResult::NotMatched,
-                                MatchResult::GuardFailed => return MatchResult::GuardFailed,
-                            }
-                        } else {
-                            return MatchResult::NotMatched;
+                if struct_name != pattern_name {
+                    return MatchResult::NotMatched;
+                }
+                // Copy the matched fields out so the lock is not held while matching
+                let mut field_values = Vec::new();
+                {
+                    let value_fields = value_fields.read();
+                    for (field_name, _) in pattern_fields {
+                        match value_fields.get(field_name) {
+                            Some(value_field) => field_values.push(value_field.clone()),
+                            None => return MatchResult::NotMatched,
                         }
                     }
-                    MatchResult::Matched { bindings, guard_passed: true }
-                } else {
-                    MatchResult::NotMatched
                 }
+                let pairs = field_values.iter().zip(pattern_fields.iter().map(|(_, p)| p));
+                self.match_all(pairs, context)
             },
-            
+
             // Tuple patterns
             (Value::Tuple(value_elements), Pattern::Tuple { patterns: pattern_elements, .. }) => {
                 if value_elements.len() == pattern_elements.len() {
-                    let mut bindings = HashMap::new();
-                    for (value_elem, pattern_elem) in value_elements.iter().zip(pattern_elements.iter()) {
-                        match self.match_pattern_internal(value_elem, pattern_elem) {
-                            MatchResult::Matched { bindings: elem_bindings, guard_passed } => {
-                                if !guard_passed {
-                                    return MatchResult::GuardFailed;
-                                }
-                                bindings.extend(elem_bindings);
-                            },
-                            MatchResult::NotMatched => return MatchResult::NotMatched,
-                            MatchResult::GuardFailed => return MatchResult::GuardFailed,
-                        }
-                    }
-                    MatchResult::Matched { bindings, guard_passed: true }
+                    self.match_all(value_elements.iter().zip(pattern_elements.iter()), context)
                 } else {
                     MatchResult::NotMatched
                 }
             },
-            
+
             // Trait check patterns
-            (value, Pattern::TraitCheck { trait_name, .. }) => {
-                // TODO: Implement trait checking
-                // For now, always match
-                MatchResult::Matched { bindings: HashMap::new(), guard_passed: true }
+            (value, Pattern::TraitCheck { name, trait_name, .. }) => {
+                if context.value_implements(value, trait_name) {
+                    Self::bind(name, value)
+                } else {
+                    MatchResult::NotMatched
+                }
             },
-            
+
             // Default case - no match
             _ => MatchResult::NotMatched,
         }
     }
-    
-    /// Check if a pattern is exhaustive for a given type
-    pub fn check_exhaustiveness(&self, patterns: &[Pattern], target_type: &Type) -> bool {
-        // TODO: Implement exhaustiveness checking
-        // This is a complex algorithm that needs to analyze the type structure
-        // and ensure all possible cases are covered
-        true
+
+    /// Match each value against its sub-pattern, collecting all bindings
+    fn match_all<'a>(
+        &mut self,
+        pairs: impl Iterator<Item = (&'a Value, &'a Pattern)>,
+        context: &dyn PatternContext,
+    ) -> MatchResult {
+        let mut bindings = HashMap::new();
+        for (value, pattern) in pairs {
+            match self.match_pattern_internal(value, pattern, context) {
+                MatchResult::Matched { bindings: inner_bindings, guard_passed } => {
+                    if !guard_passed {
+                        return MatchResult::GuardFailed;
+                    }
+                    bindings.extend(inner_bindings);
+                },
+                MatchResult::NotMatched => return MatchResult::NotMatched,
+                MatchResult::GuardFailed => return MatchResult::GuardFailed,
+            }
+        }
+        MatchResult::Matched { bindings, guard_passed: true }
     }
-    
-    /// Compile a pattern for efficient matching
-    pub fn compile_pattern(&mut self, name: String, pattern: Pattern) -> CompiledPattern {
-        let bindings = self.extract_bindings(&pattern);
-        let guards = Vec::new(); // TODO: Extract guards from pattern
-        
-        CompiledPattern {
-            pattern,
-            bindings,
-            guards,
-            exhaustiveness_checked: false,
+
+    fn matched_if(condition: bool) -> MatchResult {
+        if condition {
+            MatchResult::Matched { bindings: HashMap::new(), guard_passed: true }
+        } else {
+            MatchResult::NotMatched
         }
     }
-    
+
+    fn bind(name: &str, value: &Value) -> MatchResult {
+        let mut bindings = HashMap::new();
+        bindings.insert(name.to_string(), value.clone());
+        MatchResult::Matched { bindings, guard_passed: true }
+    }
+
     /// Extract variable bindings from a pattern
-    fn extract_bindings(&self, pattern: &Pattern) -> Vec<String> {
+    pub fn extract_bindings(&self, pattern: &Pattern) -> Vec<String> {
         match pattern {
             Pattern::Variable { name, .. } => vec![name.clone()],
+            Pattern::TraitCheck { name, .. } => vec![name.clone()],
             Pattern::Constructor { fields, .. } => {
                 fields.iter().flat_map(|f| self.extract_bindings(f)).collect()
             },
@@ -234,46 +209,21 @@ impl PatternMatcher {
             Pattern::Tuple { patterns, .. } => {
                 patterns.iter().flat_map(|p| self.extract_bindings(p)).collect()
             },
-            Pattern::TraitCheck { .. } => Vec::new(),
             Pattern::Wildcard(_) => Vec::new(),
             Pattern::Literal(_) => Vec::new(),
         }
     }
-    
+
     /// Get current bindings
     pub fn get_bindings(&self) -> &HashMap<String, Value> {
         &self.bindings
     }
-    
+
     /// Clear bindings
     pub fn clear_bindings(&mut self) {
         self.bindings.clear();
     }
-    
-    /// Match multiple patterns in sequence
-    pub fn match_sequence(&mut self, value: &Value, patterns: &[Pattern]) -> Vec<MatchResult> {
-        patterns.iter().map(|pattern| self.match_pattern(value, pattern)).collect()
-    }
-    
-    /// Find the first matching pattern
-    pub fn find_first_match(&mut self, value: &Value, patterns: &[Pattern]) -> Option<(usize, MatchResult)> {
-        for (i, pattern) in patterns.iter().enumerate() {
-            let result = self.match_pattern(value, pattern);
-            match result {
-                MatchResult::Matched { .. } => return Some((i, result)),
-                _ => continue,
-            }
-        }
-        None
-    }
-    
-    /// Check if a value matches any of the patterns
-    pub fn matches_any(&mut self, value: &Value, patterns: &[Pattern]) -> bool {
-        patterns.iter().any(|pattern| {
-            matches!(self.match_pattern(value, pattern), MatchResult::Matched { .. })
-        })
-    }
-    
+
     /// Get pattern complexity (for optimization)
     pub fn get_pattern_complexity(&self, pattern: &Pattern) -> usize {
         match pattern {
@@ -2,8 +2,13 @@
 //!
 //! This module provides methods that work on all primitive types (int, float, bool, str, None)
 
-use crate::values::Value;
+use crate::bigint::BigInt;
+use crate::stdlib::random::{Generator, RANDOM};
+use crate::stdlib::regex::{Pattern, Regex, REGEX};
+use crate::stdlib_integration::FromValue;
+use crate::values::{Native, NativeObject, Value};
 use std::collections::HashMap;
+use std::time::Duration;
 use tjlang_diagnostics::debug_println;
 
 /// Get a method for a primitive value
@@ -17,7 +22,7 @@ pub fn get_primitive_method(target: &Value, method: &str) -> Result<Value, Strin
     match method {
         // Core methods that work on all primitives
         "to_string" => Ok(Value::String(target.to_string())),
-        "clone" => Ok(target.clone()),
+        "clone" | "copy" => Ok(target.deep_copy()),
         "equals" => {
             // For methods that need arguments, we need to return a function
             // But for now, let's handle simple methods directly
@@ -32,7 +37,7 @@ pub fn get_primitive_method(target: &Value, method: &str) -> Result<Value, Strin
         "hash" => Ok(Value::Int(get_hash_code(target))),
 
         // Type checking methods
-        "is_int" => Ok(Value::Bool(matches!(target, Value::Int(_)))),
+        "is_int" => Ok(Value::Bool(matches!(target, Value::Int(_) | Value::BigInt(_)))),
         "is_float" => Ok(Value::Bool(matches!(target, Value::Float(_)))),
         "is_bool" => Ok(Value::Bool(matches!(target, Value::Bool(_)))),
         "is_str" => Ok(Value::Bool(matches!(target, Value::String(_)))),
@@ -67,14 +72,33 @@ pub fn execute_primitive_method(
     );
 
     // Handle collection methods with arguments
+    if is_mutating_method(target, method) {
+        return execute_mutating_method(target, method, args);
+    }
     if let Value::Vec(vec) = target {
-        return execute_vec_method(vec, method, args);
+        return execute_vec_method(&vec.read(), method, args);
     }
     if let Value::Set(set) = target {
-        return execute_set_method(set, method, args);
+        return execute_set_method(&set.read(), method, args);
     }
     if let Value::Map(map) = target {
-        return execute_map_method(map, method, args);
+        return execute_map_method(&map.read(), method, args);
+    }
+    if let Value::Regex(regex) = target {
+        return execute_regex_method(regex, method, args);
+    }
+    if let Value::Generator(generator) = target {
+        return execute_generator_method(generator, method, args);
+    }
+    if let Value::Native(object) = target {
+        return execute_native_method(object, method, args);
+    }
+
+    if let Some(result) = execute_integer_method(target, method, args) {
+        return result;
+    }
+    if let Some(result) = execute_string_pattern_method(target, method, args) {
+        return result;
     }
 
     match method {
@@ -98,6 +122,7 @@ pub fn execute_primitive_method(
 fn get_type_specific_method(target: &Value, method: &str) -> Result<Value, String> {
     match target {
         Value::Int(_) => get_integer_method(target, method),
+        Value::BigInt(_) => get_big_integer_method(target, method),
         Value::Float(_) => get_float_method(target, method),
         Value::Bool(_) => get_boolean_method(target, method),
         Value::String(_) => get_string_method(target, method),
@@ -106,6 +131,9 @@ fn get_type_specific_method(target: &Value, method: &str) -> Result<Value, Strin
         Value::Vec(_) => get_vec_method(target, method),
         Value::Set(_) => get_set_method(target, method),
         Value::Map(_) => get_map_method(target, method),
+        Value::Regex(regex) => get_regex_method(regex, method),
+        Value::Generator(generator) => execute_generator_method(generator, method, &[]),
+        Value::Native(object) => execute_native_method(object, method, &[]),
         _ => Err(format!(
             "No method '{}' found on {} value",
             method,
@@ -117,11 +145,12 @@ fn get_type_specific_method(target: &Value, method: &str) -> Result<Value, Strin
 /// Integer-specific methods
 fn get_integer_method(target: &Value, method: &str) -> Result<Value, String> {
     if let Value::Int(value) = target {
+        let overflow = || format!("Integer overflow in {}.{}()", value, method);
         match method {
-            "abs" => Ok(Value::Int(value.abs())),
-            "neg" => Ok(Value::Int(-value)),
-            "inc" => Ok(Value::Int(value + 1)),
-            "dec" => Ok(Value::Int(value - 1)),
+            "abs" => value.checked_abs().map(Value::Int).ok_or_else(overflow),
+            "neg" => value.checked_neg().map(Value::Int).ok_or_else(overflow),
+            "inc" => value.checked_add(1).map(Value::Int).ok_or_else(overflow),
+            "dec" => value.checked_sub(1).map(Value::Int).ok_or_else(overflow),
             "is_even" => Ok(Value::Bool(value % 2 == 0)),
             "is_odd" => Ok(Value::Bool(value % 2 != 0)),
             "is_positive" => Ok(Value::Bool(*value > 0)),
@@ -134,6 +163,89 @@ fn get_integer_method(target: &Value, method: &str) -> Result<Value, String> {
     }
 }
 
+/// Methods on integers outside the `i64` range
+fn get_big_integer_method(target: &Value, method: &str) -> Result<Value, String> {
+    if let Value::BigInt(value) = target {
+        let one = BigInt::from(1i64);
+        match method {
+            "abs" => Ok(Value::integer(value.abs())),
+            "neg" => Ok(Value::integer(-value)),
+            "inc" => Ok(Value::integer(value + &one)),
+            "dec" => Ok(Value::integer(value - &one)),
+            "is_even" | "is_odd" => {
+                let (_, remainder) = value.div_rem(&BigInt::from(2i64)).unwrap_or_default();
+                Ok(Value::Bool(remainder.is_zero() == (method == "is_even")))
+            }
+            "is_positive" => Ok(Value::Bool(!value.is_negative())),
+            "is_negative" => Ok(Value::Bool(value.is_negative())),
+            "is_zero" => Ok(Value::Bool(false)),
+            _ => Err(format!("No method '{}' found on integer", method)),
+        }
+    } else {
+        Err("Expected integer value".to_string())
+    }
+}
+
+/// Integer arithmetic with an explicit overflow behaviour: `checked_*` returns
+/// `None` on overflow, `wrapping_*` wraps around and `saturating_*` clamps to
+/// the range of `int`. `None` if `method` isn't one of them.
+fn execute_integer_method(target: &Value, method: &str, args: &[Value]) -> Option<Result<Value, String>> {
+    let (mode, operation) = method.split_once('_')?;
+    if !matches!(mode, "checked" | "wrapping" | "saturating")
+        || !matches!(operation, "add" | "sub" | "mul" | "div" | "pow")
+        || !matches!(target, Value::Int(_) | Value::BigInt(_))
+    {
+        return None;
+    }
+    let Value::Int(a) = *target else {
+        return Some(Err(format!("{} needs an int in the 64-bit range", method)));
+    };
+    let b = match args {
+        [Value::Int(b)] => *b,
+        _ => return Some(Err(format!("{} expects one int argument", method))),
+    };
+
+    let (checked, wrapping, saturating) = match operation {
+        "add" => (a.checked_add(b), a.wrapping_add(b), a.saturating_add(b)),
+        "sub" => (a.checked_sub(b), a.wrapping_sub(b), a.saturating_sub(b)),
+        "mul" => (a.checked_mul(b), a.wrapping_mul(b), a.saturating_mul(b)),
+        "div" if b == 0 => return Some(Err("Division by zero".to_string())),
+        "div" => (a.checked_div(b), a.wrapping_div(b), a.saturating_div(b)),
+        _ => {
+            let Ok(exponent) = u32::try_from(b) else {
+                return Some(Err(format!("{} expects a non-negative exponent", method)));
+            };
+            (a.checked_pow(exponent), a.wrapping_pow(exponent), a.saturating_pow(exponent))
+        }
+    };
+    Some(Ok(match mode {
+        "checked" => checked.map_or(Value::None, |result| Value::some(Value::Int(result))),
+        "wrapping" => Value::Int(wrapping),
+        _ => Value::Int(saturating),
+    }))
+}
+
+/// String methods taking a pattern, which may be a pattern string or a
+/// compiled `Regex`. `None` if `method` isn't one of them.
+fn execute_string_pattern_method(target: &Value, method: &str, args: &[Value]) -> Option<Result<Value, String>> {
+    let Value::String(text) = target else {
+        return None;
+    };
+    let pattern = |value: &Value| {
+        Pattern::from_value(value).ok_or_else(|| format!("{} expects a pattern string or Regex", method))
+    };
+    let result = match (method, args) {
+        ("matches", [p]) => pattern(p).and_then(|p| REGEX::is_match(&p, text)).map(Value::Bool),
+        ("replace_regex", [p, Value::String(replacement)]) => {
+            pattern(p).and_then(|p| REGEX::replace_all(&p, text, replacement)).map(Value::String)
+        }
+        ("matches", _) => Err("matches expects one pattern argument".to_string()),
+        ("replace_regex", _) => Err("replace_regex expects a pattern and a replacement str".to_string()),
+        _ => return None,
+    };
+    Some(result)
+}
+
 /// Float-specific methods
 fn get_float_method(target: &Value, method: &str) -> Result<Value, String> {
     if let Value::Float(value) = target {
@@ -196,6 +308,147 @@ fn get_string_method(target: &Value, method: &str) -> Result<Value, String> {
     }
 }
 
+/// Methods of compiled regexes without arguments
+fn get_regex_method(regex: &Regex, method: &str) -> Result<Value, String> {
+    match method {
+        "pattern" => Ok(Value::String(regex.pattern().to_string())),
+        _ => Err(format!("No method '{}' found on Regex", method)),
+    }
+}
+
+/// Methods of compiled regexes that search a text
+fn execute_regex_method(regex: &Regex, method: &str, args: &[Value]) -> Result<Value, String> {
+    let strings = |strings: Vec<String>| Value::vec(strings.into_iter().map(Value::String).collect());
+    match (method, args) {
+        ("is_match", [Value::String(text)]) => Ok(Value::Bool(regex.is_match(text))),
+        ("find", [Value::String(text)]) => {
+            Ok(regex.find(text).map_or(Value::None, |found| Value::some(Value::String(found.to_string()))))
+        }
+        ("find_all", [Value::String(text)]) => Ok(strings(
+            regex
+                .captures_iter(text)
+                .filter_map(|captures| captures.get(0).map(str::to_string))
+                .collect(),
+        )),
+        ("captures", [Value::String(text)]) => Ok(regex.captures(text).map_or(Value::None, |captures| {
+            Value::some(Value::map(
+                captures
+                    .to_map()
+                    .into_iter()
+                    .map(|(name, text)| (Value::String(name), Value::String(text)))
+                    .collect(),
+            ))
+        })),
+        ("replace_all", [Value::String(text), Value::String(replacement)]) => {
+            Ok(Value::String(regex.replace_all(text, replacement)))
+        }
+        ("split", [Value::String(text)]) => Ok(strings(regex.split(text))),
+        ("replace_all", _) => Err("replace_all expects a text and a replacement str".to_string()),
+        ("is_match" | "find" | "find_all" | "captures" | "split", _) => {
+            Err(format!("{} expects one str argument", method))
+        }
+        _ => Err(format!("No method '{}' found on Regex", method)),
+    }
+}
+
+/// Methods of random number generators, the same as the `RANDOM` functions
+fn execute_generator_method(generator: &Generator, method: &str, args: &[Value]) -> Result<Value, String> {
+    let float = |value: &Value| match value {
+        Value::Int(i) => Ok(*i as f64),
+        Value::Float(f) => Ok(*f),
+        _ => Err(format!("{} expects numeric arguments", method)),
+    };
+    match (method, args) {
+        ("seed", [Value::Int(seed)]) => {
+            generator.reseed(*seed as u64);
+            Ok(Value::None)
+        }
+        ("int", [Value::Int(lo), Value::Int(hi)]) => generator.int(*lo, *hi).map(Value::Int),
+        ("float", []) => generator.float(0.0, 1.0).map(Value::Float),
+        ("float", [lo, hi]) => generator.float(float(lo)?, float(hi)?).map(Value::Float),
+        ("gaussian", []) => generator.gaussian(0.0, 1.0).map(Value::Float),
+        ("gaussian", [mean, std_dev]) => generator.gaussian(float(mean)?, float(std_dev)?).map(Value::Float),
+        ("choice", [Value::Vec(items)]) => generator.choice(&items.read()),
+        ("sample", [Value::Vec(items), Value::Int(k)]) => {
+            let k = usize::try_from(*k).map_err(|_| format!("Cannot sample {} elements", k))?;
+            generator.sample(&items.read(), k).map(Value::vec)
+        }
+        ("shuffle", [Value::Vec(items)]) => {
+            generator.shuffle(&mut items.write());
+            Ok(Value::None)
+        }
+        ("uuid4", []) => Ok(Value::String(generator.uuid4())),
+        ("generator", []) => Ok(Value::Generator(RANDOM::generator(generator, None))),
+        ("seed" | "int" | "float" | "gaussian" | "choice" | "sample" | "shuffle" | "uuid4" | "generator", _) => {
+            Err(format!("Invalid arguments to Generator.{}; see `tjlang doc RANDOM.{}`", method, method))
+        }
+        _ => Err(format!("No method '{}' found on Generator", method)),
+    }
+}
+
+/// Methods TJLang code can call on each kind of native object
+pub const NATIVE_METHODS: &[(&str, &[&str])] = &[
+    ("ProgressBar", &["update", "increment", "finish"]),
+    ("Spinner", &["spin", "stop"]),
+    ("Timer", &["elapsed", "elapsed_millis", "elapsed_micros", "elapsed_nanos", "reset"]),
+    ("Stopwatch", &["start", "stop", "lap", "reset", "is_running", "get_laps", "get_total_time"]),
+    ("FileWatcher", &["start", "stop", "is_watching"]),
+];
+
+/// Methods of progress bars, spinners, timers, stopwatches and file watchers,
+/// the same as their Rust methods. Durations are floats of seconds.
+fn execute_native_method(object: &NativeObject, method: &str, args: &[Value]) -> Result<Value, String> {
+    let seconds = |duration: Duration| Value::Float(duration.as_secs_f64());
+    let whole = |count: u128| {
+        i64::try_from(count)
+            .map(Value::Int)
+            .map_err(|_| "Elapsed time out of range".to_string())
+    };
+    let done = |result: Result<(), String>| result.map(|_| Value::None);
+    let type_name = object.type_name();
+    match (&mut *object.lock(), method, args) {
+        (Native::ProgressBar(bar), "update", [Value::Int(current)]) => {
+            let current =
+                u64::try_from(*current).map_err(|_| format!("Cannot update a progress bar to {}", current))?;
+            done(bar.update(current))
+        }
+        (Native::ProgressBar(bar), "increment", []) => done(bar.increment()),
+        (Native::ProgressBar(bar), "finish", []) => done(bar.finish()),
+        (Native::Spinner(spinner), "spin", [Value::String(message)]) => done(spinner.spin(message)),
+        (Native::Spinner(spinner), "stop", []) => done(spinner.stop()),
+        (Native::Timer(timer), "elapsed", []) => Ok(seconds(timer.elapsed())),
+        (Native::Timer(timer), "elapsed_millis", []) => whole(timer.elapsed_millis()),
+        (Native::Timer(timer), "elapsed_micros", []) => whole(timer.elapsed_micros()),
+        (Native::Timer(timer), "elapsed_nanos", []) => whole(timer.elapsed_nanos()),
+        (Native::Timer(timer), "reset", []) => {
+            timer.reset();
+            Ok(Value::None)
+        }
+        (Native::Stopwatch(stopwatch), "start", []) => {
+            stopwatch.start();
+            Ok(Value::None)
+        }
+        (Native::Stopwatch(stopwatch), "stop", []) => Ok(stopwatch.stop().map_or(Value::None, |d| Value::some(seconds(d)))),
+        (Native::Stopwatch(stopwatch), "lap", []) => Ok(stopwatch.lap().map_or(Value::None, |d| Value::some(seconds(d)))),
+        (Native::Stopwatch(stopwatch), "reset", []) => {
+            stopwatch.reset();
+            Ok(Value::None)
+        }
+        (Native::Stopwatch(stopwatch), "is_running", []) => Ok(Value::Bool(stopwatch.is_running())),
+        (Native::Stopwatch(stopwatch), "get_laps", []) => {
+            Ok(Value::vec(stopwatch.get_laps().iter().copied().map(seconds).collect()))
+        }
+        (Native::Stopwatch(stopwatch), "get_total_time", []) => Ok(seconds(stopwatch.get_total_time())),
+        (Native::FileWatcher(watcher), "start", []) => done(watcher.start()),
+        (Native::FileWatcher(watcher), "stop", []) => done(watcher.stop()),
+        (Native::FileWatcher(watcher), "is_watching", []) => Ok(Value::Bool(watcher.is_watching())),
+        _ if NATIVE_METHODS.iter().any(|(kind, methods)| *kind == type_name && methods.contains(&method)) => {
+            Err(format!("Invalid arguments to {}.{}", type_name, method))
+        }
+        _ => Err(format!("No method '{}' found on {}", method, type_name)),
+    }
+}
+
 /// None-specific methods
 fn get_none_method(_target: &Value, method: &str) -> Result<Value, String> {
     match method {
@@ -205,10 +458,338 @@ fn get_none_method(_target: &Value, method: &str) -> Result<Value, String> {
     }
 }
 
+/// Calls a TJLang function value on behalf of a method such as `map`
+pub type FunctionCaller<'a> = dyn FnMut(&Value, &[Value]) -> Result<Value, String> + 'a;
+
+/// Methods shared by Option and Result values
+pub const OPTION_RESULT_METHODS: &[&str] = &[
+    "is_some",
+    "is_ok",
+    "is_err",
+    "unwrap",
+    "expect",
+    "unwrap_err",
+    "unwrap_or",
+    "unwrap_or_else",
+    "map",
+    "map_err",
+    "and_then",
+];
+
+/// Check whether a method call targets an Option or Result method
+pub fn is_option_result_method(target: &Value, method: &str) -> bool {
+    let is_option_or_result = match target {
+        Value::None => true,
+        Value::Enum { name, .. } => name == "Option" || name == "Result",
+        _ => false,
+    };
+    is_option_or_result && OPTION_RESULT_METHODS.contains(&method)
+}
+
+/// Execute an Option or Result method. `None` is the empty Option; function
+/// arguments are invoked through `call`.
+pub fn execute_option_result_method(
+    target: &Value,
+    method: &str,
+    args: &[Value],
+    call: &mut FunctionCaller,
+) -> Result<Value, String> {
+    debug_println!(
+        "[DEBUG] execute_option_result_method: method={}, args.len()={}",
+        method,
+        args.len()
+    );
+
+    // The wrapped value, and whether this is the Some/Ok side
+    let (is_result, present, payload) = match target {
+        Value::None => (false, false, Value::None),
+        Value::Enum { name, variant, fields } => (
+            name == "Result",
+            variant == "Some" || variant == "Ok",
+            fields.first().cloned().unwrap_or(Value::None),
+        ),
+        _ => return Err(format!("No method '{}' found on {}", method, get_type_name(target))),
+    };
+    let kind = if is_result { "Result" } else { "Option" };
+
+    let expected_args = match method {
+        "expect" | "unwrap_or" | "unwrap_or_else" | "map" | "map_err" | "and_then" => 1,
+        _ => 0,
+    };
+    if args.len() != expected_args {
+        return Err(format!(
+            "{} method requires exactly {} argument(s)",
+            method, expected_args
+        ));
+    }
+
+    match method {
+        "is_some" if !is_result => Ok(Value::Bool(present)),
+        "is_ok" if is_result => Ok(Value::Bool(present)),
+        "is_err" if is_result => Ok(Value::Bool(!present)),
+        "unwrap" => {
+            if present {
+                Ok(payload)
+            } else if is_result {
+                Err(format!("Called unwrap on Err({})", payload.to_string()))
+            } else {
+                Err("Called unwrap on None".to_string())
+            }
+        }
+        "expect" => {
+            if present {
+                Ok(payload)
+            } else {
+                Err(args[0].to_string())
+            }
+        }
+        "unwrap_err" if is_result => {
+            if present {
+                Err(format!("Called unwrap_err on Ok({})", payload.to_string()))
+            } else {
+                Ok(payload)
+            }
+        }
+        "unwrap_or" => Ok(if present { payload } else { args[0].clone() }),
+        "unwrap_or_else" => {
+            if present {
+                Ok(payload)
+            } else if is_result {
+                call(&args[0], &[payload])
+            } else {
+                call(&args[0], &[])
+            }
+        }
+        "map" => {
+            if !present {
+                return Ok(target.clone());
+            }
+            let mapped = call(&args[0], &[payload])?;
+            Ok(if is_result { Value::ok(mapped) } else { Value::some(mapped) })
+        }
+        "map_err" if is_result => {
+            if present {
+                return Ok(target.clone());
+            }
+            Ok(Value::err(call(&args[0], &[payload])?))
+        }
+        "and_then" => {
+            if present {
+                call(&args[0], &[payload])
+            } else {
+                Ok(target.clone())
+            }
+        }
+        _ => Err(format!("No method '{}' found on {}", method, kind)),
+    }
+}
+
+/// Methods of vectors, sets and maps that take a TJLang function
+const COLLECTION_CALLBACK_METHODS: &[&str] = &[
+    "map", "filter", "reduce", "sort_by", "find", "any", "all", "group_by",
+];
+
+/// Check whether a method call passes a TJLang function to a vector, set or map
+pub fn is_collection_callback_method(target: &Value, method: &str) -> bool {
+    matches!(target, Value::Vec(_) | Value::Set(_) | Value::Map(_))
+        && COLLECTION_CALLBACK_METHODS.contains(&method)
+}
+
+/// Execute a vector, set or map method that takes a TJLang function, invoked
+/// through `call`. Functions receive each element, or each key and value of a
+/// map. `map`, `filter` and `group_by` build the same kind of collection they
+/// were called on; `sort_by` sorts a vector in place and returns the sorted
+/// elements (or `(key, value)` entries) of a set or map as a vector.
+pub fn execute_collection_callback_method(
+    target: &Value,
+    method: &str,
+    args: &[Value],
+    call: &mut FunctionCaller,
+) -> Result<Value, String> {
+    debug_println!(
+        "[DEBUG] execute_collection_callback_method: method={}, args.len()={}",
+        method,
+        args.len()
+    );
+
+    // The arguments each element is passed to the function with. The collection
+    // is copied first so the function may use it without deadlocking.
+    let (kind, items): (&str, Vec<Vec<Value>>) = match target {
+        Value::Vec(vec) => ("vector", vec.read().iter().map(|item| vec![item.clone()]).collect()),
+        Value::Set(set) => ("set", set.read().iter().map(|item| vec![item.clone()]).collect()),
+        Value::Map(map) => (
+            "map",
+            map.read().iter().map(|(key, value)| vec![key.clone(), value.clone()]).collect(),
+        ),
+        _ => return Err(format!("No method '{}' found on {}", method, get_type_name(target))),
+    };
+
+    // Build a collection of the target's kind from some of its elements
+    let rebuild = |items: Vec<Vec<Value>>| -> Value {
+        match target {
+            Value::Vec(_) => Value::vec(items.into_iter().map(|mut item| item.remove(0)).collect()),
+            Value::Set(_) => Value::set(items.into_iter().map(|mut item| item.remove(0)).collect()),
+            _ => Value::map(
+                items
+                    .into_iter()
+                    .map(|mut item| {
+                        let value = item.pop().unwrap_or(Value::None);
+                        (item.pop().unwrap_or(Value::None), value)
+                    })
+                    .collect(),
+            ),
+        }
+    };
+    // A single element as a value: map entries become `(key, value)` tuples
+    let element = |mut item: Vec<Value>| -> Value {
+        if item.len() == 1 {
+            item.remove(0)
+        } else {
+            Value::Tuple(item)
+        }
+    };
+
+    let expected_args = match method {
+        "reduce" => 1..=2,
+        _ => 1..=1,
+    };
+    if !expected_args.contains(&args.len()) {
+        return Err(match method {
+            "reduce" => "reduce method requires a function, optionally after an initial value"
+                .to_string(),
+            _ => format!("{} method requires exactly 1 argument (a function)", method),
+        });
+    }
+    let function = &args[args.len() - 1];
+
+    match method {
+        "map" => match target {
+            Value::Map(_) => {
+                let mut mapped = HashMap::new();
+                for item in items {
+                    let value = call(function, &item)?;
+                    mapped.insert(item[0].clone(), value);
+                }
+                Ok(Value::map(mapped))
+            }
+            _ => {
+                let mapped = items
+                    .iter()
+                    .map(|item| call(function, item).map(|value| vec![value]))
+                    .collect::<Result<Vec<_>, String>>()?;
+                Ok(rebuild(mapped))
+            }
+        },
+        "filter" => {
+            let mut kept = Vec::new();
+            for item in items {
+                if call(function, &item)?.is_truthy() {
+                    kept.push(item);
+                }
+            }
+            Ok(rebuild(kept))
+        }
+        "reduce" => {
+            let mut items = items.into_iter();
+            let mut accumulator = if args.len() == 2 {
+                args[0].clone()
+            } else if matches!(target, Value::Map(_)) {
+                return Err("reduce on a map requires an initial value".to_string());
+            } else {
+                match items.next() {
+                    Some(first) => element(first),
+                    None => return Err(format!("Cannot reduce an empty {} without an initial value", kind)),
+                }
+            };
+            for item in items {
+                let mut call_args = vec![accumulator];
+                call_args.extend(item);
+                accumulator = call(function, &call_args)?;
+            }
+            Ok(accumulator)
+        }
+        "find" => {
+            for item in items {
+                if call(function, &item)?.is_truthy() {
+                    return Ok(Value::some(element(item)));
+                }
+            }
+            Ok(Value::None)
+        }
+        "any" | "all" => {
+            let want = method == "any";
+            for item in items {
+                if call(function, &item)?.is_truthy() == want {
+                    return Ok(Value::Bool(want));
+                }
+            }
+            Ok(Value::Bool(!want))
+        }
+        "group_by" => {
+            let mut groups: HashMap<Value, Vec<Vec<Value>>> = HashMap::new();
+            for item in items {
+                let key = call(function, &item)?;
+                groups.entry(key).or_default().push(item);
+            }
+            Ok(Value::map(
+                groups.into_iter().map(|(key, group)| (key, rebuild(group))).collect(),
+            ))
+        }
+        "sort_by" => {
+            let elements = items.into_iter().map(element).collect();
+            let sorted = merge_sort(elements, &mut |a, b| {
+                match call(function, &[a.clone(), b.clone()])? {
+                    Value::Int(order) => Ok(order.cmp(&0)),
+                    other => Err(format!(
+                        "sort_by comparison function must return an int, got {}",
+                        get_type_name(&other)
+                    )),
+                }
+            })?;
+            match target {
+                Value::Vec(vec) => {
+                    *vec.write() = sorted;
+                    Ok(Value::None)
+                }
+                _ => Ok(Value::vec(sorted)),
+            }
+        }
+        _ => Err(format!("No method '{}' found on {}", method, kind)),
+    }
+}
+
+/// Stable merge sort with a comparison that may fail
+fn merge_sort(
+    mut items: Vec<Value>,
+    compare: &mut dyn FnMut(&Value, &Value) -> Result<std::cmp::Ordering, String>,
+) -> Result<Vec<Value>, String> {
+    if items.len() < 2 {
+        return Ok(items);
+    }
+    let right = items.split_off(items.len() / 2);
+    let left = merge_sort(items, compare)?;
+    let mut right = merge_sort(right, compare)?.into_iter().peekable();
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    for item in left {
+        // Right-hand elements only pass left-hand ones that sort strictly after
+        // them, which keeps the sort stable
+        while let Some(next) = right.peek() {
+            if compare(next, &item)? != std::cmp::Ordering::Less {
+                break;
+            }
+            merged.extend(right.next());
+        }
+        merged.push(item);
+    }
+    merged.extend(right);
+    Ok(merged)
+}
+
 /// Helper functions
 pub fn get_type_name(value: &Value) -> String {
     match value {
-        Value::Int(_) => "int".to_string(),
+        Value::Int(_) | Value::BigInt(_) => "int".to_string(),
         Value::Float(_) => "float".to_string(),
         Value::Bool(_) => "bool".to_string(),
         Value::String(_) => "str".to_string(),
@@ -237,6 +818,7 @@ pub fn get_hash_code(value: &Value) -> i64 {
 pub fn get_pretty_string(value: &Value) -> String {
     match value {
         Value::Int(i) => i.to_string(),
+        Value::BigInt(i) => i.to_string(),
         Value::Float(f) => {
             if f.fract() == 0.0 {
                 format!("{:.0}", f)
@@ -254,6 +836,7 @@ pub fn get_pretty_string(value: &Value) -> String {
 fn primitive_equals(left: &Value, right: &Value) -> bool {
     match (left, right) {
         (Value::Int(a), Value::Int(b)) => a == b,
+        (Value::BigInt(a), Value::BigInt(b)) => a == b,
         (Value::Float(a), Value::Float(b)) => (a - b).abs() < f64::EPSILON,
         (Value::Bool(a), Value::Bool(b)) => a == b,
         (Value::String(a), Value::String(b)) => a == b,
@@ -264,7 +847,7 @@ fn primitive_equals(left: &Value, right: &Value) -> bool {
 
 fn convert_to_int(value: &Value) -> Result<Value, String> {
     match value {
-        Value::Int(i) => Ok(Value::Int(*i)),
+        Value::Int(_) | Value::BigInt(_) => Ok(value.clone()),
         Value::Float(f) => Ok(Value::Int(*f as i64)),
         Value::Bool(b) => Ok(Value::Int(if *b { 1 } else { 0 })),
         Value::String(s) => s
@@ -281,8 +864,9 @@ fn convert_to_int(value: &Value) -> Result<Value, String> {
 
 fn convert_to_float(value: &Value) -> Result<Value, String> {
     match value {
-        Value::Int(i) => Ok(Value::Float(*i as f64)),
-        Value::Float(f) => Ok(Value::Float(*f)),
+        Value::Int(_) | Value::BigInt(_) | Value::Float(_) => {
+            Ok(Value::Float(value.as_f64().unwrap_or_default()))
+        }
         Value::Bool(b) => Ok(Value::Float(if *b { 1.0 } else { 0.0 })),
         Value::String(s) => s
             .parse::<f64>()
@@ -296,6 +880,7 @@ fn convert_to_float(value: &Value) -> Result<Value, String> {
 fn convert_to_bool(value: &Value) -> Result<Value, String> {
     match value {
         Value::Int(i) => Ok(Value::Bool(*i != 0)),
+        Value::BigInt(_) => Ok(Value::Bool(true)),
         Value::Float(f) => Ok(Value::Bool(*f != 0.0)),
         Value::Bool(b) => Ok(Value::Bool(*b)),
         Value::String(s) => Ok(Value::Bool(!s.is_empty())),
@@ -321,9 +906,28 @@ fn get_tuple_method(target: &Value, method: &str) -> Result<Value, String> {
     }
 }
 
+/// Order values for `sort`: numbers, strings and booleans compare naturally,
+/// anything else by its string form
+pub(crate) fn compare_values(a: &Value, b: &Value) -> std::cmp::Ordering {
+    match (a, b) {
+        // Integer comparison
+        (Value::Int(x), Value::Int(y)) => x.cmp(y),
+        (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => a.to_big_int().cmp(&b.to_big_int()),
+        // Float comparison
+        (Value::Float(x), Value::Float(y)) => x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal),
+        // String comparison
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        // Boolean comparison (false < true)
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        // Mixed types: convert to string for comparison
+        _ => a.to_string().cmp(&b.to_string()),
+    }
+}
+
 /// Vector-specific methods
 fn get_vec_method(target: &Value, method: &str) -> Result<Value, String> {
     if let Value::Vec(vec) = target {
+        let vec = vec.read();
         match method {
             // Basic properties (no arguments needed)
             "length" => Ok(Value::Int(vec.len() as i64)),
@@ -331,36 +935,9 @@ fn get_vec_method(target: &Value, method: &str) -> Result<Value, String> {
             "capacity" => Ok(Value::Int(vec.capacity() as i64)),
             "is_empty" => Ok(Value::Bool(vec.is_empty())),
             "is_not_empty" => Ok(Value::Bool(!vec.is_empty())),
-            "reverse" => {
-                let mut new_vec = vec.clone();
-                new_vec.reverse();
-                Ok(Value::Vec(new_vec))
-            }
-            "sort" => {
-                let mut new_vec = vec.clone();
-                // Improved sorting that handles different types more effectively
-                new_vec.sort_by(|a, b| {
-                    match (a, b) {
-                        // Integer comparison
-                        (Value::Int(x), Value::Int(y)) => x.cmp(y),
-                        // Float comparison
-                        (Value::Float(x), Value::Float(y)) => {
-                            x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
-                        }
-                        // String comparison
-                        (Value::String(x), Value::String(y)) => x.cmp(y),
-                        // Boolean comparison (false < true)
-                        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
-                        // Mixed types: convert to string for comparison
-                        _ => a.to_string().cmp(&b.to_string()),
-                    }
-                });
-                Ok(Value::Vec(new_vec))
-            }
 
             // Methods that require arguments - these will be handled by the interpreter
-            "push" | "pop" | "insert" | "remove" | "get" | "at" | "get_mut" | "set" | "slice"
-            | "append" | "extend" | "sort_by" | "shuffle" | "unique" | "filter" | "map"
+            "get" | "at" | "get_mut" | "slice" | "shuffle" | "unique" | "filter" | "map"
             | "reduce" | "fold" | "any" | "all" | "find" | "find_index" | "contains"
             | "index_of" | "last_index_of" => Err(format!(
                 "{} method requires arguments - use execute_primitive_method instead",
@@ -375,7 +952,7 @@ fn get_vec_method(target: &Value, method: &str) -> Result<Value, String> {
 }
 
 /// Execute vector methods with arguments
-fn execute_vec_method(vec: &Vec<Value>, method: &str, args: &[Value]) -> Result<Value, String> {
+fn execute_vec_method(vec: &[Value], method: &str, args: &[Value]) -> Result<Value, String> {
     debug_println!(
         "[DEBUG] execute_vec_method called: method={}, args={:?}",
         method,
@@ -383,14 +960,6 @@ fn execute_vec_method(vec: &Vec<Value>, method: &str, args: &[Value]) -> Result<
     );
 
     match method {
-        "push" => {
-            if args.len() != 1 {
-                return Err("push method requires exactly 1 argument".to_string());
-            }
-            let mut new_vec = vec.clone();
-            new_vec.push(args[0].clone());
-            Ok(Value::Vec(new_vec))
-        }
         "get" | "at" => {
             if args.len() != 1 {
                 return Err("get/at method requires exactly 1 argument".to_string());
@@ -405,154 +974,222 @@ fn execute_vec_method(vec: &Vec<Value>, method: &str, args: &[Value]) -> Result<
                 Err("get/at method requires integer index".to_string())
             }
         }
-        "set" => {
+        "slice" => {
             if args.len() != 2 {
-                return Err("set method requires exactly 2 arguments".to_string());
+                return Err("slice method requires exactly 2 arguments".to_string());
             }
-            if let Value::Int(index) = &args[0] {
-                if *index >= 0 && (*index as usize) < vec.len() {
-                    let mut new_vec = vec.clone();
-                    new_vec[*index as usize] = args[1].clone();
-                    Ok(Value::Vec(new_vec))
+            if let (Value::Int(start), Value::Int(end)) = (&args[0], &args[1]) {
+                if *start >= 0
+                    && *end >= 0
+                    && *start as usize <= vec.len()
+                    && *end as usize <= vec.len()
+                    && *start <= *end
+                {
+                    let slice: Vec<Value> = vec[*start as usize..*end as usize].to_vec();
+                    Ok(Value::vec(slice))
                 } else {
-                    Err("Index out of bounds".to_string())
+                    Err("Invalid slice bounds".to_string())
                 }
             } else {
-                Err("set method requires integer index".to_string())
+                Err("slice method requires integer start and end".to_string())
+            }
+        }
+        "contains" => {
+            if args.len() != 1 {
+                return Err("contains method requires exactly 1 argument".to_string());
+            }
+            Ok(Value::Bool(vec.contains(&args[0])))
+        }
+        _ => Err(format!("No method '{}' found on vector", method)),
+    }
+}
+
+/// Check whether a method changes its collection in place
+pub fn is_mutating_method(target: &Value, method: &str) -> bool {
+    match target {
+        Value::Vec(_) => matches!(
+            method,
+            "push" | "pop" | "insert" | "remove" | "set" | "reverse" | "sort" | "clear"
+                | "extend" | "append"
+        ),
+        Value::Set(_) => matches!(method, "insert" | "remove" | "clear"),
+        Value::Map(_) => matches!(method, "insert" | "set" | "remove" | "clear"),
+        _ => false,
+    }
+}
+
+/// Execute a method that changes a vector, set or map in place. Every value
+/// sharing the collection sees the change.
+pub fn execute_mutating_method(target: &Value, method: &str, args: &[Value]) -> Result<Value, String> {
+    debug_println!(
+        "[DEBUG] execute_mutating_method: method={}, args.len()={}",
+        method,
+        args.len()
+    );
+
+    match target {
+        Value::Vec(vec) => {
+            // Read the appended items first: the argument may be this same vector
+            let extra = match (method, args) {
+                ("extend" | "append", [Value::Vec(other)]) => Some(other.read().clone()),
+                _ => None,
+            };
+            mutate_vec(&mut vec.write(), method, args, extra)
+        }
+        Value::Set(set) => mutate_set(&mut set.write(), method, args),
+        Value::Map(map) => mutate_map(&mut map.write(), method, args),
+        _ => Err(format!(
+            "No method '{}' found on {}",
+            method,
+            get_type_name(target)
+        )),
+    }
+}
+
+/// Change a vector in place. `pop` and `remove` return the removed element.
+fn mutate_vec(
+    vec: &mut Vec<Value>,
+    method: &str,
+    args: &[Value],
+    extra: Option<Vec<Value>>,
+) -> Result<Value, String> {
+    let index_arg = |arg: &Value, limit: usize| match arg {
+        Value::Int(index) if *index >= 0 && (*index as usize) < limit => Ok(*index as usize),
+        Value::Int(_) => Err("Index out of bounds".to_string()),
+        _ => Err(format!("{} method requires integer index", method)),
+    };
+
+    match method {
+        "push" => {
+            if args.len() != 1 {
+                return Err("push method requires exactly 1 argument".to_string());
             }
+            vec.push(args[0].clone());
+            Ok(Value::None)
         }
         "pop" => {
-            if args.len() != 0 {
+            if !args.is_empty() {
                 return Err("pop method takes no arguments".to_string());
             }
-            let mut new_vec = vec.clone();
-            if let Some(value) = new_vec.pop() {
-                Ok(value)
-            } else {
-                Err("Cannot pop from empty vector".to_string())
-            }
+            vec.pop()
+                .ok_or_else(|| "Cannot pop from empty vector".to_string())
         }
         "insert" => {
             if args.len() != 2 {
                 return Err("insert method requires exactly 2 arguments".to_string());
             }
-            if let Value::Int(index) = &args[0] {
-                if *index >= 0 && (*index as usize) <= vec.len() {
-                    let mut new_vec = vec.clone();
-                    new_vec.insert(*index as usize, args[1].clone());
-                    Ok(Value::Vec(new_vec))
-                } else {
-                    Err("Index out of bounds".to_string())
-                }
-            } else {
-                Err("insert method requires integer index".to_string())
-            }
+            let index = index_arg(&args[0], vec.len() + 1)?;
+            vec.insert(index, args[1].clone());
+            Ok(Value::None)
         }
         "remove" => {
             if args.len() != 1 {
                 return Err("remove method requires exactly 1 argument".to_string());
             }
-            if let Value::Int(index) = &args[0] {
-                if *index >= 0 && (*index as usize) < vec.len() {
-                    let mut new_vec = vec.clone();
-                    let removed = new_vec.remove(*index as usize);
-                    Ok(removed)
-                } else {
-                    Err("Index out of bounds".to_string())
-                }
-            } else {
-                Err("remove method requires integer index".to_string())
-            }
+            let index = index_arg(&args[0], vec.len())?;
+            Ok(vec.remove(index))
         }
-        "slice" => {
+        "set" => {
             if args.len() != 2 {
-                return Err("slice method requires exactly 2 arguments".to_string());
-            }
-            if let (Value::Int(start), Value::Int(end)) = (&args[0], &args[1]) {
-                if *start >= 0
-                    && *end >= 0
-                    && *start as usize <= vec.len()
-                    && *end as usize <= vec.len()
-                    && *start <= *end
-                {
-                    let slice: Vec<Value> = vec[*start as usize..*end as usize].to_vec();
-                    Ok(Value::Vec(slice))
-                } else {
-                    Err("Invalid slice bounds".to_string())
-                }
-            } else {
-                Err("slice method requires integer start and end".to_string())
+                return Err("set method requires exactly 2 arguments".to_string());
             }
+            let index = index_arg(&args[0], vec.len())?;
+            vec[index] = args[1].clone();
+            Ok(Value::None)
         }
         "reverse" => {
-            if args.len() != 0 {
+            if !args.is_empty() {
                 return Err("reverse method takes no arguments".to_string());
             }
-            let mut new_vec = vec.clone();
-            new_vec.reverse();
-            Ok(Value::Vec(new_vec))
+            vec.reverse();
+            Ok(Value::None)
         }
         "sort" => {
-            if args.len() != 0 {
+            if !args.is_empty() {
                 return Err("sort method takes no arguments".to_string());
             }
-            let mut new_vec = vec.clone();
-            // Improved sorting that handles different types more effectively
-            new_vec.sort_by(|a, b| {
-                match (a, b) {
-                    // Integer comparison
-                    (Value::Int(x), Value::Int(y)) => x.cmp(y),
-                    // Float comparison
-                    (Value::Float(x), Value::Float(y)) => {
-                        x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
-                    }
-                    // String comparison
-                    (Value::String(x), Value::String(y)) => x.cmp(y),
-                    // Boolean comparison (false < true)
-                    (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
-                    // Mixed types: convert to string for comparison
-                    _ => a.to_string().cmp(&b.to_string()),
-                }
-            });
-            Ok(Value::Vec(new_vec))
+            vec.sort_by(compare_values);
+            Ok(Value::None)
         }
-        "sort_by" => {
-            if args.len() != 1 {
-                return Err(
-                    "sort_by method requires exactly 1 argument (comparison function)".to_string(),
-                );
-            }
-
-            // Check if the argument is a closure (lambda function)
-            match &args[0] {
-                Value::Closure { params, body, .. } => {
-                    if params.len() != 2 {
-                        return Err("sort_by comparison function must take exactly 2 parameters"
-                            .to_string());
-                    }
-
-                    let mut new_vec = vec.clone();
-                    // For now, we'll use a simple comparison based on the closure
-                    // In a full implementation, we would execute the closure for each comparison
-                    new_vec.sort_by(|a, b| {
-                        // For now, use string comparison as a fallback
-                        // TODO: Execute the closure with parameters a and b
-                        a.to_string().cmp(&b.to_string())
-                    });
-                    Ok(Value::Vec(new_vec))
-                }
-                _ => {
-                    return Err("sort_by requires a lambda function as argument".to_string());
-                }
+        "clear" => {
+            if !args.is_empty() {
+                return Err("clear method takes no arguments".to_string());
             }
+            vec.clear();
+            Ok(Value::None)
         }
+        "extend" | "append" => match extra {
+            Some(items) if args.len() == 1 => {
+                vec.extend(items);
+                Ok(Value::None)
+            }
+            _ => Err(format!("{} method requires exactly 1 vector argument", method)),
+        },
         _ => Err(format!("No method '{}' found on vector", method)),
     }
 }
 
+/// Change a set in place. `insert` and `remove` return whether the set changed.
+fn mutate_set(
+    set: &mut std::collections::HashSet<Value>,
+    method: &str,
+    args: &[Value],
+) -> Result<Value, String> {
+    match method {
+        "insert" => {
+            if args.len() != 1 {
+                return Err("insert method requires exactly 1 argument".to_string());
+            }
+            Ok(Value::Bool(set.insert(args[0].clone())))
+        }
+        "remove" => {
+            if args.len() != 1 {
+                return Err("remove method requires exactly 1 argument".to_string());
+            }
+            Ok(Value::Bool(set.remove(&args[0])))
+        }
+        "clear" => {
+            if !args.is_empty() {
+                return Err("clear method takes no arguments".to_string());
+            }
+            set.clear();
+            Ok(Value::None)
+        }
+        _ => Err(format!("No method '{}' found on set", method)),
+    }
+}
+
+/// Change a map in place. `remove` returns the removed value, or None.
+fn mutate_map(map: &mut HashMap<Value, Value>, method: &str, args: &[Value]) -> Result<Value, String> {
+    match method {
+        "insert" | "set" => {
+            if args.len() != 2 {
+                return Err(format!("{} method requires exactly 2 arguments", method));
+            }
+            map.insert(args[0].clone(), args[1].clone());
+            Ok(Value::None)
+        }
+        "remove" => {
+            if args.len() != 1 {
+                return Err("remove method requires exactly 1 argument".to_string());
+            }
+            Ok(map.remove(&args[0]).unwrap_or(Value::None))
+        }
+        "clear" => {
+            if !args.is_empty() {
+                return Err("clear method takes no arguments".to_string());
+            }
+            map.clear();
+            Ok(Value::None)
+        }
+        _ => Err(format!("No method '{}' found on map", method)),
+    }
+}
+
 /// Set-specific methods
 fn get_set_method(target: &Value, method: &str) -> Result<Value, String> {
     if let Value::Set(set) = target {
+        let set = set.read();
         match method {
             // Basic properties (no arguments needed)
             "length" => Ok(Value::Int(set.len() as i64)),
@@ -561,9 +1198,7 @@ fn get_set_method(target: &Value, method: &str) -> Result<Value, String> {
             "is_not_empty" => Ok(Value::Bool(!set.is_empty())),
 
             // Methods that require arguments - these will be handled by the interpreter
-            "insert"
-            | "remove"
-            | "contains"
+            "contains"
             | "union"
             | "intersection"
             | "difference"
@@ -595,22 +1230,6 @@ fn execute_set_method(
     );
 
     match method {
-        "insert" => {
-            if args.len() != 1 {
-                return Err("insert method requires exactly 1 argument".to_string());
-            }
-            let mut new_set = set.clone();
-            new_set.insert(args[0].clone());
-            Ok(Value::Set(new_set))
-        }
-        "remove" => {
-            if args.len() != 1 {
-                return Err("remove method requires exactly 1 argument".to_string());
-            }
-            let mut new_set = set.clone();
-            new_set.remove(&args[0]);
-            Ok(Value::Set(new_set))
-        }
         "contains" => {
             if args.len() != 1 {
                 return Err("contains method requires exactly 1 argument".to_string());
@@ -624,6 +1243,7 @@ fn execute_set_method(
 /// Map-specific methods
 fn get_map_method(target: &Value, method: &str) -> Result<Value, String> {
     if let Value::Map(map) = target {
+        let map = map.read();
         match method {
             // Basic properties (no arguments needed)
             "length" => Ok(Value::Int(map.len() as i64)),
@@ -632,8 +1252,7 @@ fn get_map_method(target: &Value, method: &str) -> Result<Value, String> {
             "is_not_empty" => Ok(Value::Bool(!map.is_empty())),
 
             // Methods that require arguments - these will be handled by the interpreter
-            "insert" | "remove" | "get" | "set" | "contains_key" | "keys" | "values"
-            | "entries" | "clear" => Err(format!(
+            "get" | "contains_key" | "keys" | "values" | "entries" => Err(format!(
                 "{} method requires arguments - use execute_primitive_method instead",
                 method
             )),
@@ -658,22 +1277,6 @@ fn execute_map_method(
     );
 
     match method {
-        "insert" => {
-            if args.len() != 2 {
-                return Err("insert method requires exactly 2 arguments".to_string());
-            }
-            let mut new_map = map.clone();
-            new_map.insert(args[0].clone(), args[1].clone());
-            Ok(Value::Map(new_map))
-        }
-        "set" => {
-            if args.len() != 2 {
-                return Err("set method requires exactly 2 arguments".to_string());
-            }
-            let mut new_map = map.clone();
-            new_map.insert(args[0].clone(), args[1].clone());
-            Ok(Value::Map(new_map))
-        }
         "get" => {
             if args.len() != 1 {
                 return Err("get method requires exactly 1 argument".to_string());
@@ -684,14 +1287,6 @@ fn execute_map_method(
                 Ok(Value::None)
             }
         }
-        "remove" => {
-            if args.len() != 1 {
-                return Err("remove method requires exactly 1 argument".to_string());
-            }
-            let mut new_map = map.clone();
-            new_map.remove(&args[0]);
-            Ok(Value::Map(new_map))
-        }
         "contains_key" => {
             if args.len() != 1 {
                 return Err("contains_key method requires exactly 1 argument".to_string());
@@ -703,14 +1298,14 @@ fn execute_map_method(
                 return Err("keys method takes no arguments".to_string());
             }
             let keys: Vec<Value> = map.keys().cloned().collect();
-            Ok(Value::Vec(keys))
+            Ok(Value::vec(keys))
         }
         "values" => {
             if args.len() != 0 {
                 return Err("values method takes no arguments".to_string());
             }
             let values: Vec<Value> = map.values().cloned().collect();
-            Ok(Value::Vec(values))
+            Ok(Value::vec(values))
         }
         "entries" => {
             if args.len() != 0 {
@@ -720,13 +1315,7 @@ fn execute_map_method(
                 .iter()
                 .map(|(k, v)| Value::Tuple(vec![k.clone(), v.clone()]))
                 .collect();
-            Ok(Value::Vec(entries))
-        }
-        "clear" => {
-            if args.len() != 0 {
-                return Err("clear method takes no arguments".to_string());
-            }
-            Ok(Value::Map(HashMap::new()))
+            Ok(Value::vec(entries))
         }
         _ => Err(format!("No method '{}' found on map", method)),
     }
@@ -0,0 +1,525 @@
+//! Variable resolution
+//!
+//! Runs over a program before it executes and decides where each variable
+//! lives. Parameters, local variables and pattern bindings get a slot in the
+//! frame of the function that declares them; every other name is a global of
+//! the module and is looked up by name at runtime.
+
+use codespan::{FileId, Span};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tjlang_ast::*;
+
+/// Where a local variable is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Slot {
+    /// How many closures lie between the use and the function declaring the
+    /// variable; 0 is the frame of the running call
+    pub depth: usize,
+    /// Position in that function's frame
+    pub index: usize,
+}
+
+/// Source location of a use or declaration, identifying the AST node
+type Site = (FileId, Span);
+
+fn site(span: &SourceSpan) -> Site {
+    (span.file_id, span.span)
+}
+
+/// The slots chosen for a program, keyed by the location of each use or declaration
+#[derive(Debug, Clone, Default)]
+pub struct Resolution {
+    /// Variable uses, assignment targets, declarations and for-loop variables
+    locals: HashMap<Site, Slot>,
+    /// Names bound by each match arm or except clause pattern, with their slots
+    patterns: HashMap<Site, Vec<(String, usize)>>,
+}
+
+impl Resolution {
+    /// Slot of the variable named at `span`, or `None` when it is a global
+    pub fn local(&self, span: &SourceSpan) -> Option<Slot> {
+        self.locals.get(&site(span)).copied()
+    }
+
+    /// Slot a match arm or except clause at `span` binds `name` to
+    pub fn pattern_slot(&self, span: &SourceSpan, name: &str) -> Option<usize> {
+        self.patterns
+            .get(&site(span))?
+            .iter()
+            .find(|(bound, _)| bound == name)
+            .map(|(_, index)| *index)
+    }
+
+    /// Add the resolution of another program, such as an imported module
+    pub fn extend(&mut self, other: Resolution) {
+        self.locals.extend(other.locals);
+        self.patterns.extend(other.patterns);
+    }
+}
+
+/// Resolve every variable in a program
+pub fn resolve_program(program: &Program) -> Resolution {
+    let mut resolver = Resolver {
+        globals: HashSet::new(),
+        functions: vec![FunctionScope::new(true)],
+        resolution: Resolution::default(),
+    };
+    resolver.declare_globals(program);
+    resolver.resolve_units(program);
+    resolver.resolution
+}
+
+/// Names declared by a function, a closure or the top level of a program
+struct FunctionScope {
+    /// Scopes of the function, innermost last. Match arms and except clauses
+    /// open a scope for the names they bind.
+    scopes: Vec<HashMap<String, usize>>,
+    slot_count: usize,
+    /// Top-level code declares globals, except inside a match arm or except clause
+    top_level: bool,
+}
+
+impl FunctionScope {
+    fn new(top_level: bool) -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            slot_count: 0,
+            top_level,
+        }
+    }
+}
+
+struct Resolver {
+    /// Names the program declares at the top level
+    globals: HashSet<String>,
+    /// Functions being resolved, innermost last
+    functions: Vec<FunctionScope>,
+    resolution: Resolution,
+}
+
+impl Resolver {
+    /// Find the slot a name refers to from the current scope
+    fn lookup(&self, name: &str) -> Option<Slot> {
+        self.functions.iter().rev().enumerate().find_map(|(depth, function)| {
+            function
+                .scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(name))
+                .map(|&index| Slot { depth, index })
+        })
+    }
+
+    /// Declare a name in the innermost scope, reusing its slot if it is already there
+    fn declare(&mut self, name: &str) {
+        let function = self.functions.last_mut().expect("resolver has no function scope");
+        if function.top_level && function.scopes.len() == 1 {
+            self.globals.insert(name.to_string());
+            return;
+        }
+        let scope = function.scopes.last_mut().expect("function has no scope");
+        if !scope.contains_key(name) {
+            scope.insert(name.to_string(), function.slot_count);
+            function.slot_count += 1;
+        }
+    }
+
+    /// Remember which slot the name at `span` refers to, if it is a local
+    fn record(&mut self, span: &SourceSpan, name: &str) {
+        if let Some(slot) = self.lookup(name) {
+            self.resolution.locals.insert(site(span), slot);
+        }
+    }
+
+    fn push_scope(&mut self) {
+        if let Some(function) = self.functions.last_mut() {
+            function.scopes.push(HashMap::new());
+        }
+    }
+
+    fn pop_scope(&mut self) {
+        if let Some(function) = self.functions.last_mut() {
+            function.scopes.pop();
+        }
+    }
+
+    /// Collect every name the program binds at the top level, so functions
+    /// assigning to one update the global instead of declaring a local
+    fn declare_globals(&mut self, program: &Program) {
+        for unit in &program.units {
+            match unit {
+                ProgramUnit::Declaration(decl) | ProgramUnit::Export(ExportDecl::Declaration(decl)) => {
+                    match decl {
+                        Declaration::Function(func) => self.declare(&func.name),
+                        Declaration::Variable(var) => {
+                            self.hoist_expression(&var.value);
+                            self.declare(&var.name);
+                        }
+                        Declaration::Enum(enum_decl) => {
+                            self.declare(&enum_decl.name);
+                            for variant in &enum_decl.variants {
+                                self.declare(&variant.name);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                ProgramUnit::Import(ImportDecl::Simple { module, alias, .. }) => {
+                    if let Some(name) = alias.as_ref().or(module.parts.last()) {
+                        self.declare(name);
+                    }
+                }
+                ProgramUnit::Import(ImportDecl::Selective { items, .. }) => {
+                    for item in items {
+                        self.declare(item);
+                    }
+                }
+                ProgramUnit::Statement(stmt) => self.hoist_statement(stmt),
+                ProgramUnit::Expression(expr) => self.hoist_expression(expr),
+                _ => {}
+            }
+        }
+    }
+
+    fn resolve_units(&mut self, program: &Program) {
+        for unit in &program.units {
+            match unit {
+                ProgramUnit::Declaration(decl) | ProgramUnit::Export(ExportDecl::Declaration(decl)) => {
+                    match decl {
+                        Declaration::Function(func) => self.resolve_function(&func.params, false, &func.body),
+                        Declaration::Variable(var) => self.resolve_expression(&var.value),
+                        Declaration::Implementation(impl_block) => {
+                            for method in &impl_block.methods {
+                                // An explicit leading `self` is bound like the implicit one
+                                let params = match method.params.first() {
+                                    Some(first) if first.name == "self" => &method.params[1..],
+                                    _ => &method.params[..],
+                                };
+                                self.resolve_function(params, true, &method.body);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                ProgramUnit::Statement(stmt) => self.resolve_statement(stmt),
+                ProgramUnit::Expression(expr) => self.resolve_expression(expr),
+                _ => {}
+            }
+        }
+    }
+
+    /// Resolve a function or method body. Slots are numbered `self` first, then
+    /// the parameters in order, then the body's locals.
+    fn resolve_function(&mut self, params: &[Parameter], has_self: bool, body: &Block) {
+        self.functions.push(FunctionScope::new(false));
+        if has_self {
+            self.declare("self");
+        }
+        for param in params {
+            self.declare(&param.name);
+        }
+        self.hoist_block(body);
+        for default in params.iter().filter_map(|p| p.default_value.as_ref()) {
+            self.resolve_expression(default);
+        }
+        self.resolve_block(body);
+        self.functions.pop();
+    }
+
+    /// Resolve a match arm or except clause, whose pattern bindings and
+    /// declarations are local to it
+    fn resolve_clause(
+        &mut self,
+        span: &SourceSpan,
+        pattern: Option<&Pattern>,
+        guard: Option<&Expression>,
+        body: &Block,
+    ) {
+        self.push_scope();
+        let mut names = Vec::new();
+        if let Some(pattern) = pattern {
+            pattern_bindings(pattern, &mut names);
+        }
+        let mut slots = Vec::with_capacity(names.len());
+        for name in names {
+            self.declare(&name);
+            if let Some(slot) = self.lookup(&name) {
+                slots.push((name, slot.index));
+            }
+        }
+        self.resolution.patterns.insert(site(span), slots);
+
+        if let Some(guard) = guard {
+            self.hoist_expression(guard);
+        }
+        self.hoist_block(body);
+        if let Some(guard) = guard {
+            self.resolve_expression(guard);
+        }
+        self.resolve_block(body);
+        self.pop_scope();
+    }
+
+    /// Declare the names a block binds in the current scope: declarations,
+    /// loop variables, and assignments to names that are not already visible.
+    /// Running this before resolving lets a use refer to a variable that is
+    /// only assigned later, as in a loop.
+    fn hoist_block(&mut self, block: &Block) {
+        for stmt in &block.statements {
+            self.hoist_statement(stmt);
+        }
+    }
+
+    fn hoist_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Variable(var) => {
+                self.hoist_expression(&var.value);
+                self.declare(&var.name);
+            }
+            Statement::Expression(expr) => self.hoist_expression(expr),
+            Statement::If(if_stmt) => {
+                self.hoist_expression(&if_stmt.condition);
+                self.hoist_block(&if_stmt.then_block);
+                for elif in &if_stmt.elif_branches {
+                    self.hoist_expression(&elif.condition);
+                    self.hoist_block(&elif.block);
+                }
+                if let Some(else_block) = &if_stmt.else_block {
+                    self.hoist_block(else_block);
+                }
+            }
+            Statement::While(while_stmt) => {
+                self.hoist_expression(&while_stmt.condition);
+                self.hoist_block(&while_stmt.body);
+            }
+            Statement::DoWhile(do_while) => {
+                self.hoist_block(&do_while.body);
+                self.hoist_expression(&do_while.condition);
+            }
+            Statement::For(ForStatement::ForEach { var_name, iterable, body, .. }) => {
+                self.hoist_expression(iterable);
+                self.declare(var_name);
+                self.hoist_block(body);
+            }
+            Statement::For(ForStatement::CStyle { initializer, condition, increment, body, .. }) => {
+                if let Some(initializer) = initializer {
+                    self.hoist_statement(initializer);
+                }
+                for expr in condition.iter().chain(increment) {
+                    self.hoist_expression(expr);
+                }
+                self.hoist_block(body);
+            }
+            Statement::Match(match_stmt) => self.hoist_expression(&match_stmt.expression),
+            Statement::Return(ret) => {
+                if let Some(value) = &ret.value {
+                    self.hoist_expression(value);
+                }
+            }
+            Statement::Raise(raise) => self.hoist_expression(&raise.value),
+            Statement::Try(try_stmt) => self.hoist_block(&try_stmt.body),
+            Statement::Block(block) => self.hoist_block(block),
+            Statement::Break(_) | Statement::Continue(_) | Statement::Pass(_) => {}
+        }
+    }
+
+    /// Declare the names assigned within an expression. Closures and match
+    /// arms are left to be hoisted when they are resolved.
+    fn hoist_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Binary { left, operator, right, .. } => {
+                if let (true, Expression::Variable { name, .. }) = (operator.is_assignment(), left.as_ref()) {
+                    if self.lookup(name).is_none() && !self.globals.contains(name) {
+                        self.declare(name);
+                    }
+                }
+                self.hoist_expression(left);
+                self.hoist_expression(right);
+            }
+            _ => self.for_each_subexpression(expr, Self::hoist_expression),
+        }
+    }
+
+    fn resolve_block(&mut self, block: &Block) {
+        for stmt in &block.statements {
+            self.resolve_statement(stmt);
+        }
+    }
+
+    fn resolve_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Variable(var) => {
+                self.resolve_expression(&var.value);
+                self.record(&var.span, &var.name);
+            }
+            Statement::Expression(expr) => self.resolve_expression(expr),
+            Statement::If(if_stmt) => {
+                self.resolve_expression(&if_stmt.condition);
+                self.resolve_block(&if_stmt.then_block);
+                for elif in &if_stmt.elif_branches {
+                    self.resolve_expression(&elif.condition);
+                    self.resolve_block(&elif.block);
+                }
+                if let Some(else_block) = &if_stmt.else_block {
+                    self.resolve_block(else_block);
+                }
+            }
+            Statement::While(while_stmt) => {
+                self.resolve_expression(&while_stmt.condition);
+                self.resolve_block(&while_stmt.body);
+            }
+            Statement::DoWhile(do_while) => {
+                self.resolve_block(&do_while.body);
+                self.resolve_expression(&do_while.condition);
+            }
+            Statement::For(ForStatement::ForEach { var_name, iterable, body, span, .. }) => {
+                self.resolve_expression(iterable);
+                self.record(span, var_name);
+                self.resolve_block(body);
+            }
+            Statement::For(ForStatement::CStyle { initializer, condition, increment, body, .. }) => {
+                if let Some(initializer) = initializer {
+                    self.resolve_statement(initializer);
+                }
+                for expr in condition.iter().chain(increment) {
+                    self.resolve_expression(expr);
+                }
+                self.resolve_block(body);
+            }
+            Statement::Match(match_stmt) => {
+                self.resolve_expression(&match_stmt.expression);
+                for arm in &match_stmt.arms {
+                    self.resolve_clause(&arm.span, Some(&arm.pattern), arm.guard.as_ref(), &arm.body);
+                }
+            }
+            Statement::Return(ret) => {
+                if let Some(value) = &ret.value {
+                    self.resolve_expression(value);
+                }
+            }
+            Statement::Raise(raise) => self.resolve_expression(&raise.value),
+            Statement::Try(try_stmt) => {
+                self.resolve_block(&try_stmt.body);
+                for handler in &try_stmt.handlers {
+                    self.resolve_clause(&handler.span, handler.pattern.as_ref(), None, &handler.body);
+                }
+            }
+            Statement::Block(block) => self.resolve_block(block),
+            Statement::Break(_) | Statement::Continue(_) | Statement::Pass(_) => {}
+        }
+    }
+
+    fn resolve_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Variable { name, span } => self.record(span, name),
+            Expression::Lambda { params, body, .. } => {
+                // Closures get a frame of their own; the frames of the functions
+                // around them are reached through the depth of each slot
+                self.functions.push(FunctionScope::new(false));
+                for param in params {
+                    self.declare(&param.name);
+                }
+                self.hoist_expression(body);
+                self.resolve_expression(body);
+                self.functions.pop();
+            }
+            Expression::Match { expression, arms, .. } => {
+                self.resolve_expression(expression);
+                for arm in arms {
+                    self.resolve_clause(&arm.span, Some(&arm.pattern), arm.guard.as_ref(), &arm.body);
+                }
+            }
+            _ => self.for_each_subexpression(expr, Self::resolve_expression),
+        }
+    }
+
+    /// Apply `visit` to the expressions directly inside `expr`. Lambda bodies
+    /// and match arms are not visited.
+    fn for_each_subexpression(&mut self, expr: &Expression, mut visit: impl FnMut(&mut Self, &Expression)) {
+        match expr {
+            Expression::Literal(Literal::FStringInterpolation(parts)) => {
+                for part in parts {
+                    if let FStringPart::Expression(inner) = part {
+                        visit(self, inner);
+                    }
+                }
+            }
+            Expression::Literal(_) | Expression::Variable { .. } | Expression::Lambda { .. } => {}
+            Expression::Binary { left, right, .. } => {
+                visit(self, left);
+                visit(self, right);
+            }
+            Expression::Unary { operand, .. } => visit(self, operand),
+            Expression::Call { callee, args, named_args, .. } => {
+                visit(self, callee);
+                for arg in args {
+                    visit(self, arg);
+                }
+                for named_arg in named_args {
+                    visit(self, &named_arg.value);
+                }
+            }
+            Expression::Index { target, index, .. } => {
+                visit(self, target);
+                visit(self, index);
+            }
+            Expression::Member { target, .. } => visit(self, target),
+            Expression::Propagate { expression, .. }
+            | Expression::Spawn { expression, .. }
+            | Expression::Match { expression, .. } => visit(self, expression),
+            Expression::Range { start, end, .. } => {
+                visit(self, start);
+                visit(self, end);
+            }
+            Expression::If { condition, then_expr, else_expr, .. } => {
+                visit(self, condition);
+                visit(self, then_expr);
+                visit(self, else_expr);
+            }
+            Expression::StructLiteral { fields, .. } => {
+                for field in fields {
+                    visit(self, &field.value);
+                }
+            }
+            Expression::TupleLiteral { elements, .. }
+            | Expression::VecLiteral { elements, .. }
+            | Expression::SetLiteral { elements, .. } => {
+                for element in elements {
+                    visit(self, element);
+                }
+            }
+            Expression::MapLiteral { entries, .. } => {
+                for entry in entries {
+                    visit(self, &entry.key);
+                    visit(self, &entry.value);
+                }
+            }
+        }
+    }
+}
+
+/// Names a pattern may bind, matching what the pattern matcher produces. A bare
+/// name binds the value unless it turns out to be an enum variant at runtime.
+fn pattern_bindings(pattern: &Pattern, names: &mut Vec<String>) {
+    match pattern {
+        Pattern::Variable { name, .. } | Pattern::TraitCheck { name, .. } => names.push(name.clone()),
+        Pattern::Constructor { name, fields, .. } if fields.is_empty() => names.push(name.clone()),
+        Pattern::Constructor { fields, .. } => {
+            for field in fields {
+                pattern_bindings(field, names);
+            }
+        }
+        Pattern::Struct { fields, .. } => {
+            for (_, field) in fields {
+                pattern_bindings(field, names);
+            }
+        }
+        Pattern::Tuple { patterns, .. } => {
+            for inner in patterns {
+                pattern_bindings(inner, names);
+            }
+        }
+        Pattern::Wildcard(_) | Pattern::Literal(_) => {}
+    }
+}
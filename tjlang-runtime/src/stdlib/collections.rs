@@ -8,6 +8,7 @@
 //! - Collection algorithms
 //! - Data structure implementations
 
+use crate::stdlib::random::Generator;
 use crate::values::Value;
 use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
 
@@ -100,10 +101,8 @@ impl COLLECTIONS {
         vec.sort_by(compare);
     }
 
-    pub fn array_shuffle<T>(vec: &mut Vec<T>) {
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        vec.shuffle(&mut rng);
+    pub fn array_shuffle<T>(vec: &mut [T], random: &Generator) {
+        random.shuffle(vec);
     }
 
     pub fn array_unique<T: Clone + Eq + std::hash::Hash>(vec: &mut Vec<T>) {
@@ -541,10 +540,8 @@ impl COLLECTIONS {
     pub fn reverse<T>(vec: &mut Vec<T>) {
         vec.reverse();
     }
-    pub fn shuffle<T>(vec: &mut Vec<T>) {
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        vec.shuffle(&mut rng);
+    pub fn shuffle<T>(vec: &mut [T], random: &Generator) {
+        random.shuffle(vec);
     }
 
     pub fn rotate_left<T>(vec: &mut Vec<T>, mid: usize) {
@@ -559,7 +556,10 @@ impl COLLECTIONS {
         F: FnMut(&T) -> bool,
     {
         let (left, right): (Vec<T>, Vec<T>) = vec.drain(..).partition(|x| predicate(x));
-        left.len()
+        let count = left.len();
+        vec.extend(left);
+        vec.extend(right);
+        count
     }
 
     pub fn dedup<T: PartialEq>(vec: &mut Vec<T>) {
@@ -863,6 +863,10 @@ impl<K: std::hash::Hash + Eq + Clone, V: Clone, F: Fn() -> V> DefaultDict<K, V,
     pub fn is_empty(&self) -> bool {
         self.data.is_empty()
     }
+
+    pub fn into_map(self) -> HashMap<K, V> {
+        self.data
+    }
 }
 
 /// ChainMap - Chain multiple dictionaries (like Python's ChainMap)
@@ -903,6 +907,10 @@ impl<K: std::hash::Hash + Eq + Clone, V: Clone> ChainMap<K, V> {
     pub fn is_empty(&self) -> bool {
         self.maps.is_empty()
     }
+
+    pub fn maps(&self) -> &[HashMap<K, V>] {
+        &self.maps
+    }
 }
 
 /// NamedTuple - Tuple with named fields (like Python's namedtuple)
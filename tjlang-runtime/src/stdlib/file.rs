@@ -10,6 +10,7 @@
 //! - Archive operations
 //! - File searching and filtering
 
+use crate::stdlib::regex::Regex;
 use crate::values::Value;
 use std::fs::{self, File, Metadata, OpenOptions, Permissions};
 use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
@@ -209,39 +210,42 @@ impl FILE {
         Ok(files)
     }
 
-    /// Find files matching pattern
+    /// Find the entries of a directory whose names match a glob such as `*.txt`
     pub fn find_files(path: &str, pattern: &str) -> Result<Vec<String>, String> {
-        // TODO: Implement glob pattern matching
+        let glob = Regex::from_glob(pattern)?;
         let entries = Self::list_dir(path)?;
-        let mut matches = Vec::new();
-        for entry in entries {
-            if entry.contains(pattern) {
-                matches.push(entry);
-            }
-        }
-        Ok(matches)
+        Ok(entries.into_iter().filter(|entry| glob.is_match(entry)).collect())
     }
 
-    /// Find files recursively
+    /// Find files recursively. A glob containing `/` is matched against the path
+    /// relative to `path`, as in `src/**/*.rs`; any other glob against file names.
     pub fn find_files_recursive(path: &str, pattern: &str) -> Result<Vec<String>, String> {
+        let glob = Regex::from_glob(pattern)?;
         let mut matches = Vec::new();
-        Self::find_files_recursive_helper(path, pattern, &mut matches)?;
+        Self::find_files_recursive_helper(path, "", &glob, pattern.contains('/'), &mut matches)?;
         Ok(matches)
     }
 
     fn find_files_recursive_helper(
         path: &str,
-        pattern: &str,
+        relative: &str,
+        glob: &Regex,
+        match_paths: bool,
         matches: &mut Vec<String>,
     ) -> Result<(), String> {
         let entries = Self::list_dir(path)?;
         for entry in entries {
             let full_path = Path::new(path).join(&entry);
             let full_path_str = full_path.to_string_lossy().to_string();
+            let relative_path = if relative.is_empty() {
+                entry.clone()
+            } else {
+                format!("{}/{}", relative, entry)
+            };
 
             if Self::is_dir(&full_path_str) {
-                Self::find_files_recursive_helper(&full_path_str, pattern, matches)?;
-            } else if entry.contains(pattern) {
+                Self::find_files_recursive_helper(&full_path_str, &relative_path, glob, match_paths, matches)?;
+            } else if glob.is_match(if match_paths { &relative_path } else { &entry }) {
                 matches.push(full_path_str);
             }
         }
@@ -44,7 +44,7 @@ impl IO {
             match arg {
                 Value::Vec(vec) => {
                     // Unpack array elements
-                    for item in vec {
+                    for item in vec.read().iter() {
                         flattened_args.push(item.clone());
                     }
                 }
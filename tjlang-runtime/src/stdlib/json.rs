@@ -0,0 +1,335 @@
+//! JSON Module - JSON parsing and serialization
+//!
+//! Provides JSON functionality including:
+//! - Parsing JSON text into TJLang values and back
+//! - Compact and pretty-printed output
+//! - Typed decoding into declared structs, with errors naming the field
+//! - Streaming line-delimited JSON (one document per line) from files
+//!
+//! JSON objects become maps with string keys, arrays become vectors, numbers
+//! become ints or floats and `null` becomes `None`.
+
+use crate::bigint::BigInt;
+use crate::interpreter::format_type;
+use crate::values::Value;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use tjlang_ast::{PrimitiveType, Type};
+
+/// How deeply values may nest when converted to JSON, the same limit parsing
+/// has. Deeper values are almost certainly cyclic.
+const MAX_DEPTH: usize = 128;
+
+/// Declared types that `JSON::decode` builds values of
+pub trait TypeDeclarations {
+    /// Field names and types of a declared struct
+    fn struct_fields(&self, name: &str) -> Option<Vec<(String, Type)>>;
+
+    /// The type a type alias stands for
+    fn type_alias(&self, name: &str) -> Option<Type>;
+}
+
+/// JSON module for parsing and serialization
+pub struct JSON;
+
+impl JSON {
+    /// Parse JSON text into a value
+    pub fn parse(text: &str) -> Result<Value, String> {
+        let json: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+        Ok(from_json(json))
+    }
+
+    /// Parse a file containing one JSON document
+    pub fn parse_file(path: &str) -> Result<Value, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Serialize a value as compact JSON
+    pub fn stringify(value: &Value) -> Result<String, String> {
+        serde_json::to_string(&to_json(value, 0)?).map_err(|e| e.to_string())
+    }
+
+    /// Serialize a value as JSON indented by `indent` spaces per level
+    pub fn stringify_pretty(value: &Value, indent: usize) -> Result<String, String> {
+        let indent = " ".repeat(indent);
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        let mut output = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+        to_json(value, 0)?
+            .serialize(&mut serializer)
+            .map_err(|e| e.to_string())?;
+        String::from_utf8(output).map_err(|e| e.to_string())
+    }
+
+    /// Parse JSON text into a value of type `ty`. Struct fields missing from the
+    /// text must have an Option type; keys the struct does not declare are ignored.
+    pub fn decode(text: &str, ty: &Type, types: &dyn TypeDeclarations) -> Result<Value, String> {
+        let json: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+        decode_value(json, ty, &format_type(ty), types)
+    }
+
+    /// Read a line-delimited JSON file one document at a time. Blank lines are skipped.
+    pub fn read_lines(path: &str) -> Result<JsonLines, String> {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        Ok(JsonLines {
+            path: path.to_string(),
+            lines: BufReader::new(file).lines(),
+            line_number: 0,
+        })
+    }
+}
+
+/// The documents of a line-delimited JSON file, read as they are needed
+pub struct JsonLines {
+    path: String,
+    lines: Lines<BufReader<File>>,
+    line_number: usize,
+}
+
+impl Iterator for JsonLines {
+    type Item = Result<Value, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(format!("{}: {}", self.path, e))),
+            };
+            self.line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(
+                JSON::parse(&line).map_err(|e| format!("{}:{}: {}", self.path, self.line_number, e)),
+            );
+        }
+    }
+}
+
+/// Convert parsed JSON into a value
+fn from_json(json: serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::None,
+        serde_json::Value::Bool(b) => Value::Bool(b),
+        serde_json::Value::Number(n) => {
+            // Numbers keep their source text, so integers of any size stay exact
+            if let Some(i) = n.as_i64() {
+                Value::Int(i)
+            } else if let Ok(i) = n.to_string().parse::<BigInt>() {
+                Value::BigInt(i)
+            } else {
+                Value::Float(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        serde_json::Value::String(s) => Value::String(s),
+        serde_json::Value::Array(items) => Value::vec(items.into_iter().map(from_json).collect()),
+        serde_json::Value::Object(entries) => Value::map(
+            entries
+                .into_iter()
+                .map(|(key, value)| (Value::String(key), from_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Convert a value to JSON. Structs become objects, `Some(x)` becomes `x`, and
+/// other enum variants become `"Variant"` or `{"Variant": fields}`.
+fn to_json(value: &Value, depth: usize) -> Result<serde_json::Value, String> {
+    if depth > MAX_DEPTH {
+        return Err("Value is nested too deeply to convert to JSON; is it cyclic?".to_string());
+    }
+    let array = |items: &mut dyn Iterator<Item = &Value>| -> Result<serde_json::Value, String> {
+        items
+            .map(|item| to_json(item, depth + 1))
+            .collect::<Result<Vec<_>, _>>()
+            .map(serde_json::Value::Array)
+    };
+
+    Ok(match value {
+        Value::None => serde_json::Value::Null,
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Int(i) => serde_json::Value::from(*i),
+        Value::BigInt(i) => match i.to_string().parse::<serde_json::Number>() {
+            Ok(n) => serde_json::Value::Number(n),
+            Err(_) => return Err(format!("int {} is too large for JSON", i)),
+        },
+        Value::Float(f) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| format!("float {} cannot be represented in JSON", f))?,
+        Value::String(s) => serde_json::Value::String(s.clone()),
+        Value::Tuple(items) => array(&mut items.iter())?,
+        Value::Vec(items) => array(&mut items.read().iter())?,
+        Value::Set(items) => array(&mut items.read().iter())?,
+        Value::Map(entries) => {
+            let mut object = serde_json::Map::new();
+            for (key, value) in entries.read().iter() {
+                object.insert(object_key(key)?, to_json(value, depth + 1)?);
+            }
+            serde_json::Value::Object(object)
+        }
+        Value::Struct { fields, .. } => {
+            let mut object = serde_json::Map::new();
+            for (name, value) in fields.read().iter() {
+                object.insert(name.clone(), to_json(value, depth + 1)?);
+            }
+            serde_json::Value::Object(object)
+        }
+        Value::Enum { name, variant, fields } => {
+            if name == "Option" && variant == "Some" && fields.len() == 1 {
+                return to_json(&fields[0], depth + 1);
+            }
+            let payload = match fields.as_slice() {
+                [] => return Ok(serde_json::Value::String(variant.clone())),
+                [field] => to_json(field, depth + 1)?,
+                fields => array(&mut fields.iter())?,
+            };
+            let mut object = serde_json::Map::new();
+            object.insert(variant.clone(), payload);
+            serde_json::Value::Object(object)
+        }
+        Value::Union { value, .. } => to_json(value, depth)?,
+        other => {
+            return Err(format!(
+                "Cannot convert {} to JSON",
+                format_type(&other.get_type())
+            ))
+        }
+    })
+}
+
+/// JSON object keys are strings; numbers and bools are written as text
+fn object_key(key: &Value) -> Result<String, String> {
+    match key.unwrap_union() {
+        Value::String(s) => Ok(s.clone()),
+        key @ (Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Bool(_)) => Ok(key.to_string()),
+        other => Err(format!(
+            "JSON object keys must be strings, numbers or bools, got {}",
+            format_type(&other.get_type())
+        )),
+    }
+}
+
+/// Short description of a JSON value for decoding errors
+fn describe(json: &serde_json::Value) -> String {
+    match json {
+        serde_json::Value::Null => "null".to_string(),
+        serde_json::Value::Bool(b) => b.to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => format!("{:?}", s),
+        serde_json::Value::Array(_) => "an array".to_string(),
+        serde_json::Value::Object(_) => "an object".to_string(),
+    }
+}
+
+/// Build a value of type `ty` from JSON. `path` names the part of the document
+/// being decoded, such as `Line.points[1].x`.
+fn decode_value(
+    json: serde_json::Value,
+    ty: &Type,
+    path: &str,
+    types: &dyn TypeDeclarations,
+) -> Result<Value, String> {
+    let mismatch = |json: &serde_json::Value| {
+        format!("{}: expected {}, got {}", path, format_type(ty), describe(json))
+    };
+
+    match (ty, json) {
+        (Type::Primitive(PrimitiveType::Any), json) => Ok(from_json(json)),
+        (Type::Primitive(PrimitiveType::Int), serde_json::Value::Number(n)) if !n.is_f64() => {
+            Ok(from_json(serde_json::Value::Number(n)))
+        }
+        (Type::Primitive(PrimitiveType::Float), serde_json::Value::Number(n)) => {
+            Ok(Value::Float(n.as_f64().unwrap_or(f64::NAN)))
+        }
+        (Type::Primitive(PrimitiveType::Bool), serde_json::Value::Bool(b)) => Ok(Value::Bool(b)),
+        (Type::Primitive(PrimitiveType::Str), serde_json::Value::String(s)) => Ok(Value::String(s)),
+        (Type::Option { .. }, serde_json::Value::Null) => Ok(Value::None),
+        (Type::Option { inner, .. }, json) => Ok(Value::some(decode_value(json, inner, path, types)?)),
+        (Type::Vec { element_type, .. }, serde_json::Value::Array(items)) => {
+            let items = decode_items(items, element_type, path, types)?;
+            Ok(Value::vec(items))
+        }
+        (Type::Set { element_type, .. }, serde_json::Value::Array(items)) => {
+            let items = decode_items(items, element_type, path, types)?;
+            Ok(Value::set(items.into_iter().collect::<HashSet<_>>()))
+        }
+        (Type::Tuple { types: item_types, .. }, serde_json::Value::Array(items))
+            if items.len() == item_types.len() =>
+        {
+            let items = items
+                .into_iter()
+                .zip(item_types)
+                .enumerate()
+                .map(|(index, (item, ty))| decode_value(item, ty, &format!("{}[{}]", path, index), types))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::Tuple(items))
+        }
+        (Type::Map { key_type, value_type, .. }, serde_json::Value::Object(entries)) => {
+            let mut map = HashMap::new();
+            for (key, value) in entries {
+                let entry_path = format!("{}[{:?}]", path, key);
+                let key = decode_key(key, key_type, &entry_path)?;
+                map.insert(key, decode_value(value, value_type, &entry_path, types)?);
+            }
+            Ok(Value::map(map))
+        }
+        (Type::Union { types: members, .. }, json) => members
+            .iter()
+            .find_map(|member| decode_value(json.clone(), member, path, types).ok())
+            .ok_or_else(|| mismatch(&json)),
+        (Type::Identifier(name), json) => {
+            if let Some(alias) = types.type_alias(name) {
+                return decode_value(json, &alias, path, types);
+            }
+            let Some(fields) = types.struct_fields(name) else {
+                return Err(format!("{}: cannot decode JSON into type {}", path, name));
+            };
+            let serde_json::Value::Object(mut entries) = json else {
+                return Err(mismatch(&json));
+            };
+            let mut values = HashMap::new();
+            for (field, field_type) in fields {
+                let field_path = format!("{}.{}", path, field);
+                let value = match entries.remove(&field) {
+                    Some(json) => decode_value(json, &field_type, &field_path, types)?,
+                    None if matches!(field_type, Type::Option { .. }) => Value::None,
+                    None => return Err(format!("{}: missing field of type {}", field_path, format_type(&field_type))),
+                };
+                values.insert(field, value);
+            }
+            Ok(Value::new_struct(name.clone(), values))
+        }
+        (_, json) => Err(mismatch(&json)),
+    }
+}
+
+/// Decode the elements of a JSON array
+fn decode_items(
+    items: Vec<serde_json::Value>,
+    element_type: &Type,
+    path: &str,
+    types: &dyn TypeDeclarations,
+) -> Result<Vec<Value>, String> {
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| decode_value(item, element_type, &format!("{}[{}]", path, index), types))
+        .collect()
+}
+
+/// Decode a JSON object key, which is always a string, as a map key of type `ty`
+fn decode_key(key: String, ty: &Type, path: &str) -> Result<Value, String> {
+    match ty {
+        Type::Primitive(PrimitiveType::Str | PrimitiveType::Any) => Ok(Value::String(key)),
+        Type::Primitive(PrimitiveType::Int) => key
+            .parse::<i64>()
+            .map(Value::Int)
+            .map_err(|_| format!("{}: expected an int key, got {:?}", path, key)),
+        _ => Err(format!("{}: JSON object keys cannot be decoded as {}", path, format_type(ty))),
+    }
+}
@@ -12,6 +12,7 @@
 //! - Optimization algorithms
 //! - Numerical analysis
 
+use crate::bigint::BigInt;
 use crate::values::Value;
 use std::f64::consts::*;
 
@@ -266,20 +267,19 @@ impl MATH {
         true
     }
 
-    pub fn factorial(n: u64) -> u64 {
-        if n <= 1 {
-            1
-        } else {
-            n * Self::factorial(n - 1)
-        }
+    /// Exact factorial, however large
+    pub fn factorial(n: u64) -> BigInt {
+        (2..=n).fold(BigInt::from(1u64), |product, i| &product * &BigInt::from(i))
     }
 
-    pub fn fibonacci(n: u64) -> u64 {
-        if n <= 1 {
-            n
-        } else {
-            Self::fibonacci(n - 1) + Self::fibonacci(n - 2)
+    /// Exact `n`th Fibonacci number, however large
+    pub fn fibonacci(n: u64) -> BigInt {
+        let (mut current, mut next) = (BigInt::from(0u64), BigInt::from(1u64));
+        for _ in 0..n {
+            let sum = &current + &next;
+            current = std::mem::replace(&mut next, sum);
         }
+        current
     }
 
     // Calculus
@@ -337,33 +337,33 @@ impl MATH {
     where
         F: Fn(f64) -> f64,
     {
-        let phi = (1.0 + 5.0_f64.sqrt()) / 2.0;
-        let resphi = 2.0 - phi;
+        let invphi = (5.0_f64.sqrt() - 1.0) / 2.0;
 
-        let mut x1 = a + resphi * (b - a);
-        let mut x2 = a + resphi * (b - a);
+        let (mut a, mut b) = (a, b);
+        let mut x1 = b - invphi * (b - a);
+        let mut x2 = a + invphi * (b - a);
         let mut f1 = f(x1);
         let mut f2 = f(x2);
 
+        // Keep the bracket around the lower of the two probes, reusing the
+        // probe that stays inside it
         while (b - a).abs() > tol {
             if f1 < f2 {
-                let b = x2;
+                b = x2;
                 x2 = x1;
-                x2 = a + resphi * (b - a);
-                f2 = f(x2);
+                f2 = f1;
+                x1 = b - invphi * (b - a);
+                f1 = f(x1);
             } else {
-                let a = x1;
+                a = x1;
                 x1 = x2;
-                x1 = a + resphi * (b - a);
-                f1 = f(x1);
+                f1 = f2;
+                x2 = a + invphi * (b - a);
+                f2 = f(x2);
             }
         }
 
-        if f1 < f2 {
-            x1
-        } else {
-            x2
-        }
+        (a + b) / 2.0
     }
 
     // Numerical analysis
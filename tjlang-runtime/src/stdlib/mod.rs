@@ -4,8 +4,11 @@
 //! - IO operations (print, input, formatting)
 //! - File operations (read, write, copy, move, delete, rename)
 //! - Math functions (trigonometry, logarithms, statistics, etc.)
+//! - Random numbers, with seedable generators
 //! - String operations (manipulation, regex, encoding)
 //! - Collections (arrays, maps, sets, iterators)
+//! - JSON parsing and serialization
+//! - Regular expressions and glob patterns
 //! - Time and date operations
 //! - Network operations
 //! - System operations
@@ -19,7 +22,10 @@ pub mod collections;
 pub mod error;
 pub mod file;
 pub mod io;
+pub mod json;
 pub mod math;
+pub mod random;
+pub mod regex;
 pub mod string;
 pub mod testing;
 pub mod time;
@@ -236,7 +242,13 @@ pub use collections::*;
 pub use error::*;
 pub use file::*;
 pub use io::*;
+pub use json::*;
 pub use math::*;
+pub use random::*;
+pub use regex::*;
 pub use string::*;
 pub use testing::*;
 pub use time::*;
+
+#[cfg(test)]
+mod tests;
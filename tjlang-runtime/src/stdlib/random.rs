@@ -0,0 +1,132 @@
+//! RANDOM Module - Random numbers
+//!
+//! Provides randomness including:
+//! - Uniform ints and floats in a range
+//! - Normally distributed floats
+//! - Choosing, sampling and shuffling collection elements
+//! - Version 4 UUIDs
+//! - Independent generators with their own seeds
+//!
+//! Every program has a default generator, shared with the tasks it spawns, that
+//! the `RANDOM` functions and collection shuffles draw from. It is seeded from
+//! the operating system unless `RANDOM.seed` or `tjlang run --seed` fixes the
+//! seed, in which case every run produces the same numbers.
+
+use crate::values::Value;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A random number generator. Copies share the generator's state.
+#[derive(Debug, Clone)]
+pub struct Generator(Arc<Mutex<StdRng>>);
+
+impl Generator {
+    /// A generator seeded from the operating system
+    pub fn from_entropy() -> Self {
+        Generator(Arc::new(Mutex::new(StdRng::from_entropy())))
+    }
+
+    /// A generator that always produces the same numbers for the same seed
+    pub fn from_seed(seed: u64) -> Self {
+        Generator(Arc::new(Mutex::new(StdRng::seed_from_u64(seed))))
+    }
+
+    /// A new generator seeded from this one, so that it is repeatable when this is
+    fn split(&self) -> Self {
+        Generator::from_seed(self.rng().gen())
+    }
+
+    /// Whether both are the same generator
+    pub fn ptr_eq(&self, other: &Generator) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    pub fn as_ptr(&self) -> usize {
+        Arc::as_ptr(&self.0) as usize
+    }
+
+    fn rng(&self) -> MutexGuard<'_, StdRng> {
+        // The generator's state stays valid even if a thread panicked holding it
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Restart the sequence of numbers from `seed`
+    pub fn reseed(&self, seed: u64) {
+        *self.rng() = StdRng::seed_from_u64(seed);
+    }
+
+    /// A uniform int between `lo` and `hi`, both included
+    pub fn int(&self, lo: i64, hi: i64) -> Result<i64, String> {
+        if lo > hi {
+            return Err(format!("Empty range: {} is greater than {}", lo, hi));
+        }
+        Ok(self.rng().gen_range(lo..=hi))
+    }
+
+    /// A uniform float at least `lo` and less than `hi`
+    pub fn float(&self, lo: f64, hi: f64) -> Result<f64, String> {
+        if lo >= hi || !(hi - lo).is_finite() {
+            return Err(format!("Invalid range: {} to {}", lo, hi));
+        }
+        Ok(self.rng().gen_range(lo..hi))
+    }
+
+    /// A normally distributed float, by the Box-Muller transform
+    pub fn gaussian(&self, mean: f64, std_dev: f64) -> Result<f64, String> {
+        if std_dev < 0.0 || !std_dev.is_finite() {
+            return Err(format!("Standard deviation must be non-negative, got {}", std_dev));
+        }
+        let mut rng = self.rng();
+        // 1 - u is in (0, 1], so its logarithm is finite
+        let u: f64 = 1.0 - rng.gen::<f64>();
+        let v: f64 = rng.gen();
+        let z = (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos();
+        Ok(mean + std_dev * z)
+    }
+
+    /// One element chosen uniformly
+    pub fn choice(&self, items: &[Value]) -> Result<Value, String> {
+        items
+            .choose(&mut *self.rng())
+            .cloned()
+            .ok_or_else(|| "Cannot choose from an empty collection".to_string())
+    }
+
+    /// `k` distinct elements in random order
+    pub fn sample(&self, items: &[Value], k: usize) -> Result<Vec<Value>, String> {
+        if k > items.len() {
+            return Err(format!("Cannot sample {} elements from {}", k, items.len()));
+        }
+        Ok(items.choose_multiple(&mut *self.rng(), k).cloned().collect())
+    }
+
+    /// Shuffle in place
+    pub fn shuffle<T>(&self, items: &mut [T]) {
+        items.shuffle(&mut *self.rng());
+    }
+
+    /// A random (version 4) UUID, such as `0b6a3f8e-5c2d-4e71-9a04-6f1d2c3b4a59`
+    pub fn uuid4(&self) -> String {
+        let mut bytes: [u8; 16] = self.rng().gen();
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+    }
+}
+
+/// RANDOM module for random numbers
+pub struct RANDOM;
+
+impl RANDOM {
+    /// A new generator with its own sequence of numbers. Without a seed it is
+    /// seeded from `parent`, so it is repeatable whenever `parent` is.
+    pub fn generator(parent: &Generator, seed: Option<i64>) -> Generator {
+        match seed {
+            Some(seed) => Generator::from_seed(seed as u64),
+            None => parent.split(),
+        }
+    }
+}
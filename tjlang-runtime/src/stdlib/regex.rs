@@ -0,0 +1,233 @@
+//! REGEX Module - Regular expressions
+//!
+//! Provides pattern matching including:
+//! - Compiling patterns into reusable `Regex` values
+//! - Searching for the first or every match
+//! - Capture groups, by number or by name
+//! - Replacement with `$1` and `${name}` backreferences
+//! - Splitting text on a pattern
+//! - Glob patterns for matching file names
+//!
+//! Patterns are compiled by Rust's `regex` crate and use its syntax. Matching is leftmost-first,
+//! as in Perl, and takes time linear in the length of the text for any pattern.
+//! Positions are byte offsets, as in `STRING`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A compiled regular expression
+#[derive(Debug)]
+pub struct Regex {
+    regex: ::regex::Regex,
+}
+
+impl Regex {
+    /// Compile a pattern
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let regex = ::regex::Regex::new(pattern).map_err(|e| format!("Invalid regex: {}", e))?;
+        Ok(Regex { regex })
+    }
+
+    /// Compile a glob that must match the whole of a file name. `*` matches any
+    /// run of characters except `/`, `**` also crosses directories, `?` matches
+    /// one character, `[abc]` and `[!abc]` match one character from a set and
+    /// `{a,b}` matches either alternative.
+    pub fn from_glob(glob: &str) -> Result<Self, String> {
+        let mut pattern = String::from("^");
+        let mut chars = glob.chars().peekable();
+        let mut alternatives = 0;
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        pattern.push_str("(?:.*/)?");
+                    } else {
+                        pattern.push_str(".*");
+                    }
+                }
+                '*' => pattern.push_str("[^/]*"),
+                '?' => pattern.push_str("[^/]"),
+                '[' => {
+                    let class: String = chars.clone().take_while(|&c| c != ']').collect();
+                    if class.is_empty() || chars.clone().nth(class.chars().count()).is_none() {
+                        pattern.push_str(r"\[");
+                        continue;
+                    }
+                    chars.nth(class.chars().count());
+                    let (negated, members) = match class.strip_prefix('!') {
+                        Some(members) => (true, members),
+                        None => (false, class.as_str()),
+                    };
+                    pattern.push('[');
+                    if negated {
+                        pattern.push('^');
+                    }
+                    for member in members.chars() {
+                        if matches!(member, '\\' | '[' | '&' | '~' | '^') {
+                            pattern.push('\\');
+                        }
+                        pattern.push(member);
+                    }
+                    pattern.push(']');
+                }
+                '{' => {
+                    alternatives += 1;
+                    pattern.push_str("(?:");
+                }
+                ',' if alternatives > 0 => pattern.push('|'),
+                '}' if alternatives > 0 => {
+                    alternatives -= 1;
+                    pattern.push(')');
+                }
+                c => pattern.push_str(&::regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        if alternatives > 0 {
+            return Err(format!("Invalid glob '{}': unclosed '{{'", glob));
+        }
+        pattern.push('$');
+        Regex::new(&pattern).map_err(|e| format!("Invalid glob '{}': {}", glob, e))
+    }
+
+    /// The pattern this was compiled from
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    /// Whether the pattern matches anywhere in `text`
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// The first match in `text`
+    pub fn find<'t>(&self, text: &'t str) -> Option<&'t str> {
+        self.regex.find(text).map(|found| found.as_str())
+    }
+
+    /// The first match in `text` and its capture groups
+    pub fn captures<'r, 't>(&'r self, text: &'t str) -> Option<Captures<'r, 't>> {
+        self.regex.captures(text).map(|captures| Captures { captures, regex: self })
+    }
+
+    /// Every non-overlapping match in `text`, from left to right
+    pub fn captures_iter<'r, 't>(&'r self, text: &'t str) -> impl Iterator<Item = Captures<'r, 't>> {
+        self.regex.captures_iter(text).map(move |captures| Captures { captures, regex: self })
+    }
+
+    /// Replace every match, expanding `$1`, `${1}`, `$name` and `${name}` in
+    /// `replacement` to the text of that group; `$$` is a literal `$`
+    pub fn replace_all(&self, text: &str, replacement: &str) -> String {
+        self.regex.replace_all(text, replacement).into_owned()
+    }
+
+    /// The parts of `text` between matches
+    pub fn split(&self, text: &str) -> Vec<String> {
+        self.regex.split(text).map(str::to_string).collect()
+    }
+}
+
+/// A match and the text of its capture groups
+pub struct Captures<'r, 't> {
+    captures: ::regex::Captures<'t>,
+    regex: &'r Regex,
+}
+
+impl<'t> Captures<'_, 't> {
+    /// The text of group `index`, if it took part in the match
+    pub fn get(&self, index: usize) -> Option<&'t str> {
+        self.captures.get(index).map(|group| group.as_str())
+    }
+
+    /// The text of the group called `name`
+    pub fn name(&self, name: &str) -> Option<&'t str> {
+        self.captures.name(name).map(|group| group.as_str())
+    }
+
+    /// Byte range of the whole match
+    pub fn range(&self) -> (usize, usize) {
+        self.captures.get(0).map_or((0, 0), |whole| (whole.start(), whole.end()))
+    }
+
+    /// The groups that took part in the match, keyed by name, or by number for
+    /// groups without one; `"0"` is the whole match
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.regex
+            .regex
+            .capture_names()
+            .enumerate()
+            .filter_map(|(index, name)| {
+                let key = name.map_or_else(|| index.to_string(), str::to_string);
+                self.get(index).map(|text| (key, text.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// A pattern argument: source text, compiled for the call, or a `Regex` value
+/// compiled once with `REGEX.compile`
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Source(String),
+    Compiled(Arc<Regex>),
+}
+
+impl Pattern {
+    pub fn regex(&self) -> Result<Arc<Regex>, String> {
+        match self {
+            Pattern::Source(pattern) => Regex::new(pattern).map(Arc::new),
+            Pattern::Compiled(regex) => Ok(regex.clone()),
+        }
+    }
+}
+
+/// REGEX module for pattern matching
+pub struct REGEX;
+
+impl REGEX {
+    /// Compile a pattern into a reusable regex
+    pub fn compile(pattern: &str) -> Result<Arc<Regex>, String> {
+        Regex::new(pattern).map(Arc::new)
+    }
+
+    /// Whether the pattern matches anywhere in the text
+    pub fn is_match(pattern: &Pattern, text: &str) -> Result<bool, String> {
+        Ok(pattern.regex()?.is_match(text))
+    }
+
+    /// The first match
+    pub fn find(pattern: &Pattern, text: &str) -> Result<Option<String>, String> {
+        Ok(pattern.regex()?.find(text).map(str::to_string))
+    }
+
+    /// Every non-overlapping match
+    pub fn find_all(pattern: &Pattern, text: &str) -> Result<Vec<String>, String> {
+        let regex = pattern.regex()?;
+        let matches = regex
+            .captures_iter(text)
+            .filter_map(|captures| captures.get(0).map(str::to_string))
+            .collect();
+        Ok(matches)
+    }
+
+    /// The capture groups of the first match, keyed by name or number
+    pub fn captures(pattern: &Pattern, text: &str) -> Result<Option<HashMap<String, String>>, String> {
+        Ok(pattern.regex()?.captures(text).map(|captures| captures.to_map()))
+    }
+
+    /// Replace every match, expanding `$1` and `${name}` group references
+    pub fn replace_all(pattern: &Pattern, text: &str, replacement: &str) -> Result<String, String> {
+        Ok(pattern.regex()?.replace_all(text, replacement))
+    }
+
+    /// Split the text on every match
+    pub fn split(pattern: &Pattern, text: &str) -> Result<Vec<String>, String> {
+        Ok(pattern.regex()?.split(text))
+    }
+
+    /// Escape the characters that have a meaning in patterns
+    pub fn escape(text: &str) -> String {
+        ::regex::escape(text)
+    }
+}
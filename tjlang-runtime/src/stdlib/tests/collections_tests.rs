@@ -3,6 +3,7 @@
 #[cfg(test)]
 mod tests {
     use crate::stdlib::collections::*;
+    use crate::stdlib::random::Generator;
     use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
     #[test]
@@ -147,7 +148,7 @@ mod tests {
     fn test_array_shuffle() {
         let mut vec = vec![1, 2, 3, 4, 5];
         let original = vec.clone();
-        COLLECTIONS::array_shuffle(&mut vec);
+        COLLECTIONS::array_shuffle(&mut vec, &Generator::from_seed(0));
         // The shuffled array should have the same elements but potentially different order
         assert_eq!(vec.len(), original.len());
     }
@@ -592,7 +593,7 @@ mod tests {
     fn test_shuffle() {
         let mut vec = vec![1, 2, 3, 4, 5];
         let original = vec.clone();
-        COLLECTIONS::shuffle(&mut vec);
+        COLLECTIONS::shuffle(&mut vec, &Generator::from_seed(0));
         // The shuffled array should have the same elements but potentially different order
         assert_eq!(vec.len(), original.len());
     }
@@ -616,6 +617,7 @@ mod tests {
         let mut vec = vec![1, 2, 3, 4, 5];
         let count = COLLECTIONS::partition(&mut vec, |x| x % 2 == 0);
         assert_eq!(count, 2);
+        assert_eq!(vec, vec![2, 4, 1, 3, 5]);
     }
 
     #[test]
@@ -369,6 +369,17 @@ mod tests {
         assert_eq!(result, 55);
     }
 
+    #[test]
+    fn test_factorial_and_fibonacci_are_exact() {
+        assert_eq!(MATH::factorial(0), 1);
+        assert_eq!(
+            MATH::factorial(50).to_string(),
+            "30414093201713378043612608166064768844377641568960512000000000000"
+        );
+        assert_eq!(MATH::fibonacci(0), 0);
+        assert_eq!(MATH::fibonacci(100).to_string(), "354224848179261915075");
+    }
+
     #[test]
     fn test_derivative() {
         // Test derivative of x^2 at x=2
@@ -384,7 +395,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore]
     fn test_golden_section_search() {
         // Test finding minimum of x^2
         let result = MATH::golden_section_search(|x| x * x, -10.0, 10.0, 0.001);
@@ -12,7 +12,7 @@ mod tests {
     fn create_test_values() -> (Value, Value, Value, Value, Value) {
         (
             Value::Int(42),
-            Value::Float(3.14),
+            Value::Float(3.25),
             Value::Bool(true),
             Value::String("Hello World".to_string()),
             Value::None,
@@ -36,7 +36,7 @@ mod tests {
         let result = get_primitive_method(&float_val, "to_string").unwrap();
         assert!(matches!(result, Value::String(_)));
         if let Value::String(s) = result {
-            assert_eq!(s, "3.14");
+            assert_eq!(s, "3.25");
         }
 
         // Test boolean to_string
@@ -374,12 +374,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_integer_methods_overflow() {
+        let max = Value::Int(i64::MAX);
+        let min = Value::Int(i64::MIN);
+
+        assert!(get_primitive_method(&max, "inc").is_err());
+        assert!(get_primitive_method(&min, "dec").is_err());
+        assert!(get_primitive_method(&min, "abs").is_err());
+        assert!(get_primitive_method(&min, "neg").is_err());
+    }
+
+    #[test]
+    fn test_integer_overflow_methods() {
+        let max = Value::Int(i64::MAX);
+        let one = [Value::Int(1)];
+
+        assert_eq!(execute_primitive_method(&max, "checked_add", &one), Ok(Value::None));
+        assert_eq!(
+            execute_primitive_method(&max, "checked_sub", &one),
+            Ok(Value::some(Value::Int(i64::MAX - 1)))
+        );
+        assert_eq!(execute_primitive_method(&max, "wrapping_add", &one), Ok(Value::Int(i64::MIN)));
+        assert_eq!(
+            execute_primitive_method(&max, "wrapping_mul", &[Value::Int(2)]),
+            Ok(Value::Int(-2))
+        );
+        assert_eq!(
+            execute_primitive_method(&Value::Int(i64::MIN), "saturating_sub", &one),
+            Ok(Value::Int(i64::MIN))
+        );
+        assert_eq!(
+            execute_primitive_method(&Value::Int(3), "saturating_pow", &[Value::Int(50)]),
+            Ok(Value::Int(i64::MAX))
+        );
+        assert_eq!(
+            execute_primitive_method(&Value::Int(7), "checked_div", &[Value::Int(2)]),
+            Ok(Value::some(Value::Int(3)))
+        );
+        assert!(execute_primitive_method(&Value::Int(7), "wrapping_div", &[Value::Int(0)]).is_err());
+        assert!(execute_primitive_method(&max, "checked_add", &[Value::Float(1.0)]).is_err());
+    }
+
     // ===== FLOAT-SPECIFIC METHOD TESTS =====
 
     #[test]
     fn test_float_methods() {
-        let float_val = Value::Float(3.14);
-        let neg_float_val = Value::Float(-3.14);
+        let float_val = Value::Float(3.25);
+        let neg_float_val = Value::Float(-3.25);
         let zero_float_val = Value::Float(0.0);
         let nan_val = Value::Float(f64::NAN);
         let inf_val = Value::Float(f64::INFINITY);
@@ -562,7 +604,7 @@ mod tests {
 
         let result = get_primitive_method(&float_val, "pretty_string").unwrap();
         if let Value::String(s) = result {
-            assert_eq!(s, "3.14");
+            assert_eq!(s, "3.25");
         }
 
         let result = get_primitive_method(&str_val, "pretty_string").unwrap();
@@ -620,7 +662,7 @@ mod tests {
 
         // Test get_pretty_string
         assert_eq!(get_pretty_string(&int_val), "42");
-        assert_eq!(get_pretty_string(&float_val), "3.14");
+        assert_eq!(get_pretty_string(&float_val), "3.25");
         assert_eq!(get_pretty_string(&bool_val), "true");
         assert_eq!(get_pretty_string(&str_val), "\"Hello World\"");
         assert_eq!(get_pretty_string(&none_val), "None");
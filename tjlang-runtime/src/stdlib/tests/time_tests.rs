@@ -264,7 +264,7 @@ mod tests {
         let start = TIME::now();
         TIME::sleep(0.1);
         let end = TIME::now();
-        assert!(end - start >= 0);
+        assert!(end >= start);
     }
 
     #[test]
@@ -320,7 +320,7 @@ mod tests {
     fn test_get_age() {
         let birth_timestamp = 1609459200; // 2021-01-01
         let result = TIME::get_age(birth_timestamp);
-        assert!(result >= 0);
+        assert!(result >= 5);
     }
 
     #[test]
@@ -342,21 +342,21 @@ mod tests {
     fn test_timer_elapsed_millis() {
         let timer = TIME::create_timer();
         let elapsed = timer.elapsed_millis();
-        assert!(elapsed >= 0);
+        assert!(elapsed < 1_000);
     }
 
     #[test]
     fn test_timer_elapsed_micros() {
         let timer = TIME::create_timer();
         let elapsed = timer.elapsed_micros();
-        assert!(elapsed >= 0);
+        assert!(elapsed < 1_000_000);
     }
 
     #[test]
     fn test_timer_elapsed_nanos() {
         let timer = TIME::create_timer();
         let elapsed = timer.elapsed_nanos();
-        assert!(elapsed >= 0);
+        assert!(elapsed < 1_000_000_000);
     }
 
     #[test]
@@ -2,14 +2,64 @@
 //!
 //! This module provides integration between the TJLang standard library
 //! and the runtime interpreter, allowing TJLang code to call stdlib functions.
-
-use crate::interpreter::Interpreter;
-use crate::values::Value;
-use std::collections::HashMap;
-use tjlang_ast::{Expression, Literal};
+//!
+//! Every binding is declared once, in the `stdlib_bindings!` table at the end
+//! of this file. An entry names the module function, its parameters and return
+//! value as Rust types, and the Rust expression that implements it:
+//!
+//! ```text
+//! MATH {
+//!     /// Square root
+//!     fn sqrt(a: f64) -> f64 = MATH::sqrt(a);
+//! }
+//! ```
+//!
+//! From that entry the table generates the native function the interpreter
+//! calls, with its arity and argument type checks, and the `StdlibSignature`
+//! the analyzer and `tjlang doc` read. Argument conversion goes through
+//! `FromValue` and results through `IntoValue`, so the Rust types are the only
+//! place a binding's types are written down. Parameters may take a default
+//! (`width: usize = 0`), and a last `..rest: T` parameter collects any further
+//! arguments. An entry written `fn [interpreter] name(...)` can use the
+//! interpreter in its body, for example to call a TJLang function argument
+//! through `Interpreter::call_function_value`, or to pass TJLang functions to a
+//! Rust function that takes closures through `Callbacks`.
+//!
+//! `TESTING::new_suite` is not bound: a suite keeps its tests as Rust closures
+//! that outlive the call, so they cannot call back into the interpreter. TJLang
+//! code runs its tests one at a time with `TESTING::run_test`.
+
+use crate::bigint::BigInt;
+use crate::gc::GcStats;
+use crate::interpreter::{format_type, Interpreter};
+use crate::iterators::{new_iterator, IteratorState, NativeSource};
+use crate::primitive_methods::compare_values;
+use crate::stdlib::collections::{ChainMap, Counter, DefaultDict, Deque, Heap, NamedTuple, OrderedDict, COLLECTIONS};
+use crate::stdlib::error::ERROR;
+use crate::stdlib::file::{DirEntry, FileMetadata, FileType, FileWatcher, HashAlgorithm, FILE};
+use crate::stdlib::io::{ProgressBar, Spinner, IO};
+use crate::stdlib::json::{JsonLines, JSON};
+use crate::stdlib::math::MATH;
+use crate::stdlib::random::{Generator, RANDOM};
+use crate::stdlib::regex::{Pattern, Regex, REGEX};
+use crate::stdlib::string::STRING;
+use crate::stdlib::testing::{TestResult, TESTING};
+use crate::stdlib::time::{Stopwatch, Timer, TIME};
+use crate::values::{Native, NativeObject, Shared, Value};
+use codespan::{Files, Span};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tjlang_ast::{PrimitiveType, SourceSpan, Type};
 use tjlang_diagnostics::debug_println;
 
 /// Native function registry for standard library functions
+#[derive(Clone)]
 pub struct StdlibRegistry {
     functions: HashMap<String, NativeFunction>,
     structs: HashMap<String, Value>,
@@ -25,976 +75,2739 @@ impl StdlibRegistry {
             functions: HashMap::new(),
             structs: HashMap::new(),
         };
-        registry.register_stdlib_functions();
+        register_bindings(&mut registry.functions);
         debug_println!(" Stdlib registry created (functions enabled)");
         registry
     }
 
-    /// Register all standard library functions
-    fn register_stdlib_functions(&mut self) {
-        // IO Module functions
-        self.register_io_functions();
-
-        // FILE Module functions
-        self.register_file_functions();
+    /// Get a native function by name
+    pub fn get_function(&self, name: &str) -> Option<&NativeFunction> {
+        self.functions.get(name)
+    }
 
-        // MATH Module functions
-        self.register_math_functions();
+    /// Check if a function exists
+    pub fn has_function(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
 
-        // STRING Module functions
-        self.register_string_functions();
+    /// Get all available function names
+    pub fn get_function_names(&self) -> Vec<String> {
+        self.functions.keys().cloned().collect()
+    }
+}
 
-        // COLLECTIONS Module functions
-        self.register_collections_functions();
+impl Default for StdlibRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        // TIME Module functions
-        self.register_time_functions();
+/// A parameter of a stdlib function
+#[derive(Debug, Clone, PartialEq)]
+pub struct StdlibParam {
+    pub name: &'static str,
+    pub ty: Type,
+    /// Whether the argument may be left out
+    pub optional: bool,
+}
 
-        // ERROR Module functions
-        self.register_error_functions();
+/// The signature of a stdlib function, as declared in the binding table
+#[derive(Debug, Clone, PartialEq)]
+pub struct StdlibSignature {
+    pub module: &'static str,
+    pub name: &'static str,
+    pub params: Vec<StdlibParam>,
+    /// Parameter collecting any arguments after `params`
+    pub rest: Option<StdlibParam>,
+    pub return_type: Type,
+    pub doc: &'static [&'static str],
+}
 
-        // TESTING Module functions
-        self.register_testing_functions();
+impl StdlibSignature {
+    /// The registry key, `MODULE::name`
+    pub fn qualified_name(&self) -> String {
+        format!("{}::{}", self.module, self.name)
     }
 
-    /// Register IO module functions
-    fn register_io_functions(&mut self) {
-        // Basic output functions
-        self.functions
-            .insert("IO::print".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("IO::print expects 1 argument".to_string());
-                }
-                crate::stdlib::io::IO::print(&args[0])
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
-
-        self.functions
-            .insert("IO::println".to_string(), |_interpreter, args| {
-                match args.len() {
-                    1 => crate::stdlib::io::IO::println(&args[0])
-                        .map(|_| Value::None)
-                        .map_err(|e| e.to_string()),
+    /// Fewest arguments the function accepts
+    pub fn min_args(&self) -> usize {
+        self.params.iter().filter(|param| !param.optional).count()
+    }
 
-                    0 => crate::stdlib::io::IO::println(&Value::String("".to_string()))
-                        .map(|_| Value::None)
-                        .map_err(|e| e.to_string()),
+    /// Most arguments the function accepts; `None` when it takes a rest parameter
+    pub fn max_args(&self) -> Option<usize> {
+        match self.rest {
+            Some(_) => None,
+            None => Some(self.params.len()),
+        }
+    }
 
-                    _ => Err("IO::println expects 0 or 1 argument".to_string()),
-                }
-            });
+    /// The documentation, one line per entry
+    pub fn doc(&self) -> String {
+        self.doc.iter().map(|line| line.trim()).collect::<Vec<_>>().join("\n")
+    }
+}
 
-        self.functions
-            .insert("IO::printf".to_string(), |_interpreter, args| {
-                if args.len() < 1 {
-                    return Err("IO::printf expects at least 1 argument".to_string());
+impl fmt::Display for StdlibSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params: Vec<String> = self
+            .params
+            .iter()
+            .map(|param| {
+                let param_text = format!("{}: {}", param.name, format_type(&param.ty));
+                if param.optional {
+                    format!("[{}]", param_text)
+                } else {
+                    param_text
                 }
-                let format = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::printf expects string format".to_string()),
-                };
-                let format_args = &args[1..];
-                crate::stdlib::io::IO::printf(format, format_args)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
-
-        // Input functions
-        self.functions
-            .insert("IO::read_line".to_string(), |_interpreter, _args| {
-                crate::stdlib::io::IO::read_line()
-                    .map(|s| Value::String(s))
-                    .map_err(|e| e.to_string())
-            });
+            })
+            .collect();
+        if let Some(rest) = &self.rest {
+            params.push(format!("..{}: {}", rest.name, format_type(&rest.ty)));
+        }
+        write!(
+            f,
+            "{}.{}({}) -> {}",
+            self.module,
+            self.name,
+            params.join(", "),
+            format_type(&self.return_type)
+        )
+    }
+}
 
-        self.functions
-            .insert("IO::read_char".to_string(), |_interpreter, _args| {
-                crate::stdlib::io::IO::read_char()
-                    .map(|c| Value::String(c.to_string()))
-                    .map_err(|e| e.to_string())
-            });
+/// Signatures of every stdlib function, in the order the binding table declares them
+pub fn stdlib_signatures() -> &'static [StdlibSignature] {
+    static SIGNATURES: OnceLock<Vec<StdlibSignature>> = OnceLock::new();
+    SIGNATURES.get_or_init(binding_signatures)
+}
 
-        self.functions
-            .insert("IO::read_int".to_string(), |_interpreter, _args| {
-                crate::stdlib::io::IO::read_int()
-                    .map(|i| Value::Int(i))
-                    .map_err(|e| e.to_string())
-            });
+/// Create a dummy SourceSpan for stdlib types
+fn dummy_span() -> SourceSpan {
+    let mut files = Files::new();
+    let file_id = files.add("stdlib", "");
+    SourceSpan {
+        file_id,
+        span: Span::new(0, 0),
+    }
+}
 
-        self.functions
-            .insert("IO::read_float".to_string(), |_interpreter, _args| {
-                crate::stdlib::io::IO::read_float()
-                    .map(|f| Value::Float(f))
-                    .map_err(|e| e.to_string())
-            });
+fn primitive(ty: PrimitiveType) -> Type {
+    Type::Primitive(ty)
+}
 
-        self.functions
-            .insert("IO::read_bool".to_string(), |_interpreter, _args| {
-                crate::stdlib::io::IO::read_bool()
-                    .map(|b| Value::Bool(b))
-                    .map_err(|e| e.to_string())
-            });
+fn vec_type(element_type: Type) -> Type {
+    Type::Vec {
+        element_type: Box::new(element_type),
+        span: dummy_span(),
+    }
+}
 
-        // Colored output functions
-        self.functions
-            .insert("IO::print_color".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("IO::print_color expects 2 arguments".to_string());
-                }
-                let text = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::print_color expects string text".to_string()),
-                };
-                let color = match &args[1] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::print_color expects string color".to_string()),
-                };
-                // Convert string color to Color enum
-                let color_enum = match color.as_str() {
-                    "red" => crate::stdlib::io::Color::Red,
-                    "green" => crate::stdlib::io::Color::Green,
-                    "yellow" => crate::stdlib::io::Color::Yellow,
-                    "blue" => crate::stdlib::io::Color::Blue,
-                    "magenta" => crate::stdlib::io::Color::Magenta,
-                    "cyan" => crate::stdlib::io::Color::Cyan,
-                    "white" => crate::stdlib::io::Color::White,
-                    "black" => crate::stdlib::io::Color::Black,
-                    "reset" => crate::stdlib::io::Color::Reset,
-                    _ => return Err("Invalid color name".to_string()),
-                };
-                crate::stdlib::io::IO::print_color(text, color_enum)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+fn map_type(key_type: Type, value_type: Type) -> Type {
+    Type::Map {
+        key_type: Box::new(key_type),
+        value_type: Box::new(value_type),
+        span: dummy_span(),
+    }
+}
 
-        self.functions
-            .insert("IO::print_error".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("IO::print_error expects 1 argument".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::print_error expects string message".to_string()),
-                };
-                crate::stdlib::io::IO::print_error(message)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+/// A Rust type a stdlib argument can be converted to
+pub trait FromValue: Sized {
+    /// The TJLang type of arguments this accepts
+    fn tjlang_type() -> Type;
 
-        self.functions
-            .insert("IO::print_warning".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("IO::print_warning expects 1 argument".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::print_warning expects string message".to_string()),
-                };
-                crate::stdlib::io::IO::print_warning(message)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+    /// How type errors describe the accepted arguments
+    fn expected() -> String {
+        format_type(&Self::tjlang_type())
+    }
 
-        self.functions
-            .insert("IO::print_success".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("IO::print_success expects 1 argument".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::print_success expects string message".to_string()),
-                };
-                crate::stdlib::io::IO::print_success(message)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+    fn from_value(value: &Value) -> Option<Self>;
+}
 
-        self.functions
-            .insert("IO::print_info".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("IO::print_info expects 1 argument".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::print_info expects string message".to_string()),
-                };
-                crate::stdlib::io::IO::print_info(message)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+/// A Rust type a stdlib function can return
+pub trait IntoValue {
+    /// The TJLang type of the values this produces
+    fn tjlang_type() -> Type;
 
-        self.functions
-            .insert("IO::print_debug".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("IO::print_debug expects 1 argument".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::print_debug expects string message".to_string()),
-                };
-                crate::stdlib::io::IO::print_debug(message)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+    fn into_value(self, call: &NativeCall) -> Result<Value, String>;
+}
 
-        // Terminal control functions
-        debug_println!(" Registering IO::clear_screen");
-        self.functions
-            .insert("IO::clear_screen".to_string(), |_interpreter, _args| {
-                debug_println!(" IO::clear_screen called");
-                crate::stdlib::io::IO::clear_screen()
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+/// The stdlib call a result is being converted for
+pub struct NativeCall<'a> {
+    pub interpreter: &'a Interpreter,
+    pub name: &'static str,
+    pub args: &'a [Value],
+}
 
-        self.functions
-            .insert("IO::move_cursor".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("IO::move_cursor expects 2 arguments".to_string());
-                }
-                let row = match &args[0] {
-                    Value::Int(i) => *i as u16,
-                    _ => return Err("IO::move_cursor expects int row".to_string()),
-                };
-                let col = match &args[1] {
-                    Value::Int(i) => *i as u16,
-                    _ => return Err("IO::move_cursor expects int col".to_string()),
-                };
-                crate::stdlib::io::IO::move_cursor(row, col)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+impl NativeCall<'_> {
+    /// An int result, subject to the interpreter's overflow policy
+    pub fn integer(&self, value: BigInt) -> Result<Value, String> {
+        self.interpreter.integer_value(value).ok_or_else(|| {
+            let args: Vec<String> = self.args.iter().map(Value::to_string).collect();
+            format!("Integer overflow in {}({})", self.name, args.join(", "))
+        })
+    }
+}
 
-        self.functions
-            .insert("IO::hide_cursor".to_string(), |_interpreter, _args| {
-                crate::stdlib::io::IO::hide_cursor()
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+/// A value ordered the way `sort` orders values, for the collections that need `Ord`
+#[derive(Clone, PartialEq, Eq)]
+pub struct Ordered(pub Value);
 
-        self.functions
-            .insert("IO::show_cursor".to_string(), |_interpreter, _args| {
-                crate::stdlib::io::IO::show_cursor()
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+impl Ord for Ordered {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_values(&self.0, &other.0)
+    }
+}
 
-        self.functions.insert(
-            "IO::get_terminal_size".to_string(),
-            |_interpreter, _args| {
-                crate::stdlib::io::IO::get_terminal_size()
-                    .map(|(w, h)| Value::Tuple(vec![Value::Int(w as i64), Value::Int(h as i64)]))
-                    .map_err(|e| e.to_string())
-            },
-        );
+impl PartialOrd for Ordered {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-        self.functions
-            .insert("IO::is_terminal".to_string(), |_interpreter, _args| {
-                Ok(Value::Bool(crate::stdlib::io::IO::is_terminal()))
-            });
+impl fmt::Debug for Ordered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
 
-        self.functions.insert(
-            "IO::is_input_terminal".to_string(),
-            |_interpreter, _args| Ok(Value::Bool(crate::stdlib::io::IO::is_input_terminal())),
-        );
+/// Check the number of arguments against the parameters' optional flags
+fn check_arity(name: &str, optional: &[bool], variadic: bool, args: &[Value]) -> Result<(), String> {
+    let required = optional.iter().filter(|optional| !**optional).count();
+    if args.len() >= required && (variadic || args.len() <= optional.len()) {
+        return Ok(());
+    }
+    let expected = if variadic {
+        format!("at least {}", required)
+    } else if required == optional.len() {
+        required.to_string()
+    } else {
+        format!("{} to {}", required, optional.len())
+    };
+    Err(format!("{} expects {} argument(s), got {}", name, expected, args.len()))
+}
 
-        // User interaction functions
-        self.functions
-            .insert("IO::prompt".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("IO::prompt expects 1 argument".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::prompt expects string message".to_string()),
-                };
-                crate::stdlib::io::IO::prompt(message)
-                    .map(|s| Value::String(s))
-                    .map_err(|e| e.to_string())
-            });
+/// Convert an argument, naming the parameter when it has the wrong type
+fn argument<T: FromValue>(function: &str, param: &str, value: &Value) -> Result<T, String> {
+    T::from_value(value).ok_or_else(|| {
+        let actual = match value {
+            Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Bool(_) => value.to_string(),
+            Value::None => "None".to_string(),
+            _ => format_type(&value.get_type()),
+        };
+        format!("{} expects {} for '{}', got {}", function, T::expected(), param, actual)
+    })
+}
 
-        self.functions.insert(
-            "IO::prompt_with_default".to_string(),
-            |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("IO::prompt_with_default expects 2 arguments".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::prompt_with_default expects string message".to_string()),
-                };
-                let default = match &args[1] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::prompt_with_default expects string default".to_string()),
-                };
-                crate::stdlib::io::IO::prompt_with_default(message, default)
-                    .map(|s| Value::String(s))
-                    .map_err(|e| e.to_string())
-            },
-        );
+impl FromValue for Value {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Any)
+    }
 
-        self.functions
-            .insert("IO::confirm".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("IO::confirm expects 1 argument".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::confirm expects string message".to_string()),
-                };
-                crate::stdlib::io::IO::confirm(message)
-                    .map(|b| Value::Bool(b))
-                    .map_err(|e| e.to_string())
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+}
 
-        // Progress bar and spinner functions
-        self.functions.insert(
-            "IO::create_progress_bar".to_string(),
-            |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("IO::create_progress_bar expects 1 argument".to_string());
-                }
-                let total = match &args[0] {
-                    Value::Int(i) => *i as u64,
-                    _ => return Err("IO::create_progress_bar expects int total".to_string()),
-                };
-                // Note: ProgressBar is a struct that would need to be stored in the interpreter
-                // For now, we'll return a placeholder value
-                Ok(Value::String(format!("ProgressBar(total={})", total)))
-            },
-        );
+impl FromValue for Ordered {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Any)
+    }
 
-        self.functions
-            .insert("IO::create_spinner".to_string(), |_interpreter, _args| {
-                // Note: Spinner is a struct that would need to be stored in the interpreter
-                // For now, we'll return a placeholder value
-                Ok(Value::String("Spinner".to_string()))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(Ordered(value.clone()))
+    }
+}
 
-        // Select functions - these are complex because they need to handle arrays
-        self.functions
-            .insert("IO::select".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("IO::select expects 2 arguments".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::select expects string message".to_string()),
-                };
-                let options = match &args[1] {
-                    Value::Vec(v) => {
-                        let mut string_options = Vec::new();
-                        for option in v {
-                            match option {
-                                Value::String(s) => string_options.push(s.clone()),
-                                _ => return Err("IO::select options must be strings".to_string()),
-                            }
-                        }
-                        string_options
-                    }
-                    _ => return Err("IO::select expects array of strings".to_string()),
-                };
-                crate::stdlib::io::IO::select(message, &options)
-                    .map(|i| Value::Int(i as i64))
-                    .map_err(|e| e.to_string())
-            });
+impl FromValue for i64 {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Int)
+    }
 
-        self.functions
-            .insert("IO::multi_select".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("IO::multi_select expects 2 arguments".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("IO::multi_select expects string message".to_string()),
-                };
-                let options = match &args[1] {
-                    Value::Vec(v) => {
-                        let mut string_options = Vec::new();
-                        for option in v {
-                            match option {
-                                Value::String(s) => string_options.push(s.clone()),
-                                _ => {
-                                    return Err(
-                                        "IO::multi_select options must be strings".to_string()
-                                    )
-                                }
-                            }
-                        }
-                        string_options
-                    }
-                    _ => return Err("IO::multi_select expects array of strings".to_string()),
-                };
-                crate::stdlib::io::IO::multi_select(message, &options)
-                    .map(|indices| {
-                        Value::Vec(indices.into_iter().map(|i| Value::Int(i as i64)).collect())
-                    })
-                    .map_err(|e| e.to_string())
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
     }
+}
 
-    /// Register FILE module functions
-    fn register_file_functions(&mut self) {
-        self.functions
-            .insert("FILE::read_to_string".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("FILE::read_to_string expects 1 argument".to_string());
+/// Ints narrower than `i64`, accepted when the argument is in range
+macro_rules! narrow_int_from_value {
+    ($($ty:ty => $expected:literal),* $(,)?) => {$(
+        impl FromValue for $ty {
+            fn tjlang_type() -> Type {
+                primitive(PrimitiveType::Int)
+            }
+
+            fn expected() -> String {
+                $expected.to_string()
+            }
+
+            fn from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::Int(i) => <$ty>::try_from(*i).ok(),
+                    _ => None,
                 }
-                let path = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("FILE::read_to_string expects string argument".to_string()),
-                };
-                crate::stdlib::file::FILE::read_to_string(path)
-                    .map(|s| Value::String(s))
-                    .map_err(|e| e.to_string())
-            });
+            }
+        }
+    )*};
+}
 
-        self.functions
-            .insert("FILE::write_string".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("FILE::write_string expects 2 arguments".to_string());
-                }
-                let path = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("FILE::write_string expects string path".to_string()),
-                };
-                let content = match &args[1] {
-                    Value::String(s) => s,
-                    _ => return Err("FILE::write_string expects string content".to_string()),
-                };
-                crate::stdlib::file::FILE::write_string(path, content)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+narrow_int_from_value! {
+    i32 => "a 32-bit int",
+    isize => "an int",
+    u8 => "an int from 0 to 255",
+    u16 => "an int from 0 to 65535",
+    u32 => "a non-negative 32-bit int",
+    u64 => "a non-negative int",
+    usize => "a non-negative int",
+}
 
-        self.functions
-            .insert("FILE::exists".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("FILE::exists expects 1 argument".to_string());
-                }
-                let path = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("FILE::exists expects string argument".to_string()),
-                };
-                Ok(Value::Bool(crate::stdlib::file::FILE::exists(path)))
-            });
+impl FromValue for f64 {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Float)
+    }
 
-        self.functions
-            .insert("FILE::is_file".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("FILE::is_file expects 1 argument".to_string());
-                }
-                let path = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("FILE::is_file expects string argument".to_string()),
-                };
-                Ok(Value::Bool(crate::stdlib::file::FILE::is_file(path)))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_f64()
+    }
+}
 
-        self.functions
-            .insert("FILE::is_dir".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("FILE::is_dir expects 1 argument".to_string());
-                }
-                let path = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("FILE::is_dir expects string argument".to_string()),
-                };
-                Ok(Value::Bool(crate::stdlib::file::FILE::is_dir(path)))
-            });
+impl FromValue for bool {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Bool)
     }
 
-    /// Register MATH module functions
-    fn register_math_functions(&mut self) {
-        // Basic arithmetic
-        self.functions
-            .insert("MATH::add".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("MATH::add expects 2 arguments".to_string());
-                }
-                let a = match &args[0] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::add expects numeric arguments".to_string()),
-                };
-                let b = match &args[1] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::add expects numeric arguments".to_string()),
-                };
-                Ok(Value::Float(crate::stdlib::math::MATH::add(a, b)))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
 
-        self.functions
-            .insert("MATH::subtract".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("MATH::subtract expects 2 arguments".to_string());
-                }
-                let a = match &args[0] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::subtract expects numeric arguments".to_string()),
-                };
-                let b = match &args[1] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::subtract expects numeric arguments".to_string()),
-                };
-                Ok(Value::Float(crate::stdlib::math::MATH::subtract(a, b)))
-            });
+impl FromValue for String {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Str)
+    }
 
-        self.functions
-            .insert("MATH::multiply".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("MATH::multiply expects 2 arguments".to_string());
-                }
-                let a = match &args[0] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::multiply expects numeric arguments".to_string()),
-                };
-                let b = match &args[1] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::multiply expects numeric arguments".to_string()),
-                };
-                Ok(Value::Float(crate::stdlib::math::MATH::multiply(a, b)))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
 
-        self.functions
-            .insert("MATH::divide".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("MATH::divide expects 2 arguments".to_string());
-                }
-                let a = match &args[0] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::divide expects numeric arguments".to_string()),
-                };
-                let b = match &args[1] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::divide expects numeric arguments".to_string()),
-                };
-                Ok(Value::Float(crate::stdlib::math::MATH::divide(a, b)))
-            });
+impl FromValue for char {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Str)
+    }
 
-        self.functions
-            .insert("MATH::sqrt".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("MATH::sqrt expects 1 argument".to_string());
-                }
-                let a = match &args[0] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::sqrt expects numeric argument".to_string()),
-                };
-                Ok(Value::Float(crate::stdlib::math::MATH::sqrt(a)))
-            });
+    fn expected() -> String {
+        "a single-character str".to_string()
+    }
 
-        self.functions
-            .insert("MATH::sin".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("MATH::sin expects 1 argument".to_string());
-                }
-                let a = match &args[0] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::sin expects numeric argument".to_string()),
-                };
-                Ok(Value::Float(crate::stdlib::math::MATH::sin(a)))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        let Value::String(s) = value else {
+            return None;
+        };
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    }
+}
 
-        self.functions
-            .insert("MATH::cos".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("MATH::cos expects 1 argument".to_string());
-                }
-                let a = match &args[0] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("MATH::cos expects numeric argument".to_string()),
-                };
-                Ok(Value::Float(crate::stdlib::math::MATH::cos(a)))
-            });
+impl<T: FromValue> FromValue for Option<T> {
+    fn tjlang_type() -> Type {
+        Type::Option {
+            inner: Box::new(T::tjlang_type()),
+            span: dummy_span(),
+        }
+    }
 
-        // Constants
-        self.functions
-            .insert("MATH::PI".to_string(), |_interpreter, _args| {
-                Ok(Value::Float(crate::stdlib::math::MATH::PI))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::None => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
 
-        self.functions
-            .insert("MATH::E".to_string(), |_interpreter, _args| {
-                Ok(Value::Float(crate::stdlib::math::MATH::E))
-            });
+impl<T: FromValue> FromValue for Vec<T> {
+    fn tjlang_type() -> Type {
+        vec_type(T::tjlang_type())
     }
 
-    /// Register STRING module functions
-    fn register_string_functions(&mut self) {
-        self.functions
-            .insert("STRING::length".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("STRING::length expects 1 argument".to_string());
-                }
-                let s = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("STRING::length expects string argument".to_string()),
-                };
-                Ok(Value::Int(crate::stdlib::string::STRING::length(s) as i64))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Vec(items) => items.read().iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
 
-        self.functions
-            .insert("STRING::to_uppercase".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("STRING::to_uppercase expects 1 argument".to_string());
-                }
-                let s = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("STRING::to_uppercase expects string argument".to_string()),
-                };
-                Ok(Value::String(crate::stdlib::string::STRING::to_uppercase(
-                    s,
-                )))
-            });
+impl<T: FromValue + Eq + Hash> FromValue for HashSet<T> {
+    fn tjlang_type() -> Type {
+        Type::Set {
+            element_type: Box::new(T::tjlang_type()),
+            span: dummy_span(),
+        }
+    }
 
-        self.functions
-            .insert("STRING::to_lowercase".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("STRING::to_lowercase expects 1 argument".to_string());
-                }
-                let s = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("STRING::to_lowercase expects string argument".to_string()),
-                };
-                Ok(Value::String(crate::stdlib::string::STRING::to_lowercase(
-                    s,
-                )))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Set(items) => items.read().iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
 
-        self.functions
-            .insert("STRING::contains".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("STRING::contains expects 2 arguments".to_string());
-                }
-                let s = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("STRING::contains expects string argument".to_string()),
-                };
-                let pattern = match &args[1] {
-                    Value::String(s) => s,
-                    _ => return Err("STRING::contains expects string pattern".to_string()),
-                };
-                Ok(Value::Bool(crate::stdlib::string::STRING::contains(
-                    s, pattern,
-                )))
-            });
+impl<K: FromValue + Eq + Hash, V: FromValue> FromValue for HashMap<K, V> {
+    fn tjlang_type() -> Type {
+        map_type(K::tjlang_type(), V::tjlang_type())
+    }
 
-        self.functions
-            .insert("STRING::replace".to_string(), |_interpreter, args| {
-                if args.len() != 3 {
-                    return Err("STRING::replace expects 3 arguments".to_string());
-                }
-                let s = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("STRING::replace expects string argument".to_string()),
-                };
-                let from = match &args[1] {
-                    Value::String(s) => s,
-                    _ => return Err("STRING::replace expects string from".to_string()),
-                };
-                let to = match &args[2] {
-                    Value::String(s) => s,
-                    _ => return Err("STRING::replace expects string to".to_string()),
-                };
-                Ok(Value::String(crate::stdlib::string::STRING::replace(
-                    s, from, to,
-                )))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Map(entries) => entries
+                .read()
+                .iter()
+                .map(|(k, v)| Some((K::from_value(k)?, V::from_value(v)?)))
+                .collect(),
+            _ => None,
+        }
     }
+}
 
-    /// Register COLLECTIONS module functions
-    fn register_collections_functions(&mut self) {
-        self.functions.insert(
-            "COLLECTIONS::array_new".to_string(),
-            |_interpreter, _args| Ok(Value::Vec(Vec::new())),
-        );
+/// Vectors, sets and maps the binding mutates in place
+impl FromValue for Shared<Vec<Value>> {
+    fn tjlang_type() -> Type {
+        vec_type(primitive(PrimitiveType::Any))
+    }
 
-        self.functions.insert(
-            "COLLECTIONS::array_push".to_string(),
-            |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("COLLECTIONS::array_push expects 2 arguments".to_string());
-                }
-                let mut vec = match &args[0] {
-                    Value::Vec(v) => v.clone(),
-                    _ => return Err("COLLECTIONS::array_push expects vector argument".to_string()),
-                };
-                vec.push(args[1].clone());
-                Ok(Value::Vec(vec))
-            },
-        );
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Vec(items) => Some(items.clone()),
+            _ => None,
+        }
+    }
+}
 
-        self.functions.insert(
-            "COLLECTIONS::array_get".to_string(),
-            |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("COLLECTIONS::array_get expects 2 arguments".to_string());
-                }
-                let vec = match &args[0] {
-                    Value::Vec(v) => v,
-                    _ => return Err("COLLECTIONS::array_get expects vector argument".to_string()),
-                };
-                let index = match &args[1] {
-                    Value::Int(i) => *i as usize,
-                    _ => return Err("COLLECTIONS::array_get expects integer index".to_string()),
-                };
-                if index >= vec.len() {
-                    return Err("Index out of bounds".to_string());
-                }
-                Ok(vec[index].clone())
-            },
-        );
+impl FromValue for Shared<HashSet<Value>> {
+    fn tjlang_type() -> Type {
+        <HashSet<Value> as FromValue>::tjlang_type()
+    }
 
-        // Set creation functions
-        self.functions
-            .insert("COLLECTIONS::set_new".to_string(), |_interpreter, _args| {
-                Ok(Value::Set(std::collections::HashSet::new()))
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Set(items) => Some(items.clone()),
+            _ => None,
+        }
+    }
+}
 
-        // Map creation functions
-        self.functions
-            .insert("COLLECTIONS::map_new".to_string(), |_interpreter, _args| {
-                Ok(Value::Map(std::collections::HashMap::new()))
-            });
+impl FromValue for Shared<HashMap<Value, Value>> {
+    fn tjlang_type() -> Type {
+        <HashMap<Value, Value> as FromValue>::tjlang_type()
+    }
 
-        // Queue creation functions
-        self.functions.insert(
-            "COLLECTIONS::queue_new".to_string(),
-            |_interpreter, _args| {
-                debug_println!("[DEBUG] DEBUG: COLLECTIONS::queue_new called");
-                Ok(Value::Struct {
-                    name: "Queue".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Map(entries) => Some(entries.clone()),
+            _ => None,
+        }
+    }
+}
 
-        // Priority Queue creation functions
-        self.functions.insert(
-            "COLLECTIONS::priority_queue_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "PriorityQueue".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+/// Times are floats of seconds since the Unix epoch
+impl FromValue for SystemTime {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Float)
+    }
 
-        // BTree creation functions
-        self.functions.insert(
-            "COLLECTIONS::btree_map_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "BTreeMap".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+    fn expected() -> String {
+        "a float of seconds since the Unix epoch".to_string()
+    }
 
-        self.functions.insert(
-            "COLLECTIONS::btree_set_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "BTreeSet".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+    fn from_value(value: &Value) -> Option<Self> {
+        let seconds = value.as_f64()?;
+        let offset = Duration::try_from_secs_f64(seconds.abs()).ok()?;
+        if seconds >= 0.0 {
+            UNIX_EPOCH.checked_add(offset)
+        } else {
+            UNIX_EPOCH.checked_sub(offset)
+        }
+    }
+}
 
-        // Advanced collection creation functions
-        self.functions.insert(
-            "COLLECTIONS::counter_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "Counter".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+impl FromValue for HashAlgorithm {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Str)
+    }
 
-        self.functions.insert(
-            "COLLECTIONS::default_dict_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "DefaultDict".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+    fn expected() -> String {
+        "\"md5\", \"sha1\", \"sha256\" or \"sha512\"".to_string()
+    }
 
-        self.functions.insert(
-            "COLLECTIONS::chain_map_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "ChainMap".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(name) => match name.to_lowercase().as_str() {
+                "md5" => Some(HashAlgorithm::MD5),
+                "sha1" => Some(HashAlgorithm::SHA1),
+                "sha256" => Some(HashAlgorithm::SHA256),
+                "sha512" => Some(HashAlgorithm::SHA512),
+                _ => None,
             },
-        );
+            _ => None,
+        }
+    }
+}
 
-        self.functions.insert(
-            "COLLECTIONS::named_tuple_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "NamedTuple".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+/// Counters are maps from element to count
+impl FromValue for Counter<Value> {
+    fn tjlang_type() -> Type {
+        map_type(primitive(PrimitiveType::Any), primitive(PrimitiveType::Int))
+    }
 
-        self.functions.insert(
-            "COLLECTIONS::ordered_dict_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "OrderedDict".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+    fn from_value(value: &Value) -> Option<Self> {
+        let counts = <HashMap<Value, usize> as FromValue>::from_value(value)?;
+        let mut counter = Counter::new();
+        for (element, count) in counts {
+            counter.set(element, count);
+        }
+        Some(counter)
+    }
+}
 
-        self.functions.insert(
-            "COLLECTIONS::deque_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "Deque".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+/// Named tuples are `NamedTuple` structs
+impl FromValue for NamedTuple<Value> {
+    fn tjlang_type() -> Type {
+        Type::Identifier("NamedTuple".to_string())
+    }
 
-        self.functions.insert(
-            "COLLECTIONS::heap_new".to_string(),
-            |_interpreter, _args| {
-                Ok(Value::Struct {
-                    name: "Heap".to_string(),
-                    fields: std::collections::HashMap::new(),
-                })
-            },
-        );
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Struct { name, fields } if name == "NamedTuple" => {
+                let (names, values) = fields.read().iter().map(|(k, v)| (k.clone(), v.clone())).unzip();
+                Some(NamedTuple::new(names, values))
+            }
+            _ => None,
+        }
     }
+}
 
-    /// Register TIME module functions
-    fn register_time_functions(&mut self) {
-        self.functions
-            .insert("TIME::now".to_string(), |_interpreter, _args| {
-                Ok(Value::Float(crate::stdlib::time::TIME::now() as f64))
-            });
+/// Default dicts are `DefaultDict` structs: a `data` map of the entries and the
+/// `default_factory` function that makes the values of missing keys
+pub struct DefaultDictValue {
+    default_factory: Value,
+    data: Shared<HashMap<Value, Value>>,
+}
 
-        self.functions
-            .insert("TIME::now_string".to_string(), |_interpreter, _args| {
-                Ok(Value::String(crate::stdlib::time::TIME::now_string()))
-            });
+impl DefaultDictValue {
+    /// A `COLLECTIONS` default dict of the entries
+    fn to_native<F: Fn() -> Value>(&self, default_factory: F) -> DefaultDict<Value, Value, F> {
+        let mut dict = COLLECTIONS::default_dict_new(default_factory);
+        for (key, value) in self.data.read().iter() {
+            COLLECTIONS::default_dict_set(&mut dict, key.clone(), value.clone());
+        }
+        dict
+    }
+}
 
-        self.functions
-            .insert("TIME::sleep".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("TIME::sleep expects 1 argument".to_string());
-                }
-                let seconds = match &args[0] {
-                    Value::Float(f) => *f,
-                    Value::Int(i) => *i as f64,
-                    _ => return Err("TIME::sleep expects numeric argument".to_string()),
-                };
-                crate::stdlib::time::TIME::sleep(seconds);
-                Ok(Value::None)
-            });
+impl FromValue for DefaultDictValue {
+    fn tjlang_type() -> Type {
+        Type::Identifier("DefaultDict".to_string())
     }
 
-    /// Register ERROR module functions
-    fn register_error_functions(&mut self) {
-        self.functions
-            .insert("ERROR::new".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("ERROR::new expects 1 argument".to_string());
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Struct { name, fields } if name == "DefaultDict" => {
+                let fields = fields.read();
+                match (fields.get("default_factory"), fields.get("data")) {
+                    (Some(default_factory), Some(Value::Map(data))) => Some(DefaultDictValue {
+                        default_factory: default_factory.clone(),
+                        data: data.clone(),
+                    }),
+                    _ => None,
                 }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("ERROR::new expects string argument".to_string()),
-                };
-                Ok(Value::String(crate::stdlib::error::ERROR::new(message)))
-            });
+            }
+            _ => None,
+        }
+    }
+}
 
-        self.functions
-            .insert("ERROR::log".to_string(), |_interpreter, args| {
-                if args.len() != 1 {
-                    return Err("ERROR::log expects 1 argument".to_string());
-                }
-                let message = match &args[0] {
-                    Value::String(s) => s,
-                    _ => return Err("ERROR::log expects string argument".to_string()),
-                };
-                crate::stdlib::error::ERROR::log(message);
-                Ok(Value::None)
-            });
+/// Declared type names such as `Point` evaluate to their type
+impl FromValue for Type {
+    fn tjlang_type() -> Type {
+        Type::Identifier("type".to_string())
     }
 
-    /// Register TESTING module functions
-    fn register_testing_functions(&mut self) {
-        self.functions
-            .insert("TESTING::assert_true".to_string(), |_interpreter, args| {
-                if args.len() != 2 {
-                    return Err("TESTING::assert_true expects 2 arguments".to_string());
-                }
-                let condition = match &args[0] {
-                    Value::Bool(b) => *b,
-                    _ => return Err("TESTING::assert_true expects boolean condition".to_string()),
-                };
-                let message = match &args[1] {
-                    Value::String(s) => s,
-                    _ => return Err("TESTING::assert_true expects string message".to_string()),
-                };
-                crate::stdlib::testing::TESTING::assert_true(condition, message)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+    fn expected() -> String {
+        "a type, such as a struct name".to_string()
+    }
 
-        self.functions
-            .insert("TESTING::assert_equal".to_string(), |_interpreter, args| {
-                if args.len() != 3 {
-                    return Err("TESTING::assert_equal expects 3 arguments".to_string());
-                }
-                let actual = &args[0];
-                let expected = &args[1];
-                let message = match &args[2] {
-                    Value::String(s) => s,
-                    _ => return Err("TESTING::assert_equal expects string message".to_string()),
-                };
-                crate::stdlib::testing::TESTING::assert_equal(actual, expected, message)
-                    .map(|_| Value::None)
-                    .map_err(|e| e.to_string())
-            });
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Type(ty) => Some(ty.clone()),
+            _ => None,
+        }
     }
+}
 
-    /// Get a native function by name
-    pub fn get_function(&self, name: &str) -> Option<&NativeFunction> {
-        self.functions.get(name)
+impl FromValue for Pattern {
+    fn tjlang_type() -> Type {
+        Type::Union {
+            types: vec![primitive(PrimitiveType::Str), Type::Identifier("Regex".to_string())],
+            span: dummy_span(),
+        }
     }
 
-    /// Check if a function exists
-    pub fn has_function(&self, name: &str) -> bool {
-        self.functions.contains_key(name)
+    fn expected() -> String {
+        "a pattern str or Regex".to_string()
     }
 
-    /// Get all available function names
-    pub fn get_function_names(&self) -> Vec<String> {
-        self.functions.keys().cloned().collect()
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(pattern) => Some(Pattern::Source(pattern.clone())),
+            Value::Regex(regex) => Some(Pattern::Compiled(regex.clone())),
+            _ => None,
+        }
     }
 }
 
-impl Default for StdlibRegistry {
-    fn default() -> Self {
-        Self::new()
+/// A Rust collection that TJLang stores as a vector of its elements. Bindings
+/// that mutate one run on a copy and write its elements back with `with_backed`.
+trait VecBacked: Sized {
+    fn element_type() -> Type {
+        primitive(PrimitiveType::Any)
+    }
+
+    fn from_elements(elements: Vec<Value>) -> Option<Self>;
+
+    fn into_elements(self) -> Vec<Value>;
+}
+
+impl VecBacked for VecDeque<Value> {
+    fn from_elements(elements: Vec<Value>) -> Option<Self> {
+        Some(elements.into())
+    }
+
+    fn into_elements(self) -> Vec<Value> {
+        self.into()
+    }
+}
+
+/// Priority queues and heaps list their elements largest first
+impl VecBacked for BinaryHeap<Ordered> {
+    fn from_elements(elements: Vec<Value>) -> Option<Self> {
+        Some(elements.into_iter().map(Ordered).collect())
+    }
+
+    fn into_elements(self) -> Vec<Value> {
+        self.into_sorted_vec().into_iter().rev().map(|item| item.0).collect()
+    }
+}
+
+impl VecBacked for Heap<Ordered> {
+    fn from_elements(elements: Vec<Value>) -> Option<Self> {
+        let mut heap = Heap::new();
+        for element in elements {
+            heap.push(Ordered(element));
+        }
+        Some(heap)
+    }
+
+    fn into_elements(mut self) -> Vec<Value> {
+        std::iter::from_fn(|| self.pop()).map(|item| item.0).collect()
+    }
+}
+
+impl VecBacked for Deque<Value> {
+    fn from_elements(elements: Vec<Value>) -> Option<Self> {
+        let mut deque = Deque::new();
+        deque.extend(elements.into_iter());
+        Some(deque)
+    }
+
+    fn into_elements(mut self) -> Vec<Value> {
+        std::iter::from_fn(|| self.pop_front()).collect()
+    }
+}
+
+/// Ordered dicts are vectors of `(key, value)` tuples
+impl VecBacked for OrderedDict<Value, Value> {
+    fn element_type() -> Type {
+        Type::Tuple {
+            types: vec![primitive(PrimitiveType::Any), primitive(PrimitiveType::Any)],
+            span: dummy_span(),
+        }
+    }
+
+    fn from_elements(elements: Vec<Value>) -> Option<Self> {
+        let mut dict = OrderedDict::new();
+        for element in elements {
+            match element {
+                Value::Tuple(entry) if entry.len() == 2 => {
+                    dict.insert(entry[0].clone(), entry[1].clone());
+                }
+                _ => return None,
+            }
+        }
+        Some(dict)
+    }
+
+    fn into_elements(self) -> Vec<Value> {
+        self.keys()
+            .zip(self.values())
+            .map(|(key, value)| Value::Tuple(vec![key.clone(), value.clone()]))
+            .collect()
+    }
+}
+
+/// Chain maps are vectors of maps, searched last to first
+impl VecBacked for ChainMap<Value, Value> {
+    fn element_type() -> Type {
+        map_type(primitive(PrimitiveType::Any), primitive(PrimitiveType::Any))
+    }
+
+    fn from_elements(elements: Vec<Value>) -> Option<Self> {
+        let mut chain = ChainMap::new();
+        for element in elements {
+            chain.add(<HashMap<Value, Value> as FromValue>::from_value(&element)?);
+        }
+        Some(chain)
+    }
+
+    fn into_elements(self) -> Vec<Value> {
+        self.maps().iter().map(|map| Value::map(map.clone())).collect()
+    }
+}
+
+macro_rules! vec_backed_values {
+    ($($ty:ty),* $(,)?) => {$(
+        impl FromValue for $ty {
+            fn tjlang_type() -> Type {
+                vec_type(<$ty as VecBacked>::element_type())
+            }
+
+            fn from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::Vec(items) => <$ty as VecBacked>::from_elements(items.read().clone()),
+                    _ => None,
+                }
+            }
+        }
+
+        impl IntoValue for $ty {
+            fn tjlang_type() -> Type {
+                <$ty as FromValue>::tjlang_type()
+            }
+
+            fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+                Ok(Value::vec(self.into_elements()))
+            }
+        }
+    )*};
+}
+
+vec_backed_values!(
+    VecDeque<Value>,
+    BinaryHeap<Ordered>,
+    Heap<Ordered>,
+    Deque<Value>,
+    OrderedDict<Value, Value>,
+    ChainMap<Value, Value>,
+);
+
+/// Run `f` on the collection stored in `vec`, then store the collection back
+fn with_backed<C: VecBacked, R>(vec: &Shared<Vec<Value>>, f: impl FnOnce(&mut C) -> R) -> Result<R, String> {
+    let elements = vec.read().clone();
+    let mut collection = C::from_elements(elements)
+        .ok_or_else(|| format!("expected a vector of {}", format_type(&C::element_type())))?;
+    let result = f(&mut collection);
+    *vec.write() = collection.into_elements();
+    Ok(result)
+}
+
+impl IntoValue for () {
+    fn tjlang_type() -> Type {
+        Type::Identifier("None".to_string())
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::None)
+    }
+}
+
+impl IntoValue for Value {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Any)
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(self)
+    }
+}
+
+impl IntoValue for Ordered {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Any)
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(self.0)
+    }
+}
+
+/// Ints that always fit in an `i64`
+macro_rules! small_int_into_value {
+    ($($ty:ty),*) => {$(
+        impl IntoValue for $ty {
+            fn tjlang_type() -> Type {
+                primitive(PrimitiveType::Int)
+            }
+
+            fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+                Ok(Value::Int(self as i64))
+            }
+        }
+    )*};
+}
+
+small_int_into_value!(i64, i32, u8, u16, u32);
+
+impl IntoValue for BigInt {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Int)
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        call.integer(self)
+    }
+}
+
+impl IntoValue for u64 {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Int)
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        match i64::try_from(self) {
+            Ok(i) => Ok(Value::Int(i)),
+            Err(_) => call.integer(BigInt::from(self)),
+        }
+    }
+}
+
+impl IntoValue for usize {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Int)
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        (self as u64).into_value(call)
+    }
+}
+
+impl IntoValue for u128 {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Int)
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        if let Ok(i) = i64::try_from(self) {
+            return Ok(Value::Int(i));
+        }
+        let high = BigInt::from((self >> 64) as u64);
+        let shift = BigInt::from(1i64 << 32).pow(2);
+        call.integer(&(&high * &shift) + &BigInt::from(self as u64))
+    }
+}
+
+impl IntoValue for f64 {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Float)
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::Float(self))
+    }
+}
+
+impl IntoValue for bool {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Bool)
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::Bool(self))
+    }
+}
+
+impl IntoValue for String {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Str)
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::String(self))
+    }
+}
+
+impl IntoValue for char {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Str)
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::String(self.to_string()))
+    }
+}
+
+/// Missing results are `None`
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn tjlang_type() -> Type {
+        Type::Option {
+            inner: Box::new(T::tjlang_type()),
+            span: dummy_span(),
+        }
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        match self {
+            Some(value) => value.into_value(call),
+            None => Ok(Value::None),
+        }
+    }
+}
+
+/// A result returned as a TJLang `Option`, `Some(value)` or `None`, unlike a
+/// plain `Option` result, which is the bare value or `None`
+pub struct OptionValue<T>(pub Option<T>);
+
+impl<T: IntoValue> IntoValue for OptionValue<T> {
+    fn tjlang_type() -> Type {
+        Option::<T>::tjlang_type()
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        match self.0 {
+            Some(value) => Ok(Value::some(value.into_value(call)?)),
+            None => Ok(Value::None),
+        }
+    }
+}
+
+/// Errors become runtime errors
+impl<T: IntoValue, E: fmt::Display> IntoValue for Result<T, E> {
+    fn tjlang_type() -> Type {
+        T::tjlang_type()
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        self.map_err(|e| e.to_string())?.into_value(call)
+    }
+}
+
+fn into_values<T: IntoValue>(items: impl IntoIterator<Item = T>, call: &NativeCall) -> Result<Vec<Value>, String> {
+    items.into_iter().map(|item| item.into_value(call)).collect()
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn tjlang_type() -> Type {
+        vec_type(T::tjlang_type())
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::vec(into_values(self, call)?))
+    }
+}
+
+impl<T: IntoValue> IntoValue for HashSet<T> {
+    fn tjlang_type() -> Type {
+        Type::Set {
+            element_type: Box::new(T::tjlang_type()),
+            span: dummy_span(),
+        }
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::set(into_values(self, call)?.into_iter().collect()))
+    }
+}
+
+impl<T: IntoValue> IntoValue for BTreeSet<T> {
+    fn tjlang_type() -> Type {
+        <HashSet<T> as IntoValue>::tjlang_type()
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::set(into_values(self, call)?.into_iter().collect()))
+    }
+}
+
+fn into_entries<K: IntoValue, V: IntoValue>(
+    entries: impl IntoIterator<Item = (K, V)>,
+    call: &NativeCall,
+) -> Result<HashMap<Value, Value>, String> {
+    entries
+        .into_iter()
+        .map(|(k, v)| Ok((k.into_value(call)?, v.into_value(call)?)))
+        .collect()
+}
+
+impl<K: IntoValue, V: IntoValue> IntoValue for HashMap<K, V> {
+    fn tjlang_type() -> Type {
+        map_type(K::tjlang_type(), V::tjlang_type())
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::map(into_entries(self, call)?))
+    }
+}
+
+impl<K: IntoValue, V: IntoValue> IntoValue for BTreeMap<K, V> {
+    fn tjlang_type() -> Type {
+        map_type(K::tjlang_type(), V::tjlang_type())
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::map(into_entries(self, call)?))
+    }
+}
+
+macro_rules! tuple_into_value {
+    ($(($($name:ident),+)),* $(,)?) => {$(
+        impl<$($name: IntoValue),+> IntoValue for ($($name,)+) {
+            fn tjlang_type() -> Type {
+                Type::Tuple {
+                    types: vec![$($name::tjlang_type()),+],
+                    span: dummy_span(),
+                }
+            }
+
+            #[allow(non_snake_case)]
+            fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+                let ($($name,)+) = self;
+                Ok(Value::Tuple(vec![$($name.into_value(call)?),+]))
+            }
+        }
+    )*};
+}
+
+tuple_into_value!((A, B), (A, B, C), (A, B, C, D), (A, B, C, D, E), (A, B, C, D, E, F));
+
+impl IntoValue for Shared<Vec<Value>> {
+    fn tjlang_type() -> Type {
+        <Shared<Vec<Value>> as FromValue>::tjlang_type()
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::Vec(self))
+    }
+}
+
+impl IntoValue for SystemTime {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Float)
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        let seconds = match self.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs_f64(),
+            Err(before) => -before.duration().as_secs_f64(),
+        };
+        Ok(Value::Float(seconds))
+    }
+}
+
+impl IntoValue for FileType {
+    fn tjlang_type() -> Type {
+        primitive(PrimitiveType::Str)
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::String(format!("{:?}", self).to_lowercase()))
+    }
+}
+
+fn struct_value(name: &str, fields: Vec<(&str, Value)>) -> Value {
+    Value::new_struct(
+        name,
+        fields.into_iter().map(|(field, value)| (field.to_string(), value)).collect(),
+    )
+}
+
+impl IntoValue for FileMetadata {
+    fn tjlang_type() -> Type {
+        Type::Identifier("FileMetadata".to_string())
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        Ok(struct_value(
+            "FileMetadata",
+            vec![
+                ("size", self.size.into_value(call)?),
+                ("is_file", Value::Bool(self.is_file)),
+                ("is_dir", Value::Bool(self.is_dir)),
+                ("is_symlink", Value::Bool(self.is_symlink)),
+                ("modified", self.modified.into_value(call)?),
+                ("created", self.created.into_value(call)?),
+                ("accessed", self.accessed.into_value(call)?),
+                ("permissions", self.permissions.into_value(call)?),
+            ],
+        ))
+    }
+}
+
+impl IntoValue for DirEntry {
+    fn tjlang_type() -> Type {
+        Type::Identifier("DirEntry".to_string())
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        Ok(struct_value(
+            "DirEntry",
+            vec![
+                ("name", Value::String(self.name)),
+                ("path", Value::String(self.path)),
+                ("is_file", Value::Bool(self.is_file)),
+                ("is_dir", Value::Bool(self.is_dir)),
+                ("is_symlink", Value::Bool(self.is_symlink)),
+                ("size", self.size.into_value(call)?),
+                ("modified", self.modified.into_value(call)?),
+                ("created", self.created.into_value(call)?),
+                ("accessed", self.accessed.into_value(call)?),
+            ],
+        ))
+    }
+}
+
+impl IntoValue for Counter<Value> {
+    fn tjlang_type() -> Type {
+        <Counter<Value> as FromValue>::tjlang_type()
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        self.most_common(None).into_iter().collect::<HashMap<_, _>>().into_value(call)
+    }
+}
+
+impl IntoValue for NamedTuple<Value> {
+    fn tjlang_type() -> Type {
+        <NamedTuple<Value> as FromValue>::tjlang_type()
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        let fields = self.fields().iter().cloned().zip(self.values().iter().cloned()).collect();
+        Ok(Value::new_struct("NamedTuple", fields))
+    }
+}
+
+impl IntoValue for GcStats {
+    fn tjlang_type() -> Type {
+        Type::Identifier("GcStats".to_string())
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        Ok(struct_value(
+            "GcStats",
+            vec![
+                ("collections", self.total_collections.into_value(call)?),
+                ("full_collections", self.full_collections.into_value(call)?),
+                ("objects_collected", self.objects_collected.into_value(call)?),
+                ("bytes_freed", self.bytes_freed.into_value(call)?),
+                ("young_objects", self.young_objects.into_value(call)?),
+                ("old_objects", self.old_objects.into_value(call)?),
+                ("collection_time", Value::Float(self.collection_time.as_secs_f64())),
+            ],
+        ))
+    }
+}
+
+impl IntoValue for DefaultDictValue {
+    fn tjlang_type() -> Type {
+        <DefaultDictValue as FromValue>::tjlang_type()
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(struct_value(
+            "DefaultDict",
+            vec![("default_factory", self.default_factory), ("data", Value::Map(self.data))],
+        ))
+    }
+}
+
+/// Test results are `TestResult` structs, with an `error` of None when the test passed
+impl IntoValue for TestResult {
+    fn tjlang_type() -> Type {
+        Type::Identifier("TestResult".to_string())
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        let (name, error, duration) = match self {
+            TestResult::Passed { name, duration } => (name, None, duration),
+            TestResult::Failed { name, error, duration } => (name, Some(error), duration),
+        };
+        Ok(struct_value(
+            "TestResult",
+            vec![
+                ("name", Value::String(name)),
+                ("passed", Value::Bool(error.is_none())),
+                ("error", error.into_value(call)?),
+                ("duration", Value::Float(duration.as_secs_f64())),
+            ],
+        ))
+    }
+}
+
+/// Objects with no TJLang value of their own are used through their methods
+macro_rules! native_into_value {
+    ($($ty:ident),*) => {$(
+        impl IntoValue for $ty {
+            fn tjlang_type() -> Type {
+                Type::Identifier(stringify!($ty).to_string())
+            }
+
+            fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+                Ok(Value::Native(NativeObject::new(Native::$ty(self))))
+            }
+        }
+    )*};
+}
+
+native_into_value!(ProgressBar, Spinner, Timer, Stopwatch, FileWatcher);
+
+/// Rust iterators are iterated lazily, producing a value per `next()`
+impl IntoValue for NativeSource {
+    fn tjlang_type() -> Type {
+        Type::Identifier("Iterator".to_string())
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::Iterator(new_iterator(IteratorState::Native(self))))
+    }
+}
+
+/// Line-delimited JSON files are iterated lazily, reading a line per value
+impl IntoValue for JsonLines {
+    fn tjlang_type() -> Type {
+        <NativeSource as IntoValue>::tjlang_type()
+    }
+
+    fn into_value(self, call: &NativeCall) -> Result<Value, String> {
+        NativeSource::new(self).into_value(call)
+    }
+}
+
+impl IntoValue for Generator {
+    fn tjlang_type() -> Type {
+        Type::Identifier("Generator".to_string())
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::Generator(self))
+    }
+}
+
+impl IntoValue for Arc<Regex> {
+    fn tjlang_type() -> Type {
+        Type::Identifier("Regex".to_string())
+    }
+
+    fn into_value(self, _call: &NativeCall) -> Result<Value, String> {
+        Ok(Value::Regex(self))
+    }
+}
+
+/// Sort a vector in place with one of the `Ord`-bounded sorts
+fn sort_in_place(vec: &Shared<Vec<Value>>, sort: fn(&mut Vec<Ordered>)) {
+    let mut items: Vec<Ordered> = vec.read().iter().cloned().map(Ordered).collect();
+    sort(&mut items);
+    *vec.write() = items.into_iter().map(|item| item.0).collect();
+}
+
+/// Rotate a vector in place, refusing to rotate past its length
+fn rotate(vec: &Shared<Vec<Value>>, by: usize, rotate: fn(&mut Vec<Value>, usize)) -> Result<(), String> {
+    let mut items = vec.write();
+    if by > items.len() {
+        return Err(format!("Cannot rotate a vector of length {} by {}", items.len(), by));
+    }
+    rotate(&mut items, by);
+    Ok(())
+}
+
+/// Move the elements of `other` to the end of `vec`
+fn append(vec: &Shared<Vec<Value>>, other: &Shared<Vec<Value>>) {
+    let mut items = other.read().clone();
+    COLLECTIONS::array_append(&mut vec.write(), &mut items);
+    if !vec.ptr_eq(other) {
+        other.write().clear();
+    }
+}
+
+/// TJLang functions passed as closures to a Rust function. Once a call fails,
+/// later calls return `None` without running, and `finish` reports the failure
+/// after the Rust function returns.
+struct Callbacks<'a> {
+    name: &'static str,
+    interpreter: RefCell<&'a mut Interpreter>,
+    error: RefCell<Option<String>>,
+}
+
+impl<'a> Callbacks<'a> {
+    fn new(name: &'static str, interpreter: &'a mut Interpreter) -> Self {
+        Callbacks {
+            name,
+            interpreter: RefCell::new(interpreter),
+            error: RefCell::new(None),
+        }
+    }
+
+    /// Call `function`, or return `None` if this or an earlier call failed
+    fn call(&self, function: &Value, args: &[Value]) -> Option<Value> {
+        if self.error.borrow().is_some() {
+            return None;
+        }
+        let result = self.interpreter.borrow_mut().call_function_value(function, args);
+        result.map_err(|error| self.fail(error)).ok()
+    }
+
+    /// Call `function` for a result of type `T`
+    fn call_as<T: FromValue>(&self, function: &Value, args: &[Value]) -> Option<T> {
+        let value = self.call(function, args)?;
+        let result = T::from_value(&value);
+        if result.is_none() {
+            self.fail(format!(
+                "{} expects its function to return {}, got {}",
+                self.name,
+                T::expected(),
+                format_type(&value.get_type())
+            ));
+        }
+        result
+    }
+
+    /// The result of `function`, or None once a call has failed
+    fn value(&self, function: &Value, args: &[Value]) -> Value {
+        self.call(function, args).unwrap_or(Value::None)
+    }
+
+    /// Whether `predicate` returns a truthy value
+    fn test(&self, predicate: &Value, args: &[Value]) -> bool {
+        self.call(predicate, args).is_some_and(|value| value.is_truthy())
+    }
+
+    /// `function` as a function of floats, returning NaN once a call has failed
+    fn float(&self, function: &Value, x: f64) -> f64 {
+        self.call_as(function, &[Value::Float(x)]).unwrap_or(f64::NAN)
+    }
+
+    /// The order `compare` gives two values, as a negative, zero or positive int
+    fn order(&self, compare: &Value, a: &Value, b: &Value) -> Ordering {
+        self.call_as::<i64>(compare, &[a.clone(), b.clone()])
+            .map_or(Ordering::Equal, |order| order.cmp(&0))
+    }
+
+    fn fail(&self, error: String) {
+        self.error.borrow_mut().get_or_insert(error);
+    }
+
+    /// The Rust function's result, or the error of the call that failed
+    fn finish<T>(self, result: T) -> Result<T, String> {
+        match self.error.into_inner() {
+            Some(error) => Err(error),
+            None => Ok(result),
+        }
+    }
+}
+
+/// Rearrange a copy of a vector with `f`, whose closures call back into
+/// TJLang, and store it back if every call succeeded. Rust's sorts may panic
+/// when the comparisons are not a total order, which a TJLang function need
+/// not give.
+fn rearrange<R>(
+    callbacks: Callbacks,
+    vec: &Shared<Vec<Value>>,
+    f: impl FnOnce(&mut Vec<Value>, &Callbacks) -> R,
+) -> Result<R, String> {
+    let mut items = vec.read().clone();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(&mut items, &callbacks)));
+    let name = callbacks.name;
+    let result = callbacks
+        .finish(result)?
+        .map_err(|_| format!("{} expects a function that orders the elements consistently", name))?;
+    *vec.write() = items;
+    Ok(result)
+}
+
+/// Look up a key in a default dict, adding the value `default_factory` makes
+/// when it is missing
+fn default_dict_get(interpreter: &mut Interpreter, dict: DefaultDictValue, key: Value) -> Result<Value, String> {
+    let callbacks = Callbacks::new("COLLECTIONS::default_dict_get", interpreter);
+    let mut native = dict.to_native(|| callbacks.value(&dict.default_factory, &[]));
+    let value = COLLECTIONS::default_dict_get(&mut native, key);
+    let entries = native.into_map();
+    let value = callbacks.finish(value)?;
+    *dict.data.write() = entries;
+    Ok(value)
+}
+
+/// Check that a search can narrow `[a, b]` to within `tol`, which must be wider
+/// than the floats around the bounds can tell apart
+fn check_tolerance(function: &str, a: f64, b: f64, tol: f64) -> Result<(), String> {
+    let finest = a.abs().max(b.abs()) * f64::EPSILON * 4.0;
+    if tol > finest {
+        Ok(())
+    } else {
+        Err(format!("{} expects a tolerance above {:e}, got {}", function, finest, tol))
+    }
+}
+
+fn vector3(values: &[f64]) -> Result<[f64; 3], String> {
+    values
+        .try_into()
+        .map_err(|_| format!("MATH::cross_product expects 3-element vectors, got {} elements", values.len()))
+}
+
+/// Check that a matrix is non-empty and that its rows have the same length
+fn check_matrix(function: &str, matrix: &[Vec<f64>]) -> Result<(), String> {
+    match matrix.first() {
+        Some(first) if !first.is_empty() && matrix.iter().all(|row| row.len() == first.len()) => Ok(()),
+        _ => Err(format!("{} expects a non-empty matrix with rows of equal length", function)),
+    }
+}
+
+fn matrix_multiply(a: &[Vec<f64>], b: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, String> {
+    check_matrix("MATH::matrix_multiply", a)?;
+    check_matrix("MATH::matrix_multiply", b)?;
+    if a[0].len() != b.len() {
+        return Err(format!(
+            "MATH::matrix_multiply cannot multiply a {}x{} matrix by a {}x{} matrix",
+            a.len(),
+            a[0].len(),
+            b.len(),
+            b[0].len()
+        ));
+    }
+    Ok(MATH::matrix_multiply(a, b))
+}
+
+fn matrix_determinant(matrix: &[Vec<f64>]) -> Result<f64, String> {
+    check_matrix("MATH::matrix_determinant", matrix)?;
+    if matrix.len() != matrix[0].len() {
+        return Err("MATH::matrix_determinant expects a square matrix".to_string());
+    }
+    Ok(MATH::matrix_determinant(matrix))
+}
+
+fn median(values: Vec<f64>) -> Result<f64, String> {
+    if values.iter().any(|value| value.is_nan()) {
+        return Err("MATH::median cannot order NaN".to_string());
+    }
+    let mut values = values;
+    Ok(MATH::median(&mut values))
+}
+
+fn lcm(a: i64, b: i64) -> Result<i64, String> {
+    if a == 0 || b == 0 {
+        return Ok(0);
+    }
+    (a / MATH::gcd(a, b))
+        .checked_mul(b)
+        .and_then(i64::checked_abs)
+        .ok_or_else(|| format!("Integer overflow in MATH::lcm({}, {})", a, b))
+}
+
+/// Move a timestamp by `amount` units of `unit` seconds
+fn shift_seconds(timestamp: u64, amount: i64, unit: i64) -> Result<u64, String> {
+    amount
+        .checked_mul(unit)
+        .filter(|seconds| (timestamp as i64).checked_add(*seconds).is_some_and(|moved| moved >= 0))
+        .map(|seconds| TIME::add_seconds(timestamp, seconds))
+        .ok_or_else(|| "Timestamp out of range".to_string())
+}
+
+/// Move a timestamp by whole months
+fn shift_months(timestamp: u64, months: u32) -> Result<u64, String> {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .and_then(|datetime| datetime.checked_add_months(chrono::Months::new(months)))
+        .map(|datetime| datetime.timestamp() as u64)
+        .ok_or_else(|| "Timestamp out of range".to_string())
+}
+
+/// Whether a binding parameter has a default
+macro_rules! binding_optional {
+    () => {
+        false
+    };
+    ($default:expr) => {
+        true
+    };
+}
+
+/// The value of an omitted argument
+macro_rules! binding_default {
+    () => {
+        unreachable!("arity is checked before arguments are converted")
+    };
+    ($default:expr) => {
+        $default
+    };
+}
+
+macro_rules! binding_rest {
+    () => {
+        None
+    };
+    ($rest:ident: $rest_ty:ty) => {
+        Some(StdlibParam {
+            name: stringify!($rest),
+            ty: <$rest_ty as FromValue>::tjlang_type(),
+            optional: true,
+        })
+    };
+}
+
+/// Declare the stdlib functions. Generates `register_bindings`, which adds a
+/// native function per entry to the registry, and `binding_signatures`.
+macro_rules! stdlib_bindings {
+    ($(
+        $module:ident {
+            $(
+                $(#[doc = $doc:literal])*
+                fn $([$interpreter:ident])? $name:ident(
+                    $($param:ident: $ty:ty $(= $default:expr)?),* $(, ..$rest:ident: $rest_ty:ty)?
+                ) -> $ret:ty = $body:expr;
+            )*
+        }
+    )*) => {
+        fn register_bindings(functions: &mut HashMap<String, NativeFunction>) {
+            $($({
+                #[allow(unused_mut, unused_variables, clippy::let_unit_value)]
+                fn native(interpreter: &mut Interpreter, args: &[Value]) -> Result<Value, String> {
+                    const NAME: &str = concat!(stringify!($module), "::", stringify!($name));
+                    let optional = [$(binding_optional!($($default)?)),*];
+                    check_arity(NAME, &optional, binding_optional!($($rest)?), args)?;
+                    let mut remaining = args.iter();
+                    $(
+                        let $param: $ty = match remaining.next() {
+                            Some(value) => argument(NAME, stringify!($param), value)?,
+                            None => binding_default!($($default)?),
+                        };
+                    )*
+                    $(
+                        let $rest = remaining
+                            .map(|value| argument::<$rest_ty>(NAME, stringify!($rest), value))
+                            .collect::<Result<Vec<_>, String>>()?;
+                    )?
+                    $(let $interpreter = &mut *interpreter;)?
+                    let result: $ret = $body;
+                    result.into_value(&NativeCall { interpreter, name: NAME, args })
+                }
+                functions.insert(
+                    concat!(stringify!($module), "::", stringify!($name)).to_string(),
+                    native,
+                );
+            })*)*
+        }
+
+        fn binding_signatures() -> Vec<StdlibSignature> {
+            vec![$($(
+                StdlibSignature {
+                    module: stringify!($module),
+                    name: stringify!($name),
+                    params: vec![$(StdlibParam {
+                        name: stringify!($param),
+                        ty: <$ty as FromValue>::tjlang_type(),
+                        optional: binding_optional!($($default)?),
+                    }),*],
+                    rest: binding_rest!($($rest: $rest_ty)?),
+                    return_type: <$ret as IntoValue>::tjlang_type(),
+                    doc: &[$($doc),*],
+                },
+            )*)*]
+        }
+    };
+}
+
+stdlib_bindings! {
+    IO {
+        /// Print a value without a trailing newline
+        fn print(value: Value) -> Result<(), String> = IO::print(&value);
+        /// Print a value and a newline; with no value, print an empty line
+        fn println(value: Value = Value::String(String::new())) -> Result<(), String> = IO::println(&value);
+        /// Print a format string, replacing each `{}` with the next argument
+        fn printf(format: String, ..args: Value) -> Result<(), String> = IO::printf(&format, &args);
+        /// Read a line from standard input, without its line ending
+        fn read_line() -> Result<String, String> = IO::read_line();
+        /// Read one character from standard input
+        fn read_char() -> Result<char, String> = IO::read_char();
+        /// Read a line from standard input as an int
+        fn read_int() -> Result<i64, String> = IO::read_int();
+        /// Read a line from standard input as a float
+        fn read_float() -> Result<f64, String> = IO::read_float();
+        /// Read a line from standard input as a bool (true/false, yes/no, 1/0, on/off)
+        fn read_bool() -> Result<bool, String> = IO::read_bool();
+        /// Print text in red, green, yellow, blue, magenta, cyan, white or black
+        fn print_color(text: String, color: String) -> Result<(), String> = IO::print_color(&text, color.as_str());
+        /// Print an error message to standard error
+        fn print_error(message: String) -> Result<(), String> = IO::print_error(&message);
+        /// Print a warning message
+        fn print_warning(message: String) -> Result<(), String> = IO::print_warning(&message);
+        /// Print a success message
+        fn print_success(message: String) -> Result<(), String> = IO::print_success(&message);
+        /// Print an informational message
+        fn print_info(message: String) -> Result<(), String> = IO::print_info(&message);
+        /// Print a debug message
+        fn print_debug(message: String) -> Result<(), String> = IO::print_debug(&message);
+        /// Clear the terminal
+        fn clear_screen() -> Result<(), String> = IO::clear_screen();
+        /// Move the terminal cursor to a row and column
+        fn move_cursor(row: u16, col: u16) -> Result<(), String> = IO::move_cursor(row, col);
+        /// Hide the terminal cursor
+        fn hide_cursor() -> Result<(), String> = IO::hide_cursor();
+        /// Show the terminal cursor
+        fn show_cursor() -> Result<(), String> = IO::show_cursor();
+        /// The terminal's size as (columns, rows)
+        fn get_terminal_size() -> Result<(u16, u16), String> = IO::get_terminal_size();
+        /// Whether standard output is a terminal
+        fn is_terminal() -> bool = IO::is_terminal();
+        /// Whether standard input is a terminal
+        fn is_input_terminal() -> bool = IO::is_input_terminal();
+        /// A progress bar counting up to `total`, with `update`, `increment` and `finish` methods
+        fn create_progress_bar(total: u64) -> ProgressBar = IO::create_progress_bar(total);
+        /// A spinner, with `spin` and `stop` methods
+        fn create_spinner() -> Spinner = IO::create_spinner();
+        /// Print a message and read the reply
+        fn prompt(message: String) -> Result<String, String> = IO::prompt(&message);
+        /// Print a message and read the reply, using `default` for an empty reply
+        fn prompt_with_default(message: String, default: String) -> Result<String, String> =
+            IO::prompt_with_default(&message, &default);
+        /// Ask a yes/no question
+        fn confirm(message: String) -> Result<bool, String> = IO::confirm(&message);
+        /// Ask the user to pick one option; returns its index
+        fn select(message: String, options: Vec<String>) -> Result<usize, String> = IO::select(&message, &options);
+        /// Ask the user to pick any number of options; returns their indices
+        fn multi_select(message: String, options: Vec<String>) -> Result<Vec<usize>, String> =
+            IO::multi_select(&message, &options);
+    }
+
+    FILE {
+        /// Read a whole file as a string
+        fn read_to_string(path: String) -> Result<String, String> = FILE::read_to_string(&path);
+        /// Read a whole file as bytes
+        fn read_to_bytes(path: String) -> Result<Vec<u8>, String> = FILE::read_to_bytes(&path);
+        /// Write a string to a file, replacing its contents
+        fn write_string(path: String, content: String) -> Result<(), String> = FILE::write_string(&path, &content);
+        /// Write bytes to a file, replacing its contents
+        fn write_bytes(path: String, content: Vec<u8>) -> Result<(), String> = FILE::write_bytes(&path, &content);
+        /// Append a string to a file
+        fn append_string(path: String, content: String) -> Result<(), String> = FILE::append_string(&path, &content);
+        /// Append bytes to a file
+        fn append_bytes(path: String, content: Vec<u8>) -> Result<(), String> = FILE::append_bytes(&path, &content);
+        /// Copy a file; returns the number of bytes copied
+        fn copy(src: String, dst: String) -> Result<u64, String> = FILE::copy(&src, &dst);
+        /// Move or rename a file
+        fn move_file(src: String, dst: String) -> Result<(), String> = FILE::move_file(&src, &dst);
+        /// Delete a file
+        fn delete(path: String) -> Result<(), String> = FILE::delete(&path);
+        /// Delete a directory and everything in it
+        fn delete_dir(path: String) -> Result<(), String> = FILE::delete_dir(&path);
+        /// Create a directory
+        fn create_dir(path: String) -> Result<(), String> = FILE::create_dir(&path);
+        /// Create a directory and any missing parents
+        fn create_dir_all(path: String) -> Result<(), String> = FILE::create_dir_all(&path);
+        /// Whether a path exists
+        fn exists(path: String) -> bool = FILE::exists(&path);
+        /// Whether a path is a file
+        fn is_file(path: String) -> bool = FILE::is_file(&path);
+        /// Whether a path is a directory
+        fn is_dir(path: String) -> bool = FILE::is_dir(&path);
+        /// Whether a path is a symbolic link
+        fn is_symlink(path: String) -> bool = FILE::is_symlink(&path);
+        /// A file's size in bytes
+        fn size(path: String) -> Result<u64, String> = FILE::size(&path);
+        /// A file's size, kind, times and permissions
+        fn metadata(path: String) -> Result<FileMetadata, String> = FILE::metadata(&path);
+        /// Set a file's Unix permission bits
+        fn set_permissions(path: String, permissions: u32) -> Result<(), String> =
+            FILE::set_permissions(&path, permissions);
+        /// A file's Unix permission bits
+        fn get_permissions(path: String) -> Result<u32, String> = FILE::get_permissions(&path);
+        /// Set a file's modification time, in seconds since the Unix epoch
+        fn set_modified_time(path: String, time: SystemTime) -> Result<(), String> =
+            FILE::set_modified_time(&path, time);
+        /// A file's modification time, in seconds since the Unix epoch
+        fn get_modified_time(path: String) -> Result<SystemTime, String> = FILE::get_modified_time(&path);
+        /// A file's creation time, in seconds since the Unix epoch
+        fn get_created_time(path: String) -> Result<SystemTime, String> = FILE::get_created_time(&path);
+        /// A file's last access time, in seconds since the Unix epoch
+        fn get_accessed_time(path: String) -> Result<SystemTime, String> = FILE::get_accessed_time(&path);
+        /// The names of the entries in a directory
+        fn list_dir(path: String) -> Result<Vec<String>, String> = FILE::list_dir(&path);
+        /// The entries in a directory with their metadata
+        fn list_dir_with_metadata(path: String) -> Result<Vec<DirEntry>, String> = FILE::list_dir_with_metadata(&path);
+        /// Names of the entries in a directory matching a glob such as `*.txt`
+        fn find_files(path: String, pattern: String) -> Result<Vec<String>, String> = FILE::find_files(&path, &pattern);
+        /// Paths of the files under a directory whose names match a glob; a glob
+        /// with a `/`, such as `src/**/*.rs`, matches the path below the directory
+        fn find_files_recursive(path: String, pattern: String) -> Result<Vec<String>, String> =
+            FILE::find_files_recursive(&path, &pattern);
+        /// Create a symbolic link at `dst` pointing to `src`
+        fn create_symlink(src: String, dst: String) -> Result<(), String> = FILE::create_symlink(&src, &dst);
+        /// The target of a symbolic link
+        fn read_symlink(path: String) -> Result<String, String> = FILE::read_symlink(&path);
+        /// The absolute form of a path
+        fn absolute_path(path: String) -> Result<String, String> = FILE::absolute_path(&path);
+        /// A path relative to `base`
+        fn relative_path(path: String, base: String) -> Result<String, String> = FILE::relative_path(&path, &base);
+        /// A path's extension, without the dot
+        fn extension(path: String) -> Option<String> = FILE::extension(&path);
+        /// A path's file name without its extension
+        fn stem(path: String) -> Option<String> = FILE::stem(&path);
+        /// A path's last component
+        fn filename(path: String) -> Option<String> = FILE::filename(&path);
+        /// A path's parent directory
+        fn parent(path: String) -> Option<String> = FILE::parent(&path);
+        /// Join two paths
+        fn join(path1: String, path2: String) -> String = FILE::join(&path1, &path2);
+        /// Resolve `.` and `..` components of a path
+        fn normalize(path: String) -> String = FILE::normalize(&path);
+        /// The current working directory
+        fn current_dir() -> Result<String, String> = FILE::current_dir();
+        /// Change the current working directory
+        fn change_dir(path: String) -> Result<(), String> = FILE::change_dir(&path);
+        /// The user's home directory
+        fn home_dir() -> Option<String> = FILE::home_dir();
+        /// The system's temporary directory
+        fn temp_dir() -> String = FILE::temp_dir();
+        /// Create an empty temporary file; returns its path
+        fn create_temp_file() -> Result<String, String> = FILE::create_temp_file();
+        /// Create an empty temporary directory; returns its path
+        fn create_temp_dir() -> Result<String, String> = FILE::create_temp_dir();
+        /// A watcher for changes to a file, with `start`, `stop` and `is_watching` methods.
+        /// Watching is not implemented yet, so `callback` is never called.
+        fn watch_file(path: String, callback: Value) -> Result<FileWatcher, String> =
+            FILE::watch_file(&path, Box::new(|| Ok(())));
+        /// Pack files into an archive
+        fn archive(files: Vec<String>, archive_path: String) -> Result<(), String> = FILE::archive(&files, &archive_path);
+        /// Unpack an archive into a directory
+        fn extract(archive_path: String, extract_to: String) -> Result<(), String> = FILE::extract(&archive_path, &extract_to);
+        /// Hash a file's contents with md5, sha1, sha256 or sha512
+        fn hash(path: String, algorithm: HashAlgorithm) -> Result<String, String> = FILE::hash(&path, algorithm);
+        /// Whether two files have the same contents
+        fn compare(file1: String, file2: String) -> Result<bool, String> = FILE::compare(&file1, &file2);
+        /// A file's kind: text, binary, image, video, audio, archive, executable or unknown
+        fn get_file_type(path: String) -> Result<FileType, String> = FILE::get_file_type(&path);
+    }
+
+    MATH {
+        /// Add two numbers
+        fn add(a: f64, b: f64) -> f64 = MATH::add(a, b);
+        /// Subtract `b` from `a`
+        fn subtract(a: f64, b: f64) -> f64 = MATH::subtract(a, b);
+        /// Multiply two numbers
+        fn multiply(a: f64, b: f64) -> f64 = MATH::multiply(a, b);
+        /// Divide `a` by `b`
+        fn divide(a: f64, b: f64) -> f64 = MATH::divide(a, b);
+        /// The remainder of dividing `a` by `b`
+        fn modulo(a: f64, b: f64) -> f64 = MATH::modulo(a, b);
+        /// `a` raised to the power `b`
+        fn power(a: f64, b: f64) -> f64 = MATH::power(a, b);
+        /// Square root
+        fn sqrt(a: f64) -> f64 = MATH::sqrt(a);
+        /// Cube root
+        fn cbrt(a: f64) -> f64 = MATH::cbrt(a);
+        /// Absolute value
+        fn abs(a: f64) -> f64 = MATH::abs(a);
+        /// 1.0 for positive numbers, -1.0 for negative ones
+        fn sign(a: f64) -> f64 = MATH::sign(a);
+        /// Round down
+        fn floor(a: f64) -> f64 = MATH::floor(a);
+        /// Round up
+        fn ceil(a: f64) -> f64 = MATH::ceil(a);
+        /// Round to the nearest integer, halves away from zero
+        fn round(a: f64) -> f64 = MATH::round(a);
+        /// Round toward zero
+        fn trunc(a: f64) -> f64 = MATH::trunc(a);
+        /// The fractional part
+        fn fract(a: f64) -> f64 = MATH::fract(a);
+        /// Sine of an angle in radians
+        fn sin(a: f64) -> f64 = MATH::sin(a);
+        /// Cosine of an angle in radians
+        fn cos(a: f64) -> f64 = MATH::cos(a);
+        /// Tangent of an angle in radians
+        fn tan(a: f64) -> f64 = MATH::tan(a);
+        /// Arcsine, in radians
+        fn asin(a: f64) -> f64 = MATH::asin(a);
+        /// Arccosine, in radians
+        fn acos(a: f64) -> f64 = MATH::acos(a);
+        /// Arctangent, in radians
+        fn atan(a: f64) -> f64 = MATH::atan(a);
+        /// The angle of the point (x, y), in radians
+        fn atan2(y: f64, x: f64) -> f64 = MATH::atan2(y, x);
+        /// Hyperbolic sine
+        fn sinh(a: f64) -> f64 = MATH::sinh(a);
+        /// Hyperbolic cosine
+        fn cosh(a: f64) -> f64 = MATH::cosh(a);
+        /// Hyperbolic tangent
+        fn tanh(a: f64) -> f64 = MATH::tanh(a);
+        /// Inverse hyperbolic sine
+        fn asinh(a: f64) -> f64 = MATH::asinh(a);
+        /// Inverse hyperbolic cosine
+        fn acosh(a: f64) -> f64 = MATH::acosh(a);
+        /// Inverse hyperbolic tangent
+        fn atanh(a: f64) -> f64 = MATH::atanh(a);
+        /// Natural logarithm
+        fn ln(a: f64) -> f64 = MATH::ln(a);
+        /// Base-10 logarithm
+        fn log10(a: f64) -> f64 = MATH::log10(a);
+        /// Base-2 logarithm
+        fn log2(a: f64) -> f64 = MATH::log2(a);
+        /// Logarithm in any base
+        fn log(a: f64, base: f64) -> f64 = MATH::log(a, base);
+        /// e raised to a power
+        fn exp(a: f64) -> f64 = MATH::exp(a);
+        /// 2 raised to a power
+        fn exp2(a: f64) -> f64 = MATH::exp2(a);
+        /// `exp(a) - 1`, accurate near zero
+        fn exp_m1(a: f64) -> f64 = MATH::exp_m1(a);
+        /// `ln(1 + a)`, accurate near zero
+        fn ln_1p(a: f64) -> f64 = MATH::ln_1p(a);
+        /// The arithmetic mean; 0.0 for an empty vector
+        fn mean(values: Vec<f64>) -> f64 = MATH::mean(&values);
+        /// The middle value; 0.0 for an empty vector
+        fn median(values: Vec<f64>) -> Result<f64, String> = median(values);
+        /// The most common value
+        fn mode(values: Vec<f64>) -> f64 = MATH::mode(&values);
+        /// The population variance
+        fn variance(values: Vec<f64>) -> f64 = MATH::variance(&values);
+        /// The population standard deviation
+        fn std_dev(values: Vec<f64>) -> f64 = MATH::std_dev(&values);
+        /// The smallest value
+        fn min(values: Vec<f64>) -> f64 = MATH::min(&values);
+        /// The largest value
+        fn max(values: Vec<f64>) -> f64 = MATH::max(&values);
+        /// The sum of the values
+        fn sum(values: Vec<f64>) -> f64 = MATH::sum(&values);
+        /// The product of the values
+        fn product(values: Vec<f64>) -> f64 = MATH::product(&values);
+        /// The dot product of two vectors
+        fn dot_product(a: Vec<f64>, b: Vec<f64>) -> f64 = MATH::dot_product(&a, &b);
+        /// The cross product of two 3-element vectors
+        fn cross_product(a: Vec<f64>, b: Vec<f64>) -> Vec<f64> =
+            MATH::cross_product(&vector3(&a)?, &vector3(&b)?).to_vec();
+        /// The length of a vector
+        fn magnitude(vector: Vec<f64>) -> f64 = MATH::magnitude(&vector);
+        /// A vector scaled to length 1
+        fn normalize(vector: Vec<f64>) -> Vec<f64> = MATH::normalize(&vector);
+        /// Greatest common divisor
+        fn gcd(a: i64, b: i64) -> i64 = MATH::gcd(a, b);
+        /// Least common multiple
+        fn lcm(a: i64, b: i64) -> Result<i64, String> = lcm(a, b);
+        /// Whether a number is prime
+        fn is_prime(n: u64) -> bool = MATH::is_prime(n);
+        /// `n!`; results too big for an int follow the overflow policy
+        fn factorial(n: u64) -> BigInt = MATH::factorial(n);
+        /// The `n`th Fibonacci number; results too big for an int follow the overflow policy
+        fn fibonacci(n: u64) -> BigInt = MATH::fibonacci(n);
+        /// The derivative of `f` at `x`, by central difference with step `h`
+        fn [interpreter] derivative(f: Value, x: f64, h: f64) -> Result<f64, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = MATH::derivative(|x| callbacks.float(&f, x), x, h);
+            callbacks.finish(result)
+        };
+        /// The integral of `f` from `a` to `b`, summing `n` rectangles
+        fn [interpreter] integral(f: Value, a: f64, b: f64, n: u32) -> Result<f64, String> = {
+            if n == 0 {
+                return Err(format!("{} expects at least 1 rectangle", NAME));
+            }
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = MATH::integral(|x| callbacks.float(&f, x), a, b, n);
+            callbacks.finish(result)
+        };
+        /// The normal distribution's probability density at `x`
+        fn normal_pdf(x: f64, mean: f64, std_dev: f64) -> f64 = MATH::normal_pdf(x, mean, std_dev);
+        /// The normal distribution's cumulative probability at `x`
+        fn normal_cdf(x: f64, mean: f64, std_dev: f64) -> f64 = MATH::normal_cdf(x, mean, std_dev);
+        /// The error function
+        fn erf(x: f64) -> f64 = MATH::erf(x);
+        /// The `x` in `[a, b]` where `f` is lowest, for `f` with a single minimum there
+        fn [interpreter] golden_section_search(f: Value, a: f64, b: f64, tol: f64) -> Result<f64, String> = {
+            check_tolerance(NAME, a, b, tol)?;
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = MATH::golden_section_search(|x| callbacks.float(&f, x), a, b, tol);
+            callbacks.finish(result)
+        };
+        /// A root of `f` by Newton's method from `x0`, given its derivative `f_prime`
+        fn [interpreter] newton_raphson(f: Value, f_prime: Value, x0: f64, tol: f64, max_iter: u32) -> Result<f64, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = MATH::newton_raphson(|x| callbacks.float(&f, x), |x| callbacks.float(&f_prime, x), x0, tol, max_iter);
+            callbacks.finish(result)
+        };
+        /// A root of `f` in `[a, b]` by bisection, for `f(a)` and `f(b)` of opposite signs
+        fn [interpreter] bisection(f: Value, a: f64, b: f64, tol: f64) -> Result<f64, String> = {
+            check_tolerance(NAME, a, b, tol)?;
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = MATH::bisection(|x| callbacks.float(&f, x), a, b, tol);
+            callbacks.finish(result)
+        };
+        /// The product of two matrices
+        fn matrix_multiply(a: Vec<Vec<f64>>, b: Vec<Vec<f64>>) -> Result<Vec<Vec<f64>>, String> = matrix_multiply(&a, &b);
+        /// A matrix with its rows and columns swapped
+        fn matrix_transpose(matrix: Vec<Vec<f64>>) -> Vec<Vec<f64>> = {
+            check_matrix("MATH::matrix_transpose", &matrix)?;
+            MATH::matrix_transpose(&matrix)
+        };
+        /// The determinant of a square matrix
+        fn matrix_determinant(matrix: Vec<Vec<f64>>) -> Result<f64, String> = matrix_determinant(&matrix);
+        /// Whether a number is neither infinite nor NaN
+        fn is_finite(x: f64) -> bool = MATH::is_finite(x);
+        /// Whether a number is infinite
+        fn is_infinite(x: f64) -> bool = MATH::is_infinite(x);
+        /// Whether a number is NaN
+        fn is_nan(x: f64) -> bool = MATH::is_nan(x);
+        /// `x` limited to the range [min, max]
+        fn clamp(x: f64, min: f64, max: f64) -> f64 = MATH::clamp(x, min, max);
+        /// Linear interpolation from `a` to `b`
+        fn lerp(a: f64, b: f64, t: f64) -> f64 = MATH::lerp(a, b, t);
+        /// Smooth Hermite interpolation between two edges
+        fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 = MATH::smoothstep(edge0, edge1, x);
+        /// π
+        fn PI() -> f64 = MATH::PI;
+        /// Euler's number
+        fn E() -> f64 = MATH::E;
+        /// 2π
+        fn TAU() -> f64 = MATH::TAU;
+        /// √2
+        fn SQRT_2() -> f64 = MATH::SQRT_2;
+        /// √π
+        fn SQRT_PI() -> f64 = MATH::SQRT_PI;
+        /// ln 2
+        fn LN_2() -> f64 = MATH::LN_2;
+        /// ln 10
+        fn LN_10() -> f64 = MATH::LN_10;
+        /// log₂ e
+        fn LOG2_E() -> f64 = MATH::LOG2_E;
+        /// log₁₀ e
+        fn LOG10_E() -> f64 = MATH::LOG10_E;
+        /// Positive infinity
+        fn INFINITY() -> f64 = MATH::INFINITY;
+        /// Negative infinity
+        fn NEG_INFINITY() -> f64 = MATH::NEG_INFINITY;
+        /// Not a number
+        fn NAN() -> f64 = MATH::NAN;
+    }
+
+    STRING {
+        /// Length in bytes
+        fn length(s: String) -> usize = STRING::length(&s);
+        /// Number of characters
+        fn char_count(s: String) -> usize = STRING::char_count(&s);
+        /// Number of bytes
+        fn byte_count(s: String) -> usize = STRING::byte_count(&s);
+        /// Convert to upper case
+        fn to_uppercase(s: String) -> String = STRING::to_uppercase(&s);
+        /// Convert to lower case
+        fn to_lowercase(s: String) -> String = STRING::to_lowercase(&s);
+        /// Capitalize the first letter of each word
+        fn to_titlecase(s: String) -> String = STRING::to_titlecase(&s);
+        /// Capitalize the first letter
+        fn capitalize(s: String) -> String = STRING::capitalize(&s);
+        /// Reverse the characters
+        fn reverse(s: String) -> String = STRING::reverse(&s);
+        /// Remove leading and trailing whitespace
+        fn trim(s: String) -> String = STRING::trim(&s);
+        /// Remove leading whitespace
+        fn trim_start(s: String) -> String = STRING::trim_start(&s);
+        /// Remove trailing whitespace
+        fn trim_end(s: String) -> String = STRING::trim_end(&s);
+        /// Whether `s` contains `pattern`
+        fn contains(s: String, pattern: String) -> bool = STRING::contains(&s, &pattern);
+        /// Whether `s` starts with `prefix`
+        fn starts_with(s: String, prefix: String) -> bool = STRING::starts_with(&s, &prefix);
+        /// Whether `s` ends with `suffix`
+        fn ends_with(s: String, suffix: String) -> bool = STRING::ends_with(&s, &suffix);
+        /// Byte index of the first occurrence of `pattern`
+        fn find(s: String, pattern: String) -> Option<usize> = STRING::find(&s, &pattern);
+        /// Byte index of the last occurrence of `pattern`
+        fn rfind(s: String, pattern: String) -> Option<usize> = STRING::rfind(&s, &pattern);
+        /// Byte indices of every occurrence of `pattern`
+        fn find_all(s: String, pattern: String) -> Vec<usize> = STRING::find_all(&s, &pattern);
+        /// Replace every occurrence of `from` with `to`
+        fn replace(s: String, from: String, to: String) -> String = STRING::replace(&s, &from, &to);
+        /// Replace the first occurrence of `from` with `to`
+        fn replace_first(s: String, from: String, to: String) -> String = STRING::replace_first(&s, &from, &to);
+        /// Replace the last occurrence of `from` with `to`
+        fn replace_last(s: String, from: String, to: String) -> String = STRING::replace_last(&s, &from, &to);
+        /// Split on a delimiter
+        fn split(s: String, delimiter: String) -> Vec<String> = STRING::split(&s, &delimiter);
+        /// Split on runs of whitespace
+        fn split_whitespace(s: String) -> Vec<String> = STRING::split_whitespace(&s);
+        /// Split into lines
+        fn split_lines(s: String) -> Vec<String> = STRING::split_lines(&s);
+        /// Split into characters
+        fn split_chars(s: String) -> Vec<String> = STRING::split_chars(&s);
+        /// Join strings with a separator
+        fn join(strings: Vec<String>, separator: String) -> String = STRING::join(&strings, &separator);
+        /// The characters from index `start` up to `end`
+        fn slice(s: String, start: usize, end: usize) -> String = STRING::slice(&s, start, end);
+        /// `length` characters starting at index `start`
+        fn substring(s: String, start: usize, length: usize) -> String = STRING::substring(&s, start, length);
+        /// Pad on the left to `width` characters
+        fn pad_left(s: String, width: usize, fill_char: char = ' ') -> String = STRING::pad_left(&s, width, fill_char);
+        /// Pad on the right to `width` characters
+        fn pad_right(s: String, width: usize, fill_char: char = ' ') -> String = STRING::pad_right(&s, width, fill_char);
+        /// Pad on both sides to `width` characters
+        fn pad_center(s: String, width: usize, fill_char: char = ' ') -> String =
+            STRING::pad_center(&s, width, fill_char);
+        /// Replace `{0}`, `{1}`, ... in a template with the arguments at those positions
+        fn format(template: String, ..args: Value) -> String =
+            STRING::format(&template, &args.iter().map(Value::to_string).collect::<Vec<_>>());
+        /// Replace each `{name}` in a template with its value in a map
+        fn format_named(template: String, args: HashMap<String, String>) -> String = STRING::format_named(&template, &args);
+        /// Whether a string is empty
+        fn is_empty(s: String) -> bool = STRING::is_empty(&s);
+        /// Whether every character is whitespace
+        fn is_whitespace(s: String) -> bool = STRING::is_whitespace(&s);
+        /// Whether every character is alphabetic
+        fn is_alpha(s: String) -> bool = STRING::is_alpha(&s);
+        /// Whether every character is numeric
+        fn is_numeric(s: String) -> bool = STRING::is_numeric(&s);
+        /// Whether every character is alphanumeric
+        fn is_alphanumeric(s: String) -> bool = STRING::is_alphanumeric(&s);
+        /// Whether every character is ASCII
+        fn is_ascii(s: String) -> bool = STRING::is_ascii(&s);
+        /// Whether every character is a decimal digit
+        fn is_digit(s: String) -> bool = STRING::is_digit(&s);
+        /// Whether every character is a hex digit
+        fn is_hex(s: String) -> bool = STRING::is_hex(&s);
+        /// Drop non-ASCII characters
+        fn to_ascii(s: String) -> String = STRING::to_ascii(&s);
+        /// The Unicode code point of each character
+        fn to_unicode(s: String) -> Vec<u32> = STRING::to_unicode(&s);
+        /// Build a string from Unicode code points
+        fn from_unicode(codes: Vec<u32>) -> String = STRING::from_unicode(&codes);
+        /// Encode as base64
+        fn encode_base64(s: String) -> String = STRING::encode_base64(&s);
+        /// Decode base64
+        fn decode_base64(s: String) -> Result<String, String> = STRING::decode_base64(&s);
+        /// Percent-encode for use in a URL
+        fn encode_url(s: String) -> String = STRING::encode_url(&s);
+        /// Decode a percent-encoded string
+        fn decode_url(s: String) -> Result<String, String> = STRING::decode_url(&s);
+        /// SHA-1 hash as hex
+        fn hash_sha1(s: String) -> String = STRING::hash_sha1(&s);
+        /// SHA-256 hash as hex
+        fn hash_sha256(s: String) -> String = STRING::hash_sha256(&s);
+        /// The number of single-character edits between two strings
+        fn levenshtein_distance(s1: String, s2: String) -> usize = STRING::levenshtein_distance(&s1, &s2);
+        /// Jaro similarity from 0.0 to 1.0
+        fn jaro_similarity(s1: String, s2: String) -> f64 = STRING::jaro_similarity(&s1, &s2);
+        /// Replace each `${name}` in a template with its value in a map
+        fn template(template: String, variables: HashMap<String, String>) -> String = STRING::template(&template, &variables);
+        /// Replace `$0`, `$1`, ... in a template with the values at those indices
+        fn interpolate(template: String, values: Vec<String>) -> String = STRING::interpolate(&template, &values);
+        /// Convert to snake_case
+        fn to_snake_case(s: String) -> String = STRING::to_snake_case(&s);
+        /// Convert to camelCase
+        fn to_camel_case(s: String) -> String = STRING::to_camel_case(&s);
+        /// Convert to PascalCase
+        fn to_pascal_case(s: String) -> String = STRING::to_pascal_case(&s);
+        /// Convert to kebab-case
+        fn to_kebab_case(s: String) -> String = STRING::to_kebab_case(&s);
+        /// Number of whitespace-separated words
+        fn word_count(s: String) -> usize = STRING::word_count(&s);
+        /// Number of lines
+        fn line_count(s: String) -> usize = STRING::line_count(&s);
+        /// How many times each character occurs
+        fn char_frequency(s: String) -> HashMap<char, usize> = STRING::char_frequency(&s);
+        /// How many times each word occurs
+        fn word_frequency(s: String) -> HashMap<String, usize> = STRING::word_frequency(&s);
+        /// Remove all whitespace
+        fn remove_whitespace(s: String) -> String = STRING::remove_whitespace(&s);
+        /// Remove all punctuation
+        fn remove_punctuation(s: String) -> String = STRING::remove_punctuation(&s);
+        /// Remove all digits
+        fn remove_digits(s: String) -> String = STRING::remove_digits(&s);
+        /// Remove all alphabetic characters
+        fn remove_alpha(s: String) -> String = STRING::remove_alpha(&s);
+        /// Collapse runs of whitespace into single spaces
+        fn normalize_whitespace(s: String) -> String = STRING::normalize_whitespace(&s);
+        /// Convert to lower case
+        fn normalize_case(s: String) -> String = STRING::normalize_case(&s);
+        /// Normalize Unicode
+        fn normalize_unicode(s: String) -> String = STRING::normalize_unicode(&s);
+        /// Whether two strings are equal ignoring case
+        fn equals_ignore_case(s1: String, s2: String) -> bool = STRING::equals_ignore_case(&s1, &s2);
+        /// -1, 0 or 1 as `s1` sorts before, equal to or after `s2`
+        fn compare(s1: String, s2: String) -> i32 = STRING::compare(&s1, &s2);
+        /// `compare`, ignoring case
+        fn compare_ignore_case(s1: String, s2: String) -> i32 = STRING::compare_ignore_case(&s1, &s2);
+    }
+
+    COLLECTIONS {
+        /// Create an empty vector
+        fn array_new() -> Vec<Value> = COLLECTIONS::array_new();
+        /// Create an empty vector with room for `capacity` elements
+        fn array_with_capacity(capacity: usize) -> Vec<Value> = COLLECTIONS::array_with_capacity(capacity);
+        /// Copy a vector
+        fn array_from_slice(values: Vec<Value>) -> Vec<Value> = COLLECTIONS::array_from_slice(&values);
+        /// Append an element and return the vector
+        fn array_push(vec: Shared<Vec<Value>>, item: Value) -> Shared<Vec<Value>> = {
+            COLLECTIONS::array_push(&mut vec.write(), item);
+            vec
+        };
+        /// Remove and return the last element
+        fn array_pop(vec: Shared<Vec<Value>>) -> Option<Value> = COLLECTIONS::array_pop(&mut vec.write());
+        /// Insert an element at an index
+        fn array_insert(vec: Shared<Vec<Value>>, index: usize, item: Value) -> Result<(), String> =
+            COLLECTIONS::array_insert(&mut vec.write(), index, item);
+        /// Remove and return the element at an index
+        fn array_remove(vec: Shared<Vec<Value>>, index: usize) -> Result<Value, String> =
+            COLLECTIONS::array_remove(&mut vec.write(), index);
+        /// Number of elements
+        fn array_len(vec: Vec<Value>) -> usize = COLLECTIONS::array_len(&vec);
+        /// Number of elements the vector can hold without reallocating
+        fn array_capacity(vec: Shared<Vec<Value>>) -> usize = COLLECTIONS::array_capacity(&vec.read());
+        /// Whether a vector is empty
+        fn array_is_empty(vec: Vec<Value>) -> bool = COLLECTIONS::array_is_empty(&vec);
+        /// The element at an index, or None
+        fn array_get(vec: Vec<Value>, index: usize) -> Option<Value> = COLLECTIONS::array_get(&vec, index).cloned();
+        /// The element at an index, or None; vectors, sets, maps and structs are shared, so changing it changes the element
+        fn array_get_mut(vec: Shared<Vec<Value>>, index: usize) -> Option<Value> =
+            COLLECTIONS::array_get_mut(&mut vec.write(), index).cloned();
+        /// Replace the element at an index
+        fn array_set(vec: Shared<Vec<Value>>, index: usize, item: Value) -> Result<(), String> =
+            COLLECTIONS::array_set(&mut vec.write(), index, item);
+        /// The elements from index `start` up to `end`
+        fn array_slice(vec: Vec<Value>, start: usize, end: usize) -> Vec<Value> = COLLECTIONS::array_slice(&vec, start, end);
+        /// Move every element of `other` to the end of `vec`
+        fn array_append(vec: Shared<Vec<Value>>, other: Shared<Vec<Value>>) -> () = append(&vec, &other);
+        /// Copy every element of `other` to the end of `vec`
+        fn array_extend(vec: Shared<Vec<Value>>, other: Vec<Value>) -> () = COLLECTIONS::array_extend(&mut vec.write(), &other);
+        /// Reverse a vector in place
+        fn array_reverse(vec: Shared<Vec<Value>>) -> () = COLLECTIONS::array_reverse(&mut vec.write());
+        /// Sort a vector in place
+        fn array_sort(vec: Shared<Vec<Value>>) -> () = sort_in_place(&vec, COLLECTIONS::array_sort);
+        /// Sort a vector in place by a function returning a negative, zero or positive int
+        fn [interpreter] array_sort_by(vec: Shared<Vec<Value>>, compare: Value) -> Result<(), String> =
+            rearrange(Callbacks::new(NAME, interpreter), &vec, |items, callbacks| {
+                COLLECTIONS::array_sort_by(items, |a, b| callbacks.order(&compare, a, b))
+            });
+        /// Shuffle a vector in place
+        fn [interpreter] array_shuffle(vec: Shared<Vec<Value>>) -> () =
+            COLLECTIONS::array_shuffle(&mut vec.write(), interpreter.random());
+        /// Remove duplicate elements in place
+        fn array_unique(vec: Shared<Vec<Value>>) -> () = COLLECTIONS::array_unique(&mut vec.write());
+        /// Whether a vector contains an element
+        fn array_contains(vec: Vec<Value>, item: Value) -> bool = COLLECTIONS::array_contains(&vec, &item);
+        /// Index of the first occurrence of an element
+        fn array_index_of(vec: Vec<Value>, item: Value) -> Option<usize> = COLLECTIONS::array_index_of(&vec, &item);
+        /// Index of the last occurrence of an element
+        fn array_last_index_of(vec: Vec<Value>, item: Value) -> Option<usize> = COLLECTIONS::array_last_index_of(&vec, &item);
+        /// Apply a function to every element
+        fn [interpreter] array_map(vec: Vec<Value>, mapper: Value) -> Result<Vec<Value>, String> = vec
+            .iter()
+            .map(|item| interpreter.call_function_value(&mapper, std::slice::from_ref(item)))
+            .collect();
+        /// The elements a function returns true for
+        fn [interpreter] array_filter(vec: Vec<Value>, predicate: Value) -> Result<Vec<Value>, String> = {
+            let mut kept = Vec::new();
+            for item in vec {
+                if interpreter.call_function_value(&predicate, std::slice::from_ref(&item))?.is_truthy() {
+                    kept.push(item);
+                }
+            }
+            Ok(kept)
+        };
+        /// Combine the elements into one value, calling `reducer(accumulator, element)` from `initial`
+        fn [interpreter] array_reduce(vec: Vec<Value>, initial: Value, reducer: Value) -> Result<Value, String> = vec
+            .into_iter()
+            .try_fold(initial, |accumulator, item| interpreter.call_function_value(&reducer, &[accumulator, item]));
+        /// Combine the elements into one value with a function of the value so far and the next element
+        fn [interpreter] array_fold(vec: Vec<Value>, initial: Value, folder: Value) -> Result<Value, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::array_fold(&vec, initial, |accumulator, item| {
+                callbacks.value(&folder, &[accumulator, item.clone()])
+            });
+            callbacks.finish(result)
+        };
+        /// Whether a function returns true for any element
+        fn [interpreter] array_any(vec: Vec<Value>, predicate: Value) -> Result<bool, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::array_any(&vec, |item| callbacks.test(&predicate, std::slice::from_ref(item)));
+            callbacks.finish(result)
+        };
+        /// Whether a function returns true for every element
+        fn [interpreter] array_all(vec: Vec<Value>, predicate: Value) -> Result<bool, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::array_all(&vec, |item| callbacks.test(&predicate, std::slice::from_ref(item)));
+            callbacks.finish(result)
+        };
+        /// The first element a function returns true for, as Some(element), or None
+        fn [interpreter] array_find(vec: Vec<Value>, predicate: Value) -> Result<OptionValue<Value>, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::array_find(&vec, |item| callbacks.test(&predicate, std::slice::from_ref(*item))).cloned();
+            callbacks.finish(OptionValue(result))
+        };
+        /// Index of the first element a function returns true for, or None
+        fn [interpreter] array_find_index(vec: Vec<Value>, predicate: Value) -> Result<Option<usize>, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::array_find_index(&vec, |item| callbacks.test(&predicate, std::slice::from_ref(item)));
+            callbacks.finish(result)
+        };
+        /// Create an empty map
+        fn map_new() -> HashMap<Value, Value> = COLLECTIONS::map_new();
+        /// Create an empty map with room for `capacity` entries
+        fn map_with_capacity(capacity: usize) -> HashMap<Value, Value> = COLLECTIONS::map_with_capacity(capacity);
+        /// Insert an entry; returns the value it replaced
+        fn map_insert(map: Shared<HashMap<Value, Value>>, key: Value, value: Value) -> Option<Value> =
+            COLLECTIONS::map_insert(&mut map.write(), key, value);
+        /// The value for a key, or None
+        fn map_get(map: Shared<HashMap<Value, Value>>, key: Value) -> Option<Value> = COLLECTIONS::map_get(&map.read(), &key).cloned();
+        /// The value for a key, or None; vectors, sets, maps and structs are shared, so changing it changes the entry
+        fn map_get_mut(map: Shared<HashMap<Value, Value>>, key: Value) -> Option<Value> =
+            COLLECTIONS::map_get_mut(&mut map.write(), &key).cloned();
+        /// Remove an entry; returns its value
+        fn map_remove(map: Shared<HashMap<Value, Value>>, key: Value) -> Option<Value> = COLLECTIONS::map_remove(&mut map.write(), &key);
+        /// Whether a map has a key
+        fn map_contains_key(map: Shared<HashMap<Value, Value>>, key: Value) -> bool = COLLECTIONS::map_contains_key(&map.read(), &key);
+        /// Number of entries
+        fn map_len(map: Shared<HashMap<Value, Value>>) -> usize = COLLECTIONS::map_len(&map.read());
+        /// Whether a map is empty
+        fn map_is_empty(map: Shared<HashMap<Value, Value>>) -> bool = COLLECTIONS::map_is_empty(&map.read());
+        /// The keys of a map
+        fn map_keys(map: Shared<HashMap<Value, Value>>) -> Vec<Value> =
+            COLLECTIONS::map_keys(&map.read()).into_iter().cloned().collect();
+        /// The values of a map
+        fn map_values(map: Shared<HashMap<Value, Value>>) -> Vec<Value> =
+            COLLECTIONS::map_values(&map.read()).into_iter().cloned().collect();
+        /// The entries of a map as (key, value) tuples
+        fn map_entries(map: Shared<HashMap<Value, Value>>) -> Vec<(Value, Value)> = COLLECTIONS::map_entries(&map.read())
+            .into_iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        /// Remove every entry
+        fn map_clear(map: Shared<HashMap<Value, Value>>) -> () = COLLECTIONS::map_clear(&mut map.write());
+        /// Create an empty set
+        fn set_new() -> HashSet<Value> = COLLECTIONS::set_new();
+        /// Create an empty set with room for `capacity` elements
+        fn set_with_capacity(capacity: usize) -> HashSet<Value> = COLLECTIONS::set_with_capacity(capacity);
+        /// The set of a vector's elements
+        fn set_from_vec(values: Vec<Value>) -> HashSet<Value> = COLLECTIONS::set_from_vec(values);
+        /// Add an element; returns whether it was new
+        fn set_insert(set: Shared<HashSet<Value>>, item: Value) -> bool = COLLECTIONS::set_insert(&mut set.write(), item);
+        /// Remove an element; returns whether it was present
+        fn set_remove(set: Shared<HashSet<Value>>, item: Value) -> bool = COLLECTIONS::set_remove(&mut set.write(), &item);
+        /// Whether a set contains an element
+        fn set_contains(set: HashSet<Value>, item: Value) -> bool = COLLECTIONS::set_contains(&set, &item);
+        /// Number of elements
+        fn set_len(set: HashSet<Value>) -> usize = COLLECTIONS::set_len(&set);
+        /// Whether a set is empty
+        fn set_is_empty(set: HashSet<Value>) -> bool = COLLECTIONS::set_is_empty(&set);
+        /// Elements in either set
+        fn set_union(set1: HashSet<Value>, set2: HashSet<Value>) -> HashSet<Value> = COLLECTIONS::set_union(&set1, &set2);
+        /// Elements in both sets
+        fn set_intersection(set1: HashSet<Value>, set2: HashSet<Value>) -> HashSet<Value> =
+            COLLECTIONS::set_intersection(&set1, &set2);
+        /// Elements of `set1` not in `set2`
+        fn set_difference(set1: HashSet<Value>, set2: HashSet<Value>) -> HashSet<Value> =
+            COLLECTIONS::set_difference(&set1, &set2);
+        /// Elements in exactly one of the sets
+        fn set_symmetric_difference(set1: HashSet<Value>, set2: HashSet<Value>) -> HashSet<Value> =
+            COLLECTIONS::set_symmetric_difference(&set1, &set2);
+        /// Whether every element of `set1` is in `set2`
+        fn set_is_subset(set1: HashSet<Value>, set2: HashSet<Value>) -> bool = COLLECTIONS::set_is_subset(&set1, &set2);
+        /// Whether every element of `set2` is in `set1`
+        fn set_is_superset(set1: HashSet<Value>, set2: HashSet<Value>) -> bool = COLLECTIONS::set_is_superset(&set1, &set2);
+        /// Whether the sets have no element in common
+        fn set_is_disjoint(set1: HashSet<Value>, set2: HashSet<Value>) -> bool = COLLECTIONS::set_is_disjoint(&set1, &set2);
+        /// Create an empty queue
+        fn queue_new() -> VecDeque<Value> = COLLECTIONS::queue_new();
+        /// Create an empty queue with room for `capacity` elements
+        fn queue_with_capacity(capacity: usize) -> VecDeque<Value> = COLLECTIONS::queue_with_capacity(capacity);
+        /// Add an element at the back
+        fn queue_push_back(queue: Shared<Vec<Value>>, item: Value) -> () =
+            with_backed(&queue, |queue| COLLECTIONS::queue_push_back(queue, item))?;
+        /// Add an element at the front
+        fn queue_push_front(queue: Shared<Vec<Value>>, item: Value) -> () =
+            with_backed(&queue, |queue| COLLECTIONS::queue_push_front(queue, item))?;
+        /// Remove and return the back element
+        fn queue_pop_back(queue: Shared<Vec<Value>>) -> Option<Value> = with_backed(&queue, COLLECTIONS::queue_pop_back)?;
+        /// Remove and return the front element
+        fn queue_pop_front(queue: Shared<Vec<Value>>) -> Option<Value> = with_backed(&queue, COLLECTIONS::queue_pop_front)?;
+        /// Number of elements
+        fn queue_len(queue: VecDeque<Value>) -> usize = COLLECTIONS::queue_len(&queue);
+        /// Whether a queue is empty
+        fn queue_is_empty(queue: VecDeque<Value>) -> bool = COLLECTIONS::queue_is_empty(&queue);
+        /// Create an empty priority queue; it lists its elements largest first
+        fn priority_queue_new() -> BinaryHeap<Ordered> = COLLECTIONS::priority_queue_new();
+        /// Create an empty priority queue with room for `capacity` elements
+        fn priority_queue_with_capacity(capacity: usize) -> BinaryHeap<Ordered> = COLLECTIONS::priority_queue_with_capacity(capacity);
+        /// Add an element
+        fn priority_queue_push(queue: Shared<Vec<Value>>, item: Value) -> () =
+            with_backed(&queue, |queue| COLLECTIONS::priority_queue_push(queue, Ordered(item)))?;
+        /// Remove and return the largest element
+        fn priority_queue_pop(queue: Shared<Vec<Value>>) -> Option<Ordered> = with_backed(&queue, COLLECTIONS::priority_queue_pop)?;
+        /// The largest element
+        fn priority_queue_peek(queue: BinaryHeap<Ordered>) -> Option<Ordered> = COLLECTIONS::priority_queue_peek(&queue).cloned();
+        /// Number of elements
+        fn priority_queue_len(queue: BinaryHeap<Ordered>) -> usize = COLLECTIONS::priority_queue_len(&queue);
+        /// Whether a priority queue is empty
+        fn priority_queue_is_empty(queue: BinaryHeap<Ordered>) -> bool = COLLECTIONS::priority_queue_is_empty(&queue);
+        /// Create an empty sorted map
+        fn btree_map_new() -> BTreeMap<Ordered, Value> = COLLECTIONS::btree_map_new();
+        /// Create an empty sorted set
+        fn btree_set_new() -> BTreeSet<Ordered> = COLLECTIONS::btree_set_new();
+        /// Number of elements
+        fn iterator_count(values: Vec<Value>) -> usize = COLLECTIONS::iterator_count(values.into_iter());
+        /// The results of calling a function on each value
+        fn [interpreter] iterator_map(values: Vec<Value>, mapper: Value) -> Result<Vec<Value>, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::iterator_map(values.into_iter(), |item| callbacks.value(&mapper, &[item])).collect();
+            callbacks.finish(result)
+        };
+        /// The values a function returns true for
+        fn [interpreter] iterator_filter(values: Vec<Value>, predicate: Value) -> Result<Vec<Value>, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::iterator_filter(values.into_iter(), |item| {
+                callbacks.test(&predicate, std::slice::from_ref(item))
+            })
+            .collect();
+            callbacks.finish(result)
+        };
+        /// Combine the values into one with a function of the value so far and the next value
+        fn [interpreter] iterator_fold(values: Vec<Value>, initial: Value, folder: Value) -> Result<Value, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::iterator_fold(values.into_iter(), initial, |accumulator, item| {
+                callbacks.value(&folder, &[accumulator, item])
+            });
+            callbacks.finish(result)
+        };
+        /// Combine the values into one with a function of the value so far and the next value,
+        /// starting from the first; None for no values
+        fn [interpreter] iterator_reduce(values: Vec<Value>, reducer: Value) -> Result<Option<Value>, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::iterator_reduce(values.into_iter(), |accumulator, item| {
+                callbacks.value(&reducer, &[accumulator, item])
+            });
+            callbacks.finish(result)
+        };
+        /// Whether a function returns true for any value
+        fn [interpreter] iterator_any(values: Vec<Value>, predicate: Value) -> Result<bool, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::iterator_any(values.into_iter(), |item| callbacks.test(&predicate, &[item]));
+            callbacks.finish(result)
+        };
+        /// Whether a function returns true for every value
+        fn [interpreter] iterator_all(values: Vec<Value>, predicate: Value) -> Result<bool, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::iterator_all(values.into_iter(), |item| callbacks.test(&predicate, &[item]));
+            callbacks.finish(result)
+        };
+        /// The first value a function returns true for, as Some(value), or None
+        fn [interpreter] iterator_find(values: Vec<Value>, predicate: Value) -> Result<OptionValue<Value>, String> = {
+            let callbacks = Callbacks::new(NAME, interpreter);
+            let result = COLLECTIONS::iterator_find(values.into_iter(), |item| {
+                callbacks.test(&predicate, std::slice::from_ref(item))
+            });
+            callbacks.finish(OptionValue(result))
+        };
+        /// The sum of the values
+        fn iterator_sum(values: Vec<f64>) -> f64 = COLLECTIONS::iterator_sum(values.into_iter());
+        /// The product of the values
+        fn iterator_product(values: Vec<f64>) -> f64 = COLLECTIONS::iterator_product(values.into_iter());
+        /// The smallest element
+        fn iterator_min(values: Vec<Ordered>) -> Option<Ordered> = COLLECTIONS::iterator_min(values.into_iter());
+        /// The largest element
+        fn iterator_max(values: Vec<Ordered>) -> Option<Ordered> = COLLECTIONS::iterator_max(values.into_iter());
+        /// Pair up the elements of two vectors
+        fn iterator_zip(a: Vec<Value>, b: Vec<Value>) -> Vec<(Value, Value)> =
+            COLLECTIONS::iterator_zip(a.into_iter(), b.into_iter()).collect();
+        /// The elements of `a` followed by those of `b`
+        fn iterator_chain(a: Vec<Value>, b: Vec<Value>) -> Vec<Value> =
+            COLLECTIONS::iterator_chain(a.into_iter(), b.into_iter()).collect();
+        /// An iterator repeating the values endlessly; empty for no values
+        fn iterator_cycle(values: Vec<Value>) -> NativeSource =
+            NativeSource::new(COLLECTIONS::iterator_cycle(values.into_iter()).map(Ok));
+        /// The first `n` elements
+        fn iterator_take(values: Vec<Value>, n: usize) -> Vec<Value> = COLLECTIONS::iterator_take(values.into_iter(), n).collect();
+        /// All but the first `n` elements
+        fn iterator_skip(values: Vec<Value>, n: usize) -> Vec<Value> = COLLECTIONS::iterator_skip(values.into_iter(), n).collect();
+        /// Every `step`th element, starting with the first
+        fn iterator_step_by(values: Vec<Value>, step: usize) -> Vec<Value> = {
+            if step == 0 {
+                return Err("COLLECTIONS::iterator_step_by expects a step of at least 1".to_string());
+            }
+            COLLECTIONS::iterator_step_by(values.into_iter(), step).collect()
+        };
+        /// Each element paired with its index
+        fn iterator_enumerate(values: Vec<Value>) -> Vec<(usize, Value)> =
+            COLLECTIONS::iterator_enumerate(values.into_iter()).collect();
+        /// The elements in reverse order
+        fn iterator_rev(values: Vec<Value>) -> Vec<Value> = COLLECTIONS::iterator_rev(values.into_iter()).collect();
+        /// Index of `target` in a sorted vector, or None
+        fn binary_search(vec: Vec<Ordered>, target: Ordered) -> Option<usize> = COLLECTIONS::binary_search(&vec, &target).ok();
+        /// Index of the first occurrence of `target`, or None
+        fn linear_search(vec: Vec<Value>, target: Value) -> Option<usize> = COLLECTIONS::linear_search(&vec, &target);
+        /// Sort a vector in place
+        fn sort(vec: Shared<Vec<Value>>) -> () = sort_in_place(&vec, COLLECTIONS::sort);
+        /// Sort a vector in place by a function returning a negative, zero or positive int
+        fn [interpreter] sort_by(vec: Shared<Vec<Value>>, compare: Value) -> Result<(), String> =
+            rearrange(Callbacks::new(NAME, interpreter), &vec, |items, callbacks| {
+                COLLECTIONS::sort_by(items, |a, b| callbacks.order(&compare, a, b))
+            });
+        /// Sort a vector in place by the values a function returns for its elements
+        fn [interpreter] sort_by_key(vec: Shared<Vec<Value>>, key: Value) -> Result<(), String> =
+            rearrange(Callbacks::new(NAME, interpreter), &vec, |items, callbacks| {
+                COLLECTIONS::sort_by_key(items, |item| Ordered(callbacks.value(&key, std::slice::from_ref(item))))
+            });
+        /// Reverse a vector in place
+        fn reverse(vec: Shared<Vec<Value>>) -> () = COLLECTIONS::reverse(&mut vec.write());
+        /// Shuffle a vector in place
+        fn [interpreter] shuffle(vec: Shared<Vec<Value>>) -> () = COLLECTIONS::shuffle(&mut vec.write(), interpreter.random());
+        /// Rotate a vector in place so the element at `mid` comes first
+        fn rotate_left(vec: Shared<Vec<Value>>, mid: usize) -> Result<(), String> = rotate(&vec, mid, COLLECTIONS::rotate_left);
+        /// Rotate a vector in place so the last `k` elements come first
+        fn rotate_right(vec: Shared<Vec<Value>>, k: usize) -> Result<(), String> = rotate(&vec, k, COLLECTIONS::rotate_right);
+        /// Move the elements a function returns true for to the front, keeping their order;
+        /// returns how many there are
+        fn [interpreter] partition(vec: Shared<Vec<Value>>, predicate: Value) -> Result<usize, String> =
+            rearrange(Callbacks::new(NAME, interpreter), &vec, |items, callbacks| {
+                COLLECTIONS::partition(items, |item| callbacks.test(&predicate, std::slice::from_ref(item)))
+            });
+        /// Remove consecutive duplicates in place
+        fn dedup(vec: Shared<Vec<Value>>) -> () = COLLECTIONS::dedup(&mut vec.write());
+        /// Remove each element in place that `same(element, previous)` returns true for,
+        /// where `previous` is the last element kept
+        fn [interpreter] dedup_by(vec: Shared<Vec<Value>>, same: Value) -> Result<(), String> =
+            rearrange(Callbacks::new(NAME, interpreter), &vec, |items, callbacks| {
+                COLLECTIONS::dedup_by(items, |a, b| callbacks.test(&same, &[a.clone(), b.clone()]))
+            });
+        /// Remove consecutive elements in place that a function returns the same value for
+        fn [interpreter] dedup_by_key(vec: Shared<Vec<Value>>, key: Value) -> Result<(), String> =
+            rearrange(Callbacks::new(NAME, interpreter), &vec, |items, callbacks| {
+                COLLECTIONS::dedup_by_key(items, |item| callbacks.value(&key, std::slice::from_ref(item)))
+            });
+        /// Create an empty counter, a map from element to count
+        fn counter_new() -> Counter<Value> = COLLECTIONS::counter_new();
+        /// Count the elements of a vector
+        fn counter_from_iter(values: Vec<Value>) -> Counter<Value> = COLLECTIONS::counter_from_iter(values.into_iter());
+        /// The `n` most common elements with their counts, most common first; all of them by default
+        fn counter_most_common(counter: Counter<Value>, n: Option<usize> = None) -> Vec<(Value, usize)> =
+            COLLECTIONS::counter_most_common(&counter, n);
+        /// The sum of the counts
+        fn counter_total(counter: Counter<Value>) -> usize = COLLECTIONS::counter_total(&counter);
+        /// A copy of `counter` with the counts in `other` taken away
+        fn counter_subtract(counter: Counter<Value>, other: Counter<Value>) -> Counter<Value> = {
+            let mut counter = counter;
+            COLLECTIONS::counter_subtract(&mut counter, &other);
+            counter
+        };
+        /// A copy of `counter` with the elements of a vector counted too
+        fn counter_update(counter: Counter<Value>, values: Vec<Value>) -> Counter<Value> = {
+            let mut counter = counter;
+            COLLECTIONS::counter_update(&mut counter, values.into_iter());
+            counter
+        };
+        /// Create an empty default dict, a `DefaultDict` struct whose missing keys get the
+        /// value `default_factory()` returns
+        fn default_dict_new(default_factory: Value) -> DefaultDictValue = DefaultDictValue {
+            default_factory,
+            data: Shared::new(HashMap::new()),
+        };
+        /// The value for a key, adding the default value when the key is missing
+        fn [interpreter] default_dict_get(dict: DefaultDictValue, key: Value) -> Result<Value, String> =
+            default_dict_get(interpreter, dict, key);
+        /// Set the value for a key
+        fn default_dict_set(dict: DefaultDictValue, key: Value, value: Value) -> () = {
+            let mut native = dict.to_native(|| Value::None);
+            COLLECTIONS::default_dict_set(&mut native, key, value);
+            *dict.data.write() = native.into_map();
+        };
+        /// Create an empty chain map, a vector of maps searched last to first
+        fn chain_map_new() -> ChainMap<Value, Value> = COLLECTIONS::chain_map_new();
+        /// Add a map to the end of a chain
+        fn chain_map_add(chain: Shared<Vec<Value>>, map: HashMap<Value, Value>) -> () =
+            with_backed(&chain, |chain| COLLECTIONS::chain_map_add(chain, map))?;
+        /// The value for a key in the last map that has it
+        fn chain_map_get(chain: ChainMap<Value, Value>, key: Value) -> Option<Value> =
+            COLLECTIONS::chain_map_get(&chain, &key).cloned();
+        /// Create a `NamedTuple` struct from field names and values
+        fn named_tuple_new(fields: Vec<String>, values: Vec<Value>) -> Result<NamedTuple<Value>, String> = {
+            if fields.len() != values.len() {
+                return Err(format!(
+                    "COLLECTIONS::named_tuple_new got {} field(s) but {} value(s)",
+                    fields.len(),
+                    values.len()
+                ));
+            }
+            Ok::<_, String>(COLLECTIONS::named_tuple_new(fields, values))
+        };
+        /// The value of a field
+        fn named_tuple_get(tuple: NamedTuple<Value>, field: String) -> Option<Value> =
+            COLLECTIONS::named_tuple_get(&tuple, &field).cloned();
+        /// A copy of a named tuple with one field replaced
+        fn named_tuple_set(tuple: NamedTuple<Value>, field: String, value: Value) -> Result<NamedTuple<Value>, String> = {
+            let mut tuple = tuple;
+            COLLECTIONS::named_tuple_set(&mut tuple, &field, value).map(|_| tuple)
+        };
+        /// Create an empty ordered dict, a vector of (key, value) tuples
+        fn ordered_dict_new() -> OrderedDict<Value, Value> = COLLECTIONS::ordered_dict_new();
+        /// Move an entry to the end
+        fn ordered_dict_move_to_end(dict: Shared<Vec<Value>>, key: Value) -> () =
+            with_backed(&dict, |dict| COLLECTIONS::ordered_dict_move_to_end(dict, &key))?;
+        /// Remove and return the last entry, or the first when `last` is false
+        fn ordered_dict_popitem(dict: Shared<Vec<Value>>, last: bool = true) -> Option<(Value, Value)> =
+            with_backed(&dict, |dict| COLLECTIONS::ordered_dict_popitem(dict, last))?;
+        /// Create an empty deque
+        fn deque_new() -> Deque<Value> = COLLECTIONS::deque_new();
+        /// Rotate a deque in place `n` steps to the right; negative `n` rotates left
+        fn deque_rotate(deque: Shared<Vec<Value>>, n: isize) -> () =
+            with_backed(&deque, |deque: &mut Deque<Value>| COLLECTIONS::deque_rotate(deque, n))?;
+        /// Add the elements of a vector to the back of a deque
+        fn deque_extend(deque: Shared<Vec<Value>>, values: Vec<Value>) -> () =
+            with_backed(&deque, |deque: &mut Deque<Value>| COLLECTIONS::deque_extend(deque, values.into_iter()))?;
+        /// Create an empty heap; it lists its elements largest first
+        fn heap_new() -> Heap<Ordered> = COLLECTIONS::heap_new();
+        /// Add the elements of a vector to a heap
+        fn heap_merge(heap: Shared<Vec<Value>>, other: Vec<Ordered>) -> () =
+            with_backed(&heap, |heap| COLLECTIONS::heap_merge(heap, other.into_iter().collect()))?;
+        /// The `n` largest elements, largest first
+        fn heap_nlargest(heap: Heap<Ordered>, n: usize) -> Vec<Ordered> = COLLECTIONS::heap_nlargest(&heap, n);
+        /// The `n` smallest elements, smallest first
+        fn heap_nsmallest(heap: Heap<Ordered>, n: usize) -> Vec<Ordered> = COLLECTIONS::heap_nsmallest(&heap, n);
+    }
+
+    TIME {
+        /// Seconds since the Unix epoch
+        fn now() -> u64 = TIME::now();
+        /// Milliseconds since the Unix epoch
+        fn now_millis() -> u128 = TIME::now_millis();
+        /// Microseconds since the Unix epoch
+        fn now_micros() -> u128 = TIME::now_micros();
+        /// Nanoseconds since the Unix epoch
+        fn now_nanos() -> u128 = TIME::now_nanos();
+        /// The current local date and time
+        fn now_string() -> String = TIME::now_string();
+        /// The current local date
+        fn today_string() -> String = TIME::today_string();
+        /// The current local time
+        fn time_string() -> String = TIME::time_string();
+        /// Format a timestamp with a strftime-style format
+        fn format_timestamp(timestamp: u64, format: String) -> String = TIME::format_timestamp(timestamp, &format);
+        /// Parse a date with a strftime-style format into a timestamp
+        fn parse_date(date: String, format: String) -> Result<u64, String> = TIME::parse_date(&date, &format);
+        /// The UTC timestamp of a date and time
+        fn from_components(year: i32, month: u32, day: u32, hour: u32 = 0, minute: u32 = 0, second: u32 = 0) -> Result<u64, String> =
+            TIME::from_components(year, month, day, hour, minute, second);
+        /// A timestamp as (year, month, day, hour, minute, second) in UTC
+        fn to_components(timestamp: u64) -> (i32, u32, u32, u32, u32, u32) = TIME::to_components(timestamp);
+        /// Move a timestamp by a number of seconds
+        fn add_seconds(timestamp: u64, seconds: i64) -> Result<u64, String> = shift_seconds(timestamp, seconds, 1);
+        /// Move a timestamp by a number of minutes
+        fn add_minutes(timestamp: u64, minutes: i64) -> Result<u64, String> = shift_seconds(timestamp, minutes, 60);
+        /// Move a timestamp by a number of hours
+        fn add_hours(timestamp: u64, hours: i64) -> Result<u64, String> = shift_seconds(timestamp, hours, 3600);
+        /// Move a timestamp by a number of days
+        fn add_days(timestamp: u64, days: i64) -> Result<u64, String> = shift_seconds(timestamp, days, 86400);
+        /// Move a timestamp by a number of weeks
+        fn add_weeks(timestamp: u64, weeks: i64) -> Result<u64, String> = shift_seconds(timestamp, weeks, 604800);
+        /// Move a timestamp forward by calendar months
+        fn add_months(timestamp: u64, months: u32) -> Result<u64, String> = shift_months(timestamp, months);
+        /// Move a timestamp forward by calendar years
+        fn add_years(timestamp: u64, years: u32) -> Result<u64, String> = match years.checked_mul(12) {
+            Some(months) => shift_months(timestamp, months),
+            None => Err("Timestamp out of range".to_string()),
+        };
+        /// Seconds from `timestamp1` to `timestamp2`
+        fn diff_seconds(timestamp1: u64, timestamp2: u64) -> i64 = TIME::diff_seconds(timestamp1, timestamp2);
+        /// Whole minutes from `timestamp1` to `timestamp2`
+        fn diff_minutes(timestamp1: u64, timestamp2: u64) -> i64 = TIME::diff_minutes(timestamp1, timestamp2);
+        /// Whole hours from `timestamp1` to `timestamp2`
+        fn diff_hours(timestamp1: u64, timestamp2: u64) -> i64 = TIME::diff_hours(timestamp1, timestamp2);
+        /// Whole days from `timestamp1` to `timestamp2`
+        fn diff_days(timestamp1: u64, timestamp2: u64) -> i64 = TIME::diff_days(timestamp1, timestamp2);
+        /// Whether a year is a leap year
+        fn is_leap_year(year: i32) -> bool = TIME::is_leap_year(year);
+        /// Number of days in a month
+        fn days_in_month(year: i32, month: u32) -> u32 = TIME::days_in_month(year, month);
+        /// Day of the week, 0 for Sunday
+        fn day_of_week(timestamp: u64) -> u32 = TIME::day_of_week(timestamp);
+        /// Day of the year, from 1
+        fn day_of_year(timestamp: u64) -> u32 = TIME::day_of_year(timestamp);
+        /// ISO week of the year
+        fn week_of_year(timestamp: u64) -> u32 = TIME::week_of_year(timestamp);
+        /// The local time zone's offset from UTC in seconds
+        fn timezone_offset() -> i32 = TIME::timezone_offset();
+        /// A timestamp shifted into a time zone
+        fn to_timezone(timestamp: u64, timezone: String) -> Result<u64, String> = TIME::to_timezone(timestamp, &timezone);
+        /// Midnight at the start of a timestamp's day
+        fn start_of_day(timestamp: u64) -> u64 = TIME::start_of_day(timestamp);
+        /// The last second of a timestamp's day
+        fn end_of_day(timestamp: u64) -> u64 = TIME::end_of_day(timestamp);
+        /// Midnight at the start of a timestamp's week
+        fn start_of_week(timestamp: u64) -> u64 = TIME::start_of_week(timestamp);
+        /// Midnight at the start of a timestamp's month
+        fn start_of_month(timestamp: u64) -> u64 = TIME::start_of_month(timestamp);
+        /// Midnight at the start of a timestamp's year
+        fn start_of_year(timestamp: u64) -> u64 = TIME::start_of_year(timestamp);
+        /// Pause for a number of seconds
+        fn sleep(seconds: f64) -> () = TIME::sleep(seconds.max(0.0));
+        /// Pause for a number of milliseconds
+        fn sleep_millis(millis: u64) -> () = TIME::sleep_millis(millis);
+        /// Pause for a number of microseconds
+        fn sleep_micros(micros: u64) -> () = TIME::sleep_micros(micros);
+        /// Pause for a number of nanoseconds
+        fn sleep_nanos(nanos: u64) -> () = TIME::sleep_nanos(nanos);
+        /// A timer started now, with `elapsed`, `elapsed_millis`, `elapsed_micros`,
+        /// `elapsed_nanos` and `reset` methods
+        fn create_timer() -> Timer = TIME::create_timer();
+        /// A stopped stopwatch, with `start`, `stop`, `lap`, `reset`, `is_running`,
+        /// `get_laps` and `get_total_time` methods
+        fn create_stopwatch() -> Stopwatch = TIME::create_stopwatch();
+        /// The local time zone's name
+        fn timezone_name() -> String = TIME::timezone_name();
+        /// The names of the known time zones
+        fn list_timezones() -> Vec<String> = TIME::list_timezones();
+        /// Whether a year, month and day form a date
+        fn is_valid_date(year: i32, month: u32, day: u32) -> bool = TIME::is_valid_date(year, month, day);
+        /// Whether an hour, minute and second form a time of day
+        fn is_valid_time(hour: u32, minute: u32, second: u32) -> bool = TIME::is_valid_time(hour, minute, second);
+        /// Whole years since a birth timestamp
+        fn get_age(birth_timestamp: u64) -> u32 = TIME::get_age(birth_timestamp);
+        /// A timestamp relative to now, such as "3 hours ago"
+        fn relative_time(timestamp: u64) -> String = TIME::relative_time(timestamp);
+    }
+
+    ERROR {
+        /// Create an error message
+        fn new(message: String) -> String = ERROR::new(&message);
+        /// Replace `{0}`, `{1}`, ... in a message with the arguments at those positions
+        fn format(message: String, ..args: Value) -> String =
+            ERROR::format(&message, &args.iter().map(Value::to_string).collect::<Vec<_>>());
+        /// Log an error message
+        fn log(message: String) -> () = ERROR::log(&message);
+        /// Log a warning
+        fn warn(message: String) -> () = ERROR::warn(&message);
+        /// Log an informational message
+        fn info(message: String) -> () = ERROR::info(&message);
+        /// Log a debug message
+        fn debug(message: String) -> () = ERROR::debug(&message);
+    }
+
+    TESTING {
+        /// Call a test function as a `TestResult` struct; the test fails if the call raises an error
+        fn [interpreter] run_test(name: String, test: Value) -> TestResult = {
+            let interpreter = RefCell::new(interpreter);
+            TESTING::run_test(&name, || interpreter.borrow_mut().call_function_value(&test, &[]).map(drop))
+        };
+        /// Fail with `message` unless `condition` holds
+        fn assert_true(condition: bool, message: String) -> Result<(), String> = TESTING::assert_true(condition, &message);
+        /// Fail with `message` if `condition` holds
+        fn assert_false(condition: bool, message: String) -> Result<(), String> = TESTING::assert_false(condition, &message);
+        /// Fail with `message` unless the values are equal
+        fn assert_equal(actual: Value, expected: Value, message: String) -> Result<(), String> =
+            TESTING::assert_equal(&actual, &expected, &message);
+        /// Fail with `message` if the values are equal
+        fn assert_not_equal(actual: Value, expected: Value, message: String) -> Result<(), String> =
+            TESTING::assert_not_equal(&actual, &expected, &message);
+        /// Fail with `message` unless `min <= value <= max`
+        fn assert_in_range(value: Ordered, min: Ordered, max: Ordered, message: String) -> Result<(), String> =
+            TESTING::assert_in_range(&value, &min, &max, &message);
+        /// Fail with `message` unless `actual` contains `expected`
+        fn assert_contains(actual: String, expected: String, message: String) -> Result<(), String> =
+            TESTING::assert_contains(&actual, &expected, &message);
+        /// Fail with `message` unless calling `f` raises an error
+        fn [interpreter] assert_panics(f: Value, message: String) -> Result<(), String> = TESTING::assert_panics(
+            AssertUnwindSafe(|| {
+                if interpreter.call_function_value(&f, &[]).is_err() {
+                    panic::resume_unwind(Box::new(()));
+                }
+            }),
+            &message,
+        );
+    }
+
+    CHANNEL {
+        /// Create a channel; sending and receiving are methods on the channel
+        fn [interpreter] new() -> Result<Value, String> = interpreter.create_channel();
+    }
+
+    GC {
+        /// Collect unreachable reference cycles now; returns how many objects were freed
+        fn collect() -> usize = crate::gc::collect();
+        /// Collector statistics as a `GcStats` struct
+        fn stats() -> GcStats = crate::gc::stats();
+    }
+
+    JSON {
+        /// Parse JSON text; objects become maps, arrays vectors and null None
+        fn parse(text: String) -> Result<Value, String> = JSON::parse(&text);
+        /// Parse a file containing one JSON document
+        fn parse_file(path: String) -> Result<Value, String> = JSON::parse_file(&path);
+        /// Serialize a value as compact JSON; structs become objects
+        fn stringify(value: Value) -> Result<String, String> = JSON::stringify(&value);
+        /// Serialize a value as JSON, indenting each level by `indent` spaces
+        fn stringify_pretty(value: Value, indent: usize = 2) -> Result<String, String> =
+            JSON::stringify_pretty(&value, indent);
+        /// Parse JSON text into a value of a declared type, such as a struct;
+        /// errors name the field that does not match
+        fn [interpreter] decode(text: String, ty: Type) -> Result<Value, String> = JSON::decode(&text, &ty, interpreter);
+        /// Iterate over the documents of a line-delimited JSON file, reading a line at a time
+        fn read_lines(path: String) -> Result<JsonLines, String> = JSON::read_lines(&path);
+    }
+
+    REGEX {
+        /// Compile a pattern into a Regex that can be reused without recompiling
+        fn compile(pattern: String) -> Result<Arc<Regex>, String> = REGEX::compile(&pattern);
+        /// Whether the pattern matches anywhere in the text
+        fn is_match(pattern: Pattern, text: String) -> Result<bool, String> = REGEX::is_match(&pattern, &text);
+        /// The first match as Some(match), or None
+        fn find(pattern: Pattern, text: String) -> Result<OptionValue<String>, String> =
+            REGEX::find(&pattern, &text).map(OptionValue);
+        /// Every non-overlapping match
+        fn find_all(pattern: Pattern, text: String) -> Result<Vec<String>, String> = REGEX::find_all(&pattern, &text);
+        /// The groups of the first match keyed by name, or by number for unnamed
+        /// groups ("0" is the whole match), as Some(groups), or None if there is no match
+        fn captures(pattern: Pattern, text: String) -> Result<OptionValue<HashMap<String, String>>, String> =
+            REGEX::captures(&pattern, &text).map(OptionValue);
+        /// Replace every match; `$1` and `${name}` in the replacement insert groups
+        fn replace_all(pattern: Pattern, text: String, replacement: String) -> Result<String, String> =
+            REGEX::replace_all(&pattern, &text, &replacement);
+        /// Split the text on every match
+        fn split(pattern: Pattern, text: String) -> Result<Vec<String>, String> = REGEX::split(&pattern, &text);
+        /// Escape the characters that have a meaning in patterns
+        fn escape(text: String) -> String = REGEX::escape(&text);
+    }
+
+    RANDOM {
+        /// Restart the program's random numbers from a seed, making them repeatable
+        fn [interpreter] seed(seed: i64) -> () = interpreter.random().reseed(seed as u64);
+        /// A uniform int between `lo` and `hi`, both included
+        fn [interpreter] int(lo: i64, hi: i64) -> Result<i64, String> = interpreter.random().int(lo, hi);
+        /// A uniform float at least `lo` and less than `hi`
+        fn [interpreter] float(lo: f64 = 0.0, hi: f64 = 1.0) -> Result<f64, String> = interpreter.random().float(lo, hi);
+        /// A normally distributed float
+        fn [interpreter] gaussian(mean: f64 = 0.0, std_dev: f64 = 1.0) -> Result<f64, String> =
+            interpreter.random().gaussian(mean, std_dev);
+        /// One element of a vector, chosen uniformly
+        fn [interpreter] choice(items: Vec<Value>) -> Result<Value, String> = interpreter.random().choice(&items);
+        /// `k` distinct elements of a vector, in random order
+        fn [interpreter] sample(items: Vec<Value>, k: usize) -> Result<Vec<Value>, String> =
+            interpreter.random().sample(&items, k);
+        /// Shuffle a vector in place
+        fn [interpreter] shuffle(vec: Shared<Vec<Value>>) -> () = interpreter.random().shuffle(&mut vec.write());
+        /// A random (version 4) UUID string
+        fn [interpreter] uuid4() -> String = interpreter.random().uuid4();
+        /// A generator with its own sequence of numbers and the same functions as
+        /// methods; seeded from the program's generator when no seed is given
+        fn [interpreter] generator(seed: Option<i64> = None) -> Generator = RANDOM::generator(interpreter.random(), seed);
     }
 }
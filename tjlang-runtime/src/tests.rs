@@ -5,7 +5,8 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::{Interpreter, Value};
+    use crate::compiled::{CompiledProgram, FORMAT_VERSION};
+    use crate::{Backend, IntOverflow, Interpreter, Value};
     use codespan::Files;
     use tjlang_ast::*;
     use tjlang_parser::parse;
@@ -27,6 +28,18 @@ mod tests {
             .map_err(|e| format!("Runtime error: {}", e))
     }
 
+    /// Helper function to parse TJLang code and run it on the bytecode VM
+    fn run_on_vm(source: &str) -> Result<Value, String> {
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).map_err(|e| format!("Parse error: {:?}", e))?;
+
+        let mut interpreter = Interpreter::new();
+        interpreter.set_backend(Backend::Vm);
+        interpreter
+            .interpret_program(&ast)
+            .map_err(|e| format!("Runtime error: {}", e))
+    }
+
     /// Helper function to test primitive method calls
     fn test_primitive_method_call(value_expr: &str, method: &str) -> Result<Value, String> {
         let source = format!("def main() -> int {{ {} }}", value_expr);
@@ -317,6 +330,3264 @@ mod tests {
         assert_eq!(result, Value::None);
     }
 
+    // ===== STRUCT, ENUM AND IMPL TESTS =====
+
+    #[test]
+    fn test_impl_method_dispatch_on_struct() {
+        let source = r#"
+            interface Printable { show() -> str }
+
+            impl Printable: Point {
+                show() -> str {
+                    return self.x.to_string() + "," + self.y.to_string()
+                }
+            }
+
+            type Point { x: int, y: int }
+
+            def main() -> str {
+                p: Point = Point { x: 1, y: 2 }
+                return p.show()
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::String("1,2".to_string()));
+    }
+
+    #[test]
+    fn test_impl_method_with_arguments() {
+        let source = r#"
+            type Counter { count: int }
+
+            impl Scalable: Counter {
+                scaled(factor: int) -> int {
+                    return self.count * factor
+                }
+            }
+
+            def main() -> int {
+                c: Counter = Counter { count: 7 }
+                return c.scaled(3)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(21));
+    }
+
+    #[test]
+    fn test_enum_variant_constructors() {
+        let source = r#"
+            enum Shape { Circle(float), Square(float), Empty }
+
+            def main() -> any {
+                return [Circle(2.0), Shape.Square(3.0), Empty]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![
+                Value::Enum {
+                    name: "Shape".to_string(),
+                    variant: "Circle".to_string(),
+                    fields: vec![Value::Float(2.0)],
+                },
+                Value::Enum {
+                    name: "Shape".to_string(),
+                    variant: "Square".to_string(),
+                    fields: vec![Value::Float(3.0)],
+                },
+                Value::Enum {
+                    name: "Shape".to_string(),
+                    variant: "Empty".to_string(),
+                    fields: vec![],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_enum_variant_constructor_is_a_function_value() {
+        let source = r#"
+            enum Shape { Circle(float), Empty }
+
+            def build(make: fn(float) -> Shape, size: float) -> Shape {
+                return make(size)
+            }
+
+            def main() -> any {
+                return [build(Circle, 1.5), Circle]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        let Value::Vec(items) = result else { panic!("expected a vector, got {:?}", result) };
+        let items = items.read();
+        assert_eq!(
+            items[0],
+            Value::Enum {
+                name: "Shape".to_string(),
+                variant: "Circle".to_string(),
+                fields: vec![Value::Float(1.5)],
+            }
+        );
+        assert!(
+            matches!(&items[1], Value::EnumConstructor { enum_name, variant, .. } if enum_name == "Shape" && variant == "Circle"),
+            "unexpected constructor: {:?}",
+            items[1]
+        );
+    }
+
+    #[test]
+    fn test_enum_method_dispatch_and_interface_param() {
+        let source = r#"
+            interface Named { name() -> str }
+            enum Color { Red, Green }
+
+            impl Named: Color {
+                name() -> str {
+                    return "color " + self.to_string()
+                }
+            }
+
+            def describe(n: Named) -> str {
+                return n.name()
+            }
+
+            def main() -> str {
+                return describe(Green)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::String("color Color::Green".to_string()));
+    }
+
+    #[test]
+    fn test_struct_literal_with_undeclared_field_fails() {
+        let source = r#"
+            type Point { x: int, y: int }
+
+            def main() -> int {
+                p: Point = Point { x: 1, z: 2 }
+                return 0
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Undeclared field should be rejected");
+        assert!(err.contains("no field named 'z'"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_impl_missing_interface_method_fails() {
+        let source = r#"
+            interface Printable { show() -> str }
+            type Point { x: int }
+
+            impl Printable: Point {
+                other() -> str { return "" }
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Incomplete impl should be rejected");
+        assert!(err.contains("missing method 'show'"), "unexpected error: {}", err);
+    }
+
+    // ===== CLOSURE TESTS =====
+
+    #[test]
+    fn test_closure_captures_enclosing_parameter() {
+        let source = r#"
+            def make_adder(n: int) -> any {
+                return (x: int) -> x + n
+            }
+
+            def main() -> int {
+                add5: any = make_adder(5)
+                return add5(10)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(15));
+    }
+
+    #[test]
+    fn test_closure_sees_globals() {
+        let source = r#"
+            base: int = 100
+
+            def main() -> int {
+                f: any = (x: int) -> x + base
+                return f(1)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(101));
+    }
+
+    #[test]
+    fn test_closure_passed_to_user_function() {
+        let source = r#"
+            def apply(f: fn(int) -> int, v: int) -> int {
+                return f(v)
+            }
+
+            def main() -> int {
+                factor: int = 3
+                return apply((x: int) -> x * factor, 7)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(21));
+    }
+
+    #[test]
+    fn test_closure_does_not_see_caller_locals() {
+        let source = r#"
+            def apply(f: fn(int) -> int) -> int {
+                secret: int = 5
+                return f(1)
+            }
+
+            def main() -> int {
+                return apply((x: int) -> x + secret)
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Closures should be lexically scoped");
+        assert!(err.contains("secret"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_closures_stored_in_collection_keep_own_captures() {
+        let source = r#"
+            def make_adder(n: int) -> any {
+                return (x: int) -> x + n
+            }
+
+            def main() -> any {
+                adders: any = [make_adder(1), make_adder(2)]
+                return [adders[0](10), adders[1](10)]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(11), Value::Int(12)]));
+    }
+
+    #[test]
+    fn test_closure_wrong_argument_count_fails() {
+        let source = r#"
+            def main() -> int {
+                f: any = (x: int) -> x
+                return f(1, 2)
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Arity mismatch should be rejected");
+        assert!(err.contains("expects 1 arguments"), "unexpected error: {}", err);
+    }
+
+    // ===== PATTERN MATCHING TESTS =====
+
+    #[test]
+    fn test_match_statement_binds_constructor_fields() {
+        let source = r#"
+            enum Shape { Circle(int), Rect(int, int), Empty }
+
+            def area(s: Shape) -> int {
+                match s {
+                    Circle(r): { return 3 * r * r }
+                    Rect(w, h): { return w * h }
+                    Empty: { return 0 }
+                }
+                return -1
+            }
+
+            def main() -> any {
+                return [area(Circle(2)), area(Rect(2, 3)), area(Empty)]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(12), Value::Int(6), Value::Int(0)]));
+    }
+
+    #[test]
+    fn test_match_guard_falls_through_to_next_arm() {
+        let source = r#"
+            def parity(v: int) -> str {
+                return match v {
+                    n: int if n % 2 == 0: { "even" }
+                    _: { "odd" }
+                }
+            }
+
+            def main() -> any {
+                return [parity(4), parity(7)]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![Value::String("even".to_string()), Value::String("odd".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_match_typed_binding_checks_type() {
+        let source = r#"
+            def describe(v: any) -> str {
+                return match v {
+                    n: int: { "int " + n.to_string() }
+                    s: str: { "str " + s }
+                    _: { "other" }
+                }
+            }
+
+            def main() -> any {
+                return [describe(3), describe("x"), describe(1.5)]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![
+                Value::String("int 3".to_string()),
+                Value::String("str x".to_string()),
+                Value::String("other".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_match_tuple_and_struct_destructuring() {
+        let source = r#"
+            type Point { x: int, y: int }
+
+            def main() -> any {
+                p: Point = Point { x: 0, y: 5 }
+                from_struct: int = match p {
+                    Point { x: 1, y: y: int }: { y }
+                    Point { x: x: int, y: y: int }: { x + y + 100 }
+                }
+                from_tuple: int = match (3, 4) {
+                    (0, b: int): { b }
+                    (a: int, b: int): { a * b }
+                }
+                return [from_struct, from_tuple]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(105), Value::Int(12)]));
+    }
+
+    #[test]
+    fn test_match_trait_check_pattern() {
+        let source = r#"
+            interface Named { name() -> str }
+            type Dog { age: int }
+
+            impl Named: Dog {
+                name() -> str { return "dog" }
+            }
+
+            def main() -> str {
+                d: Dog = Dog { age: 3 }
+                return match d {
+                    n: implements [Named]: { n.name() }
+                    _: { "unnamed" }
+                }
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::String("dog".to_string()));
+    }
+
+    #[test]
+    fn test_match_bindings_are_arm_local() {
+        let source = r#"
+            def main() -> int {
+                total: int = 1
+                match 5 {
+                    n: int: { total = total + n }
+                }
+                return n
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Arm bindings should not leak");
+        assert!(err.contains("Undefined variable: n"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_match_assignment_updates_outer_variable() {
+        let source = r#"
+            def main() -> int {
+                total: int = 1
+                match 5 {
+                    n: int: { total = total + n }
+                }
+                return total
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(6));
+    }
+
+    #[test]
+    fn test_match_without_matching_arm_fails() {
+        let source = r#"
+            def main() -> int {
+                match 5 {
+                    0: { return 0 }
+                }
+                return 1
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Unmatched value should be rejected");
+        assert!(err.contains("No matching pattern found"), "unexpected error: {}", err);
+    }
+
+    // ===== EXCEPTION TESTS =====
+
+    #[test]
+    fn test_raise_caught_across_function_calls() {
+        let source = r#"
+            def inner(n: int) -> int {
+                if n > 2 {
+                    raise "too big"
+                }
+                return n
+            }
+
+            def outer(n: int) -> int {
+                return inner(n) * 10
+            }
+
+            def main() -> str {
+                try {
+                    outer(5)
+                } except msg: str {
+                    return "caught " + msg
+                }
+                return "not caught"
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::String("caught too big".to_string()));
+    }
+
+    #[test]
+    fn test_except_clauses_match_raised_value() {
+        let source = r#"
+            enum AppError { NotFound(str), Invalid }
+
+            def check(v: int) -> str {
+                try {
+                    if v == 0 { raise Invalid }
+                    if v == 1 { raise NotFound("key") }
+                    raise v
+                } except Invalid {
+                    return "invalid"
+                } except NotFound(what) {
+                    return "missing " + what
+                } except {
+                    return "other"
+                }
+                return "unreachable"
+            }
+
+            def main() -> any {
+                return [check(0), check(1), check(2)]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![
+                Value::String("invalid".to_string()),
+                Value::String("missing key".to_string()),
+                Value::String("other".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_raise_unwinds_out_of_loops() {
+        let source = r#"
+            def main() -> int {
+                found: int = 0
+                try {
+                    for (i: int; [1, 2, 3, 4]) {
+                        if i == 3 { raise i }
+                    }
+                } except v: int {
+                    found = v
+                }
+                return found
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(3));
+    }
+
+    #[test]
+    fn test_unmatched_except_keeps_unwinding() {
+        let source = r#"
+            def main() -> int {
+                try {
+                    raise "boom"
+                } except n: int {
+                    return n
+                }
+                return 0
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Unmatched raise should escape");
+        assert!(err.contains("Uncaught exception: boom"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_except_catches_runtime_errors_as_their_message() {
+        let source = r#"
+            def divide(a: int, b: int) -> int {
+                return a / b
+            }
+
+            def check(text: str) -> Result<int, str> {
+                try {
+                    n: int = Err(text)?
+                    return Ok(n)
+                } except {
+                    return Ok(0)
+                }
+                return Ok(1)
+            }
+
+            def main() -> any {
+                caught: [str] = []
+                try {
+                    divide(1, 0)
+                } except msg: str {
+                    caught.push(msg)
+                }
+                try {
+                    JSON.parse("{bad")
+                } except msg: str {
+                    caught.push(msg)
+                }
+                return [caught, check("early")]
+            }
+        "#;
+
+        let expected = Value::vec(vec![
+            Value::vec(vec![
+                Value::String("Division by zero".to_string()),
+                Value::String("Invalid JSON: key must be a string at line 1 column 2".to_string()),
+            ]),
+            Value::err(Value::String("early".to_string())),
+        ]);
+        assert_eq!(interpret_code(source), Ok(expected.clone()));
+        assert_eq!(run_on_vm(source), Ok(expected));
+    }
+
+    #[test]
+    fn test_uncaught_raise_records_call_chain() {
+        let source = r#"
+            def inner() -> int {
+                raise "boom"
+                return 0
+            }
+
+            def outer() -> int {
+                return inner()
+            }
+
+            def main() -> int {
+                return outer()
+            }
+        "#;
+
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let mut interpreter = Interpreter::new();
+        let err = interpreter
+            .interpret_program(&ast)
+            .expect_err("Raise should be uncaught");
+
+        assert_eq!(err.raised.as_deref(), Some(&Value::String("boom".to_string())));
+        let functions: Vec<&str> = err.stack.iter().map(|f| f.function.as_str()).collect();
+        assert_eq!(functions, vec!["inner", "outer", "main"]);
+    }
+
+    // ===== OPTION AND RESULT TESTS =====
+
+    #[test]
+    fn test_question_mark_propagates_err() {
+        let source = r#"
+            def parse(s: str) -> Result<int, str> {
+                if s == "bad" {
+                    return Err("bad input")
+                }
+                return Ok(21)
+            }
+
+            def doubled(s: str) -> Result<int, str> {
+                v: int = parse(s)?
+                return Ok(v * 2)
+            }
+
+            def main() -> str {
+                return doubled("good").to_string() + " " + doubled("bad").to_string()
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::String("Ok(42) Err(bad input)".to_string()));
+    }
+
+    #[test]
+    fn test_question_mark_propagates_none() {
+        let source = r#"
+            def checked_sqrt(n: int) -> ?int {
+                if n < 0 {
+                    return None
+                }
+                return Some(MATH.sqrt(n).to_int())
+            }
+
+            def sqrt_plus_one(n: int) -> ?int {
+                return Some(checked_sqrt(n)? + 1)
+            }
+
+            def main() -> bool {
+                return sqrt_plus_one(16).unwrap() == 5 and sqrt_plus_one(-4).is_none()
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Bool(true));
+    }
+
+    #[test]
+    fn test_question_mark_outside_function_is_an_error() {
+        let source = r#"
+            x: ?int = None
+            y: int = x?
+        "#;
+
+        let err = interpret_code(source).expect_err("Should fail at runtime");
+        assert!(err.contains("outside of a function"));
+    }
+
+    #[test]
+    fn test_option_result_methods() {
+        let source = r#"
+            def main() -> [int] {
+                a: int = Some(3).map((x: int) -> x + 1).unwrap()
+                b: int = None.unwrap_or(7)
+                c: int = Ok(2).and_then((x: int) -> Ok(x * 10)).unwrap()
+                d: int = Err("no").map((x: int) -> x + 1).unwrap_or(-1)
+                e: int = Err(5).unwrap_err()
+                f: int = 0
+                if Ok(1).is_ok() and Err(1).is_err() and Some(1).is_some() {
+                    f = 1
+                }
+                return [a, b, c, d, e, f]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![
+                Value::Int(4),
+                Value::Int(7),
+                Value::Int(20),
+                Value::Int(-1),
+                Value::Int(5),
+                Value::Int(1),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unwrap_on_none_fails() {
+        let source = r#"
+            def main() -> int {
+                x: ?int = None
+                return x.unwrap()
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Should fail at runtime");
+        assert!(err.contains("Called unwrap on None"));
+    }
+
+    #[test]
+    fn test_match_on_option_and_result() {
+        let source = r#"
+            def describe(r: Result<int, str>) -> str {
+                return match r {
+                    Ok(v) if v > 10: { "big" }
+                    Ok(v): { "small" }
+                    Err(e): { "error: " + e }
+                }
+            }
+
+            def main() -> str {
+                opt: ?int = Some(2)
+                name: str = match opt {
+                    Some(n): { "some" }
+                    None: { "none" }
+                }
+                return describe(Ok(50)) + "," + describe(Ok(1)) + "," + describe(Err("x")) + "," + name
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::String("big,small,error: x,some".to_string()));
+    }
+
+    // ===== CONCURRENCY TESTS =====
+
+    #[test]
+    fn test_spawn_and_join_task() {
+        let source = r#"
+            def square(n: int) -> int {
+                return n * n
+            }
+
+            def main() -> int {
+                a = spawn square(6)
+                b = spawn square(2)
+                return a.join() + b.await() + a.join()
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(76));
+    }
+
+    #[test]
+    fn test_channels_between_tasks() {
+        let source = r#"
+            def producer(ch: Channel, count: int) -> int {
+                i: int = 1
+                while i <= count {
+                    ch.send(i * 10)
+                    i = i + 1
+                }
+                return count
+            }
+
+            def main() -> [int] {
+                ch = CHANNEL.new()
+                task = spawn producer(ch, 3)
+                total: int = ch.recv() + ch.recv() + ch.recv()
+                sent: int = task.join()
+                empty: int = 0
+                if ch.try_recv().is_none() {
+                    empty = 1
+                }
+                ch.send(5)
+                return [total, sent, empty, ch.try_recv().unwrap()]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![Value::Int(60), Value::Int(3), Value::Int(1), Value::Int(5)])
+        );
+    }
+
+    #[test]
+    fn test_raise_in_task_is_caught_by_joiner() {
+        let source = r#"
+            def failing() -> int {
+                raise "task broke"
+            }
+
+            def main() -> str {
+                task = spawn failing()
+                try {
+                    task.join()
+                } except e: str {
+                    return "caught " + e
+                }
+                return "not caught"
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::String("caught task broke".to_string()));
+    }
+
+    #[test]
+    fn test_task_runtime_error_fails_join() {
+        let source = r#"
+            def broken() -> int {
+                return missing_value + 1
+            }
+
+            def main() -> int {
+                task = spawn broken()
+                return task.join()
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Should fail at runtime");
+        assert!(err.contains("Task 0 failed"));
+        assert!(err.contains("missing_value"));
+    }
+
+    // ===== ITERATOR TESTS =====
+
+    #[test]
+    fn test_huge_range_is_lazy() {
+        let source = r#"
+            def main() -> int {
+                total: int = 0
+                for (i: int; 0 $ 1000000000000) {
+                    if i >= 5 {
+                        break
+                    }
+                    total = total + i
+                }
+                return total
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(10));
+    }
+
+    #[test]
+    fn test_range_value_methods() {
+        let source = r#"
+            def main() -> [int] {
+                r: Range = 2 $= 6
+                found: int = 0
+                if r.contains(6) and not r.contains(7) {
+                    found = 1
+                }
+                return [r.length(), found]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(5), Value::Int(1)]));
+    }
+
+    #[test]
+    fn test_iterator_adapter_chain() {
+        let source = r#"
+            def main() -> [int] {
+                return (1 $= 1000000).map((x: int) -> x * x).filter((x: int) -> x % 2 == 0).take(3).collect()
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(4), Value::Int(16), Value::Int(36)]));
+    }
+
+    #[test]
+    fn test_collection_iter_enumerate_and_zip() {
+        let source = r#"
+            def main() -> [(int, (int, str))] {
+                return [10, 20].iter().zip("ab".iter()).enumerate().map((p: (int, (int, str))) -> p).collect()
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        let pair = |i: i64, n: i64, c: &str| {
+            Value::Tuple(vec![
+                Value::Int(i),
+                Value::Tuple(vec![Value::Int(n), Value::String(c.to_string())]),
+            ])
+        };
+        assert_eq!(result, Value::vec(vec![pair(0, 10, "a"), pair(1, 20, "b")]));
+    }
+
+    #[test]
+    fn test_iterator_next_advances_shared_state() {
+        let source = r#"
+            def main() -> [int] {
+                it: Iterator = (0 $ 4).iter()
+                same: Iterator = it
+                first: int = it.next().unwrap()
+                second: int = same.next().unwrap()
+                rest: [int] = it.collect()
+                done: int = 0
+                if it.next().is_none() {
+                    done = 1
+                }
+                return [first, second, rest.length(), done]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![Value::Int(0), Value::Int(1), Value::Int(2), Value::Int(1)])
+        );
+    }
+
+    #[test]
+    fn test_user_type_implementing_iterator() {
+        let source = r#"
+            type Countdown { n: int }
+
+            impl Iterator: Countdown {
+                next() -> ?int {
+                    if self.n <= 0 {
+                        return None
+                    }
+                    current: int = self.n
+                    self = Countdown { n: self.n - 1 }
+                    return Some(current)
+                }
+            }
+
+            def main() -> [int] {
+                total: int = 0
+                for (v: int; Countdown { n: 4 }) {
+                    total = total + v
+                }
+                doubled: [int] = Countdown { n: 3 }.map((x: int) -> x * 2).collect()
+                doubled.push(total)
+                return doubled
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![Value::Int(6), Value::Int(4), Value::Int(2), Value::Int(10)])
+        );
+    }
+
+    #[test]
+    fn test_iterator_next_must_return_option() {
+        let source = r#"
+            type Broken { n: int }
+
+            impl Iterator: Broken {
+                next() -> ?int {
+                    return 5
+                }
+            }
+
+            def main() -> int {
+                for (v: int; Broken { n: 0 }) {
+                    return v
+                }
+                return 0
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Should fail at runtime");
+        assert!(err.contains("Iterator next() must return an Option"));
+    }
+
+    // ===== MODULE TESTS =====
+
+    /// Write `files` into a fresh directory and run `main.tj` with that directory on the search path
+    fn interpret_project(name: &str, files: &[(&str, &str)]) -> Result<Value, String> {
+        let root = std::env::temp_dir().join(format!("tjlang_modules_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for (path, source) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, source).unwrap();
+        }
+
+        let entry = root.join("main.tj");
+        let source = std::fs::read_to_string(&entry).unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.module_system_mut().add_search_path(root.clone());
+        let file_id = interpreter.module_system_mut().add_file(&entry, source.clone());
+        let (ast, _) = parse(&source, file_id).map_err(|e| format!("Parse error: {:?}", e))?;
+        let result = interpreter
+            .interpret_program(&ast)
+            .map_err(|e| format!("Runtime error: {}", e));
+        let _ = std::fs::remove_dir_all(&root);
+        result
+    }
+
+    #[test]
+    fn test_import_module_with_alias() {
+        let result = interpret_project("alias", &[
+            ("main.tj", "import geometry.shapes as s\ndef main() -> int {\n    return s.area(2, 3)\n}\n"),
+            ("geometry/shapes.tj", "export def area(w: int, h: int) -> int {\n    return w * h\n}\n"),
+        ]).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(6));
+    }
+
+    #[test]
+    fn test_selective_import_uses_module_scope() {
+        let result = interpret_project("selective", &[
+            ("main.tj", "import { scaled } from shapes\ndef main() -> int {\n    return scaled(4)\n}\n"),
+            ("shapes.tj", "def factor() -> int {\n    return 3\n}\nexport def scaled(n: int) -> int {\n    return n * factor()\n}\n"),
+        ]).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(12));
+    }
+
+    #[test]
+    fn test_module_executes_once() {
+        // Both importers see the channel created by a single run of `shared`
+        let result = interpret_project("once", &[
+            ("main.tj", "import left\nimport right\ndef main() -> int {\n    left.put(7)\n    return right.take()\n}\n"),
+            ("left.tj", "import { channel } from shared\nexport def put(n: int) -> int {\n    channel().send(n)\n    return n\n}\n"),
+            ("right.tj", "import { channel } from shared\nexport def take() -> int {\n    return channel().try_recv().unwrap()\n}\n"),
+            ("shared.tj", "ch = CHANNEL.new()\nexport def channel() -> Channel {\n    return ch\n}\n"),
+        ]).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(7));
+    }
+
+    #[test]
+    fn test_import_of_unexported_name_fails() {
+        let err = interpret_project("unexported", &[
+            ("main.tj", "import { hidden } from lib\ndef main() -> int {\n    return hidden()\n}\n"),
+            ("lib.tj", "def hidden() -> int {\n    return 1\n}\nexport def visible() -> int {\n    return 2\n}\n"),
+        ]).expect_err("Should fail at runtime");
+        assert!(err.contains("Module 'lib' does not export 'hidden'"));
+    }
+
+    #[test]
+    fn test_import_cycle_is_reported() {
+        let err = interpret_project("cycle", &[
+            ("main.tj", "import a\ndef main() -> int {\n    return 0\n}\n"),
+            ("a.tj", "import b\nexport def fa() -> int {\n    return 1\n}\n"),
+            ("b.tj", "import a\nexport def fb() -> int {\n    return 2\n}\n"),
+        ]).expect_err("Should fail at runtime");
+        assert!(err.contains("Import cycle detected: a -> b -> a"));
+    }
+
+    #[test]
+    fn test_missing_module_is_reported() {
+        let err = interpret_project("missing", &[
+            ("main.tj", "import nowhere\ndef main() -> int {\n    return 0\n}\n"),
+        ]).expect_err("Should fail at runtime");
+        assert!(err.contains("nowhere"));
+    }
+
+    #[test]
+    fn test_unexported_type_is_not_visible_to_importer() {
+        let lib = "type Hidden { x: int }\nexport def make() -> int {\n    h: Hidden = Hidden { x: 1 }\n    return h.x\n}\n";
+
+        // The module's own functions still see the type
+        let result = interpret_project("hidden_type_inside", &[
+            ("main.tj", "import lib\ndef main() -> int {\n    return lib.make()\n}\n"),
+            ("lib.tj", lib),
+        ]).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(1));
+
+        let err = interpret_project("hidden_type", &[
+            ("main.tj", "import lib\ndef main() -> int {\n    h: any = Hidden { x: 2 }\n    return h.x\n}\n"),
+            ("lib.tj", lib),
+        ]).expect_err("Should fail at runtime");
+        assert!(err.contains("Unknown struct type 'Hidden'"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_exported_type_keeps_its_methods() {
+        let result = interpret_project("exported_type", &[
+            ("main.tj", "import { Point } from geometry\ndef main() -> int {\n    p: Point = Point { x: 3, y: 4 }\n    return p.norm1()\n}\n"),
+            ("geometry.tj", "interface Norm { norm1() -> int }\ntype Point { x: int, y: int }\nimpl Norm: Point {\n    norm1() -> int {\n        return self.x + self.y\n    }\n}\nexport Point\n"),
+        ]).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(7));
+    }
+
+    // ===== SHARED CONTAINER TESTS =====
+
+    #[test]
+    fn test_push_through_struct_field_persists() {
+        let source = r#"
+            type Bag { items: [int] }
+            interface Fillable {
+                add(x: int) -> int
+            }
+            impl Fillable: Bag {
+                add(x: int) -> int {
+                    self.items.push(x)
+                    return self.items.len()
+                }
+            }
+            def main() -> [int] {
+                b: Bag = Bag { items: [] }
+                b.add(1)
+                b.add(2)
+                return b.items
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(1), Value::Int(2)]));
+    }
+
+    #[test]
+    fn test_push_through_index_persists() {
+        let source = r#"
+            def main() -> [[int]] {
+                grid: [[int]] = [[], [1]]
+                grid[0].push(5)
+                grid[1].push(6)
+                return grid
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![
+                Value::vec(vec![Value::Int(5)]),
+                Value::vec(vec![Value::Int(1), Value::Int(6)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_mutation_through_parameter_is_visible() {
+        let source = r#"
+            def fill(items: [int], count: int) -> int {
+                for (i: int; 0 $ count) {
+                    items.push(i)
+                }
+                return 0
+            }
+            def main() -> [int] {
+                v: [int] = []
+                fill(v, 3)
+                return v
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(0), Value::Int(1), Value::Int(2)]));
+    }
+
+    #[test]
+    fn test_copy_is_independent() {
+        let source = r#"
+            def main() -> ([int], [int], [int]) {
+                a: [int] = [1]
+                alias: [int] = a
+                copied: [int] = a.copy()
+                alias.push(2)
+                copied.push(3)
+                return (a, alias, copied)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::Tuple(vec![
+                Value::vec(vec![Value::Int(1), Value::Int(2)]),
+                Value::vec(vec![Value::Int(1), Value::Int(2)]),
+                Value::vec(vec![Value::Int(1), Value::Int(3)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_copy_is_deep() {
+        let source = r#"
+            def main() -> [[int]] {
+                grid: [[int]] = [[1]]
+                copied: [[int]] = grid.copy()
+                copied[0].push(2)
+                return grid
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::vec(vec![Value::Int(1)])]));
+    }
+
+    #[test]
+    fn test_pop_and_remove_return_element() {
+        let source = r#"
+            def main() -> [int] {
+                v: [int] = [10, 20, 30]
+                last: int = v.pop()
+                first: int = v.remove(0)
+                return [last, first, v.len()]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(30), Value::Int(10), Value::Int(1)]));
+    }
+
+    #[test]
+    fn test_map_and_set_mutation_is_shared() {
+        let source = r#"
+            def main() -> (int, int, bool, bool) {
+                m: Map<str, int> = {"a": 1}
+                other: Map<str, int> = m
+                other.insert("b", 2)
+                s: {int} = {1}
+                added: bool = s.insert(2)
+                again: bool = s.insert(2)
+                return (m.len(), s.len(), added, again)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::Tuple(vec![Value::Int(2), Value::Int(2), Value::Bool(true), Value::Bool(false)])
+        );
+    }
+
+    // ===== ASSIGNMENT TESTS =====
+
+    #[test]
+    fn test_index_assignment() {
+        let source = r#"
+            def main() -> ([int], int, int) {
+                v: [int] = [1, 2, 3]
+                v[1] = 20
+                m: Map<str, int> = {"a": 1}
+                m["b"] = 2
+                m["a"] = 10
+                return (v, m["a"], m["b"])
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::Tuple(vec![
+                Value::vec(vec![Value::Int(1), Value::Int(20), Value::Int(3)]),
+                Value::Int(10),
+                Value::Int(2),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_nested_field_and_index_assignment() {
+        let source = r#"
+            type Cell { c: int }
+            type Row { b: [Cell] }
+            def main() -> [int] {
+                a: Row = Row { b: [Cell { c: 0 }, Cell { c: 0 }, Cell { c: 0 }] }
+                a.b[2].c = 1
+                a.b[0].c += 5
+                return [a.b[0].c, a.b[1].c, a.b[2].c]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(5), Value::Int(0), Value::Int(1)]));
+    }
+
+    #[test]
+    fn test_compound_assignment_operators() {
+        let source = r#"
+            def main() -> [int] {
+                a: int = 10
+                a += 5
+                a -= 3
+                a *= 2
+                a /= 4
+                a %= 4
+                b: int = 3
+                b **= 3
+                c: int = 1
+                c <<= 4
+                c >>= 1
+                d: int = 12
+                d &= 10
+                d |= 1
+                d ^= 3
+                return [a, b, c, d]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::vec(vec![Value::Int(2), Value::Int(27), Value::Int(8), Value::Int(10)])
+        );
+    }
+
+    #[test]
+    fn test_compound_assignment_evaluates_index_once() {
+        let source = r#"
+            def first_index(calls: [int]) -> int {
+                calls.push(0)
+                return 0
+            }
+            def main() -> [int] {
+                calls: [int] = []
+                v: [int] = [1, 2]
+                v[first_index(calls)] += 10
+                return [v[0], v[1], calls.len()]
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::vec(vec![Value::Int(11), Value::Int(2), Value::Int(1)]));
+    }
+
+    #[test]
+    fn test_assignment_to_missing_field_fails() {
+        let source = r#"
+            type Point { x: int, y: int }
+            def main() -> int {
+                p: Point = Point { x: 1, y: 2 }
+                p.z = 3
+                return 0
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Should fail at runtime");
+        assert!(err.contains("Struct 'Point' has no field named 'z'"));
+    }
+
+    #[test]
+    fn test_assignment_out_of_bounds_fails() {
+        let source = r#"
+            def main() -> int {
+                v: [int] = [1]
+                v[3] = 2
+                return 0
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Should fail at runtime");
+        assert!(err.contains("Index out of bounds"));
+    }
+
+    // ===== OPERATOR OVERLOADING TESTS =====
+
+    #[test]
+    fn test_arithmetic_operator_overloading() {
+        let source = r#"
+            type Vec2 { x: int, y: int }
+            interface Arith {
+                +(other: Vec2) -> Vec2
+                -(other: Vec2) -> Vec2
+            }
+            impl Arith: Vec2 {
+                +(other: Vec2) -> Vec2 {
+                    return Vec2 { x: self.x + other.x, y: self.y + other.y }
+                }
+                -(other: Vec2) -> Vec2 {
+                    return Vec2 { x: self.x - other.x, y: self.y - other.y }
+                }
+            }
+            interface Negate {
+                -() -> Vec2
+            }
+            impl Negate: Vec2 {
+                -() -> Vec2 {
+                    return Vec2 { x: 0 - self.x, y: 0 - self.y }
+                }
+            }
+            def main() -> (int, int, int) {
+                a: Vec2 = Vec2 { x: 1, y: 2 }
+                b: Vec2 = a + a - Vec2 { x: 1, y: 1 }
+                b += a
+                c: Vec2 = -b
+                return (b.x, b.y, c.y)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Tuple(vec![Value::Int(2), Value::Int(5), Value::Int(-5)]));
+    }
+
+    #[test]
+    fn test_comparisons_derived_from_order_and_eq() {
+        let source = r#"
+            type Money { cents: int }
+            impl Order: Money {
+                <(other: Money) -> bool {
+                    return self.cents < other.cents
+                }
+            }
+            impl Eq: Money {
+                ==(other: Money) -> bool {
+                    return self.cents / 100 == other.cents / 100
+                }
+            }
+            def main() -> (bool, bool, bool, bool, bool, bool) {
+                m: Money = Money { cents: 150 }
+                n: Money = Money { cents: 199 }
+                return (m < n, m > n, m <= n, m >= n, m == n, m != n)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::Tuple(vec![
+                Value::Bool(true),
+                Value::Bool(false),
+                Value::Bool(true),
+                Value::Bool(false),
+                Value::Bool(true),
+                Value::Bool(false),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_index_and_unary_operator_overloading() {
+        let source = r#"
+            type Grid { cells: [int] }
+            interface Negate {
+                -() -> Grid
+            }
+            impl Indexable: Grid {
+                [](i: int) -> int {
+                    return self.cells[i] * 10
+                }
+            }
+            impl Negate: Grid {
+                -() -> Grid {
+                    return Grid { cells: [0 - self.cells[0]] }
+                }
+            }
+            def main() -> (int, int) {
+                g: Grid = Grid { cells: [1, 2, 3] }
+                return (g[2], (-g)[0])
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Tuple(vec![Value::Int(30), Value::Int(-10)]));
+    }
+
+    #[test]
+    fn test_missing_operator_impl_fails() {
+        let source = r#"
+            type Vec2 { x: int, y: int }
+            def main() -> int {
+                a: Vec2 = Vec2 { x: 1, y: 2 }
+                b: Vec2 = a * a
+                return 0
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Should fail at runtime");
+        assert!(err.contains("Type 'Vec2' does not implement operator '*'"));
+    }
+
+    #[test]
+    fn test_commutative_operator_dispatches_on_right_operand() {
+        let source = r#"
+            type Money { cents: int }
+            interface Scale {
+                *(factor: int) -> Money
+                -(amount: int) -> Money
+            }
+            impl Scale: Money {
+                *(factor: int) -> Money {
+                    return Money { cents: self.cents * factor }
+                }
+                -(amount: int) -> Money {
+                    return Money { cents: self.cents - amount }
+                }
+            }
+            def main() -> int {
+                m: Money = Money { cents: 5 }
+                return (3 * m).cents
+            }
+        "#;
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(15));
+
+        let source = source.replace("(3 * m).cents", "(3 - m).cents");
+        let err = interpret_code(&source).expect_err("Should fail at runtime");
+        assert!(err.contains("Operator '-' is not commutative"));
+    }
+
+    // ===== GENERIC TESTS =====
+
+    #[test]
+    fn test_generic_function_with_bounds() {
+        let source = r#"
+            type Money { cents: int }
+            impl Order: Money {
+                <(other: Money) -> bool {
+                    return self.cents < other.cents
+                }
+            }
+            def smaller<T: implements [Order]>(a: T, b: T) -> T {
+                if b < a {
+                    return b
+                }
+                return a
+            }
+            def main() -> (int, float, int) {
+                m: Money = smaller(Money { cents: 5 }, Money { cents: 3 })
+                return (smaller(4, 9), smaller(2.5, 1.5), m.cents)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::Tuple(vec![Value::Int(4), Value::Float(1.5), Value::Int(3)])
+        );
+    }
+
+    #[test]
+    fn test_generic_bound_violation_fails() {
+        let source = r#"
+            type Point { x: int }
+            def same<T: implements [Eq]>(a: T, b: T) -> bool {
+                return a == b
+            }
+            def main() -> bool {
+                return same(Point { x: 1 }, Point { x: 1 })
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Should fail at runtime");
+        assert!(err.contains("requires type parameter 'T' to implement 'Eq', but 'Point' does not"));
+    }
+
+    #[test]
+    fn test_generic_parameter_binds_one_type() {
+        let source = r#"
+            def same<T: implements [Eq]>(a: T, b: T) -> bool {
+                return a == b
+            }
+            def main() -> bool {
+                return same(1, "one")
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Should fail at runtime");
+        assert!(err.contains("parameter 'b' (position 2)"));
+    }
+
+    #[test]
+    fn test_generic_collection_parameter() {
+        let source = r#"
+            def first<T: implements [Eq]>(items: [T]) -> T {
+                return items[0]
+            }
+            def main() -> int {
+                return first([7, 8])
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(7));
+    }
+
+    // ===== DEFAULT AND KEYWORD ARGUMENT TESTS =====
+
+    #[test]
+    fn test_default_parameter_values() {
+        let source = r#"
+            def connect(host: str, port: int = 8080) -> str {
+                return host + ":" + port.to_string()
+            }
+            def main() -> (str, str) {
+                return (connect("a"), connect("b", 1))
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::Tuple(vec![
+                Value::String("a:8080".to_string()),
+                Value::String("b:1".to_string())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_keyword_arguments_in_any_order() {
+        let source = r#"
+            def span(start: int, end: int = 10, step: int = 1) -> int {
+                return (end - start) / step
+            }
+            def main() -> (int, int, int) {
+                return (span(0, step: 2), span(step: 5, start: 0), span(4, end: 8))
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(
+            result,
+            Value::Tuple(vec![Value::Int(5), Value::Int(2), Value::Int(4)])
+        );
+    }
+
+    #[test]
+    fn test_default_refers_to_earlier_parameter() {
+        let source = r#"
+            def area(width: int, height: int = width) -> int {
+                return width * height
+            }
+            def main() -> int {
+                return area(3)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(9));
+    }
+
+    #[test]
+    fn test_default_and_keyword_on_method() {
+        let source = r#"
+            type Counter { n: int }
+            interface Step {
+                bump(by: int) -> int
+            }
+            impl Step: Counter {
+                bump(by: int = 1) -> int {
+                    return self.n + by
+                }
+            }
+            def main() -> (int, int) {
+                c: Counter = Counter { n: 10 }
+                return (c.bump(), c.bump(by: 5))
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Tuple(vec![Value::Int(11), Value::Int(15)]));
+    }
+
+    #[test]
+    fn test_keyword_argument_errors() {
+        let cases = [
+            ("connect(\"a\", hots: 1)", "has no parameter named 'hots'"),
+            ("connect(\"a\", host: \"b\")", "got multiple values for parameter 'host'"),
+            ("connect(port: 1)", "is missing an argument for parameter 'host'"),
+            ("connect()", "expects 1 to 2 arguments, got 0"),
+            ("connect(\"a\", 1, 2)", "expects 1 to 2 arguments, got 3"),
+            ("connect(\"a\", port: \"x\")", "parameter 'port' (position 2)"),
+        ];
+        for (call, expected) in cases {
+            let source = format!(
+                r#"
+                def connect(host: str, port: int = 8080) -> str {{
+                    return host
+                }}
+                def main() -> str {{
+                    return {}
+                }}
+            "#,
+                call
+            );
+            let err = interpret_code(&source).expect_err("Should fail at runtime");
+            assert!(err.contains(expected), "Unexpected error for {}: {}", call, err);
+        }
+    }
+
+    // ===== CALL STACK TESTS =====
+
+    #[test]
+    fn test_runtime_error_records_call_chain() {
+        let source = r#"
+            def divide(a: int, b: int) -> int {
+                return a / b
+            }
+
+            def average(items: [int], count: int) -> int {
+                return divide(6, count)
+            }
+
+            def main() -> int {
+                return average([1, 2, 3], 0)
+            }
+        "#;
+
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let mut interpreter = Interpreter::new();
+        let err = interpreter
+            .interpret_program(&ast)
+            .expect_err("Division by zero should fail");
+
+        let functions: Vec<&str> = err.stack.iter().map(|f| f.function.as_str()).collect();
+        assert_eq!(functions, vec!["divide", "average", "main"]);
+        assert_eq!(err.stack[0].arguments, "a = 6, b = 0");
+        assert_eq!(err.stack[1].arguments, "items = [1, 2, 3], count = 0");
+    }
+
+    #[test]
+    fn test_recursion_limit_reports_stack_overflow() {
+        let source = r#"
+            def forever(n: int) -> int {
+                return forever(n + 1)
+            }
+
+            def main() -> int {
+                return forever(0)
+            }
+        "#;
+
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let mut interpreter = Interpreter::new();
+        interpreter.set_max_call_depth(8);
+        let err = interpreter
+            .interpret_program(&ast)
+            .expect_err("Unbounded recursion should fail");
+
+        assert!(err.message.contains("Stack overflow"), "unexpected error: {}", err.message);
+        assert_eq!(err.stack.len(), 8);
+        assert_eq!(err.stack[0].arguments, "n = 6");
+    }
+
+    #[test]
+    fn test_recursion_through_nested_control_flow_reports_stack_overflow() {
+        // Each call nests several statements deep, which takes far more stack
+        // than the test thread has before the depth limit is reached
+        let source = r#"
+            def dive(n: int) -> int {
+                if n >= 0 {
+                    while true {
+                        for (i: int; 0$1) {
+                            match n {
+                                _ : {
+                                    if true {
+                                        return 1 + (2 * (3 + dive(n + 1)))
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                return 0
+            }
+
+            def main() -> int {
+                return dive(0)
+            }
+        "#;
+
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let mut interpreter = Interpreter::new();
+        let err = interpreter
+            .interpret_program(&ast)
+            .expect_err("Unbounded recursion should fail");
+
+        assert!(err.message.contains("Stack overflow"), "unexpected error: {}", err.message);
+    }
+
+    #[test]
+    fn test_top_level_error_points_at_statement() {
+        let source = "x: int = 1\ny: int = missing + 1\n";
+
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let mut interpreter = Interpreter::new();
+        let err = interpreter
+            .interpret_program(&ast)
+            .expect_err("Undefined variable should fail");
+
+        let start = err.span.start().to_usize();
+        assert_eq!(&source[start..start + "missing".len()], "missing");
+    }
+
+    // ===== SCOPE RESOLUTION TESTS =====
+
+    #[test]
+    fn test_functions_assign_shared_globals() {
+        let source = r#"
+            counter: int = 0
+
+            def bump() -> int {
+                counter += 1
+                return counter
+            }
+
+            def main() -> int {
+                bump()
+                bump()
+                return counter
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(2));
+    }
+
+    #[test]
+    fn test_callee_does_not_see_caller_locals() {
+        let source = r#"
+            def peek() -> int {
+                return secret
+            }
+
+            def main() -> int {
+                secret: int = 5
+                return peek()
+            }
+        "#;
+
+        let err = interpret_code(source).expect_err("Caller locals should not be visible");
+        assert!(err.contains("Undefined variable: secret"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_nested_closures_capture_enclosing_frames() {
+        let source = r#"
+            def curry(a: int) -> any {
+                return (b: int) -> ((c: int) -> a * 100 + b * 10 + c)
+            }
+
+            def main() -> int {
+                first: any = curry(1)
+                second: any = first(2)
+                return second(3)
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(123));
+    }
+
+    #[test]
+    fn test_match_binding_does_not_overwrite_local() {
+        let source = r#"
+            def main() -> int {
+                x: int = 1
+                inner: int = match 5 {
+                    x: int: { x * 2 }
+                }
+                return x * 100 + inner
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(110));
+    }
+
+    #[test]
+    fn test_local_assigned_later_in_loop() {
+        let source = r#"
+            def main() -> int {
+                total: int = 0
+                for (i: int; 0 $ 4) {
+                    if i > 0 {
+                        total = total + previous
+                    }
+                    previous: int = i * 10
+                }
+                return total
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(30));
+    }
+
+    // ===== BYTECODE VM TESTS =====
+
+    #[test]
+    fn test_vm_loops_with_break_and_continue() {
+        let source = r#"
+            def main() -> int {
+                total: int = 0
+                i: int = 0
+                while true {
+                    i = i + 1
+                    if i % 2 == 0 {
+                        continue
+                    }
+                    if i > 9 {
+                        break
+                    }
+                    total += i
+                }
+                for (j: int; 0 $= 3) {
+                    total = total + j * 100
+                }
+                return total
+            }
+        "#;
+
+        let result = run_on_vm(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(625));
+    }
+
+    #[test]
+    fn test_vm_closures_capture_locals() {
+        let source = r#"
+            def adder(n: int) -> any {
+                return (x: int) -> x + n
+            }
+
+            def main() -> int {
+                add_five: any = adder(5)
+                return add_five(10)
+            }
+        "#;
+
+        let result = run_on_vm(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(15));
+    }
+
+    #[test]
+    fn test_vm_match_expression_and_statement() {
+        let source = r#"
+            def classify(n: int) -> int {
+                match n {
+                    0: { return 0 }
+                    k: int if k < 0: { return -1 }
+                    _: { return 1 }
+                }
+                return 99
+            }
+
+            def main() -> int {
+                picked: int = match (2, 3) {
+                    (a, b): { a * b }
+                }
+                return picked * 100 + classify(-5) + classify(7)
+            }
+        "#;
+
+        let result = run_on_vm(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(600));
+        assert_eq!(interpret_code(source), Ok(result));
+    }
+
+    #[test]
+    fn test_vm_raise_caught_across_calls() {
+        let source = r#"
+            def fail(n: int) -> int {
+                if n > 2 {
+                    raise n
+                }
+                return n
+            }
+
+            def main() -> int {
+                total: int = 0
+                try {
+                    for (i: int; 0 $ 10) {
+                        total += fail(i)
+                    }
+                } except code: int {
+                    total = total + code * 100
+                }
+                return total
+            }
+        "#;
+
+        let result = run_on_vm(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(303));
+    }
+
+    #[test]
+    fn test_vm_keyword_arguments_and_defaults() {
+        let source = r#"
+            def span(start: int, end: int = 10, step: int = 1) -> int {
+                return (end - start) / step
+            }
+
+            def main() -> int {
+                return span(0, step: 2) * 10 + span(step: 5, start: 0)
+            }
+        "#;
+
+        let result = run_on_vm(source).expect("Should parse and run successfully");
+        assert_eq!(result, Value::Int(52));
+    }
+
+    #[test]
+    fn test_vm_runtime_error_matches_interpreter() {
+        let source = r#"
+            def divide(a: int, b: int) -> int {
+                return a / b
+            }
+
+            def main() -> int {
+                return divide(1, 0)
+            }
+        "#;
+
+        let interpreted = interpret_code(source).expect_err("Division by zero should fail");
+        let compiled = run_on_vm(source).expect_err("Division by zero should fail");
+        assert_eq!(interpreted, compiled);
+    }
+
+    // ===== COMPILED PROGRAM TESTS =====
+
+    /// Helper function to compile TJLang code, save it and run the loaded copy
+    fn run_compiled(source: &str) -> Result<Value, String> {
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).map_err(|e| format!("Parse error: {:?}", e))?;
+        let bytes = CompiledProgram::compile(ast, "test.tj".to_string(), source, file_id).to_bytes()?;
+        let compiled = CompiledProgram::from_bytes(&bytes)?;
+
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .interpret_compiled(&compiled)
+            .map_err(|e| format!("Runtime error: {}", e))
+    }
+
+    #[test]
+    fn test_compiled_program_runs_after_loading() {
+        let source = r#"
+            type Point { x: int, y: int }
+
+            scale: int = 3
+
+            def apply(f: any, value: int) -> int {
+                return f(value)
+            }
+
+            def total(points: [Point], offset: int = 0.5.to_string().length()) -> int {
+                sum: int = offset
+                for (p: Point; points) {
+                    sum += p.x * p.y
+                }
+                return sum
+            }
+
+            def main() -> int {
+                points: [Point] = [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]
+                doubled: int = apply((n: int) -> n * scale, total(points))
+                return match doubled {
+                    n: int if n > 40: { n + 1000 }
+                    _: { -1 }
+                }
+            }
+        "#;
+
+        let result = run_compiled(source).expect("Should compile, load and run successfully");
+        assert_eq!(result, Value::Int(1051));
+        assert_eq!(interpret_code(source), Ok(result));
+    }
+
+    #[test]
+    fn test_compiled_program_keeps_error_locations() {
+        let source = r#"
+            def main() -> int {
+                zero: int = 0
+                return 1 / zero
+            }
+        "#;
+
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let interpreted = Interpreter::new()
+            .interpret_program(&ast)
+            .expect_err("Division by zero should fail");
+        let bytes = CompiledProgram::compile(ast, "test.tj".to_string(), source, file_id)
+            .to_bytes()
+            .expect("Should encode");
+        let compiled = CompiledProgram::from_bytes(&bytes).expect("Should decode");
+        assert!(compiled.matches_source(source));
+        assert!(!compiled.matches_source("def main() -> int { return 0 }"));
+
+        let err = Interpreter::new()
+            .interpret_compiled(&compiled)
+            .expect_err("Division by zero should fail");
+        assert_eq!(err.message, interpreted.message);
+        assert_eq!((err.file_id, err.span), (interpreted.file_id, interpreted.span));
+    }
+
+    #[test]
+    fn test_compiled_program_rejects_other_versions_and_corrupt_files() {
+        let source = "def main() -> int { return 1 }";
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let bytes = CompiledProgram::compile(ast, "test.tj".to_string(), source, file_id)
+            .to_bytes()
+            .expect("Should encode");
+
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        let err = CompiledProgram::from_bytes(&newer).expect_err("Other versions should be rejected");
+        assert!(err.contains("format version"), "unexpected error: {}", err);
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(CompiledProgram::from_bytes(truncated).is_err());
+        assert!(CompiledProgram::from_bytes(b"not bytecode").is_err());
+    }
+
+    // ===== GARBAGE COLLECTION TESTS =====
+
+    #[test]
+    fn test_gc_collects_unreachable_cycles() {
+        let source = r#"
+            type Node { name: str, next: any }
+
+            def make_cycle() -> int {
+                a: Node = Node { name: "a", next: None }
+                b: Node = Node { name: "b", next: a }
+                a.next = b
+                return 0
+            }
+
+            def main() -> int {
+                GC.collect()
+                for (i: int; 0 $ 10) {
+                    make_cycle()
+                }
+                keep: Node = Node { name: "keep", next: None }
+                keep.next = keep
+                collected: int = GC.collect()
+                return match keep.next.next.name {
+                    "keep": { collected }
+                    _: { -1 }
+                }
+            }
+        "#;
+
+        // Dropping the first interpreter leaves garbage, which each program
+        // collects before making its own
+        for result in [interpret_code(source), run_on_vm(source)] {
+            assert_eq!(result.expect("Should run successfully"), Value::Int(20));
+        }
+    }
+
+    #[test]
+    fn test_gc_collects_containers_and_closures_in_cycles() {
+        let source = r#"
+            type Counter { count: int, bump: any }
+
+            def make_garbage() -> int {
+                items: [any] = [1, 2]
+                items.push(items)
+                lookup: Map<str, any> = {"self": 0}
+                lookup.insert("self", lookup)
+                counter: Counter = Counter { count: 0, bump: None }
+                counter.bump = () -> counter.count + 1
+                return 0
+            }
+
+            def main() -> int {
+                GC.collect()
+                make_garbage()
+                return GC.collect()
+            }
+        "#;
+
+        // The vector, the map, the counter and the frame its closure captured
+        for result in [interpret_code(source), run_on_vm(source)] {
+            assert_eq!(result.expect("Should run successfully"), Value::Int(4));
+        }
+    }
+
+    #[test]
+    fn test_gc_stats() {
+        let source = r#"
+            type Node { next: any }
+
+            def main() -> int {
+                for (i: int; 0 $ 3000) {
+                    n: Node = Node { next: None }
+                    n.next = n
+                }
+                stats: GcStats = GC.stats()
+                return stats.collections * 100000 + stats.objects_collected
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should run successfully");
+        let Value::Int(encoded) = result else {
+            panic!("Expected an int, got {:?}", result);
+        };
+        let (collections, collected) = (encoded / 100000, encoded % 100000);
+        assert!(collections >= 2, "expected automatic collections, got {}", collections);
+        assert!(collected >= 2000, "expected cycles to be collected, got {}", collected);
+    }
+
+    // ===== INTEGER OVERFLOW TESTS =====
+
+    /// Helper function to run TJLang code with overflow promoting to big integers
+    fn run_with_promotion(source: &str, backend: Backend) -> Result<Value, String> {
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).map_err(|e| format!("Parse error: {:?}", e))?;
+
+        let mut interpreter = Interpreter::new();
+        interpreter.set_backend(backend);
+        interpreter.set_int_overflow(IntOverflow::Promote);
+        interpreter
+            .interpret_program(&ast)
+            .map_err(|e| format!("Runtime error: {}", e))
+    }
+
+    #[test]
+    fn test_integer_overflow_is_an_error() {
+        let cases = [
+            ("9223372036854775807 + 1", "9223372036854775807 + 1"),
+            ("-9223372036854775807 - 2", "-9223372036854775807 - 2"),
+            ("4611686018427387904 * 2", "4611686018427387904 * 2"),
+            ("(-9223372036854775807 - 1) / -1", "-9223372036854775808 / -1"),
+            ("-(-9223372036854775807 - 1)", "-(-9223372036854775808)"),
+            ("3 ** 40", "3 ** 40"),
+            ("1 << 63", "1 << 63"),
+            ("-3 << 62", "-3 << 62"),
+        ];
+        for (expression, shown) in cases {
+            let source = format!("def main() -> int {{ return {} }}", expression);
+            for result in [interpret_code(&source), run_on_vm(&source)] {
+                let err = result.expect_err("Overflow should be an error");
+                assert!(
+                    err.contains(&format!("Integer overflow in {}", shown)),
+                    "unexpected error for {}: {}",
+                    expression,
+                    err
+                );
+            }
+        }
+
+        // Values at the edge of the range are fine
+        let source = "def main() -> int { return (-9223372036854775807 - 1) % -1 + 9223372036854775806 + 1 }";
+        assert_eq!(interpret_code(source), Ok(Value::Int(i64::MAX)));
+    }
+
+    #[test]
+    fn test_integer_overflow_promotes_to_big_integers() {
+        let source = r#"
+            def factorial(n: int) -> int {
+                result: int = 1
+                i: int = 2
+                while i <= n {
+                    result = result * i
+                    i = i + 1
+                }
+                return result
+            }
+
+            def main() -> str {
+                big: int = factorial(30)
+                back: int = big / factorial(28) - 870
+                larger: bool = big > 9223372036854775807
+                return big.to_string() + " " + back.to_string() + " " + back.is_zero().to_string() + " " + larger.to_string() + " " + (-big % 1000000007).to_string() + " " + (2 ** 100).to_string()
+            }
+        "#;
+
+        let expected = "265252859812191058636308480000000 0 true true -109361473 1267650600228229401496703205376";
+        for backend in [Backend::Interpreter, Backend::Vm] {
+            let result = run_with_promotion(source, backend).expect("Should run successfully");
+            assert_eq!(result, Value::String(expected.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_shifts_and_bitwise_operators_on_big_integers() {
+        let source = r#"
+            def main() -> str {
+                big: int = 1 << 100
+                results: [int] = [
+                    big,
+                    big >> 98,
+                    -big >> 99,
+                    (-big - 1) >> 99,
+                    (big | 5) & 7,
+                    (1 << 64) ^ -1,
+                    -(1 << 70) & ((1 << 72) - 1),
+                    -5 >> 70
+                ]
+                text: str = ""
+                for (r: int; results) {
+                    text = text + r.to_string() + " "
+                }
+                return text
+            }
+        "#;
+
+        let expected = "1267650600228229401496703205376 4 -2 -3 5 -18446744073709551617 3541774862152233910272 -1 ";
+        for backend in [Backend::Interpreter, Backend::Vm] {
+            let result = run_with_promotion(source, backend).expect("Should run successfully");
+            assert_eq!(result, Value::String(expected.to_string()));
+        }
+
+        let err = interpret_code("def main() -> int { return 1 << -1 }").expect_err("Negative shifts are an error");
+        assert!(err.contains("Shift amount -1 is out of range"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_math_factorial_follows_overflow_policy() {
+        let source = "def main() -> str { return MATH.factorial(25).to_string() }";
+        let err = interpret_code(source).expect_err("25! does not fit in an int");
+        assert!(err.contains("Integer overflow in MATH::factorial(25)"), "unexpected error: {}", err);
+
+        let result = run_with_promotion(source, Backend::Interpreter).expect("Should run successfully");
+        assert_eq!(result, Value::String("15511210043330985984000000".to_string()));
+    }
+
+    // ===== RUNTIME TYPE CHECK TESTS =====
+
+    #[test]
+    fn test_variable_type_mismatch_is_an_error() {
+        let source = r#"
+            def main() -> int {
+                name: str = 42
+                return 0
+            }
+        "#;
+
+        for result in [interpret_code(source), run_on_vm(source)] {
+            let err = result.expect_err("Binding an int to a str variable should fail");
+            assert!(
+                err.contains("Type mismatch: variable 'name' is declared as str, got int"),
+                "unexpected error: {}",
+                err
+            );
+        }
+
+        let source = "values: [int] = [1, 2, \"three\"]";
+        let err = interpret_code(source).expect_err("Mixed vec should not fit [int]");
+        assert!(err.contains("variable 'values' is declared as [int]"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_return_type_mismatch_is_an_error() {
+        let source = r#"
+            def half(n: int) -> int {
+                if n % 2 == 1 {
+                    return "odd"
+                }
+                return n / 2
+            }
+
+            def main() -> str {
+                half(4)
+                half(3)
+                return "unreachable"
+            }
+        "#;
+
+        for result in [interpret_code(source), run_on_vm(source)] {
+            let err = result.expect_err("Returning a str from an int function should fail");
+            assert!(
+                err.contains("Type mismatch: function 'half' is declared to return int, got str"),
+                "unexpected error: {}",
+                err
+            );
+        }
+
+        let source = r#"
+            interface Labelled { label() -> str }
+
+            type Counter { n: int }
+
+            impl Labelled: Counter {
+                label() -> str {
+                    return self.n
+                }
+            }
+
+            def main() -> str {
+                return Counter { n: 1 }.label()
+            }
+        "#;
+        let err = interpret_code(source).expect_err("Method return type should be checked");
+        assert!(
+            err.contains("function 'Counter.label' is declared to return str, got int"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_only_type_parameters_in_scope_accept_any_value() {
+        let source = "def main() -> int { x: Missing = 5\n return 0 }";
+        let err = interpret_code(source).expect_err("An undeclared type name should not match");
+        assert!(err.contains("variable 'x' is declared as Missing, got int"), "unexpected error: {}", err);
+
+        let source = r#"
+            type Tag { name: str }
+            impl Eq: Tag {
+                ==(other: Tag) -> bool {
+                    return self.name == other.name
+                }
+            }
+
+            def keeper<T: implements [Eq]>(value: T) -> any {
+                copy: T = value
+                return (v: T) -> v
+            }
+
+            def main() -> str {
+                tag: Tag = Tag { name: "kept" }
+                keep: any = keeper(tag)
+                return keep(tag).name
+            }
+        "#;
+        for result in [interpret_code(source), run_on_vm(source)] {
+            assert_eq!(result, Ok(Value::String("kept".to_string())));
+        }
+    }
+
+    #[test]
+    fn test_generic_collection_and_option_types_check_their_arguments() {
+        let source = r#"
+            def main() -> int {
+                m: Map<str, int> = {"a": "b"}
+                return 0
+            }
+        "#;
+        for result in [interpret_code(source), run_on_vm(source)] {
+            let err = result.expect_err("A str value should not fit Map<str, int>");
+            assert!(
+                err.contains("variable 'm' is declared as Map<str, int>, got Map<str, str>"),
+                "unexpected error: {}",
+                err
+            );
+        }
+
+        let source = r#"
+            def find(xs: [int], target: int) -> Option<int> {
+                for (x: int; xs) {
+                    if x == target {
+                        return Some(x)
+                    }
+                }
+                return None
+            }
+
+            def parse(text: str) -> Result<int, str> {
+                if text == "" {
+                    return Err("empty")
+                }
+                return Ok(text.length())
+            }
+
+            def main() -> any {
+                counts: Map<str, int> = {"a": 1}
+                return [find([1, 2], 2), find([1, 2], 5), parse("abc"), parse("")]
+            }
+        "#;
+        let expected = Value::vec(vec![
+            Value::some(Value::Int(2)),
+            Value::None,
+            Value::ok(Value::Int(3)),
+            Value::err(Value::String("empty".to_string())),
+        ]);
+        assert_eq!(interpret_code(source), Ok(expected.clone()));
+        assert_eq!(run_on_vm(source), Ok(expected));
+
+        let source = "def main() -> Result<int, str> { return Ok(\"three\") }";
+        let err = interpret_code(source).expect_err("A str should not fit Result<int, str>");
+        assert!(err.contains("declared to return Result<int, str>"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_float_is_not_an_int() {
+        let source = "def main() -> int { x: int = 2.5\n return 0 }";
+        let err = interpret_code(source).expect_err("A float should not fit an int");
+        assert!(err.contains("variable 'x' is declared as int, got float"), "unexpected error: {}", err);
+
+        let source = "def main() -> float { x: float = 2\n return x }";
+        assert_eq!(interpret_code(source), Ok(Value::Int(2)));
+    }
+
+    #[test]
+    fn test_closure_parameter_types_are_checked() {
+        let source = r#"
+            def main() -> int {
+                inc: any = (x: int) -> x + 1
+                return inc("one")
+            }
+        "#;
+        for result in [interpret_code(source), run_on_vm(source)] {
+            let err = result.expect_err("A str argument should not fit an int parameter");
+            assert!(
+                err.contains("Closure parameter 'x' (position 1) expects type int, got str"),
+                "unexpected error: {}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_implicit_return_value_is_checked() {
+        let source = r#"
+            def f() -> int { "x" }
+
+            def main() -> int {
+                return f()
+            }
+        "#;
+        for result in [interpret_code(source), run_on_vm(source)] {
+            let err = result.expect_err("The last statement's value should be checked");
+            assert!(
+                err.contains("Type mismatch: function 'f' is declared to return int, got str"),
+                "unexpected error: {}",
+                err
+            );
+        }
+
+        let source = r#"
+            interface Sized { size() -> int }
+
+            type Bag { items: [int] }
+
+            impl Sized: Bag {
+                size() -> int {
+                    "many"
+                }
+            }
+
+            def main() -> int {
+                return Bag { items: [1] }.size()
+            }
+        "#;
+        let err = interpret_code(source).expect_err("A method's last statement value should be checked");
+        assert!(
+            err.contains("function 'Bag.size' is declared to return int, got str"),
+            "unexpected error: {}",
+            err
+        );
+
+        let source = "def g() -> int { 41 + 1 }\ndef main() -> int { return g() }";
+        assert_eq!(interpret_code(source), Ok(Value::Int(42)));
+    }
+
+    #[test]
+    fn test_union_declarations_wrap_values() {
+        let source = r#"
+            def describe(value: int | str) -> str {
+                return match value {
+                    n: int: { "int " + (n + 1).to_string() }
+                    s: str: { "str " + s.upper() }
+                }
+            }
+
+            def main() -> int | str {
+                x: int | str = 41
+                y: int | str = "hi"
+                plain: int = x + 1
+                if describe(x) == "int 42" and describe(y) == "str HI" and plain == 42 {
+                    return x
+                }
+                return "mismatch"
+            }
+        "#;
+
+        for result in [interpret_code(source), run_on_vm(source)] {
+            let result = result.expect("Should run successfully");
+            match &result {
+                Value::Union { value, possible_types } => {
+                    assert_eq!(**value, Value::Int(41));
+                    assert_eq!(possible_types, &vec!["int".to_string(), "str".to_string()]);
+                }
+                other => panic!("Expected a union value, got: {:?}", other),
+            }
+            assert_eq!(result, Value::Int(41));
+            assert_eq!(result.to_string(), "41");
+        }
+
+        let err = interpret_code("x: int | str = true").expect_err("A bool is neither int nor str");
+        assert!(
+            err.contains("Type mismatch: variable 'x' is declared as int | str, got bool"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_runtime_typecheck_can_be_disabled() {
+        let source = r#"
+            def name() -> str {
+                return 7
+            }
+
+            def main() -> int {
+                count: int = "three"
+                either: int | str = name()
+                return either
+            }
+        "#;
+
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let mut interpreter = Interpreter::new();
+        interpreter.set_runtime_typecheck(false);
+        let result = interpreter.interpret_program(&ast).expect("Should run without type checks");
+        assert!(matches!(result, Value::Int(7)));
+    }
+
+    // ===== STDLIB BINDING TESTS =====
+
+    #[test]
+    fn test_stdlib_checks_arguments_against_signatures() {
+        let err = interpret_code("def main() -> float { return MATH.sqrt() }").expect_err("sqrt needs an argument");
+        assert!(err.contains("MATH::sqrt expects 1 argument(s), got 0"), "unexpected error: {}", err);
+
+        let err = interpret_code("def main() -> str { return STRING.pad_left(\"7\") }").expect_err("pad_left needs a width");
+        assert!(err.contains("STRING::pad_left expects 2 to 3 argument(s), got 1"), "unexpected error: {}", err);
+
+        let err = interpret_code("def main() -> float { return MATH.sqrt(\"four\") }").expect_err("sqrt takes a float");
+        assert!(err.contains("MATH::sqrt expects float for 'a', got str"), "unexpected error: {}", err);
+
+        let err = interpret_code("def main() -> str { return STRING.pad_left(\"7\", -1) }")
+            .expect_err("Widths are non-negative");
+        assert!(
+            err.contains("STRING::pad_left expects a non-negative int for 'width', got -1"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_stdlib_bindings_convert_arguments_and_results() {
+        let source = r#"
+            def main() -> str {
+                values: [int] = [3, 1, 2]
+                COLLECTIONS.array_sort(values)
+                midnight: int = TIME.from_components(2024, 1, 1)
+                return STRING.pad_left("7", 3, "0") + " " + MATH.mean([1, 2, 3]).to_string() + " " + values.to_string()
+                    + " " + midnight.to_string() + " " + STRING.format("{0}-{1}", 1, "b")
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should run successfully");
+        assert_eq!(result, Value::String("007 2 [1, 2, 3] 1704067200 1-b".to_string()));
+    }
+
+    #[test]
+    fn test_stdlib_signatures_match_registry() {
+        use crate::stdlib_integration::{stdlib_signatures, StdlibRegistry};
+
+        let registry = StdlibRegistry::new();
+        let signatures = stdlib_signatures();
+        assert_eq!(signatures.len(), registry.get_function_names().len());
+        for signature in signatures {
+            assert!(registry.has_function(&signature.qualified_name()), "{} is not registered", signature);
+            assert!(!signature.doc().is_empty(), "{} is undocumented", signature);
+        }
+
+        let pad_left = signatures.iter().find(|s| s.qualified_name() == "STRING::pad_left").unwrap();
+        assert_eq!(pad_left.to_string(), "STRING.pad_left(s: str, width: int, [fill_char: str]) -> str");
+        assert_eq!((pad_left.min_args(), pad_left.max_args()), (2, Some(3)));
+
+        let printf = signatures.iter().find(|s| s.qualified_name() == "IO::printf").unwrap();
+        assert_eq!(printf.to_string(), "IO.printf(format: str, ..args: any) -> None");
+        assert_eq!(printf.max_args(), None);
+    }
+
+    // ===== COLLECTION CALLBACK TESTS =====
+
+    #[test]
+    fn test_vector_methods_call_lambdas() {
+        let source = r#"
+            def main() -> str {
+                nums: [int] = [5, 3, 8, 1, 4]
+                doubled: [int] = nums.map((x: int) -> x * 2)
+                evens: [int] = nums.filter((x: int) -> x % 2 == 0)
+                total: int = nums.reduce((acc: int, x: int) -> acc + x)
+                product: int = nums.reduce(1, (acc: int, x: int) -> acc * x)
+                first_big: ?int = nums.find((x: int) -> x > 4)
+                missing: ?int = nums.find((x: int) -> x > 100)
+                checks: [bool] = [nums.any((x: int) -> x > 7), nums.all((x: int) -> x > 1)]
+                groups: Map<bool, [int]> = nums.group_by((x: int) -> x % 2 == 0)
+                nums.sort_by((a: int, b: int) -> b - a)
+                return doubled.to_string() + " " + evens.to_string() + " " + total.to_string() + " "
+                    + product.to_string() + " " + first_big.to_string() + " " + missing.is_none().to_string() + " "
+                    + checks.to_string() + " " + groups.get(false).to_string() + " " + nums.to_string()
+            }
+        "#;
+
+        let expected = "[10, 6, 16, 2, 8] [8, 4] 21 480 Some(5) true [true, false] [5, 3, 1] [8, 5, 4, 3, 1]";
+        for result in [interpret_code(source), run_on_vm(source)] {
+            assert_eq!(result, Ok(Value::String(expected.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_sort_by_keeps_equal_elements_in_order() {
+        let source = r#"
+            def main() -> [str] {
+                words: [str] = ["ccc", "a", "bb", "d", "ee", "f"]
+                words.sort_by((a: str, b: str) -> a.length() - b.length())
+                return words
+            }
+        "#;
+
+        let words = ["a", "d", "f", "bb", "ee", "ccc"];
+        let expected = Value::vec(words.iter().map(|w| Value::String(w.to_string())).collect());
+        assert_eq!(interpret_code(source), Ok(expected));
+    }
+
+    #[test]
+    fn test_set_and_map_methods_call_lambdas() {
+        let source = r#"
+            def main() -> str {
+                s: {int} = {1, 2, 3, 4}
+                squares: {int} = s.map((x: int) -> x * x)
+                odd: {int} = s.filter((x: int) -> x % 2 == 1)
+                sorted: [int] = s.sort_by((a: int, b: int) -> b - a)
+
+                stock: Map<str, int> = {"apples": 3, "pears": 0, "plums": 7}
+                labels: Map<str, str> = stock.map((k: str, v: int) -> k + "=" + v.to_string())
+                in_stock: Map<str, int> = stock.filter((k: str, v: int) -> v > 0)
+                count: int = stock.reduce(0, (acc: int, k: str, v: int) -> acc + v)
+                empty: (str, int) = stock.find((k: str, v: int) -> v == 0).unwrap()
+                by_count: [(str, int)] = stock.sort_by((a: (str, int), b: (str, int)) -> a[1] - b[1])
+                return squares.contains(16).to_string() + " " + odd.len().to_string() + " " + sorted.to_string() + " "
+                    + labels.get("plums") + " " + in_stock.len().to_string() + " " + count.to_string() + " "
+                    + empty.to_string() + " " + by_count.to_string() + " " + stock.all((k: str, v: int) -> k.length() > 4).to_string()
+            }
+        "#;
+
+        let expected = "true 2 [4, 3, 2, 1] plums=7 2 10 (pears, 0) [(pears, 0), (apples, 3), (plums, 7)] true";
+        for result in [interpret_code(source), run_on_vm(source)] {
+            assert_eq!(result, Ok(Value::String(expected.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_native_functions_call_lambdas() {
+        let source = r#"
+            def main() -> str {
+                nums: [int] = [1, 2, 3, 4]
+                squares: [int] = COLLECTIONS.array_map(nums, (x: int) -> x * x)
+                odd: [int] = COLLECTIONS.array_filter(nums, (x: int) -> x % 2 == 1)
+                total: int = COLLECTIONS.array_reduce(nums, 100, (acc: int, x: int) -> acc + x)
+                return squares.to_string() + " " + odd.to_string() + " " + total.to_string()
+            }
+        "#;
+
+        let result = interpret_code(source).expect("Should run successfully");
+        assert_eq!(result, Value::String("[1, 4, 9, 16] [1, 3] 110".to_string()));
+    }
+
+    #[test]
+    fn test_collection_functions_taking_closures() {
+        let source = r#"
+            def main() -> str {
+                nums: [int] = [3, 1, 4, 1, 5]
+                total: int = COLLECTIONS.array_fold(nums, 0, (acc: int, x: int) -> acc + x)
+                found: Option<int> = COLLECTIONS.array_find(nums, (x: int) -> x > 3)
+                index: int = COLLECTIONS.array_find_index(nums, (x: int) -> x > 3)
+                checks: (bool, bool) = (COLLECTIONS.array_any(nums, (x: int) -> x > 4), COLLECTIONS.iterator_all(nums, (x: int) -> x > 1))
+                COLLECTIONS.sort_by(nums, (a: int, b: int) -> b - a)
+                evens: int = COLLECTIONS.partition(nums, (x: int) -> x % 2 == 0)
+                COLLECTIONS.dedup_by_key(nums, (x: int) -> x)
+                words: [str] = ["ccc", "a", "bb"]
+                COLLECTIONS.sort_by_key(words, (w: str) -> w.length())
+                product: int = COLLECTIONS.iterator_reduce([1, 2, 3, 4], (a: int, b: int) -> a * b)
+                cycled: [int] = COLLECTIONS.iterator_cycle([1, 2]).take(5).collect()
+                return total.to_string() + " " + found.to_string() + " " + index.to_string() + " " + checks.to_string() + " "
+                    + evens.to_string() + " " + nums.to_string() + " " + words.to_string() + " " + product.to_string() + " "
+                    + cycled.to_string()
+            }
+        "#;
+
+        let expected = "14 Some(4) 2 (true, false) 1 [4, 5, 3, 1] [a, bb, ccc] 24 [1, 2, 1, 2, 1]";
+        for result in [interpret_code(source), run_on_vm(source)] {
+            assert_eq!(result, Ok(Value::String(expected.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_default_dicts_call_their_factory_for_missing_keys() {
+        let source = r#"
+            def main() -> str {
+                counts: DefaultDict = COLLECTIONS.default_dict_new(() -> 0)
+                for (word: str; ["a", "b", "a"]) {
+                    COLLECTIONS.default_dict_set(counts, word, COLLECTIONS.default_dict_get(counts, word) + 1)
+                }
+                return COLLECTIONS.default_dict_get(counts, "a").to_string() + " " + COLLECTIONS.default_dict_get(counts, "c").to_string()
+                    + " " + counts.data.len().to_string()
+            }
+        "#;
+        assert_eq!(interpret_code(source), Ok(Value::String("2 0 3".to_string())));
+    }
+
+    #[test]
+    fn test_math_functions_taking_closures() {
+        let source = r#"
+            def main() -> [float] {
+                return [
+                    MATH.derivative((x: float) -> x * x, 3.0, 0.001),
+                    MATH.integral((x: float) -> 2.0 * x, 0.0, 1.0, 1000),
+                    MATH.bisection((x: float) -> x * x - 4.0, 0.0, 5.0, 0.0001),
+                    MATH.golden_section_search((x: float) -> (x - 1.0) * (x - 1.0), -10.0, 10.0, 0.0001),
+                    MATH.newton_raphson((x: float) -> x * x - 4.0, (x: float) -> 2.0 * x, 3.0, 0.0001, 100)
+                ]
+            }
+        "#;
+        let results = match interpret_code(source) {
+            Ok(Value::Vec(results)) => results.read().clone(),
+            other => panic!("Expected a vector, got {:?}", other),
+        };
+        let expected = [6.0, 1.0, 2.0, 1.0, 2.0];
+        for (result, expected) in results.iter().zip(expected) {
+            let result = result.as_f64().expect("Should be a float");
+            assert!((result - expected).abs() < 0.01, "expected {}, got {}", expected, result);
+        }
+
+        let source = "def main() -> float {\n    return MATH.bisection((x: float) -> x, -1.0, 1.0, 0.0)\n}\n";
+        let err = interpret_code(source).expect_err("A zero tolerance would never be reached");
+        assert!(err.contains("MATH::bisection expects a tolerance above"), "{}", err);
+    }
+
+    #[test]
+    fn test_closure_results_of_the_wrong_type_are_errors() {
+        let source = r#"
+            def main() -> [int] {
+                nums: [int] = [2, 1]
+                COLLECTIONS.array_sort_by(nums, (a: int, b: int) -> a > b)
+                return nums
+            }
+        "#;
+        let err = interpret_code(source).expect_err("Comparisons must return ints");
+        assert!(err.contains("COLLECTIONS::array_sort_by expects its function to return int, got bool"), "{}", err);
+    }
+
+    #[test]
+    fn test_timers_stopwatches_and_file_watchers_have_methods() {
+        let source = r#"
+            def main() -> str {
+                timer: Timer = TIME.create_timer()
+                watch: Stopwatch = TIME.create_stopwatch()
+                stopped: Option<float> = watch.stop()
+                watch.start()
+                lap: Option<float> = watch.lap()
+                watcher: FileWatcher = FILE.watch_file(".", () -> None)
+                watcher.start()
+                return (timer.elapsed() >= 0.0).to_string() + " " + stopped.is_none().to_string() + " " + lap.is_some().to_string()
+                    + " " + watch.is_running().to_string() + " " + watch.get_laps().len().to_string() + " "
+                    + watcher.is_watching().to_string()
+            }
+        "#;
+
+        let expected = "true true true true 1 true";
+        for result in [interpret_code(source), run_on_vm(source)] {
+            assert_eq!(result, Ok(Value::String(expected.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_run_test_and_assert_panics_catch_errors() {
+        let source = r#"
+            def main() -> str {
+                good: TestResult = TESTING.run_test("passes", () -> TESTING.assert_true(true, "fine"))
+                bad: TestResult = TESTING.run_test("fails", () -> TESTING.assert_equal(1, 2, "one is not two"))
+                TESTING.assert_panics(() -> 1 / 0, "dividing by zero fails")
+                return good.passed.to_string() + " " + bad.passed.to_string() + " " + bad.error
+            }
+        "#;
+        assert_eq!(
+            interpret_code(source),
+            Ok(Value::String("true false Assertion failed: one is not two - Expected Int(2), got Int(1)".to_string()))
+        );
+
+        let source = "def main() -> int {\n    TESTING.assert_panics(() -> 1, \"should fail\")\n    return 0\n}\n";
+        let err = interpret_code(source).expect_err("The function does not fail");
+        assert!(err.contains("should fail"), "{}", err);
+    }
+
+    #[test]
+    fn test_errors_in_callbacks_keep_their_value_and_stack() {
+        let source = r#"
+            enum Problem { Negative(int) }
+
+            def check(x: int) -> int {
+                if x < 0 { raise Negative(x) }
+                return x
+            }
+
+            def main() -> str {
+                try {
+                    COLLECTIONS.array_map([1, -2], (x: int) -> check(x))
+                } except Negative(n) {
+                    return "negative " + n.to_string()
+                }
+                return "no error"
+            }
+        "#;
+        assert_eq!(interpret_code(source), Ok(Value::String("negative -2".to_string())));
+
+        let source = "def main() -> [int] {\n    return [2, 0].map((x: int) -> 10 / x)\n}\n";
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let mut interpreter = Interpreter::new();
+        let err = interpreter.interpret_program(&ast).expect_err("Division by zero should fail");
+        let functions: Vec<&str> = err.stack.iter().map(|f| f.function.as_str()).collect();
+        assert_eq!(functions, vec!["<lambda>", "main"]);
+        assert_eq!(err.stack[0].arguments, "x = 0");
+        let start = err.span.start().to_usize();
+        assert!(source[start..].starts_with("10 / x"), "error points at {:?}", &source[start..]);
+    }
+
+    #[test]
+    fn test_native_errors_after_callbacks_point_at_the_call() {
+        let source = "def main() -> [int] {\n    v: [int] = [2, 1]\n    v.sort_by((a: int, b: int) -> a > b)\n    return v\n}\n";
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).expect("Should parse");
+        let mut interpreter = Interpreter::new();
+        let err = interpreter.interpret_program(&ast).expect_err("Comparisons must return ints");
+        assert!(err.message.contains("sort_by comparison function must return an int, got bool"), "{}", err.message);
+        let start = err.span.start().to_usize();
+        let call = source.find("v.sort_by").unwrap();
+        assert!(start >= call && start < source.find("a > b").unwrap(), "error points at {:?}", &source[start..]);
+
+        let err = interpret_code("def main() -> [int] { return [1].map(5) }").expect_err("5 is not a function");
+        assert!(err.contains("Expected a function, got int"), "unexpected error: {}", err);
+    }
+
+    // ===== JSON TESTS =====
+
+    /// Write a JSON file for a test and return its path
+    fn write_json_file(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("tjlang_json_{}_{}", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_json_parse_and_stringify() {
+        use crate::stdlib::json::JSON;
+
+        let text = r#"{"a":[1,2.5,"x",true],"b":{"c":null},"big":18446744073709551615}"#;
+        let value = JSON::parse(text).expect("Valid JSON");
+        assert!(matches!(&value, Value::Map(_)));
+        assert_eq!(JSON::stringify(&value), Ok(text.to_string()));
+
+        // Integers beyond 64 bits stay exact instead of rounding through a float
+        let text = "[99999999999999999999,-123456789012345678901234567890,2.5e20]";
+        let value = JSON::parse(text).expect("Valid JSON");
+        let Value::Vec(items) = &value else {
+            panic!("expected a vector, got {:?}", value);
+        };
+        let items = items.read().clone();
+        assert!(matches!(items[0], Value::BigInt(_)) && matches!(items[1], Value::BigInt(_)));
+        assert_eq!(items[0].to_string(), "99999999999999999999");
+        assert_eq!(items[1].to_string(), "-123456789012345678901234567890");
+        assert_eq!(items[2], Value::Float(2.5e20));
+        assert_eq!(JSON::stringify(&Value::vec(items[..2].to_vec())), Ok("[99999999999999999999,-123456789012345678901234567890]".to_string()));
+        assert_eq!(
+            JSON::stringify_pretty(&JSON::parse("[1, []]").unwrap(), 4),
+            Ok("[\n    1,\n    []\n]".to_string())
+        );
+
+        let mut fields = std::collections::HashMap::new();
+        fields.insert("y".to_string(), Value::some(Value::Float(0.5)));
+        fields.insert("x".to_string(), Value::Tuple(vec![Value::Int(1), Value::None]));
+        let mut keys = std::collections::HashMap::new();
+        keys.insert(Value::Int(1), Value::new_struct("Point", fields));
+        assert_eq!(JSON::stringify(&Value::map(keys)), Ok(r#"{"1":{"x":[1,null],"y":0.5}}"#.to_string()));
+
+        let err = JSON::parse(r#"{"a": }"#).unwrap_err();
+        assert!(err.starts_with("Invalid JSON") && err.contains("line 1 column 7"), "unexpected error: {}", err);
+        let err = JSON::stringify(&Value::Float(f64::NAN)).unwrap_err();
+        assert!(err.contains("cannot be represented in JSON"), "unexpected error: {}", err);
+
+        let cyclic = Value::vec(vec![]);
+        if let Value::Vec(items) = &cyclic {
+            items.write().push(cyclic.clone());
+        }
+        let err = JSON::stringify(&cyclic).unwrap_err();
+        assert!(err.contains("cyclic"), "unexpected error: {}", err);
+        if let Value::Vec(items) = &cyclic {
+            items.write().clear();
+        }
+    }
+
+    #[test]
+    fn test_json_decode_into_structs() {
+        let good = write_json_file("good", r#"{"name": "path", "points": [{"x": 1, "y": 2}, {"x": 3, "y": 4, "label": "end"}], "extra": 0}"#);
+        let bad = write_json_file("bad", r#"{"name": "path", "points": [{"x": 1, "y": 2}, {"x": 3, "y": "four"}]}"#);
+        let source = format!(
+            r#"
+            type Point {{ x: int, y: int, label: ?str }}
+            type Line {{ name: str, points: [Point] }}
+
+            def main() -> str {{
+                line: Line = JSON.decode(FILE.read_to_string("{}"), Line)
+                last: Point = line.points[1]
+                return line.name + " " + (line.points[0].x + last.y).to_string() + " " + last.label.unwrap()
+            }}
+            "#,
+            good
+        );
+        for result in [interpret_code(&source), run_on_vm(&source)] {
+            assert_eq!(result, Ok(Value::String("path 5 end".to_string())));
+        }
+
+        let source = source.replace(&good, &bad);
+        let err = interpret_code(&source).expect_err("y must be an int");
+        assert!(err.contains(r#"Line.points[1].y: expected int, got "four""#), "unexpected error: {}", err);
+
+        let source = "type Point { x: int, y: int }\ndef main() -> any {\n    return JSON.decode(\"{}\", Point)\n}\n";
+        let err = interpret_code(source).expect_err("Fields are required");
+        assert!(err.contains("Point.x: missing field of type int"), "unexpected error: {}", err);
+        let _ = std::fs::remove_file(good);
+        let _ = std::fs::remove_file(bad);
+    }
+
+    #[test]
+    fn test_json_read_lines_streams_documents() {
+        let log = write_json_file("log", "{\"level\": \"info\", \"ms\": 5}\n\n{\"level\": \"warn\", \"ms\": 7}\n{\"level\": \"info\", \"ms\": 11}\n");
+        let source = format!(
+            r#"
+            def main() -> int {{
+                total: int = 0
+                for (record: Map<str, any>; JSON.read_lines("{0}").filter((r: Map<str, any>) -> r.get("level") == "info")) {{
+                    total = total + record.get("ms")
+                }}
+                return total
+            }}
+            "#,
+            log
+        );
+        for result in [interpret_code(&source), run_on_vm(&source)] {
+            assert_eq!(result, Ok(Value::Int(16)));
+        }
+
+        std::fs::write(&log, "[1]\n{oops}\n").unwrap();
+        let source = format!("def main() -> [any] {{\n    return JSON.read_lines(\"{}\").collect()\n}}\n", log);
+        let err = interpret_code(&source).expect_err("The second line is invalid");
+        assert!(err.contains(&format!("{}:2: Invalid JSON", log)), "unexpected error: {}", err);
+        let _ = std::fs::remove_file(log);
+    }
+
+    // ===== REGEX TESTS =====
+
+    #[test]
+    fn test_regex_matching_and_captures() {
+        use crate::stdlib::regex::Regex;
+
+        let date = Regex::new(r"(?P<year>\d{4})-(?P<month>\d{2})-(\d{2})").unwrap();
+        let text = "from 2024-05-17 to 2023-01-02";
+        assert_eq!(date.find(text), Some("2024-05-17"));
+        let captures = date.captures(text).unwrap();
+        assert_eq!(captures.name("month"), Some("05"));
+        assert_eq!(captures.get(3), Some("17"));
+        assert_eq!(captures.range(), (5, 15));
+        assert_eq!(date.replace_all(text, "$3/${month}/$year $$"), "from 17/05/2024 $ to 02/01/2023 $");
+
+        // Leftmost-first alternation, lazy repetition, Unicode and word boundaries
+        assert_eq!(Regex::new("a|ab").unwrap().find("ab"), Some("a"));
+        assert_eq!(Regex::new("a+?").unwrap().find("aaa"), Some("a"));
+        assert_eq!(Regex::new(r"\w+").unwrap().find("  héllo!"), Some("héllo"));
+        assert_eq!(Regex::new(r"\bcat\b").unwrap().captures("concat cat").unwrap().range(), (7, 10));
+        assert_eq!(Regex::new(r"(?m)^\d+$").unwrap().find("x\n42\ny"), Some("42"));
+        assert_eq!(Regex::new(r",\s*").unwrap().split("a, b,c"), vec!["a", "b", "c"]);
+        let empty: Vec<_> = Regex::new("x*").unwrap().captures_iter("axxb").map(|c| c.range()).collect();
+        assert_eq!(empty, vec![(0, 0), (1, 3), (4, 4)]);
+
+        // Matching time doesn't blow up on patterns that backtrack exponentially
+        let text = "a".repeat(30);
+        assert!(!Regex::new("(a*)*b").unwrap().is_match(&text));
+
+        let err = Regex::new("(unclosed").unwrap_err();
+        assert!(err.starts_with("Invalid regex"), "unexpected error: {}", err);
+        let glob = Regex::from_glob("src/**/*.{rs,toml}").unwrap();
+        assert!(glob.is_match("src/main.rs") && glob.is_match("src/a/b/Cargo.toml"));
+        assert!(!glob.is_match("src/main.rsx") && !glob.is_match("lib/main.rs"));
+        assert!(Regex::from_glob("file[!0-4]?.txt").unwrap().is_match("file5a.txt"));
+    }
+
+    #[test]
+    fn test_regex_module_and_methods() {
+        let source = r#"
+            def main() -> [any] {
+                date: Regex = REGEX.compile("(?P<year>\d{4})-(?P<month>\d{2})")
+                text: str = "in 2024-05 and 2023-11"
+                groups: Map<str, str> = date.captures(text).unwrap()
+                return [
+                    date.find_all(text),
+                    groups.get("year") + "/" + groups.get("month"),
+                    date.replace_all(text, "${month}.$1"),
+                    REGEX.is_match(date, "no dates"),
+                    REGEX.split("[,;] *", "a, b;c"),
+                    REGEX.find("z+", "abc"),
+                    REGEX.find("\d+", text),
+                    date.find(text),
+                    REGEX.captures("z+", "abc").is_none(),
+                    text.matches("\d{4}"),
+                    text.replace_regex(date, "?"),
+                    date.pattern()
+                ]
+            }
+        "#;
+        let expected = Value::vec(vec![
+            Value::vec(vec![Value::String("2024-05".to_string()), Value::String("2023-11".to_string())]),
+            Value::String("2024/05".to_string()),
+            Value::String("in 05.2024 and 11.2023".to_string()),
+            Value::Bool(false),
+            Value::vec(vec![Value::String("a".to_string()), Value::String("b".to_string()), Value::String("c".to_string())]),
+            Value::None,
+            Value::some(Value::String("2024".to_string())),
+            Value::some(Value::String("2024-05".to_string())),
+            Value::Bool(true),
+            Value::Bool(true),
+            Value::String("in ? and ?".to_string()),
+            Value::String(r"(?P<year>\d{4})-(?P<month>\d{2})".to_string()),
+        ]);
+        for result in [interpret_code(source), run_on_vm(source)] {
+            assert_eq!(result, Ok(expected.clone()));
+        }
+
+        let err = interpret_code("def main() -> any {\n    return REGEX.compile(\"a(\")\n}\n").expect_err("Invalid pattern");
+        assert!(err.contains("Invalid regex"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_find_files_matches_globs() {
+        let root = std::env::temp_dir().join(format!("tjlang_glob_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        for file in ["a.txt", "b.rs", "notes.txt.bak", "nested/c.txt", "nested/d.rs"] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        let source = format!(
+            r#"
+            def main() -> [int] {{
+                return [
+                    FILE.find_files("{0}", "*.txt").length(),
+                    FILE.find_files_recursive("{0}", "*.txt").length(),
+                    FILE.find_files_recursive("{0}", "nested/*.{{rs,txt}}").length(),
+                    FILE.find_files_recursive("{0}", "**/?.rs").length()
+                ]
+            }}
+            "#,
+            root.to_string_lossy()
+        );
+        let counts = Value::vec(vec![Value::Int(1), Value::Int(2), Value::Int(2), Value::Int(2)]);
+        assert_eq!(interpret_code(&source), Ok(counts));
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    // ===== RANDOM TESTS =====
+
+    /// Helper function to run TJLang code with the random number generator seeded
+    fn run_with_seed(source: &str, backend: Backend, seed: u64) -> Result<Value, String> {
+        let file_id = create_test_file_id();
+        let (ast, _) = parse(source, file_id).map_err(|e| format!("Parse error: {:?}", e))?;
+
+        let mut interpreter = Interpreter::new();
+        interpreter.set_backend(backend);
+        interpreter.set_random_seed(seed);
+        interpreter
+            .interpret_program(&ast)
+            .map_err(|e| format!("Runtime error: {}", e))
+    }
+
+    #[test]
+    fn test_random_seed_makes_runs_repeatable() {
+        let source = r#"
+            def main() -> [any] {
+                xs: [int] = [1, 2, 3, 4, 5, 6, 7, 8]
+                COLLECTIONS.shuffle(xs)
+                g: Generator = RANDOM.generator()
+                return [RANDOM.int(1, 6), RANDOM.float(), RANDOM.gaussian(), RANDOM.choice(xs), RANDOM.sample(xs, 3), RANDOM.uuid4(), xs, g.int(1, 1000)]
+            }
+        "#;
+        let first = run_with_seed(source, Backend::Interpreter, 42).expect("Should run");
+        assert_eq!(run_with_seed(source, Backend::Interpreter, 42), Ok(first.clone()));
+        assert_eq!(run_with_seed(source, Backend::Vm, 42), Ok(first.clone()));
+        assert_ne!(run_with_seed(source, Backend::Interpreter, 43), Ok(first));
+
+        // Seeding from the program restarts the same sequence
+        let source = r#"
+            def main() -> bool {
+                RANDOM.seed(7)
+                a: [int] = [RANDOM.int(0, 1000000), RANDOM.int(0, 1000000)]
+                RANDOM.seed(7)
+                return a == [RANDOM.int(0, 1000000), RANDOM.int(0, 1000000)]
+            }
+        "#;
+        assert_eq!(interpret_code(source), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_seeded_shuffle_is_reproducible() {
+        let shuffled = r#"
+            def main() -> [int] {
+                xs: [int] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+                COLLECTIONS.shuffle(xs)
+                return xs
+            }
+        "#;
+        let first = run_with_seed(shuffled, Backend::Interpreter, 9).expect("Should run");
+        assert_ne!(first, Value::vec((1..=10).map(Value::Int).collect()));
+        for backend in [Backend::Interpreter, Backend::Vm] {
+            assert_eq!(run_with_seed(shuffled, backend, 9), Ok(first.clone()));
+        }
+        assert_ne!(run_with_seed(shuffled, Backend::Interpreter, 10), Ok(first.clone()));
+
+        // RANDOM.seed in the program gives the same order as Interpreter::set_random_seed
+        let seeded_in_program = shuffled.replace("COLLECTIONS.shuffle(xs)", "RANDOM.seed(9)\n                COLLECTIONS.shuffle(xs)");
+        for result in [interpret_code(&seeded_in_program), run_on_vm(&seeded_in_program)] {
+            assert_eq!(result, Ok(first.clone()));
+        }
+    }
+
+    #[test]
+    fn test_random_values_and_generators() {
+        let source = r#"
+            def main() -> [any] {
+                a: Generator = RANDOM.generator(5)
+                b: Generator = RANDOM.generator(5)
+                first: [int] = [a.int(1, 100), a.int(1, 100), a.int(1, 100)]
+                # Generators are independent of each other and of RANDOM.seed
+                RANDOM.seed(1)
+                c: Generator = RANDOM.generator(6)
+                c.int(1, 100)
+                second: [int] = [b.int(1, 100), b.int(1, 100), b.int(1, 100)]
+                xs: [int] = [1, 2, 3, 4, 5]
+                a.shuffle(xs)
+                d: int = RANDOM.int(3, 3)
+                u: str = RANDOM.uuid4()
+                return [first == second, xs.length(), d, u.matches("^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$"), RANDOM.sample(xs, 5).length()]
+            }
+        "#;
+        let expected = Value::vec(vec![
+            Value::Bool(true),
+            Value::Int(5),
+            Value::Int(3),
+            Value::Bool(true),
+            Value::Int(5),
+        ]);
+        for result in [interpret_code(source), run_on_vm(source)] {
+            assert_eq!(result, Ok(expected.clone()));
+        }
+
+        use crate::stdlib::random::Generator;
+        let generator = Generator::from_seed(3);
+        for _ in 0..1000 {
+            let i = generator.int(-2, 2).unwrap();
+            assert!((-2..=2).contains(&i));
+            let f = generator.float(1.5, 2.5).unwrap();
+            assert!((1.5..2.5).contains(&f));
+        }
+        let mean = (0..10000).map(|_| generator.gaussian(5.0, 2.0).unwrap()).sum::<f64>() / 10000.0;
+        assert!((mean - 5.0).abs() < 0.1, "mean of gaussian samples was {}", mean);
+
+        let err = interpret_code("def main() -> any {\n    return RANDOM.int(5, 1)\n}\n").unwrap_err();
+        assert!(err.contains("Empty range"), "unexpected error: {}", err);
+        let err = interpret_code("def main() -> any {\n    return RANDOM.sample([1, 2], 3)\n}\n").unwrap_err();
+        assert!(err.contains("Cannot sample 3 elements from 2"), "unexpected error: {}", err);
+    }
+
     // ===== INTEGRATION TESTS COMMENTED OUT =====
     // Note: Integration tests that parse TJLang code are disabled due to parsing issues
     // with method names that conflict with keywords (e.g., 'not', 'type', etc.).
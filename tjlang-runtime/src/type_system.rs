@@ -86,6 +86,10 @@ impl TypeChecker {
                 true
             },
             
+            (Value::EnumConstructor { field_types, .. }, Type::Function { params, .. }) => {
+                field_types.len() == params.len()
+            },
+            
             (Value::Channel { .. }, Type::Identifier(type_name)) => {
                 type_name == "Channel"
             },
@@ -94,6 +98,18 @@ impl TypeChecker {
                 type_name == "Task"
             },
             
+            (Value::Regex(_), Type::Identifier(type_name)) => {
+                type_name == "Regex"
+            },
+            
+            (Value::Generator(_), Type::Identifier(type_name)) => {
+                type_name == "Generator"
+            },
+            
+            (Value::Native(object), Type::Identifier(type_name)) => {
+                type_name == object.type_name()
+            },
+            
             // Union types
             (value, Type::Union { types, .. }) => {
                 types.iter().any(|typ| self.check_type(value, typ))
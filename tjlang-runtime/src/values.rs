@@ -1,12 +1,23 @@
 //! TJLang Runtime Values
 //!
 //! Advanced value system supporting all TJLang types with garbage collection.
+//! Containers are reference counted; the `gc` module reclaims the ones caught
+//! in reference cycles.
 
 use codespan::{Files, Span};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
-use std::sync::Arc;
-use tjlang_ast::{Block, Expression, PrimitiveType, SourceSpan, Type};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tjlang_ast::{Block, Expression, GenericParam, PrimitiveType, SourceSpan, Type};
+
+use crate::bigint::BigInt;
+use crate::gc::{self, Traceable};
+use crate::iterators::IteratorRef;
+use crate::stdlib::file::FileWatcher;
+use crate::stdlib::io::{ProgressBar, Spinner};
+use crate::stdlib::random::Generator;
+use crate::stdlib::regex::Regex;
+use crate::stdlib::time::{Stopwatch, Timer};
 
 /// Create a dummy SourceSpan for runtime values
 fn dummy_span() -> SourceSpan {
@@ -18,20 +29,163 @@ fn dummy_span() -> SourceSpan {
     }
 }
 
+/// Shared, mutable storage for container values. Clones refer to the same
+/// contents, so a mutation through one path is visible through every other.
+pub struct Shared<T>(Arc<RwLock<T>>);
+
+impl<T: Traceable> Shared<T> {
+    /// Allocate a container, registered with the garbage collector
+    pub fn new(value: T) -> Self {
+        let contents = Arc::new(RwLock::new(value));
+        gc::register(T::handle(&contents));
+        Shared(contents)
+    }
+}
+
+impl<T> Shared<T> {
+    /// Read the contents
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.0.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Modify the contents in place
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.0.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Whether both handles refer to the same contents
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Address of the shared contents, identifying the container
+    pub fn address(&self) -> usize {
+        Arc::as_ptr(&self.0) as *const () as usize
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(self.0.clone())
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.read().fmt(f)
+    }
+}
+
+/// A stdlib object with no TJLang value of its own, used through its methods.
+/// Copies share the object.
+#[derive(Clone)]
+pub struct NativeObject(Arc<Mutex<Native>>);
+
+/// The stdlib objects a `NativeObject` can hold
+pub enum Native {
+    ProgressBar(ProgressBar),
+    Spinner(Spinner),
+    Timer(Timer),
+    Stopwatch(Stopwatch),
+    FileWatcher(FileWatcher),
+}
+
+impl NativeObject {
+    pub fn new(native: Native) -> Self {
+        NativeObject(Arc::new(Mutex::new(native)))
+    }
+
+    /// The object, for calling its methods
+    pub fn lock(&self) -> MutexGuard<'_, Native> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The object's type, as TJLang code names it
+    pub fn type_name(&self) -> &'static str {
+        match *self.lock() {
+            Native::ProgressBar(_) => "ProgressBar",
+            Native::Spinner(_) => "Spinner",
+            Native::Timer(_) => "Timer",
+            Native::Stopwatch(_) => "Stopwatch",
+            Native::FileWatcher(_) => "FileWatcher",
+        }
+    }
+
+    /// Whether both are the same object
+    pub fn ptr_eq(&self, other: &NativeObject) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    pub fn as_ptr(&self) -> usize {
+        Arc::as_ptr(&self.0) as usize
+    }
+}
+
+impl std::fmt::Debug for NativeObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}>", self.type_name())
+    }
+}
+
+/// Local variable slots of one call, numbered by the resolver. A slot is
+/// empty until its variable is assigned.
+pub type Frame = Vec<Option<Value>>;
+
+/// Module-level variables, shared by every function declared in the module
+#[derive(Clone)]
+pub struct Globals(Shared<HashMap<String, Value>>);
+
+impl Globals {
+    pub fn new() -> Self {
+        Globals(Shared::new(HashMap::new()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.0.read().get(name).cloned()
+    }
+
+    /// Define a global, or replace its value
+    pub fn set(&self, name: String, value: Value) {
+        self.0.write().insert(name, value);
+    }
+
+    /// Address of the variables, identifying the module
+    pub fn address(&self) -> usize {
+        self.0.address()
+    }
+}
+
+impl Default for Globals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Functions stored in the globals refer back to them, so only the names are shown
+impl std::fmt::Debug for Globals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.read().keys()).finish()
+    }
+}
+
 /// Runtime value that can represent any TJLang value
 #[derive(Debug)]
 pub enum Value {
     // Primitive types
     Int(i64),
+    /// An `int` outside the `i64` range. Only produced when overflow promotes,
+    /// and never holds a value that fits in `Int`.
+    BigInt(BigInt),
     Float(f64),
     Bool(bool),
     String(String),
     None,
 
-    // Complex types
+    // Complex types. Structs, vectors, sets and maps share their contents
+    // between copies of the value; use `deep_copy` for an independent value.
     Struct {
         name: String,
-        fields: HashMap<String, Value>,
+        fields: Shared<HashMap<String, Value>>,
     },
     Enum {
         name: String,
@@ -43,32 +197,63 @@ pub enum Value {
         possible_types: Vec<String>, // Type names for debugging
     },
     Tuple(Vec<Value>),
-    Vec(Vec<Value>),
-    Set(std::collections::HashSet<Value>),
-    Map(HashMap<Value, Value>),
+    Vec(Shared<Vec<Value>>),
+    Set(Shared<HashSet<Value>>),
+    Map(Shared<HashMap<Value, Value>>),
+
+    // Lazy sequences
+    Range {
+        start: i64,
+        end: i64,
+        inclusive: bool,
+    },
+    Iterator(IteratorRef),
+
+    // Compiled regular expression
+    Regex(Arc<Regex>),
+    // Random number generator, shared between copies
+    Generator(Generator),
+    // Progress bar, spinner, timer, stopwatch or file watcher
+    Native(NativeObject),
 
     // Function types
     Function {
         name: String,
+        /// Type parameters and their interface bounds
+        generic_params: Vec<GenericParam>,
         params: Vec<String>,
         param_types: Vec<Type>,
-        body: Block,
-        closure: HashMap<String, Value>,
+        /// Default value expressions, evaluated at call time for omitted arguments
+        param_defaults: Vec<Option<Expression>>,
+        /// Declared return type; `any` for native functions
+        return_type: Type,
+        body: Arc<Block>,
+        /// Globals of the module that declared the function; `None` for native functions
+        globals: Option<Globals>,
     },
     Closure {
         params: Vec<String>,
-        body: Expression,
-        closure: HashMap<String, Value>,
+        param_types: Vec<Type>,
+        body: Arc<Expression>,
+        /// Frames of the calls the closure was created in, innermost first
+        captured: Vec<Arc<Frame>>,
+        globals: Globals,
+        /// Type parameters in scope where the closure was created
+        type_params: Vec<String>,
+    },
+    /// Constructor of an enum variant with fields, called to build the variant
+    EnumConstructor {
+        enum_name: String,
+        variant: String,
+        field_types: Vec<Type>,
     },
 
-    // Concurrency types
+    // Concurrency types, as handles into the interpreter's ConcurrencyRuntime
     Channel {
-        sender: std::sync::mpsc::Sender<Value>,
-        receiver: std::sync::mpsc::Receiver<Value>,
+        id: u64,
     },
     Task {
         id: u64,
-        handle: std::thread::JoinHandle<Value>,
     },
 
     // Reference types (for GC)
@@ -82,6 +267,7 @@ impl Clone for Value {
     fn clone(&self) -> Self {
         match self {
             Value::Int(i) => Value::Int(*i),
+            Value::BigInt(i) => Value::BigInt(i.clone()),
             Value::Float(f) => Value::Float(*f),
             Value::Bool(b) => Value::Bool(*b),
             Value::String(s) => Value::String(s.clone()),
@@ -103,36 +289,60 @@ impl Clone for Value {
             Value::Vec(elements) => Value::Vec(elements.clone()),
             Value::Set(elements) => Value::Set(elements.clone()),
             Value::Map(entries) => Value::Map(entries.clone()),
+            Value::Range { start, end, inclusive } => Value::Range {
+                start: *start,
+                end: *end,
+                inclusive: *inclusive,
+            },
+            Value::Iterator(iterator) => Value::Iterator(iterator.clone()),
+            Value::Regex(regex) => Value::Regex(regex.clone()),
+            Value::Generator(generator) => Value::Generator(generator.clone()),
+            Value::Native(object) => Value::Native(object.clone()),
             Value::Function {
                 name,
+                generic_params,
                 params,
                 param_types,
+                param_defaults,
+                return_type,
                 body,
-                closure,
+                globals,
             } => Value::Function {
                 name: name.clone(),
+                generic_params: generic_params.clone(),
                 params: params.clone(),
                 param_types: param_types.clone(),
+                param_defaults: param_defaults.clone(),
+                return_type: return_type.clone(),
                 body: body.clone(),
-                closure: closure.clone(),
+                globals: globals.clone(),
+            },
+            Value::EnumConstructor {
+                enum_name,
+                variant,
+                field_types,
+            } => Value::EnumConstructor {
+                enum_name: enum_name.clone(),
+                variant: variant.clone(),
+                field_types: field_types.clone(),
             },
             Value::Closure {
                 params,
+                param_types,
                 body,
-                closure,
+                captured,
+                globals,
+                type_params,
             } => Value::Closure {
                 params: params.clone(),
+                param_types: param_types.clone(),
                 body: body.clone(),
-                closure: closure.clone(),
-            },
-            Value::Channel { .. } => Value::Channel {
-                sender: std::sync::mpsc::channel().0,
-                receiver: std::sync::mpsc::channel().1,
-            },
-            Value::Task { id, .. } => Value::Task {
-                id: *id,
-                handle: std::thread::spawn(|| Value::None),
+                captured: captured.clone(),
+                globals: globals.clone(),
+                type_params: type_params.clone(),
             },
+            Value::Channel { id } => Value::Channel { id: *id },
+            Value::Task { id } => Value::Task { id: *id },
             Value::Reference(addr) => Value::Reference(*addr),
             Value::Type(t) => Value::Type(t.clone()),
             Value::Union { value, possible_types } => Value::Union {
@@ -146,7 +356,9 @@ impl Clone for Value {
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            (Value::Union { value, .. }, other) | (other, Value::Union { value, .. }) => **value == *other,
             (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::BigInt(a), Value::BigInt(b)) => a == b,
             (Value::Float(a), Value::Float(b)) => (a - b).abs() < f64::EPSILON,
             (Value::Bool(a), Value::Bool(b)) => a == b,
             (Value::String(a), Value::String(b)) => a == b,
@@ -160,7 +372,7 @@ impl PartialEq for Value {
                     name: b,
                     fields: fb,
                 },
-            ) => a == b && fa == fb,
+            ) => a == b && (fa.ptr_eq(fb) || *fa.read() == *fb.read()),
             (
                 Value::Enum {
                     name: a,
@@ -174,10 +386,24 @@ impl PartialEq for Value {
                 },
             ) => a == b && va == vb && fa == fb,
             (Value::Tuple(a), Value::Tuple(b)) => a == b,
-            (Value::Vec(a), Value::Vec(b)) => a == b,
-            (Value::Set(a), Value::Set(b)) => a == b,
-            (Value::Map(a), Value::Map(b)) => a == b,
+            (Value::Vec(a), Value::Vec(b)) => a.ptr_eq(b) || *a.read() == *b.read(),
+            (Value::Set(a), Value::Set(b)) => a.ptr_eq(b) || *a.read() == *b.read(),
+            (Value::Map(a), Value::Map(b)) => a.ptr_eq(b) || *a.read() == *b.read(),
+            (
+                Value::Range { start: sa, end: ea, inclusive: ia },
+                Value::Range { start: sb, end: eb, inclusive: ib },
+            ) => sa == sb && ea == eb && ia == ib,
+            (Value::Iterator(a), Value::Iterator(b)) => std::sync::Arc::ptr_eq(a, b),
+            (Value::Regex(a), Value::Regex(b)) => a.pattern() == b.pattern(),
+            (Value::Generator(a), Value::Generator(b)) => a.ptr_eq(b),
+            (Value::Native(a), Value::Native(b)) => a.ptr_eq(b),
+            (
+                Value::EnumConstructor { enum_name: a, variant: va, .. },
+                Value::EnumConstructor { enum_name: b, variant: vb, .. },
+            ) => a == b && va == vb,
             (Value::Reference(a), Value::Reference(b)) => a == b,
+            (Value::Channel { id: a }, Value::Channel { id: b }) => a == b,
+            (Value::Task { id: a }, Value::Task { id: b }) => a == b,
             _ => false,
         }
     }
@@ -188,14 +414,16 @@ impl Eq for Value {}
 impl std::hash::Hash for Value {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         match self {
+            Value::Union { value, .. } => value.hash(state),
             Value::Int(i) => i.hash(state),
+            Value::BigInt(i) => i.hash(state),
             Value::Float(f) => f.to_bits().hash(state),
             Value::Bool(b) => b.hash(state),
             Value::String(s) => s.hash(state),
             Value::None => 0.hash(state),
             Value::Struct { name, fields } => {
                 name.hash(state);
-                for (k, v) in fields {
+                for (k, v) in fields.read().iter() {
                     k.hash(state);
                     v.hash(state);
                 }
@@ -217,23 +445,33 @@ impl std::hash::Hash for Value {
                 }
             }
             Value::Vec(values) => {
-                for value in values {
+                for value in values.read().iter() {
                     value.hash(state);
                 }
             }
             Value::Set(set) => {
-                for value in set {
+                for value in set.read().iter() {
                     value.hash(state);
                 }
             }
             Value::Map(map) => {
-                for (k, v) in map {
+                for (k, v) in map.read().iter() {
                     k.hash(state);
                     v.hash(state);
                 }
             }
+            Value::Range { start, end, inclusive } => {
+                start.hash(state);
+                end.hash(state);
+                inclusive.hash(state);
+            }
+            Value::Iterator(iterator) => (std::sync::Arc::as_ptr(iterator) as usize).hash(state),
+            Value::Regex(regex) => regex.pattern().hash(state),
+            Value::Generator(generator) => generator.as_ptr().hash(state),
+            Value::Native(object) => object.as_ptr().hash(state),
             Value::Reference(addr) => addr.hash(state),
-            _ => 0.hash(state), // Functions, channels, tasks are not hashable
+            Value::Channel { id } | Value::Task { id } => id.hash(state),
+            _ => 0.hash(state), // Functions are not hashable
         }
     }
 }
@@ -242,7 +480,7 @@ impl Value {
     /// Get the type of this value
     pub fn get_type(&self) -> Type {
         match self {
-            Value::Int(_) => Type::Primitive(PrimitiveType::Int),
+            Value::Int(_) | Value::BigInt(_) => Type::Primitive(PrimitiveType::Int),
             Value::Float(_) => Type::Primitive(PrimitiveType::Float),
             Value::Bool(_) => Type::Primitive(PrimitiveType::Bool),
             Value::String(_) => Type::Primitive(PrimitiveType::Str),
@@ -254,6 +492,7 @@ impl Value {
                 span: dummy_span(),
             },
             Value::Vec(elements) => {
+                let elements = elements.read();
                 if elements.is_empty() {
                     Type::Vec {
                         element_type: Box::new(Type::Primitive(PrimitiveType::Any)),
@@ -267,6 +506,7 @@ impl Value {
                 }
             }
             Value::Set(elements) => {
+                let elements = elements.read();
                 if elements.is_empty() {
                     Type::Set {
                         element_type: Box::new(Type::Primitive(PrimitiveType::Any)),
@@ -280,6 +520,7 @@ impl Value {
                 }
             }
             Value::Map(entries) => {
+                let entries = entries.read();
                 if entries.is_empty() {
                     Type::Map {
                         key_type: Box::new(Type::Primitive(PrimitiveType::Any)),
@@ -295,6 +536,11 @@ impl Value {
                     }
                 }
             }
+            Value::Range { .. } => Type::Identifier("Range".to_string()),
+            Value::Iterator(_) => Type::Identifier("Iterator".to_string()),
+            Value::Regex(_) => Type::Identifier("Regex".to_string()),
+            Value::Generator(_) => Type::Identifier("Generator".to_string()),
+            Value::Native(object) => Type::Identifier(object.type_name().to_string()),
             Value::Function { .. } => Type::Function {
                 params: vec![], // TODO: Extract from function
                 return_type: Box::new(Type::Primitive(PrimitiveType::Any)),
@@ -305,6 +551,11 @@ impl Value {
                 return_type: Box::new(Type::Primitive(PrimitiveType::Any)),
                 span: dummy_span(),
             },
+            Value::EnumConstructor { enum_name, field_types, .. } => Type::Function {
+                params: field_types.clone(),
+                return_type: Box::new(Type::Identifier(enum_name.clone())),
+                span: dummy_span(),
+            },
             Value::Channel { .. } => Type::Identifier("Channel".to_string()),
             Value::Task { .. } => Type::Identifier("Task".to_string()),
             Value::Reference(_) => Type::Primitive(PrimitiveType::Any),
@@ -316,14 +567,18 @@ impl Value {
     /// Check if this value is truthy
     pub fn is_truthy(&self) -> bool {
         match self {
+            Value::Union { value, .. } => value.is_truthy(),
             Value::Bool(b) => *b,
             Value::None => false,
             Value::Int(0) => false,
             Value::Float(f) => *f != 0.0,
             Value::String(s) => !s.is_empty(),
-            Value::Vec(v) => !v.is_empty(),
-            Value::Set(s) => !s.is_empty(),
-            Value::Map(m) => !m.is_empty(),
+            Value::Vec(v) => !v.read().is_empty(),
+            Value::Set(s) => !s.read().is_empty(),
+            Value::Map(m) => !m.read().is_empty(),
+            Value::Range { start, end, inclusive } => {
+                crate::iterators::range_length(*start, *end, *inclusive) > 0
+            }
             _ => true,
         }
     }
@@ -332,12 +587,14 @@ impl Value {
     pub fn to_string(&self) -> String {
         match self {
             Value::Int(i) => i.to_string(),
+            Value::BigInt(i) => i.to_string(),
             Value::Float(f) => f.to_string(),
             Value::Bool(b) => b.to_string(),
             Value::String(s) => s.clone(),
             Value::None => "None".to_string(),
             Value::Struct { name, fields } => {
                 let field_strs: Vec<String> = fields
+                    .read()
                     .iter()
                     .map(|(k, v)| format!("{}: {}", k, v.to_string()))
                     .collect();
@@ -348,48 +605,168 @@ impl Value {
                 variant,
                 fields,
             } => {
-                if fields.is_empty() {
+                if name == "Option" || name == "Result" {
+                    let field_strs: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+                    format!("{}({})", variant, field_strs.join(", "))
+                } else if fields.is_empty() {
                     format!("{}::{}", name, variant)
                 } else {
                     let field_strs: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
                     format!("{}::{} ({})", name, variant, field_strs.join(", "))
                 }
             }
-            Value::Union { value, possible_types } => {
-                format!("Union<{}>({})", possible_types.join(" | "), value.to_string())
-            }
+            Value::Union { value, .. } => value.to_string(),
             Value::Tuple(values) => {
                 let value_strs: Vec<String> = values.iter().map(|v| v.to_string()).collect();
                 format!("({})", value_strs.join(", "))
             }
             Value::Vec(values) => {
-                let value_strs: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                let value_strs: Vec<String> = values.read().iter().map(|v| v.to_string()).collect();
                 format!("[{}]", value_strs.join(", "))
             }
             Value::Set(values) => {
-                let value_strs: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                let value_strs: Vec<String> = values.read().iter().map(|v| v.to_string()).collect();
                 format!("{{{}}}", value_strs.join(", "))
             }
             Value::Map(entries) => {
                 let entry_strs: Vec<String> = entries
+                    .read()
                     .iter()
                     .map(|(k, v)| format!("{}: {}", k.to_string(), v.to_string()))
                     .collect();
                 format!("{{{}}}", entry_strs.join(", "))
             }
+            Value::Range { start, end, inclusive } => {
+                format!("{}${}{}", start, if *inclusive { "=" } else { "" }, end)
+            }
+            Value::Iterator(_) => "<iterator>".to_string(),
+            Value::Regex(regex) => format!("<regex {}>", regex.pattern()),
+            Value::Generator(_) => "<generator>".to_string(),
+            Value::Native(object) => format!("{:?}", object),
             Value::Function { name, .. } => format!("<function {}>", name),
             Value::Closure { .. } => "<closure>".to_string(),
-            Value::Channel { .. } => "<channel>".to_string(),
-            Value::Task { id, .. } => format!("<task {}>", id),
+            Value::EnumConstructor { enum_name, variant, .. } => {
+                format!("<constructor {}.{}>", enum_name, variant)
+            }
+            Value::Channel { id } => format!("<channel {}>", id),
+            Value::Task { id } => format!("<task {}>", id),
             Value::Reference(addr) => format!("<ref {}>", addr),
             Value::Type(t) => format!("<type {:?}>", t),
         }
     }
 
+    /// Create an integer value, using `Int` when it fits
+    pub fn integer(value: BigInt) -> Value {
+        match value.to_i64() {
+            Some(i) => Value::Int(i),
+            None => Value::BigInt(value),
+        }
+    }
+
+    /// The value as an arbitrary-precision integer, if it is an `int`
+    pub fn to_big_int(&self) -> Option<BigInt> {
+        match self {
+            Value::Int(i) => Some(BigInt::from(*i)),
+            Value::BigInt(i) => Some(i.clone()),
+            _ => None,
+        }
+    }
+
+    /// The value as a float, if it is a number
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::BigInt(i) => Some(i.to_f64()),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Create a vector value
+    pub fn vec(items: Vec<Value>) -> Value {
+        Value::Vec(Shared::new(items))
+    }
+
+    /// Create a set value
+    pub fn set(items: HashSet<Value>) -> Value {
+        Value::Set(Shared::new(items))
+    }
+
+    /// Create a map value
+    pub fn map(entries: HashMap<Value, Value>) -> Value {
+        Value::Map(Shared::new(entries))
+    }
+
+    /// Create a struct value
+    pub fn new_struct(name: impl Into<String>, fields: HashMap<String, Value>) -> Value {
+        Value::Struct {
+            name: name.into(),
+            fields: Shared::new(fields),
+        }
+    }
+
+    /// Copy a value so that it no longer shares any container with the original.
+    /// Nested containers are copied too.
+    pub fn deep_copy(&self) -> Value {
+        match self {
+            Value::Struct { name, fields } => Value::new_struct(
+                name.clone(),
+                fields.read().iter().map(|(k, v)| (k.clone(), v.deep_copy())).collect(),
+            ),
+            Value::Vec(items) => Value::vec(items.read().iter().map(Value::deep_copy).collect()),
+            Value::Set(items) => Value::set(items.read().iter().map(Value::deep_copy).collect()),
+            Value::Map(entries) => Value::map(
+                entries
+                    .read()
+                    .iter()
+                    .map(|(k, v)| (k.deep_copy(), v.deep_copy()))
+                    .collect(),
+            ),
+            Value::Enum { name, variant, fields } => Value::Enum {
+                name: name.clone(),
+                variant: variant.clone(),
+                fields: fields.iter().map(Value::deep_copy).collect(),
+            },
+            Value::Tuple(items) => Value::Tuple(items.iter().map(Value::deep_copy).collect()),
+            Value::Union { value, possible_types } => Value::Union {
+                value: Box::new(value.deep_copy()),
+                possible_types: possible_types.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Wrap a value as `Some(value)`
+    pub fn some(value: Value) -> Value {
+        Value::Enum {
+            name: "Option".to_string(),
+            variant: "Some".to_string(),
+            fields: vec![value],
+        }
+    }
+
+    /// Wrap a value as `Ok(value)`
+    pub fn ok(value: Value) -> Value {
+        Value::Enum {
+            name: "Result".to_string(),
+            variant: "Ok".to_string(),
+            fields: vec![value],
+        }
+    }
+
+    /// Wrap a value as `Err(value)`
+    pub fn err(value: Value) -> Value {
+        Value::Enum {
+            name: "Result".to_string(),
+            variant: "Err".to_string(),
+            fields: vec![value],
+        }
+    }
+
     /// Check if this value is compatible with a union type
     pub fn is_compatible_with_union(&self, union_types: &[String]) -> bool {
         match self {
-            Value::Int(_) => union_types.contains(&"int".to_string()),
+            Value::Int(_) | Value::BigInt(_) => union_types.contains(&"int".to_string()),
             Value::Float(_) => union_types.contains(&"float".to_string()),
             Value::Bool(_) => union_types.contains(&"bool".to_string()),
             Value::String(_) => union_types.contains(&"str".to_string()),
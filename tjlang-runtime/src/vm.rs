@@ -1,484 +1,330 @@
 //! TJLang Virtual Machine
-//! 
-//! Advanced VM with stack, heap, and concurrency support.
+//!
+//! Runs compiled bytecode on an operand stack. Variables live in the same
+//! frames and globals the tree-walking interpreter uses, and calls, method
+//! dispatch and operators go through the interpreter, so a program can call
+//! between bytecode and the standard library freely. Each call runs its
+//! body's bytecode on a new `VirtualMachine`.
 
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use crate::bytecode::{AssignTarget, Bytecode, Instruction};
+use crate::interpreter::{ExecutionResult, Interpreter, Place, RuntimeError};
+use crate::iterators;
+use crate::pattern_matching::{MatchResult, PatternMatcher};
 use crate::values::Value;
-use crate::bytecode::{Bytecode, Instruction, Function};
-use crate::gc::GarbageCollector;
-use crate::concurrency::ConcurrencyRuntime;
+use std::collections::{HashMap, HashSet};
 
-/// Virtual machine state
-pub struct VirtualMachine {
-    /// Stack for local variables and temporary values
+/// An open try block
+struct Handler {
+    /// Where its except clauses start
+    address: usize,
+    /// Stack height when the block was entered
+    depth: usize,
+}
+
+/// Virtual machine state for one run of a bytecode program
+pub struct VirtualMachine<'a> {
+    interpreter: &'a mut Interpreter,
+    code: &'a Bytecode,
+    /// Operands and temporary values
     stack: Vec<Value>,
-    
-    /// Local variables in current scope
-    locals: Vec<Value>,
-    
-    /// Global variables
-    globals: HashMap<String, Value>,
-    
-    /// Function definitions
-    functions: HashMap<String, Function>,
-    
+    /// Try blocks open in this bytecode, innermost last
+    handlers: Vec<Handler>,
+    /// Errors of raised values an except clause is being chosen for
+    caught: Vec<RuntimeError>,
     /// Program counter
     pc: usize,
-    
-    /// Call stack for function calls
-    call_stack: Vec<CallFrame>,
-    
-    /// Garbage collector
-    gc: GarbageCollector,
-    
-    /// Concurrency runtime
-    concurrency: ConcurrencyRuntime,
-    
-    /// Bytecode program
-    bytecode: Arc<Bytecode>,
-    
-    /// Running flag
-    running: bool,
 }
 
-/// Call frame for function execution
-#[derive(Debug, Clone)]
-struct CallFrame {
-    pub function_name: String,
-    pub return_address: usize,
-    pub locals: Vec<Value>,
-    pub stack_base: usize,
-}
+impl<'a> VirtualMachine<'a> {
+    /// Create a virtual machine running `code` in the interpreter's current environment
+    pub fn new(interpreter: &'a mut Interpreter, code: &'a Bytecode) -> Self {
+        Self::with_stack(interpreter, code, Vec::new())
+    }
 
-impl VirtualMachine {
-    /// Create a new virtual machine
-    pub fn new(bytecode: Bytecode) -> Self {
+    /// Create a virtual machine whose stack starts with the given values
+    pub fn with_stack(interpreter: &'a mut Interpreter, code: &'a Bytecode, stack: Vec<Value>) -> Self {
         Self {
-            stack: Vec::new(),
-            locals: Vec::new(),
-            globals: HashMap::new(),
-            functions: HashMap::new(),
+            interpreter,
+            code,
+            stack,
+            handlers: Vec::new(),
+            caught: Vec::new(),
             pc: 0,
-            call_stack: Vec::new(),
-            gc: GarbageCollector::new(),
-            concurrency: ConcurrencyRuntime::new(),
-            bytecode: Arc::new(bytecode),
-            running: false,
         }
     }
-    
-    /// Run the virtual machine
-    pub fn run(&mut self) -> Result<Value, String> {
-        self.running = true;
-        
-        // Initialize functions
-        for function in &self.bytecode.functions {
-            self.functions.insert(function.name.clone(), function.clone());
-        }
-        
-        while self.running && self.pc < self.bytecode.instructions.len() {
-            let instruction = self.bytecode.instructions[self.pc].clone();
-            self.execute_instruction(&instruction)?;
+
+    /// Run until the bytecode returns or runs off its end, which gives the value left
+    /// on top of the stack
+    pub fn run(&mut self) -> Result<ExecutionResult, RuntimeError> {
+        let code = self.code;
+        while let Some(instruction) = code.instructions.get(self.pc) {
+            if let Some(span) = &code.locations[self.pc] {
+                self.interpreter.set_execution_context(span.file_id, span.span);
+            }
             self.pc += 1;
+            match self.execute(instruction) {
+                Ok(None) => {}
+                Ok(Some(result)) => return Ok(result),
+                Err(error) => self.catch(error)?,
+            }
         }
-        
-        // Return the top of the stack
-        Ok(self.stack.pop().unwrap_or(Value::None))
+        Ok(ExecutionResult::Value(self.pop()))
+    }
+
+    /// Continue at the innermost open except handler when a value was raised or a
+    /// runtime error occurred
+    fn catch(&mut self, error: RuntimeError) -> Result<(), RuntimeError> {
+        let raised = match error.caught_value() {
+            Some(value) => value,
+            None => return Err(error),
+        };
+        let handler = match self.handlers.pop() {
+            Some(handler) => handler,
+            None => return Err(error),
+        };
+        self.stack.truncate(handler.depth);
+        self.stack.push(raised);
+        self.caught.push(error);
+        self.pc = handler.address;
+        Ok(())
     }
-    
-    /// Execute a single instruction
-    fn execute_instruction(&mut self, instruction: &Instruction) -> Result<(), String> {
+
+    fn push(&mut self, value: Value) {
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap_or(Value::None)
+    }
+
+    /// Pop the top `count` values, in the order they were pushed
+    fn pop_many(&mut self, count: usize) -> Vec<Value> {
+        let start = self.stack.len().saturating_sub(count);
+        self.stack.split_off(start)
+    }
+
+    /// Pop the values of a call's keyword arguments and then its positional arguments
+    fn pop_arguments(&mut self, args: usize, keywords: &[String]) -> (Vec<Value>, Vec<(String, Value)>) {
+        let keyword_values = self.pop_many(keywords.len());
+        let keyword_args = keywords.iter().cloned().zip(keyword_values).collect();
+        (self.pop_many(args), keyword_args)
+    }
+
+    /// Execute one instruction, returning a result when it leaves the bytecode
+    fn execute(&mut self, instruction: &Instruction) -> Result<Option<ExecutionResult>, RuntimeError> {
+        let code = self.code;
         match instruction {
-            // Stack operations
-            Instruction::Push(value) => {
-                self.stack.push(value.clone());
-            },
+            Instruction::Push(index) => self.push(code.constants[*index].to_value()),
             Instruction::Pop => {
-                self.stack.pop();
-            },
+                self.pop();
+            }
             Instruction::Dup => {
-                if let Some(value) = self.stack.last() {
-                    self.stack.push(value.clone());
-                }
-            },
-            Instruction::Swap => {
-                if self.stack.len() >= 2 {
-                    let len = self.stack.len();
-                    self.stack.swap(len - 1, len - 2);
-                }
-            },
-            
-            // Arithmetic operations
-            Instruction::Add => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.add_values(a, b)?;
-                self.stack.push(result);
-            },
-            Instruction::Subtract => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.subtract_values(a, b)?;
-                self.stack.push(result);
-            },
-            Instruction::Multiply => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.multiply_values(a, b)?;
-                self.stack.push(result);
-            },
-            Instruction::Divide => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.divide_values(a, b)?;
-                self.stack.push(result);
-            },
-            Instruction::Modulo => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.modulo_values(a, b)?;
-                self.stack.push(result);
-            },
-            Instruction::Power => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.power_values(a, b)?;
-                self.stack.push(result);
-            },
-            
-            // Comparison operations
-            Instruction::Equal => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                self.stack.push(Value::Bool(a == b));
-            },
-            Instruction::NotEqual => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                self.stack.push(Value::Bool(a != b));
-            },
-            Instruction::LessThan => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.compare_values(a, b, |a, b| a < b)?;
-                self.stack.push(Value::Bool(result));
-            },
-            Instruction::GreaterThan => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.compare_values(a, b, |a, b| a > b)?;
-                self.stack.push(Value::Bool(result));
-            },
-            Instruction::LessThanEqual => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.compare_values(a, b, |a, b| a <= b)?;
-                self.stack.push(Value::Bool(result));
-            },
-            Instruction::GreaterThanEqual => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = self.compare_values(a, b, |a, b| a >= b)?;
-                self.stack.push(Value::Bool(result));
-            },
-            
-            // Logical operations
-            Instruction::And => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = a.is_truthy() && b.is_truthy();
-                self.stack.push(Value::Bool(result));
-            },
-            Instruction::Or => {
-                let b = self.pop_value()?;
-                let a = self.pop_value()?;
-                let result = a.is_truthy() || b.is_truthy();
-                self.stack.push(Value::Bool(result));
-            },
-            Instruction::Not => {
-                let a = self.pop_value()?;
-                self.stack.push(Value::Bool(!a.is_truthy()));
-            },
-            
-            // Variable operations
-            Instruction::Load(name) => {
-                if let Some(value) = self.globals.get(name) {
-                    self.stack.push(value.clone());
-                } else {
-                    return Err(format!("Undefined variable: {}", name));
-                }
-            },
-            Instruction::Store(name) => {
-                if let Some(value) = self.stack.pop() {
-                    self.globals.insert(name.clone(), value);
-                }
-            },
-            Instruction::LoadLocal(index) => {
-                if let Some(value) = self.locals.get(*index) {
-                    self.stack.push(value.clone());
-                } else {
-                    return Err(format!("Undefined local variable at index {}", index));
-                }
-            },
-            Instruction::StoreLocal(index) => {
-                if let Some(value) = self.stack.pop() {
-                    while self.locals.len() <= *index {
-                        self.locals.push(Value::None);
-                    }
-                    self.locals[*index] = value;
-                }
-            },
-            
-            // Function operations
-            Instruction::Call(name, arg_count) => {
-                if let Some(function) = self.functions.get(name).cloned() {
-                    self.call_function(&function, *arg_count)?;
-                } else {
-                    return Err(format!("Undefined function: {}", name));
-                }
-            },
-            Instruction::CallClosure(arg_count) => {
-                // TODO: Implement closure calls
-                return Err("Closure calls not yet implemented".to_string());
-            },
-            Instruction::Return => {
-                if let Some(frame) = self.call_stack.pop() {
-                    self.pc = frame.return_address;
-                    self.locals = frame.locals;
-                    // Restore stack to base
-                    while self.stack.len() > frame.stack_base {
-                        self.stack.pop();
-                    }
-                } else {
-                    self.running = false;
-                }
-            },
-            
-            // Control flow
-            Instruction::Jump(address) => {
-                self.pc = *address - 1; // -1 because pc will be incremented
-            },
-            Instruction::JumpIf(address) => {
-                if let Some(value) = self.stack.pop() {
-                    if value.is_truthy() {
-                        self.pc = *address - 1;
+                let value = self.stack.last().cloned().unwrap_or(Value::None);
+                self.push(value);
+            }
+            Instruction::Binary(operator) => {
+                let right = self.pop();
+                let left = self.pop();
+                let result = self.interpreter.apply_binary_operator(&left, operator, &right)?;
+                self.push(result);
+            }
+            Instruction::Unary(operator) => {
+                let operand = self.pop();
+                let result = self.interpreter.apply_unary_operator(operator, &operand)?;
+                self.push(result);
+            }
+            Instruction::Index => {
+                let index = self.pop();
+                let target = self.pop();
+                let result = self.interpreter.index_value(&target, &index)?;
+                self.push(result);
+            }
+            Instruction::GetField(member) => {
+                let target = self.pop();
+                let result = self.interpreter.interpret_member_access(&target, member)?;
+                self.push(result);
+            }
+            Instruction::LoadLocal { slot, name, span } => {
+                let value = self.interpreter.lookup_variable(name, Some(*slot));
+                let value = self.defined(value, name, span)?;
+                self.push(value);
+            }
+            Instruction::LoadGlobal { name, span } => {
+                let value = self.interpreter.lookup_variable(name, None);
+                let value = self.defined(value, name, span)?;
+                self.push(value);
+            }
+            Instruction::StoreLocal(slot) => {
+                let value = self.pop();
+                self.interpreter.environment.set_local(*slot, value);
+            }
+            Instruction::StoreGlobal(name) => {
+                let value = self.pop();
+                self.interpreter.environment.define(name.clone(), value);
+            }
+            Instruction::Assign { target, operator, span } => {
+                let mut value = self.pop();
+                let place = match target {
+                    AssignTarget::Variable { name, slot } => Place::Variable { name: name.clone(), slot: *slot },
+                    AssignTarget::Index => {
+                        let index = self.pop();
+                        let target = self.pop();
+                        Place::Index { target, index }
                     }
+                    AssignTarget::Field(name) => Place::Field { target: self.pop(), name: name.clone() },
+                };
+                if let Some(operator) = operator {
+                    let current = self.interpreter.read_place(&place)?;
+                    self.interpreter.set_execution_context(span.file_id, span.span);
+                    value = self.interpreter.apply_binary_operator(&current, operator, &value)?;
                 }
-            },
+                self.interpreter.set_execution_context(span.file_id, span.span);
+                self.interpreter.write_place(place, value.clone())?;
+                self.push(value);
+            }
+            Instruction::Call { args, keywords } => {
+                let (arg_values, keyword_args) = self.pop_arguments(*args, keywords);
+                let callee = self.pop();
+                let result = self.interpreter.interpret_call_with_keywords(&callee, &arg_values, &keyword_args)?;
+                self.push(result);
+            }
+            Instruction::CallMethod { name, args, keywords } => {
+                let (arg_values, keyword_args) = self.pop_arguments(*args, keywords);
+                let receiver = self.pop();
+                let result = self.interpreter.call_member(&receiver, name, &arg_values, &keyword_args)?;
+                self.push(result);
+            }
+            Instruction::MakeClosure(index) => {
+                let lambda = &code.lambdas[*index];
+                let closure = self.interpreter.make_closure(
+                    lambda.params.clone(),
+                    lambda.param_types.clone(),
+                    lambda.body.clone(),
+                );
+                self.push(closure);
+            }
+            Instruction::Return => return Ok(Some(ExecutionResult::Return(self.pop()))),
+            Instruction::Break => return Ok(Some(ExecutionResult::Break)),
+            Instruction::Continue => return Ok(Some(ExecutionResult::Continue)),
+            Instruction::Propagate(span) => {
+                let value = self.pop();
+                let result = self.interpreter.propagate(value, span)?;
+                self.push(result);
+            }
+            Instruction::Jump(address) => self.pc = *address,
             Instruction::JumpIfNot(address) => {
-                if let Some(value) = self.stack.pop() {
-                    if !value.is_truthy() {
-                        self.pc = *address - 1;
-                    }
-                }
-            },
-            
-            // Concurrency
-            Instruction::Spawn => {
-                let task = self.concurrency.spawn_task()?;
-                self.stack.push(Value::Task { id: task.id, handle: std::thread::spawn(|| Value::None) });
-            },
-            Instruction::Send => {
-                // TODO: Implement channel send
-            },
-            Instruction::Receive => {
-                // TODO: Implement channel receive
-            },
-            Instruction::Yield => {
-                thread::yield_now();
-            },
-            Instruction::Join => {
-                if let Some(Value::Task { handle, .. }) = self.stack.pop() {
-                    match handle.join() {
-                        Ok(result) => self.stack.push(result),
-                        Err(_) => return Err("Task join failed".to_string()),
-                    }
+                let condition = self.pop();
+                if !self.interpreter.is_truthy(&condition) {
+                    self.pc = *address;
                 }
-            },
-            
-            // Debug operations
-            Instruction::DebugPrint => {
-                if let Some(value) = self.stack.last() {
-                    println!("{}", value.to_string());
+            }
+            Instruction::MatchPattern { pattern, clause, otherwise } => {
+                let value = self.stack.last().cloned().unwrap_or(Value::None);
+                match PatternMatcher::new().match_pattern(&value, &code.patterns[*pattern], &*self.interpreter) {
+                    MatchResult::Matched { bindings, .. } => self.interpreter.bind_pattern(clause, bindings),
+                    MatchResult::NotMatched | MatchResult::GuardFailed => self.pc = *otherwise,
                 }
-            },
-            Instruction::DebugStack => {
-                println!("Stack: {:?}", self.stack);
-            },
-            Instruction::DebugHeap => {
-                println!("Heap: {} objects", self.gc.object_count());
-            },
-            Instruction::Breakpoint => {
-                println!("Breakpoint hit at PC: {}", self.pc);
-            },
-            
-            // Special operations
-            Instruction::Halt => {
-                self.running = false;
-            },
-            Instruction::Nop => {
-                // Do nothing
-            },
-            
-            _ => {
-                return Err(format!("Unimplemented instruction: {:?}", instruction));
             }
-        }
-        
-        Ok(())
-    }
-    
-    /// Pop a value from the stack
-    fn pop_value(&mut self) -> Result<Value, String> {
-        self.stack.pop().ok_or("Stack underflow".to_string())
-    }
-    
-    /// Call a function
-    fn call_function(&mut self, function: &Function, arg_count: usize) -> Result<(), String> {
-        if arg_count != function.params.len() {
-            return Err(format!(
-                "Function {} expects {} arguments, got {}",
-                function.name, function.params.len(), arg_count
-            ));
-        }
-        
-        // Save current state
-        let return_address = self.pc + 1;
-        let stack_base = self.stack.len() - arg_count;
-        let current_locals = std::mem::take(&mut self.locals);
-        
-        // Set up new locals
-        self.locals = vec![Value::None; function.local_count];
-        
-        // Copy arguments to locals
-        for (i, param) in function.params.iter().enumerate() {
-            if let Some(value) = self.stack.get(stack_base + i) {
-                self.locals[i] = value.clone();
+            Instruction::MatchExpression(index) => {
+                let value = self.pop();
+                let arms = &code.matches[*index];
+                let result = VirtualMachine::with_stack(self.interpreter, arms, vec![value]).run()?;
+                let value = self.interpreter.completion_value(result)?;
+                self.push(value);
             }
-        }
-        
-        // Push call frame
-        self.call_stack.push(CallFrame {
-            function_name: function.name.clone(),
-            return_address,
-            locals: current_locals,
-            stack_base,
-        });
-        
-        // Jump to function
-        self.pc = function.start_address - 1; // -1 because pc will be incremented
-        
-        Ok(())
-    }
-    
-    /// Add two values
-    fn add_values(&self, a: Value, b: Value) -> Result<Value, String> {
-        match (a, b) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a + b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a + b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Float(a as f64 + b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a + b as f64)),
-            (Value::String(a), Value::String(b)) => Ok(Value::String(a + &b)),
-            _ => Err("Cannot add these types".to_string()),
-        }
-    }
-    
-    /// Subtract two values
-    fn subtract_values(&self, a: Value, b: Value) -> Result<Value, String> {
-        match (a, b) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a - b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a - b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Float(a as f64 - b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a - b as f64)),
-            _ => Err("Cannot subtract these types".to_string()),
-        }
-    }
-    
-    /// Multiply two values
-    fn multiply_values(&self, a: Value, b: Value) -> Result<Value, String> {
-        match (a, b) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a * b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a * b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Float(a as f64 * b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a * b as f64)),
-            _ => Err("Cannot multiply these types".to_string()),
-        }
-    }
-    
-    /// Divide two values
-    fn divide_values(&self, a: Value, b: Value) -> Result<Value, String> {
-        match (a, b) {
-            (Value::Int(a), Value::Int(b)) => {
-                if b == 0 {
-                    Err("Division by zero".to_string())
-                } else {
-                    Ok(Value::Int(a / b))
-                }
-            },
-            (Value::Float(a), Value::Float(b)) => {
-                if b == 0.0 {
-                    Err("Division by zero".to_string())
-                } else {
-                    Ok(Value::Float(a / b))
-                }
-            },
-            (Value::Int(a), Value::Float(b)) => {
-                if b == 0.0 {
-                    Err("Division by zero".to_string())
-                } else {
-                    Ok(Value::Float(a as f64 / b))
+            Instruction::Raise => {
+                let value = self.pop();
+                return Err(self.interpreter.raised_error(value));
+            }
+            Instruction::SetupExcept(address) => self.handlers.push(Handler {
+                address: *address,
+                depth: self.stack.len(),
+            }),
+            Instruction::PopExcept => {
+                self.handlers.pop();
+            }
+            Instruction::EndExcept => {
+                self.caught.pop();
+            }
+            Instruction::Reraise => {
+                if let Some(error) = self.caught.pop() {
+                    return Err(error);
                 }
-            },
-            (Value::Float(a), Value::Int(b)) => {
-                if b == 0 {
-                    Err("Division by zero".to_string())
-                } else {
-                    Ok(Value::Float(a / b as f64))
+            }
+            Instruction::Fail(message) => return Err(self.interpreter.runtime_error(message.clone())),
+            Instruction::Spawn(index) => {
+                let task = self.interpreter.spawn_task(code.spawns[*index].clone())?;
+                self.push(task);
+            }
+            Instruction::CheckType { name, declared } => {
+                let value = self.pop();
+                let value = self.interpreter.check_variable_type(value, declared, name)?;
+                self.push(value);
+            }
+            Instruction::CheckStruct { name, fields } => {
+                self.interpreter
+                    .check_struct_fields(name, fields.iter().map(|(field, span)| (field.as_str(), span)))?;
+            }
+            Instruction::NewStruct { name, fields } => {
+                let values = self.pop_many(fields.len());
+                let field_map: HashMap<String, Value> = fields.iter().cloned().zip(values).collect();
+                self.push(Value::new_struct(name.clone(), field_map));
+            }
+            Instruction::NewTuple(count) => {
+                let elements = self.pop_many(*count);
+                self.push(Value::Tuple(elements));
+            }
+            Instruction::NewVec(count) => {
+                let elements = self.pop_many(*count);
+                self.push(Value::vec(elements));
+            }
+            Instruction::NewSet(count) => {
+                let elements: HashSet<Value> = self.pop_many(*count).into_iter().collect();
+                self.push(Value::set(elements));
+            }
+            Instruction::NewMap(count) => {
+                let mut items = self.pop_many(count * 2).into_iter();
+                let mut map = HashMap::new();
+                while let (Some(key), Some(value)) = (items.next(), items.next()) {
+                    map.insert(key, value);
                 }
-            },
-            _ => Err("Cannot divide these types".to_string()),
-        }
-    }
-    
-    /// Modulo two values
-    fn modulo_values(&self, a: Value, b: Value) -> Result<Value, String> {
-        match (a, b) {
-            (Value::Int(a), Value::Int(b)) => {
-                if b == 0 {
-                    Err("Modulo by zero".to_string())
-                } else {
-                    Ok(Value::Int(a % b))
+                self.push(Value::map(map));
+            }
+            Instruction::NewRange { inclusive } => {
+                let end = self.pop();
+                let start = self.pop();
+                let range = self.interpreter.make_range(start, end, *inclusive)?;
+                self.push(range);
+            }
+            Instruction::Concat(count) => {
+                let text: String = self.pop_many(*count).iter().map(|part| part.to_string()).collect();
+                self.push(Value::String(text));
+            }
+            Instruction::GetIter => {
+                let iterable = self.pop();
+                let iterator = self.interpreter.iterator_over(iterable)?;
+                self.push(Value::Iterator(iterator));
+            }
+            Instruction::ForIter(exit) => {
+                let iterator = match self.stack.last() {
+                    Some(Value::Iterator(iterator)) => iterator.clone(),
+                    _ => return Err(self.interpreter.runtime_error("Expected an iterator".to_string())),
+                };
+                match iterators::next_value(&iterator, &mut *self.interpreter)? {
+                    Some(item) => self.push(item),
+                    None => self.pc = *exit,
                 }
-            },
-            _ => Err("Cannot modulo these types".to_string()),
-        }
-    }
-    
-    /// Power of two values
-    fn power_values(&self, a: Value, b: Value) -> Result<Value, String> {
-        match (a, b) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a.pow(b as u32))),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a.powf(b))),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Float((a as f64).powf(b))),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a.powi(b as i32))),
-            _ => Err("Cannot compute power of these types".to_string()),
+            }
+            Instruction::Nop => {}
         }
+        Ok(None)
     }
-    
-    /// Compare two values
-    fn compare_values<F>(&self, a: Value, b: Value, cmp: F) -> Result<bool, String>
-    where
-        F: FnOnce(f64, f64) -> bool,
-    {
-        match (a, b) {
-            (Value::Int(a), Value::Int(b)) => Ok(cmp(a as f64, b as f64)),
-            (Value::Float(a), Value::Float(b)) => Ok(cmp(a, b)),
-            (Value::Int(a), Value::Float(b)) => Ok(cmp(a as f64, b)),
-            (Value::Float(a), Value::Int(b)) => Ok(cmp(a, b as f64)),
-            _ => Err("Cannot compare these types".to_string()),
-        }
+
+    /// The value of a variable, or an undefined variable error located at its use
+    fn defined(&mut self, value: Option<Value>, name: &str, span: &tjlang_ast::SourceSpan) -> Result<Value, RuntimeError> {
+        value.ok_or_else(|| {
+            self.interpreter.set_execution_context(span.file_id, span.span);
+            self.interpreter.runtime_error(format!("Undefined variable: {}", name))
+        })
     }
 }
@@ -14,9 +14,17 @@
 //!     | Task(τ)             (task types)
 //!     | T[τ₁, ...]         (generic type application)
 
-use std::collections::HashMap;
+use std::fmt;
 use tjlang_ast::{Type, PrimitiveType, SourceSpan};
-use tjlang_diagnostics::SourceSpan as DiagnosticSourceSpan;
+
+/// Span for types built from an `AlgebraicType` rather than parsed from source
+fn builtin_span() -> SourceSpan {
+    let mut files = codespan::Files::new();
+    SourceSpan {
+        file_id: files.add("builtin", ""),
+        span: codespan::Span::new(0, 0),
+    }
+}
 
 /// Algebraic type representation
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
@@ -117,61 +125,61 @@ impl AlgebraicType {
             AlgebraicType::Product(types) => {
                 Type::Tuple {
                     types: types.iter().map(|t| t.to_ast_type()).collect(),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             },
             AlgebraicType::Sum(types) => {
                 Type::Union {
                     types: types.iter().map(|t| t.to_ast_type()).collect(),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             },
             AlgebraicType::Option(inner) => {
                 Type::Option {
                     inner: Box::new(inner.to_ast_type()),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             },
             AlgebraicType::Result(ok_type, error_type) => {
                 Type::Result {
                     ok_type: Box::new(ok_type.to_ast_type()),
                     error_type: Box::new(error_type.to_ast_type()),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             },
             AlgebraicType::Function(params, return_type) => {
                 Type::Function {
                     params: params.iter().map(|t| t.to_ast_type()).collect(),
                     return_type: Box::new(return_type.to_ast_type()),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             },
             AlgebraicType::Vec(element_type) => {
                 Type::Vec {
                     element_type: Box::new(element_type.to_ast_type()),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             },
             AlgebraicType::Set(element_type) => {
                 Type::Set {
                     element_type: Box::new(element_type.to_ast_type()),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             },
             AlgebraicType::Map(key_type, value_type) => {
                 Type::Map {
                     key_type: Box::new(key_type.to_ast_type()),
                     value_type: Box::new(value_type.to_ast_type()),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             },
             AlgebraicType::Tuple(types) => {
                 Type::Tuple {
                     types: types.iter().map(|t| t.to_ast_type()).collect(),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             },
-            AlgebraicType::Task(inner) => {
+            AlgebraicType::Task(_) => {
                 Type::Identifier("Task".to_string())
             },
             AlgebraicType::Generic(name, type_args) => {
@@ -181,7 +189,7 @@ impl AlgebraicType {
                     Type::Generic {
                         name: name.clone(),
                         type_args: type_args.iter().map(|t| t.to_ast_type()).collect(),
-                        span: SourceSpan::default(),
+                        span: builtin_span(),
                     }
                 }
             },
@@ -258,10 +266,12 @@ impl AlgebraicType {
             },
         }
     }
-    
-    /// Convert type to string for error messages
-    pub fn to_string(&self) -> String {
-        match self {
+}
+
+/// Formats the type for error messages
+impl fmt::Display for AlgebraicType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
             AlgebraicType::Int => "int".to_string(),
             AlgebraicType::Float => "float".to_string(),
             AlgebraicType::Bool => "bool".to_string(),
@@ -271,21 +281,21 @@ impl AlgebraicType {
                 format!("({})", types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", "))
             },
             AlgebraicType::Sum(types) => {
-                format!("{}", types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" | "))
+                types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" | ")
             },
             AlgebraicType::Function(params, return_type) => {
                 format!("({}) -> {}", 
                        params.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", "),
-                       return_type.to_string())
+                       return_type)
             },
             AlgebraicType::Vec(element_type) => {
-                format!("Vec<{}>", element_type.to_string())
+                format!("Vec<{}>", element_type)
             },
             AlgebraicType::Option(inner) => {
-                format!("Option<{}>", inner.to_string())
+                format!("Option<{}>", inner)
             },
             AlgebraicType::Result(ok_type, error_type) => {
-                format!("Result<{}, {}>", ok_type.to_string(), error_type.to_string())
+                format!("Result<{}, {}>", ok_type, error_type)
             },
             AlgebraicType::Generic(name, type_args) => {
                 if type_args.is_empty() {
@@ -296,7 +306,8 @@ impl AlgebraicType {
             },
             AlgebraicType::Variable(name) => name.clone(),
             _ => "unknown".to_string(),
-        }
+        };
+        write!(f, "{}", text)
     }
 }
 
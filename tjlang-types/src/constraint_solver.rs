@@ -7,9 +7,10 @@
 
 use std::collections::HashMap;
 use crate::algebraic_types::AlgebraicType;
-use crate::interfaces::{Interface, MethodSignature};
+use crate::interfaces::{builtin_span, MethodSignature};
 use crate::type_environment::InterfaceConstraint;
-use tjlang_diagnostics::{DiagnosticCollection, TJLangDiagnostic, ErrorCode, SourceSpan, Severity};
+use codespan_reporting::diagnostic::Severity;
+use tjlang_diagnostics::{DiagnosticCollection, TJLangDiagnostic, ErrorCode};
 
 /// Constraint solver for interface satisfaction
 #[derive(Debug, Clone)]
@@ -32,48 +33,39 @@ impl ConstraintSolver {
     
     /// Register built-in interface implementations
     fn register_builtin_implementations(&mut self) {
-        // Int implements Addable, Eq, Order
-        self.register_implementation(
-            "int".to_string(),
-            "Addable".to_string(),
-            vec![MethodSignature {
-                name: "add".to_string(),
-                params: vec![AlgebraicType::Int, AlgebraicType::Int],
-                return_type: AlgebraicType::Int,
-                span: SourceSpan::default(),
-            }]
-        );
-        
-        // Float implements Addable, Eq, Order
-        self.register_implementation(
-            "float".to_string(),
-            "Addable".to_string(),
-            vec![MethodSignature {
-                name: "add".to_string(),
-                params: vec![AlgebraicType::Float, AlgebraicType::Float],
-                return_type: AlgebraicType::Float,
-                span: SourceSpan::default(),
-            }]
-        );
-        
-        // String implements Addable, Eq
-        self.register_implementation(
-            "str".to_string(),
-            "Addable".to_string(),
-            vec![MethodSignature {
-                name: "add".to_string(),
-                params: vec![AlgebraicType::Str, AlgebraicType::Str],
-                return_type: AlgebraicType::Str,
-                span: SourceSpan::default(),
-            }]
-        );
+        // Numbers implement Addable, Eq and Order, strings Addable and Eq, bool only Eq
+        let primitives = [
+            ("int", AlgebraicType::Int, &["Addable", "Eq", "Order"][..]),
+            ("float", AlgebraicType::Float, &["Addable", "Eq", "Order"][..]),
+            ("str", AlgebraicType::Str, &["Addable", "Eq"][..]),
+            ("bool", AlgebraicType::Bool, &["Eq"][..]),
+        ];
+        for (type_name, ty, interfaces) in primitives {
+            for interface in interfaces {
+                let (symbol, return_type) = match *interface {
+                    "Addable" => ("+", ty.clone()),
+                    "Eq" => ("==", AlgebraicType::Bool),
+                    _ => ("<", AlgebraicType::Bool),
+                };
+                self.register_implementation(
+                    type_name.to_string(),
+                    interface.to_string(),
+                    vec![MethodSignature {
+                        name: symbol.to_string(),
+                        params: vec![ty.clone(), ty.clone()],
+                        return_type,
+                        span: builtin_span(),
+                    }]
+                );
+            }
+        }
     }
     
     /// Register an interface implementation
     pub fn register_implementation(&mut self, type_name: String, interface_name: String, methods: Vec<MethodSignature>) {
         self.implementations
             .entry(type_name)
-            .or_insert_with(HashMap::new)
+            .or_default()
             .insert(interface_name, methods);
     }
     
@@ -122,3 +114,9 @@ impl ConstraintSolver {
             .is_some()
     }
 }
+
+impl Default for ConstraintSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -7,6 +7,15 @@
 use crate::algebraic_types::AlgebraicType;
 use tjlang_diagnostics::SourceSpan;
 
+/// Span for built-in interfaces and implementations, which have no source location
+pub(crate) fn builtin_span() -> SourceSpan {
+    let mut files = codespan::Files::new();
+    SourceSpan {
+        file_id: files.add("builtin", ""),
+        span: codespan::Span::new(0, 0),
+    }
+}
+
 /// Interface definition (trait/type class)
 /// 
 /// I ::= { m_i : (τ_i^1, ..., τ_i^n) → ρ_i }
@@ -38,13 +47,13 @@ pub mod builtin_interfaces {
             name: "Addable".to_string(),
             methods: vec![
                 MethodSignature {
-                    name: "add".to_string(),
+                    name: "+".to_string(),
                     params: vec![AlgebraicType::Variable("Self".to_string()), AlgebraicType::Variable("Self".to_string())],
                     return_type: AlgebraicType::Variable("Self".to_string()),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             ],
-            span: SourceSpan::default(),
+            span: builtin_span(),
         }
     }
     
@@ -54,35 +63,29 @@ pub mod builtin_interfaces {
             name: "Eq".to_string(),
             methods: vec![
                 MethodSignature {
-                    name: "eq".to_string(),
+                    name: "==".to_string(),
                     params: vec![AlgebraicType::Variable("Self".to_string()), AlgebraicType::Variable("Self".to_string())],
                     return_type: AlgebraicType::Bool,
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             ],
-            span: SourceSpan::default(),
+            span: builtin_span(),
         }
     }
     
-    /// Order interface for <, > operators
+    /// Order interface for <, >, <= and >= operators (all derived from <)
     pub fn order_interface() -> Interface {
         Interface {
             name: "Order".to_string(),
             methods: vec![
                 MethodSignature {
-                    name: "lt".to_string(),
-                    params: vec![AlgebraicType::Variable("Self".to_string()), AlgebraicType::Variable("Self".to_string())],
-                    return_type: AlgebraicType::Bool,
-                    span: SourceSpan::default(),
-                },
-                MethodSignature {
-                    name: "gt".to_string(),
+                    name: "<".to_string(),
                     params: vec![AlgebraicType::Variable("Self".to_string()), AlgebraicType::Variable("Self".to_string())],
                     return_type: AlgebraicType::Bool,
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             ],
-            span: SourceSpan::default(),
+            span: builtin_span(),
         }
     }
     
@@ -92,13 +95,13 @@ pub mod builtin_interfaces {
             name: "Indexable".to_string(),
             methods: vec![
                 MethodSignature {
-                    name: "index".to_string(),
+                    name: "[]".to_string(),
                     params: vec![AlgebraicType::Variable("Self".to_string()), AlgebraicType::Int],
                     return_type: AlgebraicType::Variable("T".to_string()),
-                    span: SourceSpan::default(),
+                    span: builtin_span(),
                 }
             ],
-            span: SourceSpan::default(),
+            span: builtin_span(),
         }
     }
 }
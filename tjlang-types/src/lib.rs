@@ -12,10 +12,15 @@
 
 pub mod types;
 pub mod checker;
+pub mod algebraic_types;
+pub mod interfaces;
+pub mod type_environment;
+pub mod constraint_solver;
 
 #[cfg(test)]
 mod tests;
 
 // Re-export commonly used types
 pub use types::*;
-pub use checker::*;
\ No newline at end of file
+pub use checker::*;
+pub use constraint_solver::ConstraintSolver;
\ No newline at end of file
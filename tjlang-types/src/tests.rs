@@ -6,6 +6,7 @@
 mod tests {
     use crate::types::{Type, TypeEnvironment};
     use crate::checker::TypeChecker;
+    use crate::constraint_solver::ConstraintSolver;
 
     // ============================================================================
     // Basic Type Tests
@@ -667,4 +668,29 @@ mod tests {
         let func3 = Type::Function(vec![float.clone()], Box::new(float.clone()));
         assert!(!checker.check_compatibility(&func1, &func3));
     }
+
+    #[test]
+    fn test_constraint_solver_builtin_implementations() {
+        let solver = ConstraintSolver::new();
+
+        for type_name in ["int", "float"] {
+            assert!(solver.type_implements_interface(type_name, "Addable"));
+            assert!(solver.type_implements_interface(type_name, "Eq"));
+            assert!(solver.type_implements_interface(type_name, "Order"));
+        }
+        assert!(solver.type_implements_interface("str", "Addable"));
+        assert!(!solver.type_implements_interface("str", "Order"));
+        assert!(solver.type_implements_interface("bool", "Eq"));
+        assert!(!solver.type_implements_interface("bool", "Order"));
+        assert!(!solver.type_implements_interface("Point", "Eq"));
+    }
+
+    #[test]
+    fn test_constraint_solver_registered_implementation() {
+        let mut solver = ConstraintSolver::new();
+        solver.register_implementation("Point".to_string(), "Eq".to_string(), vec![]);
+
+        assert!(solver.type_implements_interface("Point", "Eq"));
+        assert!(!solver.type_implements_interface("Point", "Order"));
+    }
 }
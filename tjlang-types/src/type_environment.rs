@@ -105,7 +105,7 @@ impl TypeEnvironment {
     pub fn implements_interface(&self, type_name: &str, interface_name: &str) -> bool {
         self.implementations
             .get(type_name)
-            .map(|interfaces| interfaces.contains(interface_name))
+            .map(|interfaces| interfaces.iter().any(|i| i == interface_name))
             .unwrap_or(false)
     }
     
@@ -119,3 +119,9 @@ impl TypeEnvironment {
         self.constraints.clear();
     }
 }
+
+impl Default for TypeEnvironment {
+    fn default() -> Self {
+        Self::new()
+    }
+}